@@ -0,0 +1,36 @@
+//! 原始帧与解析结果的归档: 按 (device_no, 方向, hex, 解析字段, 时间) 落库，
+//! 供事后排查，以及用 protocol-testkit 的 Replayer 重放对比。
+//!
+//! - [`ArchiveWriter`]: 攒批写入，同时实现 `ReportSink`，可以和 Kafka 等输出端并列挂在 bridge 之后
+//! - `SqliteArchive`: 基于 rusqlite (feature `sqlite`)
+//! - `PostgresArchive`: 基于 postgres 同步客户端 (feature `postgres`)
+//!
+//! 默认不启用任何后端；只用 [`ArchiveStore`] 接入自有存储时不会引入数据库依赖。
+//!
+//! ```
+//! # #[cfg(feature = "sqlite")] {
+//! use protocol_archive::{ArchiveFilter, ArchiveWriter, SqliteArchive};
+//! use protocol_kernel::JniResponse;
+//!
+//! let writer = ArchiveWriter::new(SqliteArchive::open_in_memory().unwrap()).with_batch_size(100);
+//! let rsp = JniResponse::from(br#"{"success":true,"deviceNo":"01","reqHex":"68AA16"}"#).unwrap();
+//! writer.archive(&rsp).unwrap();
+//! let log = writer.replay_log(&ArchiveFilter::new().device_no("01")).unwrap();
+//! assert_eq!(log[0].req_hex(), "68AA16");
+//! # }
+//! ```
+#[cfg(feature = "postgres")]
+mod postgres;
+mod record;
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+mod sql;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod writer;
+
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresArchive;
+pub use record::{ArchiveFilter, ArchiveRecord, Direction};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteArchive;
+pub use writer::{ArchiveStore, ArchiveWriter};
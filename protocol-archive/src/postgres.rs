@@ -0,0 +1,189 @@
+use std::error::Error;
+
+use bytes::{BufMut, BytesMut};
+use postgres::{
+    types::{to_sql_checked, FromSql, IsNull, ToSql, Type},
+    Client, NoTls, Row,
+};
+use protocol_kernel::{ProtocolError, ProtocolResult};
+
+use crate::{
+    record::{ArchiveFilter, ArchiveRecord},
+    sql::{self, SqlValue, COLUMNS, DEFAULT_TABLE},
+    writer::ArchiveStore,
+};
+
+// 一条语句最多 65535 个参数
+const MAX_ROWS_PER_INSERT: usize = 65535 / COLUMNS.len();
+
+/// Postgres 归档 (postgres 同步客户端，不启用 TLS)。conninfo 为 libpq 格式的连接串，如
+/// "host=127.0.0.1 dbname=meter user=meter password=..."。
+/// 连接时自动建表，每批记录用多行 INSERT 在一个事务内写入
+pub struct PostgresArchive {
+    client: Client,
+    table: String,
+}
+
+impl PostgresArchive {
+    pub fn connect(conninfo: &str) -> ProtocolResult<Self> {
+        Self::connect_with_table(conninfo, DEFAULT_TABLE)
+    }
+
+    pub fn connect_with_table(conninfo: &str, table: &str) -> ProtocolResult<Self> {
+        sql::validate_table(table)?;
+        let client = Client::connect(conninfo, NoTls).map_err(postgres_error("connect"))?;
+        let mut archive = Self {
+            client,
+            table: table.into(),
+        };
+        archive.migrate()?;
+        Ok(archive)
+    }
+
+    fn migrate(&mut self) -> ProtocolResult<()> {
+        self.client
+            .batch_execute(&format!(
+                "CREATE TABLE IF NOT EXISTS {t} (
+                    id BIGSERIAL PRIMARY KEY,
+                    device_no TEXT NOT NULL,
+                    device_id TEXT NOT NULL,
+                    direction TEXT NOT NULL,
+                    cmd_code TEXT NOT NULL,
+                    msg_type TEXT NOT NULL,
+                    hex TEXT NOT NULL,
+                    fields JSONB NOT NULL,
+                    reply_hex TEXT NOT NULL,
+                    reply_fields JSONB NOT NULL,
+                    success BOOLEAN NOT NULL,
+                    err_msg TEXT,
+                    received_at BIGINT NOT NULL,
+                    decode_micros BIGINT
+                );
+                CREATE INDEX IF NOT EXISTS {t}_device_time ON {t} (device_no, received_at);",
+                t = self.table
+            ))
+            .map_err(postgres_error("migrate"))
+    }
+}
+
+fn dollar(n: usize) -> String {
+    format!("${}", n)
+}
+
+impl ArchiveStore for PostgresArchive {
+    fn insert_batch(&mut self, records: &[ArchiveRecord]) -> ProtocolResult<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        // 未 commit 的事务在 drop 时回滚
+        let mut tx = self.client.transaction().map_err(postgres_error("begin"))?;
+        for chunk in records.chunks(MAX_ROWS_PER_INSERT) {
+            let params = chunk
+                .iter()
+                .map(sql::to_params)
+                .collect::<ProtocolResult<Vec<_>>>()?
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>();
+            tx.execute(
+                &sql::insert_sql(&self.table, chunk.len(), dollar),
+                &param_refs(&params),
+            )
+            .map_err(postgres_error("insert"))?;
+        }
+        tx.commit().map_err(postgres_error("commit"))
+    }
+
+    fn query(&mut self, filter: &ArchiveFilter) -> ProtocolResult<Vec<ArchiveRecord>> {
+        let (select, params) = sql::select_sql(&self.table, filter, dollar);
+        self.client
+            .query(&select, &param_refs(&params))
+            .map_err(postgres_error("query"))?
+            .iter()
+            .map(|row| sql::from_row(text_row(row)?))
+            .collect()
+    }
+}
+
+fn param_refs(params: &[SqlValue]) -> Vec<&(dyn ToSql + Sync)> {
+    params.iter().map(|p| p as &(dyn ToSql + Sync)).collect()
+}
+
+// 按列类型编码: success 写入 BOOLEAN，fields 写入 JSONB
+impl ToSql for SqlValue {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        match self {
+            SqlValue::Null => Ok(IsNull::Yes),
+            SqlValue::Int(v) => match *ty {
+                Type::BOOL => (*v != 0).to_sql(ty, out),
+                Type::INT4 => i32::try_from(*v)?.to_sql(ty, out),
+                _ => v.to_sql(ty, out),
+            },
+            SqlValue::Text(s) => match *ty {
+                // JSONB 二进制格式: 版本号 1 + JSON 文本
+                Type::JSONB => {
+                    out.put_u8(1);
+                    out.put_slice(s.as_bytes());
+                    Ok(IsNull::No)
+                }
+                _ => s.as_str().to_sql(ty, out),
+            },
+        }
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+// 任意列类型读为文本，BOOLEAN 为 t/f，与 sql::from_row 的约定一致
+struct Text(String);
+
+impl<'a> FromSql<'a> for Text {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(Text(match *ty {
+            Type::BOOL => if bool::from_sql(ty, raw)? { "t" } else { "f" }.into(),
+            Type::INT8 => i64::from_sql(ty, raw)?.to_string(),
+            Type::INT4 => i32::from_sql(ty, raw)?.to_string(),
+            Type::JSONB => String::from_utf8_lossy(raw.get(1..).unwrap_or_default()).into_owned(),
+            _ => <&str>::from_sql(ty, raw)?.into(),
+        }))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+fn text_row(row: &Row) -> ProtocolResult<Vec<Option<String>>> {
+    (0..COLUMNS.len())
+        .map(|col| {
+            row.try_get::<_, Option<Text>>(col)
+                .map(|v| v.map(|t| t.0))
+                .map_err(postgres_error("query"))
+        })
+        .collect()
+}
+
+fn postgres_error(action: &'static str) -> impl Fn(postgres::Error) -> ProtocolError {
+    move |e| ProtocolError::CommonError(format!("postgres {}: {}", action, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connect_error() {
+        let err = PostgresArchive::connect("host=127.0.0.1 port=1 connect_timeout=1")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("postgres connect"));
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use protocol_kernel::{JniResponse, ProtocolError, ProtocolResult, ReportField};
+
+/// 帧的方向，库中存为 "up"/"down"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+impl Direction {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "up" => Some(Direction::Up),
+            "down" => Some(Direction::Down),
+            _ => None,
+        }
+    }
+}
+
+/// 归档的一帧。上行帧的 reply_hex/reply_fields 是同一次处理产生的应答帧，下行帧没有应答
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveRecord {
+    pub device_no: String,
+    pub device_id: String,
+    pub direction: Direction,
+    pub cmd_code: String,
+    pub msg_type: String,
+    pub hex: String,
+    pub fields: Vec<ReportField>,
+    pub reply_hex: String,
+    pub reply_fields: Vec<ReportField>,
+    pub success: bool,
+    pub err_msg: Option<String>,
+    // 收到/下发的时间，毫秒时间戳
+    pub received_at: i64,
+    pub decode_micros: Option<u64>,
+}
+
+impl ArchiveRecord {
+    /// 由 bridge 的响应生成，received_at 取当前时间。
+    /// 有 req_hex 的是上行帧，否则是 encode_downstream 产生的下行帧
+    pub fn from_response(rsp: &JniResponse) -> ProtocolResult<Self> {
+        let upstream = !rsp.req_hex().is_empty();
+        let (hex, fields, reply_hex, reply_fields) = if upstream {
+            (
+                rsp.req_hex_clone(),
+                rsp.req_jsons_clone(),
+                rsp.rsp_hex_clone(),
+                rsp.rsp_jsons_clone(),
+            )
+        } else {
+            (
+                rsp.rsp_hex_clone(),
+                rsp.rsp_jsons_clone(),
+                String::new(),
+                Vec::new(),
+            )
+        };
+        Ok(Self {
+            device_no: rsp.device_no_clone(),
+            device_id: rsp.device_id_clone(),
+            direction: if upstream {
+                Direction::Up
+            } else {
+                Direction::Down
+            },
+            cmd_code: rsp.cmd_code_clone(),
+            msg_type: rsp.msg_type_clone(),
+            hex,
+            fields,
+            reply_hex,
+            reply_fields,
+            success: rsp.success(),
+            err_msg: rsp.err_msg().map(|s| s.to_string()),
+            received_at: now_millis()?,
+            decode_micros: rsp.decode_micros(),
+        })
+    }
+
+    pub fn with_received_at(mut self, received_at: i64) -> Self {
+        self.received_at = received_at;
+        self
+    }
+
+    /// 还原成 JniResponse，供 protocol-testkit 的 Replayer 重放
+    pub fn to_response(&self) -> ProtocolResult<JniResponse> {
+        let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+        rsp.set_success(self.success);
+        rsp.set_device_no(&self.device_no);
+        if !self.device_id.is_empty() {
+            rsp.set_device_id(&self.device_id);
+        }
+        rsp.set_cmd_code(&self.cmd_code);
+        rsp.set_msgt_type(&self.msg_type);
+        if let Some(err_msg) = &self.err_msg {
+            rsp.set_err_msg(err_msg);
+        }
+        match self.direction {
+            Direction::Up => {
+                rsp.set_req_hex(&self.hex);
+                rsp.set_req_jsons(self.fields.clone());
+                rsp.set_rsp_hex(&self.reply_hex);
+                rsp.set_rsp_jsons(self.reply_fields.clone());
+            }
+            Direction::Down => {
+                rsp.set_rsp_hex(&self.hex);
+                rsp.set_rsp_jsons(self.fields.clone());
+            }
+        }
+        Ok(rsp)
+    }
+}
+
+/// 查询条件，未设置的条件不过滤。结果按 received_at 升序
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveFilter {
+    pub device_no: Option<String>,
+    pub direction: Option<Direction>,
+    // [since, until) 毫秒时间戳
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+impl ArchiveFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn device_no(mut self, device_no: &str) -> Self {
+        self.device_no = Some(device_no.into());
+        self
+    }
+
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    pub fn since(mut self, millis: i64) -> Self {
+        self.since = Some(millis);
+        self
+    }
+
+    pub fn until(mut self, millis: i64) -> Self {
+        self.until = Some(millis);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+pub(crate) fn now_millis() -> ProtocolResult<i64> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| ProtocolError::CommonError(e.to_string()))?;
+    Ok(i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_roundtrip() {
+        let rsp = JniResponse::from(
+            br#"{"success":true,"deviceNo":"01","cmdCode":"91","msgType":"report","reqHex":"6891","rspHex":"6811","reqJsons":[{"name":"a","code":"a","value":"1","alert":false}]}"#,
+        )
+        .unwrap();
+        let record = ArchiveRecord::from_response(&rsp).unwrap();
+        assert_eq!(record.direction, Direction::Up);
+        assert_eq!(record.hex, "6891");
+        assert_eq!(record.reply_hex, "6811");
+        let back = record.to_response().unwrap();
+        assert_eq!(back.req_hex(), "6891");
+        assert_eq!(back.rsp_hex(), "6811");
+        assert_eq!(back.req_jsons(), rsp.req_jsons());
+        assert_eq!(back.msg_type(), Some("report"));
+
+        let down =
+            JniResponse::from(br#"{"success":true,"deviceNo":"01","rspHex":"6811"}"#).unwrap();
+        let record = ArchiveRecord::from_response(&down).unwrap();
+        assert_eq!(record.direction, Direction::Down);
+        assert_eq!(record.hex, "6811");
+        assert_eq!(record.to_response().unwrap().req_hex(), "");
+    }
+}
@@ -0,0 +1,245 @@
+//! SQLite 与 Postgres 共用的列定义、查询拼接与行解析
+use protocol_kernel::{ProtocolError, ProtocolResult, ReportField};
+
+use crate::record::{ArchiveFilter, ArchiveRecord, Direction};
+
+pub(crate) const DEFAULT_TABLE: &str = "frame_archive";
+
+/// 插入与查询的列，顺序与 to_params / from_row 一致
+pub(crate) const COLUMNS: [&str; 13] = [
+    "device_no",
+    "device_id",
+    "direction",
+    "cmd_code",
+    "msg_type",
+    "hex",
+    "fields",
+    "reply_hex",
+    "reply_fields",
+    "success",
+    "err_msg",
+    "received_at",
+    "decode_micros",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SqlValue {
+    Text(String),
+    Int(i64),
+    Null,
+}
+
+/// 表名只允许字母、数字与下划线，避免拼进 SQL 时被注入
+pub(crate) fn validate_table(table: &str) -> ProtocolResult<()> {
+    let valid = !table.is_empty()
+        && !table.starts_with(|c: char| c.is_ascii_digit())
+        && table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ProtocolError::ValidationFailed(format!(
+            "invalid archive table name '{}'",
+            table
+        )))
+    }
+}
+
+pub(crate) fn to_params(record: &ArchiveRecord) -> ProtocolResult<Vec<SqlValue>> {
+    let optional_int = |v: Option<u64>| {
+        v.map(|v| SqlValue::Int(i64::try_from(v).unwrap_or(i64::MAX)))
+            .unwrap_or(SqlValue::Null)
+    };
+    Ok(vec![
+        SqlValue::Text(record.device_no.clone()),
+        SqlValue::Text(record.device_id.clone()),
+        SqlValue::Text(record.direction.code().into()),
+        SqlValue::Text(record.cmd_code.clone()),
+        SqlValue::Text(record.msg_type.clone()),
+        SqlValue::Text(record.hex.clone()),
+        SqlValue::Text(fields_to_json(&record.fields)?),
+        SqlValue::Text(record.reply_hex.clone()),
+        SqlValue::Text(fields_to_json(&record.reply_fields)?),
+        SqlValue::Int(record.success as i64),
+        record
+            .err_msg
+            .clone()
+            .map(SqlValue::Text)
+            .unwrap_or(SqlValue::Null),
+        SqlValue::Int(record.received_at),
+        optional_int(record.decode_micros),
+    ])
+}
+
+/// 按 COLUMNS 顺序的一行文本值 (NULL 为 None)
+pub(crate) fn from_row(row: Vec<Option<String>>) -> ProtocolResult<ArchiveRecord> {
+    if row.len() != COLUMNS.len() {
+        return Err(ProtocolError::CommonError(format!(
+            "archive row has {} columns, expected {}",
+            row.len(),
+            COLUMNS.len()
+        )));
+    }
+    let mut row = row.into_iter();
+    let mut text = || row.next().flatten();
+    let device_no = text().unwrap_or_default();
+    let device_id = text().unwrap_or_default();
+    let direction = text().unwrap_or_default();
+    let direction = Direction::from_code(&direction).ok_or_else(|| {
+        ProtocolError::CommonError(format!("unknown archive direction '{}'", direction))
+    })?;
+    let cmd_code = text().unwrap_or_default();
+    let msg_type = text().unwrap_or_default();
+    let hex = text().unwrap_or_default();
+    let fields = fields_from_json(&text().unwrap_or_default())?;
+    let reply_hex = text().unwrap_or_default();
+    let reply_fields = fields_from_json(&text().unwrap_or_default())?;
+    // SQLite 为 0/1，Postgres 为 t/f
+    let success = matches!(text().as_deref(), Some("1" | "t" | "true"));
+    let err_msg = text();
+    let received_at = parse_int(text())?.unwrap_or_default();
+    let decode_micros = parse_int(text())?.map(|v| v.max(0) as u64);
+    Ok(ArchiveRecord {
+        device_no,
+        device_id,
+        direction,
+        cmd_code,
+        msg_type,
+        hex,
+        fields,
+        reply_hex,
+        reply_fields,
+        success,
+        err_msg,
+        received_at,
+        decode_micros,
+    })
+}
+
+fn fields_to_json(fields: &[ReportField]) -> ProtocolResult<String> {
+    serde_json::to_string(fields).map_err(|e| ProtocolError::CommonError(e.to_string()))
+}
+
+fn fields_from_json(json: &str) -> ProtocolResult<Vec<ReportField>> {
+    if json.is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(json).map_err(|e| ProtocolError::CommonError(e.to_string()))
+}
+
+fn parse_int(value: Option<String>) -> ProtocolResult<Option<i64>> {
+    value
+        .map(|v| {
+            v.parse().map_err(|_| {
+                ProtocolError::CommonError(format!("archive column is not an integer: '{}'", v))
+            })
+        })
+        .transpose()
+}
+
+/// 多行 INSERT，placeholder(n) 生成第 n 个 (从 1 开始) 参数占位符
+pub(crate) fn insert_sql(table: &str, rows: usize, placeholder: fn(usize) -> String) -> String {
+    let values: Vec<String> = (0..rows)
+        .map(|row| {
+            let params: Vec<String> = (1..=COLUMNS.len())
+                .map(|i| placeholder(row * COLUMNS.len() + i))
+                .collect();
+            format!("({})", params.join(", "))
+        })
+        .collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES {}",
+        table,
+        COLUMNS.join(", "),
+        values.join(", ")
+    )
+}
+
+pub(crate) fn select_sql(
+    table: &str,
+    filter: &ArchiveFilter,
+    placeholder: fn(usize) -> String,
+) -> (String, Vec<SqlValue>) {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+    let mut push = |condition: &str, value: SqlValue| {
+        params.push(value);
+        conditions.push(format!("{} {}", condition, placeholder(params.len())));
+    };
+    if let Some(device_no) = &filter.device_no {
+        push("device_no =", SqlValue::Text(device_no.clone()));
+    }
+    if let Some(direction) = filter.direction {
+        push("direction =", SqlValue::Text(direction.code().into()));
+    }
+    if let Some(since) = filter.since {
+        push("received_at >=", SqlValue::Int(since));
+    }
+    if let Some(until) = filter.until {
+        push("received_at <", SqlValue::Int(until));
+    }
+    let mut sql = format!("SELECT {} FROM {}", COLUMNS.join(", "), table);
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+    sql.push_str(" ORDER BY received_at, id");
+    if let Some(limit) = filter.limit {
+        sql.push_str(&format!(" LIMIT {}", limit));
+    }
+    (sql, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dollar(n: usize) -> String {
+        format!("${}", n)
+    }
+
+    #[test]
+    fn test_sql() {
+        assert!(validate_table("frame_archive").is_ok());
+        assert!(validate_table("a; DROP TABLE b").is_err());
+        assert!(validate_table("1abc").is_err());
+
+        let sql = insert_sql("t", 2, dollar);
+        assert!(sql.ends_with("($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13), ($14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26)"));
+
+        let filter = ArchiveFilter::new().device_no("01").since(10).limit(5);
+        let (sql, params) = select_sql("t", &filter, dollar);
+        assert!(sql.ends_with(
+            "FROM t WHERE device_no = $1 AND received_at >= $2 ORDER BY received_at, id LIMIT 5"
+        ));
+        assert_eq!(params, [SqlValue::Text("01".into()), SqlValue::Int(10)]);
+    }
+
+    #[test]
+    fn test_row_roundtrip() {
+        let record = ArchiveRecord {
+            device_no: "01".into(),
+            device_id: String::new(),
+            direction: Direction::Down,
+            cmd_code: "25".into(),
+            msg_type: String::new(),
+            hex: "6825".into(),
+            fields: Vec::new(),
+            reply_hex: String::new(),
+            reply_fields: Vec::new(),
+            success: true,
+            err_msg: None,
+            received_at: 1_700_000_000_000,
+            decode_micros: Some(12),
+        };
+        let row = to_params(&record)
+            .unwrap()
+            .into_iter()
+            .map(|v| match v {
+                SqlValue::Text(s) => Some(s),
+                SqlValue::Int(v) => Some(v.to_string()),
+                SqlValue::Null => None,
+            })
+            .collect();
+        assert_eq!(from_row(row).unwrap(), record);
+    }
+}
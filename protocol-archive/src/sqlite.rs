@@ -0,0 +1,194 @@
+use std::time::Duration;
+
+use protocol_kernel::{ProtocolError, ProtocolResult};
+use rusqlite::{
+    params_from_iter,
+    types::{ToSqlOutput, ValueRef},
+    Connection, Row, ToSql,
+};
+
+use crate::{
+    record::{ArchiveFilter, ArchiveRecord},
+    sql::{self, SqlValue, COLUMNS, DEFAULT_TABLE},
+    writer::ArchiveStore,
+};
+
+/// SQLite 归档 (rusqlite)。打开时自动建表，每批记录在一个事务内写入
+pub struct SqliteArchive {
+    conn: Connection,
+    table: String,
+}
+
+impl SqliteArchive {
+    pub fn open(path: &str) -> ProtocolResult<Self> {
+        Self::open_with_table(path, DEFAULT_TABLE)
+    }
+
+    pub fn open_in_memory() -> ProtocolResult<Self> {
+        Self::open(":memory:")
+    }
+
+    pub fn open_with_table(path: &str, table: &str) -> ProtocolResult<Self> {
+        sql::validate_table(table)?;
+        let conn = Connection::open(path).map_err(sqlite_error("open"))?;
+        conn.busy_timeout(Duration::from_secs(5))
+            .map_err(sqlite_error("open"))?;
+        let archive = Self {
+            conn,
+            table: table.into(),
+        };
+        archive.migrate()?;
+        Ok(archive)
+    }
+
+    fn migrate(&self) -> ProtocolResult<()> {
+        self.conn
+            .execute_batch(&format!(
+                "CREATE TABLE IF NOT EXISTS {t} (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    device_no TEXT NOT NULL,
+                    device_id TEXT NOT NULL,
+                    direction TEXT NOT NULL,
+                    cmd_code TEXT NOT NULL,
+                    msg_type TEXT NOT NULL,
+                    hex TEXT NOT NULL,
+                    fields TEXT NOT NULL,
+                    reply_hex TEXT NOT NULL,
+                    reply_fields TEXT NOT NULL,
+                    success INTEGER NOT NULL,
+                    err_msg TEXT,
+                    received_at INTEGER NOT NULL,
+                    decode_micros INTEGER
+                );
+                CREATE INDEX IF NOT EXISTS {t}_device_time ON {t} (device_no, received_at);",
+                t = self.table
+            ))
+            .map_err(sqlite_error("migrate"))
+    }
+}
+
+impl ArchiveStore for SqliteArchive {
+    fn insert_batch(&mut self, records: &[ArchiveRecord]) -> ProtocolResult<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        // 未 commit 的事务在 drop 时回滚
+        let tx = self.conn.transaction().map_err(sqlite_error("begin"))?;
+        {
+            let mut stmt = tx
+                .prepare_cached(&sql::insert_sql(&self.table, 1, question))
+                .map_err(sqlite_error("prepare"))?;
+            for record in records {
+                stmt.execute(params_from_iter(sql::to_params(record)?))
+                    .map_err(sqlite_error("insert"))?;
+            }
+        }
+        tx.commit().map_err(sqlite_error("commit"))
+    }
+
+    fn query(&mut self, filter: &ArchiveFilter) -> ProtocolResult<Vec<ArchiveRecord>> {
+        let (select, params) = sql::select_sql(&self.table, filter, question);
+        let mut stmt = self
+            .conn
+            .prepare_cached(&select)
+            .map_err(sqlite_error("prepare"))?;
+        let rows = stmt
+            .query_map(params_from_iter(params), text_row)
+            .map_err(sqlite_error("query"))?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(sqlite_error("query"))?;
+        rows.into_iter().map(sql::from_row).collect()
+    }
+}
+
+impl ToSql for SqlValue {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            SqlValue::Text(s) => ToSqlOutput::Borrowed(ValueRef::Text(s.as_bytes())),
+            SqlValue::Int(v) => ToSqlOutput::Borrowed(ValueRef::Integer(*v)),
+            SqlValue::Null => ToSqlOutput::Borrowed(ValueRef::Null),
+        })
+    }
+}
+
+// 按 COLUMNS 顺序把一行转为文本，交给 sql::from_row 解析
+fn text_row(row: &Row<'_>) -> rusqlite::Result<Vec<Option<String>>> {
+    (0..COLUMNS.len())
+        .map(|col| {
+            Ok(match row.get_ref(col)? {
+                ValueRef::Null => None,
+                ValueRef::Integer(v) => Some(v.to_string()),
+                ValueRef::Real(v) => Some(v.to_string()),
+                ValueRef::Text(s) | ValueRef::Blob(s) => {
+                    Some(String::from_utf8_lossy(s).into_owned())
+                }
+            })
+        })
+        .collect()
+}
+
+fn question(n: usize) -> String {
+    format!("?{}", n)
+}
+
+fn sqlite_error(action: &'static str) -> impl Fn(rusqlite::Error) -> ProtocolError {
+    move |e| ProtocolError::CommonError(format!("sqlite {}: {}", action, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{record::Direction, writer::ArchiveWriter};
+    use protocol_kernel::JniResponse;
+
+    fn response(device_no: &str, hex: &str) -> JniResponse {
+        JniResponse::from(
+            format!(
+                r#"{{"success":true,"deviceNo":"{}","cmdCode":"91","reqHex":"{}","reqJsons":[{{"name":"读数","code":"du_shu","value":"1.5","alert":false}}]}}"#,
+                device_no, hex
+            )
+            .as_bytes(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_insert_and_query() {
+        let mut archive = SqliteArchive::open_in_memory().unwrap();
+        let records: Vec<ArchiveRecord> =
+            [("01", "6801", 10), ("02", "6802", 20), ("01", "6803", 30)]
+                .iter()
+                .map(|(no, hex, at)| {
+                    ArchiveRecord::from_response(&response(no, hex))
+                        .unwrap()
+                        .with_received_at(*at)
+                })
+                .collect();
+        archive.insert_batch(&records).unwrap();
+
+        let found = archive
+            .query(&ArchiveFilter::new().device_no("01").since(15))
+            .unwrap();
+        assert_eq!(found, [records[2].clone()]);
+        let found = archive
+            .query(&ArchiveFilter::new().direction(Direction::Up).limit(2))
+            .unwrap();
+        assert_eq!(found, records[..2]);
+        assert!(archive
+            .query(&ArchiveFilter::new().direction(Direction::Down))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_writer() {
+        let writer = ArchiveWriter::new(SqliteArchive::open_in_memory().unwrap());
+        writer.archive(&response("01", "6801")).unwrap();
+        let log = writer.replay_log(&ArchiveFilter::new()).unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].req_hex(), "6801");
+        assert_eq!(log[0].req_jsons()[0].value, "1.5");
+
+        assert!(SqliteArchive::open_with_table(":memory:", "bad table").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use std::sync::Mutex;
+
+use protocol_kernel::{bridge::sink::ReportSink, JniResponse, ProtocolResult};
+
+use crate::record::{ArchiveFilter, ArchiveRecord};
+
+/// 归档存储。insert_batch 应在一个事务内写入整批记录
+pub trait ArchiveStore: Send {
+    fn insert_batch(&mut self, records: &[ArchiveRecord]) -> ProtocolResult<()>;
+
+    fn query(&mut self, filter: &ArchiveFilter) -> ProtocolResult<Vec<ArchiveRecord>>;
+}
+
+/// 攒批写入。记录先放在内存中，达到 batch_size 时整批写入；
+/// 宿主应定时调用 flush，避免低流量时记录长时间停留在内存里。drop 时会写入剩余记录
+pub struct ArchiveWriter<S: ArchiveStore> {
+    store: Mutex<S>,
+    pending: Mutex<Vec<ArchiveRecord>>,
+    batch_size: usize,
+}
+
+impl<S: ArchiveStore> ArchiveWriter<S> {
+    pub fn new(store: S) -> Self {
+        Self {
+            store: Mutex::new(store),
+            pending: Mutex::new(Vec::new()),
+            batch_size: 500,
+        }
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn push(&self, record: ArchiveRecord) -> ProtocolResult<()> {
+        let full = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.push(record);
+            pending.len() >= self.batch_size
+        };
+        if full {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn archive(&self, rsp: &JniResponse) -> ProtocolResult<()> {
+        self.push(ArchiveRecord::from_response(rsp)?)
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// 写入全部待写记录。失败时记录放回队首，下次 flush 重试
+    pub fn flush(&self) -> ProtocolResult<()> {
+        let batch = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let result = self
+            .store
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert_batch(&batch);
+        if result.is_err() {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            let newer = std::mem::replace(&mut *pending, batch);
+            pending.extend(newer);
+        }
+        result
+    }
+
+    /// 先 flush，再按条件查询
+    pub fn query(&self, filter: &ArchiveFilter) -> ProtocolResult<Vec<ArchiveRecord>> {
+        self.flush()?;
+        self.store
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .query(filter)
+    }
+
+    /// 查询结果还原成 JniResponse，可直接交给 protocol-testkit 的 Replayer
+    pub fn replay_log(&self, filter: &ArchiveFilter) -> ProtocolResult<Vec<JniResponse>> {
+        self.query(filter)?
+            .iter()
+            .map(ArchiveRecord::to_response)
+            .collect()
+    }
+}
+
+impl<S: ArchiveStore> ReportSink for ArchiveWriter<S> {
+    fn publish(&self, batch: &[JniResponse]) -> ProtocolResult<()> {
+        batch.iter().try_for_each(|rsp| self.archive(rsp))
+    }
+}
+
+impl<S: ArchiveStore> Drop for ArchiveWriter<S> {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            eprintln!(
+                "[WARN] archive flush on drop failed, {} records lost: {}",
+                self.pending_len(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record::Direction;
+    use protocol_kernel::ProtocolError;
+    use std::sync::{Arc, Mutex};
+
+    // 记录每次写入的批大小，fail 为 true 时写入失败
+    #[derive(Clone, Default)]
+    struct MemoryStore {
+        batches: Arc<Mutex<Vec<usize>>>,
+        rows: Arc<Mutex<Vec<ArchiveRecord>>>,
+        fail: Arc<Mutex<bool>>,
+    }
+
+    impl ArchiveStore for MemoryStore {
+        fn insert_batch(&mut self, records: &[ArchiveRecord]) -> ProtocolResult<()> {
+            if *self.fail.lock().unwrap() {
+                return Err(ProtocolError::CommonError("down".into()));
+            }
+            self.batches.lock().unwrap().push(records.len());
+            self.rows.lock().unwrap().extend_from_slice(records);
+            Ok(())
+        }
+
+        fn query(&mut self, filter: &ArchiveFilter) -> ProtocolResult<Vec<ArchiveRecord>> {
+            Ok(self
+                .rows
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|r| filter.direction.is_none_or(|d| d == r.direction))
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn response(hex: &str) -> JniResponse {
+        JniResponse::from(
+            format!(r#"{{"success":true,"deviceNo":"01","reqHex":"{}"}}"#, hex).as_bytes(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_batching() {
+        let store = MemoryStore::default();
+        let writer = ArchiveWriter::new(store.clone()).with_batch_size(2);
+        writer
+            .publish(&[response("01"), response("02"), response("03")])
+            .unwrap();
+        assert_eq!(*store.batches.lock().unwrap(), [2]);
+        assert_eq!(writer.pending_len(), 1);
+
+        *store.fail.lock().unwrap() = true;
+        assert!(writer.archive(&response("04")).is_err());
+        assert_eq!(writer.pending_len(), 2);
+
+        *store.fail.lock().unwrap() = false;
+        let log = writer
+            .replay_log(&ArchiveFilter::new().direction(Direction::Up))
+            .unwrap();
+        let hex: Vec<&str> = log.iter().map(|r| r.req_hex()).collect();
+        assert_eq!(hex, ["01", "02", "03", "04"]);
+        drop(writer);
+        assert_eq!(*store.batches.lock().unwrap(), [2, 2]);
+    }
+}
@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CrcType {
     Crc16Ccitt,
     Crc16CcittFalse,
@@ -1,3 +1,4 @@
+use alloc::string::String;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -5,3 +6,12 @@ pub enum CommError {
     #[error("Unknown msg-type: {0}")]
     UnknownMsgType(String),
 }
+
+impl CommError {
+    /// 稳定的错误码，供 Java 端/前端按类型分支，不随错误文案变化
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommError::UnknownMsgType(_) => "E_COMM_001",
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::error::{ProtocolError, comm_error::CommError, hex_digest_error::HexDigestError};
+
+/// 跨 bridge 传递的结构化错误: 稳定错误码 + 文案 + 变体相关的细节字段
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ErrorEnvelope {
+    pub code: String,
+    pub message: String,
+    #[serde(default)]
+    pub details: BTreeMap<String, String>,
+}
+
+impl ProtocolError {
+    /// 变体携带的结构化字段，文案之外供调用方直接读取
+    pub fn details(&self) -> BTreeMap<String, String> {
+        let mut map = BTreeMap::new();
+        if let ProtocolError::DecodeError { source, .. } = self {
+            // 先放内部错误的细节，外层的 field/offset 覆盖同名字段
+            map = source.details();
+        }
+        let mut put = |k: &str, v: String| {
+            map.insert(k.to_string(), v);
+        };
+        match self {
+            ProtocolError::HexDigestError(HexDigestError::CrcMismatch { expected, actual }) => {
+                put("expected", format!("{:04X}", expected));
+                put("actual", format!("{:04X}", actual));
+            }
+            ProtocolError::CommError(CommError::UnknownMsgType(t)) => put("msgType", t.clone()),
+            ProtocolError::CrcError { ori_crc, calc_crc } => {
+                put("oriCrc", format!("{:04X}", ori_crc));
+                put("calcCrc", format!("{:04X}", calc_crc));
+            }
+            ProtocolError::InvalidKeyLength { actual } => put("actual", actual.to_string()),
+            ProtocolError::InputTooShort { needed, available } => {
+                put("needed", needed.to_string());
+                put("available", available.to_string());
+            }
+            ProtocolError::DecodeError { field, offset, source } => {
+                put("field", field.clone());
+                put("offset", offset.to_string());
+                put("cause", source.to_string());
+            }
+            _ => {}
+        }
+        map
+    }
+
+    pub fn to_envelope(&self) -> ErrorEnvelope {
+        ErrorEnvelope {
+            code: self.code().to_string(),
+            message: self.to_string(),
+            details: self.details(),
+        }
+    }
+}
+
+impl From<&ProtocolError> for ErrorEnvelope {
+    fn from(err: &ProtocolError) -> Self {
+        err.to_envelope()
+    }
+}
+
+// 序列化为 {code, message, details}，反序列化请使用 ErrorEnvelope
+impl Serialize for ProtocolError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_envelope().serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::hex_error::HexError;
+
+    #[test]
+    fn test_envelope_serialization() {
+        let err = ProtocolError::InputTooShort {
+            needed: 4,
+            available: 2,
+        };
+        let json = serde_json::to_value(&err).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "E_CORE_002",
+                "message": err.to_string(),
+                "details": {"needed": "4", "available": "2"},
+            })
+        );
+        // 序列化结果可以按 ErrorEnvelope 读回
+        let envelope: ErrorEnvelope = serde_json::from_value(json).unwrap();
+        assert_eq!(envelope, err.to_envelope());
+
+        // 没有结构化字段的变体 details 为空，缺省时也能反序列化
+        let envelope = ProtocolError::ValidationFailed("x".into()).to_envelope();
+        assert!(envelope.details.is_empty());
+        let envelope: ErrorEnvelope =
+            serde_json::from_str(r#"{"code":"E_CORE_003","message":"x"}"#).unwrap();
+        assert!(envelope.details.is_empty());
+    }
+
+    #[test]
+    fn test_decode_error_details() {
+        let err = ProtocolError::from(HexDigestError::CrcMismatch {
+            expected: 0x1234,
+            actual: 0xABCD,
+        })
+        .with_context("校验码", 30);
+        let envelope = ErrorEnvelope::from(&err);
+        assert_eq!(envelope.code, "E_CRC_001");
+        assert_eq!(envelope.details["expected"], "1234");
+        assert_eq!(envelope.details["actual"], "ABCD");
+        assert_eq!(envelope.details["field"], "校验码");
+        assert_eq!(envelope.details["offset"], "30");
+        assert_eq!(
+            envelope.details["cause"],
+            "CRC checksum mismatch. Expected 4660, but got 43981."
+        );
+
+        let err = ProtocolError::from(HexError::NotBcd("1A".into())).with_context("表号", 2);
+        assert_eq!(err.details().len(), 3);
+    }
+}
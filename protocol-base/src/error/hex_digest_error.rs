@@ -17,3 +17,16 @@ pub enum HexDigestError {
     #[error("crc calculation error")]
     CRCCalculateError,
 }
+
+impl HexDigestError {
+    /// 稳定的错误码，供 Java 端/前端按类型分支，不随错误文案变化
+    pub fn code(&self) -> &'static str {
+        match self {
+            HexDigestError::CrcMismatch { .. } => "E_CRC_001",
+            HexDigestError::CRCCalculateError => "E_CRC_002",
+            HexDigestError::InvalidHead => "E_FRAME_001",
+            HexDigestError::InvalidTail => "E_FRAME_002",
+            HexDigestError::UnknownCommandId(_) => "E_FRAME_003",
+        }
+    }
+}
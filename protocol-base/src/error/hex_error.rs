@@ -1,3 +1,4 @@
+use alloc::string::String;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -57,6 +58,9 @@ pub enum HexError {
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    #[error("Invalid timestamp {value}: {reason}")]
+    InvalidTimestamp { value: String, reason: String },
+
     #[error(
         "Padding error: original byte length ({original_len}) exceeds target byte length ({target_len})."
     )]
@@ -65,3 +69,25 @@ pub enum HexError {
         target_len: usize,
     },
 }
+
+impl HexError {
+    /// 稳定的错误码，供 Java 端/前端按类型分支，不随错误文案变化
+    pub fn code(&self) -> &'static str {
+        match self {
+            HexError::NotHex(_) => "E_HEX_001",
+            HexError::InvalidFloatLength { .. } => "E_HEX_002",
+            HexError::InvalidFloatLengthEither { .. } => "E_HEX_003",
+            HexError::HexParseError { .. } => "E_HEX_004",
+            HexError::HexLengthError { .. } => "E_HEX_005",
+            HexError::BinaryLengthErrorNegative { .. } => "E_HEX_006",
+            HexError::BinaryParseError { .. } => "E_HEX_007",
+            HexError::InvalidRange { .. } => "E_HEX_008",
+            HexError::NotAscii(_) => "E_HEX_009",
+            HexError::NotBcd(_) => "E_HEX_010",
+            HexError::NotMachineCode(_) => "E_HEX_011",
+            HexError::InvalidInput(_) => "E_HEX_012",
+            HexError::PaddingError { .. } => "E_HEX_013",
+            HexError::InvalidTimestamp { .. } => "E_HEX_014",
+        }
+    }
+}
@@ -1,7 +1,12 @@
 pub mod comm_error;
+pub mod error_envelope;
 pub mod hex_digest_error;
 pub mod hex_error;
 
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+};
 use thiserror::Error;
 
 use crate::error::{
@@ -43,4 +48,149 @@ pub enum ProtocolError {
 
     #[error("Validation failed: {0}")]
     ValidationFailed(String),
+
+    // 带位置信息的解码错误，由 with_context 包装产生
+    #[error("field {field} at offset {offset}: {source}")]
+    DecodeError {
+        field: String,
+        offset: usize,
+        source: Box<ProtocolError>,
+    },
+}
+
+impl ProtocolError {
+    /// 稳定的错误码 (如 E_HEX_001、E_CRC_002)。
+    /// 已发布的错误码不允许修改含义，新增变体只能追加新的编号。
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProtocolError::HexDigestError(e) => e.code(),
+            ProtocolError::HexError(e) => e.code(),
+            ProtocolError::CommError(e) => e.code(),
+            ProtocolError::CommonError(_) => "E_CORE_001",
+            ProtocolError::CrcError { .. } => "E_CRC_003",
+            ProtocolError::CryptoError(_) => "E_CRYPTO_001",
+            ProtocolError::InvalidKeyLength { .. } => "E_CRYPTO_002",
+            ProtocolError::UnsupportedMode(_) => "E_CRYPTO_003",
+            ProtocolError::InputTooShort { .. } => "E_CORE_002",
+            ProtocolError::ValidationFailed(_) => "E_CORE_003",
+            // 位置信息不影响错误类型，沿用内部错误的错误码
+            ProtocolError::DecodeError { source, .. } => source.code(),
+        }
+    }
+
+    /// 为错误附加字段名与字节偏移。已经带有位置信息的错误保持不变，
+    /// 保证最终报出的是最内层 (最精确) 的位置
+    pub fn with_context(self, field_title: &str, byte_offset: usize) -> Self {
+        match self {
+            ProtocolError::DecodeError { .. } => self,
+            other => ProtocolError::DecodeError {
+                field: field_title.to_string(),
+                offset: byte_offset,
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// 去掉位置信息，返回最内层的原始错误
+    pub fn root(&self) -> &ProtocolError {
+        match self {
+            ProtocolError::DecodeError { source, .. } => source.root(),
+            other => other,
+        }
+    }
+}
+
+/// 在 `ProtocolResult` 上直接附加字段/偏移信息
+pub trait ResultExt<T> {
+    fn with_context(self, field_title: &str, byte_offset: usize) -> Result<T, ProtocolError>;
+}
+
+impl<T> ResultExt<T> for Result<T, ProtocolError> {
+    fn with_context(self, field_title: &str, byte_offset: usize) -> Result<T, ProtocolError> {
+        self.map_err(|e| e.with_context(field_title, byte_offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    use super::*;
+
+    #[test]
+    fn test_error_codes() {
+        let cases: Vec<(ProtocolError, &str)> = vec![
+            (HexError::NotHex("zz".into()).into(), "E_HEX_001"),
+            (HexError::NotBcd("1A".into()).into(), "E_HEX_010"),
+            (
+                HexError::InvalidTimestamp {
+                    value: "2023-13-01".into(),
+                    reason: "month 13 is out of range".into(),
+                }
+                .into(),
+                "E_HEX_014",
+            ),
+            (
+                HexDigestError::CrcMismatch {
+                    expected: 1,
+                    actual: 2,
+                }
+                .into(),
+                "E_CRC_001",
+            ),
+            (HexDigestError::InvalidHead.into(), "E_FRAME_001"),
+            (CommError::UnknownMsgType("x".into()).into(), "E_COMM_001"),
+            (ProtocolError::CommonError("x".into()), "E_CORE_001"),
+            (
+                ProtocolError::CrcError {
+                    ori_crc: 1,
+                    calc_crc: 2,
+                },
+                "E_CRC_003",
+            ),
+            (
+                ProtocolError::InvalidKeyLength { actual: 7 },
+                "E_CRYPTO_002",
+            ),
+            (
+                ProtocolError::InputTooShort {
+                    needed: 4,
+                    available: 2,
+                },
+                "E_CORE_002",
+            ),
+            (ProtocolError::ValidationFailed("x".into()), "E_CORE_003"),
+        ];
+        for (err, code) in cases {
+            assert_eq!(err.code(), code, "{}", err);
+        }
+        // 错误码不受文案影响
+        assert_eq!(
+            ProtocolError::ValidationFailed("价格必须为正".to_string()).code(),
+            ProtocolError::ValidationFailed("other".to_string()).code()
+        );
+    }
+
+    #[test]
+    fn test_decode_error_keeps_inner_code() {
+        let err = ProtocolError::from(HexError::NotBcd("1A".into())).with_context("累计流量", 14);
+        assert_eq!(err.code(), "E_HEX_010");
+    }
+
+    #[test]
+    fn test_with_context_keeps_innermost_location() {
+        let inner: Result<(), ProtocolError> = Err(HexError::NotBcd("1A".into()).into());
+        let err = inner
+            .with_context("累计流量", 14)
+            .with_context("数据域", 10)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "field 累计流量 at offset 14: Input string is not valid BCD: 1A"
+        );
+        assert!(matches!(
+            err.root(),
+            ProtocolError::HexError(HexError::NotBcd(_))
+        ));
+    }
 }
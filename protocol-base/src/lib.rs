@@ -1,6 +1,10 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod definitions;
 pub mod error;
 
-pub use error::ProtocolError;
+pub use error::{ProtocolError, ResultExt, error_envelope::ErrorEnvelope};
 pub type ProtocolResult<T> = Result<T, ProtocolError>;
 pub use definitions::defi::CrcType;
@@ -0,0 +1,59 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use protocol_base::definitions::defi::CrcType;
+use protocol_bench::{decode, encode, golden_frame};
+use protocol_kernel::{hex_util, utils::crc_util};
+
+fn codec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    for readings in [4u8, 64, 250] {
+        let frame = golden_frame(readings);
+        group.throughput(Throughput::Bytes(frame.len() as u64));
+        group.bench_with_input(BenchmarkId::new("fields", readings), &frame, |b, frame| {
+            b.iter(|| decode(black_box(frame)).unwrap())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("encode");
+    for readings in [4u8, 64, 250] {
+        let values: Vec<u32> = (0..readings as u32).collect();
+        group.bench_with_input(
+            BenchmarkId::new("fields", readings),
+            &values,
+            |b, values| b.iter(|| encode(black_box(values)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn utils(c: &mut Criterion) {
+    let payload: Vec<u8> = (0..=255u8).cycle().take(1024).collect();
+    let hex = hex_util::bytes_to_hex(&payload).unwrap();
+
+    let mut group = c.benchmark_group("utils_1k");
+    group.throughput(Throughput::Bytes(payload.len() as u64));
+    group.bench_function("crc/modbus", |b| {
+        b.iter(|| {
+            crc_util::calculate_from_bytes(CrcType::Crc16Modbus, black_box(&payload)).unwrap()
+        })
+    });
+    group.bench_function("crc/ccitt", |b| {
+        b.iter(|| crc_util::calculate_from_bytes(CrcType::Crc16Ccitt, black_box(&payload)).unwrap())
+    });
+    group.bench_function("hex/encode", |b| {
+        b.iter(|| hex_util::bytes_to_hex(black_box(&payload)).unwrap())
+    });
+    group.bench_function("hex/decode", |b| {
+        b.iter(|| hex_util::hex_to_bytes(black_box(&hex)).unwrap())
+    });
+    let mut words = payload.clone();
+    group.bench_function("swap/u32_slice", |b| {
+        b.iter(|| hex_util::swap_u32_slice(black_box(&mut words)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, codec, utils);
+criterion_main!(benches);
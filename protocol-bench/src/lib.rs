@@ -0,0 +1,84 @@
+//! 热点路径的基准测试: 上行解码、下行编码、CRC 与 hex 转换。基准项在 benches/codec.rs，由 criterion 执行。
+//!
+//! ```text
+//! cargo bench                              # 全部
+//! cargo bench -- decode                    # 名称包含 decode 的项
+//! cargo bench -- --save-baseline v0.1      # 保存为基线
+//! cargo bench -- --baseline v0.1           # 与基线对比
+//! ```
+//!
+//! 发布前与上一版本的基线对比，发现热点路径的性能回退。本模块只提供基准用的黄金帧与编解码流程
+use protocol_base::definitions::defi::CrcType;
+use protocol_kernel::{
+    FieldConvertDecoder, FieldTranslator, FieldType, ProtocolResult, Rawfield, Reader, ReportField,
+    Writer,
+};
+
+// 黄金帧: 68 | 地址(7, BCD) | 命令 | 读数个数 | 读数 (u32 大端, 0.01) x N | CRC16 Modbus | 16
+const HEAD: u8 = 0x68;
+const TAIL: u8 = 0x16;
+const ADDRESS: [u8; 7] = [0x12, 0x34, 0x56, 0x78, 0x90, 0x00, 0x01];
+const CMD_REPORT: u8 = 0x01;
+
+/// 带 `readings` 个读数的上行帧
+pub fn golden_frame(readings: u8) -> Vec<u8> {
+    let values: Vec<u32> = (0..readings as u32).map(|i| 123_400 + i).collect();
+    encode(&values).expect("golden frame")
+}
+
+/// 端到端解码: 校验 CRC、逐字段翻译并生成上报字段
+pub fn decode(frame: &[u8]) -> ProtocolResult<Vec<ReportField>> {
+    let mut reader = Reader::new(frame);
+    reader
+        .read_and_translate_tail(1, |b| Ok(Rawfield::new(b, "结束符", "16".into())))?
+        .read_and_translate_crc(2, CrcType::Crc16Modbus, 0, -3)?
+        .read_and_translate_head(1, |b| Ok(Rawfield::new(b, "起始符", "68".into())))?
+        .read_and_translate_head(7, |b| {
+            FieldConvertDecoder::new("表号", FieldType::StringOrBCD, None, false).translate(b)
+        })?
+        .read_and_translate_head(1, |b| {
+            FieldConvertDecoder::new("命令", FieldType::UnsignedU8(1.0), None, false).translate(b)
+        })?;
+    let count = reader.read_bytes(1)?[0];
+    let reading = FieldConvertDecoder::new("读数", FieldType::UnsignedU32(0.01), None, false);
+    for _ in 0..count {
+        reader.read_and_translate_head(4, |b| reading.translate(b))?;
+    }
+    reader.to_report_fields()
+}
+
+/// 端到端编码: 写入字段、占位并回填 CRC
+pub fn encode(values: &[u32]) -> ProtocolResult<Vec<u8>> {
+    let mut writer = Writer::new();
+    writer
+        .write_bytes("起始符", &[HEAD], "68")?
+        .write_bytes("表号", &ADDRESS, "12345678900001")?
+        .write_bytes("命令", &[CMD_REPORT], "1")?
+        .write_bytes("读数个数", &[values.len() as u8], "")?;
+    for value in values {
+        writer.write_bytes("读数", &value.to_be_bytes(), "")?;
+    }
+    writer
+        .write_placeholder("crc", 2)?
+        .write_bytes("结束符", &[TAIL], "16")?
+        .write_crc::<()>(CrcType::Crc16Modbus, 0, -3, "crc", false)?;
+    Ok(writer.buffer()?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_golden_frame_round_trip() {
+        let frame = golden_frame(3);
+        assert_eq!(frame.len(), 1 + 7 + 1 + 1 + 3 * 4 + 2 + 1);
+        let fields = decode(&frame).unwrap();
+        let readings: Vec<&str> = fields
+            .iter()
+            .filter(|f| f.name == "读数")
+            .map(|f| f.value.as_str())
+            .collect();
+        assert_eq!(readings, ["1234", "1234.01", "1234.02"]);
+    }
+}
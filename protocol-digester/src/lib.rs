@@ -1,5 +1,10 @@
+#[cfg(feature = "aes")]
 pub mod aes_digester;
+#[cfg(feature = "des")]
 pub mod des_digester;
+#[cfg(feature = "hmac")]
 pub mod hmac_sha256_digester;
+#[cfg(feature = "md5")]
 pub mod md5_digester;
+#[cfg(feature = "sha256")]
 pub mod sha256_digester;
@@ -0,0 +1,78 @@
+/*
+ * protocol-ffi C ABI. Generated by cbindgen from src/lib.rs, do not edit.
+ *
+ * Requests and responses use the same encoding as the JNI bridge: JSON
+ * (JniRequest/JniResponse, camelCase) or the compact TLV format starting
+ * with byte 0xB7. The response uses the same encoding as the request.
+ *
+ * The protocol library built on top of protocol-ffi must register its
+ * handler (register_handler/register_protocol) before the first call.
+ */
+
+#ifndef PROTOCOL_FFI_H
+#define PROTOCOL_FFI_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define PROTOCOL_FFI_OK 0
+
+#define PROTOCOL_FFI_NULL_ARGUMENT -1
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * 处理一条请求。成功时 `*out_ptr`/`*out_len` 指向新分配的响应缓冲区，
+ * 必须用 `protocol_free_buffer` 释放。协议层面的失败也返回 PROTOCOL_FFI_OK，
+ * 失败信息在响应的 success/errCode 中
+ *
+ * # Safety
+ * `input_ptr` 必须指向 `input_len` 个可读字节 (input_len 为 0 时可为 null)，
+ * `out_ptr` 与 `out_len` 必须是可写的有效指针
+ */
+int32_t protocol_process_request(const uint8_t *input_ptr,
+                                 size_t input_len,
+                                 uint8_t **out_ptr,
+                                 size_t *out_len);
+
+/**
+ * 一次调用处理一批报文 (JniBatchRequest)，输出同格式的 JniBatchResponse。
+ * 缓冲区约定与 `protocol_process_request` 相同
+ *
+ * # Safety
+ * 同 `protocol_process_request`
+ */
+int32_t protocol_process_batch(const uint8_t *input_ptr,
+                               size_t input_len,
+                               uint8_t **out_ptr,
+                               size_t *out_len);
+
+/**
+ * 输出已注册协议的能力描述 (JSON)，缓冲区同样用 `protocol_free_buffer` 释放
+ *
+ * # Safety
+ * `out_ptr` 与 `out_len` 必须是可写的有效指针
+ */
+int32_t protocol_describe(uint8_t **out_ptr, size_t *out_len);
+
+/**
+ * 释放本库输出的缓冲区 (`protocol_process_request`/`protocol_process_batch`/
+ * `protocol_describe`)，ptr 为 null 时不做任何事
+ *
+ * # Safety
+ * `ptr`/`len` 必须是上述函数输出的原值，且只能释放一次
+ */
+void protocol_free_buffer(uint8_t *ptr, size_t len);
+
+/**
+ * 返回 ABI 版本字符串 (静态内存，无需释放)
+ */
+const char *protocol_ffi_version(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PROTOCOL_FFI_H */
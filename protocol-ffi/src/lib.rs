@@ -0,0 +1,182 @@
+//! C ABI 绑定层，供 C/C++、Go (cgo) 等非 JVM 宿主嵌入解码器。
+//!
+//! 具体协议的 cdylib 依赖本 crate，并在首次调用前 (通常是协议库自己的初始化入口)
+//! 调用 `register_handler` 或 `register_protocol`;
+//! 导出的函数声明见 `include/protocol_ffi.h` (由 cbindgen 生成，见 cbindgen.toml)。输入输出与 JNI 层相同，是 JSON 或 TLV
+//! 编码的请求/响应 (按首字节自动识别)。
+use std::{ffi::c_char, ptr, slice};
+
+pub use protocol_kernel::bridge::dispatch::{
+    describe_protocol, process_batch_bytes, process_bytes, register_handler, BridgeHandler,
+};
+pub use protocol_kernel::bridge::registry::{register_protocol, ProtocolHandler};
+
+pub const PROTOCOL_FFI_OK: i32 = 0;
+pub const PROTOCOL_FFI_NULL_ARGUMENT: i32 = -1;
+
+/// 处理一条请求。成功时 `*out_ptr`/`*out_len` 指向新分配的响应缓冲区，
+/// 必须用 `protocol_free_buffer` 释放。协议层面的失败也返回 PROTOCOL_FFI_OK，
+/// 失败信息在响应的 success/errCode 中
+///
+/// # Safety
+/// `input_ptr` 必须指向 `input_len` 个可读字节 (input_len 为 0 时可为 null)，
+/// `out_ptr` 与 `out_len` 必须是可写的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn protocol_process_request(
+    input_ptr: *const u8,
+    input_len: usize,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    if out_ptr.is_null() || out_len.is_null() || (input_ptr.is_null() && input_len > 0) {
+        return PROTOCOL_FFI_NULL_ARGUMENT;
+    }
+    let input = if input_len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(input_ptr, input_len)
+    };
+    let output = process_bytes(input).into_boxed_slice();
+    *out_len = output.len();
+    *out_ptr = Box::into_raw(output) as *mut u8;
+    PROTOCOL_FFI_OK
+}
+
+/// 一次调用处理一批报文 (JniBatchRequest)，输出同格式的 JniBatchResponse。
+/// 缓冲区约定与 `protocol_process_request` 相同
+///
+/// # Safety
+/// 同 `protocol_process_request`
+#[no_mangle]
+pub unsafe extern "C" fn protocol_process_batch(
+    input_ptr: *const u8,
+    input_len: usize,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    if out_ptr.is_null() || out_len.is_null() || (input_ptr.is_null() && input_len > 0) {
+        return PROTOCOL_FFI_NULL_ARGUMENT;
+    }
+    let input = if input_len == 0 {
+        &[][..]
+    } else {
+        slice::from_raw_parts(input_ptr, input_len)
+    };
+    let output = process_batch_bytes(input).into_boxed_slice();
+    *out_len = output.len();
+    *out_ptr = Box::into_raw(output) as *mut u8;
+    PROTOCOL_FFI_OK
+}
+
+/// 输出已注册协议的能力描述 (JSON)，缓冲区同样用 `protocol_free_buffer` 释放
+///
+/// # Safety
+/// `out_ptr` 与 `out_len` 必须是可写的有效指针
+#[no_mangle]
+pub unsafe extern "C" fn protocol_describe(out_ptr: *mut *mut u8, out_len: *mut usize) -> i32 {
+    if out_ptr.is_null() || out_len.is_null() {
+        return PROTOCOL_FFI_NULL_ARGUMENT;
+    }
+    let output = describe_protocol().into_boxed_slice();
+    *out_len = output.len();
+    *out_ptr = Box::into_raw(output) as *mut u8;
+    PROTOCOL_FFI_OK
+}
+
+/// 释放本库输出的缓冲区 (`protocol_process_request`/`protocol_process_batch`/
+/// `protocol_describe`)，ptr 为 null 时不做任何事
+///
+/// # Safety
+/// `ptr`/`len` 必须是上述函数输出的原值，且只能释放一次
+#[no_mangle]
+pub unsafe extern "C" fn protocol_free_buffer(ptr: *mut u8, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)));
+}
+
+/// 返回 ABI 版本字符串 (静态内存，无需释放)
+#[no_mangle]
+pub extern "C" fn protocol_ffi_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_kernel::bridge::{BridgeFormat, JniBatchRequest, JniBatchResponse};
+    use protocol_kernel::{JniRequest, JniResponse};
+
+    #[test]
+    fn test_process_and_free() {
+        register_handler(|req: &JniRequest| {
+            if req.hex().is_empty() {
+                return Err(protocol_kernel::ProtocolError::ValidationFailed(
+                    "empty frame".into(),
+                ));
+            }
+            let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+            rsp.set_req_hex(req.hex());
+            Ok(rsp)
+        });
+        let input = br#"{"hex":"68AA16"}"#;
+        let (mut out, mut len) = (ptr::null_mut(), 0usize);
+        unsafe {
+            assert_eq!(
+                protocol_process_request(input.as_ptr(), input.len(), &mut out, &mut len),
+                PROTOCOL_FFI_OK
+            );
+            let rsp = JniResponse::from(slice::from_raw_parts(out, len)).unwrap();
+            assert_eq!(rsp.req_hex(), "68AA16");
+            protocol_free_buffer(out, len);
+            assert_eq!(
+                protocol_process_request(input.as_ptr(), input.len(), ptr::null_mut(), &mut len),
+                PROTOCOL_FFI_NULL_ARGUMENT
+            );
+        }
+
+        let batch = JniBatchRequest::new(vec![
+            JniRequest::builder().hex("68AA16").build().unwrap(),
+            JniRequest::builder().device_no("02").build().unwrap(),
+        ]);
+        for format in [BridgeFormat::Json, BridgeFormat::Tlv] {
+            let input = batch.to_bytes_with(format).unwrap();
+            unsafe {
+                assert_eq!(
+                    protocol_process_batch(input.as_ptr(), input.len(), &mut out, &mut len),
+                    PROTOCOL_FFI_OK
+                );
+                let rsp = JniBatchResponse::from(slice::from_raw_parts(out, len)).unwrap();
+                assert_eq!((rsp.total(), rsp.succeeded(), rsp.failed()), (2, 1, 1));
+                assert_eq!(rsp.items()[0].req_hex(), "68AA16");
+                assert_eq!(rsp.items()[1].device_no(), Some("02"));
+                protocol_free_buffer(out, len);
+            }
+        }
+    }
+
+    // 头文件由 cbindgen 生成，与导出函数的签名不一致时失败
+    #[test]
+    fn test_header_is_generated() {
+        let crate_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+        let mut generated = Vec::new();
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(crate_dir.join("src/lib.rs"))
+            .generate()
+            .unwrap()
+            .write(&mut generated);
+        let generated = String::from_utf8(generated).unwrap();
+        let path = crate_dir.join("include/protocol_ffi.h");
+        if std::env::var_os("UPDATE_FFI_HEADER").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+        }
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            generated,
+            "include/protocol_ffi.h is stale, regenerate with `UPDATE_FFI_HEADER=1 cargo test`"
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use protocol_kernel::{cmd_table, AutoDecoding, Cmd};
+
+use crate::field::DataField;
+
+cmd_table! {
+    /// 命令表。code 为数据标识 (DI1 DI0，帧中 DI0 在前)，请求与应答共用，
+    /// 控制码由命令的功能码与应答标志组成，见 Cjt188Cmd::control
+    pub enum Cjt188Cmd {
+        ReadMetering => ("901F", "读计量数据", Both, Read, DataReport),
+        ReadAddress => ("810A", "读地址", Both, Read, DeviceParamSetting),
+        PriceTable => ("A010", "写价格表", Both, Write, UpdateGasPrice),
+        ValveControl => ("A017", "阀门控制", Both, Write, ValveOperation),
+    }
+}
+
+/// 控制码 D7: 1 表示表端发出的应答
+pub const REPLY_FLAG: u8 = 0x80;
+/// 控制码 D6: 1 表示异常应答
+pub const ABNORMAL_FLAG: u8 = 0x40;
+
+impl Cjt188Cmd {
+    pub fn di(&self) -> u16 {
+        // 命令表中的 code 均为合法的 4 位 hex
+        u16::from_str_radix(&self.code(), 16).unwrap_or_default()
+    }
+
+    /// 功能码: 01 读数据，03 读地址，04 写数据
+    pub fn function(&self) -> u8 {
+        match self {
+            Cjt188Cmd::ReadMetering => 0x01,
+            Cjt188Cmd::ReadAddress => 0x03,
+            Cjt188Cmd::PriceTable | Cjt188Cmd::ValveControl => 0x04,
+        }
+    }
+
+    pub fn control(&self, reply: bool) -> u8 {
+        if reply {
+            self.function() | REPLY_FLAG
+        } else {
+            self.function()
+        }
+    }
+}
+
+/// 数据域布局。数据标识与序号之后的字段随命令与方向而不同，异常应答只有序号与状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Request(Cjt188Cmd),
+    Reply(Cjt188Cmd),
+    Abnormal,
+}
+
+impl AutoDecoding<DataField> for Layout {
+    fn variants(&self) -> Vec<DataField> {
+        use DataField::*;
+        let body = match self {
+            Layout::Abnormal => return vec![Sequence, Status],
+            Layout::Request(Cjt188Cmd::PriceTable) => {
+                vec![Price1, Usage1, Price2, Usage2, Price3, EffectiveDay]
+            }
+            Layout::Request(Cjt188Cmd::ValveControl) => vec![ValveCommand],
+            Layout::Reply(Cjt188Cmd::ReadMetering) => vec![
+                CurrentFlow,
+                CurrentUnit,
+                SettlementFlow,
+                SettlementUnit,
+                RealTime,
+                Status,
+            ],
+            Layout::Reply(Cjt188Cmd::ValveControl) => vec![Status],
+            Layout::Request(_) | Layout::Reply(_) => vec![],
+        };
+        [Identifier, Sequence].into_iter().chain(body).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_kernel::CmdTable;
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(Cjt188Cmd::from_code("a017"), Some(Cjt188Cmd::ValveControl));
+        assert_eq!(Cjt188Cmd::ValveControl.control(true), 0x84);
+        assert_eq!(Cjt188Cmd::ReadAddress.di(), 0x810A);
+
+        let request = Layout::Request(Cjt188Cmd::ValveControl).variants();
+        assert_eq!(
+            request,
+            [
+                DataField::Identifier,
+                DataField::Sequence,
+                DataField::ValveCommand
+            ]
+        );
+        assert_eq!(Layout::Abnormal.variants().len(), 2);
+    }
+}
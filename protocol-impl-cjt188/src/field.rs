@@ -0,0 +1,358 @@
+use protocol_kernel::{
+    bcd_util, hex_util, math_util, AutoDecodingParam, AutoEncodingParam, DecodeContext,
+    FieldConvertDecoder, FieldEnumDecoder, FieldTranslator, FieldType, ProtocolError,
+    ProtocolResult, Rawfield, ValueType,
+};
+
+/// 数据域字段。上行按 AutoDecodingParam 解码，下行作为参数按 AutoEncodingParam 编码。
+/// 多字节字段在帧中均为低字节在前，流量、价格、用量为带小数位的 BCD
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataField {
+    Identifier,
+    Sequence,
+    CurrentFlow,
+    CurrentUnit,
+    SettlementFlow,
+    SettlementUnit,
+    RealTime,
+    Status,
+    ValveCommand,
+    Price1,
+    Usage1,
+    Price2,
+    Usage2,
+    Price3,
+    EffectiveDay,
+    Value,
+}
+
+const UNITS: &[(u8, &str)] = &[
+    (0x02, "Wh"),
+    (0x05, "kWh"),
+    (0x08, "MWh"),
+    (0x0B, "kJ"),
+    (0x0E, "MJ"),
+    (0x11, "GJ"),
+    (0x29, "L"),
+    (0x2C, "m³"),
+    (0x32, "L/h"),
+    (0x35, "m³/h"),
+];
+
+impl DataField {
+    pub const ALL: [DataField; 16] = [
+        DataField::Identifier,
+        DataField::Sequence,
+        DataField::CurrentFlow,
+        DataField::CurrentUnit,
+        DataField::SettlementFlow,
+        DataField::SettlementUnit,
+        DataField::RealTime,
+        DataField::Status,
+        DataField::ValveCommand,
+        DataField::Price1,
+        DataField::Usage1,
+        DataField::Price2,
+        DataField::Usage2,
+        DataField::Price3,
+        DataField::EffectiveDay,
+        DataField::Value,
+    ];
+
+    /// 下行参数的 key
+    pub fn key(&self) -> &'static str {
+        match self {
+            DataField::Identifier => "di",
+            DataField::Sequence => "seq",
+            DataField::CurrentFlow => "current_flow",
+            DataField::CurrentUnit => "current_unit",
+            DataField::SettlementFlow => "settlement_flow",
+            DataField::SettlementUnit => "settlement_unit",
+            DataField::RealTime => "real_time",
+            DataField::Status => "status",
+            DataField::ValveCommand => "valve",
+            DataField::Price1 => "price1",
+            DataField::Usage1 => "usage1",
+            DataField::Price2 => "price2",
+            DataField::Usage2 => "usage2",
+            DataField::Price3 => "price3",
+            DataField::EffectiveDay => "effective_day",
+            DataField::Value => "value",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataField::Identifier => "数据标识",
+            DataField::Sequence => "序号",
+            DataField::CurrentFlow => "当前累积流量",
+            DataField::CurrentUnit => "当前累积流量单位",
+            DataField::SettlementFlow => "结算日累积流量",
+            DataField::SettlementUnit => "结算日累积流量单位",
+            DataField::RealTime => "实时时间",
+            DataField::Status => "状态",
+            DataField::ValveCommand => "阀门控制",
+            DataField::Price1 => "价格1",
+            DataField::Usage1 => "用量1",
+            DataField::Price2 => "价格2",
+            DataField::Usage2 => "用量2",
+            DataField::Price3 => "价格3",
+            DataField::EffectiveDay => "启用日期",
+            DataField::Value => "数据",
+        }
+    }
+
+    /// 字节数，0 表示变长
+    pub fn byte_len(&self) -> usize {
+        match self {
+            DataField::Identifier | DataField::Status => 2,
+            DataField::Sequence
+            | DataField::CurrentUnit
+            | DataField::SettlementUnit
+            | DataField::ValveCommand
+            | DataField::EffectiveDay => 1,
+            DataField::CurrentFlow | DataField::SettlementFlow => 4,
+            DataField::Price1
+            | DataField::Usage1
+            | DataField::Price2
+            | DataField::Usage2
+            | DataField::Price3 => 3,
+            DataField::RealTime => 7,
+            DataField::Value => 0,
+        }
+    }
+
+    /// 带小数位的 BCD 字段的小数位数
+    pub fn decimals(&self) -> Option<u32> {
+        match self {
+            DataField::CurrentFlow
+            | DataField::SettlementFlow
+            | DataField::Price1
+            | DataField::Price2
+            | DataField::Price3 => Some(2),
+            DataField::Usage1 | DataField::Usage2 => Some(0),
+            _ => None,
+        }
+    }
+
+    fn unit(&self) -> Option<&'static str> {
+        match self {
+            DataField::Price1 | DataField::Price2 | DataField::Price3 => Some("元"),
+            _ => None,
+        }
+    }
+
+    fn kind(&self) -> FieldType {
+        match self {
+            DataField::Sequence => FieldType::UnsignedU8(1.0),
+            _ => FieldType::StringOrBCD,
+        }
+    }
+}
+
+// 低字节在前的 BCD -> 带小数位的数值
+fn decode_bcd(field: &DataField, bytes: &[u8], decimals: u32) -> ProtocolResult<Rawfield> {
+    let scale = 1.0 / 10f64.powi(decimals as i32);
+    let value = (bcd_util::bcd_to_u64_swap(bytes)? as f64 * scale).to_string();
+    let rf = match field.unit() {
+        Some(unit) => {
+            Rawfield::new(bytes, field.name(), format!("{} {}", value, unit)).with_unit(unit)
+        }
+        None => Rawfield::new(bytes, field.name(), value),
+    };
+    Ok(rf.with_value_type(ValueType::Float).with_scale(scale))
+}
+
+fn encode_bcd(field: &DataField, input: &str, decimals: u32) -> ProtocolResult<Vec<u8>> {
+    let scaled = (math_util::parse_numeric(input)? * 10f64.powi(decimals as i32)).round();
+    if scaled < 0.0 {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "{} cannot be negative: {}",
+            field.name(),
+            input
+        )));
+    }
+    bcd_util::u64_to_bcd_swap(scaled as u64, field.byte_len())
+}
+
+// 状态 ST 的第一个字节: D0~D1 阀门状态，D2 电池电压
+fn decode_status(bytes: &[u8]) -> String {
+    let st = bytes.first().copied().unwrap_or_default();
+    let valve = match st & 0x03 {
+        0x00 => "开阀",
+        0x01 => "关阀",
+        _ => "阀门异常",
+    };
+    let battery = if st & 0x04 == 0 {
+        "电池正常"
+    } else {
+        "电池欠压"
+    };
+    format!("{}，{}", valve, battery)
+}
+
+/// 带小数位的 BCD、时间与状态字不在默认的翻译模式之内，因此重写 translate
+impl AutoDecodingParam for DataField {
+    fn byte_length(&self) -> usize {
+        self.byte_len()
+    }
+
+    fn title(&self) -> String {
+        self.name().into()
+    }
+
+    fn swap(&self) -> bool {
+        matches!(self, DataField::Identifier | DataField::RealTime)
+    }
+
+    fn field_type(&self) -> FieldType {
+        self.kind()
+    }
+
+    fn enum_values(&self) -> Vec<(u8, String)> {
+        let values: &[(u8, &str)] = match self {
+            DataField::CurrentUnit | DataField::SettlementUnit => UNITS,
+            DataField::ValveCommand => &[(0x55, "开阀"), (0x99, "关阀")],
+            _ => &[],
+        };
+        values.iter().map(|(v, t)| (*v, t.to_string())).collect()
+    }
+
+    fn translate(&self, bytes: &[u8]) -> ProtocolResult<Rawfield> {
+        if let Some(decimals) = self.decimals() {
+            return decode_bcd(self, bytes, decimals);
+        }
+        match self {
+            DataField::RealTime => {
+                let d = hex_util::bytes_to_hex_swap(bytes)?;
+                let value = format!(
+                    "{}-{}-{} {}:{}:{}",
+                    &d[0..4],
+                    &d[4..6],
+                    &d[6..8],
+                    &d[8..10],
+                    &d[10..12],
+                    &d[12..14]
+                );
+                Ok(Rawfield::new(bytes, self.name(), value).with_value_type(ValueType::String))
+            }
+            DataField::Status => Ok(Rawfield::new(bytes, self.name(), decode_status(bytes))
+                .with_value_type(ValueType::Enum)),
+            _ if self.is_enum_mode() => {
+                FieldEnumDecoder::new(self.name(), self.enum_values(), false).translate(bytes)
+            }
+            _ => FieldConvertDecoder::new(
+                self.name(),
+                self.kind(),
+                None,
+                AutoDecodingParam::swap(self),
+            )
+            .translate(bytes),
+        }
+    }
+
+    // auto_process_with 同样按上面的规则解码
+    fn translate_with(&self, bytes: &[u8], _ctx: &mut DecodeContext) -> ProtocolResult<Rawfield> {
+        self.translate(bytes)
+    }
+}
+
+impl AutoEncodingParam for DataField {
+    fn code(&self) -> String {
+        self.key().into()
+    }
+
+    fn title(&self) -> String {
+        self.name().into()
+    }
+
+    fn byte_length(&self) -> usize {
+        self.byte_len()
+    }
+
+    fn field_type(&self) -> FieldType {
+        self.kind()
+    }
+
+    fn input_field_type(&self) -> String {
+        match self.decimals() {
+            Some(0) => "int".into(),
+            Some(_) => "float".into(),
+            None if *self == DataField::Sequence => "int".into(),
+            None => "string".into(),
+        }
+    }
+
+    fn default_value(&self) -> String {
+        match self {
+            DataField::Sequence => "0".into(),
+            _ => String::new(),
+        }
+    }
+
+    fn to_bytes(&self, input: &str) -> ProtocolResult<Vec<u8>> {
+        let input = match input {
+            "" => self.default_value(),
+            _ => input.to_string(),
+        };
+        if input.is_empty() {
+            return Err(ProtocolError::CommonError(format!(
+                "Field '{}' is required but no value provided",
+                self.key()
+            )));
+        }
+        if let Some(decimals) = self.decimals() {
+            return encode_bcd(self, &input, decimals);
+        }
+        let bytes = self.kind().encode_with_title(&input, self.name())?;
+        if self.byte_len() > 0 && bytes.len() != self.byte_len() {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "{} expects {} bytes, got '{}'",
+                self.name(),
+                self.byte_len(),
+                input
+            )));
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        let rf = DataField::CurrentFlow
+            .translate(&[0x67, 0x45, 0x23, 0x01])
+            .unwrap();
+        assert_eq!(rf.value(), "12345.67");
+        let rf = DataField::CurrentUnit.translate(&[0x2C]).unwrap();
+        assert_eq!(rf.value(), "m³");
+        let rf = DataField::RealTime
+            .translate(&[0x00, 0x30, 0x12, 0x01, 0x05, 0x24, 0x20])
+            .unwrap();
+        assert_eq!(rf.value(), "2024-05-01 12:30:00");
+        let rf = DataField::Status.translate(&[0x05, 0x00]).unwrap();
+        assert_eq!(rf.value(), "关阀，电池欠压");
+        let rf = DataField::Identifier.translate(&[0x1F, 0x90]).unwrap();
+        assert_eq!(rf.value(), "901F");
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        assert_eq!(
+            DataField::Price1.to_bytes("3.5").unwrap(),
+            [0x50, 0x03, 0x00]
+        );
+        assert_eq!(
+            DataField::Usage1.to_bytes("100").unwrap(),
+            [0x00, 0x01, 0x00]
+        );
+        assert_eq!(DataField::Sequence.to_bytes("").unwrap(), [0x00]);
+        assert_eq!(DataField::ValveCommand.to_bytes("99").unwrap(), [0x99]);
+        assert!(DataField::ValveCommand.to_bytes("").is_err());
+        assert!(DataField::ValveCommand.to_bytes("5599").is_err());
+        assert!(DataField::Price1.to_bytes("-1").is_err());
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use protocol_base::error::hex_digest_error::HexDigestError;
+use protocol_kernel::{
+    hex_util, AutoDecoding, AutoDecodingParam, AutoEncodingParam, Cmd, CmdTable, FieldType,
+    ProtocolConfig, ProtocolError, ProtocolResult, Reader, ReportField, Writer,
+};
+
+use crate::{
+    cmd::{Cjt188Cmd, Layout, ABNORMAL_FLAG, REPLY_FLAG},
+    field::DataField,
+};
+
+pub const START: u8 = 0x68;
+pub const END: u8 = 0x16;
+/// 唤醒前导字节，主站发送时通常在帧前附加 2~4 个
+pub const PREAMBLE: u8 = 0xFE;
+/// 68 | 仪表类型 | 地址 (7) | 控制码 | 数据域长度
+pub const HEADER_LEN: usize = 11;
+/// 读地址使用的通配地址
+pub const WILDCARD_ADDRESS: &str = "AAAAAAAAAAAAAA";
+
+/// 仪表类型 T
+pub const METER_TYPES: &[(u8, &str)] = &[
+    (0x10, "冷水水表"),
+    (0x11, "生活热水水表"),
+    (0x12, "直饮水水表"),
+    (0x13, "中水水表"),
+    (0x20, "热量表(计热量)"),
+    (0x21, "热量表(计冷量)"),
+    (0x30, "燃气表"),
+    (0x40, "电度表"),
+];
+
+/// 帧结构: 68 | T | A0..A6 | C | L | DATA | CS | 16。长度域只计数据域
+pub fn config() -> ProtocolConfig {
+    ProtocolConfig::new("cjt188-2018", 9, 1)
+        .with_head(&[START])
+        .with_tail(&[END])
+        .with_address(2, 7)
+        .with_control(9)
+        .with_length_field(10, 1, false)
+        .with_data_offset(HEADER_LEN)
+        .with_crc_len(1)
+}
+
+/// 校验码: 帧起始符到数据域末尾的字节和，模 256
+pub fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b))
+}
+
+/// 去掉帧前的唤醒字节
+pub fn strip_preamble(frame: &[u8]) -> &[u8] {
+    let skip = frame.iter().take_while(|b| **b == PREAMBLE).count();
+    &frame[skip..]
+}
+
+/// 表地址 (14 位，显示顺序) -> 帧中的 7 字节 (低字节在前)。通配地址的 A 按原样保留
+pub fn address_to_bytes(address: &str) -> ProtocolResult<[u8; 7]> {
+    let bytes = hex_util::hex_to_bytes(address)?;
+    let mut array: [u8; 7] = bytes.try_into().map_err(|_| {
+        ProtocolError::ValidationFailed(format!(
+            "CJ/T 188 address must be 14 digits, got '{}'",
+            address
+        ))
+    })?;
+    array.reverse();
+    Ok(array)
+}
+
+/// 帧头部各字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderField {
+    Start,
+    MeterType,
+    Address,
+    Control,
+    Length,
+}
+
+impl AutoDecodingParam for HeaderField {
+    fn byte_length(&self) -> usize {
+        match self {
+            HeaderField::Address => 7,
+            _ => 1,
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            HeaderField::Start => "帧起始符",
+            HeaderField::MeterType => "仪表类型",
+            HeaderField::Address => "表地址",
+            HeaderField::Control => "控制码",
+            HeaderField::Length => "数据域长度",
+        }
+        .into()
+    }
+
+    fn swap(&self) -> bool {
+        matches!(self, HeaderField::Address)
+    }
+
+    fn field_type(&self) -> FieldType {
+        match self {
+            HeaderField::Address | HeaderField::Control => FieldType::StringOrBCD,
+            HeaderField::Length => FieldType::UnsignedU8(1.0),
+            _ => FieldType::Empty,
+        }
+    }
+
+    fn compare_target(&self) -> Vec<u8> {
+        match self {
+            HeaderField::Start => vec![START],
+            _ => vec![],
+        }
+    }
+
+    fn enum_values(&self) -> Vec<(u8, String)> {
+        match self {
+            HeaderField::MeterType => METER_TYPES
+                .iter()
+                .map(|(v, t)| (*v, t.to_string()))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// 帧头部的解码顺序
+pub struct Header;
+
+impl AutoDecoding<HeaderField> for Header {
+    fn variants(&self) -> Vec<HeaderField> {
+        vec![
+            HeaderField::Start,
+            HeaderField::MeterType,
+            HeaderField::Address,
+            HeaderField::Control,
+            HeaderField::Length,
+        ]
+    }
+}
+
+/// 一帧的解码结果
+#[derive(Debug, Clone)]
+pub struct Cjt188Frame {
+    pub meter_type: u8,
+    /// 表地址 (显示顺序)
+    pub address: String,
+    pub control: u8,
+    /// 异常应答不带数据标识，此时为 None
+    pub cmd: Option<Cjt188Cmd>,
+    pub fields: Vec<ReportField>,
+}
+
+impl Cjt188Frame {
+    pub fn is_reply(&self) -> bool {
+        self.control & REPLY_FLAG != 0
+    }
+
+    pub fn is_abnormal(&self) -> bool {
+        self.control & ABNORMAL_FLAG != 0
+    }
+}
+
+/// 解码一帧 (可带唤醒前导)。校验帧结构与校验码后按数据标识对应的布局逐字段翻译
+pub fn decode(frame: &[u8]) -> ProtocolResult<Cjt188Frame> {
+    let frame = strip_preamble(frame);
+    config().check_frame(frame)?;
+    let data_end = frame.len() - 2;
+    let expected = checksum(&frame[..data_end]);
+    if frame[data_end] != expected {
+        return Err(HexDigestError::CrcMismatch {
+            expected: expected as u16,
+            actual: frame[data_end] as u16,
+        }
+        .into());
+    }
+
+    let control = frame[9];
+    let data = &frame[HEADER_LEN..data_end];
+    let cmd = if control & ABNORMAL_FLAG != 0 || data.len() < 2 {
+        None
+    } else {
+        let di = u16::from_le_bytes([data[0], data[1]]);
+        let code = format!("{:04X}", di);
+        Some(Cjt188Cmd::from_code(&code).ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!("unknown CJ/T 188 data identifier {}", code))
+        })?)
+    };
+
+    let mut reader = Reader::new(&frame[..data_end]);
+    Header.auto_process(&mut reader)?;
+    let layout = match cmd {
+        Some(cmd) if control & REPLY_FLAG != 0 => Some(Layout::Reply(cmd)),
+        Some(cmd) => Some(Layout::Request(cmd)),
+        None if control & ABNORMAL_FLAG != 0 => Some(Layout::Abnormal),
+        None => None,
+    };
+    if let Some(layout) = layout {
+        layout.auto_process(&mut reader)?;
+    }
+    // 厂商扩展的数据按原始数据上报
+    if reader.remaining_len() > 0 {
+        reader.read_and_translate_remaining(|b| DataField::Value.translate(b))?;
+    }
+    Ok(Cjt188Frame {
+        meter_type: frame[1],
+        address: hex_util::bytes_to_hex_swap(&frame[2..9])?,
+        control,
+        cmd,
+        fields: reader.to_report_fields()?,
+    })
+}
+
+/// 生成请求帧 (不含唤醒前导)。params 的 key 为 DataField::key，缺省的字段取默认值
+pub fn encode(
+    meter_type: u8,
+    cmd: Cjt188Cmd,
+    address: &str,
+    params: &HashMap<String, String>,
+) -> ProtocolResult<Writer> {
+    let mut writer = Writer::new();
+    writer
+        .write_bytes("帧起始符", &[START], "68")?
+        .write_bytes("仪表类型", &[meter_type], &format!("{:02X}", meter_type))?
+        .write_bytes("表地址", &address_to_bytes(address)?, address)?
+        .write_bytes("控制码", &[cmd.control(false)], &cmd.title())?
+        .write_placeholder("length", 1)?;
+
+    let mut length = 0;
+    for field in Layout::Request(cmd).variants() {
+        let (bytes, input) = match field {
+            DataField::Identifier => (cmd.di().to_le_bytes().to_vec(), cmd.code()),
+            _ => {
+                let input = params.get(field.key()).cloned().unwrap_or_default();
+                (field.to_bytes(&input)?, input)
+            }
+        };
+        length += bytes.len();
+        writer.write_bytes(field.name(), &bytes, &input)?;
+    }
+    let length = u8::try_from(length).map_err(|_| {
+        ProtocolError::ValidationFailed(format!("CJ/T 188 data too long: {} bytes", length))
+    })?;
+    writer.rewrite_placeholder("length", "数据域长度", &[length], &length.to_string())?;
+
+    let cs = checksum(writer.buffer()?);
+    writer
+        .write_bytes("校验码", &[cs], &format!("{:02X}", cs))?
+        .write_bytes("帧结束符", &[END], "16")?;
+    Ok(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "12345678901234";
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn value_of<'a>(fields: &'a [ReportField], name: &str) -> &'a str {
+        fields
+            .iter()
+            .find(|f| f.name == name)
+            .map(|f| f.value.as_str())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_encode_rejects_bad_params() {
+        // 黄金帧见 tests/golden.rs
+        assert!(encode(0x10, Cjt188Cmd::ValveControl, ADDRESS, &params(&[])).is_err());
+        assert!(encode(0x10, Cjt188Cmd::ReadMetering, "1234", &params(&[])).is_err());
+    }
+
+    #[test]
+    fn test_decode_frames() {
+        let frame = hex_util::hex_to_bytes(
+            "FEFE68103412907856341281161F9000674523012C000020012C0030120105242001007E16",
+        )
+        .unwrap();
+        let decoded = decode(&frame).unwrap();
+        assert_eq!(decoded.cmd, Some(Cjt188Cmd::ReadMetering));
+        assert_eq!(decoded.address, ADDRESS);
+        assert!(decoded.is_reply() && !decoded.is_abnormal());
+
+        let decoded =
+            decode(&hex_util::hex_to_bytes("681034129078563412C4030004002D16").unwrap()).unwrap();
+        assert!(decoded.is_abnormal());
+        assert_eq!(decoded.cmd, None);
+
+        // 请求帧也可以解码，便于抓包分析 (Cjt188Handler 只接受应答帧，黄金帧表覆盖不到)
+        let decoded = decode(
+            &hex_util::hex_to_bytes(
+                "683034129078563412041310A00150030000010020040000020000050015DE16",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(value_of(&decoded.fields, "价格2"), "4.2 元");
+        assert_eq!(value_of(&decoded.fields, "启用日期"), "15");
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_frames() {
+        let err = decode(&hex_util::hex_to_bytes("681034129078563412C4030004002E16").unwrap());
+        assert!(err.unwrap_err().to_string().contains("mismatch"));
+        assert!(
+            decode(&hex_util::hex_to_bytes("681034129078563412C4040004002D16").unwrap()).is_err()
+        );
+        // 未知数据标识
+        let mut raw = hex_util::hex_to_bytes("6810341290785634128103FFFF00").unwrap();
+        raw.push(checksum(&raw));
+        raw.push(END);
+        assert!(decode(&raw).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+use protocol_kernel::{
+    hex_util, AutoDecoding, AutoDecodingParam, Cmd, CmdTable, CommandDescription, JniRequest,
+    JniResponse, MsgTypeEnum, ParamSchema, ProtocolDescription, ProtocolError, ProtocolHandler,
+    ProtocolResult,
+};
+
+use crate::{
+    cmd::{Cjt188Cmd, Layout},
+    field::DataField,
+    frame::{self, Header, WILDCARD_ADDRESS},
+    PROTOCOL_ID,
+};
+
+/// 冷水水表
+pub const DEFAULT_METER_TYPE: u8 = 0x10;
+
+/// CJ/T 188-2018 的 ProtocolHandler。
+/// 上行解析表端应答，下行按 cmd_code (数据标识) 与 params 生成请求帧，device_no 为表地址
+#[derive(Debug, Clone)]
+pub struct Cjt188Handler {
+    meter_type: u8,
+    preamble: bool,
+}
+
+impl Default for Cjt188Handler {
+    fn default() -> Self {
+        Self {
+            meter_type: DEFAULT_METER_TYPE,
+            preamble: false,
+        }
+    }
+}
+
+impl Cjt188Handler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 下行帧中的仪表类型，如燃气表为 0x30
+    pub fn with_meter_type(mut self, meter_type: u8) -> Self {
+        self.meter_type = meter_type;
+        self
+    }
+
+    /// 下行帧前附加 2 个唤醒字节 FE
+    pub fn with_preamble(mut self, preamble: bool) -> Self {
+        self.preamble = preamble;
+        self
+    }
+}
+
+impl ProtocolHandler for Cjt188Handler {
+    fn decode_upstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        let bytes = hex_util::hex_to_bytes(request.hex())?;
+        let decoded = frame::decode(&bytes)?;
+        if !decoded.is_reply() {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "control code {:02X} is not a meter reply",
+                decoded.control
+            )));
+        }
+        let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+        rsp.set_device_no(&decoded.address);
+        match decoded.cmd {
+            Some(cmd) if !decoded.is_abnormal() => {
+                rsp.set_cmd_code(&cmd.code());
+                if let Some(msg_type) = cmd.msg_type() {
+                    rsp.set_msg_type(&msg_type);
+                }
+            }
+            _ => {
+                rsp.set_cmd_code(&format!("{:02X}", decoded.control));
+                rsp.set_msg_type(&MsgTypeEnum::ErrorRespond);
+            }
+        }
+        rsp.set_req_hex(request.hex());
+        rsp.set_req_jsons(decoded.fields);
+        Ok(rsp)
+    }
+
+    fn encode_downstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        let code = request.cmd_code().unwrap_or_default();
+        let cmd = Cjt188Cmd::from_code(code).ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!("unknown CJ/T 188 request '{}'", code))
+        })?;
+        let address = match request.device_no() {
+            Some(no) if !no.is_empty() => no.to_string(),
+            _ if cmd == Cjt188Cmd::ReadAddress => WILDCARD_ADDRESS.into(),
+            _ => {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "device_no (meter address) is required for {}",
+                    cmd.title()
+                )))
+            }
+        };
+        let writer = frame::encode(self.meter_type, cmd, &address, &request.params_clone())?;
+        let fields = writer.to_report_fields()?;
+        let mut hex = writer.full_hex()?;
+        if self.preamble {
+            hex.insert_str(0, "FEFE");
+        }
+
+        let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+        rsp.set_device_no(&address);
+        rsp.set_cmd_code(&cmd.code());
+        if let Some(msg_type) = cmd.msg_type() {
+            rsp.set_msg_type(&msg_type);
+        }
+        rsp.set_rsp_hex(&hex);
+        rsp.set_rsp_jsons(fields);
+        Ok(rsp)
+    }
+
+    fn describe(&self) -> Option<ProtocolDescription> {
+        let description = <Cjt188Cmd as CmdTable>::variants()
+            .iter()
+            .map(|cmd| {
+                // 数据标识由命令决定，不作为参数
+                let params = Layout::Request(*cmd)
+                    .variants()
+                    .iter()
+                    .filter(|f| **f != DataField::Identifier)
+                    .map(ParamSchema::of)
+                    .collect();
+                CommandDescription::of(cmd, params).with_fields(&Layout::Reply(*cmd))
+            })
+            .fold(ProtocolDescription::new(PROTOCOL_ID, "2018"), |d, c| {
+                d.with_command(c)
+            });
+        Some(description)
+    }
+
+    fn field_titles(&self) -> Vec<String> {
+        let mut titles: Vec<String> = Header.variants().iter().map(|f| f.title()).collect();
+        titles.extend(DataField::ALL.iter().map(|f| f.name().to_string()));
+        titles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> JniRequest {
+        JniRequest::from(json.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_upstream() {
+        // 解析结果见 tests/golden.rs
+        let handler = Cjt188Handler::new();
+        let rsp = handler
+            .decode_upstream(&request(
+                r#"{"hex":"68103412907856341281161F9000674523012C000020012C0030120105242001007E16"}"#,
+            ))
+            .unwrap();
+        assert_eq!(rsp.device_no(), Some("12345678901234"));
+        assert_eq!(rsp.msg_type(), Some("data_report"));
+
+        let rsp = handler
+            .decode_upstream(&request(r#"{"hex":"681034129078563412C4030004002D16"}"#))
+            .unwrap();
+        assert_eq!(rsp.msg_type(), Some("error_respond"));
+
+        assert!(handler
+            .decode_upstream(&request(r#"{"hex":"68103412907856341201031F90001516"}"#))
+            .is_err());
+    }
+
+    #[test]
+    fn test_encode_downstream() {
+        let handler = Cjt188Handler::new()
+            .with_meter_type(0x30)
+            .with_preamble(true);
+        let rsp = handler
+            .encode_downstream(&request(
+                r#"{"deviceNo":"12345678901234","cmdCode":"A017","params":{"valve":"55"}}"#,
+            ))
+            .unwrap();
+        assert!(rsp.rsp_hex().starts_with("FEFE6830341290785634120404"));
+        assert_eq!(rsp.msg_type(), Some("valve_operation"));
+        let valve = rsp
+            .rsp_jsons()
+            .iter()
+            .find(|f| f.name == "阀门控制")
+            .unwrap();
+        assert_eq!(valve.value, "55");
+
+        let rsp = handler
+            .encode_downstream(&request(r#"{"cmdCode":"810A"}"#))
+            .unwrap();
+        assert_eq!(rsp.device_no(), Some(WILDCARD_ADDRESS));
+
+        assert!(handler
+            .encode_downstream(&request(r#"{"cmdCode":"A017","params":{"valve":"55"}}"#))
+            .is_err());
+        assert!(handler
+            .encode_downstream(&request(
+                r#"{"deviceNo":"12345678901234","cmdCode":"FFFF"}"#
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_describe() {
+        let description = Cjt188Handler::new().describe().unwrap();
+        let prices = description
+            .commands
+            .iter()
+            .find(|c| c.code == "A010")
+            .unwrap();
+        let params: Vec<&str> = prices.params.iter().map(|p| p.code.as_str()).collect();
+        assert_eq!(
+            params,
+            [
+                "seq",
+                "price1",
+                "usage1",
+                "price2",
+                "usage2",
+                "price3",
+                "effective_day"
+            ]
+        );
+        assert_eq!(prices.params[1].input_field_type, "float");
+        let metering = description
+            .commands
+            .iter()
+            .find(|c| c.code == "901F")
+            .unwrap();
+        let fields: Vec<&str> = metering.fields.iter().map(|f| f.title.as_str()).collect();
+        assert_eq!(fields.len(), 8);
+        assert_eq!(fields[2], DataField::CurrentFlow.title());
+    }
+}
@@ -0,0 +1,35 @@
+//! CJ/T 188-2018《户用计量仪表数据传输技术条件》参考实现，覆盖水表/燃气表的
+//! 读计量数据、阀门控制与价格表下发。与 protocol-impl-dlt645 一样完全基于 protocol-kernel:
+//!
+//! - [`frame::config`]: 帧结构的 `ProtocolConfig`
+//! - [`Cjt188Cmd`]: 以数据标识为 code 的命令表 (`cmd_table!`)
+//! - [`Layout`]: 各命令请求、应答的数据域布局，实现 `AutoDecoding`
+//! - [`DataField`]: 数据域字段，BCD 与小端数值在 translate/to_bytes 中处理
+//! - [`Cjt188Handler`]: `ProtocolHandler` 实现
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use protocol_impl_cjt188::{frame, Cjt188Cmd};
+//!
+//! // 关阀
+//! let params = HashMap::from([("valve".to_string(), "99".to_string())]);
+//! let request = frame::encode(0x10, Cjt188Cmd::ValveControl, "12345678901234", &params).unwrap();
+//! assert_eq!(request.full_hex().unwrap(), "681034129078563412040417A00099BA16");
+//! ```
+pub mod cmd;
+pub mod field;
+pub mod frame;
+pub mod handler;
+
+pub use cmd::{Cjt188Cmd, Layout};
+pub use field::DataField;
+pub use frame::{decode, encode, Cjt188Frame};
+pub use handler::Cjt188Handler;
+
+/// 注册到 ProtocolRouter 时使用的协议 id
+pub const PROTOCOL_ID: &str = "cjt188-2018";
+
+/// 以默认配置 (冷水水表) 注册到全局协议表
+pub fn register() {
+    protocol_kernel::bridge::registry::register_protocol(PROTOCOL_ID, Cjt188Handler::new());
+}
@@ -0,0 +1,51 @@
+//! CJ/T 188-2018 黄金帧，经 Cjt188Handler 校验上行解析与下行编码
+use protocol_impl_cjt188::Cjt188Handler;
+use protocol_testkit::{golden_frames, GoldenCase};
+
+golden_frames!(
+    cjt188_golden,
+    Cjt188Handler::new(),
+    [
+        GoldenCase::decode(
+            "读计量数据应答",
+            "FEFE 68 10 34129078563412 81 16 1F90 00 67452301 2C 00002001 2C 00301201052420 0100 7E 16",
+        )
+        .cmd("901F")
+        .field("yi_biao_lei_xing", "冷水水表")
+        .field("shu_ju_biao_shi", "901F")
+        .field("dang_qian_lei_ji_liu_liang", "12345.67")
+        .field("jie_suan_ri_lei_ji_liu_liang", "12000")
+        .field("dang_qian_lei_ji_liu_liang_dan_wei", "m³")
+        .field("shi_shi_shi_jian", "2024-05-01 12:30:00")
+        .field("zhuang_tai", "关阀，电池正常"),
+        GoldenCase::decode("阀门控制应答", "68 10 34129078563412 84 05 17A0 00 0100 A3 16")
+            .cmd("A017")
+            .field("zhuang_tai", "关阀，电池正常"),
+        GoldenCase::decode("异常应答", "68 10 34129078563412 C4 03 00 0400 2D 16")
+            .cmd("C4")
+            .field("zhuang_tai", "开阀，电池欠压"),
+        GoldenCase::encode("读计量数据", "901F")
+            .device_no("12345678901234")
+            .hex("68 10 34129078563412 01 03 1F90 00 15 16"),
+        GoldenCase::encode("开阀", "A017")
+            .device_no("12345678901234")
+            .param("valve", "99")
+            .hex("68 10 34129078563412 04 04 17A0 00 99 BA 16"),
+        GoldenCase::encode("读地址", "810A").hex("68 10 AAAAAAAAAAAAAA 03 03 0A81 00 AF 16"),
+    ]
+);
+
+golden_frames!(
+    cjt188_gas_golden,
+    Cjt188Handler::new().with_meter_type(0x30),
+    [GoldenCase::encode("写价格表", "A010")
+        .device_no("12345678901234")
+        .param("seq", "1")
+        .param("price1", "3.50")
+        .param("usage1", "100")
+        .param("price2", "4.2")
+        .param("usage2", "200")
+        .param("price3", "5")
+        .param("effective_day", "15")
+        .hex("68 30 34129078563412 04 13 10A0 01 500300 000100 200400 000200 000500 15 DE 16")]
+);
@@ -0,0 +1,82 @@
+use protocol_kernel::{cmd_table, AutoDecoding, Cmd};
+
+use crate::field::DataField;
+
+cmd_table! {
+    /// 控制码表。code 为完整的控制码 (含传送方向、异常应答标志)，
+    /// 例如读数据为 11，正常应答 91，异常应答 D1
+    pub enum Dlt645Cmd {
+        BroadcastTime => ("08", "广播校时", Downstream, Write, DeviceParamSetting),
+        ReadData => ("11", "读数据", Downstream, Read, DataReport),
+        ReadDataReply => ("91", "读数据应答", Upstream, None, DataReport),
+        ReadDataError => ("D1", "读数据异常应答", Upstream, None, ErrorRespond),
+        ReadFollow => ("12", "读后续数据", Downstream, Read, DataReport),
+        ReadFollowReply => ("92", "读后续数据应答", Upstream, None, DataReport),
+        ReadFollowError => ("D2", "读后续数据异常应答", Upstream, None, ErrorRespond),
+        ReadAddress => ("13", "读通信地址", Downstream, Read, DeviceParamSetting),
+        ReadAddressReply => ("93", "读通信地址应答", Upstream, None, DeviceParamSetting),
+        WriteData => ("14", "写数据", Downstream, Write, DeviceParamSetting),
+        WriteDataReply => ("94", "写数据应答", Upstream, None, DeviceParamSetting),
+        WriteDataError => ("D4", "写数据异常应答", Upstream, None, ErrorRespond),
+        Control => ("1C", "跳合闸、报警、保电", Downstream, Write, ValveOperation),
+        ControlReply => ("9C", "跳合闸应答", Upstream, None, ValveOperation),
+        ControlError => ("DC", "跳合闸异常应答", Upstream, None, ErrorRespond),
+    }
+}
+
+impl Dlt645Cmd {
+    pub fn control(&self) -> u8 {
+        // 命令表中的 code 均为合法的 2 位 hex
+        u8::from_str_radix(&self.code(), 16).unwrap_or_default()
+    }
+
+    /// 应答的数据域在数据标识之后带数据项的值，长度由数据标识决定
+    pub fn carries_value(&self) -> bool {
+        matches!(self, Dlt645Cmd::ReadDataReply | Dlt645Cmd::ReadFollowReply)
+    }
+
+    /// 数据域中的固定字段，依次排列。上行为解码顺序，下行为参数的编码顺序
+    pub fn layout(&self) -> Vec<DataField> {
+        use DataField::*;
+        match self {
+            Dlt645Cmd::BroadcastTime => vec![Time],
+            Dlt645Cmd::ReadData | Dlt645Cmd::ReadDataReply | Dlt645Cmd::ReadFollowReply => {
+                vec![Identifier]
+            }
+            Dlt645Cmd::ReadFollow => vec![Identifier, Sequence],
+            Dlt645Cmd::ReadAddressReply => vec![Address],
+            Dlt645Cmd::WriteData => vec![Identifier, Password, Operator, Value],
+            Dlt645Cmd::Control => vec![Password, Operator, ControlType, Reserved, ValidUntil],
+            Dlt645Cmd::ReadDataError
+            | Dlt645Cmd::ReadFollowError
+            | Dlt645Cmd::WriteDataError
+            | Dlt645Cmd::ControlError => vec![ErrorWord],
+            Dlt645Cmd::ReadAddress | Dlt645Cmd::WriteDataReply | Dlt645Cmd::ControlReply => {
+                vec![]
+            }
+        }
+    }
+}
+
+impl AutoDecoding<DataField> for Dlt645Cmd {
+    fn variants(&self) -> Vec<DataField> {
+        self.layout()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_kernel::CmdTable;
+
+    #[test]
+    fn test_control_codes() {
+        for cmd in <Dlt645Cmd as CmdTable>::variants() {
+            let control = cmd.control();
+            // D7 为传送方向位，1 表示从站 (电表) 发出
+            assert_eq!(control & 0x80 != 0, cmd.direction().is_upstream_only());
+            assert_eq!(Dlt645Cmd::from_code(&format!("{:02x}", control)), Some(cmd));
+        }
+        assert_eq!(Dlt645Cmd::ReadDataError.control(), 0xD1);
+    }
+}
@@ -0,0 +1,206 @@
+use protocol_kernel::{AutoDecodingParam, AutoEncodingParam, FieldType};
+
+/// 数据域中的固定字段 (数据项的值除外，见 DataIdentifier)。
+/// 上行按 AutoDecodingParam 解码，下行作为参数按 AutoEncodingParam 编码，两个方向共用一套定义。
+/// 多字节字段在帧中均为低字节在前
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataField {
+    Identifier,
+    Address,
+    ErrorWord,
+    Password,
+    Operator,
+    ControlType,
+    Reserved,
+    ValidUntil,
+    Time,
+    Sequence,
+    Value,
+}
+
+impl DataField {
+    pub const ALL: [DataField; 11] = [
+        DataField::Identifier,
+        DataField::Address,
+        DataField::ErrorWord,
+        DataField::Password,
+        DataField::Operator,
+        DataField::ControlType,
+        DataField::Reserved,
+        DataField::ValidUntil,
+        DataField::Time,
+        DataField::Sequence,
+        DataField::Value,
+    ];
+
+    /// 下行参数的 key
+    pub fn key(&self) -> &'static str {
+        match self {
+            DataField::Identifier => "di",
+            DataField::Address => "address",
+            DataField::ErrorWord => "error",
+            DataField::Password => "password",
+            DataField::Operator => "operator",
+            DataField::ControlType => "control_type",
+            DataField::Reserved => "reserved",
+            DataField::ValidUntil => "valid_until",
+            DataField::Time => "time",
+            DataField::Sequence => "seq",
+            DataField::Value => "value",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataField::Identifier => "数据标识",
+            DataField::Address => "通信地址",
+            DataField::ErrorWord => "错误信息字",
+            DataField::Password => "密码",
+            DataField::Operator => "操作者代码",
+            DataField::ControlType => "控制命令",
+            DataField::Reserved => "保留",
+            DataField::ValidUntil => "命令有效截止时间",
+            DataField::Time => "校时时间",
+            DataField::Sequence => "帧序号",
+            DataField::Value => "数据",
+        }
+    }
+
+    /// 字节数，0 表示变长
+    pub fn byte_len(&self) -> usize {
+        match self {
+            DataField::Identifier | DataField::Password | DataField::Operator => 4,
+            DataField::Address | DataField::ValidUntil | DataField::Time => 6,
+            DataField::ErrorWord
+            | DataField::ControlType
+            | DataField::Reserved
+            | DataField::Sequence => 1,
+            DataField::Value => 0,
+        }
+    }
+
+    // 密码、操作者代码按传送顺序输入，其余多字节字段按显示顺序输入
+    fn little_endian(&self) -> bool {
+        matches!(
+            self,
+            DataField::Identifier
+                | DataField::Address
+                | DataField::ValidUntil
+                | DataField::Time
+                | DataField::Value
+        )
+    }
+
+    fn kind(&self) -> FieldType {
+        match self {
+            DataField::Sequence => FieldType::UnsignedU8(1.0),
+            _ => FieldType::StringOrBCD,
+        }
+    }
+}
+
+impl AutoDecodingParam for DataField {
+    fn byte_length(&self) -> usize {
+        self.byte_len()
+    }
+
+    fn title(&self) -> String {
+        self.name().into()
+    }
+
+    fn swap(&self) -> bool {
+        self.little_endian()
+    }
+
+    fn field_type(&self) -> FieldType {
+        match self {
+            DataField::ErrorWord | DataField::ControlType => FieldType::Empty,
+            _ => self.kind(),
+        }
+    }
+
+    fn enum_values(&self) -> Vec<(u8, String)> {
+        let values: &[(u8, &str)] = match self {
+            DataField::ErrorWord => &[
+                (0x01, "其他错误"),
+                (0x02, "无请求数据"),
+                (0x04, "密码错/未授权"),
+                (0x08, "通信速率不能更改"),
+                (0x10, "年时区数超"),
+                (0x20, "日时段数超"),
+                (0x40, "费率数超"),
+            ],
+            DataField::ControlType => &[
+                (0x1A, "跳闸"),
+                (0x1B, "合闸允许"),
+                (0x1C, "直接合闸"),
+                (0x2A, "报警"),
+                (0x2B, "报警解除"),
+                (0x3A, "保电"),
+                (0x3B, "保电解除"),
+            ],
+            _ => &[],
+        };
+        values.iter().map(|(v, t)| (*v, t.to_string())).collect()
+    }
+}
+
+impl AutoEncodingParam for DataField {
+    fn code(&self) -> String {
+        self.key().into()
+    }
+
+    fn title(&self) -> String {
+        self.name().into()
+    }
+
+    fn byte_length(&self) -> usize {
+        self.byte_len()
+    }
+
+    fn field_type(&self) -> FieldType {
+        self.kind()
+    }
+
+    fn input_field_type(&self) -> String {
+        match self {
+            DataField::Sequence => "int".into(),
+            _ => "string".into(),
+        }
+    }
+
+    fn default_value(&self) -> String {
+        match self {
+            DataField::Password | DataField::Operator => "00000000".into(),
+            DataField::Reserved => "00".into(),
+            DataField::Sequence => "0".into(),
+            _ => String::new(),
+        }
+    }
+
+    fn swap(&self) -> bool {
+        self.little_endian()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_codecs() {
+        // 截止时间 2024-05-01 12:30:00 在帧中为 ss mm hh DD MM YY
+        let bytes = DataField::ValidUntil.to_bytes("240501123000").unwrap();
+        assert_eq!(bytes, [0x00, 0x30, 0x12, 0x01, 0x05, 0x24]);
+        let field = AutoDecodingParam::translate(&DataField::ValidUntil, &bytes).unwrap();
+        assert_eq!(field.value(), "240501123000");
+
+        assert_eq!(DataField::Password.to_bytes("").unwrap(), [0; 4]);
+        assert!(DataField::Time.to_bytes("").is_err());
+
+        let field = AutoDecodingParam::translate(&DataField::ControlType, &[0x1A]).unwrap();
+        assert_eq!(field.value(), "跳闸");
+        let field = AutoDecodingParam::translate(&DataField::ErrorWord, &[0x04]).unwrap();
+        assert_eq!(field.value(), "密码错/未授权");
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use protocol_base::error::hex_digest_error::HexDigestError;
+use protocol_kernel::{
+    hex_util, AutoDecoding, AutoDecodingParam, AutoEncodingParam, Cmd, CmdTable, FieldType,
+    ProtocolConfig, ProtocolError, ProtocolResult, Reader, ReportField, Writer,
+};
+
+use crate::{cmd::Dlt645Cmd, field::DataField, identifier::DataIdentifier};
+
+pub const START: u8 = 0x68;
+pub const END: u8 = 0x16;
+/// 唤醒前导字节，主站发送时通常在帧前附加 4 个
+pub const PREAMBLE: u8 = 0xFE;
+/// 数据域发送时每字节加 33H，接收时减 33H
+pub const SCRAMBLE: u8 = 0x33;
+/// 68 | 地址 (6) | 68 | 控制码 | 数据域长度
+pub const HEADER_LEN: usize = 10;
+/// 广播校时使用的广播地址
+pub const BROADCAST_ADDRESS: &str = "999999999999";
+/// 读通信地址使用的通配地址
+pub const WILDCARD_ADDRESS: &str = "AAAAAAAAAAAA";
+
+/// 帧结构: 68 | A0..A5 | 68 | C | L | DATA | CS | 16。长度域只计数据域，控制码即命令码
+pub fn config() -> ProtocolConfig {
+    ProtocolConfig::new("dlt645-2007", 8, 1)
+        .with_head(&[START])
+        .with_tail(&[END])
+        .with_address(1, 6)
+        .with_control(8)
+        .with_length_field(9, 1, false)
+        .with_data_offset(HEADER_LEN)
+        .with_crc_len(1)
+}
+
+/// 校验码: 第一个帧起始符到数据域末尾的字节和，模 256
+pub fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b))
+}
+
+/// 去掉帧前的唤醒字节
+pub fn strip_preamble(frame: &[u8]) -> &[u8] {
+    let skip = frame.iter().take_while(|b| **b == PREAMBLE).count();
+    &frame[skip..]
+}
+
+pub fn scramble(data: &mut [u8]) {
+    data.iter_mut().for_each(|b| *b = b.wrapping_add(SCRAMBLE));
+}
+
+pub fn descramble(data: &mut [u8]) {
+    data.iter_mut().for_each(|b| *b = b.wrapping_sub(SCRAMBLE));
+}
+
+/// 表地址 (12 位，显示顺序) -> 帧中的 6 字节 (低字节在前)。通配地址的 A 按原样保留
+pub fn address_to_bytes(address: &str) -> ProtocolResult<[u8; 6]> {
+    let bytes = hex_util::hex_to_bytes(address)?;
+    let mut array: [u8; 6] = bytes.try_into().map_err(|_| {
+        ProtocolError::ValidationFailed(format!(
+            "DL/T 645 address must be 12 digits, got '{}'",
+            address
+        ))
+    })?;
+    array.reverse();
+    Ok(array)
+}
+
+/// 帧头部各字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderField {
+    Start,
+    Address,
+    Restart,
+    Control,
+    Length,
+}
+
+impl AutoDecodingParam for HeaderField {
+    fn byte_length(&self) -> usize {
+        match self {
+            HeaderField::Address => 6,
+            _ => 1,
+        }
+    }
+
+    fn title(&self) -> String {
+        match self {
+            HeaderField::Start | HeaderField::Restart => "帧起始符",
+            HeaderField::Address => "通信地址",
+            HeaderField::Control => "控制码",
+            HeaderField::Length => "数据域长度",
+        }
+        .into()
+    }
+
+    fn swap(&self) -> bool {
+        matches!(self, HeaderField::Address)
+    }
+
+    fn field_type(&self) -> FieldType {
+        match self {
+            HeaderField::Address => FieldType::StringOrBCD,
+            HeaderField::Length => FieldType::UnsignedU8(1.0),
+            _ => FieldType::Empty,
+        }
+    }
+
+    fn compare_target(&self) -> Vec<u8> {
+        match self {
+            HeaderField::Start | HeaderField::Restart => vec![START],
+            _ => vec![],
+        }
+    }
+
+    fn enum_values(&self) -> Vec<(u8, String)> {
+        match self {
+            HeaderField::Control => <Dlt645Cmd as CmdTable>::variants()
+                .iter()
+                .map(|cmd| (cmd.control(), cmd.title()))
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// 帧头部的解码顺序
+pub struct Header;
+
+impl AutoDecoding<HeaderField> for Header {
+    fn variants(&self) -> Vec<HeaderField> {
+        vec![
+            HeaderField::Start,
+            HeaderField::Address,
+            HeaderField::Restart,
+            HeaderField::Control,
+            HeaderField::Length,
+        ]
+    }
+}
+
+/// 一帧的解码结果
+#[derive(Debug, Clone)]
+pub struct Dlt645Frame {
+    pub cmd: Dlt645Cmd,
+    /// 表地址 (显示顺序)
+    pub address: String,
+    /// 帧头部与数据域的字段。数据域字段的原始字节为减 33H 之后的值
+    pub fields: Vec<ReportField>,
+}
+
+/// 解码一帧 (可带唤醒前导)。校验帧结构与校验码后，数据域减 33H 再逐字段翻译
+pub fn decode(frame: &[u8]) -> ProtocolResult<Dlt645Frame> {
+    let frame = strip_preamble(frame);
+    let config = config();
+    let code = config.cmd_code_of(frame)?;
+    let cmd = Dlt645Cmd::from_code(&code).ok_or_else(|| {
+        ProtocolError::ValidationFailed(format!("unknown DL/T 645 control code {}", code))
+    })?;
+    let data_end = frame.len() - 2;
+    let expected = checksum(&frame[..data_end]);
+    if frame[data_end] != expected {
+        return Err(HexDigestError::CrcMismatch {
+            expected: expected as u16,
+            actual: frame[data_end] as u16,
+        }
+        .into());
+    }
+
+    let mut plain = frame[..data_end].to_vec();
+    descramble(&mut plain[HEADER_LEN..]);
+    let mut reader = Reader::new(&plain);
+    Header.auto_process(&mut reader)?;
+    cmd.auto_process(&mut reader)?;
+    if cmd.carries_value() {
+        let di = u32::from_le_bytes([plain[10], plain[11], plain[12], plain[13]]);
+        if let Some(item) = DataIdentifier::find(di) {
+            if reader.remaining_len() >= item.length {
+                reader.read_and_translate_head(item.length, |b| item.translate(b))?;
+            }
+        }
+        if cmd == Dlt645Cmd::ReadFollowReply && reader.remaining_len() == 1 {
+            reader.read_and_translate_head(1, |b| DataField::Sequence.translate(b))?;
+        }
+    }
+    // 未收录的数据标识或多余的数据按原始数据上报
+    if reader.remaining_len() > 0 {
+        reader.read_and_translate_remaining(|b| DataField::Value.translate(b))?;
+    }
+    Ok(Dlt645Frame {
+        cmd,
+        address: hex_util::bytes_to_hex_swap(&frame[1..7])?,
+        fields: reader.to_report_fields()?,
+    })
+}
+
+/// 生成一帧 (不含唤醒前导)。params 的 key 为 DataField::key，缺省的字段取默认值
+pub fn encode(
+    cmd: Dlt645Cmd,
+    address: &str,
+    params: &HashMap<String, String>,
+) -> ProtocolResult<Writer> {
+    let mut writer = Writer::new();
+    writer
+        .write_bytes("帧起始符", &[START], "68")?
+        .write_bytes("通信地址", &address_to_bytes(address)?, address)?
+        .write_bytes("帧起始符", &[START], "68")?
+        .write_bytes("控制码", &[cmd.control()], &cmd.title())?
+        .write_placeholder("length", 1)?;
+
+    let mut length = 0;
+    for field in cmd.layout() {
+        let input = params.get(field.key()).map(String::as_str).unwrap_or("");
+        let mut bytes = match field {
+            DataField::Value => encode_value(params, input)?,
+            _ => field.to_bytes(input)?,
+        };
+        scramble(&mut bytes);
+        length += bytes.len();
+        writer.write_bytes(field.name(), &bytes, input)?;
+    }
+    let length = u8::try_from(length).map_err(|_| {
+        ProtocolError::ValidationFailed(format!("DL/T 645 data too long: {} bytes", length))
+    })?;
+    writer.rewrite_placeholder("length", "数据域长度", &[length], &length.to_string())?;
+
+    let cs = checksum(writer.buffer()?);
+    writer
+        .write_bytes("校验码", &[cs], &format!("{:02X}", cs))?
+        .write_bytes("帧结束符", &[END], "16")?;
+    Ok(writer)
+}
+
+// 写数据的值按数据标识表编码，未收录的数据标识按 hex 原样写入
+fn encode_value(params: &HashMap<String, String>, input: &str) -> ProtocolResult<Vec<u8>> {
+    let di = params
+        .get(DataField::Identifier.key())
+        .map(String::as_str)
+        .unwrap_or("");
+    match DataIdentifier::find_hex(di) {
+        Some(item) => item.encode(input),
+        None => DataField::Value.to_bytes(input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_rejects_bad_params() {
+        // 黄金帧见 tests/golden.rs
+        assert!(encode(Dlt645Cmd::ReadData, "1234", &params(&[("di", "00010000")])).is_err());
+        assert!(encode(Dlt645Cmd::BroadcastTime, BROADCAST_ADDRESS, &params(&[])).is_err());
+    }
+
+    #[test]
+    fn test_decode_read_reply() {
+        let frame =
+            hex_util::hex_to_bytes("FEFEFEFE68129078563412689108333334339A7856348816").unwrap();
+        let decoded = decode(&frame).unwrap();
+        assert_eq!(decoded.cmd, Dlt645Cmd::ReadDataReply);
+        assert_eq!(decoded.address, "123456789012");
+        assert_eq!(decoded.fields.len(), 7);
+        assert_eq!(
+            config().address_of(strip_preamble(&frame)).unwrap(),
+            Some("129078563412".into())
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_frames() {
+        // 校验码错误
+        let err = decode(&hex_util::hex_to_bytes("6812907856341268D101358E16").unwrap());
+        assert!(err.unwrap_err().to_string().contains("mismatch"));
+        // 长度域与帧长不符
+        assert!(decode(&hex_util::hex_to_bytes("6812907856341268D102358D16").unwrap()).is_err());
+        // 未知控制码
+        let mut raw = hex_util::hex_to_bytes("6812907856341268FF00").unwrap();
+        raw.push(checksum(&raw));
+        raw.push(END);
+        assert!(decode(&raw).is_err());
+    }
+}
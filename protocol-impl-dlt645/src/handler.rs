@@ -0,0 +1,200 @@
+use protocol_kernel::{
+    hex_util, AutoDecoding, AutoDecodingParam, Cmd, CmdTable, CommandDescription, JniRequest,
+    JniResponse, ParamSchema, ProtocolDescription, ProtocolError, ProtocolHandler, ProtocolResult,
+};
+
+use crate::{
+    cmd::Dlt645Cmd,
+    field::DataField,
+    frame::{self, Header, BROADCAST_ADDRESS, WILDCARD_ADDRESS},
+    identifier::DATA_IDENTIFIERS,
+    PROTOCOL_ID,
+};
+
+/// DL/T 645-2007 的 ProtocolHandler。
+/// 上行解析电表的应答帧，下行按 cmd_code (控制码) 与 params 生成请求帧，device_no 为表地址
+#[derive(Debug, Clone, Default)]
+pub struct Dlt645Handler {
+    preamble: bool,
+}
+
+impl Dlt645Handler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 下行帧前附加 4 个唤醒字节 FE
+    pub fn with_preamble(mut self, preamble: bool) -> Self {
+        self.preamble = preamble;
+        self
+    }
+
+    // 广播校时、读通信地址可以不指定表地址
+    fn address_for(cmd: Dlt645Cmd, device_no: Option<&str>) -> ProtocolResult<String> {
+        match (device_no, cmd) {
+            (Some(no), _) if !no.is_empty() => Ok(no.into()),
+            (_, Dlt645Cmd::BroadcastTime) => Ok(BROADCAST_ADDRESS.into()),
+            (_, Dlt645Cmd::ReadAddress) => Ok(WILDCARD_ADDRESS.into()),
+            _ => Err(ProtocolError::ValidationFailed(format!(
+                "device_no (meter address) is required for {}",
+                cmd.title()
+            ))),
+        }
+    }
+}
+
+impl ProtocolHandler for Dlt645Handler {
+    fn decode_upstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        let bytes = hex_util::hex_to_bytes(request.hex())?;
+        let decoded = frame::decode(&bytes)?;
+        if !decoded.cmd.direction().is_upstream() {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "{} is not a meter reply",
+                decoded.cmd.title()
+            )));
+        }
+        let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+        rsp.set_device_no(&decoded.address);
+        rsp.set_cmd_code(&decoded.cmd.code());
+        if let Some(msg_type) = decoded.cmd.msg_type() {
+            rsp.set_msg_type(&msg_type);
+        }
+        rsp.set_req_hex(request.hex());
+        rsp.set_req_jsons(decoded.fields);
+        Ok(rsp)
+    }
+
+    fn encode_downstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        let code = request.cmd_code().unwrap_or_default();
+        let cmd = Dlt645Cmd::from_code(code)
+            .filter(|cmd| cmd.direction().is_downstream())
+            .ok_or_else(|| {
+                ProtocolError::ValidationFailed(format!("unknown DL/T 645 request '{}'", code))
+            })?;
+        let address = Self::address_for(cmd, request.device_no())?;
+        let writer = frame::encode(cmd, &address, &request.params_clone())?;
+        let fields = writer.to_report_fields()?;
+        let mut hex = writer.full_hex()?;
+        if self.preamble {
+            hex.insert_str(0, "FEFEFEFE");
+        }
+
+        let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+        rsp.set_device_no(&address);
+        rsp.set_cmd_code(&cmd.code());
+        if let Some(msg_type) = cmd.msg_type() {
+            rsp.set_msg_type(&msg_type);
+        }
+        rsp.set_rsp_hex(&hex);
+        rsp.set_rsp_jsons(fields);
+        Ok(rsp)
+    }
+
+    fn describe(&self) -> Option<ProtocolDescription> {
+        let description = <Dlt645Cmd as CmdTable>::variants()
+            .iter()
+            .map(|cmd| {
+                // 上下行共用 layout，按方向作为参数或解码字段
+                if cmd.direction().is_downstream() {
+                    let params = cmd.layout().iter().map(ParamSchema::of).collect();
+                    CommandDescription::of(cmd, params)
+                } else {
+                    CommandDescription::of(cmd, vec![]).with_fields(cmd)
+                }
+            })
+            .fold(ProtocolDescription::new(PROTOCOL_ID, "2007"), |d, c| {
+                d.with_command(c)
+            });
+        Some(description)
+    }
+
+    fn field_titles(&self) -> Vec<String> {
+        let mut titles: Vec<String> = Header.variants().iter().map(|f| f.title()).collect();
+        titles.extend(DataField::ALL.iter().map(|f| f.name().to_string()));
+        titles.extend(DATA_IDENTIFIERS.iter().map(|item| item.title.to_string()));
+        titles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> JniRequest {
+        JniRequest::from(json.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_upstream() {
+        // 解析结果见 tests/golden.rs，这里只校验黄金帧表覆盖不到的部分
+        let rsp = Dlt645Handler::new()
+            .decode_upstream(&request(
+                r#"{"hex":"68129078563412689108333334339A7856348816"}"#,
+            ))
+            .unwrap();
+        assert_eq!(rsp.device_no(), Some("123456789012"));
+        assert_eq!(rsp.msg_type(), Some("data_report"));
+        let energy = rsp
+            .req_jsons()
+            .iter()
+            .find(|f| f.name == "正向有功总电能")
+            .unwrap();
+        assert_eq!(energy.unit.as_deref(), Some("kWh"));
+
+        // 主站的请求帧不是上行
+        assert!(Dlt645Handler::new()
+            .decode_upstream(&request(r#"{"hex":"68129078563412681104333334336816"}"#))
+            .is_err());
+    }
+
+    #[test]
+    fn test_encode_downstream() {
+        let handler = Dlt645Handler::new();
+        let rsp = handler
+            .encode_downstream(&request(
+                r#"{"deviceNo":"123456789012","cmdCode":"11","params":{"di":"00010000"}}"#,
+            ))
+            .unwrap();
+        assert_eq!(rsp.rsp_jsons()[4].value, "4");
+
+        assert!(handler
+            .encode_downstream(&request(r#"{"cmdCode":"11","params":{"di":"00010000"}}"#))
+            .is_err());
+        assert!(handler
+            .encode_downstream(&request(r#"{"deviceNo":"123456789012","cmdCode":"91"}"#))
+            .is_err());
+    }
+
+    #[test]
+    fn test_describe() {
+        let description = Dlt645Handler::new().describe().unwrap();
+        let control = description
+            .commands
+            .iter()
+            .find(|c| c.code == "1C")
+            .unwrap();
+        let params: Vec<&str> = control.params.iter().map(|p| p.code.as_str()).collect();
+        assert_eq!(
+            params,
+            [
+                "password",
+                "operator",
+                "control_type",
+                "reserved",
+                "valid_until"
+            ]
+        );
+        let error = description
+            .commands
+            .iter()
+            .find(|c| c.code == "D1")
+            .unwrap();
+        assert!(error.params.is_empty());
+        assert_eq!(error.fields[0].title, "错误信息字");
+        assert!(control.fields.is_empty());
+        assert!(Dlt645Handler::new()
+            .field_titles()
+            .iter()
+            .any(|t| t == "A相电压"));
+    }
+}
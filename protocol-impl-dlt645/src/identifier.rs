@@ -0,0 +1,233 @@
+use protocol_kernel::{
+    bcd_util::{self, BcdSignMode},
+    hex_util, math_util, AutoDecodingParam, DecodeContext, FieldType, ProtocolError,
+    ProtocolResult, Rawfield, ValueType,
+};
+
+use DataFormat::{Date, Digits, Signed, Time, Unsigned};
+
+/// 数据项的值格式。数值均为 BCD，低字节在前
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    /// 无符号数，参数为小数位数
+    Unsigned(u32),
+    /// 最高位为符号位，参数为小数位数
+    Signed(u32),
+    /// YYMMDDWW
+    Date,
+    /// hhmmss
+    Time,
+    /// 按显示顺序原样输出的数字串，如表号
+    Digits,
+}
+
+/// 数据标识表中的一项。di 按 DI3 DI2 DI1 DI0 书写，帧中 DI0 在前
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataIdentifier {
+    pub di: u32,
+    pub title: &'static str,
+    pub length: usize,
+    pub format: DataFormat,
+    pub unit: &'static str,
+}
+
+const fn item(
+    di: u32,
+    title: &'static str,
+    length: usize,
+    format: DataFormat,
+    unit: &'static str,
+) -> DataIdentifier {
+    DataIdentifier {
+        di,
+        title,
+        length,
+        format,
+        unit,
+    }
+}
+
+/// 常用数据标识。未列出的数据标识按原始数据上报
+pub const DATA_IDENTIFIERS: &[DataIdentifier] = &[
+    item(0x0000_0000, "组合有功总电能", 4, Unsigned(2), "kWh"),
+    item(0x0001_0000, "正向有功总电能", 4, Unsigned(2), "kWh"),
+    item(0x0001_0100, "正向有功费率1电能", 4, Unsigned(2), "kWh"),
+    item(0x0001_0200, "正向有功费率2电能", 4, Unsigned(2), "kWh"),
+    item(0x0001_0300, "正向有功费率3电能", 4, Unsigned(2), "kWh"),
+    item(0x0001_0400, "正向有功费率4电能", 4, Unsigned(2), "kWh"),
+    item(0x0002_0000, "反向有功总电能", 4, Unsigned(2), "kWh"),
+    item(0x0003_0000, "组合无功1总电能", 4, Unsigned(2), "kvarh"),
+    item(0x0004_0000, "组合无功2总电能", 4, Unsigned(2), "kvarh"),
+    item(0x0201_0100, "A相电压", 2, Unsigned(1), "V"),
+    item(0x0201_0200, "B相电压", 2, Unsigned(1), "V"),
+    item(0x0201_0300, "C相电压", 2, Unsigned(1), "V"),
+    item(0x0202_0100, "A相电流", 3, Signed(3), "A"),
+    item(0x0202_0200, "B相电流", 3, Signed(3), "A"),
+    item(0x0202_0300, "C相电流", 3, Signed(3), "A"),
+    item(0x0203_0000, "总有功功率", 3, Signed(4), "kW"),
+    item(0x0204_0000, "总无功功率", 3, Signed(4), "kvar"),
+    item(0x0206_0000, "总功率因数", 2, Signed(3), ""),
+    item(0x0280_0002, "电网频率", 2, Unsigned(2), "Hz"),
+    item(0x0280_0007, "表内温度", 2, Signed(1), "℃"),
+    item(0x0400_0101, "日期及星期", 4, Date, ""),
+    item(0x0400_0102, "时间", 3, Time, ""),
+    item(0x0400_0401, "通信地址", 6, Digits, ""),
+    item(0x0400_0402, "表号", 6, Digits, ""),
+];
+
+const WEEKDAYS: [&str; 7] = ["日", "一", "二", "三", "四", "五", "六"];
+
+impl DataIdentifier {
+    pub fn find(di: u32) -> Option<&'static DataIdentifier> {
+        DATA_IDENTIFIERS.iter().find(|item| item.di == di)
+    }
+
+    /// 按 hex 查找，如 "00010000"
+    pub fn find_hex(di: &str) -> Option<&'static DataIdentifier> {
+        u32::from_str_radix(di, 16).ok().and_then(Self::find)
+    }
+
+    pub fn di_hex(&self) -> String {
+        format!("{:08X}", self.di)
+    }
+
+    fn decimals(&self) -> Option<u32> {
+        match self.format {
+            DataFormat::Unsigned(decimals) | DataFormat::Signed(decimals) => Some(decimals),
+            _ => None,
+        }
+    }
+
+    /// 下行 (写数据) 时把输入编码为帧中的字节 (未加 33H)
+    pub fn encode(&self, input: &str) -> ProtocolResult<Vec<u8>> {
+        let Some(decimals) = self.decimals() else {
+            let bytes = hex_util::hex_to_bytes(input)?;
+            if bytes.len() != self.length || !bcd_util::is_bcd_bytes(&bytes) {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "{} expects {} BCD digits, got '{}'",
+                    self.title,
+                    self.length * 2,
+                    input
+                )));
+            }
+            return hex_util::swap_bytes(&bytes);
+        };
+        let scaled = (math_util::parse_numeric(input)? * 10f64.powi(decimals as i32)).round();
+        match self.format {
+            DataFormat::Signed(_) => {
+                bcd_util::i64_to_signed_bcd_swap(scaled as i64, self.length, BcdSignMode::HighBit)
+            }
+            _ if scaled < 0.0 => Err(ProtocolError::ValidationFailed(format!(
+                "{} cannot be negative: {}",
+                self.title, input
+            ))),
+            _ => bcd_util::u64_to_bcd_swap(scaled as u64, self.length),
+        }
+    }
+}
+
+/// 数值是带小数位的 BCD，默认的翻译模式不支持，因此重写 translate
+impl AutoDecodingParam for DataIdentifier {
+    fn byte_length(&self) -> usize {
+        self.length
+    }
+
+    fn title(&self) -> String {
+        self.title.into()
+    }
+
+    fn swap(&self) -> bool {
+        true
+    }
+
+    fn field_type(&self) -> FieldType {
+        FieldType::StringOrBCD
+    }
+
+    fn translate(&self, bytes: &[u8]) -> ProtocolResult<Rawfield> {
+        let digits = hex_util::bytes_to_hex_swap(bytes)?;
+        let rf = match self.format {
+            DataFormat::Unsigned(decimals) | DataFormat::Signed(decimals) => {
+                let raw = match self.format {
+                    DataFormat::Signed(_) => {
+                        bcd_util::signed_bcd_to_i64_swap(bytes, BcdSignMode::HighBit)?
+                    }
+                    _ => bcd_util::bcd_to_u64_swap(bytes)? as i64,
+                };
+                let scale = 1.0 / 10f64.powi(decimals as i32);
+                let value = (raw as f64 * scale).to_string();
+                if self.unit.is_empty() {
+                    Rawfield::new(bytes, self.title, value)
+                } else {
+                    Rawfield::new(bytes, self.title, format!("{} {}", value, self.unit))
+                        .with_unit(self.unit)
+                }
+                .with_value_type(ValueType::Float)
+                .with_scale(scale)
+            }
+            DataFormat::Date => {
+                let week = u8::from_str_radix(&digits[6..8], 16).unwrap_or_default() as usize;
+                let value = format!(
+                    "20{}-{}-{} 星期{}",
+                    &digits[0..2],
+                    &digits[2..4],
+                    &digits[4..6],
+                    WEEKDAYS.get(week).copied().unwrap_or("?")
+                );
+                Rawfield::new(bytes, self.title, value).with_value_type(ValueType::String)
+            }
+            DataFormat::Time => {
+                let value = format!("{}:{}:{}", &digits[0..2], &digits[2..4], &digits[4..6]);
+                Rawfield::new(bytes, self.title, value).with_value_type(ValueType::String)
+            }
+            DataFormat::Digits => {
+                Rawfield::new(bytes, self.title, digits).with_value_type(ValueType::String)
+            }
+        };
+        Ok(rf)
+    }
+
+    // auto_process_with 同样按上面的规则解码
+    fn translate_with(&self, bytes: &[u8], _ctx: &mut DecodeContext) -> ProtocolResult<Rawfield> {
+        self.translate(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        let energy = DataIdentifier::find_hex("00010000").unwrap();
+        let rf = energy.translate(&[0x67, 0x45, 0x23, 0x01]).unwrap();
+        assert_eq!(rf.value(), "12345.67 kWh");
+        assert_eq!(rf.unit(), Some("kWh"));
+
+        // 电流最高位为符号位
+        let current = DataIdentifier::find(0x0202_0100).unwrap();
+        let rf = current.translate(&[0x00, 0x25, 0x81]).unwrap();
+        assert_eq!(rf.value(), "-12.5 A");
+
+        let date = DataIdentifier::find(0x0400_0101).unwrap();
+        let rf = date.translate(&[0x03, 0x01, 0x05, 0x24]).unwrap();
+        assert_eq!(rf.value(), "2024-05-01 星期三");
+    }
+
+    #[test]
+    fn test_encode() {
+        let energy = DataIdentifier::find(0x0001_0000).unwrap();
+        assert_eq!(energy.encode("12345.67").unwrap(), [0x67, 0x45, 0x23, 0x01]);
+        assert!(energy.encode("-1").is_err());
+
+        let current = DataIdentifier::find(0x0202_0100).unwrap();
+        assert_eq!(current.encode("-12.5").unwrap(), [0x00, 0x25, 0x81]);
+
+        let meter_no = DataIdentifier::find(0x0400_0402).unwrap();
+        assert_eq!(
+            meter_no.encode("000000001234").unwrap(),
+            [0x34, 0x12, 0x00, 0x00, 0x00, 0x00]
+        );
+        assert!(meter_no.encode("1234").is_err());
+    }
+}
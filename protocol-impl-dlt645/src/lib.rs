@@ -0,0 +1,47 @@
+//! DL/T 645-2007《多功能电能表通信协议》参考实现，完全基于 protocol-kernel 搭建，
+//! 同时也是接入新协议的范例:
+//!
+//! - [`frame::config`]: 用 `ProtocolConfig` 描述帧结构，命令识别、分帧、按表地址并行解码都可直接复用
+//! - [`Dlt645Cmd`]: 用 `cmd_table!` 生成的控制码表，并以 `AutoDecoding` 给出各命令的数据域布局
+//! - [`DataField`]: 数据域固定字段，上行实现 `AutoDecodingParam`，下行实现 `AutoEncodingParam`
+//! - [`DataIdentifier`]: 数据标识表，数值为带小数位的 BCD，重写了 translate
+//! - [`Dlt645Handler`]: `ProtocolHandler` 实现，注册后即可经由 JNI/FFI/WASM 调用
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use protocol_impl_dlt645::{frame, Dlt645Cmd};
+//!
+//! // 读正向有功总电能
+//! let params = HashMap::from([("di".to_string(), "00010000".to_string())]);
+//! let request = frame::encode(Dlt645Cmd::ReadData, "123456789012", &params).unwrap();
+//! assert_eq!(request.full_hex().unwrap(), "68129078563412681104333334336816");
+//!
+//! let reply = protocol_kernel::hex_util::hex_to_bytes(
+//!     "68129078563412689108333334339A7856348816",
+//! )
+//! .unwrap();
+//! let decoded = frame::decode(&reply).unwrap();
+//! assert!(decoded
+//!     .fields
+//!     .iter()
+//!     .any(|f| f.name == "正向有功总电能" && f.value == "12345.67 kWh"));
+//! ```
+pub mod cmd;
+pub mod field;
+pub mod frame;
+pub mod handler;
+pub mod identifier;
+
+pub use cmd::Dlt645Cmd;
+pub use field::DataField;
+pub use frame::{decode, encode, Dlt645Frame};
+pub use handler::Dlt645Handler;
+pub use identifier::{DataFormat, DataIdentifier, DATA_IDENTIFIERS};
+
+/// 注册到 ProtocolRouter 时使用的协议 id
+pub const PROTOCOL_ID: &str = "dlt645-2007";
+
+/// 以默认配置注册到全局协议表
+pub fn register() {
+    protocol_kernel::bridge::registry::register_protocol(PROTOCOL_ID, Dlt645Handler::new());
+}
@@ -0,0 +1,64 @@
+//! DL/T 645-2007 黄金帧，经 Dlt645Handler 校验上行解析与下行编码
+use protocol_impl_dlt645::Dlt645Handler;
+use protocol_testkit::{golden_frames, GoldenCase};
+
+golden_frames!(
+    dlt645_golden,
+    Dlt645Handler::new(),
+    [
+        GoldenCase::decode(
+            "读正向有功总电能应答",
+            "68 129078563412 68 91 08 33333433 9A785634 88 16"
+        )
+        .cmd("91")
+        .field("tong_xin_di_zhi", "123456789012")
+        .field("kong_zhi_ma", "读数据应答")
+        .field("shu_ju_biao_shi", "00010000")
+        .field("zheng_xiang_you_gong_zong_dian_neng", "12345.67 kWh"),
+        GoldenCase::decode(
+            "带唤醒符的应答",
+            "FEFEFEFE 68 129078563412 68 91 08 33333433 9A785634 88 16",
+        )
+        .cmd("91")
+        .field("zheng_xiang_you_gong_zong_dian_neng", "12345.67 kWh"),
+        GoldenCase::decode("读数据异常应答", "68 129078563412 68 D1 01 35 8D 16")
+            .cmd("D1")
+            .field("cuo_wu_xin_xi_zi", "无请求数据"),
+        // 未收录的数据标识按原始数据上报
+        GoldenCase::decode(
+            "未收录的数据标识",
+            "68 129078563412 68 91 06 3333333A 3435 59 16"
+        )
+        .field("shu_ju_biao_shi", "07000000")
+        .field("shu_ju", "0201"),
+        GoldenCase::decode(
+            "读通信地址应答",
+            "68 129078563412 68 93 06 45C3AB896745 07 16"
+        )
+        .cmd("93")
+        .field("tong_xin_di_zhi", "123456789012"),
+        GoldenCase::encode("读正向有功总电能", "11")
+            .device_no("123456789012")
+            .param("di", "00010000")
+            .hex("68 129078563412 68 11 04 33333433 68 16"),
+        GoldenCase::encode("写数据", "14")
+            .device_no("123456789012")
+            .param("di", "04000402")
+            .param("value", "000000001234")
+            // 密码与操作者代码取默认值
+            .hex("68 129078563412 68 14 12 35373337 33333333 33333333 674533333333 92 16"),
+        GoldenCase::encode("读通信地址", "13").hex("68 AAAAAAAAAAAA 68 13 00 DF 16"),
+        GoldenCase::encode("广播校时", "08")
+            .param("time", "240501123000")
+            .hex("68 999999999999 68 08 06 336345343857 12 16"),
+    ]
+);
+
+golden_frames!(
+    dlt645_preamble_golden,
+    Dlt645Handler::new().with_preamble(true),
+    [GoldenCase::encode("读正向有功总电能", "11")
+        .device_no("123456789012")
+        .param("di", "00010000")
+        .hex("FEFEFEFE 68 129078563412 68 11 04 33333433 68 16")]
+);
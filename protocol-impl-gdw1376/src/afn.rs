@@ -0,0 +1,75 @@
+use protocol_kernel::{cmd_table, Cmd};
+
+cmd_table! {
+    /// 应用层功能码 AFN。上下行共用，方向由控制码 DIR 位区分
+    pub enum Afn {
+        Confirm => ("00", "确认/否认", Both, None, None),
+        Reset => ("01", "复位", Downstream, Write, DeviceParamSetting),
+        LinkTest => ("02", "链路接口检测", Upstream, None, HeartBeat),
+        Relay => ("03", "中继站命令", Downstream, Write, None),
+        SetParam => ("04", "设置参数", Downstream, Write, DeviceParamSetting),
+        Control => ("05", "控制命令", Downstream, Write, ValveOperation),
+        Authentication => ("06", "身份认证及密钥协商", Both, None, None),
+        CascadeReport => ("08", "请求被级联终端主动上报", Downstream, Read, DataReport),
+        QueryConfig => ("09", "请求终端配置", Both, Read, DeviceParamSetting),
+        QueryParam => ("0A", "查询参数", Both, Read, DeviceParamSetting),
+        TaskData => ("0B", "请求任务数据", Both, Read, DataReport),
+        Class1Data => ("0C", "请求1类数据", Both, Read, DataReport),
+        Class2Data => ("0D", "请求2类数据", Both, Read, DataReport),
+        Class3Data => ("0E", "请求3类数据", Both, Read, DataReport),
+        FileTransfer => ("0F", "文件传输", Downstream, Write, None),
+        Forward => ("10", "数据转发", Both, Write, DataReport),
+    }
+}
+
+impl Afn {
+    pub fn value(&self) -> u8 {
+        // 命令表中的 code 均为合法的 2 位 hex
+        u8::from_str_radix(&self.code(), 16).unwrap_or_default()
+    }
+
+    /// 下行帧的附加信息域带 16 字节消息认证码 PW
+    pub fn carries_password(&self) -> bool {
+        matches!(
+            self,
+            Afn::Reset
+                | Afn::Relay
+                | Afn::SetParam
+                | Afn::Control
+                | Afn::Authentication
+                | Afn::FileTransfer
+                | Afn::Forward
+        )
+    }
+
+    /// 下行帧是否要求终端确认 (SEQ 的 CON 位)
+    pub fn needs_confirm(&self) -> bool {
+        matches!(self, Afn::Reset | Afn::SetParam | Afn::Control)
+    }
+
+    /// 主站下行使用的控制码: 确认帧为从动站的链路状态，复位为复位命令，
+    /// 请求 2 类数据为请求 2 级数据，其余为请求 1 级数据
+    pub fn request_control(&self) -> u8 {
+        match self {
+            Afn::Confirm => 0x0B,
+            Afn::Reset => 0x41,
+            Afn::Class2Data => 0x4B,
+            _ => 0x4A,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_kernel::CmdTable;
+
+    #[test]
+    fn test_afn() {
+        assert_eq!(Afn::from_code("0a"), Some(Afn::QueryParam));
+        assert_eq!(Afn::Forward.value(), 0x10);
+        assert!(Afn::Forward.carries_password());
+        assert!(!Afn::Class1Data.carries_password());
+        assert_eq!(Afn::Confirm.request_control(), 0x0B);
+    }
+}
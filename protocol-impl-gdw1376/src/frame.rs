@@ -0,0 +1,544 @@
+use protocol_base::error::hex_digest_error::HexDigestError;
+use protocol_kernel::{
+    bcd_util, hex_util, Cmd, CmdTable, ProtocolConfig, ProtocolError, ProtocolResult, Rawfield,
+    Reader, ReportField, ValueType, Writer,
+};
+
+use crate::{
+    afn::Afn,
+    link::{self, Address, Control, Seq},
+};
+
+pub const START: u8 = 0x68;
+/// 固定长度帧的起始字符
+pub const FIXED_START: u8 = 0x10;
+pub const END: u8 = 0x16;
+/// 68 | L | L | 68
+pub const HEADER_LEN: usize = 6;
+/// 10 | C | A (5) | CS | 16
+pub const FIXED_LEN: usize = 9;
+/// 长度域 D0~D1 的规约标识
+pub const PROTOCOL_1376: u16 = 0b10;
+pub const PROTOCOL_2005: u16 = 0b01;
+pub const PASSWORD_LEN: usize = 16;
+const EVENT_COUNTER_LEN: usize = 2;
+const TIME_TAG_LEN: usize = 6;
+
+/// 透明转发默认的通信控制字: 2400bps，偶校验，8 位数据位，1 位停止位 (DL/T 645 的缺省设置)
+pub const DEFAULT_PORT_CONTROL: u8 = 0x6B;
+/// 透明转发等待报文超时: D7=1 表示单位为秒，5 秒
+pub const DEFAULT_FRAME_TIMEOUT: u8 = 0x85;
+/// 透明转发等待字节超时，单位 10ms
+pub const DEFAULT_BYTE_TIMEOUT: u8 = 0x0A;
+
+/// 可变长度帧: 68 | L | L | 68 | C | A (5) | AFN | SEQ | 数据单元 | 附加信息 | CS | 16。
+/// 长度域带规约标识，不能用 with_length_field 描述，帧长在 decode 中另行校验
+pub fn config() -> ProtocolConfig {
+    ProtocolConfig::new("gdw1376.1", 12, 1)
+        .with_head(&[START])
+        .with_tail(&[END])
+        .with_address(7, Address::LEN)
+        .with_control(6)
+        .with_data_offset(14)
+        .with_crc_len(1)
+}
+
+/// 固定长度帧，只有控制域与地址域，用于链路层的复位与测试
+pub fn fixed_config() -> ProtocolConfig {
+    ProtocolConfig::new("gdw1376.1-fixed", 1, 1)
+        .with_head(&[FIXED_START])
+        .with_tail(&[END])
+        .with_address(2, Address::LEN)
+        .with_control(1)
+        .with_fixed_len(FIXED_LEN)
+        .with_data_offset(7)
+        .with_crc_len(1)
+}
+
+/// 校验和: 用户数据区 (控制域到附加信息域) 的字节和，模 256
+pub fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |acc, b| acc.wrapping_add(*b))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameFormat {
+    Fixed,
+    Variable,
+}
+
+/// 一个数据单元: 数据单元标识 (pn/Fn) 与数据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUnit {
+    pub pn: u16,
+    pub fn_no: u16,
+    pub data: Vec<u8>,
+}
+
+impl DataUnit {
+    pub fn new(pn: u16, fn_no: u16, data: Vec<u8>) -> Self {
+        Self { pn, fn_no, data }
+    }
+
+    /// 透明转发 (AFN=10 F1) 的下行数据单元
+    pub fn forward(port: u8, content: &[u8]) -> ProtocolResult<Self> {
+        let len = u16::try_from(content.len()).map_err(|_| {
+            ProtocolError::ValidationFailed(format!(
+                "forwarded content too long: {} bytes",
+                content.len()
+            ))
+        })?;
+        let mut data = vec![
+            port,
+            DEFAULT_PORT_CONTROL,
+            DEFAULT_FRAME_TIMEOUT,
+            DEFAULT_BYTE_TIMEOUT,
+        ];
+        data.extend_from_slice(&len.to_le_bytes());
+        data.extend_from_slice(content);
+        Ok(Self::new(0, 1, data))
+    }
+}
+
+/// 透明转发的内层帧，即集中器下挂表计的原始报文
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forwarded {
+    pub port: u8,
+    pub content: Vec<u8>,
+}
+
+/// 一帧的解码结果
+#[derive(Debug, Clone)]
+pub struct Gdw1376Frame {
+    pub format: FrameFormat,
+    pub control: Control,
+    pub address: Address,
+    /// 固定长度帧没有 AFN 与 SEQ
+    pub afn: Option<Afn>,
+    pub seq: Option<Seq>,
+    /// 第一个数据单元标识中的信息点与信息类
+    pub pns: Vec<u16>,
+    pub fns: Vec<u16>,
+    /// 数据单元标识之后、附加信息域之前的数据
+    pub data: Vec<u8>,
+    pub forwarded: Option<Forwarded>,
+    pub fields: Vec<ReportField>,
+}
+
+impl Gdw1376Frame {
+    /// 链路接口检测 F1 (登录)
+    pub fn is_login(&self) -> bool {
+        self.afn == Some(Afn::LinkTest) && self.fns == [1]
+    }
+
+    /// 链路接口检测 F3 (心跳)
+    pub fn is_heartbeat(&self) -> bool {
+        self.afn == Some(Afn::LinkTest) && self.fns == [3]
+    }
+}
+
+fn text(bytes: &[u8], title: &str, value: String) -> ProtocolResult<Rawfield> {
+    Ok(Rawfield::new(bytes, title, value).with_value_type(ValueType::String))
+}
+
+fn number(bytes: &[u8], title: &str, value: usize) -> ProtocolResult<Rawfield> {
+    Ok(Rawfield::new(bytes, title, value.to_string()).with_value_type(ValueType::Int))
+}
+
+fn hex(bytes: &[u8], title: &str) -> ProtocolResult<Rawfield> {
+    text(bytes, title, hex_util::bytes_to_hex(bytes)?)
+}
+
+fn join(prefix: &str, values: &[u16]) -> String {
+    values
+        .iter()
+        .map(|v| format!("{}{}", prefix, v))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn verify_checksum(user: &[u8], actual: u8) -> ProtocolResult<()> {
+    let expected = checksum(user);
+    if actual != expected {
+        return Err(HexDigestError::CrcMismatch {
+            expected: expected as u16,
+            actual: actual as u16,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// 解码一帧，按起始字符区分固定长度帧与可变长度帧
+pub fn decode(frame: &[u8]) -> ProtocolResult<Gdw1376Frame> {
+    match frame.first() {
+        Some(&FIXED_START) => decode_fixed(frame),
+        _ => decode_variable(frame),
+    }
+}
+
+fn decode_fixed(frame: &[u8]) -> ProtocolResult<Gdw1376Frame> {
+    fixed_config().check_frame(frame)?;
+    verify_checksum(&frame[1..7], frame[7])?;
+    let control = Control::from_byte(frame[1]);
+    let address = Address::from_bytes(&frame[2..7])?;
+
+    let mut reader = Reader::new(&frame[..7]);
+    reader
+        .read_and_translate_head(1, |b| hex(b, "起始字符"))?
+        .read_and_translate_head(1, |b| text(b, "控制域", control.describe()))?
+        .read_and_translate_head(Address::LEN, |b| text(b, "地址域", address.to_string()))?;
+    Ok(Gdw1376Frame {
+        format: FrameFormat::Fixed,
+        control,
+        address,
+        afn: None,
+        seq: None,
+        pns: vec![],
+        fns: vec![],
+        data: vec![],
+        forwarded: None,
+        fields: reader.to_report_fields()?,
+    })
+}
+
+fn decode_variable(frame: &[u8]) -> ProtocolResult<Gdw1376Frame> {
+    config().check_frame(frame)?;
+    let l1 = u16::from_le_bytes([frame[1], frame[2]]);
+    let l2 = u16::from_le_bytes([frame[3], frame[4]]);
+    if l1 != l2 || frame[5] != START {
+        return Err(ProtocolError::ValidationFailed(
+            "Q/GDW 1376.1 header must be 68 L L 68 with identical length fields".into(),
+        ));
+    }
+    let protocol = match l1 & 0x03 {
+        PROTOCOL_1376 => "Q/GDW 1376.1",
+        PROTOCOL_2005 => "Q/GDW 130-2005",
+        flag => {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "unknown protocol flag {:02b} in length field",
+                flag
+            )))
+        }
+    };
+    let user_len = (l1 >> 2) as usize;
+    if frame.len() != HEADER_LEN + user_len + 2 {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "gdw1376.1 frame length {} does not match expected {}",
+            frame.len(),
+            HEADER_LEN + user_len + 2
+        )));
+    }
+    let user_end = HEADER_LEN + user_len;
+    verify_checksum(&frame[HEADER_LEN..user_end], frame[user_end])?;
+
+    let control = Control::from_byte(frame[6]);
+    let address = Address::from_bytes(&frame[7..12])?;
+    let code = format!("{:02X}", frame[12]);
+    let afn = <Afn as CmdTable>::from_code(&code)
+        .ok_or_else(|| ProtocolError::ValidationFailed(format!("unknown AFN {}", code)))?;
+    let seq = Seq::from_byte(frame[13]);
+
+    // 附加信息域: 下行的 PW，上行的 EC，以及两个方向都可能带的 Tp
+    let password = if !control.upstream && afn.carries_password() {
+        PASSWORD_LEN
+    } else {
+        0
+    };
+    let event_counter = if control.has_event_counter() {
+        EVENT_COUNTER_LEN
+    } else {
+        0
+    };
+    let time_tag = if seq.tpv { TIME_TAG_LEN } else { 0 };
+    let data_end = user_end
+        .checked_sub(password + event_counter + time_tag)
+        .filter(|end| *end >= 18)
+        .ok_or(ProtocolError::InputTooShort {
+            needed: HEADER_LEN + 12 + password + event_counter + time_tag + 2,
+            available: frame.len(),
+        })?;
+    let pns = link::decode_pn([frame[14], frame[15]]);
+    let fns = link::decode_fn([frame[16], frame[17]]);
+    let data = &frame[18..data_end];
+
+    let mut reader = Reader::new(&frame[..user_end]);
+    reader
+        .read_and_translate_head(1, |b| hex(b, "起始字符"))?
+        .read_and_translate_head(4, |b| {
+            text(b, "长度域", format!("{}，{}", user_len, protocol))
+        })?
+        .read_and_translate_head(1, |b| hex(b, "起始字符"))?
+        .read_and_translate_head(1, |b| text(b, "控制域", control.describe()))?
+        .read_and_translate_head(Address::LEN, |b| text(b, "地址域", address.to_string()))?
+        .read_and_translate_head(1, |b| text(b, "应用层功能码", afn.title()))?
+        .read_and_translate_head(1, |b| text(b, "帧序列域", seq.describe()))?
+        .read_and_translate_head(2, |b| text(b, "信息点", join("p", &pns)))?
+        .read_and_translate_head(2, |b| text(b, "信息类", join("F", &fns)))?;
+
+    let forwarded = if afn == Afn::Forward && fns == [1] {
+        Some(read_forwarded(&mut reader, data, control.upstream)?)
+    } else {
+        if !data.is_empty() {
+            reader.read_and_translate_head(data.len(), |b| hex(b, "数据单元"))?;
+        }
+        None
+    };
+    if password > 0 {
+        reader.read_and_translate_head(password, |b| hex(b, "消息认证码"))?;
+    }
+    if event_counter > 0 {
+        reader.read_and_translate_head(event_counter, |b| {
+            text(
+                b,
+                "事件计数器",
+                format!("重要事件 {}，一般事件 {}", b[0], b[1]),
+            )
+        })?;
+    }
+    if time_tag > 0 {
+        reader.read_and_translate_head(time_tag, translate_time_tag)?;
+    }
+
+    Ok(Gdw1376Frame {
+        format: FrameFormat::Variable,
+        control,
+        address,
+        afn: Some(afn),
+        seq: Some(seq),
+        pns,
+        fns,
+        data: data.to_vec(),
+        forwarded,
+        fields: reader.to_report_fields()?,
+    })
+}
+
+// 上行: 端口号 | 长度 (2) | 内容；下行在端口号之后多出通信控制字与两个超时时间
+fn read_forwarded(reader: &mut Reader, data: &[u8], upstream: bool) -> ProtocolResult<Forwarded> {
+    let header = if upstream { 3 } else { 6 };
+    if data.len() < header {
+        return Err(ProtocolError::InputTooShort {
+            needed: header,
+            available: data.len(),
+        });
+    }
+    let len = u16::from_le_bytes([data[header - 2], data[header - 1]]) as usize;
+    if data.len() != header + len {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "forwarded content length {} does not match {} remaining bytes",
+            len,
+            data.len() - header
+        )));
+    }
+    reader.read_and_translate_head(1, |b| number(b, "终端通信端口号", b[0] as usize))?;
+    if !upstream {
+        reader
+            .read_and_translate_head(1, |b| hex(b, "透明转发通信控制字"))?
+            .read_and_translate_head(1, |b| hex(b, "透明转发接收等待报文超时时间"))?
+            .read_and_translate_head(1, |b| hex(b, "透明转发接收等待字节超时时间"))?;
+    }
+    reader
+        .read_and_translate_head(2, |b| number(b, "透明转发内容字节数", len))?
+        .read_and_translate_head(len, |b| hex(b, "透明转发内容"))?;
+    Ok(Forwarded {
+        port: data[0],
+        content: data[header..].to_vec(),
+    })
+}
+
+// Tp: 启动帧帧序号计数器 PFC | 秒 分 时 日 (BCD) | 允许发送传输延时时间 (分钟)
+fn translate_time_tag(bytes: &[u8]) -> ProtocolResult<Rawfield> {
+    let time = hex_util::bytes_to_hex_swap(&bytes[1..5])?;
+    if !bcd_util::is_bcd_bytes(&bytes[1..5]) {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "time tag is not BCD: {}",
+            time
+        )));
+    }
+    let value = format!(
+        "PFC {}，{}日 {}:{}:{}，允许延时 {} 分钟",
+        bytes[0],
+        &time[0..2],
+        &time[2..4],
+        &time[4..6],
+        &time[6..8],
+        bytes[5]
+    );
+    text(bytes, "时间标签", value)
+}
+
+/// 生成可变长度帧 (不带时间标签)。下行帧的 AFN 需要消息认证码时在数据单元之后写入 password
+pub fn encode(
+    control: Control,
+    address: &Address,
+    afn: Afn,
+    seq: Seq,
+    units: &[DataUnit],
+    password: &[u8; PASSWORD_LEN],
+) -> ProtocolResult<Writer> {
+    let seq = Seq { tpv: false, ..seq };
+    let mut writer = Writer::new();
+    writer
+        .write_bytes("起始字符", &[START], "68")?
+        .write_placeholder("length", 4)?
+        .write_bytes("起始字符", &[START], "68")?
+        .write_bytes("控制域", &[control.to_byte()], &control.describe())?
+        .write_bytes("地址域", &address.to_bytes(), &address.to_string())?
+        .write_bytes("应用层功能码", &[afn.value()], &afn.title())?
+        .write_bytes("帧序列域", &[seq.to_byte()], &seq.describe())?;
+    for unit in units {
+        writer
+            .write_bytes(
+                "信息点",
+                &link::encode_pn(unit.pn)?,
+                &format!("p{}", unit.pn),
+            )?
+            .write_bytes(
+                "信息类",
+                &link::encode_fn(unit.fn_no)?,
+                &format!("F{}", unit.fn_no),
+            )?;
+        if !unit.data.is_empty() {
+            writer.write_bytes("数据单元", &unit.data, &hex_util::bytes_to_hex(&unit.data)?)?;
+        }
+    }
+    if !control.upstream && afn.carries_password() {
+        writer.write_bytes("消息认证码", password, &hex_util::bytes_to_hex(password)?)?;
+    }
+
+    let user_len = writer.buffer()?.len() - HEADER_LEN;
+    let l = u16::try_from(user_len)
+        .ok()
+        .filter(|l| *l < 0x4000)
+        .ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!("user data too long: {} bytes", user_len))
+        })?
+        << 2
+        | PROTOCOL_1376;
+    let [l0, l1] = l.to_le_bytes();
+    writer.rewrite_placeholder(
+        "length",
+        "长度域",
+        &[l0, l1, l0, l1],
+        &format!("{}，Q/GDW 1376.1", user_len),
+    )?;
+
+    let cs = checksum(&writer.buffer()?[HEADER_LEN..]);
+    writer
+        .write_bytes("校验和", &[cs], &format!("{:02X}", cs))?
+        .write_bytes("结束字符", &[END], "16")?;
+    Ok(writer)
+}
+
+/// 生成固定长度帧
+pub fn encode_fixed(control: Control, address: &Address) -> ProtocolResult<Writer> {
+    let mut user = vec![control.to_byte()];
+    user.extend_from_slice(&address.to_bytes());
+    let cs = checksum(&user);
+    let mut writer = Writer::new();
+    writer
+        .write_bytes("起始字符", &[FIXED_START], "10")?
+        .write_bytes("控制域", &[control.to_byte()], &control.describe())?
+        .write_bytes("地址域", &address.to_bytes(), &address.to_string())?
+        .write_bytes("校验和", &[cs], &format!("{:02X}", cs))?
+        .write_bytes("结束字符", &[END], "16")?;
+    Ok(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "330100001";
+
+    fn value_of<'a>(fields: &'a [ReportField], name: &str) -> &'a str {
+        fields
+            .iter()
+            .find(|f| f.name == name)
+            .map(|f| f.value.as_str())
+            .unwrap_or_default()
+    }
+
+    fn decode_hex(hex: &str) -> ProtocolResult<Gdw1376Frame> {
+        decode(&hex_util::hex_to_bytes(hex).unwrap())
+    }
+
+    #[test]
+    fn test_decode_login_and_heartbeat() {
+        // 字段内容见 tests/golden.rs
+        let frame = decode_hex("683200320068C901330100000270000001007116").unwrap();
+        assert!(frame.is_login());
+        assert_eq!(frame.address.to_string(), ADDRESS);
+        assert_eq!(value_of(&frame.fields, "长度域"), "12，Q/GDW 1376.1");
+
+        let frame = decode_hex("683100310068C901330100000271000004007516").unwrap();
+        assert!(frame.is_heartbeat());
+        assert_eq!(value_of(&frame.fields, "长度域"), "12，Q/GDW 130-2005");
+
+        let frame = decode_hex("685200520068E9013301000002F10000040003010500301201006116").unwrap();
+        assert!(frame.is_heartbeat());
+        assert!(frame.data.is_empty());
+    }
+
+    #[test]
+    fn test_decode_forwarded() {
+        let frame = decode_hex(
+            "689E009E0068880133010000106100000100021800FEFEFEFE68129078563412689108333334339A78563488166716",
+        )
+        .unwrap();
+        let forwarded = frame.forwarded.unwrap();
+        assert_eq!(forwarded.port, 2);
+        assert_eq!(
+            hex_util::bytes_to_hex(&forwarded.content).unwrap(),
+            "FEFEFEFE68129078563412689108333334339A7856348816"
+        );
+
+        // 内容长度与数据单元不符
+        let mut raw = hex_util::hex_to_bytes(
+            "689E009E0068880133010000106100000100021900FEFEFEFE68129078563412689108333334339A785634881667",
+        )
+        .unwrap();
+        let cs = checksum(&raw[HEADER_LEN..raw.len() - 1]);
+        *raw.last_mut().unwrap() = cs;
+        raw.push(END);
+        assert!(decode(&raw).is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_frames() {
+        // 校验和错误
+        let err = decode_hex("683200320068C901330100000270000001007216").unwrap_err();
+        assert!(err.to_string().contains("mismatch"));
+        // 两个长度域不一致
+        assert!(decode_hex("683200360068C901330100000270000001007116").is_err());
+        // 规约标识非法
+        assert!(decode_hex("683300330068C901330100000270000001007116").is_err());
+        // 固定长度帧校验和错误
+        assert!(decode_hex("100B01330100004116").is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        // 经 Gdw1376Handler 组帧的黄金帧见 tests/golden.rs
+        let address = Address::parse(ADDRESS).unwrap();
+        let content = hex_util::hex_to_bytes("FEFEFEFE6811111111111168110433333433B116").unwrap();
+        let forward = encode(
+            Control::from_byte(Afn::Forward.request_control()),
+            &address,
+            Afn::Forward,
+            Seq::single(1, false),
+            &[DataUnit::forward(2, &content).unwrap()],
+            &[0; PASSWORD_LEN],
+        )
+        .unwrap();
+        let decoded = decode_hex(&forward.full_hex().unwrap()).unwrap();
+        assert_eq!(decoded.forwarded.unwrap().content, content);
+
+        // 固定长度帧不经 handler 组帧
+        let fixed = encode_fixed(Control::from_byte(0x0B), &address).unwrap();
+        assert_eq!(fixed.full_hex().unwrap(), "100B01330100004016");
+        let decoded = decode_hex("100B01330100004016").unwrap();
+        assert_eq!(decoded.format, FrameFormat::Fixed);
+        assert_eq!(decoded.control.function_title(), "链路状态");
+    }
+}
@@ -0,0 +1,309 @@
+use protocol_kernel::{
+    bridge::registry, hex_util, AutoEncodingParam, Cmd, CmdTable, CommandDescription, JniRequest,
+    JniResponse, MsgTypeEnum, ParamSchema, ProtocolConfig, ProtocolDescription, ProtocolError,
+    ProtocolHandler, ProtocolResult, ProtocolSniffer,
+};
+
+use crate::{
+    afn::Afn,
+    frame::{self, DataUnit, Gdw1376Frame, PASSWORD_LEN},
+    link::{Address, Control, Seq},
+    param::Param,
+    PROTOCOL_ID,
+};
+
+/// 表计报文前的唤醒字节
+const PREAMBLE: u8 = 0xFE;
+
+/// Q/GDW 1376.1 集中器的 ProtocolHandler。
+/// 上行先解开链路层，透明转发的内层表计报文按 with_inner 登记的协议识别，
+/// 再交给对应 protocol_id 的 handler 解码，此时 device_id 为集中器地址，device_no 为表地址。
+/// 下行按 cmd_code (AFN) 与 params 组帧，device_no 为集中器地址
+pub struct Gdw1376Handler {
+    sniffer: ProtocolSniffer,
+    // ProtocolConfig::name -> protocol_id
+    routes: Vec<(String, String)>,
+    password: [u8; PASSWORD_LEN],
+}
+
+impl Default for Gdw1376Handler {
+    fn default() -> Self {
+        Self {
+            sniffer: ProtocolSniffer::new(),
+            routes: Vec::new(),
+            password: [0; PASSWORD_LEN],
+        }
+    }
+}
+
+impl Gdw1376Handler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记透明转发中可能出现的表计协议。protocol_id 需已通过 register_protocol 注册
+    pub fn with_inner(mut self, protocol_id: &str, config: ProtocolConfig) -> Self {
+        self.routes.push((config.name.clone(), protocol_id.into()));
+        self.sniffer = self.sniffer.register(config);
+        self
+    }
+
+    /// 下行帧的消息认证码 PW，默认全 0
+    pub fn with_password(mut self, password: [u8; PASSWORD_LEN]) -> Self {
+        self.password = password;
+        self
+    }
+
+    /// 识别内层报文所属的协议，返回 protocol_id 与去掉唤醒字节后的报文
+    pub fn route<'a>(&self, content: &'a [u8]) -> Option<(&str, &'a [u8])> {
+        let skip = content.iter().take_while(|b| **b == PREAMBLE).count();
+        let inner = &content[skip..];
+        let matched = self.sniffer.sniff(inner)?;
+        self.routes
+            .iter()
+            .find(|(name, _)| *name == matched.config.name)
+            .map(|(_, id)| (id.as_str(), inner))
+    }
+
+    fn decode_inner(&self, decoded: &Gdw1376Frame) -> ProtocolResult<Option<JniResponse>> {
+        let Some((protocol_id, inner)) = decoded
+            .forwarded
+            .as_ref()
+            .and_then(|f| self.route(&f.content))
+        else {
+            return Ok(None);
+        };
+        let request = JniRequest::builder()
+            .hex(&hex_util::bytes_to_hex(inner)?)
+            .uri(protocol_id)
+            .build()?;
+        registry::protocol_handler(protocol_id)?
+            .decode_upstream(&request)
+            .map(Some)
+    }
+}
+
+impl ProtocolHandler for Gdw1376Handler {
+    fn decode_upstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        let bytes = hex_util::hex_to_bytes(request.hex())?;
+        let decoded = frame::decode(&bytes)?;
+        if !decoded.control.upstream {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "control code {:02X} is not a terminal upstream frame",
+                decoded.control.to_byte()
+            )));
+        }
+        let address = decoded.address.to_string();
+
+        if let Some(mut rsp) = self.decode_inner(&decoded)? {
+            let mut fields = decoded.fields;
+            fields.extend(rsp.req_jsons_clone());
+            rsp.set_device_id(&address);
+            rsp.set_req_hex(request.hex());
+            rsp.set_req_jsons(fields);
+            return Ok(rsp);
+        }
+
+        let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+        rsp.set_device_no(&address);
+        match decoded.afn {
+            Some(afn) => {
+                rsp.set_cmd_code(&afn.code());
+                let msg_type = if decoded.is_login() {
+                    Some(MsgTypeEnum::SignIn)
+                } else {
+                    afn.msg_type()
+                };
+                if let Some(msg_type) = msg_type {
+                    rsp.set_msg_type(&msg_type);
+                }
+            }
+            // 固定长度帧只用于链路维护
+            None => {
+                rsp.set_cmd_code(&format!("{:02X}", decoded.control.to_byte()));
+                rsp.set_msg_type(&MsgTypeEnum::HeartBeat);
+            }
+        }
+        rsp.set_req_hex(request.hex());
+        rsp.set_req_jsons(decoded.fields);
+        Ok(rsp)
+    }
+
+    fn encode_downstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        let code = request.cmd_code().unwrap_or_default();
+        let afn = <Afn as CmdTable>::from_code(code)
+            .ok_or_else(|| ProtocolError::ValidationFailed(format!("unknown AFN '{}'", code)))?;
+        let device_no = request.device_no().unwrap_or_default();
+        let address = Address::parse(device_no)?;
+        let params = request.params_clone();
+
+        let seq = Param::Seq.number(&params)?;
+        let seq = Seq::single(seq as u8, afn.needs_confirm());
+        let unit = match afn {
+            Afn::Forward => {
+                let port = u8::try_from(Param::Port.number(&params)?).map_err(|_| {
+                    ProtocolError::ValidationFailed("port must be within 0..=255".into())
+                })?;
+                let content = hex_util::hex_to_bytes(&Param::Content.value(&params)?)?;
+                DataUnit::forward(port, &content)?
+            }
+            _ => DataUnit::new(
+                Param::Pn.number(&params)?,
+                Param::Fn.number(&params)?,
+                hex_util::hex_to_bytes(&Param::Data.value(&params)?)?,
+            ),
+        };
+        let writer = frame::encode(
+            Control::from_byte(afn.request_control()),
+            &address,
+            afn,
+            seq,
+            &[unit],
+            &self.password,
+        )?;
+        let fields = writer.to_report_fields()?;
+        let hex = writer.full_hex()?;
+
+        let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+        rsp.set_device_no(device_no);
+        rsp.set_cmd_code(&afn.code());
+        if let Some(msg_type) = afn.msg_type() {
+            rsp.set_msg_type(&msg_type);
+        }
+        rsp.set_rsp_hex(&hex);
+        rsp.set_rsp_jsons(fields);
+        Ok(rsp)
+    }
+
+    fn describe(&self) -> Option<ProtocolDescription> {
+        let description = <Afn as CmdTable>::variants()
+            .iter()
+            .filter(|afn| !afn.direction().is_upstream_only())
+            .map(|afn| {
+                let params = Param::of(*afn).iter().map(ParamSchema::of).collect();
+                CommandDescription::of(afn, params)
+            })
+            .fold(ProtocolDescription::new(PROTOCOL_ID, "2013"), |d, c| {
+                d.with_command(c)
+            });
+        Some(description)
+    }
+
+    fn field_titles(&self) -> Vec<String> {
+        [
+            "起始字符",
+            "长度域",
+            "控制域",
+            "地址域",
+            "应用层功能码",
+            "帧序列域",
+            "信息点",
+            "信息类",
+            "数据单元",
+            "终端通信端口号",
+            "透明转发通信控制字",
+            "透明转发接收等待报文超时时间",
+            "透明转发接收等待字节超时时间",
+            "透明转发内容字节数",
+            "透明转发内容",
+            "消息认证码",
+            "事件计数器",
+            "时间标签",
+            "校验和",
+            "结束字符",
+        ]
+        .iter()
+        .map(|t| t.to_string())
+        .chain(Param::of(Afn::Forward).iter().map(|p| p.title()))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_impl_dlt645::{frame as dlt645, Dlt645Handler};
+
+    const FORWARDED: &str = "689E009E0068880133010000106100000100021800FEFEFEFE68129078563412689108333334339A78563488166716";
+
+    fn request(json: &str) -> JniRequest {
+        JniRequest::from(json.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_decode_link_frames() {
+        // cmd_code 与字段见 tests/golden.rs
+        let handler = Gdw1376Handler::new();
+        let rsp = handler
+            .decode_upstream(&request(
+                r#"{"hex":"683200320068C901330100000270000001007116"}"#,
+            ))
+            .unwrap();
+        assert_eq!(rsp.device_no(), Some("330100001"));
+        assert_eq!(rsp.msg_type(), Some("signin"));
+
+        let rsp = handler
+            .decode_upstream(&request(
+                r#"{"hex":"683200320068C901330100000271000004007516"}"#,
+            ))
+            .unwrap();
+        assert_eq!(rsp.msg_type(), Some("heart_beat"));
+
+        // 主站下行的确认帧不是上行帧
+        assert!(handler
+            .decode_upstream(&request(
+                r#"{"hex":"6832003200680B0133010000006000000100A116"}"#
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_decode_forwarded_meter_frame() {
+        registry::register_protocol("dlt645-2007", Dlt645Handler::new());
+        let json = format!(r#"{{"hex":"{}"}}"#, FORWARDED);
+
+        // 未登记内层协议时只解开链路层
+        let rsp = Gdw1376Handler::new()
+            .decode_upstream(&request(&json))
+            .unwrap();
+        assert_eq!(rsp.device_no(), Some("330100001"));
+        assert_eq!(rsp.cmd_code(), Some("10"));
+
+        let handler = Gdw1376Handler::new().with_inner("dlt645-2007", dlt645::config());
+        let rsp = handler.decode_upstream(&request(&json)).unwrap();
+        assert_eq!(rsp.device_id(), Some("330100001"));
+        assert_eq!(rsp.device_no(), Some("123456789012"));
+        assert_eq!(rsp.cmd_code(), Some("91"));
+        assert_eq!(rsp.req_hex(), FORWARDED);
+        let names: Vec<&str> = rsp.req_jsons().iter().map(|f| &*f.name).collect();
+        assert!(names.contains(&"透明转发内容"));
+        assert!(names.contains(&"数据标识"));
+    }
+
+    #[test]
+    fn test_encode_downstream() {
+        let handler = Gdw1376Handler::new();
+        assert!(handler
+            .encode_downstream(&request(r#"{"deviceNo":"330100001","cmdCode":"10"}"#))
+            .is_err());
+        assert!(handler
+            .encode_downstream(&request(r#"{"deviceNo":"3301","cmdCode":"00"}"#))
+            .is_err());
+        assert!(handler
+            .encode_downstream(&request(r#"{"deviceNo":"330100001","cmdCode":"7F"}"#))
+            .is_err());
+    }
+
+    #[test]
+    fn test_describe() {
+        let description = Gdw1376Handler::new().describe().unwrap();
+        assert!(description.commands.iter().all(|c| c.code != "02"));
+        let forward = description
+            .commands
+            .iter()
+            .find(|c| c.code == "10")
+            .unwrap();
+        let params: Vec<&str> = forward.params.iter().map(|p| p.code.as_str()).collect();
+        assert_eq!(params, ["seq", "port", "content"]);
+    }
+}
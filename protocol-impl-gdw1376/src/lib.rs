@@ -0,0 +1,39 @@
+//! Q/GDW 1376.1《电力用户用电信息采集系统通信协议: 主站与采集终端通信协议》链路层参考实现，
+//! GB/T 26831 社区能源计量抄收系统中集中器与主站之间的报文格式与之相同。
+//! 集中器上行的报文先在这里解开，透明转发 (AFN=10) 中的表计报文再交给各自的协议 handler:
+//!
+//! - [`frame::config`] / [`frame::fixed_config`]: 可变长度帧与固定长度帧的 `ProtocolConfig`
+//! - [`link`]: 控制域、地址域、帧序列域，以及数据单元标识 pn/Fn 的编解码
+//! - [`Afn`]: 应用层功能码表，由 `cmd_table!` 生成
+//! - [`Gdw1376Handler`]: `ProtocolHandler` 实现，with_inner 登记内层表计协议
+//!
+//! ```
+//! use protocol_impl_gdw1376::{frame, Afn};
+//! use protocol_kernel::hex_util;
+//!
+//! // 集中器登录
+//! let bytes = hex_util::hex_to_bytes("683200320068C901330100000270000001007116").unwrap();
+//! let login = frame::decode(&bytes).unwrap();
+//! assert!(login.is_login());
+//! assert_eq!(login.afn, Some(Afn::LinkTest));
+//! assert_eq!(login.address.to_string(), "330100001");
+//! ```
+pub mod afn;
+pub mod frame;
+pub mod handler;
+pub mod link;
+pub mod param;
+
+pub use afn::Afn;
+pub use frame::{decode, encode, DataUnit, Forwarded, Gdw1376Frame};
+pub use handler::Gdw1376Handler;
+pub use link::{Address, Control, Seq};
+
+/// 注册到 ProtocolRouter 时使用的协议 id
+pub const PROTOCOL_ID: &str = "gdw1376.1";
+
+/// 以默认配置 (不识别内层报文) 注册到全局协议表。需要转交表计报文时，
+/// 用 Gdw1376Handler::with_inner 构造后自行注册
+pub fn register() {
+    protocol_kernel::bridge::registry::register_protocol(PROTOCOL_ID, Gdw1376Handler::new());
+}
@@ -0,0 +1,304 @@
+use std::fmt;
+
+use protocol_kernel::{bcd_util, ProtocolError, ProtocolResult};
+
+/// 控制域 C
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Control {
+    /// D7 DIR: true 为终端发出的上行帧
+    pub upstream: bool,
+    /// D6 PRM: true 为启动站发出
+    pub primary: bool,
+    /// D5: 下行为帧计数位 FCB，上行为要求访问位 ACD
+    pub fcb_acd: bool,
+    /// D4 FCV: 帧计数有效位
+    pub fcv: bool,
+    /// D3~D0 功能码
+    pub function: u8,
+}
+
+impl Control {
+    pub fn from_byte(byte: u8) -> Self {
+        Self {
+            upstream: byte & 0x80 != 0,
+            primary: byte & 0x40 != 0,
+            fcb_acd: byte & 0x20 != 0,
+            fcv: byte & 0x10 != 0,
+            function: byte & 0x0F,
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        (self.upstream as u8) << 7
+            | (self.primary as u8) << 6
+            | (self.fcb_acd as u8) << 5
+            | (self.fcv as u8) << 4
+            | (self.function & 0x0F)
+    }
+
+    /// 上行帧 ACD=1 时附加信息域带事件计数器 EC
+    pub fn has_event_counter(&self) -> bool {
+        self.upstream && self.fcb_acd
+    }
+
+    pub fn function_title(&self) -> &'static str {
+        match (self.primary, self.function) {
+            (true, 1) => "复位命令",
+            (true, 4) => "用户数据",
+            (true, 9) => "链路测试",
+            (true, 10) => "请求1级数据",
+            (true, 11) => "请求2级数据",
+            (false, 0) => "认可",
+            (false, 8) => "用户数据",
+            (false, 9) => "否认：无所召唤的数据",
+            (false, 11) => "链路状态",
+            _ => "备用",
+        }
+    }
+
+    /// 例如 "上行，启动站，链路测试"
+    pub fn describe(&self) -> String {
+        format!(
+            "{}，{}，{}",
+            if self.upstream { "上行" } else { "下行" },
+            if self.primary {
+                "启动站"
+            } else {
+                "从动站"
+            },
+            self.function_title()
+        )
+    }
+}
+
+/// 地址域 A: 行政区划码 A1 (BCD) + 终端地址 A2 (二进制) + 主站地址和组地址标志 A3
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Address {
+    /// 行政区划码，按显示顺序存放，如 0x3301
+    pub area: u16,
+    pub terminal: u16,
+    pub master: u8,
+}
+
+impl Address {
+    pub const LEN: usize = 5;
+
+    pub fn new(area: u16, terminal: u16) -> Self {
+        Self {
+            area,
+            terminal,
+            master: 0,
+        }
+    }
+
+    /// 终端地址为组地址 (A3 的 D0)
+    pub fn is_group(&self) -> bool {
+        self.master & 0x01 != 0
+    }
+
+    /// A2 为 FFFF 且为组地址时是广播地址
+    pub fn is_broadcast(&self) -> bool {
+        self.is_group() && self.terminal == 0xFFFF
+    }
+
+    /// 设备号格式: 4 位行政区划码 + 5 位十进制终端地址，如 "330100001"
+    pub fn parse(device_no: &str) -> ProtocolResult<Self> {
+        let invalid = || {
+            ProtocolError::ValidationFailed(format!(
+                "concentrator address must be 4 area digits + 5 terminal digits, got '{}'",
+                device_no
+            ))
+        };
+        if device_no.len() != 9 || !device_no.is_ascii() {
+            return Err(invalid());
+        }
+        let (area, terminal) = device_no.split_at(4);
+        let area = u16::from_str_radix(area, 16).map_err(|_| invalid())?;
+        if !bcd_util::is_bcd_bytes(&area.to_be_bytes()) {
+            return Err(invalid());
+        }
+        let terminal = terminal.parse::<u16>().map_err(|_| invalid())?;
+        Ok(Self::new(area, terminal))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> ProtocolResult<Self> {
+        if bytes.len() < Self::LEN {
+            return Err(ProtocolError::InputTooShort {
+                needed: Self::LEN,
+                available: bytes.len(),
+            });
+        }
+        Ok(Self {
+            area: u16::from_le_bytes([bytes[0], bytes[1]]),
+            terminal: u16::from_le_bytes([bytes[2], bytes[3]]),
+            master: bytes[4],
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 5] {
+        let [a0, a1] = self.area.to_le_bytes();
+        let [t0, t1] = self.terminal.to_le_bytes();
+        [a0, a1, t0, t1, self.master]
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04X}{:05}", self.area, self.terminal)
+    }
+}
+
+/// 帧序列域 SEQ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seq {
+    /// D7 TpV: 附加信息域带时间标签 Tp
+    pub tpv: bool,
+    /// D6 FIR: 首帧
+    pub first: bool,
+    /// D5 FIN: 末帧
+    pub last: bool,
+    /// D4 CON: 需要对方确认
+    pub confirm: bool,
+    /// D3~D0 PSEQ/RSEQ
+    pub seq: u8,
+}
+
+impl Seq {
+    /// 不带时间标签的单帧
+    pub fn single(seq: u8, confirm: bool) -> Self {
+        Self {
+            tpv: false,
+            first: true,
+            last: true,
+            confirm,
+            seq: seq & 0x0F,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Self {
+        Self {
+            tpv: byte & 0x80 != 0,
+            first: byte & 0x40 != 0,
+            last: byte & 0x20 != 0,
+            confirm: byte & 0x10 != 0,
+            seq: byte & 0x0F,
+        }
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        (self.tpv as u8) << 7
+            | (self.first as u8) << 6
+            | (self.last as u8) << 5
+            | (self.confirm as u8) << 4
+            | (self.seq & 0x0F)
+    }
+
+    /// 例如 "单帧，需要确认，序号 0"
+    pub fn describe(&self) -> String {
+        let position = match (self.first, self.last) {
+            (true, true) => "单帧",
+            (true, false) => "首帧",
+            (false, false) => "中间帧",
+            (false, true) => "末帧",
+        };
+        let confirm = if self.confirm {
+            "需要确认"
+        } else {
+            "不需要确认"
+        };
+        format!("{}，{}，序号 {}", position, confirm, self.seq)
+    }
+}
+
+/// 信息点 pn -> 数据单元标识 DA (DA1 位图，DA2 组号)。p0 为 0000
+pub fn encode_pn(pn: u16) -> ProtocolResult<[u8; 2]> {
+    match pn {
+        0 => Ok([0, 0]),
+        1..=2040 => Ok([1 << ((pn - 1) % 8), ((pn - 1) / 8 + 1) as u8]),
+        _ => Err(ProtocolError::ValidationFailed(format!(
+            "pn must be within 0..=2040, got {}",
+            pn
+        ))),
+    }
+}
+
+/// DA -> 其中的全部信息点
+pub fn decode_pn(da: [u8; 2]) -> Vec<u16> {
+    if da[1] == 0 {
+        return vec![0];
+    }
+    let base = (da[1] as u16 - 1) * 8;
+    (0..8)
+        .filter(|bit| da[0] & (1 << bit) != 0)
+        .map(|bit| base + bit + 1)
+        .collect()
+}
+
+/// 信息类 Fn -> 数据单元标识 DT (DT1 位图，DT2 组号)
+pub fn encode_fn(fn_no: u16) -> ProtocolResult<[u8; 2]> {
+    match fn_no {
+        1..=248 => Ok([1 << ((fn_no - 1) % 8), ((fn_no - 1) / 8) as u8]),
+        _ => Err(ProtocolError::ValidationFailed(format!(
+            "Fn must be within 1..=248, got {}",
+            fn_no
+        ))),
+    }
+}
+
+/// DT -> 其中的全部信息类
+pub fn decode_fn(dt: [u8; 2]) -> Vec<u16> {
+    let base = dt[1] as u16 * 8;
+    (0..8)
+        .filter(|bit| dt[0] & (1 << bit) != 0)
+        .map(|bit| base + bit + 1)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_and_seq() {
+        let c = Control::from_byte(0xC9);
+        assert!(c.upstream && c.primary && !c.has_event_counter());
+        assert_eq!(c.describe(), "上行，启动站，链路测试");
+        assert_eq!(c.to_byte(), 0xC9);
+        assert!(Control::from_byte(0xE9).has_event_counter());
+
+        let seq = Seq::from_byte(0x71);
+        assert_eq!(seq, Seq::single(1, true));
+        assert_eq!(seq.to_byte(), 0x71);
+        assert_eq!(seq.describe(), "单帧，需要确认，序号 1");
+    }
+
+    #[test]
+    fn test_address() {
+        let address = Address::parse("330100001").unwrap();
+        assert_eq!(address.to_bytes(), [0x01, 0x33, 0x01, 0x00, 0x00]);
+        assert_eq!(
+            Address::from_bytes(&address.to_bytes())
+                .unwrap()
+                .to_string(),
+            "330100001"
+        );
+        assert!(Address::parse("33010001").is_err());
+        assert!(Address::parse("3A0100001").is_err());
+        assert!(Address::parse("330199999").is_err());
+    }
+
+    #[test]
+    fn test_pn_fn() {
+        assert_eq!(encode_pn(0).unwrap(), [0x00, 0x00]);
+        assert_eq!(encode_pn(1).unwrap(), [0x01, 0x01]);
+        assert_eq!(encode_pn(10).unwrap(), [0x02, 0x02]);
+        assert_eq!(decode_pn([0x03, 0x02]), [9, 10]);
+        assert!(encode_pn(2041).is_err());
+
+        assert_eq!(encode_fn(1).unwrap(), [0x01, 0x00]);
+        assert_eq!(encode_fn(3).unwrap(), [0x04, 0x00]);
+        assert_eq!(encode_fn(161).unwrap(), [0x01, 0x14]);
+        assert_eq!(decode_fn([0x05, 0x00]), [1, 3]);
+        assert!(encode_fn(0).is_err());
+    }
+}
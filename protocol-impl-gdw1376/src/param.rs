@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use protocol_kernel::{AutoEncodingParam, FieldType, ProtocolError, ProtocolResult};
+
+use crate::afn::Afn;
+
+/// 下行参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Param {
+    Seq,
+    Pn,
+    Fn,
+    Data,
+    Port,
+    Content,
+}
+
+impl Param {
+    /// 各 AFN 的下行参数。透明转发只需端口与内容，其余 AFN 按单个数据单元组帧
+    pub fn of(afn: Afn) -> Vec<Param> {
+        match afn {
+            Afn::Forward => vec![Param::Seq, Param::Port, Param::Content],
+            _ => vec![Param::Seq, Param::Pn, Param::Fn, Param::Data],
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Param::Seq => "seq",
+            Param::Pn => "pn",
+            Param::Fn => "fn",
+            Param::Data => "data",
+            Param::Port => "port",
+            Param::Content => "content",
+        }
+    }
+
+    /// 取参数值，缺省时用默认值
+    pub fn value(&self, params: &HashMap<String, String>) -> ProtocolResult<String> {
+        let value = params
+            .get(self.key())
+            .filter(|v| !v.is_empty())
+            .cloned()
+            .unwrap_or_else(|| self.default_value());
+        if value.is_empty() && self.required() {
+            return Err(ProtocolError::CommonError(format!(
+                "Field '{}' is required but no value provided",
+                self.key()
+            )));
+        }
+        Ok(value)
+    }
+
+    pub fn number(&self, params: &HashMap<String, String>) -> ProtocolResult<u16> {
+        let value = self.value(params)?;
+        value.trim().parse().map_err(|_| {
+            ProtocolError::ValidationFailed(format!(
+                "{} must be an integer, got '{}'",
+                self.key(),
+                value
+            ))
+        })
+    }
+}
+
+impl AutoEncodingParam for Param {
+    fn code(&self) -> String {
+        self.key().into()
+    }
+
+    fn title(&self) -> String {
+        match self {
+            Param::Seq => "帧序号",
+            Param::Pn => "信息点",
+            Param::Fn => "信息类",
+            Param::Data => "数据单元",
+            Param::Port => "终端通信端口号",
+            Param::Content => "透明转发内容",
+        }
+        .into()
+    }
+
+    fn byte_length(&self) -> usize {
+        match self {
+            Param::Seq | Param::Port => 1,
+            Param::Pn | Param::Fn => 2,
+            Param::Data | Param::Content => 0,
+        }
+    }
+
+    fn field_type(&self) -> FieldType {
+        match self {
+            Param::Seq | Param::Port => FieldType::UnsignedU8(1.0),
+            Param::Pn | Param::Fn => FieldType::UnsignedU16(1.0),
+            Param::Data | Param::Content => FieldType::StringOrBCD,
+        }
+    }
+
+    fn default_value(&self) -> String {
+        match self {
+            Param::Seq | Param::Pn => "0".into(),
+            Param::Fn => "1".into(),
+            Param::Port => "2".into(),
+            Param::Data | Param::Content => String::new(),
+        }
+    }
+
+    fn required(&self) -> bool {
+        !matches!(self, Param::Data)
+    }
+}
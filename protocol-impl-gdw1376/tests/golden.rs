@@ -0,0 +1,43 @@
+//! Q/GDW 1376.1 链路层黄金帧，经 Gdw1376Handler 校验上行解析与下行组帧。
+//! 未登记内层协议，透明转发帧只解开链路层
+use protocol_impl_gdw1376::Gdw1376Handler;
+use protocol_testkit::{golden_frames, GoldenCase};
+
+golden_frames!(
+    gdw1376_golden,
+    Gdw1376Handler::new(),
+    [
+        GoldenCase::decode("登录", "68 3200 3200 68 C9 0133 010000 02 70 0000 0100 71 16")
+            .cmd("02")
+            .field("kong_zhi_yu", "上行，启动站，链路测试")
+            .field("xin_xi_dian", "p0"),
+        // 2005 版规约标识
+        GoldenCase::decode("心跳", "68 3100 3100 68 C9 0133 010000 02 71 0000 0400 75 16")
+            .cmd("02"),
+        // 带事件计数器与时间标签
+        GoldenCase::decode(
+            "带时间标签的心跳",
+            "68 5200 5200 68 E9 0133 010000 02 F1 0000 0400 0301 0500301201 00 61 16",
+        )
+        .cmd("02")
+        .field("shi_jian_ji_shu_qi", "重要事件 3，一般事件 1")
+        .field("shi_jian_biao_qian", "PFC 5，01日 12:30:00，允许延时 0 分钟"),
+        GoldenCase::decode(
+            "透明转发应答",
+            "68 9E00 9E00 68 88 0133 010000 10 61 0000 0100 02 1800 FEFEFEFE68129078563412689108333334339A7856348816 67 16",
+        )
+        .cmd("10")
+        .field("tou_ming_zhuan_fa_nei_rong_zi_jie_shu", "24"),
+        GoldenCase::encode("确认", "00")
+            .device_no("330100001")
+            .hex("68 3200 3200 68 0B 0133 010000 00 60 0000 0100 A1 16"),
+        GoldenCase::encode("透明转发", "10")
+            .device_no("330100001")
+            .param("seq", "1")
+            .param("content", "FEFEFEFE6811111111111168110433333433B116")
+            .hex(
+                "68 DA00 DA00 68 4A 0133 010000 10 61 0000 0100 02 6B 850A 1400 \
+                 FEFEFEFE6811111111111168110433333433B116 00000000000000000000000000000000 D8 16",
+            ),
+    ]
+);
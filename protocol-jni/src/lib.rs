@@ -0,0 +1,250 @@
+//! JNI 绑定层，基于 `jni` crate。
+//!
+//! 各个协议只需要注册一个 handler，再用 `jni_export!` 生成对应 Java 类的导出函数:
+//!
+//! ```ignore
+//! protocol_jni::register_handler(|req: &JniRequest| my_protocol::process(req));
+//! protocol_jni::jni_export!(Java_com_example_ProtocolBridge_process);
+//! protocol_jni::jni_export_batch!(Java_com_example_ProtocolBridge_processBatch);
+//! ```
+//!
+//! 导出函数由宏在调用方 crate 中生成，因此调用宏的协议 crate 需要声明
+//! `crate-type = ["cdylib"]`，由它产出供 `System.loadLibrary` 加载的动态库;
+//! 本 crate 自身只以 rlib 形式被链接。
+//!
+//! 一个动态库承载多个表计协议时，用 `register_protocol(uri, handler)` 分别注册，
+//! 请求按 JniRequest::uri 路由。
+//!
+//! 需要访问 Redis/DB 的协议可以改用 `register_async_handler`，由 `BridgeExecutor`
+//! (默认 `ThreadParkExecutor`，也可以包装 tokio 的 `Handle::block_on`) 在调用线程上等待结果。
+//!
+//! 导出函数负责 byte[] 与 JniRequest/JniResponse 的转换，并捕获 handler 中的 panic，
+//! panic 与错误都会以 success=false 的 JniResponse 返回，不会越过 JNI 边界。
+//! 只有 byte[] 本身读写失败 (JVM 已抛出异常，如 OutOfMemoryError) 时返回 null，
+//! 由 JVM 在返回后抛出该异常。
+use jni::{
+    objects::JByteArray,
+    sys::{jbyteArray, jsize},
+    JNIEnv,
+};
+
+pub use jni;
+pub use protocol_kernel::bridge::async_handler::{
+    register_async_handler, AsyncBridgeHandler as AsyncJniHandler, BridgeExecutor,
+    ThreadParkExecutor,
+};
+pub use protocol_kernel::bridge::dispatch::{
+    describe_protocol, process_batch_bytes, process_bytes, register_handler,
+    BridgeHandler as JniHandler,
+};
+pub use protocol_kernel::bridge::registry::{register_protocol, ProtocolHandler};
+
+/// `jni_export!` 生成的导出函数的实现
+pub fn process_jbyte_array(env: &mut JNIEnv, input: &JByteArray) -> jbyteArray {
+    match read_byte_array(env, input) {
+        Some(bytes) => new_byte_array(env, &process_bytes(&bytes)),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// `jni_export_batch!` 生成的导出函数的实现
+pub fn process_batch_jbyte_array(env: &mut JNIEnv, input: &JByteArray) -> jbyteArray {
+    match read_byte_array(env, input) {
+        Some(bytes) => new_byte_array(env, &process_batch_bytes(&bytes)),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// `jni_export_describe!` 生成的导出函数的实现
+pub fn describe_jbyte_array(env: &mut JNIEnv) -> jbyteArray {
+    new_byte_array(env, &describe_protocol())
+}
+
+// Java byte[] -> Vec<u8>，null 视为空数组；读取失败 (JVM 已有异常挂起) 时返回 None
+fn read_byte_array(env: &JNIEnv, input: &JByteArray) -> Option<Vec<u8>> {
+    if input.is_null() {
+        return Some(Vec::new());
+    }
+    env.convert_byte_array(input).ok()
+}
+
+// 创建 Java byte[]；超过 byte[] 上限或内存不足时抛出 OutOfMemoryError 并返回 null
+// (内存不足时 JVM 已挂起异常)
+fn new_byte_array(env: &mut JNIEnv, data: &[u8]) -> jbyteArray {
+    if jsize::try_from(data.len()).is_err() {
+        let message = format!("response of {} bytes exceeds byte[] limit", data.len());
+        // 抛出失败时 JVM 已有异常挂起，调用方同样会看到异常
+        let _ = env.throw_new("java/lang/OutOfMemoryError", message);
+        return std::ptr::null_mut();
+    }
+    env.byte_array_from_slice(data)
+        .map(JByteArray::into_raw)
+        .unwrap_or(std::ptr::null_mut())
+}
+
+/// 生成 `byte[] process(byte[])` 形式的 JNI 导出函数，函数名需符合
+/// `Java_<包名>_<类名>_<方法名>` 的 JNI 命名规则
+#[macro_export]
+macro_rules! jni_export {
+    ($name:ident) => {
+        #[no_mangle]
+        pub extern "system" fn $name<'local>(
+            mut env: $crate::jni::JNIEnv<'local>,
+            _class: $crate::jni::objects::JClass<'local>,
+            input: $crate::jni::objects::JByteArray<'local>,
+        ) -> $crate::jni::sys::jbyteArray {
+            $crate::process_jbyte_array(&mut env, &input)
+        }
+    };
+}
+
+/// 生成 `byte[] processBatch(byte[])` 形式的 JNI 导出函数，一次调用处理一个 JniBatchRequest
+#[macro_export]
+macro_rules! jni_export_batch {
+    ($name:ident) => {
+        #[no_mangle]
+        pub extern "system" fn $name<'local>(
+            mut env: $crate::jni::JNIEnv<'local>,
+            _class: $crate::jni::objects::JClass<'local>,
+            input: $crate::jni::objects::JByteArray<'local>,
+        ) -> $crate::jni::sys::jbyteArray {
+            $crate::process_batch_jbyte_array(&mut env, &input)
+        }
+    };
+}
+
+/// 生成 `byte[] describe()` 形式的 JNI 导出函数，返回协议能力描述 (JSON)
+#[macro_export]
+macro_rules! jni_export_describe {
+    ($name:ident) => {
+        #[no_mangle]
+        pub extern "system" fn $name<'local>(
+            mut env: $crate::jni::JNIEnv<'local>,
+            _class: $crate::jni::objects::JClass<'local>,
+        ) -> $crate::jni::sys::jbyteArray {
+            $crate::describe_jbyte_array(&mut env)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jni::{
+        objects::JClass,
+        sys::{jarray, jboolean, jbyte, JNINativeInterface_, JNI_FALSE},
+    };
+    use protocol_kernel::bridge::{BridgeFormat, JniBatchRequest, JniBatchResponse};
+    use protocol_kernel::{JniRequest, JniResponse};
+
+    // 用 Box<Vec<u8>> 模拟 Java byte[]，只挂上 byte[] 进出所需的函数
+    unsafe extern "system" fn get_len(_: *mut jni::sys::JNIEnv, a: jarray) -> jsize {
+        (*(a as *mut Vec<u8>)).len() as jsize
+    }
+    unsafe extern "system" fn new_arr(_: *mut jni::sys::JNIEnv, len: jsize) -> jbyteArray {
+        Box::into_raw(Box::new(vec![0u8; len as usize])) as jbyteArray
+    }
+    unsafe extern "system" fn get_region(
+        _: *mut jni::sys::JNIEnv,
+        a: jbyteArray,
+        start: jsize,
+        len: jsize,
+        buf: *mut jbyte,
+    ) {
+        let v = &*(a as *mut Vec<u8>);
+        std::ptr::copy_nonoverlapping(v.as_ptr().add(start as usize), buf as *mut u8, len as usize);
+    }
+    unsafe extern "system" fn set_region(
+        _: *mut jni::sys::JNIEnv,
+        a: jbyteArray,
+        start: jsize,
+        len: jsize,
+        buf: *const jbyte,
+    ) {
+        let v = &mut *(a as *mut Vec<u8>);
+        std::ptr::copy_nonoverlapping(
+            buf as *const u8,
+            v.as_mut_ptr().add(start as usize),
+            len as usize,
+        );
+    }
+    unsafe extern "system" fn exception_check(_: *mut jni::sys::JNIEnv) -> jboolean {
+        JNI_FALSE
+    }
+
+    fn fake_interface() -> JNINativeInterface_ {
+        // 其余函数保持为 None，测试路径上不会用到
+        let mut table: JNINativeInterface_ = unsafe { std::mem::zeroed() };
+        table.GetArrayLength = Some(get_len);
+        table.NewByteArray = Some(new_arr);
+        table.GetByteArrayRegion = Some(get_region);
+        table.SetByteArrayRegion = Some(set_region);
+        table.ExceptionCheck = Some(exception_check);
+        table
+    }
+
+    jni_export!(Java_test_Bridge_process);
+    jni_export_batch!(Java_test_Bridge_processBatch);
+
+    #[test]
+    fn test_export_and_panic_guard() {
+        register_handler(|req: &JniRequest| {
+            if req.hex().is_empty() {
+                panic!("boom");
+            }
+            let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+            rsp.set_req_hex(req.hex());
+            Ok(rsp)
+        });
+
+        let table = fake_interface();
+        let mut raw_env: jni::sys::JNIEnv = &table;
+        let env_ptr: *mut jni::sys::JNIEnv = &mut raw_env;
+        let call = |export: extern "system" fn(JNIEnv, JClass, JByteArray) -> jbyteArray,
+                    input: Vec<u8>| unsafe {
+            let env = JNIEnv::from_raw(env_ptr).unwrap();
+            let input = Box::into_raw(Box::new(input));
+            let out = export(
+                env,
+                JClass::from_raw(std::ptr::null_mut()),
+                JByteArray::from_raw(input as jbyteArray),
+            );
+            drop(Box::from_raw(input));
+            *Box::from_raw(out as *mut Vec<u8>)
+        };
+        let process = |json: &str| {
+            JniResponse::from(&call(Java_test_Bridge_process, json.as_bytes().to_vec())).unwrap()
+        };
+
+        let ok = process(r#"{"hex":"68AA16"}"#);
+        assert!(ok.success());
+        assert_eq!(ok.req_hex(), "68AA16");
+
+        let panicked = process(r#"{"hex":"","deviceNo":"01"}"#);
+        assert!(!panicked.success());
+        assert!(panicked.err_msg().unwrap().contains("boom"));
+        assert_eq!(panicked.device_no(), Some("01"));
+
+        let bad = process("not json");
+        assert!(!bad.success());
+
+        // 一次 JNI 调用处理整批报文，响应格式跟随请求
+        let batch = JniBatchRequest::new(vec![
+            JniRequest::builder().hex("68AA16").build().unwrap(),
+            JniRequest::builder().device_no("02").build().unwrap(),
+        ])
+        .with_batch_id("b1");
+        for format in [BridgeFormat::Json, BridgeFormat::Tlv] {
+            let out = call(
+                Java_test_Bridge_processBatch,
+                batch.to_bytes_with(format).unwrap(),
+            );
+            assert_eq!(BridgeFormat::detect(&out), format);
+            let rsp = JniBatchResponse::from(&out).unwrap();
+            assert_eq!(rsp.batch_id(), Some("b1"));
+            assert_eq!((rsp.total(), rsp.succeeded(), rsp.failed()), (2, 1, 1));
+            assert_eq!(rsp.items()[0].req_hex(), "68AA16");
+            assert!(rsp.items()[1].err_msg().unwrap().contains("boom"));
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! Kafka 输出端: 基于 rdkafka (librdkafka) 实现 kernel 的 `ReportSink`。
+//! 消息的 key 与消息体 (JSON/Avro) 由 kernel 的 `SinkRecord` 生成，这里只负责路由 topic 与投递。
+//!
+//! ```no_run
+//! use protocol_kafka::{KafkaConfig, KafkaSink};
+//! use protocol_kernel::{bridge::sink::ReportSink, JniResponse};
+//!
+//! let sink = KafkaSink::new(KafkaConfig {
+//!     bootstrap: vec!["kafka-1:9092".into(), "kafka-2:9092".into()],
+//!     ..KafkaConfig::default()
+//! })
+//! .unwrap();
+//! let rsp = JniResponse::from(br#"{"success":true,"deviceNo":"01"}"#).unwrap();
+//! sink.publish(&[rsp]).unwrap();
+//! ```
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    mem,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use protocol_kernel::{
+    bridge::sink::{PayloadFormat, RecordKey, ReportSink, SinkRecord},
+    JniResponse, ProtocolError, ProtocolResult,
+};
+use rdkafka::{
+    config::ClientConfig,
+    error::{KafkaError, RDKafkaErrorCode},
+    producer::{BaseRecord, DeliveryResult, Producer, ProducerContext, ThreadedProducer},
+    ClientContext,
+};
+
+#[derive(Debug, Clone)]
+pub struct KafkaConfig {
+    // host:port
+    pub bootstrap: Vec<String>,
+    // 默认 topic
+    pub topic: String,
+    // msg_type -> topic，未列出的 msg_type 发到默认 topic
+    pub topics: HashMap<String, String>,
+    pub client_id: String,
+    pub key: RecordKey,
+    pub format: PayloadFormat,
+    // 0 不等待确认，1 leader 写入即确认，-1 等待全部 ISR
+    pub acks: i16,
+    // 单条消息的投递超时，也是 publish 等待一批消息发完的上限
+    pub timeout: Duration,
+    // 其余 librdkafka 配置 (如 compression.type、security.protocol)，覆盖上面生成的同名项
+    pub properties: HashMap<String, String>,
+}
+
+impl Default for KafkaConfig {
+    fn default() -> Self {
+        Self {
+            bootstrap: vec!["127.0.0.1:9092".into()],
+            topic: "protocol.reports".into(),
+            topics: HashMap::new(),
+            client_id: "protocol-kernel".into(),
+            key: RecordKey::DeviceNo,
+            format: PayloadFormat::Json,
+            acks: 1,
+            timeout: Duration::from_secs(5),
+            properties: HashMap::new(),
+        }
+    }
+}
+
+impl KafkaConfig {
+    /// 转换为 librdkafka 配置。分区器与 Java 客户端的默认分区器一致 (murmur2)，
+    /// 同一设备的消息总在同一分区内保持顺序；没有 key 的消息随机分区
+    pub fn client_config(&self) -> ClientConfig {
+        let mut config = ClientConfig::new();
+        config
+            .set("bootstrap.servers", self.bootstrap.join(","))
+            .set("client.id", &self.client_id)
+            .set("acks", self.acks.to_string())
+            .set("message.timeout.ms", self.timeout.as_millis().to_string())
+            .set("partitioner", "murmur2_random");
+        for (key, value) in &self.properties {
+            config.set(key, value);
+        }
+        config
+    }
+
+    pub fn topic_of(&self, rsp: &JniResponse) -> &str {
+        rsp.msg_type()
+            .and_then(|m| self.topics.get(m))
+            .unwrap_or(&self.topic)
+    }
+}
+
+// 收集后台线程回报的投递失败，publish 在 flush 之后据此返回错误
+#[derive(Default)]
+struct DeliveryLog {
+    failures: Mutex<Vec<String>>,
+}
+
+impl ClientContext for DeliveryLog {}
+
+impl ProducerContext for DeliveryLog {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &DeliveryResult<'_>, _: Self::DeliveryOpaque) {
+        if let Err((e, _)) = result {
+            let mut failures = self.failures.lock().unwrap_or_else(|e| e.into_inner());
+            failures.push(e.to_string());
+        }
+    }
+}
+
+/// Kafka 输出端。librdkafka 在后台线程中攒批、压缩与重试，
+/// publish 把一批消息放入本地队列后等待全部投递完成 (或超时)
+pub struct KafkaSink {
+    config: KafkaConfig,
+    producer: ThreadedProducer<DeliveryLog>,
+    // 同一时间只有一批消息在等待投递结果，失败计数不会混到别的批次
+    publishing: Mutex<()>,
+}
+
+impl KafkaSink {
+    pub fn new(config: KafkaConfig) -> ProtocolResult<Self> {
+        let producer = config
+            .client_config()
+            .create_with_context(DeliveryLog::default())
+            .map_err(kafka_error)?;
+        Ok(Self {
+            config,
+            producer,
+            publishing: Mutex::new(()),
+        })
+    }
+
+    pub fn config(&self) -> &KafkaConfig {
+        &self.config
+    }
+
+    fn send(&self, topic: &str, record: &SinkRecord, deadline: Instant) -> ProtocolResult<()> {
+        let mut base = BaseRecord::<[u8], [u8]>::to(topic)
+            .payload(&record.value)
+            .timestamp(record.timestamp);
+        if let Some(key) = &record.key {
+            base = base.key(key.as_slice());
+        }
+        loop {
+            match self.producer.send(base) {
+                Ok(()) => return Ok(()),
+                // 本地队列满时等后台线程发出一部分再重试
+                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned))
+                    if Instant::now() < deadline =>
+                {
+                    base = returned;
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Err((e, _)) => return Err(kafka_error(e)),
+            }
+        }
+    }
+}
+
+impl ReportSink for KafkaSink {
+    fn publish(&self, batch: &[JniResponse]) -> ProtocolResult<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let _publishing = self.publishing.lock().unwrap_or_else(|e| e.into_inner());
+        let deadline = Instant::now() + self.config.timeout;
+        let sent = batch.iter().try_for_each(|rsp| {
+            let record = SinkRecord::of(rsp, self.config.key, self.config.format)?;
+            self.send(self.config.topic_of(rsp), &record, deadline)
+        });
+        // 中途出错也要等已入队的记录发完并取走失败记录，
+        // 否则它们会算到下一批头上
+        let flushed = self
+            .producer
+            .flush(self.config.timeout)
+            .map_err(kafka_error);
+        let failures = mem::take(
+            &mut *self
+                .producer
+                .context()
+                .failures
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()),
+        );
+        sent?;
+        flushed?;
+        match failures.first() {
+            None => Ok(()),
+            Some(first) => Err(kafka_error(format!(
+                "{} of {} records failed: {}",
+                failures.len(),
+                batch.len(),
+                first
+            ))),
+        }
+    }
+}
+
+fn kafka_error(e: impl Display) -> ProtocolError {
+    ProtocolError::CommonError(format!("kafka: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_kernel::MsgTypeEnum;
+
+    #[test]
+    fn test_client_config() {
+        let mut config = KafkaConfig {
+            bootstrap: vec!["a:9092".into(), "b:9092".into()],
+            acks: -1,
+            ..KafkaConfig::default()
+        };
+        config
+            .properties
+            .insert("compression.type".into(), "lz4".into());
+        config.properties.insert("acks".into(), "0".into());
+        let client = config.client_config();
+        assert_eq!(client.get("bootstrap.servers"), Some("a:9092,b:9092"));
+        assert_eq!(client.get("message.timeout.ms"), Some("5000"));
+        assert_eq!(client.get("partitioner"), Some("murmur2_random"));
+        assert_eq!(client.get("compression.type"), Some("lz4"));
+        // properties 覆盖生成的同名项
+        assert_eq!(client.get("acks"), Some("0"));
+    }
+
+    #[test]
+    fn test_topic_routing() {
+        let mut config = KafkaConfig::default();
+        config
+            .topics
+            .insert(MsgTypeEnum::ValveOperation.code(), "protocol.valves".into());
+        let mut rsp = JniResponse::empty();
+        assert_eq!(config.topic_of(&rsp), "protocol.reports");
+        rsp.set_msg_type(&MsgTypeEnum::ValveOperation);
+        assert_eq!(config.topic_of(&rsp), "protocol.valves");
+        rsp.set_msg_type(&MsgTypeEnum::DataReport);
+        assert_eq!(config.topic_of(&rsp), "protocol.reports");
+    }
+
+    #[test]
+    fn test_publish_empty() {
+        // 创建 producer 不连接 broker，空批次直接返回
+        let sink = KafkaSink::new(KafkaConfig::default()).unwrap();
+        assert!(sink.publish(&[]).is_ok());
+    }
+}
@@ -0,0 +1,204 @@
+//! 终端帧查看器: 读取一条 JniResponse (JSON 或 TLV，文件或标准输入)，
+//! 按字段高亮报文的 hex dump，用方向键/Tab 在字段间移动。
+//!
+//! ```text
+//! frame-inspector [-d|--downstream] [-w <每行字节数>] [FILE]
+//! ```
+//!
+//! 默认查看上行 (req_hex/req_jsons)，`-d` 查看下行 (rsp_hex/rsp_jsons)。
+//! 字段位置来自 ReportField.raw_hex，没有 raw_hex 的字段不会出现在视图中。
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    process::ExitCode,
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::Print,
+    terminal::{self, ClearType},
+};
+use protocol_kernel::{FrameInspector, InspectorKey, JniResponse};
+
+const USAGE: &str = "usage: frame-inspector [-d|--downstream] [-w <bytes-per-line>] [FILE]";
+const HELP: &str = "Tab/→/l 下一个  Shift-Tab/←/h 上一个  ↑/↓ 上下行  Home/End 首尾  q 退出";
+
+struct Options {
+    downstream: bool,
+    bytes_per_line: usize,
+    path: Option<String>,
+}
+
+enum Action {
+    Move(InspectorKey),
+    Quit,
+}
+
+fn main() -> ExitCode {
+    let result = parse_args(env::args().skip(1))
+        .and_then(|options| load(&options))
+        .and_then(run);
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("frame-inspector: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        downstream: false,
+        bytes_per_line: 16,
+        path: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-d" | "--downstream" => options.downstream = true,
+            "-w" => {
+                options.bytes_per_line = args
+                    .next()
+                    .and_then(|w| w.parse().ok())
+                    .filter(|w| *w > 0)
+                    .ok_or(USAGE)?;
+            }
+            "-h" | "--help" => return Err(USAGE.into()),
+            "-" => options.path = None,
+            path if !path.starts_with('-') && options.path.is_none() => {
+                options.path = Some(path.into())
+            }
+            _ => return Err(USAGE.into()),
+        }
+    }
+    Ok(options)
+}
+
+fn load(options: &Options) -> Result<FrameInspector, String> {
+    let data = match &options.path {
+        Some(path) => fs::read(path).map_err(|e| format!("{}: {}", path, e))?,
+        None => {
+            let mut data = Vec::new();
+            io::stdin()
+                .read_to_end(&mut data)
+                .map_err(|e| e.to_string())?;
+            data
+        }
+    };
+    let response = JniResponse::from_auto(&data).map_err(|e| e.to_string())?;
+    let (hex, fields) = if options.downstream {
+        (response.rsp_hex(), response.rsp_jsons())
+    } else {
+        (response.req_hex(), response.req_jsons())
+    };
+    if hex.is_empty() {
+        return Err("response carries no frame hex in the selected direction".into());
+    }
+    FrameInspector::from_report(hex, fields)
+        .map(|inspector| inspector.with_bytes_per_line(options.bytes_per_line))
+        .map_err(|e| e.to_string())
+}
+
+fn run(mut inspector: FrameInspector) -> Result<(), String> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode().map_err(|e| e.to_string())?;
+    let result = execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)
+        .and_then(|_| event_loop(&mut stdout, &mut inspector));
+    // 无论成功与否都恢复终端
+    let _ = execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = terminal::disable_raw_mode();
+    result.map_err(|e| e.to_string())
+}
+
+fn event_loop(stdout: &mut io::Stdout, inspector: &mut FrameInspector) -> io::Result<()> {
+    loop {
+        draw(stdout, inspector)?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        match action_of(key) {
+            Some(Action::Move(key)) => {
+                inspector.handle(key);
+            }
+            Some(Action::Quit) => return Ok(()),
+            None => {}
+        }
+    }
+}
+
+fn draw(stdout: &mut io::Stdout, inspector: &FrameInspector) -> io::Result<()> {
+    // raw mode 下换行不会回到行首
+    let screen = inspector.render().replace('\n', "\r\n");
+    queue!(
+        stdout,
+        terminal::Clear(ClearType::All),
+        cursor::MoveTo(0, 0),
+        Print(screen),
+        Print("\r\n\r\n"),
+        Print(HELP)
+    )?;
+    stdout.flush()
+}
+
+fn action_of(key: KeyEvent) -> Option<Action> {
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+    let action = match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+        KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+        KeyCode::Char(c) => Action::Move(InspectorKey::from_char(c)?),
+        KeyCode::Tab | KeyCode::Right => Action::Move(InspectorKey::Next),
+        KeyCode::BackTab | KeyCode::Left => Action::Move(InspectorKey::Prev),
+        KeyCode::Down => Action::Move(InspectorKey::Down),
+        KeyCode::Up => Action::Move(InspectorKey::Up),
+        KeyCode::Home => Action::Move(InspectorKey::First),
+        KeyCode::End => Action::Move(InspectorKey::Last),
+        _ => return None,
+    };
+    Some(action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Options, String> {
+        parse_args(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let options = args(&["-d", "-w", "8", "frame.json"]).unwrap();
+        assert!(options.downstream);
+        assert_eq!(options.bytes_per_line, 8);
+        assert_eq!(options.path.as_deref(), Some("frame.json"));
+        assert!(args(&[]).unwrap().path.is_none());
+        assert!(args(&["-w", "0"]).is_err());
+        assert!(args(&["a.json", "b.json"]).is_err());
+    }
+
+    #[test]
+    fn test_action_of() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let moved = |code| match action_of(key(code)) {
+            Some(Action::Move(k)) => Some(k),
+            _ => None,
+        };
+        assert_eq!(moved(KeyCode::Tab), Some(InspectorKey::Next));
+        assert_eq!(moved(KeyCode::Left), Some(InspectorKey::Prev));
+        assert_eq!(moved(KeyCode::Char('j')), Some(InspectorKey::Down));
+        assert_eq!(moved(KeyCode::End), Some(InspectorKey::Last));
+        assert!(matches!(
+            action_of(key(KeyCode::Char('q'))),
+            Some(Action::Quit)
+        ));
+        assert!(matches!(
+            action_of(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        ));
+        assert!(action_of(key(KeyCode::Char('x'))).is_none());
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+use protocol_base::ProtocolResult;
+
+use crate::bridge::{
+    dispatch::{register_handler, BridgeHandler},
+    JniRequest, JniResponse, ProtocolDescription,
+};
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// 异步协议处理入口，适用于需要查询 Redis/DB (密钥、余额等) 的协议
+pub trait AsyncBridgeHandler: Send + Sync {
+    fn handle(&self, request: JniRequest) -> BoxFuture<'static, ProtocolResult<JniResponse>>;
+
+    fn describe(&self) -> Option<ProtocolDescription> {
+        None
+    }
+}
+
+impl<F, Fut> AsyncBridgeHandler for F
+where
+    F: Fn(JniRequest) -> Fut + Send + Sync,
+    Fut: Future<Output = ProtocolResult<JniResponse>> + Send + 'static,
+{
+    fn handle(&self, request: JniRequest) -> BoxFuture<'static, ProtocolResult<JniResponse>> {
+        Box::pin(self(request))
+    }
+}
+
+/// 在宿主调用线程上驱动异步 handler 的执行器。
+/// 使用 tokio 时可以这样接入:
+///
+/// ```ignore
+/// struct TokioExecutor(tokio::runtime::Handle);
+///
+/// impl BridgeExecutor for TokioExecutor {
+///     fn block_on(&self, f: BoxFuture<'static, ProtocolResult<JniResponse>>) -> ProtocolResult<JniResponse> {
+///         self.0.block_on(f)
+///     }
+/// }
+/// ```
+pub trait BridgeExecutor: Send + Sync {
+    fn block_on(
+        &self,
+        future: BoxFuture<'static, ProtocolResult<JniResponse>>,
+    ) -> ProtocolResult<JniResponse>;
+}
+
+/// 不依赖任何运行时的默认执行器: 在当前线程上 poll，Pending 时 park 等待唤醒
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThreadParkExecutor;
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+// 在当前线程上驱动 future 直到完成
+pub(crate) fn park_block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(result) => return result,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+impl BridgeExecutor for ThreadParkExecutor {
+    fn block_on(
+        &self,
+        future: BoxFuture<'static, ProtocolResult<JniResponse>>,
+    ) -> ProtocolResult<JniResponse> {
+        park_block_on(future)
+    }
+}
+
+/// 把异步 handler 适配成同步的 BridgeHandler，JNI/FFI 层无需改动
+pub struct BlockingAdapter<H, E> {
+    handler: H,
+    executor: E,
+}
+
+impl<H: AsyncBridgeHandler, E: BridgeExecutor> BlockingAdapter<H, E> {
+    pub fn new(handler: H, executor: E) -> Self {
+        Self { handler, executor }
+    }
+}
+
+impl<H: AsyncBridgeHandler, E: BridgeExecutor> BridgeHandler for BlockingAdapter<H, E> {
+    fn handle(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        self.executor.block_on(self.handler.handle(request.clone()))
+    }
+
+    fn describe(&self) -> Option<ProtocolDescription> {
+        self.handler.describe()
+    }
+}
+
+/// 注册异步 handler，由 executor 在调用线程上等待结果
+pub fn register_async_handler<H, E>(handler: H, executor: E)
+where
+    H: AsyncBridgeHandler + 'static,
+    E: BridgeExecutor + 'static,
+{
+    register_handler(BlockingAdapter::new(handler, executor));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::mpsc, time::Duration};
+
+    #[test]
+    fn test_block_on_wakes_from_other_thread() {
+        let handler = |req: JniRequest| async move {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(10));
+                tx.send(()).unwrap();
+            });
+            Recv(rx).await;
+            let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+            rsp.set_req_hex(req.hex());
+            Ok(rsp)
+        };
+        let adapter = BlockingAdapter::new(handler, ThreadParkExecutor);
+        let req = JniRequest::from(br#"{"hex":"68AA16"}"#).unwrap();
+        let rsp = BridgeHandler::handle(&adapter, &req).unwrap();
+        assert_eq!(rsp.req_hex(), "68AA16");
+    }
+
+    // 轮询式的最小 future，只用来验证 Pending -> wake -> Ready 路径
+    struct Recv(mpsc::Receiver<()>);
+
+    impl Future for Recv {
+        type Output = ();
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            match self.0.try_recv() {
+                Ok(()) => Poll::Ready(()),
+                Err(_) => {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+    }
+}
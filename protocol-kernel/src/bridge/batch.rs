@@ -0,0 +1,230 @@
+use protocol_base::{ProtocolError, ProtocolResult};
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::{
+    dispatch, guard::catch_panic, tlv, trace, BridgeFormat, JniRequest, JniResponse,
+};
+
+/// 一次 JNI 调用携带多帧报文。网关通常一次上传几十帧，
+/// 逐帧跨越 JNI + JSON 的开销是吞吐的瓶颈
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct JniBatchRequest {
+    #[serde(default)]
+    pub(crate) batch_id: Option<String>,
+    #[serde(default)]
+    pub(crate) items: Vec<JniRequest>,
+}
+
+/// 批量结果，items 与请求一一对应 (顺序一致)，每一项独立成功或失败
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct JniBatchResponse {
+    #[serde(default)]
+    pub(crate) batch_id: Option<String>,
+    #[serde(default)]
+    pub(crate) total: usize,
+    #[serde(default)]
+    pub(crate) succeeded: usize,
+    #[serde(default)]
+    pub(crate) failed: usize,
+    #[serde(default)]
+    pub(crate) items: Vec<JniResponse>,
+}
+
+impl JniBatchRequest {
+    pub fn new(items: Vec<JniRequest>) -> Self {
+        Self {
+            batch_id: None,
+            items,
+        }
+    }
+
+    pub fn with_batch_id(mut self, batch_id: &str) -> Self {
+        self.batch_id = Some(batch_id.into());
+        self
+    }
+
+    pub fn push(&mut self, item: JniRequest) {
+        self.items.push(item);
+    }
+
+    pub fn batch_id(&self) -> Option<&str> {
+        self.batch_id.as_deref()
+    }
+
+    pub fn items(&self) -> &[JniRequest] {
+        &self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 逐条处理。单条失败 (包括 panic) 不会中断整批，错误会转成该条的 JniResponse
+    pub fn process<F>(&self, mut handler: F) -> JniBatchResponse
+    where
+        F: FnMut(&JniRequest) -> ProtocolResult<JniResponse>,
+    {
+        let items = self
+            .items
+            .iter()
+            .map(|req| {
+                let _trace = trace::enter(req.trace_id());
+                let mut rsp = catch_panic(|| handler(req)).unwrap_or_else(|e| {
+                    JniResponse::new_with_err(&req.device_no_clone(), &req.cmd_code_clone(), &e)
+                });
+                dispatch::echo_request_fields(req, &mut rsp);
+                rsp
+            })
+            .collect();
+        JniBatchResponse::new(self.batch_id.clone(), items)
+    }
+
+    pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
+        self.to_bytes_with(BridgeFormat::Json)
+    }
+
+    pub fn to_bytes_with(&self, format: BridgeFormat) -> ProtocolResult<Vec<u8>> {
+        match format {
+            BridgeFormat::Json => {
+                serde_json::to_vec(self).map_err(|e| ProtocolError::CommonError(e.to_string()))
+            }
+            BridgeFormat::Tlv => Ok(tlv::encode_batch_request(self)),
+        }
+    }
+
+    /// 根据首字节自动识别 Json/Tlv
+    pub fn from(data: &[u8]) -> ProtocolResult<Self> {
+        match BridgeFormat::detect(data) {
+            BridgeFormat::Json => {
+                serde_json::from_slice(data).map_err(|e| ProtocolError::CommonError(e.to_string()))
+            }
+            BridgeFormat::Tlv => tlv::decode_batch_request(data),
+        }
+    }
+}
+
+impl JniBatchResponse {
+    pub fn new(batch_id: Option<String>, items: Vec<JniResponse>) -> Self {
+        let succeeded = items.iter().filter(|r| r.success()).count();
+        Self {
+            batch_id,
+            total: items.len(),
+            succeeded,
+            failed: items.len() - succeeded,
+            items,
+        }
+    }
+
+    pub fn batch_id(&self) -> Option<&str> {
+        self.batch_id.as_deref()
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn succeeded(&self) -> usize {
+        self.succeeded
+    }
+
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    pub fn items(&self) -> &[JniResponse] {
+        &self.items
+    }
+
+    pub fn into_items(self) -> Vec<JniResponse> {
+        self.items
+    }
+
+    pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
+        self.to_bytes_with(BridgeFormat::Json)
+    }
+
+    pub fn to_bytes_with(&self, format: BridgeFormat) -> ProtocolResult<Vec<u8>> {
+        match format {
+            BridgeFormat::Json => {
+                serde_json::to_vec(self).map_err(|e| ProtocolError::CommonError(e.to_string()))
+            }
+            BridgeFormat::Tlv => Ok(tlv::encode_batch_response(self)),
+        }
+    }
+
+    /// 根据首字节自动识别 Json/Tlv
+    pub fn from(data: &[u8]) -> ProtocolResult<Self> {
+        match BridgeFormat::detect(data) {
+            BridgeFormat::Json => {
+                serde_json::from_slice(data).map_err(|e| ProtocolError::CommonError(e.to_string()))
+            }
+            BridgeFormat::Tlv => tlv::decode_batch_response(data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_per_item() {
+        let batch = JniBatchRequest::new(vec![
+            JniRequest::builder()
+                .device_no("01")
+                .hex("AA")
+                .build()
+                .unwrap(),
+            JniRequest::builder()
+                .device_no("02")
+                .hex("")
+                .build()
+                .unwrap(),
+        ])
+        .with_batch_id("b1");
+        let rsp = batch.process(|req| {
+            if req.hex().is_empty() {
+                return Err(ProtocolError::ValidationFailed("empty frame".into()));
+            }
+            let mut ok = JniResponse::empty();
+            ok.set_device_no(&req.device_no_clone());
+            Ok(ok)
+        });
+        assert_eq!((rsp.total(), rsp.succeeded(), rsp.failed()), (2, 1, 1));
+        assert_eq!(rsp.items()[1].device_no(), Some("02"));
+
+        for format in [BridgeFormat::Json, BridgeFormat::Tlv] {
+            let back = JniBatchResponse::from(&rsp.to_bytes_with(format).unwrap()).unwrap();
+            assert_eq!(back.batch_id(), Some("b1"));
+            assert_eq!(back.failed(), 1);
+            let req = JniBatchRequest::from(&batch.to_bytes_with(format).unwrap()).unwrap();
+            assert_eq!(req.items()[0].hex(), "AA");
+        }
+    }
+
+    #[test]
+    fn test_process_catches_panic() {
+        let batch = JniBatchRequest::new(vec![
+            JniRequest::builder().hex("AA").build().unwrap(),
+            JniRequest::builder().hex("BB").build().unwrap(),
+        ]);
+        let rsp = batch.process(|req| {
+            let bytes = req.hex().as_bytes();
+            if bytes[0] == b'A' {
+                let _ = bytes[bytes.len() + 1];
+            }
+            Ok(JniResponse::empty())
+        });
+        assert_eq!((rsp.succeeded(), rsp.failed()), (1, 1));
+        assert!(rsp.items()[0]
+            .err_msg()
+            .unwrap()
+            .contains("index out of bounds"));
+    }
+}
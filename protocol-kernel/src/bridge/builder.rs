@@ -0,0 +1,180 @@
+use std::collections::{BTreeSet, HashMap};
+
+use protocol_base::{ProtocolError, ProtocolResult};
+
+use crate::{bridge::JniRequest, AutoEncodingParam, DecodeMode, MsgTypeEnum};
+
+/// JniRequest 的链式构造器。build 时做完整校验，非法请求在分发之前就以
+/// ValidationFailed 拒绝，而不是在解码中途失败
+#[derive(Debug, Clone, Default)]
+pub struct JniRequestBuilder {
+    device_id: Option<String>,
+    device_no: Option<String>,
+    msg_type: Option<String>,
+    cmd_code: Option<String>,
+    hex: String,
+    uri: Option<String>,
+    params: Option<HashMap<String, String>>,
+    trace_id: Option<String>,
+    decode_mode: Option<DecodeMode>,
+    // cmd_code -> 该命令可接受的参数 key
+    allowed_params: HashMap<String, BTreeSet<String>>,
+}
+
+impl JniRequestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn device_id(mut self, device_id: &str) -> Self {
+        self.device_id = Some(device_id.into());
+        self
+    }
+
+    pub fn device_no(mut self, device_no: &str) -> Self {
+        self.device_no = Some(device_no.into());
+        self
+    }
+
+    pub fn msg_type(mut self, msg_type: &str) -> Self {
+        self.msg_type = Some(msg_type.into());
+        self
+    }
+
+    pub fn cmd_code(mut self, cmd_code: &str) -> Self {
+        self.cmd_code = Some(cmd_code.into());
+        self
+    }
+
+    pub fn hex(mut self, hex: &str) -> Self {
+        self.hex = hex.into();
+        self
+    }
+
+    pub fn uri(mut self, uri: &str) -> Self {
+        self.uri = Some(uri.into());
+        self
+    }
+
+    pub fn trace_id(mut self, trace_id: &str) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    pub fn decode_mode(mut self, mode: DecodeMode) -> Self {
+        self.decode_mode = Some(mode);
+        self
+    }
+
+    pub fn param(mut self, key: &str, value: &str) -> Self {
+        self.params
+            .get_or_insert_with(HashMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+
+    pub fn params(mut self, params: HashMap<String, String>) -> Self {
+        self.params.get_or_insert_with(HashMap::new).extend(params);
+        self
+    }
+
+    /// 声明某个命令码可接受的参数 key。未声明的命令码不校验参数
+    pub fn allowed_params(mut self, cmd_code: &str, keys: &[&str]) -> Self {
+        self.allowed_params
+            .entry(cmd_code.into())
+            .or_default()
+            .extend(keys.iter().map(|k| k.to_string()));
+        self
+    }
+
+    /// 根据下行参数定义 (AutoEncodingParam::code) 声明可接受的参数 key
+    pub fn allowed_params_from<T: AutoEncodingParam>(mut self, cmd_code: &str, defs: &[T]) -> Self {
+        self.allowed_params
+            .entry(cmd_code.into())
+            .or_default()
+            .extend(defs.iter().map(|d| d.code()));
+        self
+    }
+
+    pub fn build(self) -> ProtocolResult<JniRequest> {
+        self.validate()?;
+        let mut request = JniRequest::new(
+            self.device_id,
+            self.device_no,
+            self.msg_type,
+            self.cmd_code,
+            self.hex,
+            self.uri,
+            self.params,
+        );
+        request.trace_id = self.trace_id;
+        request.decode_mode = self.decode_mode;
+        Ok(request)
+    }
+
+    fn validate(&self) -> ProtocolResult<()> {
+        if !self.hex.len().is_multiple_of(2) || !self.hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "request hex '{}' is not a well-formed hex string (even length, 0-9A-F)",
+                self.hex
+            )));
+        }
+        if let Some(msg_type) = self.msg_type.as_deref() {
+            if !msg_type.is_empty() && MsgTypeEnum::code_of(msg_type).is_err() {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "unknown msg_type '{}'",
+                    msg_type
+                )));
+            }
+        }
+        let allowed = self
+            .cmd_code
+            .as_ref()
+            .and_then(|code| self.allowed_params.get(code));
+        if let (Some(allowed), Some(params)) = (allowed, self.params.as_ref()) {
+            let mut unknown: Vec<&str> = params
+                .keys()
+                .filter(|k| !allowed.contains(*k))
+                .map(|k| k.as_str())
+                .collect();
+            if !unknown.is_empty() {
+                unknown.sort();
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "cmd_code '{}' does not accept params [{}]. Accepted: [{}]",
+                    self.cmd_code.as_deref().unwrap_or_default(),
+                    unknown.join(", "),
+                    allowed.iter().cloned().collect::<Vec<_>>().join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_and_validate() {
+        let req = JniRequestBuilder::new()
+            .device_no("0001")
+            .msg_type("data_report")
+            .cmd_code("01")
+            .hex("68AA16")
+            .param("price", "3.5")
+            .allowed_params("01", &["price"])
+            .build()
+            .unwrap();
+        assert_eq!(req.hex(), "68AA16");
+        assert!(JniRequestBuilder::new().hex("68A").build().is_err());
+        assert!(JniRequestBuilder::new().msg_type("nope").build().is_err());
+        let err = JniRequestBuilder::new()
+            .cmd_code("01")
+            .param("volume", "1")
+            .allowed_params("01", &["price"])
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("volume"));
+    }
+}
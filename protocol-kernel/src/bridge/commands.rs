@@ -0,0 +1,288 @@
+//! 业务级下行命令。平台按业务操作构造命令 (充值、开关阀、调价)，
+//! 由各协议在 ProtocolHandler::command_mapping 中声明命令码与参数 key 的对应关系，
+//! 不再直接拼装字符串 key 的 params:
+//!
+//! ```ignore
+//! let request = commands::recharge("20250918", dec!(100), dec!(3.5)).to_request("demo/gas")?;
+//! ```
+use std::collections::{BTreeMap, HashMap};
+
+use protocol_base::{ProtocolError, ProtocolResult};
+use rust_decimal::Decimal;
+
+use crate::{
+    bridge::{registry, JniRequest, JniRequestBuilder},
+    AutoEncoding, AutoEncodingParam, MsgTypeEnum,
+};
+
+/// 业务参数 key，与协议无关
+pub const AMOUNT: &str = "amount";
+pub const PRICE: &str = "price";
+pub const BALANCE: &str = "balance";
+pub const VALVE: &str = "valve";
+
+pub const VALVE_OPEN: &str = "open";
+pub const VALVE_CLOSE: &str = "close";
+
+/// 一次业务操作
+#[derive(Debug, Clone)]
+pub struct BusinessCommand {
+    pub msg_type: MsgTypeEnum,
+    pub device_no: String,
+    // 业务参数 key -> 值
+    pub params: BTreeMap<String, String>,
+    pub trace_id: Option<String>,
+}
+
+/// 充值: 金额与当前单价
+pub fn recharge(device_no: &str, amount: Decimal, price: Decimal) -> BusinessCommand {
+    BusinessCommand::new(MsgTypeEnum::Recharge, device_no)
+        .with_param(AMOUNT, &amount.normalize().to_string())
+        .with_param(PRICE, &price.normalize().to_string())
+}
+
+pub fn open_valve(device_no: &str) -> BusinessCommand {
+    BusinessCommand::new(MsgTypeEnum::ValveOperation, device_no).with_param(VALVE, VALVE_OPEN)
+}
+
+pub fn close_valve(device_no: &str) -> BusinessCommand {
+    BusinessCommand::new(MsgTypeEnum::ValveOperation, device_no).with_param(VALVE, VALVE_CLOSE)
+}
+
+pub fn update_price(device_no: &str, price: Decimal) -> BusinessCommand {
+    BusinessCommand::new(MsgTypeEnum::UpdateGasPrice, device_no)
+        .with_param(PRICE, &price.normalize().to_string())
+}
+
+/// 余额同步 (中心计费)
+pub fn sync_balance(device_no: &str, balance: Decimal) -> BusinessCommand {
+    BusinessCommand::new(MsgTypeEnum::BalanceSync, device_no)
+        .with_param(BALANCE, &balance.normalize().to_string())
+}
+
+impl BusinessCommand {
+    pub fn new(msg_type: MsgTypeEnum, device_no: &str) -> Self {
+        Self {
+            msg_type,
+            device_no: device_no.into(),
+            params: BTreeMap::new(),
+            trace_id: None,
+        }
+    }
+
+    pub fn with_param(mut self, key: &str, value: &str) -> Self {
+        self.params.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_trace_id(mut self, trace_id: &str) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    /// 按已注册协议的 command_mapping 生成请求
+    pub fn to_request(&self, protocol_id: &str) -> ProtocolResult<JniRequest> {
+        let handler = registry::protocol_handler(protocol_id)?;
+        let mapping = handler.command_mapping(&self.msg_type).ok_or_else(|| {
+            ProtocolError::UnsupportedMode(format!(
+                "protocol '{}' has no command for msg type '{}'",
+                protocol_id,
+                self.msg_type.code()
+            ))
+        })?;
+        let mut builder = mapping.apply(self)?.uri(protocol_id);
+        if let Some(trace_id) = &self.trace_id {
+            builder = builder.trace_id(trace_id);
+        }
+        builder.build()
+    }
+}
+
+/// 协议对一种业务操作的实现: 命令码、业务参数 key 到协议参数 code 的映射，
+/// 以及需要转换的取值 (如开阀 -> "55")
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandMapping {
+    pub cmd_code: String,
+    // 业务参数 key -> 协议参数 code
+    pub params: HashMap<String, String>,
+    // (业务参数 key, 业务取值) -> 协议取值，未声明的取值原样传递
+    pub values: HashMap<(String, String), String>,
+    // 与业务参数无关的固定参数
+    pub fixed: HashMap<String, String>,
+}
+
+impl CommandMapping {
+    pub fn new(cmd_code: &str) -> Self {
+        Self {
+            cmd_code: cmd_code.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn param(mut self, business_key: &str, code: &str) -> Self {
+        self.params.insert(business_key.into(), code.into());
+        self
+    }
+
+    pub fn value(mut self, business_key: &str, business_value: &str, value: &str) -> Self {
+        self.values
+            .insert((business_key.into(), business_value.into()), value.into());
+        self
+    }
+
+    pub fn fixed(mut self, code: &str, value: &str) -> Self {
+        self.fixed.insert(code.into(), value.into());
+        self
+    }
+
+    /// 检查映射到的参数 code 都在协议的下行参数表中，供协议实现的单元测试使用
+    pub fn validate_against<P, E>(&self, encoding: &E) -> ProtocolResult<()>
+    where
+        P: AutoEncodingParam,
+        E: AutoEncoding<P>,
+    {
+        let variants = encoding.variants();
+        let known = |code: &str| {
+            variants.iter().any(|p| {
+                p.code() == code
+                    && (p.cmd_code().is_empty()
+                        || p.cmd_code().eq_ignore_ascii_case(&self.cmd_code))
+            })
+        };
+        let mut missing: Vec<&str> = self
+            .params
+            .values()
+            .chain(self.fixed.keys())
+            .map(|c| c.as_str())
+            .filter(|c| !known(c))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        missing.sort();
+        Err(ProtocolError::ValidationFailed(format!(
+            "cmd_code '{}' has no params [{}]",
+            self.cmd_code,
+            missing.join(", ")
+        )))
+    }
+
+    fn apply(&self, command: &BusinessCommand) -> ProtocolResult<JniRequestBuilder> {
+        let mut params = self.fixed.clone();
+        for (key, value) in &command.params {
+            let code = self.params.get(key).ok_or_else(|| {
+                ProtocolError::ValidationFailed(format!(
+                    "cmd_code '{}' has no mapping for business param '{}'",
+                    self.cmd_code, key
+                ))
+            })?;
+            let value = self
+                .values
+                .get(&(key.clone(), value.clone()))
+                .unwrap_or(value);
+            params.insert(code.clone(), value.clone());
+        }
+        let allowed: Vec<&str> = params.keys().map(|k| k.as_str()).collect();
+        Ok(JniRequestBuilder::new()
+            .device_no(&command.device_no)
+            .msg_type(&command.msg_type.code())
+            .cmd_code(&self.cmd_code)
+            .allowed_params(&self.cmd_code, &allowed)
+            .params(params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::{
+        bridge::registry::{register_protocol, unregister_protocol},
+        core::dsl::ProtocolDefinition,
+        JniResponse, ProtocolHandler,
+    };
+
+    struct Gas;
+
+    impl ProtocolHandler for Gas {
+        fn decode_upstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+            JniResponse::from(br#"{"success":true}"#)
+        }
+
+        fn encode_downstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+            JniResponse::from(br#"{"success":true}"#)
+        }
+
+        fn command_mapping(&self, msg_type: &MsgTypeEnum) -> Option<CommandMapping> {
+            match msg_type {
+                MsgTypeEnum::Recharge => Some(
+                    CommandMapping::new("31")
+                        .param(AMOUNT, "chongzhijine")
+                        .param(PRICE, "danjia"),
+                ),
+                MsgTypeEnum::ValveOperation => Some(
+                    CommandMapping::new("33")
+                        .param(VALVE, "famen")
+                        .value(VALVE, VALVE_OPEN, "55")
+                        .value(VALVE, VALVE_CLOSE, "99"),
+                ),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_request() {
+        register_protocol("test/commands", Gas);
+        let request = recharge("20250918", dec!(100.00), dec!(3.50))
+            .with_trace_id("t-1")
+            .to_request("test/commands")
+            .unwrap();
+        assert_eq!(request.cmd_code(), Some("31"));
+        assert_eq!(request.msg_type(), Some("charge_operation"));
+        assert_eq!(request.uri(), Some("test/commands"));
+        let params = request.params().unwrap();
+        assert_eq!(params["chongzhijine"], "100");
+        assert_eq!(params["danjia"], "3.5");
+
+        let request = close_valve("20250918").to_request("test/commands").unwrap();
+        assert_eq!(request.params().unwrap()["famen"], "99");
+
+        assert!(update_price("20250918", dec!(3))
+            .to_request("test/commands")
+            .is_err());
+        assert!(open_valve("20250918")
+            .with_param(AMOUNT, "1")
+            .to_request("test/commands")
+            .is_err());
+        unregister_protocol("test/commands");
+    }
+
+    #[test]
+    fn test_validate_against() {
+        let definition = ProtocolDefinition::from_json(
+            r#"{"protocol":"demo","downstream":[{"code":"danjia","title":"单价","length":4,"type":"u32","cmd_code":"31"}]}"#,
+        )
+        .unwrap();
+        let encoding = definition.encoding("31");
+        let mapping = CommandMapping::new("31").param(PRICE, "danjia");
+        assert!(mapping.validate_against(&encoding).is_ok());
+        let err = mapping
+            .param(AMOUNT, "chongzhijine")
+            .validate_against(&encoding)
+            .unwrap_err();
+        assert!(err.to_string().contains("chongzhijine"));
+
+        // cmd_code 不区分大小写，与 param_schema_for 一致
+        let definition = ProtocolDefinition::from_json(
+            r#"{"protocol":"demo","downstream":[{"code":"danjia","title":"单价","length":4,"type":"u32","cmd_code":"1A"}]}"#,
+        )
+        .unwrap();
+        let encoding = definition.encoding("1A");
+        assert!(CommandMapping::new("1a")
+            .param(PRICE, "danjia")
+            .validate_against(&encoding)
+            .is_ok());
+    }
+}
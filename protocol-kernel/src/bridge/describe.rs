@@ -0,0 +1,232 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bridge::frame_doc::{decoding_type, type_name},
+    core::parts::schema::ParamSchema,
+    utils::to_pinyin_cached,
+    AutoDecoding, AutoDecodingParam, Cmd, DirectionEnum, JniResponse, ProtocolError, TryFromBytes,
+    RW,
+};
+
+/// 协议能力描述，供平台探查新部署的 .so 能处理哪些命令
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProtocolDescription {
+    pub protocol: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub commands: Vec<CommandDescription>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandDescription {
+    pub code: String,
+    pub title: String,
+    pub direction: String,
+    #[serde(default)]
+    pub rw: Option<String>,
+    #[serde(default)]
+    pub msg_type: Option<String>,
+    #[serde(default)]
+    pub params: Vec<ParamSchema>,
+    // 上行解码字段，与 rspJsons 中的字段一一对应
+    #[serde(default)]
+    pub fields: Vec<FieldSchema>,
+}
+
+/// 上行解码字段的描述
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldSchema {
+    pub title: String,
+    // 与 ReportField.code 相同，由标题转拼音
+    pub code: String,
+    // 类型名，与协议定义文件 (dsl) 中的 type 一致，比较/枚举字段为空
+    pub field_type: String,
+    // 0 表示变长
+    pub length: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+}
+
+impl ProtocolDescription {
+    pub fn new(protocol: &str, version: &str) -> Self {
+        Self {
+            protocol: protocol.into(),
+            version: version.into(),
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn with_command(mut self, command: CommandDescription) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_else(|e| {
+            JniResponse::from_error(&ProtocolError::CommonError(e.to_string()))
+                .to_bytes()
+                .unwrap_or_default()
+        })
+    }
+}
+
+impl CommandDescription {
+    /// 由命令定义生成描述，params 为该命令的下行参数 (见 schema::param_schema_for)
+    pub fn of<T: Cmd>(cmd: &T, params: Vec<ParamSchema>) -> Self {
+        Self {
+            code: cmd.code(),
+            title: cmd.title(),
+            direction: direction_code(&cmd.direction()).into(),
+            rw: cmd.rw().map(|rw| rw_code(&rw).into()),
+            msg_type: cmd.msg_type().map(|m| m.code()),
+            params,
+            fields: Vec::new(),
+        }
+    }
+
+    /// 添加上行解码字段。声明了其他 cmd_code 的字段不属于本命令，跳过
+    pub fn with_fields<P, U, D>(mut self, decoding: &D) -> Self
+    where
+        P: AutoDecodingParam<U>,
+        U: TryFromBytes,
+        D: AutoDecoding<P, U>,
+    {
+        let fields = decoding
+            .variants()
+            .iter()
+            .filter(|p| {
+                let code = p.cmd_code();
+                code.is_empty() || code.eq_ignore_ascii_case(&self.code)
+            })
+            .map(|p| FieldSchema {
+                code: to_pinyin_cached(&p.title()),
+                title: p.title(),
+                field_type: type_name(&decoding_type(p)).into(),
+                length: p.byte_length(),
+                unit: p.symbol().map(|s| s.tag()),
+                scale: p.field_type().scale().filter(|s| *s != 1.0),
+            })
+            .collect();
+        self.fields = fields;
+        self
+    }
+}
+
+pub(crate) fn direction_code(direction: &DirectionEnum) -> &'static str {
+    match direction {
+        DirectionEnum::Upstream => "upstream",
+        DirectionEnum::Downstream => "downstream",
+        DirectionEnum::Both => "both",
+    }
+}
+
+pub(crate) fn rw_code(rw: &RW) -> &'static str {
+    match rw {
+        RW::Read => "read",
+        RW::Write => "write",
+        RW::WriteThenRead => "write_then_read",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldType, Symbol};
+
+    #[derive(Clone)]
+    struct Query;
+
+    impl Cmd for Query {
+        fn code(&self) -> String {
+            "A1".into()
+        }
+        fn title(&self) -> String {
+            "查询".into()
+        }
+        fn direction(&self) -> DirectionEnum {
+            DirectionEnum::Downstream
+        }
+        fn rw(&self) -> Option<RW> {
+            Some(RW::Read)
+        }
+    }
+
+    #[test]
+    fn test_description_json() {
+        let desc = ProtocolDescription::new("demo", "1.0")
+            .with_command(CommandDescription::of(&Query, vec![]));
+        let json = String::from_utf8(desc.to_bytes()).unwrap();
+        assert!(json.contains("\"code\":\"A1\""));
+        assert!(json.contains("\"direction\":\"downstream\""));
+        assert!(json.contains("\"rw\":\"read\""));
+        let back: ProtocolDescription = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, desc);
+    }
+
+    enum Reading {
+        Total,
+        Balance,
+    }
+
+    impl AutoDecodingParam for Reading {
+        fn byte_length(&self) -> usize {
+            4
+        }
+
+        fn title(&self) -> String {
+            match self {
+                Reading::Total => "累计用量",
+                Reading::Balance => "余额",
+            }
+            .into()
+        }
+
+        fn cmd_code(&self) -> String {
+            match self {
+                Reading::Total => String::new(),
+                Reading::Balance => "B2".into(),
+            }
+        }
+
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU32(0.01)
+        }
+
+        fn symbol(&self) -> Option<Symbol> {
+            matches!(self, Reading::Total).then_some(Symbol::CubicMeter)
+        }
+    }
+
+    struct Readings;
+
+    impl AutoDecoding<Reading> for Readings {
+        fn variants(&self) -> Vec<Reading> {
+            vec![Reading::Total, Reading::Balance]
+        }
+    }
+
+    #[test]
+    fn test_description_fields() {
+        let desc = CommandDescription::of(&Query, vec![]).with_fields(&Readings);
+        // 余额属于 B2，不列入 A1
+        assert_eq!(desc.fields.len(), 1);
+        let field = &desc.fields[0];
+        assert_eq!(field.title, "累计用量");
+        assert_eq!(field.code, to_pinyin_cached("累计用量"));
+        assert_eq!(field.field_type, "u32");
+        assert_eq!(field.length, 4);
+        assert_eq!(field.unit, Some(Symbol::CubicMeter.tag()));
+        assert_eq!(field.scale, Some(0.01));
+
+        let json = serde_json::to_string(&desc).unwrap();
+        assert!(json.contains("\"fieldType\":\"u32\""));
+        let back: CommandDescription = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, desc);
+    }
+}
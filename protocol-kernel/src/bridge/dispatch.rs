@@ -0,0 +1,122 @@
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use protocol_base::{ProtocolError, ProtocolResult};
+
+use crate::bridge::{
+    batch::JniBatchRequest,
+    guard::catch_panic,
+    registry::{self, ProtocolRouter},
+    trace, BridgeFormat, JniRequest, JniResponse, ProtocolDescription,
+};
+
+/// 协议处理入口。JNI、C FFI 等宿主绑定层都通过它调用具体协议
+pub trait BridgeHandler: Send + Sync {
+    fn handle(&self, request: &JniRequest) -> ProtocolResult<JniResponse>;
+
+    /// 协议能力描述，未实现时 describe_protocol 返回错误响应
+    fn describe(&self) -> Option<ProtocolDescription> {
+        None
+    }
+}
+
+impl<F> BridgeHandler for F
+where
+    F: Fn(&JniRequest) -> ProtocolResult<JniResponse> + Send + Sync,
+{
+    fn handle(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        self(request)
+    }
+}
+
+static HANDLER: Lazy<RwLock<Option<Arc<dyn BridgeHandler>>>> = Lazy::new(|| RwLock::new(None));
+
+/// 注册 (或替换) 全局 handler
+pub fn register_handler<H: BridgeHandler + 'static>(handler: H) {
+    let mut guard = HANDLER.write().unwrap_or_else(|e| e.into_inner());
+    *guard = Some(Arc::new(handler));
+}
+
+// 没有全局 handler 时，若注册过协议则按 uri 路由
+fn current_handler() -> ProtocolResult<Arc<dyn BridgeHandler>> {
+    let guard = HANDLER.read().unwrap_or_else(|e| e.into_inner());
+    guard
+        .clone()
+        .or_else(|| {
+            registry::has_protocols().then(|| Arc::new(ProtocolRouter) as Arc<dyn BridgeHandler>)
+        })
+        .ok_or_else(|| ProtocolError::CommonError("no protocol handler registered".into()))
+}
+
+/// 处理一次调用: 反序列化请求 -> 调用 handler -> 序列化响应。
+/// 响应使用与请求相同的格式 (Json/Tlv)，panic 和错误都转成 success=false 的响应，
+/// 保证不会越过 FFI 边界
+pub fn process_bytes(input: &[u8]) -> Vec<u8> {
+    let format = BridgeFormat::detect(input);
+    let response = match JniRequest::from_with(input, format) {
+        Ok(request) => {
+            let _trace = trace::enter(request.trace_id());
+            let result = catch_panic(|| current_handler().and_then(|h| h.handle(&request)));
+            let mut response = result.unwrap_or_else(|e| {
+                JniResponse::new_with_err(&request.device_no_clone(), &request.cmd_code_clone(), &e)
+            });
+            echo_request_fields(&request, &mut response);
+            response
+        }
+        Err(e) => JniResponse::from_error(&e),
+    };
+    encode_response(&response, format)
+}
+
+/// 一次调用处理一批报文 (JniBatchRequest，Json/Tlv 自动识别)，响应为同格式的 JniBatchResponse。
+/// 每一帧独立成功或失败；批量请求本身无法解析或没有注册 handler 时，
+/// 返回与 `process_bytes` 相同的 success=false 的 JniResponse
+pub fn process_batch_bytes(input: &[u8]) -> Vec<u8> {
+    let format = BridgeFormat::detect(input);
+    let handler = JniBatchRequest::from(input).and_then(|batch| Ok((batch, current_handler()?)));
+    match handler {
+        Ok((batch, handler)) => {
+            let response = batch.process(|req| handler.handle(req));
+            response.to_bytes_with(format).unwrap_or_else(|e| {
+                encode_response(&JniResponse::from_error(&e), BridgeFormat::Json)
+            })
+        }
+        Err(e) => encode_response(&JniResponse::from_error(&e), format),
+    }
+}
+
+/// 返回已注册协议的能力描述 (JSON)。未注册或未实现 describe 时返回 success=false 的 JniResponse
+pub fn describe_protocol() -> Vec<u8> {
+    let description = catch_panic(|| {
+        current_handler().and_then(|h| {
+            h.describe().ok_or_else(|| {
+                ProtocolError::CommonError(
+                    "registered protocol handler does not implement describe".into(),
+                )
+            })
+        })
+    });
+    match description {
+        Ok(d) => d.to_bytes(),
+        Err(e) => encode_response(&JniResponse::from_error(&e), BridgeFormat::Json),
+    }
+}
+
+/// handler 没有显式设置时，把请求的 trace_id 与 msg_type 回写到响应
+pub fn echo_request_fields(request: &JniRequest, response: &mut JniResponse) {
+    if response.trace_id.is_none() {
+        response.trace_id = request.trace_id.clone();
+    }
+    if let Some(msg_type) = request.msg_type() {
+        response.set_msg_type_if_absent(msg_type);
+    }
+}
+
+fn encode_response(response: &JniResponse, format: BridgeFormat) -> Vec<u8> {
+    response.to_bytes_with(format).unwrap_or_else(|e| {
+        // 序列化本身失败时退回到最简的 JSON
+        JniResponse::from_error(&e)
+            .to_bytes()
+            .unwrap_or_else(|_| br#"{"success":false}"#.to_vec())
+    })
+}
@@ -0,0 +1,470 @@
+//! 由 Cmd 与 AutoDecoding/AutoEncoding 定义生成帧格式文档 (Markdown/HTML 表格)，
+//! 协议文档随代码一起更新:
+//!
+//! ```ignore
+//! let doc = FrameDoc::from_table::<GasCmd, _>("demo/gas", "1.0", |doc, cmd| match cmd {
+//!     GasCmd::Report => doc.starting_at(10).upstream(&ReportFields),
+//!     GasCmd::SetPrice => doc.downstream(&PriceParams),
+//!     _ => doc,
+//! });
+//! std::fs::write("FRAME.md", doc.to_markdown())?;
+//! ```
+use serde::Serialize;
+
+use crate::{
+    bridge::describe::{direction_code, rw_code},
+    core::{inspector::escape_xml, parts::traits::CmdTable},
+    AutoDecoding, AutoDecodingParam, AutoEncoding, AutoEncodingParam, Cmd, FieldType, TryFromBytes,
+};
+
+/// 帧中一个字段的布局
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDoc {
+    // 相对帧首的偏移，变长字段之后无法确定时为 None
+    pub offset: Option<usize>,
+    // 0 表示变长
+    pub length: usize,
+    pub title: String,
+    // 下行参数的 code，上行字段为空
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub code: String,
+    // 类型名，与协议定义文件 (dsl) 中的 type 一致
+    pub field_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+    pub little_endian: bool,
+    // 枚举值、比较目标、单位、特殊值、默认值等
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+}
+
+/// 一个命令的上下行字段表
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandDoc {
+    pub code: String,
+    pub title: String,
+    pub direction: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rw: Option<String>,
+    pub upstream: Vec<FieldDoc>,
+    pub downstream: Vec<FieldDoc>,
+    // 字段区起始偏移 (帧头、地址、命令码等固定部分的长度)
+    #[serde(skip)]
+    start: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameDoc {
+    pub protocol: String,
+    pub version: String,
+    pub commands: Vec<CommandDoc>,
+}
+
+impl CommandDoc {
+    pub fn of<T: Cmd>(cmd: &T) -> Self {
+        Self {
+            code: cmd.code(),
+            title: cmd.title(),
+            direction: direction_code(&cmd.direction()).into(),
+            rw: cmd.rw().map(|rw| rw_code(&rw).into()),
+            upstream: Vec::new(),
+            downstream: Vec::new(),
+            start: 0,
+        }
+    }
+
+    /// 之后添加的字段从 offset 开始计算偏移
+    pub fn starting_at(mut self, offset: usize) -> Self {
+        self.start = offset;
+        self
+    }
+
+    /// 添加上行字段。声明了其他 cmd_code 的字段不属于本命令，跳过
+    pub fn upstream<P, U, D>(mut self, decoding: &D) -> Self
+    where
+        P: AutoDecodingParam<U>,
+        U: TryFromBytes,
+        D: AutoDecoding<P, U>,
+    {
+        let fields = decoding
+            .variants()
+            .iter()
+            .filter(|p| self.owns(&p.cmd_code()))
+            .map(|p| FieldDoc {
+                offset: None,
+                length: p.byte_length(),
+                title: p.title(),
+                code: String::new(),
+                field_type: type_name(&decoding_type(p)).into(),
+                scale: p.field_type().scale().filter(|s| *s != 1.0),
+                little_endian: p.swap(),
+                detail: decoding_detail(p),
+            })
+            .collect();
+        self.upstream = layout(self.start, fields);
+        self
+    }
+
+    /// 添加下行参数，规则同 upstream
+    pub fn downstream<P, E>(mut self, encoding: &E) -> Self
+    where
+        P: AutoEncodingParam,
+        E: AutoEncoding<P>,
+    {
+        let fields = encoding
+            .variants()
+            .iter()
+            .filter(|p| self.owns(&p.cmd_code()))
+            .map(|p| FieldDoc {
+                offset: None,
+                length: p.byte_length(),
+                title: p.title(),
+                code: p.code(),
+                field_type: type_name(&p.field_type()).into(),
+                scale: p.field_type().scale().filter(|s| *s != 1.0),
+                little_endian: p.swap(),
+                detail: encoding_detail(p),
+            })
+            .collect();
+        self.downstream = layout(self.start, fields);
+        self
+    }
+
+    fn owns(&self, cmd_code: &str) -> bool {
+        cmd_code.is_empty() || cmd_code.eq_ignore_ascii_case(&self.code)
+    }
+}
+
+impl FrameDoc {
+    pub fn new(protocol: &str, version: &str) -> Self {
+        Self {
+            protocol: protocol.into(),
+            version: version.into(),
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn with_command(mut self, command: CommandDoc) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    /// 遍历命令表，fields 为每个命令补充字段定义
+    pub fn from_table<T, F>(protocol: &str, version: &str, mut fields: F) -> Self
+    where
+        T: CmdTable,
+        F: FnMut(CommandDoc, &T) -> CommandDoc,
+    {
+        T::variants()
+            .iter()
+            .fold(Self::new(protocol, version), |doc, cmd| {
+                doc.with_command(fields(CommandDoc::of(cmd), cmd))
+            })
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {} {}\n", self.protocol, self.version);
+        for cmd in &self.commands {
+            out += &format!("\n## {} {} ({})\n", cmd.code, cmd.title, cmd.direction);
+            for (name, fields) in [("上行", &cmd.upstream), ("下行", &cmd.downstream)] {
+                if fields.is_empty() {
+                    continue;
+                }
+                out += &format!("\n### {}\n\n", name);
+                out += "| 偏移 | 长度 | 字段 | 代码 | 类型 | 字节序 | 说明 |\n";
+                out += "|---|---|---|---|---|---|---|\n";
+                for field in fields {
+                    let cells = cells(field).map(|c| c.replace('|', "\\|"));
+                    out += &format!("| {} |\n", cells.join(" | "));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = format!(
+            "<h1>{} {}</h1>\n",
+            escape_xml(&self.protocol),
+            escape_xml(&self.version)
+        );
+        for cmd in &self.commands {
+            out += &format!(
+                "<h2>{} {} ({})</h2>\n",
+                escape_xml(&cmd.code),
+                escape_xml(&cmd.title),
+                cmd.direction
+            );
+            for (name, fields) in [("上行", &cmd.upstream), ("下行", &cmd.downstream)] {
+                if fields.is_empty() {
+                    continue;
+                }
+                out += &format!("<h3>{}</h3>\n<table>\n", name);
+                out += "<tr><th>偏移</th><th>长度</th><th>字段</th><th>代码</th><th>类型</th><th>字节序</th><th>说明</th></tr>\n";
+                for field in fields {
+                    let cells: Vec<String> = cells(field)
+                        .iter()
+                        .map(|c| format!("<td>{}</td>", escape_xml(c)))
+                        .collect();
+                    out += &format!("<tr>{}</tr>\n", cells.concat());
+                }
+                out += "</table>\n";
+            }
+        }
+        out
+    }
+}
+
+// 依次计算偏移，遇到变长字段后之后的偏移都无法确定
+fn layout(start: usize, mut fields: Vec<FieldDoc>) -> Vec<FieldDoc> {
+    let mut offset = Some(start);
+    for field in &mut fields {
+        field.offset = offset;
+        offset = match field.length {
+            0 => None,
+            len => offset.map(|o| o + len),
+        };
+    }
+    fields
+}
+
+fn cells(field: &FieldDoc) -> [String; 7] {
+    let field_type = match field.scale {
+        Some(scale) => format!("{} ×{}", field.field_type, scale),
+        None => field.field_type.clone(),
+    };
+    [
+        field.offset.map_or("-".into(), |o| o.to_string()),
+        match field.length {
+            0 => "变长".into(),
+            len => len.to_string(),
+        },
+        field.title.clone(),
+        field.code.clone(),
+        field_type,
+        if field.little_endian { "LE" } else { "BE" }.into(),
+        field.detail.clone(),
+    ]
+}
+
+// 上行字段按 translate 的优先级取实际生效的模式
+pub(crate) fn decoding_type<P: AutoDecodingParam<U>, U: TryFromBytes>(param: &P) -> FieldType {
+    if param.is_compare_mode() || !param.is_translate_mode() {
+        FieldType::Empty
+    } else {
+        param.field_type()
+    }
+}
+
+fn decoding_detail<P: AutoDecodingParam<U>, U: TryFromBytes>(param: &P) -> String {
+    let mut parts = Vec::new();
+    if param.is_compare_mode() {
+        parts.push(format!(
+            "固定值 {}",
+            hex::encode_upper(param.compare_target())
+        ));
+    } else if !param.is_translate_mode() && param.is_enum_mode() {
+        let values: Vec<String> = param
+            .enum_values()
+            .iter()
+            .map(|(value, title)| format!("{}={}", value, title))
+            .collect();
+        parts.push(values.join(", "));
+    }
+    if let Some(symbol) = param.symbol() {
+        parts.push(format!("单位 {}", symbol.tag()));
+    }
+    if let Some(filter) = param.filter() {
+        parts.push(format!(
+            "{} 表示{}",
+            hex::encode_upper(&filter.bytes),
+            filter.title()
+        ));
+    }
+    parts.join("; ")
+}
+
+fn encoding_detail<P: AutoEncodingParam>(param: &P) -> String {
+    let mut parts = Vec::new();
+    if !param.required() {
+        parts.push("可选".to_string());
+    }
+    if !param.default_hex().is_empty() {
+        parts.push(format!("默认 {}", param.default_hex()));
+    } else if !param.default_value().is_empty() {
+        parts.push(format!("默认 {}", param.default_value()));
+    }
+    parts.join("; ")
+}
+
+pub(crate) fn type_name(field_type: &FieldType) -> &'static str {
+    match field_type {
+        FieldType::Empty => "",
+        FieldType::StringOrBCD => "bcd",
+        FieldType::UnsignedU8(_) => "u8",
+        FieldType::UnsignedU16(_) => "u16",
+        FieldType::UnsignedU32(_) => "u32",
+        FieldType::UnsignedU64(_) => "u64",
+        FieldType::SignedI8(_) => "i8",
+        FieldType::SignedI16(_) => "i16",
+        FieldType::SignedI32(_) => "i32",
+        FieldType::SignedI64(_) => "i64",
+        FieldType::Float => "float",
+        FieldType::Double => "double",
+        FieldType::Ascii => "ascii",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd_table, Symbol};
+
+    cmd_table! {
+        enum GasCmd {
+            Report => ("02", "数据上报", Upstream, None, DataReport),
+            SetPrice => ("31", "调价", Downstream, Write, UpdateGasPrice),
+        }
+    }
+
+    enum ReportField {
+        Total,
+        Valve,
+        Remark,
+        Tail,
+    }
+
+    impl AutoDecodingParam for ReportField {
+        fn byte_length(&self) -> usize {
+            match self {
+                ReportField::Total => 4,
+                ReportField::Valve => 1,
+                ReportField::Remark => 0,
+                ReportField::Tail => 1,
+            }
+        }
+
+        fn title(&self) -> String {
+            match self {
+                ReportField::Total => "累计用量",
+                ReportField::Valve => "阀门状态",
+                ReportField::Remark => "备注",
+                ReportField::Tail => "结束符",
+            }
+            .into()
+        }
+
+        fn field_type(&self) -> FieldType {
+            match self {
+                ReportField::Total => FieldType::UnsignedU32(0.01),
+                ReportField::Remark => FieldType::Ascii,
+                _ => FieldType::Empty,
+            }
+        }
+
+        fn symbol(&self) -> Option<Symbol> {
+            matches!(self, ReportField::Total).then_some(Symbol::CubicMeter)
+        }
+
+        fn enum_values(&self) -> Vec<(u8, String)> {
+            match self {
+                ReportField::Valve => vec![(0x55, "开".into()), (0x99, "关<".into())],
+                _ => vec![],
+            }
+        }
+
+        fn compare_target(&self) -> Vec<u8> {
+            match self {
+                ReportField::Tail => vec![0x16],
+                _ => vec![],
+            }
+        }
+    }
+
+    struct Report;
+
+    impl AutoDecoding<ReportField> for Report {
+        fn variants(&self) -> Vec<ReportField> {
+            vec![
+                ReportField::Total,
+                ReportField::Valve,
+                ReportField::Remark,
+                ReportField::Tail,
+            ]
+        }
+    }
+
+    struct Price;
+
+    impl AutoEncodingParam for Price {
+        fn code(&self) -> String {
+            "danjia".into()
+        }
+
+        fn title(&self) -> String {
+            "单价".into()
+        }
+
+        fn byte_length(&self) -> usize {
+            4
+        }
+
+        fn cmd_code(&self) -> String {
+            "31".into()
+        }
+
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU32(0.01)
+        }
+
+        fn default_value(&self) -> String {
+            "3.5".into()
+        }
+    }
+
+    struct Prices;
+
+    impl AutoEncoding<Price> for Prices {
+        fn variants(&self) -> Vec<Price> {
+            vec![Price]
+        }
+    }
+
+    fn doc() -> FrameDoc {
+        FrameDoc::from_table::<GasCmd, _>("demo/gas", "1.0", |doc, cmd| match cmd {
+            GasCmd::Report => doc.starting_at(10).upstream(&Report),
+            GasCmd::SetPrice => doc.downstream(&Prices),
+        })
+    }
+
+    #[test]
+    fn test_layout() {
+        let doc = doc();
+        let report = &doc.commands[0];
+        let offsets: Vec<Option<usize>> = report.upstream.iter().map(|f| f.offset).collect();
+        assert_eq!(offsets, [Some(10), Some(14), Some(15), None]);
+        assert_eq!(report.upstream[0].field_type, "u32");
+        assert_eq!(report.upstream[0].detail, "单位 m³");
+        assert_eq!(report.upstream[1].detail, "85=开, 153=关<");
+        assert_eq!(report.upstream[3].detail, "固定值 16");
+        assert_eq!(doc.commands[1].downstream[0].code, "danjia");
+        assert_eq!(doc.commands[1].downstream[0].detail, "默认 3.5");
+    }
+
+    #[test]
+    fn test_render() {
+        let doc = doc();
+        let markdown = doc.to_markdown();
+        assert!(markdown.contains("## 02 数据上报 (upstream)"));
+        assert!(markdown.contains("| 10 | 4 | 累计用量 |  | u32 ×0.01 | BE | 单位 m³ |"));
+        assert!(markdown.contains("| 15 | 变长 | 备注 |  | ascii | BE |  |"));
+        assert!(markdown.contains("| 0 | 4 | 单价 | danjia | u32 ×0.01 | BE | 默认 3.5 |"));
+
+        let html = doc.to_html();
+        assert!(html.contains("<td>85=开, 153=关&lt;</td>"));
+        assert!(html.contains("<h2>31 调价 (downstream)</h2>"));
+    }
+}
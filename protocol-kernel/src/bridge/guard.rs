@@ -0,0 +1,105 @@
+use std::{
+    backtrace::Backtrace,
+    cell::{Cell, RefCell},
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+};
+
+use protocol_base::{ProtocolError, ProtocolResult};
+
+static HOOK: Once = Once::new();
+
+thread_local! {
+    // 当前线程嵌套的 catch_panic 层数，只有在 guard 内的 panic 才记录 backtrace
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+    static BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+// 在 panic 发生处记录 backtrace (unwind 之后再取就只剩 catch 处的调用栈了)，
+// 然后交给原来的 hook，保留默认的 stderr 输出
+fn install_hook() {
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if DEPTH.with(|d| d.get()) > 0 {
+                let backtrace = Backtrace::force_capture().to_string();
+                BACKTRACE.with(|b| *b.borrow_mut() = Some(backtrace));
+            }
+            previous(info);
+        }));
+    });
+}
+
+struct DepthGuard;
+
+impl DepthGuard {
+    fn enter() -> Self {
+        DEPTH.with(|d| d.set(d.get() + 1));
+        DepthGuard
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        DEPTH.with(|d| d.set(d.get().saturating_sub(1)));
+    }
+}
+
+pub fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// 执行 f，把其中的 panic 转成 ProtocolError::CommonError (带 panic 处的 backtrace)。
+/// 所有对外的入口 (bridge、batch、FFI/JNI) 都经过它，
+/// 协议实现中的越界等 panic 不会拖垮宿主线程
+pub fn catch_panic<T, F>(f: F) -> ProtocolResult<T>
+where
+    F: FnOnce() -> ProtocolResult<T>,
+{
+    install_hook();
+    let result = {
+        let _depth = DepthGuard::enter();
+        panic::catch_unwind(AssertUnwindSafe(f))
+    };
+    result.unwrap_or_else(|payload| {
+        let message = panic_message(payload.as_ref());
+        let backtrace = BACKTRACE.with(|b| b.borrow_mut().take());
+        Err(ProtocolError::CommonError(match backtrace {
+            Some(backtrace) => format!(
+                "panic in protocol handler: {}\nbacktrace:\n{}",
+                message, backtrace
+            ),
+            None => format!("panic in protocol handler: {}", message),
+        }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(catch_panic(|| Ok(1)).unwrap(), 1);
+
+        let bytes = [0x68u8];
+        let index = std::hint::black_box(3);
+        let err = catch_panic(|| Ok(bytes[index])).unwrap_err().to_string();
+        assert!(err.contains("panic in protocol handler: index out of bounds"));
+        assert!(err.contains("backtrace:"));
+
+        // 嵌套时由最内层捕获
+        let outer = catch_panic(|| {
+            let inner = catch_panic::<(), _>(|| panic!("inner"));
+            assert!(inner.is_err());
+            Ok("outer")
+        });
+        assert_eq!(outer.unwrap(), "outer");
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use protocol_base::ProtocolError;
+
+use crate::DirectionEnum;
+
+/// 处理的帧数，标签: protocol, direction (up/down), result (ok/error)
+pub const FRAMES_TOTAL: &str = "protocol_frames_total";
+/// CRC 校验失败次数 (错误码 E_CRC_*)，标签: protocol
+pub const CRC_FAILURES_TOTAL: &str = "protocol_crc_failures_total";
+/// 加解密失败次数 (错误码 E_CRYPTO_*)，标签: protocol
+pub const CIPHER_ERRORS_TOTAL: &str = "protocol_cipher_errors_total";
+/// bridge 处理一帧的耗时 (秒)，标签: protocol, cmd_code
+pub const BRIDGE_LATENCY_SECONDS: &str = "protocol_bridge_latency_seconds";
+
+/// 记录一帧的处理结果: 帧数、耗时，以及按错误码归类的 CRC/加解密失败。
+/// 经 metrics 门面输出，宿主安装 recorder (如 metrics-exporter-prometheus) 后即可采集；
+/// 关闭 metrics feature 时不产生任何指标
+pub fn record_frame(
+    _protocol_id: &str,
+    _direction: &DirectionEnum,
+    _cmd_code: Option<&str>,
+    _elapsed: Duration,
+    _error: Option<&ProtocolError>,
+) {
+    #[cfg(feature = "metrics")]
+    {
+        let protocol = _protocol_id.to_string();
+        let direction = if _direction.is_upstream() {
+            "up"
+        } else {
+            "down"
+        };
+        let result = if _error.is_some() { "error" } else { "ok" };
+        metrics::counter!(
+            FRAMES_TOTAL,
+            "protocol" => protocol.clone(),
+            "direction" => direction,
+            "result" => result
+        )
+        .increment(1);
+        metrics::histogram!(
+            BRIDGE_LATENCY_SECONDS,
+            "protocol" => protocol.clone(),
+            "cmd_code" => _cmd_code.unwrap_or("").to_string()
+        )
+        .record(_elapsed.as_secs_f64());
+        let code = _error.map(|e| e.code()).unwrap_or("");
+        if code.starts_with("E_CRC") {
+            metrics::counter!(CRC_FAILURES_TOTAL, "protocol" => protocol).increment(1);
+        } else if code.starts_with("E_CRYPTO") {
+            metrics::counter!(CIPHER_ERRORS_TOTAL, "protocol" => protocol).increment(1);
+        }
+    }
+}
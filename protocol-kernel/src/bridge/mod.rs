@@ -1,50 +1,42 @@
+pub mod async_handler;
+pub mod batch;
+pub mod builder;
+pub mod commands;
+pub mod describe;
+pub mod dispatch;
+pub mod frame_doc;
+pub mod guard;
+pub mod metrics;
+pub mod registry;
+pub mod sink;
+pub mod tlv;
+pub mod trace;
+
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
-use protocol_base::{ProtocolError, ProtocolResult};
+use protocol_base::{ErrorEnvelope, ProtocolError, ProtocolResult};
 use crate::{
-    core::parts::{
-        traits::Cmd,
-        raw_capsule::RawCapsule,
-        raw_chamber::RawChamber,
-        rawfield::Rawfield,
+    core::{
+        event::DeviceEvent,
+        parts::{
+            traits::Cmd,
+            diagnostic::Diagnostic,
+            raw_capsule::RawCapsule,
+            raw_chamber::RawChamber,
+        },
     },
-    utils,
+    DecodeMode, MsgTypeEnum,
 };
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "camelCase")]
-pub struct ReportField {
-    pub name: String,
-    pub code: String,
-    pub value: String,
-    pub alert: bool,
-}
-
-// 实现一个便捷的构造函数
-impl ReportField {
-    pub fn new(name: &str, code: &str, value: String) -> Self {
-        Self {
-            name: name.to_string(),
-            code: code.to_string(),
-            value,
-            alert: false, // 默认为false
-        }
-    }
-}
-
-impl Rawfield {
-    pub fn to_report_field(self) -> ReportField {
-        let title = self.title;
-        let code = utils::to_pinyin(&title);
-        ReportField {
-            name: title,
-            code,
-            value: self.value,
-            alert: false,
-        }
-    }
-}
+// ReportField 与 ValueType 在 no_std 下也要用到，定义在 core::parts 中
+pub use crate::core::parts::report_field::{ReportField, ValueType};
+pub use batch::{JniBatchRequest, JniBatchResponse};
+pub use builder::JniRequestBuilder;
+pub use commands::{BusinessCommand, CommandMapping};
+pub use describe::{CommandDescription, FieldSchema, ProtocolDescription};
+pub use frame_doc::{CommandDoc, FieldDoc, FrameDoc};
+pub use registry::{ProtocolHandler, ProtocolRouter};
+pub use tlv::BridgeFormat;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -63,9 +55,19 @@ pub struct JniRequest {
     pub(crate) uri: Option<String>,
     #[serde(default)]
     pub(crate) params: Option<HashMap<String, String>>,
+    // 端到端关联 id，原样回写到 JniResponse，并出现在解码期间的日志中
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) trace_id: Option<String>,
+    // 本次请求的解码模式，不传为 Strict
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) decode_mode: Option<DecodeMode>,
 }
 
 impl JniRequest {
+    pub fn builder() -> JniRequestBuilder {
+        JniRequestBuilder::new()
+    }
+
     pub fn new(
         device_id: Option<String>,
         device_no: Option<String>,
@@ -83,15 +85,60 @@ impl JniRequest {
             hex,
             uri,
             params,
+            trace_id: None,
+            decode_mode: None,
         }
     }
 
+    pub fn with_trace_id(mut self, trace_id: &str) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    pub fn trace_id(&self) -> Option<&str> {
+        self.trace_id.as_deref()
+    }
+
+    pub fn set_trace_id(&mut self, trace_id: &str) {
+        self.trace_id = Some(trace_id.into());
+    }
+
+    pub fn with_decode_mode(mut self, mode: DecodeMode) -> Self {
+        self.decode_mode = Some(mode);
+        self
+    }
+
+    /// 协议实现按此创建 Reader (Reader::with_decode_mode)
+    pub fn decode_mode(&self) -> DecodeMode {
+        self.decode_mode.unwrap_or_default()
+    }
+
     pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
         let json_string =
             serde_json::to_string(self).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
         Ok(json_string.into_bytes())
     }
 
+    /// 按指定格式序列化。Tlv 为紧凑的二进制格式，hex 字段以原始字节存放
+    pub fn to_bytes_with(&self, format: BridgeFormat) -> ProtocolResult<Vec<u8>> {
+        match format {
+            BridgeFormat::Json => self.to_bytes(),
+            BridgeFormat::Tlv => Ok(tlv::encode_request(self)),
+        }
+    }
+
+    pub fn from_with(data: &[u8], format: BridgeFormat) -> ProtocolResult<Self> {
+        match format {
+            BridgeFormat::Json => Self::from(data),
+            BridgeFormat::Tlv => tlv::decode_request(data),
+        }
+    }
+
+    /// 根据首字节自动识别 Json/Tlv
+    pub fn from_auto(data: &[u8]) -> ProtocolResult<Self> {
+        Self::from_with(data, BridgeFormat::detect(data))
+    }
+
     pub fn from(data: &[u8]) -> ProtocolResult<Self> {
         let json_string =
             std::str::from_utf8(data).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
@@ -180,6 +227,63 @@ pub struct JniResponse {
     pub(crate) rsp_jsons: Vec<ReportField>,
     #[serde(default)]
     pub(crate) err_msg: Option<String>,
+    #[serde(default)]
+    pub(crate) err_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<ErrorEnvelope>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) warnings: Vec<Diagnostic>,
+    // 字段被截断 (limit_fields) 时为 true，total_fields 为截断前的字段总数
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) truncated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) total_fields: Option<usize>,
+    // 分页返回时的页信息
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) page: Option<PageInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) trace_id: Option<String>,
+    // 多帧下行时按顺序排列的每一帧
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) segments: Vec<ResponseSegment>,
+    // 重复帧 (上行序号近期已出现过)，平台据此避免重复计费
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) duplicate: bool,
+    // 解析/编码耗时 (微秒)，capsule 记录了对应时间点时才有值，用于按 cmd_code 监控响应时延
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) decode_micros: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) encode_micros: Option<u64>,
+    // 下行命令的幂等令牌 (core::idempotency)，重发时不变
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) idempotency_token: Option<String>,
+    // 告警/事件记录 (core::event)，与 req_jsons 中的普通字段分开
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) events: Vec<DeviceEvent>,
+}
+
+/// 多帧下行中的一帧
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseSegment {
+    #[serde(default)]
+    pub cmd_code: Option<String>,
+    #[serde(default)]
+    pub hex: String,
+    #[serde(default)]
+    pub fields: Vec<ReportField>,
+}
+
+/// 分页响应的页信息，index 从 0 开始
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInfo {
+    pub index: usize,
+    pub count: usize,
+}
+
+pub(crate) fn as_micros(duration: std::time::Duration) -> u64 {
+    u64::try_from(duration.as_micros()).unwrap_or(u64::MAX)
 }
 
 impl JniResponse {
@@ -189,6 +293,26 @@ impl JniResponse {
         Ok(json_string.into_bytes())
     }
 
+    /// 按指定格式序列化。Tlv 为紧凑的二进制格式，hex 字段以原始字节存放
+    pub fn to_bytes_with(&self, format: BridgeFormat) -> ProtocolResult<Vec<u8>> {
+        match format {
+            BridgeFormat::Json => self.to_bytes(),
+            BridgeFormat::Tlv => Ok(tlv::encode_response(self)),
+        }
+    }
+
+    pub fn from_with(data: &[u8], format: BridgeFormat) -> ProtocolResult<Self> {
+        match format {
+            BridgeFormat::Json => Self::from(data),
+            BridgeFormat::Tlv => tlv::decode_response(data),
+        }
+    }
+
+    /// 根据首字节自动识别 Json/Tlv
+    pub fn from_auto(data: &[u8]) -> ProtocolResult<Self> {
+        Self::from_with(data, BridgeFormat::detect(data))
+    }
+
     pub fn new_with_err_msg(device_no: &str, cmd_code: &str, err_msg: &str) -> Self {
         Self {
             success: false,
@@ -201,6 +325,61 @@ impl JniResponse {
             req_jsons: Vec::new(),
             rsp_jsons: Vec::new(),
             err_msg: Some(err_msg.into()),
+            err_code: None,
+            error: None,
+            warnings: Vec::new(),
+            truncated: false,
+            total_fields: None,
+            page: None,
+            trace_id: None,
+            segments: Vec::new(),
+            duplicate: false,
+            decode_micros: None,
+            encode_micros: None,
+            idempotency_token: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// 与 new_with_err_msg 相同，同时带上 ProtocolError 的稳定错误码
+    pub fn new_with_err(device_no: &str, cmd_code: &str, err: &ProtocolError) -> Self {
+        let mut rsp = Self::new_with_err_msg(device_no, cmd_code, &err.to_string());
+        rsp.set_error(err);
+        rsp
+    }
+
+    /// 仅由错误构造响应，err_msg/err_code/error 均从 ProtocolError 生成
+    pub fn from_error(err: &ProtocolError) -> Self {
+        let mut rsp = Self::empty();
+        rsp.set_error(err);
+        rsp
+    }
+
+    pub(crate) fn empty() -> Self {
+        Self {
+            success: true,
+            device_id: None,
+            device_no: None,
+            msg_type: None,
+            cmd_code: None,
+            req_hex: String::new(),
+            rsp_hex: String::new(),
+            req_jsons: Vec::new(),
+            rsp_jsons: Vec::new(),
+            err_msg: None,
+            err_code: None,
+            error: None,
+            warnings: Vec::new(),
+            truncated: false,
+            total_fields: None,
+            page: None,
+            trace_id: None,
+            segments: Vec::new(),
+            duplicate: false,
+            decode_micros: None,
+            encode_micros: None,
+            idempotency_token: None,
+            events: Vec::new(),
         }
     }
 
@@ -289,6 +468,134 @@ impl JniResponse {
         self.err_msg = Some(err_msg.to_string());
     }
 
+    pub fn err_code(&self) -> Option<&str> {
+        self.err_code.as_deref()
+    }
+
+    pub fn set_err_code(&mut self, err_code: &str) {
+        self.err_code = Some(err_code.to_string());
+    }
+
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    pub fn set_warnings(&mut self, warnings: Vec<Diagnostic>) {
+        self.warnings = warnings;
+    }
+
+    pub fn trace_id(&self) -> Option<&str> {
+        self.trace_id.as_deref()
+    }
+
+    pub fn set_trace_id(&mut self, trace_id: &str) {
+        self.trace_id = Some(trace_id.into());
+    }
+
+    pub fn duplicate(&self) -> bool {
+        self.duplicate
+    }
+
+    pub fn set_duplicate(&mut self, duplicate: bool) {
+        self.duplicate = duplicate;
+    }
+
+    pub fn decode_micros(&self) -> Option<u64> {
+        self.decode_micros
+    }
+
+    pub fn encode_micros(&self) -> Option<u64> {
+        self.encode_micros
+    }
+
+    pub fn idempotency_token(&self) -> Option<&str> {
+        self.idempotency_token.as_deref()
+    }
+
+    pub fn set_idempotency_token(&mut self, token: &str) {
+        self.idempotency_token = Some(token.into());
+    }
+
+    pub fn events(&self) -> &[DeviceEvent] {
+        &self.events
+    }
+
+    pub fn set_events(&mut self, events: Vec<DeviceEvent>) {
+        self.events = events;
+    }
+
+    pub fn add_event(&mut self, event: DeviceEvent) {
+        self.events.push(event);
+    }
+
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn total_fields(&self) -> Option<usize> {
+        self.total_fields
+    }
+
+    pub fn page(&self) -> Option<PageInfo> {
+        self.page
+    }
+
+    /// 限制 req_jsons + rsp_jsons 的字段总数 (req 优先保留)，多帧响应的 segments 随 rsp_jsons 截断。
+    /// 超出时截断并设置 truncated/total_fields，避免超大的 JSON 压垮 Java 端
+    pub fn limit_fields(&mut self, max_fields: usize) -> &mut Self {
+        let total = self.req_jsons.len() + self.rsp_jsons.len();
+        if total > max_fields {
+            self.req_jsons.truncate(max_fields);
+            self.rsp_jsons
+                .truncate(max_fields.saturating_sub(self.req_jsons.len()));
+            self.segments = self.segments_within(0, self.rsp_jsons.len(), true);
+            self.truncated = true;
+            self.total_fields = Some(total);
+        }
+        self
+    }
+
+    /// 按 page_size 把字段拆成多个响应，字段顺序为 req_jsons 在前、rsp_jsons 在后。
+    /// 元数据在每页都保留，hex 只放在第 0 页。多帧响应的 segments 只保留落在本页的字段，
+    /// 第 0 页保留全部帧的 hex
+    pub fn paginate(&self, page_size: usize) -> Vec<JniResponse> {
+        let page_size = page_size.max(1);
+        let total = self.req_jsons.len() + self.rsp_jsons.len();
+        let count = total.div_ceil(page_size).max(1);
+        (0..count)
+            .map(|index| {
+                let start = index * page_size;
+                let end = (start + page_size).min(total);
+                let split = self.req_jsons.len();
+                let (rsp_start, rsp_end) = (start.max(split) - split, end.max(split) - split);
+                let mut page = self.clone();
+                page.req_jsons = self.req_jsons[start.min(split)..end.min(split)].to_vec();
+                page.rsp_jsons = self.rsp_jsons[rsp_start..rsp_end].to_vec();
+                page.segments = self.segments_within(rsp_start, rsp_end, index == 0);
+                if index > 0 {
+                    page.req_hex = String::new();
+                    page.rsp_hex = String::new();
+                }
+                page.total_fields = Some(total);
+                page.page = Some(PageInfo { index, count });
+                page
+            })
+            .collect()
+    }
+
+    /// 结构化的错误信息 (code, message, details)
+    pub fn error(&self) -> Option<&ErrorEnvelope> {
+        self.error.as_ref()
+    }
+
+    /// 同时写入错误信息、错误码和结构化错误
+    pub fn set_error(&mut self, err: &ProtocolError) {
+        self.success = false;
+        self.err_msg = Some(err.to_string());
+        self.err_code = Some(err.code().to_string());
+        self.error = Some(err.to_envelope());
+    }
+
     // Setter methods
     pub fn set_success(&mut self, success: bool) {
         self.success = success;
@@ -306,6 +613,24 @@ impl JniResponse {
         self.msg_type = Some(msgt_type.to_string());
     }
 
+    pub fn set_msg_type(&mut self, msg_type: &MsgTypeEnum) {
+        self.msg_type = Some(msg_type.code());
+    }
+
+    /// msg_type 为空时才写入，用于分发层用请求中的 msg_type 兜底
+    pub fn set_msg_type_if_absent(&mut self, msg_type: &str) {
+        if self.msg_type.as_deref().unwrap_or_default().is_empty() && !msg_type.is_empty() {
+            self.msg_type = Some(msg_type.to_string());
+        }
+    }
+
+    // 命令未定义 msg_type 时为空字符串
+    fn msg_type_of<T: Cmd>(cmd: Option<&T>) -> String {
+        cmd.and_then(|c| c.msg_type())
+            .map(|m| m.code())
+            .unwrap_or_default()
+    }
+
     pub fn set_cmd_code(&mut self, cmd_code: &str) {
         self.cmd_code = Some(cmd_code.to_string());
     }
@@ -346,8 +671,30 @@ impl JniResponse {
         } else {
             (String::new(), Vec::new())
         };
-        // msgt_type 暂时设置为空字符串，根据实际需求调整
-        let msgt_type = Some(String::new());
+        let mut warnings = Vec::new();
+        if let Some(upstream) = chamber.upstream() {
+            warnings.extend(upstream.warnings_clone());
+        }
+        if let Some(downstream) = chamber.downstream() {
+            warnings.extend(downstream.warnings_clone());
+        }
+        // msg_type 由命令定义 (Cmd::msg_type) 决定，优先取上行的命令
+        let cmd = chamber
+            .upstream()
+            .and_then(|c| c.cmd())
+            .or_else(|| chamber.downstream().and_then(|c| c.cmd()));
+        let msgt_type = Some(Self::msg_type_of(cmd));
+        let decode_micros = chamber
+            .upstream()
+            .and_then(|c| c.decode_duration())
+            .map(as_micros);
+        let encode_micros = chamber
+            .downstream()
+            .and_then(|c| c.encode_duration())
+            .map(as_micros);
+        let idempotency_token = chamber
+            .downstream()
+            .and_then(|c| c.idempotency_token_clone());
         Ok(Self {
             success: chamber.success(),
             device_id,
@@ -359,6 +706,19 @@ impl JniResponse {
             req_jsons,
             rsp_jsons,
             err_msg: None,
+            err_code: None,
+            error: None,
+            warnings,
+            truncated: false,
+            total_fields: None,
+            page: None,
+            trace_id: None,
+            segments: Vec::new(),
+            duplicate: false,
+            decode_micros,
+            encode_micros,
+            idempotency_token,
+            events: Vec::new(),
         })
     }
 
@@ -381,8 +741,12 @@ impl JniResponse {
         let rsp_hex = capsule.hex_clone();
         let rsp_jsons = capsule.field_details_clone();
 
-        // msgt_type 暂时设置为空字符串
-        let msgt_type = Some(String::new());
+        let warnings = capsule.warnings_clone();
+
+        let msgt_type = Some(Self::msg_type_of(capsule.cmd()));
+        let decode_micros = capsule.decode_duration().map(as_micros);
+        let encode_micros = capsule.encode_duration().map(as_micros);
+        let idempotency_token = capsule.idempotency_token_clone();
 
         Ok(Self {
             success: capsule.success(),
@@ -395,6 +759,295 @@ impl JniResponse {
             req_jsons,
             rsp_jsons,
             err_msg: None,
+            err_code: None,
+            error: None,
+            warnings,
+            truncated: false,
+            total_fields: None,
+            page: None,
+            trace_id: None,
+            segments: Vec::new(),
+            duplicate: false,
+            decode_micros,
+            encode_micros,
+            idempotency_token,
+            events: Vec::new(),
         })
     }
+
+    /// 多帧下行的返回。segments 按顺序保留每一帧的 hex 和字段；
+    /// 为兼容只读 rsp_hex/rsp_jsons 的调用方，这两项仍是所有帧的拼接
+    pub fn multi<T: Cmd + Clone + 'static>(capsules: Vec<RawCapsule<T>>) -> ProtocolResult<Self> {
+        let first = capsules.first().ok_or_else(|| {
+            ProtocolError::ValidationFailed("JniResponse::multi requires at least 1 capsule".into())
+        })?;
+        let mut rsp = Self::downstream_response(first)?;
+        rsp.success = capsules.iter().all(|c| c.success());
+        rsp.rsp_hex = capsules.iter().map(|c| c.hex()).collect();
+        rsp.rsp_jsons = capsules
+            .iter()
+            .flat_map(|c| c.field_details().iter().cloned())
+            .collect();
+        rsp.warnings = capsules.iter().flat_map(|c| c.warnings_clone()).collect();
+        rsp.segments = capsules
+            .iter()
+            .map(|c| ResponseSegment {
+                cmd_code: c.cmd().map(|cmd| cmd.code()),
+                hex: c.hex_clone(),
+                fields: c.field_details_clone(),
+            })
+            .collect();
+        Ok(rsp)
+    }
+
+    pub fn segments(&self) -> &[ResponseSegment] {
+        &self.segments
+    }
+
+    pub fn is_multi(&self) -> bool {
+        !self.segments.is_empty()
+    }
+
+    // segments 的字段依次对应 rsp_jsons，取出 rsp_jsons[start..end) 范围内的部分。
+    // with_hex 时保留每一帧 (即使没有字段落在范围内) 及其 hex，否则只保留有字段的帧且不带 hex
+    fn segments_within(&self, start: usize, end: usize, with_hex: bool) -> Vec<ResponseSegment> {
+        let mut offset = 0;
+        self.segments
+            .iter()
+            .filter_map(|segment| {
+                let first = offset;
+                offset += segment.fields.len();
+                let (from, to) = (start.clamp(first, offset), end.clamp(first, offset));
+                if from == to && !with_hex {
+                    return None;
+                }
+                Some(ResponseSegment {
+                    cmd_code: segment.cmd_code.clone(),
+                    hex: if with_hex {
+                        segment.hex.clone()
+                    } else {
+                        String::new()
+                    },
+                    fields: segment.fields[from - first..to - first].to_vec(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_base::error::hex_error::HexError;
+
+    #[derive(Clone)]
+    struct Command {
+        code: &'static str,
+        msg_type: Option<MsgTypeEnum>,
+    }
+
+    impl Cmd for Command {
+        fn code(&self) -> String {
+            self.code.into()
+        }
+
+        fn title(&self) -> String {
+            "测试命令".into()
+        }
+
+        fn msg_type(&self) -> Option<MsgTypeEnum> {
+            self.msg_type.clone()
+        }
+    }
+
+    fn capsule(code: &'static str, msg_type: Option<MsgTypeEnum>) -> RawCapsule<Command> {
+        RawCapsule::new_downstream(Command { code, msg_type }, "0001", "dev-1")
+    }
+
+    #[test]
+    fn test_err_code() {
+        let err = ProtocolError::from(HexError::NotBcd("1A".into()));
+        let rsp = JniResponse::new_with_err("0001", "90EF", &err);
+        assert!(!rsp.success());
+        assert_eq!(rsp.err_code(), Some("E_HEX_010"));
+        assert_eq!(rsp.err_msg(), Some(err.to_string().as_str()));
+
+        let json: serde_json::Value = serde_json::from_slice(&rsp.to_bytes().unwrap()).unwrap();
+        assert_eq!(json["errCode"], "E_HEX_010");
+        // 旧的 Java 端不带 errCode 也能解析
+        let old = JniResponse::from(br#"{"success":false,"errMsg":"x"}"#).unwrap();
+        assert_eq!(old.err_code(), None);
+    }
+
+    #[test]
+    fn test_from_error() {
+        let err = ProtocolError::CrcError {
+            ori_crc: 0x1234,
+            calc_crc: 0x4321,
+        };
+        let rsp = JniResponse::from_error(&err);
+        assert!(!rsp.success());
+        assert_eq!(rsp.err_code(), Some("E_CRC_003"));
+        assert_eq!(rsp.error(), Some(&err.to_envelope()));
+
+        let json: serde_json::Value = serde_json::from_slice(&rsp.to_bytes().unwrap()).unwrap();
+        assert_eq!(json["error"]["code"], "E_CRC_003");
+        assert_eq!(json["error"]["details"]["oriCrc"], "1234");
+        assert_eq!(json["error"]["message"], json["errMsg"]);
+        // 成功的响应不输出 error
+        let ok: serde_json::Value =
+            serde_json::from_slice(&JniResponse::empty().to_bytes().unwrap()).unwrap();
+        assert!(ok.get("error").is_none());
+    }
+
+    #[test]
+    fn test_warnings_with_success() {
+        let mut up = capsule("02", None);
+        up.add_warning(Diagnostic::new("reserved_nonzero", "保留字节不为 0").with_offset(5));
+        let mut down = capsule("82", None);
+        down.add_warning(Diagnostic::new("timestamp_range", "时间超出范围"));
+
+        let rsp = JniResponse::downstream_response(&down).unwrap();
+        assert!(rsp.success());
+        assert_eq!(rsp.warnings(), down.warnings());
+
+        // 上行在前、下行在后合并
+        let rsp = JniResponse::upstream_response(&RawChamber::new(&up, &down)).unwrap();
+        assert!(rsp.success());
+        let codes: Vec<_> = rsp.warnings().iter().map(|w| w.code.as_str()).collect();
+        assert_eq!(codes, ["reserved_nonzero", "timestamp_range"]);
+        let json: serde_json::Value = serde_json::from_slice(&rsp.to_bytes().unwrap()).unwrap();
+        assert_eq!(json["warnings"][0]["offset"], 5);
+
+        // 没有警告时不输出 warnings
+        let rsp = JniResponse::downstream_response(&capsule("82", None)).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&rsp.to_bytes().unwrap()).unwrap();
+        assert!(json.get("warnings").is_none());
+    }
+
+    #[test]
+    fn test_msg_type_of() {
+        let report = capsule("02", Some(MsgTypeEnum::DataReport));
+        let reply = capsule("82", Some(MsgTypeEnum::ServerTerminalOver));
+        let msg_type = |up: &RawCapsule<Command>, down: &RawCapsule<Command>| {
+            JniResponse::upstream_response(&RawChamber::new(up, down))
+                .unwrap()
+                .msg_type_clone()
+        };
+        // 优先取上行命令的 msg_type
+        assert_eq!(msg_type(&report, &reply), "data_report");
+        // 上行没有解析出命令时取下行的
+        let unparsed: RawCapsule<Command> = RawCapsule::new_upstream(&[0x68, 0x16]);
+        assert_eq!(msg_type(&unparsed, &reply), "server_terminal_over");
+        // 命令未定义 msg_type 时为空字符串
+        assert_eq!(msg_type(&capsule("02", None), &reply), "");
+        assert_eq!(msg_type(&unparsed, &capsule("82", None)), "");
+
+        let rsp =
+            JniResponse::downstream_response(&capsule("31", Some(MsgTypeEnum::UpdateGasPrice)))
+                .unwrap();
+        assert_eq!(rsp.msg_type(), Some("update_gas_price"));
+        let mut rsp = JniResponse::downstream_response(&capsule("31", None)).unwrap();
+        assert_eq!(rsp.msg_type(), Some(""));
+
+        // 分发层用请求中的 msg_type 兜底，不覆盖命令定义的值
+        rsp.set_msg_type_if_absent("");
+        assert_eq!(rsp.msg_type(), Some(""));
+        rsp.set_msg_type_if_absent("recharge");
+        assert_eq!(rsp.msg_type(), Some("recharge"));
+        rsp.set_msg_type_if_absent("heart_beat");
+        assert_eq!(rsp.msg_type(), Some("recharge"));
+        rsp.set_msg_type(&MsgTypeEnum::HeartBeat);
+        assert_eq!(rsp.msg_type(), Some("heart_beat"));
+    }
+
+    #[test]
+    fn test_paginate_and_limit() {
+        let mut rsp = JniResponse::empty();
+        rsp.set_req_jsons(vec![ReportField::new("a", "a", "1".into())]);
+        rsp.set_rsp_jsons(
+            (0..4)
+                .map(|i| ReportField::new("b", "b", i.to_string()))
+                .collect(),
+        );
+        let pages = rsp.paginate(2);
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].req_jsons().len(), 1);
+        assert_eq!(pages[0].rsp_jsons()[0].value, "0");
+        assert_eq!(pages[2].rsp_jsons()[0].value, "3");
+        assert_eq!(pages[1].page(), Some(PageInfo { index: 1, count: 3 }));
+        assert_eq!(pages[1].total_fields(), Some(5));
+
+        assert!(!rsp.truncated());
+        rsp.limit_fields(3);
+        assert!(rsp.truncated());
+        assert_eq!(rsp.req_jsons().len(), 1);
+        assert_eq!(rsp.rsp_jsons().len(), 2);
+
+        // 多帧响应: 每帧 2 个字段，共 3 帧
+        let frames = ["6801", "6802", "6803"]
+            .iter()
+            .map(|hex| {
+                let mut c = capsule("21", None);
+                c.set_bytes_and_generate_hex(&hex::decode(hex).unwrap())
+                    .unwrap();
+                c.set_fields(vec![
+                    ReportField::new("帧", "zhen", hex.to_string()),
+                    ReportField::new("序号", "xu_hao", hex[2..].to_string()),
+                ]);
+                c
+            })
+            .collect();
+        let multi = JniResponse::multi(frames).unwrap();
+        let pages = multi.paginate(3);
+        assert_eq!(pages.len(), 2);
+        let hexes = |page: &JniResponse| -> Vec<String> {
+            page.segments().iter().map(|s| s.hex.clone()).collect()
+        };
+        let counts = |page: &JniResponse| -> Vec<usize> {
+            page.segments().iter().map(|s| s.fields.len()).collect()
+        };
+        // 第 0 页带全部帧的 hex，字段只有前 3 个
+        assert_eq!(hexes(&pages[0]), ["6801", "6802", "6803"]);
+        assert_eq!(counts(&pages[0]), [2, 1, 0]);
+        // 之后的页只有落在本页的帧，不带 hex
+        assert_eq!(hexes(&pages[1]), ["", ""]);
+        assert_eq!(counts(&pages[1]), [1, 2]);
+        assert_eq!(pages[1].segments()[0].fields[0].value, "02");
+
+        let mut limited = multi.clone();
+        limited.limit_fields(3);
+        assert_eq!(counts(&limited), [2, 1, 0]);
+        assert_eq!(hexes(&limited), ["6801", "6802", "6803"]);
+    }
+
+    #[test]
+    fn test_encode_timing() {
+        let mut down = capsule("21", None);
+        assert!(down.encode_duration().is_none());
+        down.set_bytes_and_generate_hex(&[0x68, 0x16]).unwrap();
+        let rsp = JniResponse::downstream_response(&down).unwrap();
+        assert!(rsp.encode_micros().is_some());
+        assert!(rsp.decode_micros().is_none());
+    }
+
+    #[test]
+    fn test_multi_segments() {
+        let frames = ["6801", "6802"]
+            .iter()
+            .map(|hex| {
+                let mut c = capsule("21", Some(MsgTypeEnum::DeviceParamSetting));
+                c.set_bytes_and_generate_hex(&hex::decode(hex).unwrap())
+                    .unwrap();
+                c.set_fields(vec![ReportField::new("帧", "zhen", hex.to_string())]);
+                c
+            })
+            .collect();
+        let rsp = JniResponse::multi(frames).unwrap();
+        assert_eq!(rsp.rsp_hex(), "68016802");
+        assert_eq!(rsp.msg_type(), Some("device_param_setting"));
+        assert_eq!(rsp.segments().len(), 2);
+        assert_eq!(rsp.segments()[1].hex, "6802");
+        assert_eq!(rsp.rsp_jsons().len(), 2);
+    }
 }
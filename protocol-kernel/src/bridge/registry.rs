@@ -0,0 +1,529 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+use once_cell::sync::Lazy;
+use protocol_base::{ProtocolError, ProtocolResult};
+
+use crate::{
+    bridge::{
+        commands::CommandMapping,
+        dispatch::BridgeHandler,
+        metrics,
+        trace::{self, FrameSpan},
+        JniRequest, JniResponse, ProtocolDescription,
+    },
+    core::{cache::ProtocolCache, writer::EncodePreview},
+    utils, DirectionEnum, MsgTypeEnum,
+};
+
+/// 单个表计协议的处理入口。一个动态库可以注册多个协议，
+/// bridge 按 JniRequest::uri 路由到对应的 handler
+pub trait ProtocolHandler: Send + Sync {
+    /// 解析表端上报的帧 (请求带 hex)
+    fn decode_upstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse>;
+
+    /// 根据 cmd_code/params 生成下行帧 (请求不带 hex)
+    fn encode_downstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse>;
+
+    /// 支持的消息类型，为空表示不限制
+    fn supported_msg_types(&self) -> Vec<MsgTypeEnum> {
+        Vec::new()
+    }
+
+    fn describe(&self) -> Option<ProtocolDescription> {
+        None
+    }
+
+    /// 解码会产生的字段标题，注册时预先计算拼音 code
+    fn field_titles(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// 业务操作 (commands::recharge 等) 对应的命令码与参数映射，None 表示不支持
+    fn command_mapping(&self, _msg_type: &MsgTypeEnum) -> Option<CommandMapping> {
+        None
+    }
+
+    /// 下行帧预览 (不下发、不推进序号)，通常由 encode 流程在回填 CRC 前调用 Writer::preview 得到
+    fn preview_downstream(&self, _request: &JniRequest) -> ProtocolResult<EncodePreview> {
+        Err(ProtocolError::UnsupportedMode(
+            "downstream preview is not supported by this protocol".into(),
+        ))
+    }
+}
+
+static PROTOCOLS: Lazy<RwLock<HashMap<String, Arc<dyn ProtocolHandler>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+// 协议版本 (大写 hex) -> handler
+type VersionedHandlers = BTreeMap<String, Arc<dyn ProtocolHandler>>;
+
+static VERSIONS: Lazy<RwLock<HashMap<String, VersionedHandlers>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn precompute<H: ProtocolHandler>(handler: &H) {
+    utils::precompute_pinyin(handler.field_titles());
+    if let Some(description) = handler.describe() {
+        let params = description.commands.iter().flat_map(|c| c.params.iter());
+        utils::precompute_pinyin(params.map(|p| p.title.as_str()));
+    }
+}
+
+/// 注册 (或替换) 一个协议，protocol_id 与 Java 端下发的 uri 一致
+pub fn register_protocol<H: ProtocolHandler + 'static>(protocol_id: &str, handler: H) {
+    precompute(&handler);
+    let mut guard = PROTOCOLS.write().unwrap_or_else(|e| e.into_inner());
+    guard.insert(protocol_id.into(), Arc::new(handler));
+}
+
+/// 注册协议的某个版本。version 与设备上报的协议版本 (TransportCarrier::protocol_version) 的 hex 一致，
+/// 同一 protocol_id 可以注册多个版本，由 resolve_handler 按设备版本选择
+pub fn register_protocol_version<H: ProtocolHandler + 'static>(
+    protocol_id: &str,
+    version: &str,
+    handler: H,
+) {
+    precompute(&handler);
+    let mut guard = VERSIONS.write().unwrap_or_else(|e| e.into_inner());
+    guard
+        .entry(protocol_id.into())
+        .or_default()
+        .insert(version.to_uppercase(), Arc::new(handler));
+}
+
+/// 注销协议及其所有版本
+pub fn unregister_protocol(protocol_id: &str) -> bool {
+    let removed = {
+        let mut guard = VERSIONS.write().unwrap_or_else(|e| e.into_inner());
+        guard.remove(protocol_id).is_some()
+    };
+    let mut guard = PROTOCOLS.write().unwrap_or_else(|e| e.into_inner());
+    guard.remove(protocol_id).is_some() || removed
+}
+
+pub fn unregister_protocol_version(protocol_id: &str, version: &str) -> bool {
+    let mut guard = VERSIONS.write().unwrap_or_else(|e| e.into_inner());
+    let Some(versions) = guard.get_mut(protocol_id) else {
+        return false;
+    };
+    let removed = versions.remove(&version.to_uppercase()).is_some();
+    if versions.is_empty() {
+        guard.remove(protocol_id);
+    }
+    removed
+}
+
+pub fn protocol_ids() -> Vec<String> {
+    let mut ids: Vec<String> = {
+        let guard = PROTOCOLS.read().unwrap_or_else(|e| e.into_inner());
+        guard.keys().cloned().collect()
+    };
+    let guard = VERSIONS.read().unwrap_or_else(|e| e.into_inner());
+    ids.extend(guard.keys().cloned());
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// 协议已注册的版本，升序
+pub fn protocol_versions(protocol_id: &str) -> Vec<String> {
+    let guard = VERSIONS.read().unwrap_or_else(|e| e.into_inner());
+    guard
+        .get(protocol_id)
+        .map(|v| v.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub fn has_protocols() -> bool {
+    let versioned = {
+        let guard = VERSIONS.read().unwrap_or_else(|e| e.into_inner());
+        !guard.is_empty()
+    };
+    let guard = PROTOCOLS.read().unwrap_or_else(|e| e.into_inner());
+    versioned || !guard.is_empty()
+}
+
+/// 不区分版本的 handler: 优先 register_protocol 注册的，其次最新版本
+pub fn protocol_handler(protocol_id: &str) -> ProtocolResult<Arc<dyn ProtocolHandler>> {
+    resolve_handler(protocol_id, None)
+}
+
+/// 按设备的协议版本选择 handler，依次回退:
+/// 同版本 -> 低于该版本的最近版本 -> register_protocol 注册的默认 handler -> 最新版本
+pub fn resolve_handler(
+    protocol_id: &str,
+    version: Option<&str>,
+) -> ProtocolResult<Arc<dyn ProtocolHandler>> {
+    {
+        let guard = VERSIONS.read().unwrap_or_else(|e| e.into_inner());
+        if let (Some(versions), Some(version)) = (guard.get(protocol_id), version) {
+            // 版本按 hex 字符串比较，同一协议的版本应当等长
+            if let Some((_, handler)) = versions.range(..=version.to_uppercase()).next_back() {
+                return Ok(handler.clone());
+            }
+        }
+    }
+    let fallback = {
+        let guard = PROTOCOLS.read().unwrap_or_else(|e| e.into_inner());
+        guard.get(protocol_id).cloned()
+    };
+    fallback
+        .or_else(|| {
+            let guard = VERSIONS.read().unwrap_or_else(|e| e.into_inner());
+            guard
+                .get(protocol_id)
+                .and_then(|v| v.values().next_back().cloned())
+        })
+        .ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!("unknown protocol id '{}'", protocol_id))
+        })
+}
+
+// 设备缓存中记录的协议版本，先查以协议 id 命名的命名空间，再查默认命名空间。
+// 设备首帧之前没有缓存，使用默认 handler
+fn device_version(protocol_id: &str, request: &JniRequest) -> Option<String> {
+    let device_no = request.device_no()?;
+    let carrier = ProtocolCache::device_namespace(protocol_id, device_no).read(device_no)?;
+    carrier.protocol_version().map(|v| v.hex_clone())
+}
+
+/// 指定协议的能力描述
+pub fn describe(protocol_id: &str) -> ProtocolResult<Option<ProtocolDescription>> {
+    Ok(protocol_handler(protocol_id)?.describe())
+}
+
+/// 按 uri (及设备协议版本) 选择 handler 生成下行帧预览，供界面在下发前确认
+pub fn preview(request: &JniRequest) -> ProtocolResult<EncodePreview> {
+    let protocol_id = request
+        .uri()
+        .ok_or_else(|| ProtocolError::ValidationFailed("request uri is required".into()))?;
+    let version = device_version(protocol_id, request);
+    resolve_handler(protocol_id, version.as_deref())?.preview_downstream(request)
+}
+
+/// 按 uri 路由的 BridgeHandler。没有通过 register_handler 注册全局 handler 时，
+/// dispatch 会自动使用它
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProtocolRouter;
+
+impl BridgeHandler for ProtocolRouter {
+    fn handle(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        let protocol_id = request
+            .uri()
+            .ok_or_else(|| ProtocolError::ValidationFailed("request uri is required".into()))?;
+        let version = device_version(protocol_id, request);
+        let handler = resolve_handler(protocol_id, version.as_deref())?;
+        if let Some(msg_type) = request.msg_type() {
+            let supported = handler.supported_msg_types();
+            if !supported.is_empty() && !supported.iter().any(|m| m.code() == msg_type) {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "protocol '{}' does not support msg type '{}'",
+                    protocol_id, msg_type
+                )));
+            }
+        }
+        let direction = if request.hex().is_empty() {
+            DirectionEnum::Downstream
+        } else {
+            DirectionEnum::Upstream
+        };
+        let mut span = trace::frame_span(FrameSpan {
+            protocol_id: Some(protocol_id.into()),
+            device_no: request.device_no().map(Into::into),
+            cmd_code: request.cmd_code().map(Into::into),
+            direction: direction.clone(),
+            trace_id: request.trace_id().map(Into::into),
+        });
+        let started = Instant::now();
+        let result = match direction {
+            DirectionEnum::Downstream => handler.encode_downstream(request),
+            _ => handler.decode_upstream(request),
+        };
+        let cmd_code = match &result {
+            Ok(rsp) => {
+                if let Some(cmd_code) = rsp.cmd_code() {
+                    span.record_cmd_code(cmd_code);
+                }
+                rsp.cmd_code().or(request.cmd_code())
+            }
+            Err(e) => {
+                span.record_error(e);
+                request.cmd_code()
+            }
+        };
+        metrics::record_frame(
+            protocol_id,
+            &direction,
+            cmd_code,
+            started.elapsed(),
+            result.as_ref().err(),
+        );
+        result
+    }
+
+    /// 只注册了一个协议时返回它的描述，多个协议请用 registry::describe
+    fn describe(&self) -> Option<ProtocolDescription> {
+        match protocol_ids().as_slice() {
+            [protocol_id] => protocol_handler(protocol_id).ok()?.describe(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TransportCarrier;
+
+    struct Echo(&'static str);
+
+    impl ProtocolHandler for Echo {
+        fn decode_upstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+            let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+            rsp.set_req_hex(request.hex());
+            rsp.set_rsp_hex(self.0);
+            Ok(rsp)
+        }
+
+        fn encode_downstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+            let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+            rsp.set_rsp_hex(&format!("{}00", self.0));
+            Ok(rsp)
+        }
+
+        fn supported_msg_types(&self) -> Vec<MsgTypeEnum> {
+            vec![MsgTypeEnum::DataReport, MsgTypeEnum::Recharge]
+        }
+    }
+
+    fn request(json: &str) -> JniRequest {
+        JniRequest::from(json.as_bytes()).unwrap()
+    }
+
+    struct Previewing;
+
+    impl ProtocolHandler for Previewing {
+        fn decode_upstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+            JniResponse::from(br#"{"success":true}"#)
+        }
+
+        fn encode_downstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+            JniResponse::from(br#"{"success":true}"#)
+        }
+
+        fn preview_downstream(&self, request: &JniRequest) -> ProtocolResult<EncodePreview> {
+            let mut writer = crate::Writer::new();
+            writer
+                .write_bytes("命令码", &[0x31], request.cmd_code().unwrap_or_default())?
+                .write_placeholder("crc", 2)?;
+            Ok(writer.preview())
+        }
+    }
+
+    #[test]
+    fn test_preview() {
+        register_protocol("test/preview", Previewing);
+        register_protocol("test/no-preview", Echo("CC"));
+        let planned = preview(&request(r#"{"uri":"test/preview","cmdCode":"31"}"#)).unwrap();
+        assert_eq!(planned.hex, "31XXXX");
+        assert_eq!(planned.unresolved, ["crc"]);
+        assert!(matches!(
+            preview(&request(r#"{"uri":"test/no-preview"}"#)),
+            Err(ProtocolError::UnsupportedMode(_))
+        ));
+        unregister_protocol("test/preview");
+        unregister_protocol("test/no-preview");
+    }
+
+    #[test]
+    fn test_route_by_uri() {
+        register_protocol("test/gas", Echo("AA"));
+        register_protocol("test/water", Echo("BB"));
+
+        let rsp = ProtocolRouter
+            .handle(&request(r#"{"uri":"test/water","hex":"68"}"#))
+            .unwrap();
+        assert_eq!(rsp.rsp_hex(), "BB");
+        let rsp = ProtocolRouter
+            .handle(&request(
+                r#"{"uri":"test/gas","msgType":"charge_operation"}"#,
+            ))
+            .unwrap();
+        assert_eq!(rsp.rsp_hex(), "AA00");
+
+        assert!(ProtocolRouter
+            .handle(&request(
+                r#"{"uri":"test/gas","hex":"68","msgType":"heart_beat"}"#
+            ))
+            .is_err());
+        assert!(ProtocolRouter
+            .handle(&request(r#"{"uri":"test/none","hex":"68"}"#))
+            .is_err());
+        assert!(unregister_protocol("test/water"));
+    }
+
+    #[test]
+    fn test_route_by_protocol_version() {
+        register_protocol_version("test/versioned", "0102", Echo("V2"));
+        register_protocol_version("test/versioned", "0100", Echo("V0"));
+        assert_eq!(protocol_versions("test/versioned"), ["0100", "0102"]);
+
+        let handle = |json: &str| {
+            ProtocolRouter
+                .handle(&request(json))
+                .unwrap()
+                .rsp_hex_clone()
+        };
+        let json = r#"{"uri":"test/versioned","deviceNo":"20250916","hex":"68"}"#;
+        // 设备没有缓存时用最新版本
+        assert_eq!(handle(json), "V2");
+
+        let mut carrier =
+            TransportCarrier::new_with_device_no_and_upstream_count_hex("20250916", "01");
+        carrier.set_protocol_version("0101".into(), vec![0x01, 0x01]);
+        ProtocolCache::store("20250916", Arc::new(carrier));
+        assert_eq!(handle(json), "V0");
+
+        // 低于所有已注册版本时回退到默认 handler
+        register_protocol("test/versioned", Echo("DF"));
+        assert_eq!(
+            resolve_handler("test/versioned", Some("00FF"))
+                .unwrap()
+                .encode_downstream(&request("{}"))
+                .unwrap()
+                .rsp_hex(),
+            "DF00"
+        );
+        assert_eq!(handle(json), "V0");
+
+        ProtocolCache::remove("20250916");
+
+        // 协议使用自己的命名空间保存设备状态
+        let mut carrier =
+            TransportCarrier::new_with_device_no_and_upstream_count_hex("20250917", "01");
+        carrier.set_protocol_version("0100".into(), vec![0x01, 0x00]);
+        let namespace = ProtocolCache::namespace("test/versioned");
+        namespace.store("20250917", Arc::new(carrier));
+        let json = r#"{"uri":"test/versioned","deviceNo":"20250917","hex":"68"}"#;
+        assert_eq!(handle(json), "V0");
+        // 没有缓存时回退到默认 handler
+        namespace.remove("20250917");
+        assert_eq!(handle(json), "DF");
+
+        assert!(unregister_protocol_version("test/versioned", "0100"));
+        assert!(unregister_protocol("test/versioned"));
+        assert!(protocol_versions("test/versioned").is_empty());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_router_metrics() {
+        use crate::bridge::metrics::*;
+        use ::metrics::{
+            Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString,
+            Unit,
+        };
+        use std::sync::{
+            atomic::{AtomicU64, Ordering},
+            Mutex,
+        };
+
+        // 按 "name{k=v,...}" (标签排序) 累计计数，直方图只记录样本数
+        #[derive(Default)]
+        struct Counting(Mutex<BTreeMap<String, Arc<AtomicU64>>>);
+
+        struct Samples(Arc<AtomicU64>);
+
+        impl HistogramFn for Samples {
+            fn record(&self, _value: f64) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        impl Counting {
+            fn slot(&self, key: &str) -> Arc<AtomicU64> {
+                let mut slots = self.0.lock().unwrap();
+                slots.entry(key.into()).or_default().clone()
+            }
+
+            fn get(&self, name: &str, labels: &[(&str, &str)]) -> u64 {
+                let mut labels: Vec<String> =
+                    labels.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+                labels.sort();
+                let key = format!("{}{{{}}}", name, labels.join(","));
+                self.slot(&key).load(Ordering::Relaxed)
+            }
+
+            fn key(key: &Key) -> String {
+                let mut labels: Vec<String> = key
+                    .labels()
+                    .map(|l| format!("{}={}", l.key(), l.value()))
+                    .collect();
+                labels.sort();
+                format!("{}{{{}}}", key.name(), labels.join(","))
+            }
+        }
+
+        impl Recorder for Counting {
+            fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+            fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+            fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+            fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+                Counter::from_arc(self.slot(&Self::key(key)))
+            }
+
+            fn register_gauge(&self, _: &Key, _: &Metadata<'_>) -> Gauge {
+                Gauge::noop()
+            }
+
+            fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+                Histogram::from_arc(Arc::new(Samples(self.slot(&Self::key(key)))))
+            }
+        }
+
+        struct Failing;
+
+        impl ProtocolHandler for Failing {
+            fn decode_upstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+                Err(ProtocolError::CrcError {
+                    ori_crc: 1,
+                    calc_crc: 2,
+                })
+            }
+
+            fn encode_downstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+                Err(ProtocolError::CryptoError("bad key".into()))
+            }
+        }
+
+        let recorder = Counting::default();
+        register_protocol("test/metrics", Failing);
+        // 线程内的 recorder，不受其它并发测试影响
+        ::metrics::with_local_recorder(&recorder, || {
+            let _ = ProtocolRouter.handle(&request(r#"{"uri":"test/metrics","hex":"68"}"#));
+            let _ = ProtocolRouter.handle(&request(r#"{"uri":"test/metrics","cmdCode":"25"}"#));
+        });
+        unregister_protocol("test/metrics");
+
+        let protocol = ("protocol", "test/metrics");
+        assert_eq!(
+            recorder.get(
+                FRAMES_TOTAL,
+                &[protocol, ("direction", "up"), ("result", "error")]
+            ),
+            1
+        );
+        assert_eq!(recorder.get(CRC_FAILURES_TOTAL, &[protocol]), 1);
+        assert_eq!(recorder.get(CIPHER_ERRORS_TOTAL, &[protocol]), 1);
+        assert_eq!(
+            recorder.get(BRIDGE_LATENCY_SECONDS, &[protocol, ("cmd_code", "25")]),
+            1
+        );
+    }
+}
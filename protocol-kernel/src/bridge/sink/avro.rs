@@ -0,0 +1,150 @@
+use crate::bridge::{sink::put_varlong, JniResponse, ReportField};
+
+/// PayloadFormat::Avro 的 schema，字段与 JniResponse 的 JSON 一致。
+/// 消息体是不带 schema 头的 Avro 二进制，消费端需要用这份 schema (或登记到 schema registry 后) 解码
+pub const AVRO_SCHEMA: &str = r#"{
+  "type": "record",
+  "name": "DecodedReport",
+  "namespace": "protocol.framework",
+  "fields": [
+    {"name": "success", "type": "boolean"},
+    {"name": "deviceId", "type": ["null", "string"], "default": null},
+    {"name": "deviceNo", "type": ["null", "string"], "default": null},
+    {"name": "msgType", "type": ["null", "string"], "default": null},
+    {"name": "cmdCode", "type": ["null", "string"], "default": null},
+    {"name": "traceId", "type": ["null", "string"], "default": null},
+    {"name": "reqHex", "type": "string"},
+    {"name": "rspHex", "type": "string"},
+    {"name": "reqJsons", "type": {"type": "array", "items": {
+      "type": "record",
+      "name": "ReportField",
+      "fields": [
+        {"name": "name", "type": "string"},
+        {"name": "code", "type": "string"},
+        {"name": "value", "type": "string"},
+        {"name": "alert", "type": "boolean"},
+        {"name": "valueType", "type": ["null", "string"], "default": null},
+        {"name": "rawHex", "type": ["null", "string"], "default": null},
+        {"name": "unit", "type": ["null", "string"], "default": null},
+        {"name": "scale", "type": ["null", "double"], "default": null}
+      ]
+    }}},
+    {"name": "rspJsons", "type": {"type": "array", "items": "ReportField"}},
+    {"name": "errCode", "type": ["null", "string"], "default": null},
+    {"name": "errMsg", "type": ["null", "string"], "default": null}
+  ]
+}"#;
+
+/// 按 AVRO_SCHEMA 编码
+pub fn encode_response(rsp: &JniResponse) -> Vec<u8> {
+    let mut buf = Vec::new();
+    put_bool(&mut buf, rsp.success());
+    put_optional_str(&mut buf, rsp.device_id());
+    put_optional_str(&mut buf, rsp.device_no());
+    put_optional_str(&mut buf, rsp.msg_type());
+    put_optional_str(&mut buf, rsp.cmd_code());
+    put_optional_str(&mut buf, rsp.trace_id());
+    put_str(&mut buf, rsp.req_hex());
+    put_str(&mut buf, rsp.rsp_hex());
+    put_fields(&mut buf, rsp.req_jsons());
+    put_fields(&mut buf, rsp.rsp_jsons());
+    put_optional_str(&mut buf, rsp.err_code());
+    put_optional_str(&mut buf, rsp.err_msg());
+    buf
+}
+
+fn put_bool(buf: &mut Vec<u8>, value: bool) {
+    buf.push(value as u8);
+}
+
+fn put_str(buf: &mut Vec<u8>, value: &str) {
+    put_varlong(buf, value.len() as i64);
+    buf.extend_from_slice(value.as_bytes());
+}
+
+// ["null", T] 的 union，分支序号 0 为 null
+fn put_optional_str(buf: &mut Vec<u8>, value: Option<&str>) {
+    match value {
+        Some(v) => {
+            put_varlong(buf, 1);
+            put_str(buf, v);
+        }
+        None => put_varlong(buf, 0),
+    }
+}
+
+// 数组只写一个块: 元素个数 + 元素 + 结束标记 0
+fn put_fields(buf: &mut Vec<u8>, fields: &[ReportField]) {
+    if !fields.is_empty() {
+        put_varlong(buf, fields.len() as i64);
+        for field in fields {
+            put_str(buf, &field.name);
+            put_str(buf, &field.code);
+            put_str(buf, &field.value);
+            put_bool(buf, field.alert);
+            put_optional_str(buf, field.value_type.map(|t| t.as_str()));
+            put_optional_str(buf, field.raw_hex.as_deref());
+            put_optional_str(buf, field.unit.as_deref());
+            match field.scale {
+                Some(scale) => {
+                    put_varlong(buf, 1);
+                    buf.extend_from_slice(&scale.to_le_bytes());
+                }
+                None => put_varlong(buf, 0),
+            }
+        }
+    }
+    put_varlong(buf, 0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_is_json() {
+        let schema: serde_json::Value = serde_json::from_str(AVRO_SCHEMA).unwrap();
+        let names: Vec<&str> = schema["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names.len(), 12);
+        assert_eq!(names[0], "success");
+    }
+
+    #[test]
+    fn test_encode_response() {
+        let mut rsp = JniResponse::empty();
+        rsp.set_device_no("01");
+        rsp.set_req_hex("AB");
+        let field = ReportField {
+            name: "x".into(),
+            code: "c".into(),
+            value: "1".into(),
+            alert: true,
+            value_type: None,
+            raw_hex: None,
+            unit: Some("m".into()),
+            scale: None,
+        };
+        rsp.set_req_jsons(vec![field]);
+        assert_eq!(
+            encode_response(&rsp),
+            [
+                0x01, // success
+                0x00, // deviceId null
+                0x02, 0x04, b'0', b'1', // deviceNo
+                0x00, 0x00, 0x00, // msgType, cmdCode, traceId
+                0x04, b'A', b'B', // reqHex
+                0x00, // rspHex
+                0x02, // reqJsons 1 个元素
+                0x02, b'x', 0x02, b'c', 0x02, b'1', 0x01, 0x00, 0x00, 0x02, 0x02, b'm', 0x00,
+                0x00, // reqJsons 结束
+                0x00, // rspJsons
+                0x00, 0x00, // errCode, errMsg
+            ]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! 解析结果的输出端。平台通常把遥测数据落到 Kafka 等消息系统，
+//! 这里直接把 JniResponse 序列化成消息 (key + JSON/Avro 消息体)，省去 Java 端的二次序列化。
+//! 具体的消息系统客户端在各自的集成 crate 中实现 ReportSink (如 protocol-kafka)
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use protocol_base::{ProtocolError, ProtocolResult};
+
+use crate::bridge::JniResponse;
+
+pub mod avro;
+
+pub use avro::AVRO_SCHEMA;
+
+/// 解析结果的发布目标，一次发布一批响应
+pub trait ReportSink: Send + Sync {
+    fn publish(&self, batch: &[JniResponse]) -> ProtocolResult<()>;
+}
+
+/// 消息 key 的来源。key 为空时由 sink 自行分配分区
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordKey {
+    #[default]
+    DeviceNo,
+    DeviceId,
+    None,
+}
+
+/// 消息体格式。Json 与 bridge 返回给 Java 端的 JSON 相同，Avro 按 AVRO_SCHEMA 编码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PayloadFormat {
+    #[default]
+    Json,
+    Avro,
+}
+
+/// 一条待发布的消息
+#[derive(Debug, Clone, PartialEq)]
+pub struct SinkRecord {
+    pub key: Option<Vec<u8>>,
+    pub value: Vec<u8>,
+    // 毫秒时间戳
+    pub timestamp: i64,
+}
+
+impl SinkRecord {
+    pub fn of(rsp: &JniResponse, key: RecordKey, format: PayloadFormat) -> ProtocolResult<Self> {
+        let key = match key {
+            RecordKey::DeviceNo => rsp.device_no(),
+            RecordKey::DeviceId => rsp.device_id(),
+            RecordKey::None => None,
+        }
+        .filter(|k| !k.is_empty())
+        .map(|k| k.as_bytes().to_vec());
+        let value = match format {
+            PayloadFormat::Json => rsp.to_bytes()?,
+            PayloadFormat::Avro => avro::encode_response(rsp),
+        };
+        Ok(Self {
+            key,
+            value,
+            timestamp: now_millis()?,
+        })
+    }
+}
+
+fn now_millis() -> ProtocolResult<i64> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| ProtocolError::CommonError(e.to_string()))?;
+    Ok(i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX))
+}
+
+/// zigzag 变长整数 (Avro long)
+pub(crate) fn put_varlong(buf: &mut Vec<u8>, value: i64) {
+    let mut v = ((value << 1) ^ (value >> 63)) as u64;
+    while v >= 0x80 {
+        buf.push((v as u8 & 0x7F) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varlong() {
+        let encode = |v| {
+            let mut buf = Vec::new();
+            put_varlong(&mut buf, v);
+            buf
+        };
+        assert_eq!(encode(0), [0x00]);
+        assert_eq!(encode(-1), [0x01]);
+        assert_eq!(encode(1), [0x02]);
+        assert_eq!(encode(-64), [0x7F]);
+        assert_eq!(encode(64), [0x80, 0x01]);
+        assert_eq!(encode(300), [0xD8, 0x04]);
+    }
+
+    #[test]
+    fn test_record_key() {
+        let mut rsp = JniResponse::empty();
+        rsp.set_device_id("gw-1");
+        rsp.set_device_no("");
+        let record = SinkRecord::of(&rsp, RecordKey::DeviceNo, PayloadFormat::Json).unwrap();
+        assert_eq!(record.key, None);
+        assert!(record.timestamp > 0);
+        let record = SinkRecord::of(&rsp, RecordKey::DeviceId, PayloadFormat::Json).unwrap();
+        assert_eq!(record.key.as_deref(), Some(&b"gw-1"[..]));
+        assert_eq!(
+            JniResponse::from(&record.value).unwrap().device_id(),
+            Some("gw-1")
+        );
+    }
+}
@@ -0,0 +1,682 @@
+use std::collections::{BTreeMap, HashMap};
+
+use protocol_base::{ErrorEnvelope, ProtocolError, ProtocolResult};
+
+use crate::{
+    bridge::{
+        batch::{JniBatchRequest, JniBatchResponse},
+        JniRequest, JniResponse, PageInfo, ReportField, ResponseSegment, ValueType,
+    },
+    core::{event::DeviceEvent, parts::diagnostic::Diagnostic},
+    DecodeMode,
+};
+
+// 二进制 bridge 格式:
+// [MAGIC][VERSION][KIND] 之后是连续的 TLV: tag(1 byte) + len(4 bytes, 大端) + value。
+// hex 字段直接以原始字节存放 (而不是 hex 字符串)，体积约为 JSON 的一半。
+// 未知 tag 会被跳过，新增字段只能追加新的 tag。
+pub(crate) const MAGIC: u8 = 0xB7;
+const VERSION: u8 = 0x01;
+const KIND_REQUEST: u8 = 0x01;
+const KIND_RESPONSE: u8 = 0x02;
+const KIND_BATCH_REQUEST: u8 = 0x03;
+const KIND_BATCH_RESPONSE: u8 = 0x04;
+
+/// JniRequest/JniResponse 的序列化格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BridgeFormat {
+    #[default]
+    Json,
+    Tlv,
+}
+
+impl BridgeFormat {
+    /// 根据首字节判断格式。JSON 不可能以 MAGIC (非法 UTF-8 起始字节) 开头
+    pub fn detect(data: &[u8]) -> Self {
+        if data.first() == Some(&MAGIC) {
+            BridgeFormat::Tlv
+        } else {
+            BridgeFormat::Json
+        }
+    }
+}
+
+#[derive(Default)]
+struct TlvWriter {
+    buf: Vec<u8>,
+}
+
+impl TlvWriter {
+    fn with_header(kind: u8) -> Self {
+        Self {
+            buf: vec![MAGIC, VERSION, kind],
+        }
+    }
+
+    fn put(&mut self, tag: u8, value: &[u8]) {
+        self.buf.push(tag);
+        self.buf
+            .extend_from_slice(&(value.len() as u32).to_be_bytes());
+        self.buf.extend_from_slice(value);
+    }
+
+    fn put_str(&mut self, tag: u8, value: &str) {
+        self.put(tag, value.as_bytes());
+    }
+
+    fn put_opt_str(&mut self, tag: u8, value: &Option<String>) {
+        if let Some(v) = value {
+            self.put_str(tag, v);
+        }
+    }
+
+    fn put_bool(&mut self, tag: u8, value: bool) {
+        self.put(tag, &[value as u8]);
+    }
+
+    fn put_u64(&mut self, tag: u8, value: u64) {
+        self.put(tag, &value.to_be_bytes());
+    }
+
+    // hex 能无损还原时存原始字节 (bytes_tag)，否则原样存字符串 (str_tag)
+    fn put_hex(&mut self, bytes_tag: u8, str_tag: u8, hex: &str) {
+        match hex::decode(hex) {
+            Ok(bytes) if hex::encode_upper(&bytes) == hex => self.put(bytes_tag, &bytes),
+            _ => self.put_str(str_tag, hex),
+        }
+    }
+
+    fn put_nested(&mut self, tag: u8, nested: TlvWriter) {
+        self.put(tag, &nested.buf);
+    }
+}
+
+struct TlvReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TlvReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn with_header(data: &'a [u8], kind: u8) -> ProtocolResult<Self> {
+        if data.len() < 3 || data[0] != MAGIC {
+            return Err(ProtocolError::CommonError(
+                "tlv: missing bridge magic header".into(),
+            ));
+        }
+        if data[1] != VERSION {
+            return Err(ProtocolError::CommonError(format!(
+                "tlv: unsupported version {}",
+                data[1]
+            )));
+        }
+        if data[2] != kind {
+            return Err(ProtocolError::CommonError(format!(
+                "tlv: expected kind {}, got {}",
+                kind, data[2]
+            )));
+        }
+        Ok(Self { data, pos: 3 })
+    }
+
+    fn next_entry(&mut self) -> ProtocolResult<Option<(u8, &'a [u8])>> {
+        if self.pos == self.data.len() {
+            return Ok(None);
+        }
+        if self.data.len() - self.pos < 5 {
+            return Err(ProtocolError::CommonError(format!(
+                "tlv: truncated entry header at offset {}",
+                self.pos
+            )));
+        }
+        let tag = self.data[self.pos];
+        let len_bytes: [u8; 4] = self.data[self.pos + 1..self.pos + 5].try_into().unwrap();
+        let len = u32::from_be_bytes(len_bytes) as usize;
+        let start = self.pos + 5;
+        if self.data.len() - start < len {
+            return Err(ProtocolError::CommonError(format!(
+                "tlv: tag {} at offset {} declares {} bytes but only {} remain",
+                tag,
+                self.pos,
+                len,
+                self.data.len() - start
+            )));
+        }
+        self.pos = start + len;
+        Ok(Some((tag, &self.data[start..start + len])))
+    }
+}
+
+fn as_string(value: &[u8]) -> ProtocolResult<String> {
+    String::from_utf8(value.to_vec())
+        .map_err(|e| ProtocolError::CommonError(format!("tlv: invalid utf-8: {}", e)))
+}
+
+fn as_bool(value: &[u8]) -> ProtocolResult<bool> {
+    match value {
+        [b] => Ok(*b != 0),
+        _ => Err(ProtocolError::CommonError(
+            "tlv: bool value must be 1 byte".into(),
+        )),
+    }
+}
+
+fn as_u64(value: &[u8]) -> ProtocolResult<u64> {
+    let arr: [u8; 8] = value
+        .try_into()
+        .map_err(|_| ProtocolError::CommonError("tlv: u64 value must be 8 bytes".into()))?;
+    Ok(u64::from_be_bytes(arr))
+}
+
+fn write_pair(key: &str, value: &str) -> TlvWriter {
+    let mut w = TlvWriter::default();
+    w.put_str(1, key);
+    w.put_str(2, value);
+    w
+}
+
+fn read_pair(data: &[u8]) -> ProtocolResult<(String, String)> {
+    let mut r = TlvReader::new(data);
+    let (mut key, mut value) = (String::new(), String::new());
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => key = as_string(v)?,
+            2 => value = as_string(v)?,
+            _ => {}
+        }
+    }
+    Ok((key, value))
+}
+
+fn write_report_field(field: &ReportField) -> TlvWriter {
+    let mut w = TlvWriter::default();
+    w.put_str(1, &field.name);
+    w.put_str(2, &field.code);
+    w.put_str(3, &field.value);
+    w.put_bool(4, field.alert);
+    if let Some(value_type) = field.value_type {
+        w.put_str(5, value_type.as_str());
+    }
+    if let Some(raw_hex) = &field.raw_hex {
+        w.put_hex(6, 7, raw_hex);
+    }
+    w.put_opt_str(8, &field.unit);
+    if let Some(scale) = field.scale {
+        w.put_u64(9, scale.to_bits());
+    }
+    w
+}
+
+fn read_report_field(data: &[u8]) -> ProtocolResult<ReportField> {
+    let mut r = TlvReader::new(data);
+    let mut field = ReportField::new("", "", String::new());
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => field.name = as_string(v)?.into(),
+            2 => field.code = as_string(v)?,
+            3 => field.value = as_string(v)?,
+            4 => field.alert = as_bool(v)?,
+            5 => field.value_type = ValueType::code_of(&as_string(v)?),
+            6 => field.raw_hex = Some(hex::encode_upper(v)),
+            7 => field.raw_hex = Some(as_string(v)?),
+            8 => field.unit = Some(as_string(v)?),
+            9 => field.scale = Some(f64::from_bits(as_u64(v)?)),
+            _ => {}
+        }
+    }
+    Ok(field)
+}
+
+fn write_envelope(envelope: &ErrorEnvelope) -> TlvWriter {
+    let mut w = TlvWriter::default();
+    w.put_str(1, &envelope.code);
+    w.put_str(2, &envelope.message);
+    for (k, v) in &envelope.details {
+        w.put_nested(3, write_pair(k, v));
+    }
+    w
+}
+
+fn read_envelope(data: &[u8]) -> ProtocolResult<ErrorEnvelope> {
+    let mut r = TlvReader::new(data);
+    let mut envelope = ErrorEnvelope {
+        code: String::new(),
+        message: String::new(),
+        details: BTreeMap::new(),
+    };
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => envelope.code = as_string(v)?,
+            2 => envelope.message = as_string(v)?,
+            3 => {
+                let (k, val) = read_pair(v)?;
+                envelope.details.insert(k, val);
+            }
+            _ => {}
+        }
+    }
+    Ok(envelope)
+}
+
+fn write_diagnostic(diagnostic: &Diagnostic) -> TlvWriter {
+    let mut w = TlvWriter::default();
+    w.put_str(1, &diagnostic.code);
+    w.put_str(2, &diagnostic.message);
+    w.put_opt_str(3, &diagnostic.field);
+    if let Some(offset) = diagnostic.offset {
+        w.put_u64(4, offset as u64);
+    }
+    w
+}
+
+fn read_diagnostic(data: &[u8]) -> ProtocolResult<Diagnostic> {
+    let mut r = TlvReader::new(data);
+    let mut diagnostic = Diagnostic::new("", "");
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => diagnostic.code = as_string(v)?,
+            2 => diagnostic.message = as_string(v)?,
+            3 => diagnostic.field = Some(as_string(v)?),
+            4 => diagnostic.offset = Some(as_u64(v)? as usize),
+            _ => {}
+        }
+    }
+    Ok(diagnostic)
+}
+
+fn write_segment(segment: &ResponseSegment) -> TlvWriter {
+    let mut w = TlvWriter::default();
+    w.put_opt_str(1, &segment.cmd_code);
+    w.put_hex(2, 3, &segment.hex);
+    for field in &segment.fields {
+        w.put_nested(4, write_report_field(field));
+    }
+    w
+}
+
+fn read_segment(data: &[u8]) -> ProtocolResult<ResponseSegment> {
+    let mut r = TlvReader::new(data);
+    let mut segment = ResponseSegment {
+        cmd_code: None,
+        hex: String::new(),
+        fields: Vec::new(),
+    };
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => segment.cmd_code = Some(as_string(v)?),
+            2 => segment.hex = hex::encode_upper(v),
+            3 => segment.hex = as_string(v)?,
+            4 => segment.fields.push(read_report_field(v)?),
+            _ => {}
+        }
+    }
+    Ok(segment)
+}
+
+fn write_event(event: &DeviceEvent) -> TlvWriter {
+    let mut w = TlvWriter::default();
+    w.put_str(1, &event.code);
+    w.put_str(2, &event.title);
+    w.put_bool(3, event.alert);
+    w.put_opt_str(4, &event.occurred_at);
+    for field in &event.params {
+        w.put_nested(5, write_report_field(field));
+    }
+    w.put_hex(6, 7, &event.raw_hex);
+    w
+}
+
+fn read_event(data: &[u8]) -> ProtocolResult<DeviceEvent> {
+    let mut r = TlvReader::new(data);
+    let mut event = DeviceEvent {
+        code: String::new(),
+        title: String::new(),
+        alert: false,
+        occurred_at: None,
+        params: Vec::new(),
+        raw_hex: String::new(),
+    };
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => event.code = as_string(v)?,
+            2 => event.title = as_string(v)?,
+            3 => event.alert = as_bool(v)?,
+            4 => event.occurred_at = Some(as_string(v)?),
+            5 => event.params.push(read_report_field(v)?),
+            6 => event.raw_hex = hex::encode_upper(v),
+            7 => event.raw_hex = as_string(v)?,
+            _ => {}
+        }
+    }
+    Ok(event)
+}
+
+fn read_page(data: &[u8]) -> ProtocolResult<PageInfo> {
+    let mut r = TlvReader::new(data);
+    let mut page = PageInfo { index: 0, count: 0 };
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => page.index = as_u64(v)? as usize,
+            2 => page.count = as_u64(v)? as usize,
+            _ => {}
+        }
+    }
+    Ok(page)
+}
+
+pub(crate) fn encode_request(req: &JniRequest) -> Vec<u8> {
+    let mut w = TlvWriter::with_header(KIND_REQUEST);
+    w.put_opt_str(1, &req.device_id);
+    w.put_opt_str(2, &req.device_no);
+    w.put_opt_str(3, &req.msg_type);
+    w.put_opt_str(4, &req.cmd_code);
+    w.put_hex(5, 6, &req.hex);
+    w.put_opt_str(7, &req.uri);
+    if let Some(params) = &req.params {
+        // 空 map 也要保留 Some 语义
+        w.put(8, &[]);
+        for (k, v) in params {
+            w.put_nested(9, write_pair(k, v));
+        }
+    }
+    w.put_opt_str(10, &req.trace_id);
+    if req.decode_mode() == DecodeMode::Lenient {
+        w.put_bool(11, true);
+    }
+    w.buf
+}
+
+pub(crate) fn decode_request(data: &[u8]) -> ProtocolResult<JniRequest> {
+    let mut r = TlvReader::with_header(data, KIND_REQUEST)?;
+    let mut req = JniRequest::new(None, None, None, None, String::new(), None, None);
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => req.device_id = Some(as_string(v)?),
+            2 => req.device_no = Some(as_string(v)?),
+            3 => req.msg_type = Some(as_string(v)?),
+            4 => req.cmd_code = Some(as_string(v)?),
+            5 => req.hex = hex::encode_upper(v),
+            6 => req.hex = as_string(v)?,
+            7 => req.uri = Some(as_string(v)?),
+            8 => {
+                req.params.get_or_insert_with(HashMap::new);
+            }
+            9 => {
+                let (k, val) = read_pair(v)?;
+                req.params.get_or_insert_with(HashMap::new).insert(k, val);
+            }
+            10 => req.trace_id = Some(as_string(v)?),
+            11 => req.decode_mode = as_bool(v)?.then_some(DecodeMode::Lenient),
+            _ => {}
+        }
+    }
+    Ok(req)
+}
+
+pub(crate) fn encode_response(rsp: &JniResponse) -> Vec<u8> {
+    let mut w = TlvWriter::with_header(KIND_RESPONSE);
+    w.put_bool(1, rsp.success);
+    w.put_opt_str(2, &rsp.device_id);
+    w.put_opt_str(3, &rsp.device_no);
+    w.put_opt_str(4, &rsp.msg_type);
+    w.put_opt_str(5, &rsp.cmd_code);
+    w.put_hex(6, 7, &rsp.req_hex);
+    w.put_hex(8, 9, &rsp.rsp_hex);
+    for field in &rsp.req_jsons {
+        w.put_nested(10, write_report_field(field));
+    }
+    for field in &rsp.rsp_jsons {
+        w.put_nested(11, write_report_field(field));
+    }
+    w.put_opt_str(12, &rsp.err_msg);
+    w.put_opt_str(13, &rsp.err_code);
+    if let Some(envelope) = &rsp.error {
+        w.put_nested(14, write_envelope(envelope));
+    }
+    for diagnostic in &rsp.warnings {
+        w.put_nested(15, write_diagnostic(diagnostic));
+    }
+    if rsp.truncated {
+        w.put_bool(16, true);
+    }
+    if let Some(total) = rsp.total_fields {
+        w.put_u64(17, total as u64);
+    }
+    if let Some(page) = rsp.page {
+        let mut p = TlvWriter::default();
+        p.put_u64(1, page.index as u64);
+        p.put_u64(2, page.count as u64);
+        w.put_nested(18, p);
+    }
+    w.put_opt_str(19, &rsp.trace_id);
+    for segment in &rsp.segments {
+        w.put_nested(20, write_segment(segment));
+    }
+    if rsp.duplicate {
+        w.put_bool(21, true);
+    }
+    if let Some(micros) = rsp.decode_micros {
+        w.put_u64(22, micros);
+    }
+    if let Some(micros) = rsp.encode_micros {
+        w.put_u64(23, micros);
+    }
+    w.put_opt_str(24, &rsp.idempotency_token);
+    for event in &rsp.events {
+        w.put_nested(25, write_event(event));
+    }
+    w.buf
+}
+
+pub(crate) fn decode_response(data: &[u8]) -> ProtocolResult<JniResponse> {
+    let mut r = TlvReader::with_header(data, KIND_RESPONSE)?;
+    let mut rsp = JniResponse::empty();
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => rsp.success = as_bool(v)?,
+            2 => rsp.device_id = Some(as_string(v)?),
+            3 => rsp.device_no = Some(as_string(v)?),
+            4 => rsp.msg_type = Some(as_string(v)?),
+            5 => rsp.cmd_code = Some(as_string(v)?),
+            6 => rsp.req_hex = hex::encode_upper(v),
+            7 => rsp.req_hex = as_string(v)?,
+            8 => rsp.rsp_hex = hex::encode_upper(v),
+            9 => rsp.rsp_hex = as_string(v)?,
+            10 => rsp.req_jsons.push(read_report_field(v)?),
+            11 => rsp.rsp_jsons.push(read_report_field(v)?),
+            12 => rsp.err_msg = Some(as_string(v)?),
+            13 => rsp.err_code = Some(as_string(v)?),
+            14 => rsp.error = Some(read_envelope(v)?),
+            15 => rsp.warnings.push(read_diagnostic(v)?),
+            16 => rsp.truncated = as_bool(v)?,
+            17 => rsp.total_fields = Some(as_u64(v)? as usize),
+            18 => rsp.page = Some(read_page(v)?),
+            19 => rsp.trace_id = Some(as_string(v)?),
+            20 => rsp.segments.push(read_segment(v)?),
+            21 => rsp.duplicate = as_bool(v)?,
+            22 => rsp.decode_micros = Some(as_u64(v)?),
+            23 => rsp.encode_micros = Some(as_u64(v)?),
+            24 => rsp.idempotency_token = Some(as_string(v)?),
+            25 => rsp.events.push(read_event(v)?),
+            _ => {}
+        }
+    }
+    Ok(rsp)
+}
+
+// 批量格式: 每个 item 都是一个完整的 (带头部的) 单条编码，嵌套在 tag 2 中
+pub(crate) fn encode_batch_request(batch: &JniBatchRequest) -> Vec<u8> {
+    let mut w = TlvWriter::with_header(KIND_BATCH_REQUEST);
+    w.put_opt_str(1, &batch.batch_id);
+    for item in &batch.items {
+        w.put(2, &encode_request(item));
+    }
+    w.buf
+}
+
+pub(crate) fn decode_batch_request(data: &[u8]) -> ProtocolResult<JniBatchRequest> {
+    let mut r = TlvReader::with_header(data, KIND_BATCH_REQUEST)?;
+    let mut batch = JniBatchRequest::new(Vec::new());
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => batch.batch_id = Some(as_string(v)?),
+            2 => batch.items.push(decode_request(v)?),
+            _ => {}
+        }
+    }
+    Ok(batch)
+}
+
+pub(crate) fn encode_batch_response(batch: &JniBatchResponse) -> Vec<u8> {
+    let mut w = TlvWriter::with_header(KIND_BATCH_RESPONSE);
+    w.put_opt_str(1, &batch.batch_id);
+    for item in &batch.items {
+        w.put(2, &encode_response(item));
+    }
+    w.buf
+}
+
+pub(crate) fn decode_batch_response(data: &[u8]) -> ProtocolResult<JniBatchResponse> {
+    let mut r = TlvReader::with_header(data, KIND_BATCH_RESPONSE)?;
+    let mut batch_id = None;
+    let mut items = Vec::new();
+    while let Some((tag, v)) = r.next_entry()? {
+        match tag {
+            1 => batch_id = Some(as_string(v)?),
+            2 => items.push(decode_response(v)?),
+            _ => {}
+        }
+    }
+    Ok(JniBatchResponse::new(batch_id, items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_roundtrip() {
+        let mut params = HashMap::new();
+        params.insert("k".to_string(), "v".to_string());
+        let req = JniRequest::new(
+            None,
+            Some("0001".into()),
+            Some("up".into()),
+            None,
+            "68AABB16".into(),
+            None,
+            Some(params),
+        )
+        .with_trace_id("t-1")
+        .with_decode_mode(DecodeMode::Lenient);
+        let bytes = encode_request(&req);
+        assert_eq!(BridgeFormat::detect(&bytes), BridgeFormat::Tlv);
+        let back = decode_request(&bytes).unwrap();
+        assert_eq!(back.hex(), "68AABB16");
+        assert_eq!(back.device_no(), Some("0001"));
+        assert_eq!(back.device_id(), None);
+        assert_eq!(back.trace_id(), Some("t-1"));
+        assert_eq!(back.decode_mode(), DecodeMode::Lenient);
+        assert_eq!(
+            back.params().unwrap().get("k").map(|s| s.as_str()),
+            Some("v")
+        );
+        // 小写 hex 无法由字节无损还原，按字符串保存
+        let req = JniRequest::new(None, None, None, None, "ab".into(), None, None);
+        assert_eq!(decode_request(&encode_request(&req)).unwrap().hex(), "ab");
+    }
+
+    #[test]
+    fn test_response_roundtrip() {
+        let err = ProtocolError::InputTooShort {
+            needed: 2,
+            available: 1,
+        };
+        let mut rsp = JniResponse::from_error(&err);
+        rsp.set_rsp_hex("0102");
+        rsp.set_rsp_jsons(vec![ReportField::new("电压", "dian_ya", "220".into())
+            .with_value_type(ValueType::Int)
+            .with_raw_hex("00DC")
+            .with_unit("V")
+            .with_scale(1.0)]);
+        rsp.set_warnings(vec![Diagnostic::new("W_001", "reserved").with_offset(3)]);
+        let back = decode_response(&encode_response(&rsp)).unwrap();
+        assert!(!back.success());
+        assert_eq!(back.rsp_hex(), "0102");
+        assert_eq!(back.rsp_jsons(), rsp.rsp_jsons());
+        assert_eq!(back.err_code(), Some("E_CORE_002"));
+        assert_eq!(back.error(), rsp.error());
+        assert_eq!(back.warnings(), rsp.warnings());
+        assert!(decode_response(&encode_response(&rsp)[..10]).is_err());
+    }
+
+    #[derive(Clone)]
+    struct WriteParam;
+
+    impl crate::Cmd for WriteParam {
+        fn code(&self) -> String {
+            "21".into()
+        }
+        fn title(&self) -> String {
+            "参数设置".into()
+        }
+    }
+
+    #[test]
+    fn test_response_extensions_roundtrip() {
+        let mut rsp = JniResponse::empty();
+        rsp.set_rsp_jsons(
+            (0..4)
+                .map(|i| ReportField::new("b", "b", i.to_string()))
+                .collect(),
+        );
+        let page = rsp.paginate(2).remove(1);
+        let back = decode_response(&encode_response(&page)).unwrap();
+        assert_eq!(back.page(), page.page());
+        assert_eq!(back.total_fields(), page.total_fields());
+        assert_eq!(back.rsp_jsons(), page.rsp_jsons());
+
+        rsp.limit_fields(3);
+        rsp.set_duplicate(true);
+        rsp.set_idempotency_token("0123456789ABCDEF");
+        rsp.add_event(DeviceEvent {
+            code: "0A".into(),
+            title: "阀门动作".into(),
+            alert: true,
+            occurred_at: Some("2025-09-18 12:00:00".into()),
+            params: vec![ReportField::new("参数", "canshu", "55".into())],
+            raw_hex: "0A25091812000055".into(),
+        });
+        let back = decode_response(&encode_response(&rsp)).unwrap();
+        assert_eq!(back.truncated(), rsp.truncated());
+        assert_eq!(back.duplicate(), rsp.duplicate());
+        assert_eq!(back.idempotency_token(), rsp.idempotency_token());
+        assert_eq!(back.events(), rsp.events());
+    }
+
+    #[test]
+    fn test_timing_and_segments_roundtrip() {
+        let frames: Vec<_> = ["6801", "6802"]
+            .iter()
+            .map(|hex| {
+                let mut c = crate::RawCapsule::new_downstream(WriteParam, "0001", "");
+                c.set_bytes_and_generate_hex(&hex::decode(hex).unwrap())
+                    .unwrap();
+                c.set_fields(vec![ReportField::new("帧", "zhen", hex.to_string())]);
+                c
+            })
+            .collect();
+        let rsp = JniResponse::downstream_response(&frames[0]).unwrap();
+        let back = decode_response(&encode_response(&rsp)).unwrap();
+        assert_eq!(back.encode_micros(), rsp.encode_micros());
+        assert_eq!(back.decode_micros(), rsp.decode_micros());
+
+        let rsp = JniResponse::multi(frames).unwrap();
+        let back = decode_response(&encode_response(&rsp)).unwrap();
+        assert_eq!(back.rsp_hex(), rsp.rsp_hex());
+        assert_eq!(back.segments(), rsp.segments());
+    }
+}
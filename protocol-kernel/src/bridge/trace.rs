@@ -0,0 +1,231 @@
+use std::cell::RefCell;
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+
+use protocol_base::ProtocolError;
+
+use crate::DirectionEnum;
+
+// 当前线程正在处理的请求的 trace_id。bridge 调用在同一线程内同步完成，
+// 因此用 thread_local 即可把 trace_id 传到解码过程中的任意日志
+thread_local! {
+    static TRACE_ID: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// 进入 trace 作用域，guard 释放时恢复之前的 trace_id (支持嵌套)
+pub struct TraceScope {
+    previous: Option<String>,
+}
+
+impl Drop for TraceScope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        TRACE_ID.with(|t| *t.borrow_mut() = previous);
+    }
+}
+
+pub fn enter(trace_id: Option<&str>) -> TraceScope {
+    let previous = TRACE_ID.with(|t| t.replace(trace_id.map(|s| s.to_string())));
+    TraceScope { previous }
+}
+
+pub fn current_trace_id() -> Option<String> {
+    TRACE_ID.with(|t| t.borrow().clone())
+}
+
+/// 日志前缀，如 "[trace=abc] "；没有 trace_id 时为空串
+pub fn log_prefix() -> String {
+    current_trace_id()
+        .map(|id| format!("[trace={}] ", id))
+        .unwrap_or_default()
+}
+
+/// 一帧的处理范围: 从 bridge 收到请求到返回响应
+#[derive(Debug, Clone)]
+pub struct FrameSpan {
+    pub protocol_id: Option<String>,
+    pub device_no: Option<String>,
+    pub cmd_code: Option<String>,
+    pub direction: DirectionEnum,
+    pub trace_id: Option<String>,
+}
+
+/// 帧 span 的 guard，持有已进入的 tracing span ("frame")，释放时发出带耗时的结束事件。
+/// 关闭 tracing feature 时不产生任何 span/事件 (嵌入式构建)
+pub struct FrameSpanGuard {
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+    #[cfg(feature = "tracing")]
+    started: Instant,
+    #[cfg(feature = "tracing")]
+    failed: bool,
+}
+
+impl FrameSpanGuard {
+    /// 请求中没有 cmd_code 时，可在解码出命令码后补上
+    pub fn record_cmd_code(&self, _cmd_code: &str) {
+        #[cfg(feature = "tracing")]
+        self.span.record("cmd_code", _cmd_code);
+    }
+
+    pub fn record_error(&mut self, _error: &ProtocolError) {
+        #[cfg(feature = "tracing")]
+        {
+            self.span.record("error", tracing::field::display(_error));
+            self.failed = true;
+        }
+    }
+}
+
+impl Drop for FrameSpanGuard {
+    fn drop(&mut self) {
+        // 此时 span 仍处于进入状态，结束事件归属于该帧
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed_us = self.started.elapsed().as_micros() as u64;
+            if self.failed {
+                tracing::warn!(elapsed_us, "frame failed");
+            } else {
+                tracing::debug!(elapsed_us, "frame done");
+            }
+        }
+    }
+}
+
+/// 进入一帧的 span，trace_id 取当前作用域的值
+pub fn frame_span(_span: FrameSpan) -> FrameSpanGuard {
+    #[cfg(feature = "tracing")]
+    {
+        let trace_id = _span.trace_id.or_else(current_trace_id);
+        let span = tracing::info_span!(
+            "frame",
+            protocol_id = _span.protocol_id.as_deref(),
+            device_no = _span.device_no.as_deref(),
+            cmd_code = _span.cmd_code.as_deref(),
+            direction = ?_span.direction,
+            trace_id = trace_id.as_deref(),
+            error = tracing::field::Empty,
+        );
+        FrameSpanGuard {
+            span: span.entered(),
+            started: Instant::now(),
+            failed: false,
+        }
+    }
+    #[cfg(not(feature = "tracing"))]
+    FrameSpanGuard {}
+}
+
+/// 报告一个字段解码失败，事件归属于当前的帧 span
+pub fn field_failed(_field: Option<&str>, _offset: usize, _error: &ProtocolError) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        field = _field,
+        offset = _offset,
+        error = %_error,
+        "field decode failed"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_scope() {
+        assert_eq!(current_trace_id(), None);
+        {
+            let _outer = enter(Some("a"));
+            {
+                let _inner = enter(Some("b"));
+                assert_eq!(log_prefix(), "[trace=b] ");
+            }
+            assert_eq!(current_trace_id().as_deref(), Some("a"));
+        }
+        assert_eq!(log_prefix(), "");
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_frame_span_events() {
+        use std::{
+            fmt::Debug,
+            sync::{Arc, Mutex},
+        };
+        use tracing::{
+            field::{Field, Visit},
+            span, Event, Metadata, Subscriber,
+        };
+
+        // 把 span/事件的字段拼成一行 "kind name=value ..."
+        struct Line(String);
+
+        impl Visit for Line {
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                self.0.push_str(&format!(" {}={:?}", field.name(), value));
+            }
+        }
+
+        #[derive(Clone, Default)]
+        struct Collect(Arc<Mutex<Vec<String>>>);
+
+        impl Collect {
+            fn push(&self, kind: &str, visit: impl FnOnce(&mut Line)) {
+                let mut line = Line(kind.into());
+                visit(&mut line);
+                self.0.lock().unwrap().push(line.0);
+            }
+        }
+
+        impl Subscriber for Collect {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+                self.push("span", |l| attrs.record(l));
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _: &span::Id, values: &span::Record<'_>) {
+                self.push("record", |l| values.record(l));
+            }
+
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                self.push("event", |l| event.record(l));
+            }
+
+            fn enter(&self, _: &span::Id) {}
+
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let collect = Collect::default();
+        // 作用域内的 subscriber 只接收本线程的事件
+        tracing::subscriber::with_default(collect.clone(), || {
+            let _trace = enter(Some("t-9"));
+            let mut span = frame_span(FrameSpan {
+                protocol_id: None,
+                device_no: Some("123".into()),
+                cmd_code: None,
+                direction: DirectionEnum::Upstream,
+                trace_id: None,
+            });
+            field_failed(None, 3, &ProtocolError::CommonError("bad".into()));
+            span.record_cmd_code("01");
+            span.record_error(&ProtocolError::CommonError("bad".into()));
+        });
+        let lines = collect.0.lock().unwrap();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("span"));
+        assert!(lines[0].contains("device_no=\"123\""));
+        assert!(lines[0].contains("trace_id=\"t-9\""));
+        assert!(!lines[0].contains("cmd_code"));
+        assert!(lines[1].contains("offset=3") && lines[1].contains("field decode failed"));
+        assert_eq!(lines[2], "record cmd_code=\"01\"");
+        assert!(lines[3].starts_with("record error="));
+        assert!(lines[4].contains("elapsed_us=") && lines[4].contains("frame failed"));
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::{NaiveDateTime, Timelike};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::ReportField;
+
+/// 聚合的时间窗口
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateWindow {
+    Hourly,
+    Daily,
+}
+
+impl AggregateWindow {
+    /// 时间所在窗口的起点
+    pub fn start_of(&self, at: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            AggregateWindow::Hourly => at.date().and_hms_opt(at.hour(), 0, 0).expect("valid hour"),
+            AggregateWindow::Daily => at.date().and_hms_opt(0, 0, 0).expect("valid midnight"),
+        }
+    }
+}
+
+/// 字段的聚合方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateMode {
+    /// 窗口内求和，用于上报的是区间量 (本周期用量等)
+    Sum,
+    /// 累计量 (表码) 求差，相邻两次读数的差值计入后一次读数所在的窗口。
+    /// rollover 为计数器的模 (如 8 位表码 99999.999 时为 100000)，读数变小时按翻转处理；
+    /// 未设置时视为换表或清零，差值取新读数
+    Delta { rollover: Option<Decimal> },
+}
+
+/// 序列中的一个点
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeriesPoint {
+    pub start: NaiveDateTime,
+    pub value: Decimal,
+    // 计入该窗口的读数 (Delta 时为差值) 个数
+    pub samples: usize,
+}
+
+/// 把定时上报解析出的 ReportField 按小时/天聚合成序列。
+/// 按 code 匹配字段，value 按十进制解析与累加 (表码不经过浮点)，无法解析为数值的读数会被忽略
+pub struct Aggregator {
+    window: AggregateWindow,
+    modes: HashMap<String, AggregateMode>,
+    // code -> (读数时间 -> 数值)
+    readings: HashMap<String, BTreeMap<NaiveDateTime, Decimal>>,
+}
+
+impl Aggregator {
+    pub fn new(window: AggregateWindow) -> Self {
+        Self {
+            window,
+            modes: HashMap::new(),
+            readings: HashMap::new(),
+        }
+    }
+
+    pub fn sum(mut self, code: &str) -> Self {
+        self.modes.insert(code.into(), AggregateMode::Sum);
+        self
+    }
+
+    pub fn delta(mut self, code: &str, rollover: Option<Decimal>) -> Self {
+        self.modes
+            .insert(code.into(), AggregateMode::Delta { rollover });
+        self
+    }
+
+    /// 加入一次上报，返回被采用的字段数。同一时间重复上报时后者覆盖前者
+    pub fn push(&mut self, at: NaiveDateTime, fields: &[ReportField]) -> usize {
+        let mut accepted = 0;
+        for field in fields {
+            if !self.modes.contains_key(&field.code) {
+                continue;
+            }
+            let Ok(value) = field.value.trim().parse::<Decimal>() else {
+                continue;
+            };
+            self.readings
+                .entry(field.code.clone())
+                .or_default()
+                .insert(at, value);
+            accepted += 1;
+        }
+        accepted
+    }
+
+    /// 字段的聚合序列，按窗口起点升序，没有数据的窗口不出现
+    pub fn series(&self, code: &str) -> Vec<SeriesPoint> {
+        let (Some(mode), Some(readings)) = (self.modes.get(code), self.readings.get(code)) else {
+            return Vec::new();
+        };
+        let values: Vec<(NaiveDateTime, Decimal)> = match mode {
+            AggregateMode::Sum => readings.iter().map(|(t, v)| (*t, *v)).collect(),
+            AggregateMode::Delta { rollover } => readings
+                .iter()
+                .zip(readings.iter().skip(1))
+                .map(|((_, prev), (t, cur))| (*t, counter_delta(*prev, *cur, *rollover)))
+                .collect(),
+        };
+        let mut points: BTreeMap<NaiveDateTime, SeriesPoint> = BTreeMap::new();
+        for (at, value) in values {
+            let start = self.window.start_of(at);
+            let point = points.entry(start).or_insert(SeriesPoint {
+                start,
+                value: Decimal::ZERO,
+                samples: 0,
+            });
+            point.value += value;
+            point.samples += 1;
+        }
+        points.into_values().collect()
+    }
+
+    /// 清除已聚合的读数，保留聚合规则
+    pub fn clear(&mut self) {
+        self.readings.clear();
+    }
+}
+
+fn counter_delta(prev: Decimal, cur: Decimal, rollover: Option<Decimal>) -> Decimal {
+    if cur >= prev {
+        return cur - prev;
+    }
+    match rollover {
+        Some(modulus) => modulus - prev + cur,
+        None => cur,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal_macros::dec;
+
+    fn at(day: u32, hour: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(hour, min, 0)
+            .unwrap()
+    }
+
+    fn report(total: &str, usage: &str) -> Vec<ReportField> {
+        vec![
+            ReportField::new("累计流量", "total", total.into()),
+            ReportField::new("周期用量", "usage", usage.into()),
+            ReportField::new("阀门状态", "valve", "开".into()),
+        ]
+    }
+
+    #[test]
+    fn test_hourly_and_rollover() {
+        let mut agg = Aggregator::new(AggregateWindow::Hourly)
+            .delta("total", Some(dec!(1000)))
+            .sum("usage");
+        assert_eq!(agg.push(at(1, 10, 0), &report("990", "1")), 2);
+        agg.push(at(1, 10, 30), &report("996", "2"));
+        agg.push(at(1, 11, 15), &report("4", "3"));
+
+        let total = agg.series("total");
+        assert_eq!(total.len(), 2);
+        assert_eq!((total[0].start, total[0].value), (at(1, 10, 0), dec!(6)));
+        // 996 -> 4 翻转
+        assert_eq!((total[1].start, total[1].value), (at(1, 11, 0), dec!(8)));
+
+        let usage = agg.series("usage");
+        assert_eq!((usage[0].value, usage[0].samples), (dec!(3), 2));
+        assert!(agg.series("valve").is_empty());
+    }
+
+    #[test]
+    fn test_decimal_readings() {
+        // 浮点求差会得到 0.10000000000000142 之类的值
+        let mut agg = Aggregator::new(AggregateWindow::Daily)
+            .delta("total", Some(dec!(100000)))
+            .sum("usage");
+        agg.push(at(1, 0, 0), &report("12345.678", "0.1"));
+        agg.push(at(1, 6, 0), &report("12345.778", "0.2"));
+        agg.push(at(1, 12, 0), &report("99999.999", "0.3"));
+        agg.push(at(1, 18, 0), &report("0.001", "0.4"));
+        let total = agg.series("total");
+        assert_eq!(total[0].value, dec!(87654.323));
+        assert_eq!(total[0].value.to_string(), "87654.323");
+        assert_eq!(agg.series("usage")[0].value.to_string(), "1.0");
+    }
+
+    #[test]
+    fn test_daily_reset() {
+        let mut agg = Aggregator::new(AggregateWindow::Daily).delta("total", None);
+        agg.push(at(2, 23, 0), &report("120", "0"));
+        agg.push(at(1, 8, 0), &report("100", "0"));
+        agg.push(at(3, 1, 0), &report("5", "0"));
+        let total = agg.series("total");
+        assert_eq!(
+            total[0],
+            SeriesPoint {
+                start: at(2, 0, 0),
+                value: dec!(20),
+                samples: 1
+            }
+        );
+        // 换表后从新读数开始计
+        assert_eq!(total[1].value, dec!(5));
+    }
+}
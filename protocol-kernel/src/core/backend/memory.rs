@@ -0,0 +1,124 @@
+use moka::{notification::RemovalCause, ops::compute::Op, sync::Cache};
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    core::{
+        backend::{not_cached, notify_evicted, CacheBackend, EvictionCause},
+        parts::transport_carrier::TransportCarrier,
+    },
+    ProtocolResult,
+};
+
+/// 进程内缓存的容量与过期配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheConfig {
+    pub max_capacity: u64,
+    // 写入后多久过期 (TTL)
+    pub ttl: Option<Duration>,
+    // 多久没有访问后过期 (TTI)
+    pub tti: Option<Duration>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        // 最多 10 万个设备，TTL 1 小时
+        Self {
+            max_capacity: 100_000,
+            ttl: Some(Duration::from_secs(60 * 60)),
+            tti: None,
+        }
+    }
+}
+
+/// 进程内缓存 (moka)，单实例部署的默认后端
+pub struct MemoryBackend {
+    cache: Cache<String, Arc<TransportCarrier>>,
+}
+
+impl MemoryBackend {
+    pub fn new(max_capacity: u64, ttl: Duration) -> Self {
+        Self::with_config(CacheConfig {
+            max_capacity,
+            ttl: Some(ttl),
+            tti: None,
+        })
+    }
+
+    pub fn with_config(config: CacheConfig) -> Self {
+        let mut builder = Cache::builder()
+            .max_capacity(config.max_capacity)
+            .eviction_listener(|key: Arc<String>, value: Arc<TransportCarrier>, cause| {
+                // 更新 (Replaced) 不算移除
+                let cause = match cause {
+                    RemovalCause::Expired => EvictionCause::Expired,
+                    RemovalCause::Size => EvictionCause::Size,
+                    RemovalCause::Explicit => EvictionCause::Removed,
+                    RemovalCause::Replaced => return,
+                };
+                notify_evicted(&key, &value, cause);
+            });
+        if let Some(ttl) = config.ttl {
+            builder = builder.time_to_live(ttl);
+        }
+        if let Some(tti) = config.tti {
+            builder = builder.time_to_idle(tti);
+        }
+        Self {
+            cache: builder.build(),
+        }
+    }
+}
+
+impl Default for MemoryBackend {
+    fn default() -> Self {
+        Self::with_config(CacheConfig::default())
+    }
+}
+
+impl CacheBackend for MemoryBackend {
+    fn get(&self, unique: &str) -> Option<Arc<TransportCarrier>> {
+        self.cache.get(unique)
+    }
+
+    fn insert(&self, unique: &str, state: Arc<TransportCarrier>) {
+        self.cache.insert(unique.into(), state);
+    }
+
+    fn remove(&self, unique: &str) {
+        self.cache.invalidate(unique);
+    }
+
+    fn entry_count(&self) -> u64 {
+        self.cache.entry_count()
+    }
+
+    fn entries(&self) -> Vec<(String, Arc<TransportCarrier>)> {
+        self.cache
+            .iter()
+            .map(|(k, v)| (k.as_ref().clone(), v))
+            .collect()
+    }
+
+    // 同一个 key 的 compute 是串行的，并发更新不会互相覆盖
+    fn update(
+        &self,
+        unique: &str,
+        f: &mut dyn FnMut(&mut TransportCarrier) -> ProtocolResult<()>,
+    ) -> ProtocolResult<()> {
+        let mut result = Err(not_cached(unique));
+        self.cache
+            .entry(unique.to_string())
+            .and_compute_with(|entry| {
+                let Some(entry) = entry else {
+                    return Op::Nop;
+                };
+                let mut carrier = TransportCarrier::clone(entry.value());
+                result = f(&mut carrier);
+                match result {
+                    Ok(()) => Op::Put(Arc::new(carrier)),
+                    Err(_) => Op::Nop,
+                }
+            });
+        result
+    }
+}
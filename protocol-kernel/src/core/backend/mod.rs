@@ -0,0 +1,96 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, RwLock},
+};
+
+use once_cell::sync::Lazy;
+
+#[cfg(not(feature = "tracing"))]
+use crate::bridge::trace;
+use crate::{core::parts::transport_carrier::TransportCarrier, ProtocolError, ProtocolResult};
+
+pub mod memory;
+#[cfg(any(test, feature = "redis"))]
+mod optimistic;
+#[cfg(feature = "redis")]
+pub mod redis;
+
+pub use memory::{CacheConfig, MemoryBackend};
+#[cfg(feature = "redis")]
+pub use redis::{RedisBackend, RedisConfig};
+
+/// ProtocolCache 的存储后端。默认是进程内的 moka 缓存，
+/// 集群部署时可以换成共享存储 (如 Redis)，让多个网关实例看到同一份设备状态
+pub trait CacheBackend: Send + Sync {
+    fn get(&self, unique: &str) -> Option<Arc<TransportCarrier>>;
+
+    fn insert(&self, unique: &str, state: Arc<TransportCarrier>);
+
+    fn remove(&self, unique: &str);
+
+    /// 设备数量 (近似值)
+    fn entry_count(&self) -> u64;
+
+    /// 当前所有设备状态，用于导出快照。自身已持久化的后端 (如 Redis) 可以不实现
+    fn entries(&self) -> Vec<(String, Arc<TransportCarrier>)> {
+        Vec::new()
+    }
+
+    /// 修改已缓存的设备状态并写回。设备不存在或 f 返回错误时不写回。
+    /// 默认实现是读-改-写，不保证并发安全，后端应尽量覆盖为原子操作
+    fn update(
+        &self,
+        unique: &str,
+        f: &mut dyn FnMut(&mut TransportCarrier) -> ProtocolResult<()>,
+    ) -> ProtocolResult<()> {
+        let current = self.get(unique).ok_or_else(|| not_cached(unique))?;
+        let mut carrier = TransportCarrier::clone(&current);
+        f(&mut carrier)?;
+        self.insert(unique, Arc::new(carrier));
+        Ok(())
+    }
+}
+
+pub(crate) fn not_cached(unique: &str) -> ProtocolError {
+    ProtocolError::ValidationFailed(format!("device {} is not cached", unique))
+}
+
+/// 设备状态被移出缓存的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionCause {
+    Expired, // TTL/TTI 到期
+    Size,    // 超出容量
+    Removed, // 显式调用 remove
+}
+
+type EvictionListener = Arc<dyn Fn(&str, &TransportCarrier, EvictionCause) + Send + Sync>;
+
+static EVICTION_LISTENERS: Lazy<RwLock<Vec<EvictionListener>>> =
+    Lazy::new(|| RwLock::new(Vec::new()));
+
+pub(crate) fn add_eviction_listener(listener: EvictionListener) {
+    let mut guard = EVICTION_LISTENERS
+        .write()
+        .unwrap_or_else(|e| e.into_inner());
+    guard.push(listener);
+}
+
+/// 由后端在设备状态被移除时调用。listener 中的 panic 会被吞掉，不影响缓存本身
+pub(crate) fn notify_evicted(key: &str, carrier: &TransportCarrier, cause: EvictionCause) {
+    let listeners = EVICTION_LISTENERS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    for listener in listeners {
+        if panic::catch_unwind(AssertUnwindSafe(|| listener(key, carrier, cause))).is_err() {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(key, "eviction listener panicked");
+            #[cfg(not(feature = "tracing"))]
+            eprintln!(
+                "[WARN] {}Eviction listener panicked for {}",
+                trace::log_prefix(),
+                key
+            );
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! 共享后端 (Redis 等) 的乐观并发更新: 读取 -> 不持有连接执行 f -> 比较并写回，
+//! 期间被其他实例修改过则重新读取再执行一次 f
+use crate::{
+    core::{backend::not_cached, parts::transport_carrier::TransportCarrier},
+    ProtocolError, ProtocolResult,
+};
+
+// 写冲突时的最大重试次数
+pub(crate) const MAX_UPDATE_RETRIES: usize = 8;
+
+/// update 需要的最小存储操作，值为序列化后的 TransportCarrier
+pub(crate) trait CasStore {
+    fn load(&self, key: &str) -> ProtocolResult<Option<Vec<u8>>>;
+
+    /// key 的当前值仍等于 expected 时写入 value 并返回 true，已被修改时返回 false
+    fn compare_and_set(&self, key: &str, expected: &[u8], value: &[u8]) -> ProtocolResult<bool>;
+}
+
+pub(crate) fn update_with<S: CasStore + ?Sized>(
+    store: &S,
+    key: &str,
+    unique: &str,
+    f: &mut dyn FnMut(&mut TransportCarrier) -> ProtocolResult<()>,
+) -> ProtocolResult<()> {
+    for _ in 0..MAX_UPDATE_RETRIES {
+        let current = store.load(key)?.ok_or_else(|| not_cached(unique))?;
+        let mut carrier = decode_carrier(&current)?;
+        f(&mut carrier)?;
+        if store.compare_and_set(key, &current, &encode_carrier(&carrier)?)? {
+            return Ok(());
+        }
+    }
+    Err(ProtocolError::CommonError(format!(
+        "update for {} kept conflicting after {} retries",
+        unique, MAX_UPDATE_RETRIES
+    )))
+}
+
+pub(crate) fn encode_carrier(carrier: &TransportCarrier) -> ProtocolResult<Vec<u8>> {
+    serde_json::to_vec(carrier).map_err(|e| ProtocolError::CommonError(e.to_string()))
+}
+
+pub(crate) fn decode_carrier(bytes: &[u8]) -> ProtocolResult<TransportCarrier> {
+    serde_json::from_slice(bytes).map_err(|e| ProtocolError::CommonError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+    };
+
+    #[derive(Default)]
+    struct MemoryStore {
+        values: RefCell<HashMap<String, Vec<u8>>>,
+        writes: Cell<usize>,
+    }
+
+    impl MemoryStore {
+        fn put(&self, key: &str, carrier: &TransportCarrier) {
+            self.values
+                .borrow_mut()
+                .insert(key.into(), encode_carrier(carrier).unwrap());
+        }
+
+        fn carrier(&self, key: &str) -> TransportCarrier {
+            decode_carrier(&self.values.borrow()[key]).unwrap()
+        }
+    }
+
+    impl CasStore for MemoryStore {
+        fn load(&self, key: &str) -> ProtocolResult<Option<Vec<u8>>> {
+            Ok(self.values.borrow().get(key).cloned())
+        }
+
+        fn compare_and_set(
+            &self,
+            key: &str,
+            expected: &[u8],
+            value: &[u8],
+        ) -> ProtocolResult<bool> {
+            let mut values = self.values.borrow_mut();
+            if values.get(key).map(Vec::as_slice) != Some(expected) {
+                return Ok(false);
+            }
+            values.insert(key.into(), value.to_vec());
+            self.writes.set(self.writes.get() + 1);
+            Ok(true)
+        }
+    }
+
+    fn carrier(count: &str) -> TransportCarrier {
+        TransportCarrier::try_new_with_device_no_and_upstream_count_hex("0102", count).unwrap()
+    }
+
+    #[test]
+    fn test_retry_on_conflict() {
+        let store = MemoryStore::default();
+        store.put("k", &carrier("01"));
+        let mut calls = 0;
+        update_with(&store, "k", "0102", &mut |c| {
+            calls += 1;
+            if calls == 1 {
+                // f 执行期间另一个实例改了序号
+                store.put("k", &carrier("05"));
+            }
+            c.next_upstream_count().map(|_| ())
+        })
+        .unwrap();
+        // 第二次基于最新值重新计算，另一实例的修改没有被覆盖
+        assert_eq!(calls, 2);
+        assert_eq!(store.carrier("k").upstream_count().unwrap().hex(), "06");
+        assert_eq!(store.writes.get(), 1);
+
+        // 一直冲突时放弃
+        let mut calls = 0;
+        let result = update_with(&store, "k", "0102", &mut |c| {
+            calls += 1;
+            store.put("k", &carrier(&format!("{:02X}", 0x10 + calls)));
+            c.set_cipher_slot(1);
+            Ok(())
+        });
+        assert!(matches!(result, Err(ProtocolError::CommonError(_))));
+        assert_eq!(calls, MAX_UPDATE_RETRIES);
+        assert_eq!(store.carrier("k").cipher_slot(), -1);
+    }
+
+    #[test]
+    fn test_missing_key_and_failed_f() {
+        let store = MemoryStore::default();
+        let mut called = false;
+        let result = update_with(&store, "missing", "0102", &mut |_| {
+            called = true;
+            Ok(())
+        });
+        assert!(matches!(result, Err(ProtocolError::ValidationFailed(_))));
+        assert!(!called);
+
+        // f 出错时不写回
+        store.put("k", &carrier("01"));
+        let result = update_with(&store, "k", "0102", &mut |c| {
+            c.set_cipher_slot(3);
+            Err(ProtocolError::ValidationFailed("rejected".into()))
+        });
+        assert!(result.is_err());
+        assert_eq!(store.carrier("k").cipher_slot(), -1);
+        assert_eq!(store.writes.get(), 0);
+    }
+
+    #[test]
+    fn test_carrier_json() {
+        let back = decode_carrier(&encode_carrier(&carrier("0A")).unwrap()).unwrap();
+        assert_eq!(back.upstream_count().unwrap().hex(), "0A");
+        assert_eq!(back.cipher_slot(), -1);
+    }
+}
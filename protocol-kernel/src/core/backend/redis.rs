@@ -0,0 +1,228 @@
+use std::{fmt::Display, sync::Arc, time::Duration};
+
+use redis::{ConnectionLike, IntoConnectionInfo};
+
+#[cfg(not(feature = "tracing"))]
+use crate::bridge::trace;
+use crate::{
+    core::{
+        backend::{
+            optimistic::{decode_carrier, encode_carrier, update_with, CasStore},
+            CacheBackend,
+        },
+        parts::transport_carrier::TransportCarrier,
+    },
+    ProtocolError, ProtocolResult,
+};
+
+#[derive(Debug, Clone)]
+pub struct RedisConfig {
+    pub addr: String,
+    pub password: Option<String>,
+    pub db: u32,
+    pub key_prefix: String,
+    // None 表示不过期
+    pub ttl: Option<Duration>,
+    // 建立连接、等待空闲连接以及单条命令的超时
+    pub timeout: Duration,
+    // 连接池的最大连接数
+    pub pool_size: u32,
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        Self {
+            addr: "127.0.0.1:6379".into(),
+            password: None,
+            db: 0,
+            key_prefix: "protocol:carrier:".into(),
+            ttl: Some(Duration::from_secs(60 * 60)),
+            timeout: Duration::from_secs(2),
+            pool_size: 8,
+        }
+    }
+}
+
+/// Redis 后端，设备状态以 JSON 存放在 `key_prefix + unique` 下。
+/// 每次调用从 r2d2 连接池取一条连接，连接在首次使用时建立，断开的连接由连接池丢弃
+pub struct RedisBackend {
+    config: RedisConfig,
+    pool: r2d2::Pool<redis::Client>,
+}
+
+type PooledConnection = r2d2::PooledConnection<redis::Client>;
+
+// 连接池中的连接都设置读写超时，Redis 无响应时不会一直阻塞解码线程
+#[derive(Debug)]
+struct CommandTimeout(Duration);
+
+impl r2d2::CustomizeConnection<redis::Connection, redis::RedisError> for CommandTimeout {
+    fn on_acquire(&self, conn: &mut redis::Connection) -> Result<(), redis::RedisError> {
+        conn.set_read_timeout(Some(self.0))?;
+        conn.set_write_timeout(Some(self.0))
+    }
+}
+
+impl RedisBackend {
+    /// 创建连接池但不立即连接，Redis 暂时不可用时也能启动。addr 非法时返回错误
+    pub fn new(config: RedisConfig) -> ProtocolResult<Self> {
+        let mut info = format!("redis://{}/{}", config.addr, config.db)
+            .into_connection_info()
+            .map_err(redis_error)?;
+        info.redis.password = config.password.clone();
+        let client = redis::Client::open(info).map_err(redis_error)?;
+        let pool = r2d2::Pool::builder()
+            .max_size(config.pool_size.max(1))
+            .min_idle(Some(0))
+            .connection_timeout(config.timeout)
+            .connection_customizer(Box::new(CommandTimeout(config.timeout)))
+            .build_unchecked(client);
+        Ok(Self { config, pool })
+    }
+
+    fn key(&self, unique: &str) -> String {
+        format!("{}{}", self.config.key_prefix, unique)
+    }
+
+    fn conn(&self) -> ProtocolResult<PooledConnection> {
+        self.pool.get().map_err(redis_error)
+    }
+
+    fn set_cmd(&self, key: &str, value: &[u8]) -> redis::Cmd {
+        let mut cmd = redis::cmd("SET");
+        cmd.arg(key).arg(value);
+        if let Some(ttl) = self.config.ttl {
+            cmd.arg("PX").arg(ttl.as_millis() as u64);
+        }
+        cmd
+    }
+
+    fn log_error(&self, action: &str, unique: &str, e: &ProtocolError) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(action, unique, error = %e, "redis command failed");
+        #[cfg(not(feature = "tracing"))]
+        eprintln!(
+            "[WARN] {}Redis {} failed for {}: {}",
+            trace::log_prefix(),
+            action,
+            unique,
+            e
+        );
+    }
+}
+
+impl CasStore for RedisBackend {
+    fn load(&self, key: &str) -> ProtocolResult<Option<Vec<u8>>> {
+        redis::cmd("GET")
+            .arg(key)
+            .query(&mut *self.conn()?)
+            .map_err(redis_error)
+    }
+
+    // WATCH 之后再比较一次当前值: 与 load 读到的不同说明 f 执行期间已被修改；
+    // 相同则 EXEC 保证从比较到写入之间没有其他写入，被打断时 EXEC 返回 nil
+    fn compare_and_set(&self, key: &str, expected: &[u8], value: &[u8]) -> ProtocolResult<bool> {
+        let mut conn = self.conn()?;
+        let conn: &mut dyn ConnectionLike = &mut *conn;
+        redis::cmd("WATCH")
+            .arg(key)
+            .query::<()>(conn)
+            .map_err(redis_error)?;
+        let current: Option<Vec<u8>> = redis::cmd("GET")
+            .arg(key)
+            .query(conn)
+            .map_err(redis_error)?;
+        if current.as_deref() != Some(expected) {
+            redis::cmd("UNWATCH")
+                .query::<()>(conn)
+                .map_err(redis_error)?;
+            return Ok(false);
+        }
+        let committed: Option<()> = redis::pipe()
+            .atomic()
+            .add_command(self.set_cmd(key, value))
+            .ignore()
+            .query(conn)
+            .map_err(redis_error)?;
+        Ok(committed.is_some())
+    }
+}
+
+impl CacheBackend for RedisBackend {
+    fn get(&self, unique: &str) -> Option<Arc<TransportCarrier>> {
+        let result = self
+            .load(&self.key(unique))
+            .and_then(|value| value.map(|v| decode_carrier(&v)).transpose());
+        result
+            .unwrap_or_else(|e| {
+                self.log_error("GET", unique, &e);
+                None
+            })
+            .map(Arc::new)
+    }
+
+    fn insert(&self, unique: &str, state: Arc<TransportCarrier>) {
+        let result = encode_carrier(&state).and_then(|value| {
+            self.set_cmd(&self.key(unique), &value)
+                .query::<()>(&mut *self.conn()?)
+                .map_err(redis_error)
+        });
+        if let Err(e) = result {
+            self.log_error("SET", unique, &e);
+        }
+    }
+
+    fn remove(&self, unique: &str) {
+        let result = self.conn().and_then(|mut conn| {
+            redis::cmd("DEL")
+                .arg(self.key(unique))
+                .query::<()>(&mut *conn)
+                .map_err(redis_error)
+        });
+        if let Err(e) = result {
+            self.log_error("DEL", unique, &e);
+        }
+    }
+
+    /// 用 SCAN 统计前缀下的 key 数量，只适合监控类的低频调用
+    fn entry_count(&self) -> u64 {
+        let pattern = format!("{}*", self.config.key_prefix);
+        let result = self.conn().and_then(|mut conn| {
+            let mut cursor = 0u64;
+            let mut count = 0u64;
+            loop {
+                let (next, keys): (u64, Vec<Vec<u8>>) = redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(&pattern)
+                    .arg("COUNT")
+                    .arg(1000)
+                    .query(&mut *conn)
+                    .map_err(redis_error)?;
+                count += keys.len() as u64;
+                if next == 0 {
+                    return Ok(count);
+                }
+                cursor = next;
+            }
+        });
+        result.unwrap_or_else(|e| {
+            self.log_error("SCAN", &self.config.key_prefix, &e);
+            0
+        })
+    }
+
+    // 乐观锁: 读取后归还连接再执行 f，写回时 WATCH + 比较，其他实例同时修改时重试。
+    // 不持有任何锁，f 中再次访问缓存不会死锁，不同设备的更新也互不阻塞
+    fn update(
+        &self,
+        unique: &str,
+        f: &mut dyn FnMut(&mut TransportCarrier) -> ProtocolResult<()>,
+    ) -> ProtocolResult<()> {
+        update_with(self, &self.key(unique), unique, f)
+    }
+}
+
+fn redis_error(e: impl Display) -> ProtocolError {
+    ProtocolError::CommonError(format!("redis: {}", e))
+}
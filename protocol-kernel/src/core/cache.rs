@@ -1,65 +1,509 @@
-use moka::sync::Cache;
 use once_cell::sync::Lazy;
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
-use crate::core::parts::transport_carrier::TransportCarrier;
+use crate::{
+    bridge::trace,
+    core::{
+        backend::{add_eviction_listener, CacheBackend, CacheConfig, EvictionCause, MemoryBackend},
+        device_lock::{self, DeviceLockFuture},
+        parts::{transport_carrier::TransportCarrier, transport_pair::TransportPair},
+        snapshot::{CacheSnapshot, SnapshotEntry},
+    },
+    ProtocolError, ProtocolResult,
+};
 
 // --- 全局缓存定义 ---
 
-// 定义缓存的值类型为一个 Arc<DeviceState>。
-// 使用 Arc 可以在多个地方共享同一个设备状态实例，减少克隆开销。
-// Cache<String, Arc<DeviceState>> 是线程安全的。
-static DEVICE_CACHE: Lazy<Cache<String, Arc<TransportCarrier>>> = Lazy::new(|| {
-    Cache::builder()
-        .max_capacity(100_000) // 例如，最大缓存10万个设备
-        .time_to_live(Duration::from_secs(60 * 60)) // 例如，TTL 设置为 1 小时
-        // .time_to_idle(Duration::from_secs(1 * 60 * 60)) // 也可以设置空闲过期时间 (TTI)
-        .build()
-});
+// 设备状态存储。默认是进程内 moka 缓存 (10 万设备，TTL 1 小时)，
+// 可以通过 ProtocolCache::configure / set_backend 调整或换成 Redis 等共享后端。
+// 值类型为 Arc<TransportCarrier>，读取时不需要克隆整个设备状态。
+static BACKEND: Lazy<RwLock<Arc<dyn CacheBackend>>> =
+    Lazy::new(|| RwLock::new(Arc::new(MemoryBackend::default())));
+
+// 单独配置过的协议命名空间，各自使用独立的后端
+static NAMESPACES: Lazy<RwLock<HashMap<String, Arc<dyn CacheBackend>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn default_backend() -> Arc<dyn CacheBackend> {
+    BACKEND.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
 
 pub struct ProtocolCache {}
 
 impl ProtocolCache {
-    // --- 公共访问函数 ---
+    /// 按容量/TTL/TTI 重建默认的进程内缓存，应在库初始化时调用一次。
+    /// 已缓存的数据会被丢弃
+    pub fn configure(max_capacity: u64, ttl: Option<Duration>, tti: Option<Duration>) {
+        Self::set_backend(MemoryBackend::with_config(CacheConfig {
+            max_capacity,
+            ttl,
+            tti,
+        }));
+    }
+
+    /// 替换存储后端，应在库初始化时调用一次。已缓存的数据不会迁移
+    pub fn set_backend<B: CacheBackend + 'static>(backend: B) {
+        let mut guard = BACKEND.write().unwrap_or_else(|e| e.into_inner());
+        *guard = Arc::new(backend);
+    }
+
+    /// 注册设备状态被移除 (过期、超容量、remove) 时的回调，参数为缓存 key、移除前的状态和原因。
+    /// 宿主可以借此持久化或记录状态，避免会话无声无息地断掉。
+    /// 仅进程内缓存会触发；Redis 的过期发生在服务端，不会回调
+    pub fn on_evict<F>(listener: F)
+    where
+        F: Fn(&str, &TransportCarrier, EvictionCause) + Send + Sync + 'static,
+    {
+        add_eviction_listener(Arc::new(listener));
+    }
+
+    /// 为某个协议单独配置进程内缓存，之后 namespace(name) 使用这份缓存
+    pub fn configure_namespace(
+        name: &str,
+        max_capacity: u64,
+        ttl: Option<Duration>,
+        tti: Option<Duration>,
+    ) {
+        Self::set_namespace_backend(
+            name,
+            MemoryBackend::with_config(CacheConfig {
+                max_capacity,
+                ttl,
+                tti,
+            }),
+        );
+    }
+
+    pub fn set_namespace_backend<B: CacheBackend + 'static>(name: &str, backend: B) {
+        let mut guard = NAMESPACES.write().unwrap_or_else(|e| e.into_inner());
+        guard.insert(name.into(), Arc::new(backend));
+    }
+
+    /// 协议命名空间。没有单独配置时共用默认后端，key 加上 "name:" 前缀以免不同协议的设备号冲突
+    pub fn namespace(name: &str) -> CacheNamespace {
+        CacheNamespace {
+            name: Some(name.into()),
+        }
+    }
+
+    /// 设备状态所在的命名空间: 先查协议命名空间，再查默认命名空间；
+    /// 都没有时，协议单独配置了缓存则用协议命名空间，否则用默认命名空间
+    pub fn device_namespace(protocol: &str, unique: &str) -> CacheNamespace {
+        let namespace = Self::namespace(protocol);
+        if namespace.read(unique).is_some()
+            || (namespace.is_configured() && CacheNamespace::DEFAULT.read(unique).is_none())
+        {
+            namespace
+        } else {
+            CacheNamespace::DEFAULT
+        }
+    }
+
+    // --- 公共访问函数 (默认命名空间) ---
 
     /// 根据设备号获取设备状态的共享引用 (Arc)。
     /// 如果缓存中不存在或已过期，则返回 None。
     pub fn read(unique: &str) -> Option<Arc<TransportCarrier>> {
-        DEVICE_CACHE.get(unique)
-        // .cloned() // moka v0.10+ 返回 Option<&V>, 需要 clone() 或 cloned()
-        // 注意：moka v0.12+ get() 直接返回 Option<V> (如果是 Arc，则 Arc 被 clone)
+        CacheNamespace::DEFAULT.read(unique)
     }
 
     // 从缓存里获取，如果空，则根据unique&upstream_count_hex创建一个新的。upstream_count_hex是上行序列号，通常来说，协议都需要。如果不需要传个随便什么就行。
+    // unique 或 upstream_count_hex 不是合法 hex 时会 panic，JNI 调用链上请使用 try_read_or_default
     pub fn read_or_default(unique: &str, upstream_count_hex: &str) -> Arc<TransportCarrier> {
-        Self::read(unique).unwrap_or_else(|| {
-            eprintln!(
-                "[WARN] Failed to read cache for {}: {}, using default",
-                unique, upstream_count_hex
-            );
-            let tp = TransportCarrier::new_with_device_no_and_upstream_count_hex(
-                unique,
-                upstream_count_hex,
-            );
-            let arc_tp = Arc::new(tp);
-            Self::store(unique, Arc::clone(&arc_tp));
-            arc_tp
-        })
+        Self::try_read_or_default(unique, upstream_count_hex).unwrap()
+    }
+
+    pub fn try_read_or_default(
+        unique: &str,
+        upstream_count_hex: &str,
+    ) -> ProtocolResult<Arc<TransportCarrier>> {
+        CacheNamespace::DEFAULT.try_read_or_default(unique, upstream_count_hex)
     }
 
     /// 插入或更新设备状态到缓存中。
     /// `state` 应该是 `Arc<DeviceState>` 类型。
     pub fn store(unique: &str, state: Arc<TransportCarrier>) {
-        DEVICE_CACHE.insert(unique.into(), state);
+        CacheNamespace::DEFAULT.store(unique, state)
     }
     /// 从缓存中移除设备状态。
     pub fn remove(device_no: &str) {
-        DEVICE_CACHE.invalidate(device_no);
+        CacheNamespace::DEFAULT.remove(device_no)
+    }
+
+    /// 缓存中设备的上行序号 +1 并写回，返回新序号。同一设备的并发调用不会得到相同的序号
+    pub fn next_upstream_count(unique: &str) -> ProtocolResult<TransportPair> {
+        CacheNamespace::DEFAULT.next_upstream_count(unique)
+    }
+
+    /// 缓存中设备的下行序号 +1 并写回，返回新序号
+    pub fn next_downstream_count(unique: &str) -> ProtocolResult<TransportPair> {
+        CacheNamespace::DEFAULT.next_downstream_count(unique)
+    }
+
+    /// 原地修改缓存中的设备状态并写回，返回 f 的结果。设备不在缓存中时返回错误。
+    /// 同一设备的并发更新按顺序执行，不会出现"克隆-修改-写回"互相覆盖的问题。
+    /// 共享后端 (Redis) 发生写冲突时会重新读取并再次调用 f，所以 f 是 FnMut
+    pub fn update<F, R>(unique: &str, f: F) -> ProtocolResult<R>
+    where
+        F: FnMut(&mut TransportCarrier) -> R,
+    {
+        CacheNamespace::DEFAULT.update(unique, f)
+    }
+
+    /// 同 update，f 返回错误时不写回
+    pub fn try_update<F, R>(unique: &str, f: F) -> ProtocolResult<R>
+    where
+        F: FnMut(&mut TransportCarrier) -> ProtocolResult<R>,
+    {
+        CacheNamespace::DEFAULT.try_update(unique, f)
+    }
+
+    /// 持有设备锁执行 f。同一设备的两帧被并发处理时，用它把
+    /// "读状态-处理-写回序号/会话" 整段串行化。锁不可重入，f 内不要再次获取同一设备的锁
+    pub fn with_device_lock<F, R>(unique: &str, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        CacheNamespace::DEFAULT.with_device_lock(unique, f)
+    }
+
+    /// with_device_lock 的异步版本，不阻塞执行器线程:
+    ///
+    /// ```ignore
+    /// let _guard = ProtocolCache::lock_device(&device_no).await;
+    /// // ... 查询密钥、处理帧、写回序号
+    /// ```
+    pub fn lock_device(unique: &str) -> DeviceLockFuture {
+        CacheNamespace::DEFAULT.lock_device(unique)
     }
 
     /// 获取缓存中当前的设备数量 (近似值)。
     pub fn read_size() -> u64 {
-        DEVICE_CACHE.entry_count()
+        CacheNamespace::DEFAULT.read_size()
+    }
+
+    /// 导出默认后端中的全部设备状态 (包括共用默认后端的命名空间)
+    pub fn export_snapshot() -> ProtocolResult<Vec<u8>> {
+        CacheNamespace::DEFAULT.export_snapshot()
+    }
+
+    /// 导入快照，返回导入的设备数量。已存在的设备会被覆盖
+    pub fn import_snapshot(data: &[u8]) -> ProtocolResult<usize> {
+        CacheNamespace::DEFAULT.import_snapshot(data)
+    }
+
+    /// 导出快照到文件 (先写临时文件再改名，避免中途崩溃留下半个文件)
+    pub fn save_snapshot<P: AsRef<Path>>(path: P) -> ProtocolResult<usize> {
+        let path = path.as_ref();
+        let snapshot = CacheNamespace::DEFAULT.snapshot();
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, snapshot.to_bytes()?)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| ProtocolError::CommonError(format!("{}: {}", path.display(), e)))?;
+        Ok(snapshot.entries.len())
+    }
+
+    /// 从文件加载快照，文件不存在时返回 0
+    pub fn load_snapshot<P: AsRef<Path>>(path: P) -> ProtocolResult<usize> {
+        let path = path.as_ref();
+        match std::fs::read(path) {
+            Ok(data) => Self::import_snapshot(&data),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(ProtocolError::CommonError(format!(
+                "{}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+}
+
+/// ProtocolCache 的一个命名空间，接口与 ProtocolCache 相同
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheNamespace {
+    name: Option<String>,
+}
+
+impl CacheNamespace {
+    const DEFAULT: CacheNamespace = CacheNamespace { name: None };
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    // 是否由 configure_namespace/set_namespace_backend 单独配置了后端
+    fn is_configured(&self) -> bool {
+        let Some(name) = &self.name else {
+            return false;
+        };
+        let guard = NAMESPACES.read().unwrap_or_else(|e| e.into_inner());
+        guard.contains_key(name)
+    }
+
+    // (后端, key 前缀)
+    fn backend(&self) -> (Arc<dyn CacheBackend>, String) {
+        let Some(name) = &self.name else {
+            return (default_backend(), String::new());
+        };
+        let guard = NAMESPACES.read().unwrap_or_else(|e| e.into_inner());
+        match guard.get(name) {
+            Some(backend) => (backend.clone(), String::new()),
+            None => (default_backend(), format!("{}:", name)),
+        }
+    }
+
+    pub fn read(&self, unique: &str) -> Option<Arc<TransportCarrier>> {
+        let (backend, prefix) = self.backend();
+        backend.get(&format!("{}{}", prefix, unique))
+    }
+
+    pub fn try_read_or_default(
+        &self,
+        unique: &str,
+        upstream_count_hex: &str,
+    ) -> ProtocolResult<Arc<TransportCarrier>> {
+        if let Some(tp) = self.read(unique) {
+            return Ok(tp);
+        }
+        eprintln!(
+            "[WARN] {}Failed to read cache for {}: {}, using default",
+            trace::log_prefix(),
+            unique,
+            upstream_count_hex
+        );
+        let tp = TransportCarrier::try_new_with_device_no_and_upstream_count_hex(
+            unique,
+            upstream_count_hex,
+        )?;
+        let arc_tp = Arc::new(tp);
+        self.store(unique, Arc::clone(&arc_tp));
+        Ok(arc_tp)
+    }
+
+    pub fn store(&self, unique: &str, state: Arc<TransportCarrier>) {
+        let (backend, prefix) = self.backend();
+        backend.insert(&format!("{}{}", prefix, unique), state);
+    }
+
+    pub fn remove(&self, unique: &str) {
+        let (backend, prefix) = self.backend();
+        backend.remove(&format!("{}{}", prefix, unique));
+    }
+
+    pub fn next_upstream_count(&self, unique: &str) -> ProtocolResult<TransportPair> {
+        self.try_update(unique, TransportCarrier::next_upstream_count)
+    }
+
+    pub fn next_downstream_count(&self, unique: &str) -> ProtocolResult<TransportPair> {
+        self.try_update(unique, TransportCarrier::next_downstream_count)
+    }
+
+    pub fn update<F, R>(&self, unique: &str, mut f: F) -> ProtocolResult<R>
+    where
+        F: FnMut(&mut TransportCarrier) -> R,
+    {
+        self.try_update(unique, |carrier| Ok(f(carrier)))
+    }
+
+    pub fn try_update<F, R>(&self, unique: &str, mut f: F) -> ProtocolResult<R>
+    where
+        F: FnMut(&mut TransportCarrier) -> ProtocolResult<R>,
+    {
+        let (backend, prefix) = self.backend();
+        let mut output = None;
+        backend.update(&format!("{}{}", prefix, unique), &mut |carrier| {
+            output = Some(f(carrier)?);
+            Ok(())
+        })?;
+        // backend.update 返回 Ok 时 f 一定成功执行过
+        Ok(output.expect("update closure ran"))
+    }
+
+    // 锁只在进程内有效，与后端无关，按命名空间区分
+    fn lock_key(&self, unique: &str) -> String {
+        match &self.name {
+            Some(name) => format!("{}:{}", name, unique),
+            None => unique.to_string(),
+        }
+    }
+
+    pub fn with_device_lock<F, R>(&self, unique: &str, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        device_lock::with_lock(self.lock_key(unique), f)
+    }
+
+    pub fn lock_device(&self, unique: &str) -> DeviceLockFuture {
+        DeviceLockFuture::new(self.lock_key(unique))
+    }
+
+    /// 设备数量 (近似值)。与默认命名空间共用后端时返回的是整个后端的数量
+    pub fn read_size(&self) -> u64 {
+        self.backend().0.entry_count()
+    }
+
+    fn snapshot(&self) -> CacheSnapshot {
+        let (backend, prefix) = self.backend();
+        let entries = backend
+            .entries()
+            .into_iter()
+            .filter_map(|(key, carrier)| {
+                key.strip_prefix(&prefix).map(|unique| SnapshotEntry {
+                    unique: unique.to_string(),
+                    carrier: TransportCarrier::clone(&carrier),
+                })
+            })
+            .collect();
+        CacheSnapshot::new(entries)
+    }
+
+    pub fn export_snapshot(&self) -> ProtocolResult<Vec<u8>> {
+        self.snapshot().to_bytes()
+    }
+
+    pub fn import_snapshot(&self, data: &[u8]) -> ProtocolResult<usize> {
+        let snapshot = CacheSnapshot::from_bytes(data)?;
+        let count = snapshot.entries.len();
+        for entry in snapshot.entries {
+            self.store(&entry.unique, Arc::new(entry.carrier));
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespaces_are_isolated() {
+        let carrier = |count: &str| {
+            Arc::new(
+                TransportCarrier::try_new_with_device_no_and_upstream_count_hex("0201", count)
+                    .unwrap(),
+            )
+        };
+        ProtocolCache::configure_namespace("ns-water", 16, None, Some(Duration::from_secs(60)));
+        let gas = ProtocolCache::namespace("ns-gas");
+        let water = ProtocolCache::namespace("ns-water");
+        gas.store("0201", carrier("01"));
+        water.store("0201", carrier("02"));
+        water.update("0201", |c| c.set_cipher_slot(2)).unwrap();
+        assert_eq!(water.read("0201").unwrap().cipher_slot(), 2);
+
+        assert!(ProtocolCache::read("0201").is_none());
+        assert!(ProtocolCache::read("ns-gas:0201").is_some());
+        assert_eq!(gas.next_upstream_count("0201").unwrap().hex(), "02");
+        assert_eq!(water.next_upstream_count("0201").unwrap().hex(), "03");
+        gas.remove("0201");
+        assert!(gas.read("0201").is_none());
+        assert!(water.read("0201").is_some());
+    }
+
+    #[test]
+    fn test_try_read_or_default() {
+        let unique = "0501";
+        // 非法的序号 hex 返回错误，也不会写入缓存
+        assert!(ProtocolCache::try_read_or_default(unique, "ZZ").is_err());
+        assert!(ProtocolCache::read(unique).is_none());
+        assert!(ProtocolCache::try_read_or_default("05G1", "01").is_err());
+
+        let created = ProtocolCache::try_read_or_default(unique, "01").unwrap();
+        assert_eq!(created.upstream_count().unwrap().hex(), "01");
+        // 已存在时返回缓存中的对象，忽略传入的序号
+        let cached = ProtocolCache::try_read_or_default(unique, "ZZ").unwrap();
+        assert!(Arc::ptr_eq(&created, &cached));
+        ProtocolCache::remove(unique);
+    }
+
+    #[test]
+    fn test_update_in_place() {
+        let ns = ProtocolCache::namespace("ns-update");
+        assert!(ns.update("0601", |c| c.set_cipher_slot(1)).is_err());
+
+        let carrier =
+            TransportCarrier::try_new_with_device_no_and_upstream_count_hex("0601", "01").unwrap();
+        ns.store("0601", Arc::new(carrier));
+        let before = ns.read("0601").unwrap();
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                std::thread::spawn(move || {
+                    let ns = ProtocolCache::namespace("ns-update");
+                    for _ in 0..25 {
+                        ns.update("0601", |c| c.set_cipher_slot(c.cipher_slot() + 1))
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        // 并发更新不丢失，之前读到的 Arc 不受影响
+        assert_eq!(ns.read("0601").unwrap().cipher_slot(), -1 + 100);
+        assert_eq!(before.cipher_slot(), -1);
+
+        // f 返回错误时不写回
+        let result: ProtocolResult<()> = ns.try_update("0601", |c| {
+            c.set_cipher_slot(0);
+            Err(ProtocolError::ValidationFailed("rejected".into()))
+        });
+        assert!(result.is_err());
+        assert_eq!(ns.read("0601").unwrap().cipher_slot(), 99);
+        assert_eq!(ns.update("0601", |c| c.cipher_slot()).unwrap(), 99);
+        ns.remove("0601");
+    }
+
+    #[test]
+    fn test_on_evict() {
+        use std::sync::Mutex;
+
+        let evicted = Arc::new(Mutex::new(Vec::new()));
+        let sink = evicted.clone();
+        ProtocolCache::on_evict(move |key, carrier, cause| {
+            if key.starts_with("ns-evict:") {
+                let count = carrier.upstream_count().map(|c| c.hex_clone());
+                sink.lock().unwrap().push((key.to_string(), count, cause));
+            }
+        });
+        let ns = ProtocolCache::namespace("ns-evict");
+        let carrier =
+            TransportCarrier::try_new_with_device_no_and_upstream_count_hex("0401", "05").unwrap();
+        ns.store("0401", Arc::new(carrier.clone()));
+        ns.store("0401", Arc::new(carrier));
+        ns.remove("0401");
+        let evicted = evicted.lock().unwrap();
+        assert_eq!(
+            *evicted,
+            vec![(
+                "ns-evict:0401".to_string(),
+                Some("05".to_string()),
+                EvictionCause::Removed
+            )]
+        );
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let source = ProtocolCache::namespace("ns-snapshot-src");
+        let mut carrier =
+            TransportCarrier::try_new_with_device_no_and_upstream_count_hex("0301", "7F").unwrap();
+        carrier.set_cipher_slot(1);
+        source.store("0301", Arc::new(carrier));
+        let data = source.export_snapshot().unwrap();
+
+        let target = ProtocolCache::namespace("ns-snapshot-dst");
+        assert_eq!(target.import_snapshot(&data).unwrap(), 1);
+        let restored = target.read("0301").unwrap();
+        assert_eq!(restored.upstream_count().unwrap().hex(), "7F");
+        assert_eq!(restored.cipher_slot(), 1);
+        assert!(CacheSnapshot::from_bytes(br#"{"version":99}"#).is_err());
     }
 }
 
@@ -0,0 +1,366 @@
+use serde::{Deserialize, Serialize};
+
+use protocol_base::error::hex_digest_error::HexDigestError;
+
+use crate::{ProtocolError, ProtocolResult};
+
+/// 长度域最多的字节数，再长的值无法用 u64 表示
+pub const MAX_LENGTH_LEN: usize = 8;
+
+/// 协议的帧结构描述，供通用的分帧/命令识别代码使用。位置均为相对帧首的字节偏移
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProtocolConfig {
+    pub name: String,
+    // 帧头/帧尾，为空表示不校验
+    #[serde(with = "hex_bytes")]
+    pub head: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub tail: Vec<u8>,
+    // 命令码 (功能码) 的位置与字节数
+    pub cmd_index: usize,
+    pub cmd_len: usize,
+    // 地址域 (表号) 的位置与字节数，address_len 为 0 表示没有地址域
+    pub address_index: usize,
+    pub address_len: usize,
+    // 控制域位置 (1 字节)
+    pub control_index: Option<usize>,
+    // 数据单元的起始位置，之前的部分视为帧头部
+    pub data_offset: usize,
+    // 定长帧 (无长度域、无分隔) 的帧长
+    pub fixed_len: Option<usize>,
+    // 长度域的位置与字节数 (最多 MAX_LENGTH_LEN)，以及是否小端
+    pub length_index: Option<usize>,
+    #[serde(deserialize_with = "length_len::deserialize")]
+    pub length_len: usize,
+    pub length_le: bool,
+    // 长度域的值是否包含帧头部 (data_offset 之前的字节) / CRC。不包含时只计数据单元
+    pub length_includes_header: bool,
+    pub length_includes_crc: bool,
+    // CRC 字节数，位于帧尾之前
+    pub crc_len: usize,
+}
+
+impl ProtocolConfig {
+    pub fn new(name: &str, cmd_index: usize, cmd_len: usize) -> Self {
+        Self {
+            name: name.into(),
+            cmd_index,
+            cmd_len,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_head(mut self, head: &[u8]) -> Self {
+        self.head = head.to_vec();
+        self
+    }
+
+    pub fn with_tail(mut self, tail: &[u8]) -> Self {
+        self.tail = tail.to_vec();
+        self
+    }
+
+    pub fn with_address(mut self, index: usize, len: usize) -> Self {
+        self.address_index = index;
+        self.address_len = len;
+        self
+    }
+
+    pub fn with_control(mut self, index: usize) -> Self {
+        self.control_index = Some(index);
+        self
+    }
+
+    pub fn with_data_offset(mut self, offset: usize) -> Self {
+        self.data_offset = offset;
+        self
+    }
+
+    pub fn with_fixed_len(mut self, len: usize) -> Self {
+        self.fixed_len = Some(len);
+        self
+    }
+
+    /// 长度域。len 超过 MAX_LENGTH_LEN 时 validate 与 expected_len 返回 ValidationFailed，
+    /// 从配置文件反序列化时直接报错
+    pub fn with_length_field(mut self, index: usize, len: usize, little_endian: bool) -> Self {
+        self.length_index = Some(index);
+        self.length_len = len;
+        self.length_le = little_endian;
+        self
+    }
+
+    pub fn with_length_includes(mut self, header: bool, crc: bool) -> Self {
+        self.length_includes_header = header;
+        self.length_includes_crc = crc;
+        self
+    }
+
+    pub fn with_crc_len(mut self, len: usize) -> Self {
+        self.crc_len = len;
+        self
+    }
+
+    /// 检查配置本身是否合法，目前只检查长度域字节数
+    pub fn validate(&self) -> ProtocolResult<()> {
+        if self.length_index.is_some() && self.length_len > MAX_LENGTH_LEN {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "{} length field is {} bytes, at most {} supported",
+                self.name, self.length_len, MAX_LENGTH_LEN
+            )));
+        }
+        Ok(())
+    }
+
+    // 帧中固定位置字段都能取到所需的最小帧长
+    pub(crate) fn min_len(&self) -> usize {
+        let trailer = self.crc_len + self.tail.len();
+        [
+            self.head.len() + trailer,
+            self.cmd_index + self.cmd_len,
+            self.address_index + self.address_len,
+            self.control_index.map_or(0, |i| i + 1),
+            self.data_offset + trailer,
+            self.length_index.map_or(0, |i| i + self.length_len),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+    }
+
+    /// 根据已收到的字节推算整帧长度: 定长帧直接返回，有长度域时按长度域计算。
+    /// 长度域还没收全时返回 InputTooShort，没有长度信息 (靠帧尾分隔) 时返回 None
+    pub fn expected_len(&self, buf: &[u8]) -> ProtocolResult<Option<usize>> {
+        if let Some(len) = self.fixed_len {
+            return Ok(Some(len));
+        }
+        let Some(index) = self.length_index else {
+            return Ok(None);
+        };
+        self.validate()?;
+        let end = index + self.length_len;
+        if buf.len() < end {
+            return Err(ProtocolError::InputTooShort {
+                needed: end,
+                available: buf.len(),
+            });
+        }
+        let field = &buf[index..end];
+        let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+        let value = if self.length_le {
+            field.iter().rev().fold(0, fold)
+        } else {
+            field.iter().fold(0, fold)
+        };
+        let header = if self.length_includes_header {
+            0
+        } else {
+            self.data_offset
+        };
+        let crc = if self.length_includes_crc {
+            0
+        } else {
+            self.crc_len
+        };
+        usize::try_from(value)
+            .ok()
+            .and_then(|v| v.checked_add(self.tail.len()))
+            .and_then(|v| v.checked_add(header))
+            .and_then(|v| v.checked_add(crc))
+            .map(Some)
+            .ok_or_else(|| {
+                ProtocolError::ValidationFailed(format!(
+                    "{} length field value {} overflows",
+                    self.name, value
+                ))
+            })
+    }
+
+    /// 校验帧头/帧尾、帧长 (定长或长度域) 以及帧长是否足以包含各个字段
+    pub fn check_frame(&self, frame: &[u8]) -> ProtocolResult<()> {
+        let needed = self.min_len();
+        if frame.len() < needed {
+            return Err(ProtocolError::InputTooShort {
+                needed,
+                available: frame.len(),
+            });
+        }
+        if !frame.starts_with(&self.head) {
+            return Err(HexDigestError::InvalidHead.into());
+        }
+        if !frame.ends_with(&self.tail) {
+            return Err(HexDigestError::InvalidTail.into());
+        }
+        if let Some(expected) = self.expected_len(frame)? {
+            if expected != frame.len() {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "{} frame length {} does not match expected {}",
+                    self.name,
+                    frame.len(),
+                    expected
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// 取出帧中的命令码 (hex，大写)
+    pub fn cmd_code_of(&self, frame: &[u8]) -> ProtocolResult<String> {
+        self.check_frame(frame)?;
+        Ok(hex::encode_upper(
+            &frame[self.cmd_index..self.cmd_index + self.cmd_len],
+        ))
+    }
+
+    /// 取出地址域 (hex，大写)，没有地址域时返回 None
+    pub fn address_of(&self, frame: &[u8]) -> ProtocolResult<Option<String>> {
+        if self.address_len == 0 {
+            return Ok(None);
+        }
+        self.check_frame(frame)?;
+        Ok(Some(hex::encode_upper(
+            &frame[self.address_index..self.address_index + self.address_len],
+        )))
+    }
+
+    pub fn control_of(&self, frame: &[u8]) -> ProtocolResult<Option<u8>> {
+        let Some(index) = self.control_index else {
+            return Ok(None);
+        };
+        self.check_frame(frame)?;
+        Ok(Some(frame[index]))
+    }
+
+    /// 数据单元: data_offset 到 CRC 之前
+    pub fn data_unit<'a>(&self, frame: &'a [u8]) -> ProtocolResult<&'a [u8]> {
+        self.check_frame(frame)?;
+        let end = frame.len() - self.crc_len - self.tail.len();
+        Ok(&frame[self.data_offset..end])
+    }
+}
+
+// 配置文件中帧头/帧尾写作 hex 字符串，如 "68"
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode_upper(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.trim()).map_err(serde::de::Error::custom)
+    }
+}
+
+mod length_len {
+    use serde::{de::Error, Deserialize, Deserializer};
+
+    use super::MAX_LENGTH_LEN;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+        let len = usize::deserialize(deserializer)?;
+        if len > MAX_LENGTH_LEN {
+            return Err(D::Error::custom(format!(
+                "length field is {} bytes, at most {} supported",
+                len, MAX_LENGTH_LEN
+            )));
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 68 | 地址 4 字节 | 控制域 | 长度 (只计数据单元) | 数据单元 | CRC 2 字节 | 16
+    fn delimited() -> ProtocolConfig {
+        ProtocolConfig::new("delimited", 6, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16])
+            .with_address(1, 4)
+            .with_control(5)
+            .with_length_field(7, 1, false)
+            .with_data_offset(8)
+            .with_crc_len(2)
+    }
+
+    #[test]
+    fn test_geometry() {
+        let config = delimited();
+        let frame = [
+            0x68, 0x12, 0x34, 0x56, 0x78, 0x81, 0x02, 0x02, 0xAA, 0xBB, 0xC1, 0xC2, 0x16,
+        ];
+        assert_eq!(config.expected_len(&frame[..8]).unwrap(), Some(13));
+        assert!(config.expected_len(&frame[..5]).is_err());
+        assert_eq!(config.cmd_code_of(&frame).unwrap(), "02");
+        assert_eq!(
+            config.address_of(&frame).unwrap().as_deref(),
+            Some("12345678")
+        );
+        assert_eq!(config.control_of(&frame).unwrap(), Some(0x81));
+        assert_eq!(config.data_unit(&frame).unwrap(), &[0xAA, 0xBB]);
+
+        // 长度域与实际帧长不符
+        let mut bad = frame.to_vec();
+        bad[7] = 0x03;
+        assert!(matches!(
+            config.check_frame(&bad),
+            Err(ProtocolError::ValidationFailed(_))
+        ));
+
+        // 长度域包含帧头部和 CRC
+        let config = config.with_length_includes(true, true);
+        let mut frame = frame.to_vec();
+        frame[7] = 12;
+        assert!(config.check_frame(&frame).is_ok());
+    }
+
+    #[test]
+    fn test_length_field_limits() {
+        // 8 字节全 FF 的长度域不能溢出
+        let config = ProtocolConfig::new("wide", 0, 1)
+            .with_length_field(1, 8, false)
+            .with_data_offset(9)
+            .with_tail(&[0x16]);
+        let mut frame = vec![0x01];
+        frame.extend([0xFF; 8]);
+        assert!(matches!(
+            config.expected_len(&frame),
+            Err(ProtocolError::ValidationFailed(_))
+        ));
+
+        let config = config.with_length_field(1, 9, false);
+        assert!(config.validate().is_err());
+        assert!(matches!(
+            config.expected_len(&[0; 16]),
+            Err(ProtocolError::ValidationFailed(_))
+        ));
+        assert!(delimited().validate().is_ok());
+
+        let json = r#"{"name":"wide","lengthIndex":1,"lengthLen":9}"#;
+        assert!(serde_json::from_str::<ProtocolConfig>(json).is_err());
+        let json = r#"{"name":"wide","lengthIndex":1,"lengthLen":2}"#;
+        assert_eq!(
+            serde_json::from_str::<ProtocolConfig>(json)
+                .unwrap()
+                .length_len,
+            2
+        );
+    }
+
+    #[test]
+    fn test_fixed_len() {
+        let config = ProtocolConfig::new("fixed", 0, 1)
+            .with_fixed_len(4)
+            .with_data_offset(1);
+        assert!(config.check_frame(&[0x01, 0x02, 0x03, 0x04]).is_ok());
+        assert!(config.check_frame(&[0x01, 0x02, 0x03]).is_err());
+        assert_eq!(
+            config.data_unit(&[0x01, 0x02, 0x03, 0x04]).unwrap(),
+            &[0x02, 0x03, 0x04]
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! 解码/编码过程中传给翻译器与 AutoDecoding 的上下文。
+//! 需要设备状态的解码器 (密钥槽、上一次的读数等) 从这里取，不再自行读取全局缓存
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use protocol_base::{ProtocolError, ProtocolResult};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    bridge::trace,
+    core::{
+        cache::ProtocolCache, config::ProtocolConfig, parts::transport_carrier::TransportCarrier,
+    },
+};
+
+/// 帧加解密的实现，按 TransportCarrier 的 cipher_slot 选取
+pub trait FrameCipher: Send + Sync {
+    fn decrypt(&self, data: &[u8]) -> ProtocolResult<Vec<u8>>;
+
+    fn encrypt(&self, data: &[u8]) -> ProtocolResult<Vec<u8>>;
+}
+
+/// 密钥槽 -> 加解密实现
+#[derive(Clone, Default)]
+pub struct CipherRegistry {
+    ciphers: HashMap<i8, Arc<dyn FrameCipher>>,
+}
+
+impl CipherRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<C: FrameCipher + 'static>(mut self, slot: i8, cipher: C) -> Self {
+        self.ciphers.insert(slot, Arc::new(cipher));
+        self
+    }
+
+    pub fn get(&self, slot: i8) -> Option<Arc<dyn FrameCipher>> {
+        self.ciphers.get(&slot).cloned()
+    }
+
+    pub fn slots(&self) -> Vec<i8> {
+        let mut slots: Vec<i8> = self.ciphers.keys().copied().collect();
+        slots.sort();
+        slots
+    }
+}
+
+type DecodeLogger = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// 一帧解码/编码的上下文
+#[derive(Clone)]
+pub struct DecodeContext {
+    protocol: String,
+    // 设备状态的副本，修改后需 persist 才会写回缓存
+    carrier: TransportCarrier,
+    config: Option<ProtocolConfig>,
+    // 请求参数 (下行参数或上行附带的参数)
+    params: HashMap<String, String>,
+    ciphers: CipherRegistry,
+    logger: Option<DecodeLogger>,
+    // 设备在缓存中的 key，设置后 commit 会把改动的状态写回缓存
+    unique: Option<String>,
+    // 本次解码改动过的状态，None 表示删除
+    dirty: BTreeMap<String, Option<serde_json::Value>>,
+}
+
+impl DecodeContext {
+    pub fn new(protocol: &str, carrier: TransportCarrier) -> Self {
+        Self {
+            protocol: protocol.into(),
+            carrier,
+            config: None,
+            params: HashMap::new(),
+            ciphers: CipherRegistry::default(),
+            logger: None,
+            unique: None,
+            dirty: BTreeMap::new(),
+        }
+    }
+
+    /// 从缓存读取设备状态，没有时使用空的 TransportCarrier (不加密)。
+    /// 与 registry 一样先查以协议命名的命名空间，再查默认命名空间
+    pub fn for_device(protocol: &str, unique: &str) -> Self {
+        let carrier = ProtocolCache::device_namespace(protocol, unique)
+            .read(unique)
+            .map(|c| c.as_ref().clone())
+            .unwrap_or_else(|| {
+                let mut carrier = TransportCarrier::default();
+                carrier.set_cipher_slot(-1);
+                carrier
+            });
+        Self::new(protocol, carrier).with_unique(unique)
+    }
+
+    /// 指定设备在缓存中的 key，commit 时写回
+    pub fn with_unique(mut self, unique: &str) -> Self {
+        self.unique = Some(unique.into());
+        self
+    }
+
+    pub fn with_config(mut self, config: ProtocolConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn with_params(mut self, params: HashMap<String, String>) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn with_ciphers(mut self, ciphers: CipherRegistry) -> Self {
+        self.ciphers = ciphers;
+        self
+    }
+
+    /// 替换默认的日志输出 (stderr)
+    pub fn with_logger<F>(mut self, logger: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.logger = Some(Arc::new(logger));
+        self
+    }
+
+    pub fn protocol(&self) -> &str {
+        &self.protocol
+    }
+
+    pub fn carrier(&self) -> &TransportCarrier {
+        &self.carrier
+    }
+
+    pub fn carrier_mut(&mut self) -> &mut TransportCarrier {
+        &mut self.carrier
+    }
+
+    pub fn config(&self) -> Option<&ProtocolConfig> {
+        self.config.as_ref()
+    }
+
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params.get(key).map(|v| v.as_str())
+    }
+
+    pub fn params(&self) -> &HashMap<String, String> {
+        &self.params
+    }
+
+    /// 当前设备密钥槽对应的加解密实现，不加密 (cipher_slot < 0) 时为 None
+    pub fn cipher(&self) -> ProtocolResult<Option<Arc<dyn FrameCipher>>> {
+        let slot = self.carrier.cipher_slot();
+        if slot < 0 {
+            return Ok(None);
+        }
+        self.ciphers.get(slot).map(Some).ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!(
+                "protocol {} has no cipher for slot {}",
+                self.protocol, slot
+            ))
+        })
+    }
+
+    /// 按密钥槽解密，不加密时原样返回
+    pub fn decrypt(&self, data: &[u8]) -> ProtocolResult<Vec<u8>> {
+        match self.cipher()? {
+            Some(cipher) => cipher.decrypt(data),
+            None => Ok(data.to_vec()),
+        }
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> ProtocolResult<Vec<u8>> {
+        match self.cipher()? {
+            Some(cipher) => cipher.encrypt(data),
+            None => Ok(data.to_vec()),
+        }
+    }
+
+    pub fn warn(&self, message: &str) {
+        match &self.logger {
+            Some(logger) => logger(message),
+            None => eprintln!(
+                "[WARN] {}{}: {}",
+                trace::log_prefix(),
+                self.protocol,
+                message
+            ),
+        }
+    }
+
+    /// 把修改过的设备状态写回缓存
+    pub fn persist(&self, unique: &str) {
+        ProtocolCache::device_namespace(&self.protocol, unique)
+            .store(unique, Arc::new(self.carrier.clone()));
+    }
+
+    /// 读取跨帧保存的状态 (上一次的读数、应答位等)，保存在 TransportCarrier 的扩展状态中
+    pub fn state<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.carrier.get_ext(key)
+    }
+
+    pub fn set_state<T: Serialize>(&mut self, key: &str, value: T) -> ProtocolResult<()> {
+        self.carrier.set_ext(key, value)?;
+        self.dirty
+            .insert(key.into(), self.carrier.ext.get(key).cloned());
+        Ok(())
+    }
+
+    pub fn remove_state(&mut self, key: &str) {
+        self.carrier.remove_ext(key);
+        self.dirty.insert(key.into(), None);
+    }
+
+    /// 是否有尚未写回缓存的状态
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// 把 set_state/remove_state 的改动写回缓存，只合并改动过的 key，不覆盖其他字段 (如序号)。
+    /// 没有指定 unique 时不写回，返回 false。AutoDecoding::auto_process_with 成功后会自动调用
+    pub fn commit(&mut self) -> ProtocolResult<bool> {
+        let Some(unique) = self.unique.clone() else {
+            return Ok(false);
+        };
+        if self.dirty.is_empty() {
+            return Ok(false);
+        }
+        let namespace = ProtocolCache::device_namespace(&self.protocol, &unique);
+        if namespace.read(&unique).is_none() {
+            self.persist(&unique);
+        } else {
+            let dirty = &self.dirty;
+            namespace.update(&unique, |carrier| {
+                for (key, value) in dirty {
+                    match value {
+                        Some(value) => carrier.ext.insert(key.clone(), value.clone()),
+                        None => carrier.ext.remove(key),
+                    };
+                }
+            })?;
+        }
+        self.dirty.clear();
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{
+        core::parts::decoding_filter::DecodingFilter, AutoDecoding, AutoDecodingParam, FieldType,
+        Rawfield, Reader, Symbol,
+    };
+
+    struct Xor(u8);
+
+    impl FrameCipher for Xor {
+        fn decrypt(&self, data: &[u8]) -> ProtocolResult<Vec<u8>> {
+            Ok(data.iter().map(|b| b ^ self.0).collect())
+        }
+
+        fn encrypt(&self, data: &[u8]) -> ProtocolResult<Vec<u8>> {
+            self.decrypt(data)
+        }
+    }
+
+    // 读数字段按密钥槽解密后再翻译
+    struct Reading;
+
+    impl AutoDecodingParam for Reading {
+        fn byte_length(&self) -> usize {
+            1
+        }
+
+        fn title(&self) -> String {
+            "读数".into()
+        }
+
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU8(1.0)
+        }
+
+        fn translate_with(
+            &self,
+            bytes: &[u8],
+            ctx: &mut DecodeContext,
+        ) -> ProtocolResult<Rawfield> {
+            self.translate(&ctx.decrypt(bytes)?)
+        }
+    }
+
+    struct Report;
+
+    impl AutoDecoding<Reading> for Report {
+        fn variants(&self) -> Vec<Reading> {
+            vec![Reading]
+        }
+    }
+
+    #[test]
+    fn test_context_cipher() {
+        let mut carrier = TransportCarrier::default();
+        carrier.set_cipher_slot(1);
+        let logged = Arc::new(Mutex::new(Vec::new()));
+        let sink = logged.clone();
+        let mut ctx = DecodeContext::new("demo", carrier)
+            .with_ciphers(CipherRegistry::new().register(1, Xor(0x0F)))
+            .with_logger(move |m| sink.lock().unwrap().push(m.to_string()));
+
+        let mut reader = Reader::new(&[0x0A]);
+        Report.auto_process_with(&mut reader, &mut ctx).unwrap();
+        assert_eq!(reader.fields().unwrap()[0].value, "5");
+
+        ctx.carrier_mut().set_cipher_slot(2);
+        assert!(ctx.decrypt(&[0x01]).is_err());
+        ctx.carrier_mut().set_cipher_slot(-1);
+        assert_eq!(ctx.decrypt(&[0x01]).unwrap(), [0x01]);
+
+        ctx.warn("slot changed");
+        assert_eq!(logged.lock().unwrap().as_slice(), ["slot changed"]);
+    }
+
+    // 未覆盖 translate_with 的字段走默认流程，拦截器、单位等与 translate 一致
+    struct Balance;
+
+    impl AutoDecodingParam for Balance {
+        fn byte_length(&self) -> usize {
+            2
+        }
+
+        fn title(&self) -> String {
+            "余额".into()
+        }
+
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU16(0.01)
+        }
+
+        fn symbol(&self) -> Option<Symbol> {
+            Some(Symbol::Yuan)
+        }
+
+        fn filter(&self) -> Option<DecodingFilter> {
+            Some(DecodingFilter::new(vec![0xFF, 0xFF], "无效".into()))
+        }
+    }
+
+    #[test]
+    fn test_default_translate_with() {
+        let mut ctx = DecodeContext::new("demo", TransportCarrier::default());
+        for bytes in [[0x04, 0xD2], [0xFF, 0xFF]] {
+            let plain = Balance.translate(&bytes).unwrap();
+            let with_ctx = Balance.translate_with(&bytes, &mut ctx).unwrap();
+            assert_eq!(with_ctx.value, plain.value);
+            assert_eq!(with_ctx.unit, plain.unit);
+        }
+        assert_eq!(
+            Balance
+                .translate_with(&[0x04, 0xD2], &mut ctx)
+                .unwrap()
+                .value,
+            "12.34 元"
+        );
+        assert_eq!(
+            Balance
+                .translate_with(&[0xFF, 0xFF], &mut ctx)
+                .unwrap()
+                .value,
+            "无效"
+        );
+    }
+
+    #[test]
+    fn test_configured_namespace() {
+        ProtocolCache::configure_namespace("ctx-ns-test", 100, None, None);
+        let namespace = ProtocolCache::namespace("ctx-ns-test");
+        let mut carrier = TransportCarrier::default();
+        carrier.set_ext("flow", 100u64).unwrap();
+        namespace.store("ctx-ns-dev", Arc::new(carrier));
+
+        // 读取与写回都在协议自己的缓存中，不经过默认命名空间
+        let mut ctx = DecodeContext::for_device("ctx-ns-test", "ctx-ns-dev");
+        assert_eq!(ctx.state::<u64>("flow"), Some(100));
+        ctx.set_state("flow", 150u64).unwrap();
+        assert!(ctx.commit().unwrap());
+        let stored = namespace.read("ctx-ns-dev").unwrap();
+        assert_eq!(stored.get_ext::<u64>("flow"), Some(150));
+        assert!(ProtocolCache::read("ctx-ns-dev").is_none());
+
+        // 新设备写入协议自己的缓存
+        let mut ctx = DecodeContext::for_device("ctx-ns-test", "ctx-ns-new");
+        ctx.set_state("flow", 1u64).unwrap();
+        ctx.commit().unwrap();
+        assert!(namespace.read("ctx-ns-new").is_some());
+        assert!(ProtocolCache::read("ctx-ns-new").is_none());
+    }
+}
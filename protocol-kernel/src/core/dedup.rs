@@ -0,0 +1,80 @@
+use moka::sync::Cache;
+use once_cell::sync::Lazy;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{bridge::trace, JniResponse};
+
+// 每个设备记住的最近上行序号个数。GPRS 模块重发通常只隔几帧，16 足够
+const WINDOW: usize = 16;
+
+// 设备 -> 最近的上行序号 (hex，大写)。设备 10 分钟没有上行后自动清除
+static RECENT_COUNTS: Lazy<Cache<String, Arc<Mutex<VecDeque<String>>>>> = Lazy::new(|| {
+    Cache::builder()
+        .max_capacity(100_000)
+        .time_to_idle(Duration::from_secs(10 * 60))
+        .build()
+});
+
+/// 按上行序号 (upstream_count) 识别重复帧
+pub struct FrameDedup {}
+
+impl FrameDedup {
+    /// 记录一次上行。序号最近出现过时返回 true (重复帧)，否则记录并返回 false
+    pub fn check_and_record(unique: &str, upstream_count_hex: &str) -> bool {
+        let count = upstream_count_hex.trim().to_uppercase();
+        let entry = RECENT_COUNTS.get_with(unique.to_string(), || {
+            Arc::new(Mutex::new(VecDeque::with_capacity(WINDOW)))
+        });
+        let mut recent = entry.lock().unwrap_or_else(|e| e.into_inner());
+        if recent.contains(&count) {
+            eprintln!(
+                "[WARN] {}Duplicate frame for {}: upstream count {}",
+                trace::log_prefix(),
+                unique,
+                count
+            );
+            return true;
+        }
+        if recent.len() == WINDOW {
+            recent.pop_front();
+        }
+        recent.push_back(count);
+        false
+    }
+
+    /// 同 check_and_record，并把结果标记到响应上
+    pub fn mark(unique: &str, upstream_count_hex: &str, response: &mut JniResponse) -> bool {
+        let duplicate = Self::check_and_record(unique, upstream_count_hex);
+        response.set_duplicate(duplicate);
+        duplicate
+    }
+
+    /// 清除设备的序号记录，例如设备重新注册、序号归零时
+    pub fn forget(unique: &str) {
+        RECENT_COUNTS.invalidate(unique);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window() {
+        let unique = "dedup-test-01";
+        assert!(!FrameDedup::check_and_record(unique, "0a"));
+        assert!(FrameDedup::check_and_record(unique, "0A"));
+        for i in 0..WINDOW {
+            FrameDedup::check_and_record(unique, &format!("{:02X}", 0x10 + i));
+        }
+        // 0A 已被挤出窗口
+        assert!(!FrameDedup::check_and_record(unique, "0A"));
+        FrameDedup::forget(unique);
+        RECENT_COUNTS.run_pending_tasks();
+        assert!(!FrameDedup::check_and_record(unique, "10"));
+    }
+}
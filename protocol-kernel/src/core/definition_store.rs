@@ -0,0 +1,660 @@
+//! 运行时加载的协议定义目录。新表计固件的解码规则以 .json/.toml/.yaml 文件下发到目录，
+//! 重新加载后立即生效，无需重新部署 .so:
+//!
+//! - `watch` (feature `watch`): 基于文件系统通知 (inotify/FSEvents/ReadDirectoryChangesW)，文件写入后立即重新加载
+//! - `poll_every`: 后台线程按文件修改时间轮询，用于收不到通知的场合 (NFS 等网络文件系统)
+//! - `poll`: 由宿主自行调度
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime},
+};
+
+use protocol_base::{ProtocolError, ProtocolResult};
+
+use crate::core::dsl::ProtocolDefinition;
+
+/// 目录中一份已加载的定义
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadedDefinition {
+    pub definition: ProtocolDefinition,
+    pub path: PathBuf,
+    // 每次加载递增，同一协议的修订号单调增加
+    pub revision: u64,
+    modified: Option<SystemTime>,
+}
+
+impl LoadedDefinition {
+    pub fn protocol(&self) -> &str {
+        &self.definition.protocol
+    }
+
+    /// 定义文件中声明的版本号
+    pub fn version(&self) -> &str {
+        &self.definition.version
+    }
+}
+
+/// 一次 poll 中发生的变化
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReloadEvent {
+    Loaded {
+        protocol: String,
+        version: String,
+        revision: u64,
+    },
+    Removed {
+        protocol: String,
+    },
+    // 文件解析或校验失败，继续使用之前加载的版本
+    Failed {
+        path: PathBuf,
+        error: String,
+    },
+}
+
+#[derive(Default)]
+struct State {
+    // 协议名 -> 当前生效的定义
+    current: HashMap<String, Arc<LoadedDefinition>>,
+    // 协议名 -> 按 version 保留的历史定义，用于按版本回退
+    versions: HashMap<String, HashMap<String, Arc<LoadedDefinition>>>,
+    // 文件 -> (修改时间, 加载出的协议名)。加载失败的文件只记修改时间，避免每次 poll 都重试
+    files: HashMap<PathBuf, (Option<SystemTime>, Option<String>)>,
+    revision: u64,
+}
+
+impl State {
+    // 文件 released 不再定义 protocol 时，由目录中另一个定义该协议的文件接管:
+    // 优先重新发布最近加载过的定义，历史版本已被覆盖时重新读取文件。
+    // 没有其他文件时移除协议
+    fn release(&mut self, protocol: &str, released: &Path) -> ReloadEvent {
+        let mut candidates: Vec<PathBuf> = self
+            .files
+            .iter()
+            .filter(|(path, (_, p))| path.as_path() != released && p.as_deref() == Some(protocol))
+            .map(|(path, _)| path.clone())
+            .collect();
+        candidates.sort();
+        let published = self.versions.get(protocol).and_then(|versions| {
+            versions
+                .values()
+                .filter(|d| candidates.contains(&d.path))
+                .max_by_key(|d| d.revision)
+                .cloned()
+        });
+        if let Some(loaded) = published {
+            self.current.insert(protocol.to_string(), loaded.clone());
+            return ReloadEvent::Loaded {
+                protocol: protocol.to_string(),
+                version: loaded.version().to_string(),
+                revision: loaded.revision,
+            };
+        }
+        for path in candidates {
+            let definition = match ProtocolDefinition::from_file(&path) {
+                Ok(definition) if definition.protocol == protocol => definition,
+                _ => continue,
+            };
+            self.revision += 1;
+            let loaded = Arc::new(LoadedDefinition {
+                definition,
+                modified: self.files.get(&path).and_then(|(m, _)| *m),
+                path,
+                revision: self.revision,
+            });
+            self.versions
+                .entry(protocol.to_string())
+                .or_default()
+                .insert(loaded.version().to_string(), loaded.clone());
+            self.current.insert(protocol.to_string(), loaded.clone());
+            return ReloadEvent::Loaded {
+                protocol: protocol.to_string(),
+                version: loaded.version().to_string(),
+                revision: loaded.revision,
+            };
+        }
+        self.current.remove(protocol);
+        ReloadEvent::Removed {
+            protocol: protocol.to_string(),
+        }
+    }
+}
+
+type ReloadListener = Arc<dyn Fn(&ReloadEvent) + Send + Sync>;
+
+/// 协议定义目录。每个文件定义一个协议，文件名不限，以 protocol 字段为准
+pub struct DefinitionStore {
+    dir: PathBuf,
+    state: RwLock<State>,
+    listeners: RwLock<Vec<ReloadListener>>,
+}
+
+impl DefinitionStore {
+    /// 打开目录并加载其中所有定义。单个文件出错不影响其他文件，错误通过 poll 的事件返回
+    pub fn open<P: AsRef<Path>>(dir: P) -> ProtocolResult<Self> {
+        let store = Self {
+            dir: dir.as_ref().to_path_buf(),
+            state: RwLock::new(State::default()),
+            listeners: RwLock::new(Vec::new()),
+        };
+        store.poll()?;
+        Ok(store)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 协议当前生效的定义
+    pub fn get(&self, protocol: &str) -> Option<Arc<LoadedDefinition>> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.current.get(protocol).cloned()
+    }
+
+    /// 按定义中声明的 version 取历史版本，文件更新后旧版本仍可用于尚未升级的设备
+    pub fn get_version(&self, protocol: &str, version: &str) -> Option<Arc<LoadedDefinition>> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        state.versions.get(protocol)?.get(version).cloned()
+    }
+
+    /// 协议已加载过的所有 version
+    pub fn versions(&self, protocol: &str) -> Vec<String> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let mut versions: Vec<String> = state
+            .versions
+            .get(protocol)
+            .map(|v| v.keys().cloned().collect())
+            .unwrap_or_default();
+        versions.sort();
+        versions
+    }
+
+    pub fn protocols(&self) -> Vec<String> {
+        let state = self.state.read().unwrap_or_else(|e| e.into_inner());
+        let mut protocols: Vec<String> = state.current.keys().cloned().collect();
+        protocols.sort();
+        protocols
+    }
+
+    /// 每次 poll 产生的事件都会通知监听者
+    pub fn on_reload<F>(&self, listener: F)
+    where
+        F: Fn(&ReloadEvent) + Send + Sync + 'static,
+    {
+        let mut listeners = self.listeners.write().unwrap_or_else(|e| e.into_inner());
+        listeners.push(Arc::new(listener));
+    }
+
+    /// 扫描目录: 加载新增或修改时间变化的文件，移除已删除文件定义的协议
+    pub fn poll(&self) -> ProtocolResult<Vec<ReloadEvent>> {
+        self.refresh(&HashSet::new())
+    }
+
+    // 同 poll，forced 中的文件 (按文件名) 不论修改时间是否变化都重新加载。
+    // 文件系统通知已经说明文件被改过，修改时间精度不足时也不能漏掉
+    fn refresh(&self, forced: &HashSet<OsString>) -> ProtocolResult<Vec<ReloadEvent>> {
+        let scanned = scan(&self.dir)?;
+        let mut events = Vec::new();
+        {
+            let mut state = self.state.write().unwrap_or_else(|e| e.into_inner());
+            let removed: Vec<PathBuf> = state
+                .files
+                .keys()
+                .filter(|p| !scanned.iter().any(|(path, _)| path == *p))
+                .cloned()
+                .collect();
+            // 先全部移除，接管时不会选中同一次被删除的文件
+            let removed: Vec<(PathBuf, String)> = removed
+                .into_iter()
+                .filter_map(|path| match state.files.remove(&path) {
+                    Some((_, Some(protocol))) => Some((path, protocol)),
+                    _ => None,
+                })
+                .collect();
+            for (path, protocol) in removed {
+                // 同一协议已由其他文件接管时不移除
+                let owned = state.current.get(&protocol).is_some_and(|d| d.path == path);
+                if owned {
+                    events.push(state.release(&protocol, &path));
+                }
+            }
+
+            for (path, modified) in scanned {
+                let forced = path.file_name().is_some_and(|name| forced.contains(name));
+                let unchanged = !forced
+                    && state
+                        .files
+                        .get(&path)
+                        .is_some_and(|(m, _)| modified.is_some() && *m == modified);
+                if unchanged {
+                    continue;
+                }
+                match ProtocolDefinition::from_file(&path) {
+                    Ok(definition) => {
+                        state.revision += 1;
+                        let loaded = Arc::new(LoadedDefinition {
+                            definition,
+                            path: path.clone(),
+                            revision: state.revision,
+                            modified,
+                        });
+                        let protocol = loaded.protocol().to_string();
+                        // 文件改了 protocol 名时，旧名字不再由该文件定义
+                        let previous = state.files.get(&path).and_then(|(_, p)| p.clone());
+                        if let Some(previous) = previous.filter(|p| *p != protocol) {
+                            let owned =
+                                state.current.get(&previous).is_some_and(|d| d.path == path);
+                            if owned {
+                                events.push(state.release(&previous, &path));
+                            }
+                        }
+                        state
+                            .versions
+                            .entry(protocol.clone())
+                            .or_default()
+                            .insert(loaded.version().to_string(), loaded.clone());
+                        state.current.insert(protocol.clone(), loaded.clone());
+                        state.files.insert(path, (modified, Some(protocol.clone())));
+                        events.push(ReloadEvent::Loaded {
+                            protocol,
+                            version: loaded.version().to_string(),
+                            revision: loaded.revision,
+                        });
+                    }
+                    Err(e) => {
+                        let protocol = state.files.get(&path).and_then(|(_, p)| p.clone());
+                        state.files.insert(path.clone(), (modified, protocol));
+                        events.push(ReloadEvent::Failed {
+                            path,
+                            error: e.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        let listeners = self
+            .listeners
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        for event in &events {
+            for listener in &listeners {
+                listener(event);
+            }
+        }
+        Ok(events)
+    }
+
+    /// 启动后台线程每隔 interval 调用一次 poll (轮询，可以收到文件系统通知时优先用 watch)，
+    /// 返回的 DefinitionPoller 被 drop 时停止。interval 小于 MIN_POLL_INTERVAL 时按 MIN_POLL_INTERVAL 处理
+    pub fn poll_every(self: &Arc<Self>, interval: Duration) -> DefinitionPoller {
+        let interval = interval.max(MIN_POLL_INTERVAL);
+        let stop = Arc::new(AtomicBool::new(false));
+        let store = Arc::downgrade(self);
+        let flag = stop.clone();
+        let handle = thread::spawn(move || {
+            // 分段睡眠，stop 后尽快退出
+            let step = interval.min(Duration::from_millis(50));
+            let mut elapsed = Duration::ZERO;
+            while !flag.load(Ordering::Relaxed) {
+                thread::sleep(step);
+                elapsed += step;
+                if elapsed < interval {
+                    continue;
+                }
+                elapsed = Duration::ZERO;
+                let Some(store) = store.upgrade() else {
+                    break;
+                };
+                if let Err(e) = store.poll() {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        dir = %store.dir.display(),
+                        error = %e,
+                        "poll protocol definitions failed"
+                    );
+                    #[cfg(not(feature = "tracing"))]
+                    eprintln!("[WARN] poll protocol definitions failed: {}", e);
+                }
+            }
+        });
+        DefinitionPoller {
+            stop,
+            interval,
+            handle: Some(handle),
+        }
+    }
+}
+
+#[cfg(feature = "watch")]
+impl DefinitionStore {
+    /// 监听目录的文件系统通知，定义文件新增、修改、删除后立即重新加载。
+    /// 返回的 DefinitionWatcher 被 drop 时停止监听
+    pub fn watch(self: &Arc<Self>) -> ProtocolResult<DefinitionWatcher> {
+        use notify::{EventKind, RecursiveMode, Watcher};
+
+        let store = Arc::downgrade(self);
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Some(store) = store.upgrade() else {
+                    return;
+                };
+                let forced = match event {
+                    Ok(event) if matches!(event.kind, EventKind::Access(_)) => return,
+                    Ok(event) => event
+                        .paths
+                        .iter()
+                        .filter_map(|p| p.file_name().map(|n| n.to_os_string()))
+                        .collect(),
+                    // 通知丢失 (队列溢出等) 时按修改时间全量扫描
+                    Err(_) => HashSet::new(),
+                };
+                if let Err(e) = store.refresh(&forced) {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(
+                        dir = %store.dir.display(),
+                        error = %e,
+                        "reload protocol definitions failed"
+                    );
+                    #[cfg(not(feature = "tracing"))]
+                    eprintln!("[WARN] reload protocol definitions failed: {}", e);
+                }
+            })
+            .map_err(|e| ProtocolError::CommonError(format!("{}: {}", self.dir.display(), e)))?;
+        watcher
+            .watch(&self.dir, RecursiveMode::NonRecursive)
+            .map_err(|e| ProtocolError::CommonError(format!("{}: {}", self.dir.display(), e)))?;
+        // 开始监听之前发生的变化
+        self.poll()?;
+        Ok(DefinitionWatcher { _watcher: watcher })
+    }
+}
+
+/// 文件系统通知的句柄，drop 时停止监听
+#[cfg(feature = "watch")]
+pub struct DefinitionWatcher {
+    _watcher: notify::RecommendedWatcher,
+}
+
+/// poll_every 允许的最小轮询间隔，避免 Duration::ZERO 之类的参数让后台线程空转
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 后台轮询线程的句柄
+pub struct DefinitionPoller {
+    stop: Arc<AtomicBool>,
+    interval: Duration,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DefinitionPoller {
+    /// 实际使用的轮询间隔 (已按 MIN_POLL_INTERVAL 修正)
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for DefinitionPoller {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn is_definition(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(ProtocolDefinition::supports_extension)
+}
+
+// 目录下的定义文件及其修改时间，按路径排序，保证加载顺序稳定
+fn scan(dir: &Path) -> ProtocolResult<Vec<(PathBuf, Option<SystemTime>)>> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| ProtocolError::CommonError(format!("{}: {}", dir.display(), e)))?;
+    let mut files: Vec<(PathBuf, Option<SystemTime>)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_definition(path))
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(version: &str, field_type: &str) -> String {
+        format!(
+            r#"{{"protocol":"demo-gas","version":"{}","upstream":[{{"title":"累计用量","length":4,"type":"{}"}}]}}"#,
+            version, field_type
+        )
+    }
+
+    // 修改时间精度可能只有秒级，直接把 mtime 往后拨
+    fn write(path: &Path, content: &str, age: u64) {
+        fs::write(path, content).unwrap();
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_reload_and_versions() {
+        let dir = std::env::temp_dir().join(format!("definition_store_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gas.json");
+        write(&path, &definition("1.0", "u32"), 100);
+        fs::write(dir.join("readme.txt"), "ignored").unwrap();
+
+        let store = DefinitionStore::open(&dir).unwrap();
+        assert_eq!(store.protocols(), ["demo-gas"]);
+        assert_eq!(store.get("demo-gas").unwrap().version(), "1.0");
+        assert!(store.poll().unwrap().is_empty());
+
+        // 解析失败时保留旧定义
+        write(&path, &definition("1.1", "u24"), 50);
+        let events = store.poll().unwrap();
+        assert!(matches!(events[0], ReloadEvent::Failed { .. }));
+        assert_eq!(store.get("demo-gas").unwrap().version(), "1.0");
+
+        write(&path, &definition("1.1", "i32"), 10);
+        let events = store.poll().unwrap();
+        assert_eq!(
+            events,
+            [ReloadEvent::Loaded {
+                protocol: "demo-gas".into(),
+                version: "1.1".into(),
+                revision: 2,
+            }]
+        );
+        assert_eq!(store.versions("demo-gas"), ["1.0", "1.1"]);
+        let old = store.get_version("demo-gas", "1.0").unwrap();
+        assert_eq!(
+            old.definition.upstream[0].field_type.as_deref(),
+            Some("u32")
+        );
+
+        fs::remove_file(&path).unwrap();
+        let events = store.poll().unwrap();
+        assert_eq!(
+            events,
+            [ReloadEvent::Removed {
+                protocol: "demo-gas".into()
+            }]
+        );
+        assert!(store.get("demo-gas").is_none());
+        assert!(store.get_version("demo-gas", "1.1").is_some());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_poll_every() {
+        let dir = std::env::temp_dir().join(format!("definition_poll_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gas.json");
+        write(&path, &definition("1.0", "u32"), 100);
+
+        let store = Arc::new(DefinitionStore::open(&dir).unwrap());
+        // 间隔为 0 时按最小间隔轮询，而不是空转
+        let poller = store.poll_every(Duration::ZERO);
+        assert_eq!(poller.interval(), MIN_POLL_INTERVAL);
+        assert_eq!(
+            store.poll_every(Duration::from_secs(5)).interval(),
+            Duration::from_secs(5)
+        );
+
+        write(&path, &definition("1.1", "i32"), 10);
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while store.get("demo-gas").unwrap().version() != "1.1" {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "poller did not reload"
+            );
+            thread::sleep(Duration::from_millis(20));
+        }
+        poller.stop();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_protocol_renamed_in_file() {
+        let dir = std::env::temp_dir().join(format!("definition_rename_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gas.json");
+        write(&path, &definition("1.0", "u32"), 100);
+        let store = DefinitionStore::open(&dir).unwrap();
+
+        write(
+            &path,
+            &definition("1.0", "u32").replace("demo-gas", "demo-water"),
+            50,
+        );
+        let events = store.poll().unwrap();
+        assert_eq!(
+            events,
+            [
+                ReloadEvent::Removed {
+                    protocol: "demo-gas".into()
+                },
+                ReloadEvent::Loaded {
+                    protocol: "demo-water".into(),
+                    version: "1.0".into(),
+                    revision: 2,
+                }
+            ]
+        );
+        assert_eq!(store.protocols(), ["demo-water"]);
+        assert!(store.get("demo-gas").is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_owner_removed_falls_back() {
+        let dir = std::env::temp_dir().join(format!("definition_owner_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        write(&dir.join("a.json"), &definition("1.0", "u32"), 100);
+        write(&dir.join("b.json"), &definition("2.0", "u32"), 100);
+        // 按路径顺序加载，b.json 后加载，为当前定义
+        let store = DefinitionStore::open(&dir).unwrap();
+        assert_eq!(store.get("demo-gas").unwrap().version(), "2.0");
+
+        // 重新发布 a.json 之前加载的定义
+        fs::remove_file(dir.join("b.json")).unwrap();
+        assert_eq!(
+            store.poll().unwrap(),
+            [ReloadEvent::Loaded {
+                protocol: "demo-gas".into(),
+                version: "1.0".into(),
+                revision: 1,
+            }]
+        );
+        assert_eq!(store.get("demo-gas").unwrap().path, dir.join("a.json"));
+
+        // c.json 覆盖了 1.0 的历史版本，删除后重新读取 a.json
+        write(&dir.join("c.json"), &definition("1.0", "i32"), 50);
+        store.poll().unwrap();
+        fs::remove_file(dir.join("c.json")).unwrap();
+        assert_eq!(
+            store.poll().unwrap(),
+            [ReloadEvent::Loaded {
+                protocol: "demo-gas".into(),
+                version: "1.0".into(),
+                revision: 4,
+            }]
+        );
+        let current = store.get("demo-gas").unwrap();
+        assert_eq!(current.path, dir.join("a.json"));
+        assert_eq!(
+            current.definition.upstream[0].field_type.as_deref(),
+            Some("u32")
+        );
+
+        fs::remove_file(dir.join("a.json")).unwrap();
+        assert_eq!(
+            store.poll().unwrap(),
+            [ReloadEvent::Removed {
+                protocol: "demo-gas".into()
+            }]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn test_watch() {
+        let dir = std::env::temp_dir().join(format!("definition_watch_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let store = Arc::new(DefinitionStore::open(&dir).unwrap());
+        let watcher = store.watch().unwrap();
+
+        let wait_for = |version: Option<&str>| {
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            while store.get("demo-gas").as_ref().map(|d| d.version()) != version {
+                assert!(
+                    std::time::Instant::now() < deadline,
+                    "watcher did not reload"
+                );
+                thread::sleep(Duration::from_millis(20));
+            }
+        };
+        let path = dir.join("gas.json");
+        fs::write(&path, definition("1.0", "u32")).unwrap();
+        wait_for(Some("1.0"));
+        // 修改时间不变也会因通知重新加载
+        fs::write(&path, definition("1.1", "u32")).unwrap();
+        wait_for(Some("1.1"));
+        fs::remove_file(&path).unwrap();
+        wait_for(None);
+
+        drop(watcher);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
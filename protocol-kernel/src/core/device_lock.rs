@@ -0,0 +1,152 @@
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use crate::bridge::async_handler::park_block_on;
+
+#[derive(Default)]
+struct LockState {
+    locked: bool,
+    waiters: Vec<Waker>,
+}
+
+// 设备 -> 锁。没有持有者和等待者时从表中移除，表的大小只与并发中的设备数有关
+static DEVICE_LOCKS: Lazy<Mutex<HashMap<String, Arc<Mutex<LockState>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn lock_entry(key: &str) -> Arc<Mutex<LockState>> {
+    let mut locks = DEVICE_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    locks.entry(key.to_string()).or_default().clone()
+}
+
+// 表本身 + 调用方各持有一份时，说明已经没有其他人在用这把锁
+fn release_entry(key: &str, entry: &Arc<Mutex<LockState>>) {
+    let mut locks = DEVICE_LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    let idle = !entry.lock().unwrap_or_else(|e| e.into_inner()).locked;
+    if idle && Arc::strong_count(entry) == 2 {
+        locks.remove(key);
+    }
+}
+
+/// 等待设备锁的 future，完成时返回 DeviceLockGuard
+pub struct DeviceLockFuture {
+    key: String,
+    entry: Option<Arc<Mutex<LockState>>>,
+}
+
+impl DeviceLockFuture {
+    pub(crate) fn new(key: String) -> Self {
+        let entry = lock_entry(&key);
+        Self {
+            key,
+            entry: Some(entry),
+        }
+    }
+}
+
+impl Future for DeviceLockFuture {
+    type Output = DeviceLockGuard;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let entry = self.entry.as_ref().expect("polled after completion");
+        let mut state = entry.lock().unwrap_or_else(|e| e.into_inner());
+        if state.locked {
+            if !state.waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                state.waiters.push(cx.waker().clone());
+            }
+            return Poll::Pending;
+        }
+        state.locked = true;
+        drop(state);
+        let entry = self.entry.take().expect("polled after completion");
+        Poll::Ready(DeviceLockGuard {
+            key: std::mem::take(&mut self.key),
+            entry,
+        })
+    }
+}
+
+impl Drop for DeviceLockFuture {
+    // 等待中被取消 (如超时) 时清理
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            release_entry(&self.key, &entry);
+        }
+    }
+}
+
+/// 设备锁，drop 时释放并唤醒等待者
+pub struct DeviceLockGuard {
+    key: String,
+    entry: Arc<Mutex<LockState>>,
+}
+
+impl Drop for DeviceLockGuard {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut state = self.entry.lock().unwrap_or_else(|e| e.into_inner());
+            state.locked = false;
+            std::mem::take(&mut state.waiters)
+        };
+        // 全部唤醒，由它们重新竞争。被取消的等待者不会导致其他人永远等下去
+        for waker in waiters {
+            waker.wake();
+        }
+        release_entry(&self.key, &self.entry);
+    }
+}
+
+pub(crate) fn with_lock<F, R>(key: String, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let _guard = park_block_on(DeviceLockFuture::new(key));
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+        time::Duration,
+    };
+
+    #[test]
+    fn test_serialized() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let running = running.clone();
+                thread::spawn(move || {
+                    with_lock("lock-test-01".to_string(), || {
+                        assert_eq!(running.fetch_add(1, Ordering::SeqCst), 0);
+                        thread::sleep(Duration::from_millis(10));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let locks = DEVICE_LOCKS.lock().unwrap();
+        assert!(!locks.contains_key("lock-test-01"));
+    }
+
+    #[test]
+    fn test_cancelled_waiter() {
+        let guard = park_block_on(DeviceLockFuture::new("lock-test-02".to_string()));
+        let waiting = DeviceLockFuture::new("lock-test-02".to_string());
+        drop(waiting);
+        drop(guard);
+        with_lock("lock-test-02".to_string(), || {});
+        assert!(!DEVICE_LOCKS.lock().unwrap().contains_key("lock-test-02"));
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use crate::{
+    core::{
+        config::ProtocolConfig,
+        parts::{
+            raw_capsule::RawCapsule,
+            traits::{Cmd, CmdTable},
+        },
+        quarantine::{quarantine, UnknownFrame},
+    },
+    ProtocolError, ProtocolResult,
+};
+
+/// 按 ProtocolConfig 从上行帧中取出命令码，找到对应的 Cmd，
+/// 生成已设置好 cmd 的 RawCapsule。替代各协议手写的命令码 match
+pub struct CmdDispatcher<T: Cmd + Clone> {
+    config: ProtocolConfig,
+    // 命令码 (hex，大写) -> 命令
+    table: HashMap<String, T>,
+}
+
+impl<T: Cmd + Clone + 'static> CmdDispatcher<T> {
+    pub fn new(config: ProtocolConfig) -> Self {
+        Self {
+            config,
+            table: HashMap::new(),
+        }
+    }
+
+    /// 由命令表生成，只登记可上行的命令。上下行同码时以先出现的为准
+    pub fn from_table(config: ProtocolConfig) -> Self
+    where
+        T: CmdTable,
+    {
+        let mut dispatcher = Self::new(config);
+        for cmd in T::variants() {
+            if cmd.direction().is_upstream() {
+                dispatcher
+                    .table
+                    .entry(cmd.code().to_uppercase())
+                    .or_insert(cmd);
+            }
+        }
+        dispatcher
+    }
+
+    /// 登记命令，同码的已有命令会被替换
+    pub fn register(mut self, cmd: T) -> Self {
+        self.table.insert(cmd.code().to_uppercase(), cmd);
+        self
+    }
+
+    pub fn config(&self) -> &ProtocolConfig {
+        &self.config
+    }
+
+    /// 仅识别命令。帧校验不通过或命令码未登记时，帧会被隔离 (见 quarantine)
+    pub fn cmd_of(&self, frame: &[u8]) -> ProtocolResult<T> {
+        let result = self.config.cmd_code_of(frame).and_then(|code| {
+            self.table.get(&code).cloned().ok_or_else(|| {
+                ProtocolError::ValidationFailed(format!(
+                    "unknown cmd code {} for protocol {}",
+                    code, self.config.name
+                ))
+            })
+        });
+        if let Err(e) = &result {
+            quarantine(
+                UnknownFrame::new(frame, &e.to_string())
+                    .with_protocol(&self.config.name)
+                    .probe(&self.config, frame),
+            );
+        }
+        result
+    }
+
+    /// 识别命令并生成上行 RawCapsule
+    pub fn classify(&self, frame: &[u8]) -> ProtocolResult<RawCapsule<T>> {
+        let cmd = self.cmd_of(frame)?;
+        let mut capsule = RawCapsule::new_upstream(frame);
+        capsule.set_cmd(cmd);
+        Ok(capsule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd_table;
+
+    cmd_table! {
+        enum MeterCmd {
+            Report => ("02", "数据上报", Upstream, None, DataReport),
+            ReportAck => ("02", "上报应答", Downstream, Write, None),
+            Heartbeat => ("0F", "心跳", Both, None, HeartBeat),
+        }
+    }
+
+    fn config() -> ProtocolConfig {
+        ProtocolConfig::new("demo", 1, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16])
+    }
+
+    #[test]
+    fn test_classify() {
+        let dispatcher = CmdDispatcher::<MeterCmd>::from_table(config());
+        let capsule = dispatcher.classify(&[0x68, 0x02, 0xAA, 0x16]).unwrap();
+        assert_eq!(capsule.cmd(), Some(&MeterCmd::Report));
+        assert_eq!(capsule.hex(), "6802AA16");
+        assert_eq!(
+            dispatcher.cmd_of(&[0x68, 0x0f, 0x16]).unwrap(),
+            MeterCmd::Heartbeat
+        );
+        assert!(matches!(
+            dispatcher.cmd_of(&[0x68, 0x7F, 0x16]),
+            Err(ProtocolError::ValidationFailed(_))
+        ));
+        assert!(dispatcher.cmd_of(&[0x69, 0x02, 0x16]).is_err());
+        assert!(matches!(
+            dispatcher.cmd_of(&[0x68]),
+            Err(ProtocolError::InputTooShort { .. })
+        ));
+    }
+
+    #[test]
+    fn test_quarantine_unknown_cmd() {
+        use crate::core::quarantine::{
+            clear_quarantine_sink, set_quarantine_sink, MemoryQuarantine,
+        };
+
+        let sink = MemoryQuarantine::new(16);
+        set_quarantine_sink(sink.clone());
+        let dispatcher = CmdDispatcher::<MeterCmd>::from_table(config());
+        assert!(dispatcher.classify(&[0x68, 0x7E, 0xD1, 0x16]).is_err());
+        clear_quarantine_sink();
+        let frame = sink
+            .frames()
+            .into_iter()
+            .find(|f| f.hex == "687ED116")
+            .unwrap();
+        assert_eq!(frame.protocol.as_deref(), Some("demo"));
+        assert_eq!(frame.candidates[0].cmd_code.as_deref(), Some("7E"));
+        assert!(frame.reason.contains("unknown cmd code 7E"));
+    }
+}
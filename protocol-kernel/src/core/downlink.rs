@@ -0,0 +1,301 @@
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::bridge::trace;
+
+/// 待下发的命令 (已编码好的帧)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownlinkCommand {
+    id: u64,
+    pub cmd_code: String,
+    pub frame: Vec<u8>,
+    // 数值越大越先下发，同优先级先进先出
+    pub priority: u8,
+    // 未确认时的重发次数，总下发次数为 retries + 1
+    pub retries: u32,
+    pub attempts: u32,
+    pub expires_at: Option<Instant>,
+}
+
+impl DownlinkCommand {
+    pub fn new(cmd_code: &str, frame: &[u8]) -> Self {
+        Self {
+            id: 0,
+            cmd_code: cmd_code.into(),
+            frame: frame.to_vec(),
+            priority: 0,
+            retries: 0,
+            attempts: 0,
+            expires_at: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// 入队后多久过期
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.expires_at = Some(Instant::now() + ttl);
+        self
+    }
+
+    /// 入队时分配的 id，用于 ack
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|t| t <= now)
+    }
+}
+
+/// 命令未下发成功就被移出队列的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    Expired,
+    RetriesExhausted,
+}
+
+type DropListener = Arc<dyn Fn(&str, &DownlinkCommand, DropReason) + Send + Sync>;
+
+static QUEUES: Lazy<Mutex<HashMap<String, Vec<DownlinkCommand>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+static DROP_LISTENERS: Lazy<RwLock<Vec<DropListener>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 按设备暂存的下行命令 (存储转发)。电池表只在上报后的短窗口内接收下行，
+/// 平台下发的命令先入队，收到该设备的上行时再通过 next 取出
+pub struct DownlinkQueue {}
+
+impl DownlinkQueue {
+    /// 入队，返回命令 id
+    pub fn enqueue(unique: &str, mut command: DownlinkCommand) -> u64 {
+        command.id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        command.attempts = 0;
+        let id = command.id;
+        let mut queues = QUEUES.lock().unwrap_or_else(|e| e.into_inner());
+        queues.entry(unique.to_string()).or_default().push(command);
+        id
+    }
+
+    /// 上行窗口到来时取出应下发的命令并记一次下发。命令在 ack 之前保留在队列中，
+    /// 下次窗口会重发，直到超过重发次数或过期
+    pub fn next(unique: &str) -> Option<DownlinkCommand> {
+        let now = Instant::now();
+        let mut dropped = Vec::new();
+        let next = {
+            let mut queues = QUEUES.lock().unwrap_or_else(|e| e.into_inner());
+            let queue = queues.get_mut(unique)?;
+            remove_dropped(queue, now, |command, reason| {
+                dropped.push((command, reason))
+            });
+            // 优先级最高、其次最早入队 (id 最小) 的命令
+            let next = queue
+                .iter_mut()
+                .max_by(|a, b| a.priority.cmp(&b.priority).then(b.id.cmp(&a.id)))
+                .map(|c| {
+                    c.attempts += 1;
+                    c.clone()
+                });
+            if queue.is_empty() {
+                queues.remove(unique);
+            }
+            next
+        };
+        // 回调在锁外执行，回调中可以再次入队
+        for (command, reason) in dropped {
+            notify_dropped(unique, &command, reason);
+        }
+        next
+    }
+
+    /// 清理所有设备队列中过期或重发次数用尽的命令并触发 on_drop 回调，返回清理的数量。
+    /// 设备长期不上线时 next 不会被调用，需要宿主定期执行以便及时把命令标记为失败
+    pub fn purge_expired() -> usize {
+        let now = Instant::now();
+        let mut dropped = Vec::new();
+        {
+            let mut queues = QUEUES.lock().unwrap_or_else(|e| e.into_inner());
+            queues.retain(|unique, queue| {
+                remove_dropped(queue, now, |command, reason| {
+                    dropped.push((unique.clone(), command, reason))
+                });
+                !queue.is_empty()
+            });
+        }
+        for (unique, command, reason) in &dropped {
+            notify_dropped(unique, command, *reason);
+        }
+        dropped.len()
+    }
+
+    /// 表端已确认，移出队列
+    pub fn ack(unique: &str, id: u64) -> bool {
+        let mut queues = QUEUES.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(queue) = queues.get_mut(unique) else {
+            return false;
+        };
+        let before = queue.len();
+        queue.retain(|c| c.id != id);
+        let removed = queue.len() < before;
+        if queue.is_empty() {
+            queues.remove(unique);
+        }
+        removed
+    }
+
+    pub fn pending(unique: &str) -> usize {
+        let queues = QUEUES.lock().unwrap_or_else(|e| e.into_inner());
+        queues.get(unique).map_or(0, |q| q.len())
+    }
+
+    /// 清空设备的队列，返回被清除的命令 (不触发回调)
+    pub fn clear(unique: &str) -> Vec<DownlinkCommand> {
+        let mut queues = QUEUES.lock().unwrap_or_else(|e| e.into_inner());
+        queues.remove(unique).unwrap_or_default()
+    }
+
+    /// 注册命令过期或重发次数用尽时的回调，平台可据此把命令标记为失败
+    pub fn on_drop<F>(listener: F)
+    where
+        F: Fn(&str, &DownlinkCommand, DropReason) + Send + Sync + 'static,
+    {
+        let mut guard = DROP_LISTENERS.write().unwrap_or_else(|e| e.into_inner());
+        guard.push(Arc::new(listener));
+    }
+}
+
+// 移出过期或重发次数用尽的命令，交给 on_dropped 收集
+fn remove_dropped<F>(queue: &mut Vec<DownlinkCommand>, now: Instant, mut on_dropped: F)
+where
+    F: FnMut(DownlinkCommand, DropReason),
+{
+    queue.retain(|c| {
+        let reason = if c.is_expired(now) {
+            DropReason::Expired
+        } else if c.attempts > c.retries {
+            DropReason::RetriesExhausted
+        } else {
+            return true;
+        };
+        on_dropped(c.clone(), reason);
+        false
+    });
+}
+
+fn notify_dropped(unique: &str, command: &DownlinkCommand, reason: DropReason) {
+    let listeners = DROP_LISTENERS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    for listener in listeners {
+        if panic::catch_unwind(AssertUnwindSafe(|| listener(unique, command, reason))).is_err() {
+            eprintln!(
+                "[WARN] {}Downlink drop listener panicked for {} cmd {}",
+                trace::log_prefix(),
+                unique,
+                command.cmd_code
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_and_retry() {
+        let unique = "downlink-test-01";
+        let low = DownlinkQueue::enqueue(unique, DownlinkCommand::new("25", &[0x68, 0x25]));
+        let high = DownlinkQueue::enqueue(
+            unique,
+            DownlinkCommand::new("3A", &[0x68, 0x3A])
+                .with_priority(9)
+                .with_retries(1),
+        );
+        assert_eq!(DownlinkQueue::next(unique).unwrap().id(), high);
+        // 未确认，下个窗口重发
+        let again = DownlinkQueue::next(unique).unwrap();
+        assert_eq!((again.id(), again.attempts), (high, 2));
+        // 重发次数用尽，轮到低优先级的命令
+        assert_eq!(DownlinkQueue::next(unique).unwrap().id(), low);
+        assert!(DownlinkQueue::ack(unique, low));
+        assert_eq!(DownlinkQueue::pending(unique), 0);
+        assert!(DownlinkQueue::next(unique).is_none());
+    }
+
+    #[test]
+    fn test_expiry_callback() {
+        let unique = "downlink-test-02";
+        let expired = Arc::new(Mutex::new(Vec::new()));
+        let sink = expired.clone();
+        DownlinkQueue::on_drop(move |u, c, reason| {
+            if u == unique {
+                sink.lock().unwrap().push((c.cmd_code.clone(), reason));
+            }
+        });
+        DownlinkQueue::enqueue(
+            unique,
+            DownlinkCommand::new("25", &[0x68]).with_ttl(Duration::ZERO),
+        );
+        assert!(DownlinkQueue::next(unique).is_none());
+        assert_eq!(
+            *expired.lock().unwrap(),
+            vec![("25".to_string(), DropReason::Expired)]
+        );
+    }
+
+    #[test]
+    fn test_purge_expired_without_uplink() {
+        let unique = "downlink-test-03";
+        let expired = Arc::new(Mutex::new(Vec::new()));
+        let sink = expired.clone();
+        DownlinkQueue::on_drop(move |u, c, reason| {
+            if u == unique {
+                sink.lock().unwrap().push((c.cmd_code.clone(), reason));
+            }
+        });
+        DownlinkQueue::enqueue(
+            unique,
+            DownlinkCommand::new("25", &[0x68]).with_ttl(Duration::ZERO),
+        );
+        let kept = DownlinkQueue::enqueue(
+            unique,
+            DownlinkCommand::new("3A", &[0x68]).with_ttl(Duration::from_secs(3600)),
+        );
+        // 设备一直没有上行，靠定期清理触发回调
+        assert!(DownlinkQueue::purge_expired() >= 1);
+        assert_eq!(
+            *expired.lock().unwrap(),
+            vec![("25".to_string(), DropReason::Expired)]
+        );
+        assert_eq!(DownlinkQueue::pending(unique), 1);
+
+        assert!(DownlinkQueue::ack(unique, kept));
+        DownlinkQueue::enqueue(
+            unique,
+            DownlinkCommand::new("40", &[0x68]).with_ttl(Duration::ZERO),
+        );
+        DownlinkQueue::purge_expired();
+        // 清空的队列不再留在全局表里
+        assert!(!QUEUES.lock().unwrap().contains_key(unique));
+    }
+}
@@ -0,0 +1,510 @@
+//! 声明式协议定义。简单的协议变体可以用配置文件描述字段，无需重新编译:
+//!
+//! ```toml
+//! protocol = "demo-gas"
+//! version = "1.0"
+//!
+//! [[upstream]]
+//! title = "累计用量"
+//! length = 4
+//! type = "u32"
+//! scale = 0.01
+//!
+//! [[upstream]]
+//! title = "阀门状态"
+//! length = 1
+//! enum_values = [{ value = "00", title = "开阀" }, { value = "01", title = "关阀" }]
+//!
+//! [[downstream]]
+//! code = "price"
+//! title = "单价"
+//! length = 4
+//! type = "u32"
+//! scale = 0.01
+//! cmd_code = "31"
+//! ```
+//!
+//! JSON 总是可用；TOML 与 YAML 分别需要开启 `toml`、`yaml` feature，字段与 JSON 相同。
+use std::{fmt::Display, path::Path};
+
+use protocol_base::{ProtocolError, ProtocolResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        parts::traits::{AutoDecoding, AutoDecodingParam, AutoEncoding, AutoEncodingParam},
+        type_converter::{FieldType, TryFromBytes},
+    },
+    hex_util,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ProtocolDefinition {
+    pub protocol: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub upstream: Vec<FieldDefinition>,
+    #[serde(default)]
+    pub downstream: Vec<FieldDefinition>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldDefinition {
+    pub title: String,
+    // 下行参数的 key，上行字段可以不填
+    #[serde(default)]
+    pub code: String,
+    // 字节长度，必须大于 0 (定义文件不支持变长字段)
+    pub length: usize,
+    // u8/u16/u32/u64/i8/i16/i32/i64/float/double/ascii/bcd
+    #[serde(default, rename = "type")]
+    pub field_type: Option<String>,
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub swap: bool,
+    #[serde(default)]
+    pub enum_values: Vec<EnumValue>,
+    // 比较模式的目标 hex，如 CRC、帧尾
+    #[serde(default)]
+    pub compare: Option<String>,
+    #[serde(default)]
+    pub cmd_code: String,
+    #[serde(default)]
+    pub default_value: String,
+    #[serde(default)]
+    pub default_hex: String,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnumValue {
+    // 原始值的 hex，长度与字段一致
+    pub value: String,
+    pub title: String,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// 枚举键，按 hex 比较，长度不受限于固定宽度的整数类型
+#[derive(Debug, Clone, PartialEq)]
+pub struct HexKey(String);
+
+impl Display for HexKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl TryFromBytes for HexKey {
+    fn try_from_bytes(bytes: &[u8], swap: bool) -> ProtocolResult<Self> {
+        let mut bytes = bytes.to_vec();
+        if swap {
+            bytes.reverse();
+        }
+        Ok(HexKey(hex_util::bytes_to_hex(&bytes)?))
+    }
+}
+
+impl ProtocolDefinition {
+    pub fn from_json(s: &str) -> ProtocolResult<Self> {
+        let definition: Self =
+            serde_json::from_str(s).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(s: &str) -> ProtocolResult<Self> {
+        let definition: Self =
+            toml::from_str(s).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(s: &str) -> ProtocolResult<Self> {
+        let definition: Self =
+            serde_yaml::from_str(s).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// 按扩展名 (.json/.toml/.yaml/.yml) 加载
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ProtocolResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ProtocolError::CommonError(format!("{}: {}", path.display(), e)))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(&content),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(&content),
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => Self::from_yaml(&content),
+            other => Err(ProtocolError::UnsupportedMode(format!(
+                "protocol definition format '{}'",
+                other.unwrap_or_default()
+            ))),
+        }
+    }
+
+    /// 当前构建能否加载该扩展名的定义文件
+    pub fn supports_extension(extension: &str) -> bool {
+        extension == "json"
+            || (cfg!(feature = "toml") && extension == "toml")
+            || (cfg!(feature = "yaml") && matches!(extension, "yaml" | "yml"))
+    }
+
+    /// 加载时检查所有字段，避免到解码时才发现配置错误
+    pub fn validate(&self) -> ProtocolResult<()> {
+        for field in self.upstream.iter().chain(&self.downstream) {
+            if field.length == 0 {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "[{}] field '{}' requires a length greater than 0",
+                    self.protocol, field.title
+                )));
+            }
+            if field.scale == 0.0 || !field.scale.is_finite() {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "[{}] field '{}' has invalid scale {}",
+                    self.protocol, field.title, field.scale
+                )));
+            }
+            if let Some(width) = fixed_width(&field.parsed_type()?) {
+                if width != field.length {
+                    return Err(ProtocolError::ValidationFailed(format!(
+                        "[{}] field '{}' of type {} must be {} bytes, got length {}",
+                        self.protocol,
+                        field.title,
+                        field.field_type.as_deref().unwrap_or_default(),
+                        width,
+                        field.length
+                    )));
+                }
+            }
+            for e in &field.enum_values {
+                let bytes = hex_util::hex_to_bytes(&e.value)?;
+                if bytes.len() != field.length {
+                    return Err(ProtocolError::ValidationFailed(format!(
+                        "[{}] enum value '{}' of field '{}' must be {} bytes",
+                        self.protocol, e.value, field.title, field.length
+                    )));
+                }
+            }
+        }
+        for field in &self.upstream {
+            field.parsed_type()?;
+            field.parsed_compare()?;
+            if field.field_type.is_none() && field.enum_values.is_empty() && field.compare.is_none()
+            {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "[{}] upstream field '{}' requires one of type, enum_values, compare",
+                    self.protocol, field.title
+                )));
+            }
+        }
+        for field in &self.downstream {
+            field.parsed_type()?;
+            if field.code.is_empty() {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "[{}] downstream field '{}' requires a code",
+                    self.protocol, field.title
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// cmd_code 对应的上行解码表 (不区分大小写)。未声明 cmd_code 的字段视为通用字段
+    pub fn decoding(&self, cmd_code: &str) -> DecodingTable {
+        DecodingTable(select(&self.upstream, cmd_code))
+    }
+
+    pub fn encoding(&self, cmd_code: &str) -> EncodingTable {
+        EncodingTable(select(&self.downstream, cmd_code))
+    }
+}
+
+fn select(fields: &[FieldDefinition], cmd_code: &str) -> Vec<FieldDefinition> {
+    fields
+        .iter()
+        .filter(|f| f.cmd_code.is_empty() || f.cmd_code.eq_ignore_ascii_case(cmd_code))
+        .cloned()
+        .collect()
+}
+
+// 定长类型的字节数，变长类型 (ascii/bcd 等) 返回 None
+fn fixed_width(field_type: &FieldType) -> Option<usize> {
+    match field_type {
+        FieldType::UnsignedU8(_) | FieldType::SignedI8(_) => Some(1),
+        FieldType::UnsignedU16(_) | FieldType::SignedI16(_) => Some(2),
+        FieldType::UnsignedU32(_) | FieldType::SignedI32(_) | FieldType::Float => Some(4),
+        FieldType::UnsignedU64(_) | FieldType::SignedI64(_) | FieldType::Double => Some(8),
+        _ => None,
+    }
+}
+
+impl FieldDefinition {
+    fn parsed_type(&self) -> ProtocolResult<FieldType> {
+        let scale = self.scale;
+        let ft = match self.field_type.as_deref().unwrap_or("") {
+            "" => FieldType::Empty,
+            "u8" => FieldType::UnsignedU8(scale),
+            "u16" => FieldType::UnsignedU16(scale),
+            "u32" => FieldType::UnsignedU32(scale),
+            "u64" => FieldType::UnsignedU64(scale),
+            "i8" => FieldType::SignedI8(scale),
+            "i16" => FieldType::SignedI16(scale),
+            "i32" => FieldType::SignedI32(scale),
+            "i64" => FieldType::SignedI64(scale),
+            "float" => FieldType::Float,
+            "double" => FieldType::Double,
+            "ascii" => FieldType::Ascii,
+            "bcd" | "string" => FieldType::StringOrBCD,
+            other => {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "field '{}' has unknown type '{}'",
+                    self.title, other
+                )))
+            }
+        };
+        Ok(ft)
+    }
+
+    fn parsed_compare(&self) -> ProtocolResult<Vec<u8>> {
+        match &self.compare {
+            Some(hex) => hex_util::hex_to_bytes(hex),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+impl AutoDecodingParam<HexKey> for FieldDefinition {
+    fn byte_length(&self) -> usize {
+        self.length
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn swap(&self) -> bool {
+        self.swap
+    }
+
+    fn cmd_code(&self) -> String {
+        self.cmd_code.clone()
+    }
+
+    fn field_type(&self) -> FieldType {
+        self.parsed_type().unwrap_or(FieldType::Empty)
+    }
+
+    fn compare_target(&self) -> Vec<u8> {
+        self.parsed_compare().unwrap_or_default()
+    }
+
+    fn enum_values(&self) -> Vec<(HexKey, String)> {
+        self.enum_values
+            .iter()
+            .map(|e| (HexKey(e.value.to_uppercase()), e.title.clone()))
+            .collect()
+    }
+}
+
+impl AutoEncodingParam for FieldDefinition {
+    fn code(&self) -> String {
+        self.code.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn byte_length(&self) -> usize {
+        self.length
+    }
+
+    fn cmd_code(&self) -> String {
+        self.cmd_code.clone()
+    }
+
+    // 没有声明类型的下行参数按 hex 原样写入
+    fn field_type(&self) -> FieldType {
+        match self.parsed_type() {
+            Ok(FieldType::Empty) | Err(_) => FieldType::StringOrBCD,
+            Ok(ft) => ft,
+        }
+    }
+
+    fn default_value(&self) -> String {
+        self.default_value.clone()
+    }
+
+    fn default_hex(&self) -> String {
+        self.default_hex.clone()
+    }
+
+    fn swap(&self) -> bool {
+        self.swap
+    }
+
+    fn required(&self) -> bool {
+        self.required
+    }
+}
+
+/// 由配置生成的上行解码表
+#[derive(Debug, Clone)]
+pub struct DecodingTable(Vec<FieldDefinition>);
+
+impl AutoDecoding<FieldDefinition, HexKey> for DecodingTable {
+    fn variants(&self) -> Vec<FieldDefinition> {
+        self.0.clone()
+    }
+}
+
+/// 由配置生成的下行参数表
+#[derive(Debug, Clone)]
+pub struct EncodingTable(Vec<FieldDefinition>);
+
+impl AutoEncoding<FieldDefinition> for EncodingTable {
+    fn variants(&self) -> Vec<FieldDefinition> {
+        self.0.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{Reader, Writer};
+
+    const DEFINITION: &str = r#"{
+        "protocol": "demo-gas",
+        "upstream": [
+            {"title": "累计用量", "length": 4, "type": "u32", "scale": 0.01},
+            {"title": "阀门状态", "length": 1,
+             "enum_values": [{"value": "00", "title": "开阀"}, {"value": "01", "title": "关阀"}]},
+            {"title": "帧尾", "length": 1, "compare": "16"}
+        ],
+        "downstream": [
+            {"code": "price", "title": "单价", "length": 4, "type": "u32", "scale": 0.01, "cmd_code": "3A"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_json_definition_round_trip() {
+        let definition = ProtocolDefinition::from_json(DEFINITION).unwrap();
+
+        let bytes = hex_util::hex_to_bytes("000004D20116").unwrap();
+        let mut reader = Reader::new(&bytes);
+        definition.decoding("").auto_process(&mut reader).unwrap();
+        let fields = reader.to_report_fields().unwrap();
+        assert_eq!(fields[0].value, "12.34");
+        assert_eq!(fields[1].value, "关阀");
+
+        let params = HashMap::from([("price".to_string(), "2.5".to_string())]);
+        let mut writer = Writer::new();
+        // cmd_code 不区分大小写
+        let len = definition
+            .encoding("3a")
+            .auto_process(&params, &mut writer)
+            .unwrap();
+        assert_eq!(len, 4);
+        assert_eq!(writer.full_hex().unwrap(), "000000FA");
+        assert!(definition.encoding("31").variants().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_definition() {
+        let bad = r#"{"protocol":"x","upstream":[{"title":"a","length":1}]}"#;
+        assert!(ProtocolDefinition::from_json(bad).is_err());
+        let bad = r#"{"protocol":"x","upstream":[{"title":"a","length":1,"type":"u24"}]}"#;
+        assert!(ProtocolDefinition::from_json(bad).is_err());
+        let bad = r#"{"protocol":"x","upstream":[{"title":"a","length":0,"type":"ascii"}]}"#;
+        assert!(ProtocolDefinition::from_json(bad).is_err());
+        let bad =
+            r#"{"protocol":"x","downstream":[{"code":"a","title":"a","length":0,"type":"u8"}]}"#;
+        assert!(ProtocolDefinition::from_json(bad).is_err());
+        // 类型宽度与 length 不符
+        let bad = r#"{"protocol":"x","upstream":[{"title":"a","length":2,"type":"u32"}]}"#;
+        assert!(ProtocolDefinition::from_json(bad).is_err());
+        let bad = r#"{"protocol":"x","downstream":[{"code":"a","title":"a","length":4,"type":"double"}]}"#;
+        assert!(ProtocolDefinition::from_json(bad).is_err());
+        // 枚举值的 hex 长度与 length 不符
+        let bad = r#"{"protocol":"x","upstream":[{"title":"a","length":1,
+            "enum_values":[{"value":"0001","title":"b"}]}]}"#;
+        assert!(ProtocolDefinition::from_json(bad).is_err());
+        let bad = r#"{"protocol":"x","upstream":[{"title":"a","length":1,
+            "enum_values":[{"value":"ZZ","title":"b"}]}]}"#;
+        assert!(ProtocolDefinition::from_json(bad).is_err());
+        // scale 为 0
+        let bad =
+            r#"{"protocol":"x","upstream":[{"title":"a","length":4,"type":"u32","scale":0}]}"#;
+        assert!(ProtocolDefinition::from_json(bad).is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_definition() {
+        let toml = r#"
+protocol = "demo-gas"
+
+[[upstream]]
+title = "阀门状态"
+length = 1
+enum_values = [{ value = "00", title = "开阀" }]
+
+[[downstream]]
+code = "price"
+title = "单价"
+length = 4
+type = "u32"
+scale = 0.01
+"#;
+        let definition = ProtocolDefinition::from_toml(toml).unwrap();
+        assert_eq!(definition.upstream[0].enum_values[0].title, "开阀");
+        assert_eq!(definition.downstream[0].scale, 0.01);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml_definition() {
+        let yaml = r#"
+protocol: demo-gas
+upstream:
+  - title: 阀门状态
+    length: 1
+    enum_values:
+      - { value: "00", title: 开阀 }
+downstream:
+  - code: price
+    title: 单价
+    length: 4
+    type: u32
+    scale: 0.01
+"#;
+        let definition = ProtocolDefinition::from_yaml(yaml).unwrap();
+        assert_eq!(
+            definition,
+            ProtocolDefinition::from_json(&serde_json::to_string(&definition).unwrap()).unwrap()
+        );
+        assert_eq!(definition.upstream[0].enum_values[0].title, "开阀");
+        assert_eq!(definition.downstream[0].scale, 0.01);
+
+        let path = std::env::temp_dir().join(format!("dsl-test-{}.yml", std::process::id()));
+        std::fs::write(&path, yaml).unwrap();
+        assert_eq!(ProtocolDefinition::from_file(&path).unwrap(), definition);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,223 @@
+//! 两阶段解码。外层 (Envelope): 帧校验、地址/控制域/命令码、取出数据单元并按密钥槽解密；
+//! 内层 (Payload): 对解密后的数据单元按命令解码字段。
+//! 加密协议在两阶段之间完成解密，字段解码拿到的是明文，不需要在 Reader 里做特殊处理:
+//!
+//! ```ignore
+//! let envelope = EnvelopeDecoder::new(config).with_crc(CrcType::Crc16Modbus, 1).open_with(frame, &ctx)?;
+//! let payload = envelope.decode_payload(request.decode_mode(), |reader| Report.auto_process(reader))?;
+//! ```
+use std::borrow::Cow;
+
+use protocol_base::{definitions::defi::CrcType, ProtocolResult};
+
+use crate::core::{
+    config::ProtocolConfig,
+    context::DecodeContext,
+    parts::{diagnostic::Diagnostic, rawfield::Rawfield},
+    reader::Reader,
+    validation::{validate_frame, validate_frame_with_crc},
+    DecodeMode,
+};
+
+/// 外层解码器，按协议配置校验帧并取出数据单元
+#[derive(Debug, Clone)]
+pub struct EnvelopeDecoder {
+    config: ProtocolConfig,
+    // CRC 算法及参与计算的起始位置，None 时不校验 CRC
+    crc: Option<(CrcType, usize)>,
+}
+
+impl EnvelopeDecoder {
+    pub fn new(config: ProtocolConfig) -> Self {
+        Self { config, crc: None }
+    }
+
+    /// 校验 CRC (crc_len 需为 2)
+    pub fn with_crc(mut self, crc_type: CrcType, crc_start: usize) -> Self {
+        self.crc = Some((crc_type, crc_start));
+        self
+    }
+
+    pub fn config(&self) -> &ProtocolConfig {
+        &self.config
+    }
+
+    /// 校验并拆开外层，数据单元原样作为 payload (不加密的协议)
+    pub fn open<'a>(&self, frame: &'a [u8]) -> ProtocolResult<Envelope<'a>> {
+        let report = match self.crc {
+            Some((crc_type, crc_start)) => {
+                validate_frame_with_crc(frame, &self.config, crc_type, crc_start)
+            }
+            None => validate_frame(frame, &self.config),
+        };
+        report.into_result()?;
+        Ok(Envelope {
+            protocol: self.config.name.clone(),
+            frame,
+            cmd_code: self.config.cmd_code_of(frame)?,
+            address: self.config.address_of(frame)?,
+            control: self.config.control_of(frame)?,
+            payload: Cow::Borrowed(self.config.data_unit(frame)?),
+            decrypted: false,
+        })
+    }
+
+    /// 同 open，数据单元按 ctx 中设备的密钥槽解密，不加密 (cipher_slot < 0) 时不复制
+    pub fn open_with<'a>(
+        &self,
+        frame: &'a [u8],
+        ctx: &DecodeContext,
+    ) -> ProtocolResult<Envelope<'a>> {
+        let mut envelope = self.open(frame)?;
+        if ctx.cipher()?.is_some() {
+            envelope.payload = Cow::Owned(ctx.decrypt(&envelope.payload)?);
+            envelope.decrypted = true;
+        }
+        Ok(envelope)
+    }
+}
+
+/// 外层解码结果
+#[derive(Debug, Clone)]
+pub struct Envelope<'a> {
+    pub protocol: String,
+    // 完整的原始帧
+    pub frame: &'a [u8],
+    pub cmd_code: String,
+    pub address: Option<String>,
+    pub control: Option<u8>,
+    // 数据单元，解密后为明文
+    payload: Cow<'a, [u8]>,
+    decrypted: bool,
+}
+
+impl<'a> Envelope<'a> {
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    pub fn is_decrypted(&self) -> bool {
+        self.decrypted
+    }
+
+    /// 自行解密时替换 payload
+    pub fn replace_payload(&mut self, payload: Vec<u8>) {
+        self.payload = Cow::Owned(payload);
+        self.decrypted = true;
+    }
+
+    /// 内层解码: decode 对 payload 读取字段，剩余未读的字节记一条警告
+    pub fn decode_payload<F>(&self, mode: DecodeMode, decode: F) -> ProtocolResult<Payload>
+    where
+        F: FnOnce(&mut Reader) -> ProtocolResult<()>,
+    {
+        let mut reader = Reader::new(&self.payload).with_decode_mode(mode);
+        decode(&mut reader)?;
+        if reader.remaining_len() > 0 {
+            let message = format!("{} payload bytes left undecoded", reader.remaining_len());
+            reader.warn("payload_trailing", &message);
+        }
+        Ok(Payload {
+            cmd_code: self.cmd_code.clone(),
+            warnings: reader.take_warnings(),
+            fields: reader.fields()?.clone(),
+        })
+    }
+}
+
+/// 内层解码结果
+#[derive(Debug, Clone)]
+pub struct Payload {
+    pub cmd_code: String,
+    pub fields: Vec<Rawfield>,
+    pub warnings: Vec<Diagnostic>,
+}
+
+impl Payload {
+    /// 宽松模式下是否有字段解码失败
+    pub fn has_alert(&self) -> bool {
+        self.fields.iter().any(|f| f.is_alert())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{
+            context::{CipherRegistry, FrameCipher},
+            parts::transport_carrier::TransportCarrier,
+        },
+        utils::crc_util,
+        FieldType,
+    };
+
+    struct Xor;
+
+    impl FrameCipher for Xor {
+        fn decrypt(&self, data: &[u8]) -> ProtocolResult<Vec<u8>> {
+            Ok(data.iter().map(|b| b ^ 0xFF).collect())
+        }
+
+        fn encrypt(&self, data: &[u8]) -> ProtocolResult<Vec<u8>> {
+            self.decrypt(data)
+        }
+    }
+
+    // 68 cmd len addr | data | crc crc 16
+    fn frame(data: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0x68, 0x01, data.len() as u8, 0x12];
+        frame.extend_from_slice(data);
+        let crc = crc_util::calculate_from_bytes(CrcType::Crc16Modbus, &frame[1..]).unwrap();
+        frame.extend_from_slice(&crc.to_be_bytes());
+        frame.push(0x16);
+        frame
+    }
+
+    fn decoder() -> EnvelopeDecoder {
+        let config = ProtocolConfig::new("demo", 1, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16])
+            .with_length_field(2, 1, false)
+            .with_address(3, 1)
+            .with_data_offset(4)
+            .with_crc_len(2);
+        EnvelopeDecoder::new(config).with_crc(CrcType::Crc16Modbus, 1)
+    }
+
+    #[test]
+    fn test_envelope_then_payload() {
+        let mut carrier = TransportCarrier::default();
+        carrier.set_cipher_slot(0);
+        let ctx = DecodeContext::new("demo", carrier)
+            .with_ciphers(CipherRegistry::new().register(0, Xor));
+
+        let frame = frame(&[0xFE, 0xF5, 0x00]);
+        let envelope = decoder().open_with(&frame, &ctx).unwrap();
+        assert_eq!(envelope.cmd_code, "01");
+        assert_eq!(envelope.address.as_deref(), Some("12"));
+        assert!(envelope.is_decrypted());
+        assert_eq!(envelope.payload(), [0x01, 0x0A, 0xFF]);
+
+        let payload = envelope
+            .decode_payload(DecodeMode::Strict, |reader| {
+                reader.read_and_translate_field("读数", 2, |b| {
+                    Ok(Rawfield::new(
+                        b,
+                        "读数",
+                        FieldType::UnsignedU16(1.0).decode(b)?,
+                    ))
+                })?;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(payload.fields[0].value(), "266");
+        assert_eq!(payload.warnings[0].code, "payload_trailing");
+        assert!(!payload.has_alert());
+
+        // CRC 错误在外层就被拒绝
+        let mut bad = frame.clone();
+        bad[4] ^= 0x01;
+        assert!(decoder().open(&bad).is_err());
+    }
+}
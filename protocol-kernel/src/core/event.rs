@@ -0,0 +1,160 @@
+use protocol_base::{ProtocolError, ProtocolResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{parts::report_field::ReportField, reader::Reader},
+    utils::{hex_util, timestamp_util},
+};
+
+/// 告警/事件记录解析出的一条事件，放在 JniResponse.events 中，与普通字段分开
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceEvent {
+    pub code: String,
+    pub title: String,
+    #[serde(default)]
+    pub alert: bool,
+    // 事件发生时间 (yyyy-MM-dd HH:mm:ss)，记录不带时间时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub occurred_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<ReportField>,
+    // 整条记录的原始 hex
+    #[serde(default)]
+    pub raw_hex: String,
+}
+
+/// 事件码表中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventDefinition {
+    // 事件码 hex，大写
+    pub code: String,
+    pub title: String,
+    pub alert: bool,
+    // 参数块字节数
+    pub param_len: usize,
+}
+
+impl EventDefinition {
+    pub fn new(code: &str, title: &str, param_len: usize) -> Self {
+        Self {
+            code: code.to_uppercase(),
+            title: title.into(),
+            alert: false,
+            param_len,
+        }
+    }
+
+    pub fn alert(mut self) -> Self {
+        self.alert = true;
+        self
+    }
+}
+
+/// 事件记录上行的解析。默认记录格式为 事件码 (1 字节) + 时间 (6 字节 BCD yyMMddHHmmss) + 参数块，
+/// 格式不同的协议覆盖 code_len/time_len/decode_time/decode_params 即可
+pub trait EventDecoder {
+    /// 事件码表
+    fn definitions(&self) -> Vec<EventDefinition>;
+
+    fn code_len(&self) -> usize {
+        1
+    }
+
+    /// 时间字节数，0 表示记录不带时间
+    fn time_len(&self) -> usize {
+        6
+    }
+
+    fn decode_time(&self, bytes: &[u8]) -> ProtocolResult<String> {
+        timestamp_util::to_year_month_day_hour_min_sec(bytes)
+    }
+
+    /// 解析参数块，默认原样输出为一个 hex 字段
+    fn decode_params(
+        &self,
+        _definition: &EventDefinition,
+        bytes: &[u8],
+    ) -> ProtocolResult<Vec<ReportField>> {
+        if bytes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let hex = hex_util::bytes_to_hex(bytes)?;
+        Ok(vec![
+            ReportField::new("参数", "canshu", hex.clone()).with_raw_hex(&hex)
+        ])
+    }
+
+    /// 读取一条事件记录。未知事件码无法确定参数长度，直接报错
+    fn decode_event(&self, reader: &mut Reader) -> ProtocolResult<DeviceEvent> {
+        let mut raw = reader.read_bytes(self.code_len())?;
+        let code = hex_util::bytes_to_hex(&raw)?;
+        let definition = self
+            .definitions()
+            .into_iter()
+            .find(|d| d.code == code)
+            .ok_or_else(|| {
+                ProtocolError::ValidationFailed(format!("unknown event code {}", code))
+            })?;
+        let occurred_at = match self.time_len() {
+            0 => None,
+            len => {
+                let time = reader.read_bytes(len)?;
+                raw.extend_from_slice(&time);
+                Some(self.decode_time(&time)?)
+            }
+        };
+        let blob = reader.read_bytes(definition.param_len)?;
+        raw.extend_from_slice(&blob);
+        let params = self.decode_params(&definition, &blob)?;
+        Ok(DeviceEvent {
+            code,
+            title: definition.title,
+            alert: definition.alert,
+            occurred_at,
+            params,
+            raw_hex: hex_util::bytes_to_hex(&raw)?,
+        })
+    }
+
+    /// 连续读取 count 条记录
+    fn decode_events(&self, reader: &mut Reader, count: usize) -> ProtocolResult<Vec<DeviceEvent>> {
+        (0..count).map(|_| self.decode_event(reader)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct AlarmLog;
+
+    impl EventDecoder for AlarmLog {
+        fn definitions(&self) -> Vec<EventDefinition> {
+            vec![
+                EventDefinition::new("01", "磁干扰", 0).alert(),
+                EventDefinition::new("0a", "阀门动作", 1),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let bytes = hex_util::hex_to_bytes("012509180830000A2509181200005502").unwrap();
+        let mut reader = Reader::new(&bytes);
+        let events = AlarmLog.decode_events(&mut reader, 2).unwrap();
+        assert_eq!(events[0].title, "磁干扰");
+        assert!(events[0].alert);
+        assert_eq!(
+            events[0].occurred_at.as_deref(),
+            Some("2025-09-18 08:30:00")
+        );
+        assert!(events[0].params.is_empty());
+        assert_eq!(events[1].code, "0A");
+        assert_eq!(events[1].params[0].value, "55");
+        assert_eq!(events[1].raw_hex, "0A25091812000055");
+
+        // 第三条记录的事件码 02 未定义
+        assert!(AlarmLog.decode_event(&mut reader).is_err());
+    }
+}
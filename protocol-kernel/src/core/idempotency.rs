@@ -0,0 +1,156 @@
+use moka::sync::Cache;
+use once_cell::sync::Lazy;
+use std::time::{Duration, Instant};
+
+use protocol_base::{ProtocolError, ProtocolResult};
+
+use crate::{bridge::trace, JniResponse};
+
+// 令牌最长保留时间，record 传入更长的 ttl 时以此为准
+const MAX_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// 令牌 -> 过期时间
+static EXECUTED: Lazy<Cache<String, Instant>> = Lazy::new(|| {
+    Cache::builder()
+        .max_capacity(1_000_000)
+        .time_to_live(MAX_TTL)
+        .build()
+});
+
+/// 下行命令的幂等令牌: device_no + 下行序号 + cmd_code + 命令创建时间的 FNV-1a 64 位哈希 (16 位大写 hex)。
+/// 同一命令因超时重发时序号与创建时间都不变，令牌也不变，平台可据此避免重复执行 (如重复充值)。
+/// 下行序号只有 1~2 字节，在令牌有效期内可能回绕，issued_at (平台创建命令的 unix 毫秒) 用于区分
+/// 回绕后序号相同的新命令，重发时必须沿用首次下发的值
+pub fn idempotency_token(
+    device_no: &str,
+    downstream_count_hex: &str,
+    cmd_code: &str,
+    issued_at: u64,
+) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let parts = [
+        device_no.trim().to_uppercase(),
+        downstream_count_hex.trim().to_uppercase(),
+        cmd_code.trim().to_uppercase(),
+        issued_at.to_string(),
+    ];
+    for part in &parts {
+        // 各部分之间以 0 分隔，避免 ("12", "3") 与 ("1", "23") 相同
+        for &b in part.as_bytes().iter().chain([0u8].iter()) {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    format!("{:016X}", hash)
+}
+
+/// 按幂等令牌识别重复执行的下行命令。令牌在 ttl 内有效，过期后同一令牌视为新命令
+pub struct IdempotencyGuard {}
+
+impl IdempotencyGuard {
+    /// 记录一次执行。令牌在有效期内出现过时返回 true (重复执行)，否则记录并返回 false
+    pub fn check_and_record(token: &str, ttl: Duration) -> bool {
+        let now = Instant::now();
+        let mut duplicate = false;
+        EXECUTED
+            .entry(token.to_string())
+            .and_upsert_with(|entry| match entry {
+                Some(entry) if *entry.value() > now => {
+                    duplicate = true;
+                    *entry.value()
+                }
+                _ => now + ttl.min(MAX_TTL),
+            });
+        if duplicate {
+            eprintln!(
+                "[WARN] {}Duplicate downstream execution: token {}",
+                trace::log_prefix(),
+                token
+            );
+        }
+        duplicate
+    }
+
+    /// 令牌是否已执行且未过期，不记录
+    pub fn is_executed(token: &str) -> bool {
+        EXECUTED
+            .get(token)
+            .is_some_and(|expires_at| expires_at > Instant::now())
+    }
+
+    /// 同 check_and_record，重复时返回 ValidationFailed
+    pub fn reject_duplicate(token: &str, ttl: Duration) -> ProtocolResult<()> {
+        if Self::check_and_record(token, ttl) {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "downstream command with idempotency token {} already executed",
+                token
+            )));
+        }
+        Ok(())
+    }
+
+    /// 按响应携带的令牌检查并标记 duplicate，响应没有令牌时不处理并返回 false
+    pub fn mark(response: &mut JniResponse, ttl: Duration) -> bool {
+        let Some(token) = response.idempotency_token().map(|t| t.to_string()) else {
+            return false;
+        };
+        let duplicate = Self::check_and_record(&token, ttl);
+        response.set_duplicate(duplicate);
+        duplicate
+    }
+
+    /// 清除令牌，例如命令执行失败、需要允许重新执行时
+    pub fn forget(token: &str) {
+        EXECUTED.invalidate(token);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token() {
+        let token = idempotency_token("2025091a", "0a", "25", 1);
+        assert_eq!(token.len(), 16);
+        assert_eq!(token, idempotency_token(" 2025091A", "0A", "25", 1));
+        assert_ne!(token, idempotency_token("2025091A", "0B", "25", 1));
+        assert_ne!(
+            idempotency_token("12", "3", "", 1),
+            idempotency_token("1", "23", "", 1)
+        );
+        // 序号回绕后的新命令创建时间不同
+        assert_ne!(token, idempotency_token("2025091A", "0A", "25", 2));
+    }
+
+    #[test]
+    fn test_guard() {
+        let token = idempotency_token("idempotency-test", "01", "25", 1);
+        assert!(!IdempotencyGuard::check_and_record(
+            &token,
+            Duration::from_secs(60)
+        ));
+        assert!(IdempotencyGuard::is_executed(&token));
+        assert!(IdempotencyGuard::reject_duplicate(&token, Duration::from_secs(60)).is_err());
+
+        let mut rsp = JniResponse::from(br#"{"success":true}"#).unwrap();
+        assert!(!IdempotencyGuard::mark(&mut rsp, Duration::from_secs(60)));
+        rsp.set_idempotency_token(&token);
+        assert!(IdempotencyGuard::mark(&mut rsp, Duration::from_secs(60)));
+        assert!(rsp.duplicate());
+
+        // 过期后视为新命令
+        let expiring = idempotency_token("idempotency-test", "02", "25", 1);
+        assert!(!IdempotencyGuard::check_and_record(
+            &expiring,
+            Duration::ZERO
+        ));
+        assert!(!IdempotencyGuard::check_and_record(
+            &expiring,
+            Duration::from_secs(60)
+        ));
+
+        IdempotencyGuard::forget(&token);
+        assert!(!IdempotencyGuard::is_executed(&token));
+    }
+}
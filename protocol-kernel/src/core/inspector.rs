@@ -0,0 +1,462 @@
+use std::fmt::Write as _;
+
+use protocol_base::{ProtocolError, ProtocolResult};
+
+use crate::{
+    core::parts::{rawfield::Rawfield, report_field::ReportField},
+    utils::hex_util,
+};
+
+// 选中字段反显，其余字段交替着色以区分边界
+const SELECTED: &str = "\x1b[7m";
+const PALETTE: [&str; 2] = ["\x1b[36m", "\x1b[33m"];
+const RESET: &str = "\x1b[0m";
+
+// SVG 字节图的尺寸与配色
+const SVG_CELL_WIDTH: usize = 28;
+const SVG_CELL_HEIGHT: usize = 22;
+const SVG_OFFSET_WIDTH: usize = 48;
+const SVG_LEGEND_LINE: usize = 18;
+const SVG_PALETTE: [&str; 6] = [
+    "#cfe8fc", "#fde2c4", "#d5f0d5", "#f3d4f5", "#fff3b0", "#d9dcf7",
+];
+const SVG_UNMAPPED: &str = "#eeeeee";
+const SVG_SELECTED_STROKE: &str = "#d32f2f";
+
+/// 字段在报文中的字节范围 [start, start + len)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSpan {
+    pub start: usize,
+    pub len: usize,
+    pub title: String,
+    pub value: String,
+}
+
+impl FieldSpan {
+    pub fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        (self.start..self.end()).contains(&offset)
+    }
+}
+
+/// 交互式查看器的按键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InspectorKey {
+    Next,
+    Prev,
+    First,
+    Last,
+    // 跳到下一行/上一行同一列所在的字段
+    Down,
+    Up,
+}
+
+impl InspectorKey {
+    /// 默认键位: Tab/l/n 下一个字段，h/p 上一个，j/k 上下行，g/G 首尾
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '\t' | 'l' | 'n' => Some(InspectorKey::Next),
+            'h' | 'p' => Some(InspectorKey::Prev),
+            'g' => Some(InspectorKey::First),
+            'G' => Some(InspectorKey::Last),
+            'j' => Some(InspectorKey::Down),
+            'k' => Some(InspectorKey::Up),
+            _ => None,
+        }
+    }
+}
+
+/// 帧查看器: 把解析出的字段映射回报文的字节范围，按光标所在字段高亮 hex dump。
+/// 只负责状态与渲染 (ANSI 转义)；读键与刷屏见 `frame-inspector` (feature `tui`)
+#[derive(Debug, Clone)]
+pub struct FrameInspector {
+    frame: Vec<u8>,
+    spans: Vec<FieldSpan>,
+    cursor: usize,
+    bytes_per_line: usize,
+}
+
+impl FrameInspector {
+    /// 紧凑模式的字段直接用记录的 span；其余字段按顺序在报文中查找各自的字节
+    pub fn from_rawfields(frame: &[u8], fields: &[Rawfield]) -> ProtocolResult<Self> {
+        let mut spans = Vec::with_capacity(fields.len());
+        let mut offset = 0;
+        for field in fields {
+            let start = match field.span() {
+                Some((start, _)) => start,
+                None => locate(frame, field.bytes(), offset, field.title())?,
+            };
+            let len = field.bytes().len();
+            offset = start + len;
+            spans.push(FieldSpan {
+                start,
+                len,
+                title: field.title_clone(),
+                value: field.value_clone(),
+            });
+        }
+        Self::new(frame, spans)
+    }
+
+    /// 由 JniResponse 的 hex 与字段生成，依赖 ReportField.raw_hex；没有 raw_hex 的字段跳过
+    pub fn from_report(hex: &str, fields: &[ReportField]) -> ProtocolResult<Self> {
+        let frame = hex_util::hex_to_bytes(hex)?;
+        let mut spans = Vec::with_capacity(fields.len());
+        let mut offset = 0;
+        for field in fields {
+            let Some(raw_hex) = field.raw_hex.as_deref().filter(|h| !h.is_empty()) else {
+                continue;
+            };
+            let bytes = hex_util::hex_to_bytes(raw_hex)?;
+            let start = locate(&frame, &bytes, offset, &field.name)?;
+            offset = start + bytes.len();
+            spans.push(FieldSpan {
+                start,
+                len: bytes.len(),
+                title: field.name.to_string(),
+                value: field.value.clone(),
+            });
+        }
+        Self::new(&frame, spans)
+    }
+
+    fn new(frame: &[u8], spans: Vec<FieldSpan>) -> ProtocolResult<Self> {
+        if let Some(span) = spans.iter().find(|s| s.end() > frame.len()) {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "field '{}' [{}, {}) exceeds frame length {}",
+                span.title,
+                span.start,
+                span.end(),
+                frame.len()
+            )));
+        }
+        Ok(Self {
+            frame: frame.to_vec(),
+            spans,
+            cursor: 0,
+            bytes_per_line: 16,
+        })
+    }
+
+    pub fn with_bytes_per_line(mut self, bytes_per_line: usize) -> Self {
+        self.bytes_per_line = bytes_per_line.max(1);
+        self
+    }
+
+    pub fn frame(&self) -> &[u8] {
+        &self.frame
+    }
+
+    pub fn spans(&self) -> &[FieldSpan] {
+        &self.spans
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn selected(&self) -> Option<&FieldSpan> {
+        self.spans.get(self.cursor)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.cursor = index.min(self.spans.len().saturating_sub(1));
+    }
+
+    /// 选中包含该字节的字段，没有字段覆盖该字节时返回 false
+    pub fn select_offset(&mut self, offset: usize) -> bool {
+        match self.spans.iter().position(|s| s.contains(offset)) {
+            Some(index) => {
+                self.cursor = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 处理一个按键，光标位置变化时返回 true
+    pub fn handle(&mut self, key: InspectorKey) -> bool {
+        let before = self.cursor;
+        let last = self.spans.len().saturating_sub(1);
+        match key {
+            InspectorKey::Next => self.cursor = (self.cursor + 1).min(last),
+            InspectorKey::Prev => self.cursor = self.cursor.saturating_sub(1),
+            InspectorKey::First => self.cursor = 0,
+            InspectorKey::Last => self.cursor = last,
+            InspectorKey::Down | InspectorKey::Up => {
+                if let Some(span) = self.selected() {
+                    let target = if key == InspectorKey::Down {
+                        span.start.checked_add(self.bytes_per_line)
+                    } else {
+                        span.start.checked_sub(self.bytes_per_line)
+                    };
+                    // 目标字节不属于任何字段时不移动
+                    if let Some(target) = target.filter(|t| *t < self.frame.len()) {
+                        self.select_offset(target);
+                    }
+                }
+            }
+        }
+        self.cursor != before
+    }
+
+    /// 渲染一屏: 带偏移量的 hex dump (选中字段反显)、字段列表和状态行
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (line, chunk) in self.frame.chunks(self.bytes_per_line).enumerate() {
+            let base = line * self.bytes_per_line;
+            let _ = write!(out, "{:04X}: ", base);
+            for (i, byte) in chunk.iter().enumerate() {
+                if i > 0 {
+                    out.push(' ');
+                }
+                match self.style_of(base + i) {
+                    Some(style) => {
+                        let _ = write!(out, "{}{:02X}{}", style, byte, RESET);
+                    }
+                    None => {
+                        let _ = write!(out, "{:02X}", byte);
+                    }
+                }
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+        for (i, span) in self.spans.iter().enumerate() {
+            let marker = if i == self.cursor { '>' } else { ' ' };
+            let _ = writeln!(
+                out,
+                "{} {:04X}+{:<3} {}: {}",
+                marker, span.start, span.len, span.title, span.value
+            );
+        }
+        if let Some(span) = self.selected() {
+            let hex = hex_util::format_hex_spaced(&self.frame[span.start..span.end()], 1)
+                .unwrap_or_default();
+            let _ = write!(
+                out,
+                "\n[{}/{}] {} = {}  ({})",
+                self.cursor + 1,
+                self.spans.len(),
+                span.title,
+                span.value,
+                hex
+            );
+        }
+        out
+    }
+
+    /// 导出 SVG 字节图: 每个字段的字节按颜色区分，悬停显示字段名与值，图下方为字段图例。
+    /// 选中字段加红框，供 web 控制台和工单附件使用
+    pub fn render_svg(&self) -> String {
+        let rows = self.frame.len().div_ceil(self.bytes_per_line).max(1);
+        let grid_height = rows * SVG_CELL_HEIGHT;
+        let legend_top = grid_height + SVG_LEGEND_LINE;
+        let width = SVG_OFFSET_WIDTH + self.bytes_per_line * SVG_CELL_WIDTH + 8;
+        let height = legend_top + self.spans.len() * SVG_LEGEND_LINE + 8;
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="monospace" font-size="12">"##,
+            w = width,
+            h = height
+        );
+        for line in 0..rows {
+            let _ = writeln!(
+                out,
+                r##"<text x="0" y="{}" fill="#888">{:04X}</text>"##,
+                line * SVG_CELL_HEIGHT + 15,
+                line * self.bytes_per_line
+            );
+        }
+        for offset in 0..self.frame.len() {
+            if !self.spans.iter().any(|s| s.contains(offset)) {
+                self.svg_cell(&mut out, offset, SVG_UNMAPPED, None);
+            }
+        }
+        for (index, span) in self.spans.iter().enumerate() {
+            let color = SVG_PALETTE[index % SVG_PALETTE.len()];
+            let stroke = (index == self.cursor).then_some(SVG_SELECTED_STROKE);
+            let _ = writeln!(
+                out,
+                "<g><title>{}: {}</title>",
+                escape_xml(&span.title),
+                escape_xml(&span.value)
+            );
+            for offset in span.start..span.end() {
+                self.svg_cell(&mut out, offset, color, stroke);
+            }
+            out.push_str("</g>\n");
+
+            let y = legend_top + index * SVG_LEGEND_LINE;
+            let _ = writeln!(
+                out,
+                r##"<rect x="0" y="{}" width="12" height="12" fill="{}"/><text x="18" y="{}">{:04X}+{} {}: {}</text>"##,
+                y - 11,
+                color,
+                y,
+                span.start,
+                span.len,
+                escape_xml(&span.title),
+                escape_xml(&span.value)
+            );
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    fn svg_cell(&self, out: &mut String, offset: usize, fill: &str, stroke: Option<&str>) {
+        let x = SVG_OFFSET_WIDTH + (offset % self.bytes_per_line) * SVG_CELL_WIDTH;
+        let y = (offset / self.bytes_per_line) * SVG_CELL_HEIGHT;
+        let _ = writeln!(
+            out,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}"/><text x="{}" y="{}" text-anchor="middle">{:02X}</text>"##,
+            x,
+            y,
+            SVG_CELL_WIDTH,
+            SVG_CELL_HEIGHT,
+            fill,
+            stroke.unwrap_or("#ffffff"),
+            x + SVG_CELL_WIDTH / 2,
+            y + 15,
+            self.frame[offset]
+        );
+    }
+
+    fn style_of(&self, offset: usize) -> Option<&'static str> {
+        let index = self.spans.iter().position(|s| s.contains(offset))?;
+        Some(if index == self.cursor {
+            SELECTED
+        } else {
+            PALETTE[index % PALETTE.len()]
+        })
+    }
+}
+
+/// 转义 XML/HTML 文本
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 从 from 开始查找字段字节第一次出现的位置
+fn locate(frame: &[u8], bytes: &[u8], from: usize, title: &str) -> ProtocolResult<usize> {
+    let not_found = || {
+        ProtocolError::ValidationFailed(format!(
+            "field '{}' not found in frame after offset {}",
+            title, from
+        ))
+    };
+    if bytes.is_empty() {
+        return Ok(from.min(frame.len()));
+    }
+    frame
+        .get(from..)
+        .and_then(|rest| rest.windows(bytes.len()).position(|w| w == bytes))
+        .map(|pos| from + pos)
+        .ok_or_else(not_found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inspector() -> FrameInspector {
+        let frame = [0x68, 0x12, 0x34, 0x56, 0x68, 0x01, 0x00, 0x16];
+        let fields = [
+            Rawfield::new(&frame[0..1], "起始符", "68".into()),
+            Rawfield::new(&frame[1..4], "地址", "123456".into()),
+            // 跳过的第二个起始符没有对应字段
+            Rawfield::new(&frame[5..7], "读数", "1".into()),
+            Rawfield::new(&frame[7..8], "结束符", "16".into()),
+        ];
+        FrameInspector::from_rawfields(&frame, &fields)
+            .unwrap()
+            .with_bytes_per_line(4)
+    }
+
+    #[test]
+    fn test_spans_and_navigation() {
+        let mut inspector = inspector();
+        let starts: Vec<usize> = inspector.spans().iter().map(|s| s.start).collect();
+        assert_eq!(starts, [0, 1, 5, 7]);
+
+        assert!(!inspector.handle(InspectorKey::Prev));
+        // 下一行同列的字节 (偏移 4) 不属于任何字段
+        assert!(!inspector.handle(InspectorKey::Down));
+        inspector.handle(InspectorKey::Next);
+        assert!(inspector.handle(InspectorKey::Down));
+        assert_eq!(inspector.selected().unwrap().title, "读数");
+        assert!(inspector.handle(InspectorKey::Up));
+        assert_eq!(inspector.cursor(), 1);
+        inspector.handle(InspectorKey::Last);
+        assert!(!inspector.handle(InspectorKey::Next));
+        assert!(!inspector.select_offset(4));
+        assert!(inspector.select_offset(2));
+        assert_eq!(inspector.cursor(), 1);
+        assert_eq!(InspectorKey::from_char('G'), Some(InspectorKey::Last));
+    }
+
+    #[test]
+    fn test_render() {
+        let mut inspector = inspector();
+        inspector.handle(InspectorKey::Next);
+        let screen = inspector.render();
+        let first_line = screen.lines().next().unwrap();
+        assert_eq!(
+            first_line,
+            "0000: \x1b[36m68\x1b[0m \x1b[7m12\x1b[0m \x1b[7m34\x1b[0m \x1b[7m56\x1b[0m"
+        );
+        // 不属于任何字段的字节不着色
+        assert!(screen.lines().nth(1).unwrap().starts_with("0004: 68 "));
+        assert!(screen.contains("> 0001+3   地址: 123456"));
+        assert!(screen.ends_with("[2/4] 地址 = 123456  (12 34 56)"));
+    }
+
+    #[test]
+    fn test_from_report() {
+        let fields: Vec<ReportField> = [("起始符", "68"), ("读数", "0100"), ("备注", "")]
+            .iter()
+            .map(|(name, raw)| ReportField {
+                name: (*name).into(),
+                code: String::new(),
+                value: String::new(),
+                alert: false,
+                value_type: None,
+                raw_hex: (!raw.is_empty()).then(|| raw.to_string()),
+                unit: None,
+                scale: None,
+            })
+            .collect();
+        let inspector = FrameInspector::from_report("6812010016", &fields).unwrap();
+        let spans: Vec<(usize, usize)> =
+            inspector.spans().iter().map(|s| (s.start, s.len)).collect();
+        assert_eq!(spans, [(0, 1), (2, 2)]);
+        assert!(FrameInspector::from_report("6812", &fields).is_err());
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let mut inspector = inspector();
+        inspector.handle(InspectorKey::Next);
+        let svg = inspector.render_svg();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        // 8 个字节各一个格子，外加 4 个图例色块
+        assert_eq!(svg.matches("<rect").count(), 12);
+        assert!(svg.contains("<g><title>地址: 123456</title>"));
+        assert_eq!(svg.matches(SVG_SELECTED_STROKE).count(), 3);
+        // 未映射的字节 (偏移 4)
+        assert!(svg.contains(&format!(
+            r##"<rect x="48" y="22" width="28" height="22" fill="{}""##,
+            SVG_UNMAPPED
+        )));
+        assert!(svg.contains("0005+2 读数: 1</text>"));
+        assert_eq!(escape_xml("a<b&\"c\""), "a&lt;b&amp;&quot;c&quot;");
+    }
+}
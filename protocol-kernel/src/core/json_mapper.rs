@@ -0,0 +1,445 @@
+//! JSON 上报的字段映射。部分 NB-IoT 表直接上报 JSON，按路径把值映射成与 hex 帧解码
+//! 相同的 ReportField (标题、code、单位符号、缩放、告警)，混合表型的平台无需区分报文编码:
+//!
+//! ```
+//! use protocol_kernel::{core::json_mapper::{JsonField, JsonFieldMapper}, Symbol};
+//!
+//! let mapper = JsonFieldMapper::new()
+//!     .with_device_no("imei")
+//!     .field(JsonField::new("data.total", "累计用量").with_scale(0.01).with_symbol(Symbol::CubicMeter))
+//!     .field(JsonField::new("data.valve", "阀门状态").with_enum(&[("0", "开阀"), ("1", "关阀")]));
+//! let fields = mapper
+//!     .map_str(r#"{"imei":"860000000000001","data":{"total":1234,"valve":1}}"#)
+//!     .unwrap();
+//! assert_eq!(fields[0].value, "12.34 m³");
+//! assert_eq!(fields[0].unit.as_deref(), Some("m³"));
+//! assert_eq!(fields[1].value, "关阀");
+//! ```
+use serde_json::Value;
+
+use crate::{
+    bridge::{JniRequest, JniResponse},
+    code_registry::CodeRegistry,
+    hex_util,
+    math_util::{self, DecimalRoundingMode},
+    MsgTypeEnum, ProtocolError, ProtocolResult, Rawfield, ReportField, Symbol, ValueType,
+};
+
+/// 告警规则，命中时 ReportField.alert 为 true
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertRule {
+    /// 原始值 (转成字符串后) 等于其中之一
+    Equals(Vec<String>),
+    /// 数值 (缩放后) 不在 [min, max] 之内
+    Outside { min: f64, max: f64 },
+}
+
+/// 一个 JSON 路径到字段的映射。路径以 `.` 分隔，数组用 `[i]`，可带 `$.` 前缀，
+/// 如 `data.meters[0].flow`
+#[derive(Debug, Clone)]
+pub struct JsonField {
+    path: String,
+    title: String,
+    scale: f64,
+    symbol: Option<Symbol>,
+    enum_values: Vec<(String, String)>,
+    alert: Option<AlertRule>,
+    required: bool,
+}
+
+impl JsonField {
+    pub fn new(path: &str, title: &str) -> Self {
+        Self {
+            path: path.into(),
+            title: title.into(),
+            scale: 1.0,
+            symbol: None,
+            enum_values: Vec::new(),
+            alert: None,
+            required: true,
+        }
+    }
+
+    /// 数值的缩放倍数，与 FieldType 的 scale 含义相同
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_symbol(mut self, symbol: Symbol) -> Self {
+        self.symbol = Some(symbol);
+        self
+    }
+
+    /// 原始值 -> 显示值，原始值按字符串比较 (数字 1 与 "1" 相同)
+    pub fn with_enum(mut self, values: &[(&str, &str)]) -> Self {
+        self.enum_values = values
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        self
+    }
+
+    pub fn with_alert(mut self, rule: AlertRule) -> Self {
+        self.alert = Some(rule);
+        self
+    }
+
+    /// 路径不存在或为 null 时跳过，而不是报错
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    // 映射单个值，返回字段与是否告警
+    fn translate(&self, value: &Value) -> ProtocolResult<(Rawfield, bool)> {
+        let raw = match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if !self.enum_values.is_empty() {
+            let shown = self
+                .enum_values
+                .iter()
+                .find(|(k, _)| *k == raw)
+                .map(|(_, v)| v.clone())
+                .unwrap_or_else(|| raw.clone());
+            let rf =
+                Rawfield::new(&[], self.title.as_str(), shown).with_value_type(ValueType::Enum);
+            return Ok((rf, self.alerts(&raw, None)));
+        }
+
+        let (mut rf, number) = match value {
+            Value::Number(n) => {
+                let n = n.as_f64().ok_or_else(|| {
+                    ProtocolError::ValidationFailed(format!("{}: invalid number {}", self.path, n))
+                })?;
+                let scaled = self.scaled(n)?;
+                let (shown, value_type) = if self.scale != 1.0 {
+                    (scaled.to_string(), ValueType::Float)
+                } else if value.is_f64() {
+                    (raw.clone(), ValueType::Float)
+                } else {
+                    (raw.clone(), ValueType::Int)
+                };
+                let rf = Rawfield::new(&[], self.title.as_str(), shown)
+                    .with_value_type(value_type)
+                    .with_scale(self.scale);
+                (rf, Some(scaled))
+            }
+            Value::Bool(_) => (
+                Rawfield::new(&[], self.title.as_str(), raw.clone())
+                    .with_value_type(ValueType::Bool),
+                None,
+            ),
+            _ => (
+                Rawfield::new(&[], self.title.as_str(), raw.clone())
+                    .with_value_type(ValueType::String),
+                None,
+            ),
+        };
+        // 与 FieldConvertDecoder 一致: 值后拼接符号，同时记录单位
+        if let Some(symbol) = &self.symbol {
+            let tag = symbol.tag();
+            rf.value = format!("{} {}", rf.value, tag);
+            rf.unit = Some(tag);
+        }
+        Ok((rf, self.alerts(&raw, number)))
+    }
+
+    fn scaled(&self, n: f64) -> ProtocolResult<f64> {
+        if self.scale == 1.0 {
+            return Ok(n);
+        }
+        if self.scale == 0.0 {
+            return Err(ProtocolError::ValidationFailed(
+                "Scale factor cannot be zero.".to_string(),
+            ));
+        }
+        math_util::multiply(6, DecimalRoundingMode::HalfUp, &[n, self.scale])
+    }
+
+    fn alerts(&self, raw: &str, number: Option<f64>) -> bool {
+        match &self.alert {
+            Some(AlertRule::Equals(values)) => values.iter().any(|v| v == raw),
+            Some(AlertRule::Outside { min, max }) => number.is_some_and(|n| n < *min || n > *max),
+            None => false,
+        }
+    }
+}
+
+/// 按路径取值，路径不存在时返回 None
+pub fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.strip_prefix("$.").unwrap_or(path);
+    let mut current = value;
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let (key, indexes) = match segment.find('[') {
+            Some(i) => segment.split_at(i),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+        for index in indexes.split('[').filter(|s| !s.is_empty()) {
+            let index: usize = index.strip_suffix(']')?.parse().ok()?;
+            current = current.get(index)?;
+        }
+    }
+    Some(current)
+}
+
+/// JSON 报文的字段映射表，可同时给出设备号、命令码所在的路径与固定的消息类型
+#[derive(Debug, Clone, Default)]
+pub struct JsonFieldMapper {
+    fields: Vec<JsonField>,
+    device_no: Option<String>,
+    cmd_code: Option<String>,
+    msg_type: Option<MsgTypeEnum>,
+}
+
+impl JsonFieldMapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, field: JsonField) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    pub fn with_device_no(mut self, path: &str) -> Self {
+        self.device_no = Some(path.into());
+        self
+    }
+
+    pub fn with_cmd_code(mut self, path: &str) -> Self {
+        self.cmd_code = Some(path.into());
+        self
+    }
+
+    pub fn with_msg_type(mut self, msg_type: MsgTypeEnum) -> Self {
+        self.msg_type = Some(msg_type);
+        self
+    }
+
+    /// 所有字段标题，用于 ProtocolHandler::field_titles
+    pub fn titles(&self) -> Vec<String> {
+        self.fields.iter().map(|f| f.title.clone()).collect()
+    }
+
+    pub fn map(&self, payload: &Value) -> ProtocolResult<Vec<ReportField>> {
+        self.map_by(payload, |rf| Ok(rf.to_report_field()))
+    }
+
+    /// 通过协议的 code 注册表生成 code，与 Reader::to_report_fields_with 一致
+    pub fn map_with(
+        &self,
+        payload: &Value,
+        registry: &mut CodeRegistry,
+    ) -> ProtocolResult<Vec<ReportField>> {
+        self.map_by(payload, |rf| rf.to_report_field_with(registry))
+    }
+
+    pub fn map_str(&self, json: &str) -> ProtocolResult<Vec<ReportField>> {
+        self.map(&parse(json)?)
+    }
+
+    fn map_by<F>(&self, payload: &Value, mut to_report: F) -> ProtocolResult<Vec<ReportField>>
+    where
+        F: FnMut(Rawfield) -> ProtocolResult<ReportField>,
+    {
+        let mut fields = Vec::with_capacity(self.fields.len());
+        for field in &self.fields {
+            let value = match lookup(payload, &field.path) {
+                Some(Value::Null) | None if !field.required => continue,
+                Some(Value::Null) | None => {
+                    return Err(ProtocolError::ValidationFailed(format!(
+                        "json path '{}' ({}) not found",
+                        field.path, field.title
+                    )))
+                }
+                Some(value) => value,
+            };
+            let (rf, alert) = field.translate(value)?;
+            let mut report = to_report(rf)?;
+            report.alert = alert;
+            fields.push(report);
+        }
+        Ok(fields)
+    }
+
+    /// 解析 JniRequest 中的 JSON 报文。hex 可以是 JSON 原文，也可以是 UTF-8 JSON 的 hex
+    pub fn decode(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+        let payload = payload_of(request.hex())?;
+        let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+        let text = |path: &Option<String>| -> Option<String> {
+            path.as_deref()
+                .and_then(|p| lookup(&payload, p))
+                .map(|v| match v {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+        };
+        match text(&self.device_no).or_else(|| request.device_no().map(Into::into)) {
+            Some(device_no) => rsp.set_device_no(&device_no),
+            None => {
+                return Err(ProtocolError::ValidationFailed(
+                    "device_no not found in json payload or request".into(),
+                ))
+            }
+        }
+        if let Some(cmd_code) = text(&self.cmd_code).or_else(|| request.cmd_code().map(Into::into))
+        {
+            rsp.set_cmd_code(&cmd_code);
+        }
+        if let Some(msg_type) = &self.msg_type {
+            rsp.set_msg_type(msg_type);
+        }
+        rsp.set_req_hex(request.hex());
+        rsp.set_req_jsons(self.map(&payload)?);
+        Ok(rsp)
+    }
+}
+
+fn parse(json: &str) -> ProtocolResult<Value> {
+    serde_json::from_str(json).map_err(|e| ProtocolError::CommonError(e.to_string()))
+}
+
+// JSON 原文以 { 或 [ 开头，不会被误认为 hex
+fn payload_of(input: &str) -> ProtocolResult<Value> {
+    let trimmed = input.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return parse(trimmed);
+    }
+    let bytes = hex_util::hex_to_bytes(input)?;
+    let json = std::str::from_utf8(&bytes)
+        .map_err(|e| ProtocolError::CommonError(format!("json payload is not UTF-8: {}", e)))?;
+    parse(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FieldConvertDecoder, FieldTranslator, FieldType, Reader};
+
+    const PAYLOAD: &str = r#"{
+        "imei": "860000000000001",
+        "type": "report",
+        "data": {"total": 1234, "valve": 1, "battery": 3.2, "online": true,
+                 "meters": [{"flow": 5}, {"flow": 7}]}
+    }"#;
+
+    fn mapper() -> JsonFieldMapper {
+        JsonFieldMapper::new()
+            .with_device_no("$.imei")
+            .with_cmd_code("type")
+            .with_msg_type(MsgTypeEnum::DataReport)
+            .field(
+                JsonField::new("data.total", "累计用量")
+                    .with_scale(0.01)
+                    .with_symbol(Symbol::CubicMeter),
+            )
+            .field(
+                JsonField::new("data.valve", "阀门状态")
+                    .with_enum(&[("0", "开阀"), ("1", "关阀")])
+                    .with_alert(AlertRule::Equals(vec!["1".into()])),
+            )
+            .field(
+                JsonField::new("data.battery", "电池电压")
+                    .with_symbol(Symbol::Voltage)
+                    .with_alert(AlertRule::Outside { min: 3.3, max: 3.7 }),
+            )
+            .field(JsonField::new("data.online", "在线"))
+            .field(JsonField::new("data.meters[1].flow", "分表流量"))
+            .field(JsonField::new("data.signal", "信号强度").optional())
+    }
+
+    #[test]
+    fn test_lookup() {
+        let payload = parse(PAYLOAD).unwrap();
+        assert_eq!(
+            lookup(&payload, "data.meters[0].flow"),
+            Some(&Value::from(5))
+        );
+        assert_eq!(
+            lookup(&payload, "$.imei").and_then(Value::as_str),
+            Some("860000000000001")
+        );
+        assert!(lookup(&payload, "data.meters[2].flow").is_none());
+        assert!(lookup(&payload, "data.missing").is_none());
+    }
+
+    #[test]
+    fn test_map_matches_hex_decoding() {
+        let fields = mapper().map_str(PAYLOAD).unwrap();
+        assert_eq!(fields.len(), 5);
+
+        // 同一字段从 hex 帧解码的结果
+        let decoder = FieldConvertDecoder::new(
+            "累计用量",
+            FieldType::UnsignedU32(0.01),
+            Some(Symbol::CubicMeter),
+            false,
+        );
+        let mut reader = Reader::new(&[0x00, 0x00, 0x04, 0xD2]);
+        reader
+            .read_and_translate_head(4, |b| decoder.translate(b))
+            .unwrap();
+        let from_hex = &reader.to_report_fields().unwrap()[0];
+        let total = &fields[0];
+        assert_eq!(total.name, from_hex.name);
+        assert_eq!(total.code, from_hex.code);
+        assert_eq!(total.value, from_hex.value);
+        assert_eq!(total.unit, from_hex.unit);
+        assert_eq!(total.value_type, from_hex.value_type);
+        assert_eq!(total.value, "12.34 m³");
+        assert_eq!(total.raw_hex, None);
+        assert_eq!(from_hex.raw_hex, None);
+
+        // raw_hex 需要显式开启
+        let mut reader = Reader::new(&[0x00, 0x00, 0x04, 0xD2]).with_raw_hex();
+        reader
+            .read_and_translate_head(4, |b| decoder.translate(b))
+            .unwrap();
+        let from_hex = &reader.to_report_fields().unwrap()[0];
+        assert_eq!(from_hex.raw_hex.as_deref(), Some("000004D2"));
+        assert_eq!(from_hex.unit.as_deref(), Some("m³"));
+
+        assert_eq!(fields[1].value, "关阀");
+        assert!(fields[1].alert);
+        assert_eq!(fields[2].value, "3.2 V");
+        assert_eq!(fields[2].unit.as_deref(), Some("V"));
+        assert!(fields[2].alert);
+        assert_eq!(fields[3].value_type, Some(ValueType::Bool));
+        assert_eq!(fields[4].value, "7");
+    }
+
+    #[test]
+    fn test_map_missing_required() {
+        let mapper = mapper().field(JsonField::new("data.price", "单价"));
+        let err = mapper.map_str(PAYLOAD).unwrap_err();
+        assert!(err.to_string().contains("data.price"));
+    }
+
+    #[test]
+    fn test_decode_request() {
+        let hex = hex_util::bytes_to_hex(br#"{"imei":"1","type":"report","data":{"total":1,"valve":0,"battery":3.5,"online":false,"meters":[{},{"flow":0}]}}"#).unwrap();
+        for input in [hex.as_str(), PAYLOAD] {
+            // 原文形式的 JSON 不是合法 hex，JniRequestBuilder 会拒绝，这里直接构造
+            let request = JniRequest::new(None, None, None, None, input.into(), None, None);
+            let rsp = mapper().decode(&request).unwrap();
+            assert!(rsp.device_no().is_some());
+            assert_eq!(rsp.cmd_code(), Some("report"));
+            assert_eq!(rsp.msg_type(), Some("data_report"));
+            assert_eq!(rsp.req_jsons().len(), 5);
+        }
+    }
+}
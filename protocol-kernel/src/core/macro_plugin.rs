@@ -42,10 +42,11 @@ macro_rules! handle_int {
 #[macro_export]
 macro_rules! handle_int_encode {
     ($type:ty, $len:expr, $input:expr, $scale:expr) => {{
+        handle_int_encode!($type, $len, $input, $scale, "")
+    }};
+    ($type:ty, $len:expr, $input:expr, $scale:expr, $title:expr) => {{
         // 1. 解析输入字符串为f64
-        let parsed_value: f64 = $input.parse().map_err(|_| {
-            ProtocolError::ValidationFailed(format!("Failed to parse input '{}' as f64", $input))
-        })?;
+        let parsed_value: f64 = math_util::parse_numeric($input)?;
 
         // 2. 执行反缩放（如果需要）
         let final_value = if $scale != 1.0 && $scale != 0.0 {
@@ -59,8 +60,29 @@ macro_rules! handle_int_encode {
             parsed_value
         };
 
-        // 3. 转换为目标整数类型
-        let int_value: $type = final_value as $type;
+        // 3. 转换为目标整数类型 (小数部分截断，超出范围报错而不是静默截断)
+        // as i128 向零截断，与 trunc 相同 (trunc 在 no_std 下不可用)
+        let checked = if final_value.is_finite() && final_value.abs() < 1e38 {
+            <$type>::try_from(final_value as i128).ok()
+        } else {
+            None
+        };
+        let int_value: $type = checked.ok_or_else(|| {
+            let field = if $title.is_empty() {
+                String::new()
+            } else {
+                format!("field '{}': ", $title)
+            };
+            ProtocolError::ValidationFailed(format!(
+                "{}input '{}' (scaled to {}) is out of range for {}. Expected [{}, {}]",
+                field,
+                $input,
+                final_value,
+                stringify!($type),
+                <$type>::MIN,
+                <$type>::MAX
+            ))
+        })?;
 
         // 4. 转换为大端字节
         let bytes = int_value.to_be_bytes();
@@ -68,3 +90,156 @@ macro_rules! handle_int_encode {
         Ok(bytes.to_vec())
     }};
 }
+
+/// 由命令表生成命令枚举及其 Cmd / CmdTable 实现。
+/// 每行依次为: code, title, 方向 (DirectionEnum), 读写 (RW 或 None), 消息类型 (MsgTypeEnum 或 None)
+///
+/// ```ignore
+/// cmd_table! {
+///     pub enum GasCmd {
+///         SignIn => ("01", "注册", Upstream, None, SignIn),
+///         Valve => ("31", "阀门控制", Downstream, Write, ValveOperation),
+///     }
+/// }
+/// assert_eq!(GasCmd::from_code("31"), Some(GasCmd::Valve));
+/// ```
+#[macro_export]
+macro_rules! cmd_table {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident => ($code:expr, $title:expr, $direction:ident, $rw:ident, $msg_type:ident)),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        $vis enum $name {
+            $($variant),*
+        }
+
+        impl $crate::Cmd for $name {
+            fn code(&self) -> String {
+                match self {
+                    $($name::$variant => $code.to_string()),*
+                }
+            }
+
+            fn title(&self) -> String {
+                match self {
+                    $($name::$variant => $title.to_string()),*
+                }
+            }
+
+            fn direction(&self) -> $crate::DirectionEnum {
+                match self {
+                    $($name::$variant => $crate::DirectionEnum::$direction),*
+                }
+            }
+
+            fn rw(&self) -> Option<$crate::RW> {
+                match self {
+                    $($name::$variant => $crate::cmd_table!(@rw $rw)),*
+                }
+            }
+
+            fn msg_type(&self) -> Option<$crate::MsgTypeEnum> {
+                match self {
+                    $($name::$variant => $crate::cmd_table!(@msg_type $msg_type)),*
+                }
+            }
+        }
+
+        impl $crate::CmdTable for $name {
+            fn variants() -> Vec<Self> {
+                vec![$($name::$variant),*]
+            }
+        }
+
+        // 从归档的命令快照还原
+        impl TryFrom<$crate::CmdSnapshot> for $name {
+            type Error = $crate::ProtocolError;
+
+            fn try_from(snapshot: $crate::CmdSnapshot) -> Result<Self, Self::Error> {
+                <$name as $crate::CmdTable>::from_code(&snapshot.code).ok_or_else(|| {
+                    $crate::ProtocolError::ValidationFailed(format!(
+                        "unknown {} code {}",
+                        stringify!($name),
+                        snapshot.code
+                    ))
+                })
+            }
+        }
+    };
+    (@rw None) => { None };
+    (@rw $rw:ident) => { Some($crate::RW::$rw) };
+    (@msg_type None) => { None };
+    (@msg_type $msg_type:ident) => { Some($crate::MsgTypeEnum::$msg_type) };
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use crate::{
+        math_util::{self, DecimalRoundingMode},
+        FieldType, ProtocolError, ProtocolResult,
+    };
+    // Cmd/CmdTable 只在 std 下提供
+    #[cfg(feature = "std")]
+    use crate::{Cmd, CmdTable, MsgTypeEnum};
+
+    #[cfg(feature = "std")]
+    cmd_table! {
+        /// 测试用命令表
+        enum DemoCmd {
+            SignIn => ("01", "注册", Upstream, None, SignIn),
+            Valve => ("3A", "阀门控制", Downstream, Write, ValveOperation),
+            Query => ("40", "查询", Both, WriteThenRead, None),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_cmd_table() {
+        assert_eq!(DemoCmd::variants().len(), 3);
+        assert_eq!(DemoCmd::from_code("3a"), Some(DemoCmd::Valve));
+        assert_eq!(DemoCmd::from_code("FF"), None);
+        let valve = DemoCmd::Valve;
+        assert_eq!(valve.title(), "阀门控制");
+        assert!(valve.direction().is_downstream_only());
+        assert!(DemoCmd::SignIn.rw().is_none());
+        assert_eq!(
+            valve.msg_type().map(|m| m.code()),
+            Some(MsgTypeEnum::ValveOperation.code())
+        );
+        assert!(DemoCmd::Query.msg_type().is_none());
+    }
+
+    fn encode_u8(input: &str, scale: f64) -> ProtocolResult<Vec<u8>> {
+        handle_int_encode!(u8, 1, input, scale, "阀门状态")
+    }
+
+    #[test]
+    fn test_int_encode_range() {
+        assert_eq!(encode_u8("255", 1.0).unwrap(), [0xFF]);
+        assert_eq!(encode_u8("12.5", 0.1).unwrap(), [0x7D]);
+        // 超出 u8 范围报错，而不是截断为 300 & 0xFF
+        let err = encode_u8("300", 1.0).unwrap_err().to_string();
+        assert!(
+            err.contains("阀门状态") && err.contains("out of range for u8"),
+            "{}",
+            err
+        );
+        assert!(encode_u8("-1", 1.0).is_err());
+        assert!(encode_u8("25.6", 0.1).is_err());
+        assert!(encode_u8("1", 0.0).is_err());
+        // 经由 FieldType 编码时同样报错
+        assert!(FieldType::UnsignedU8(1.0).encode("300").is_err());
+        assert_eq!(FieldType::SignedI8(1.0).encode("-128").unwrap(), [0x80]);
+        assert!(FieldType::SignedI8(1.0).encode("128").is_err());
+    }
+}
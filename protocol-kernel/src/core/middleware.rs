@@ -0,0 +1,293 @@
+//! 解码前后的中间件链。解密、去重、指标、审计日志等与字段无关的处理登记为中间件，
+//! 协议实现只负责字段解码:
+//!
+//! ```ignore
+//! let chain = MiddlewareChain::new()
+//!     .pre("decrypt", |capsule, _ctx| decrypt(capsule))
+//!     .post("audit", |capsule, ctx| audit(capsule, ctx));
+//! chain.decode(&mut capsule, &mut MiddlewareContext::new("demo"), |capsule, _ctx| decode(capsule))?;
+//! ```
+use std::{collections::BTreeMap, sync::Arc};
+
+use protocol_base::ProtocolResult;
+
+use crate::{
+    bridge::trace,
+    core::parts::{raw_capsule::RawCapsule, traits::Cmd},
+};
+
+/// 中间件所处的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiddlewareStage {
+    PreDecode,
+    PostDecode,
+}
+
+/// 一次解码中各中间件共享的上下文
+#[derive(Debug, Clone)]
+pub struct MiddlewareContext {
+    pub protocol: String,
+    pub trace_id: Option<String>,
+    stage: MiddlewareStage,
+    // 中间件之间传递的信息，如解密所用的密钥槽
+    attrs: BTreeMap<String, String>,
+    halted: bool,
+}
+
+impl MiddlewareContext {
+    pub fn new(protocol: &str) -> Self {
+        Self {
+            protocol: protocol.into(),
+            trace_id: trace::current_trace_id(),
+            stage: MiddlewareStage::PreDecode,
+            attrs: BTreeMap::new(),
+            halted: false,
+        }
+    }
+
+    pub fn stage(&self) -> MiddlewareStage {
+        self.stage
+    }
+
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs.get(key).map(|v| v.as_str())
+    }
+
+    pub fn set_attr(&mut self, key: &str, value: &str) {
+        self.attrs.insert(key.into(), value.into());
+    }
+
+    pub fn attrs(&self) -> &BTreeMap<String, String> {
+        &self.attrs
+    }
+
+    /// 终止本次解码 (如重复帧)，之后的 pre 中间件与字段解码都不再执行，post 中间件照常执行
+    pub fn halt(&mut self) {
+        self.halted = true;
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+}
+
+pub type Middleware<T> =
+    Arc<dyn Fn(&mut RawCapsule<T>, &mut MiddlewareContext) -> ProtocolResult<()> + Send + Sync>;
+
+/// 按登记顺序执行的中间件链
+pub struct MiddlewareChain<T: Cmd> {
+    pre: Vec<(String, Middleware<T>)>,
+    post: Vec<(String, Middleware<T>)>,
+}
+
+impl<T: Cmd> Clone for MiddlewareChain<T> {
+    fn clone(&self) -> Self {
+        Self {
+            pre: self.pre.clone(),
+            post: self.post.clone(),
+        }
+    }
+}
+
+impl<T: Cmd> Default for MiddlewareChain<T> {
+    fn default() -> Self {
+        Self {
+            pre: Vec::new(),
+            post: Vec::new(),
+        }
+    }
+}
+
+impl<T: Cmd + 'static> MiddlewareChain<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记解码前执行的中间件
+    pub fn pre<F>(mut self, name: &str, middleware: F) -> Self
+    where
+        F: Fn(&mut RawCapsule<T>, &mut MiddlewareContext) -> ProtocolResult<()>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.pre.push((name.into(), Arc::new(middleware)));
+        self
+    }
+
+    /// 登记解码后执行的中间件，解码失败时也会执行 (capsule 已标记为失败)
+    pub fn post<F>(mut self, name: &str, middleware: F) -> Self
+    where
+        F: Fn(&mut RawCapsule<T>, &mut MiddlewareContext) -> ProtocolResult<()>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.post.push((name.into(), Arc::new(middleware)));
+        self
+    }
+
+    /// 已登记的中间件名称，按执行顺序
+    pub fn names(&self, stage: MiddlewareStage) -> Vec<&str> {
+        let list = match stage {
+            MiddlewareStage::PreDecode => &self.pre,
+            MiddlewareStage::PostDecode => &self.post,
+        };
+        list.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn run_pre(
+        &self,
+        capsule: &mut RawCapsule<T>,
+        ctx: &mut MiddlewareContext,
+    ) -> ProtocolResult<()> {
+        ctx.stage = MiddlewareStage::PreDecode;
+        for (name, middleware) in &self.pre {
+            if ctx.halted {
+                break;
+            }
+            run(name, middleware, capsule, ctx)?;
+        }
+        Ok(())
+    }
+
+    pub fn run_post(
+        &self,
+        capsule: &mut RawCapsule<T>,
+        ctx: &mut MiddlewareContext,
+    ) -> ProtocolResult<()> {
+        ctx.stage = MiddlewareStage::PostDecode;
+        for (name, middleware) in &self.post {
+            run(name, middleware, capsule, ctx)?;
+        }
+        Ok(())
+    }
+
+    /// pre 中间件 -> decode -> post 中间件。decode 失败时先执行 post 再返回 decode 的错误
+    pub fn decode<F>(
+        &self,
+        capsule: &mut RawCapsule<T>,
+        ctx: &mut MiddlewareContext,
+        decode: F,
+    ) -> ProtocolResult<()>
+    where
+        F: FnOnce(&mut RawCapsule<T>, &mut MiddlewareContext) -> ProtocolResult<()>,
+    {
+        self.run_pre(capsule, ctx)?;
+        let decoded = if ctx.halted {
+            Ok(())
+        } else {
+            decode(capsule, ctx)
+        };
+        match decoded {
+            Ok(()) => {
+                capsule.mark_decoded();
+                self.run_post(capsule, ctx)
+            }
+            Err(e) => {
+                capsule.fail();
+                if let Err(post) = self.run_post(capsule, ctx) {
+                    eprintln!(
+                        "[WARN] {}post-decode middleware failed after decode error: {}",
+                        trace::log_prefix(),
+                        post
+                    );
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+fn run<T: Cmd>(
+    name: &str,
+    middleware: &Middleware<T>,
+    capsule: &mut RawCapsule<T>,
+    ctx: &mut MiddlewareContext,
+) -> ProtocolResult<()> {
+    middleware(capsule, ctx).inspect_err(|e| {
+        eprintln!(
+            "[WARN] {}middleware '{}' failed: {}",
+            trace::log_prefix(),
+            name,
+            e
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd_table, ProtocolError};
+
+    cmd_table! {
+        enum MeterCmd {
+            Report => ("02", "数据上报", Upstream, None, DataReport),
+        }
+    }
+
+    fn chain() -> MiddlewareChain<MeterCmd> {
+        MiddlewareChain::new()
+            .pre("decrypt", |capsule, ctx| {
+                // 模拟解密: 每字节异或 0x55
+                let plain: Vec<u8> = capsule.bytes().iter().map(|b| b ^ 0x55).collect();
+                capsule.set_bytes_and_generate_hex(&plain)?;
+                ctx.set_attr("order", "decrypt");
+                Ok(())
+            })
+            .pre("dedup", |capsule, ctx| {
+                let order = format!("{},dedup", ctx.attr("order").unwrap_or_default());
+                ctx.set_attr("order", &order);
+                if capsule.hex() == "FFFF" {
+                    ctx.halt();
+                }
+                Ok(())
+            })
+            .post("audit", |capsule, ctx| {
+                ctx.set_attr(
+                    "audit",
+                    &format!("{}:{}", capsule.hex(), capsule.is_success()),
+                );
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_chain_order() {
+        let chain = chain();
+        assert_eq!(
+            chain.names(MiddlewareStage::PreDecode),
+            ["decrypt", "dedup"]
+        );
+
+        let mut capsule = RawCapsule::<MeterCmd>::new_upstream(&[0x3D, 0x57]);
+        let mut ctx = MiddlewareContext::new("demo");
+        chain
+            .decode(&mut capsule, &mut ctx, |capsule, ctx| {
+                assert_eq!(ctx.stage(), MiddlewareStage::PreDecode);
+                capsule.set_cmd(MeterCmd::Report);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(ctx.attr("order"), Some("decrypt,dedup"));
+        assert_eq!(ctx.attr("audit"), Some("6802:true"));
+        assert!(capsule.decoded_at().is_some());
+
+        // 解码失败时 post 仍执行
+        let mut capsule = RawCapsule::<MeterCmd>::new_upstream(&[0x3D, 0x57]);
+        let mut ctx = MiddlewareContext::new("demo");
+        let err = chain.decode(&mut capsule, &mut ctx, |_, _| {
+            Err(ProtocolError::CommonError("bad field".into()))
+        });
+        assert!(err.is_err());
+        assert_eq!(ctx.attr("audit"), Some("6802:false"));
+
+        // halt 跳过解码
+        let mut capsule = RawCapsule::<MeterCmd>::new_upstream(&[0xAA, 0xAA]);
+        let mut ctx = MiddlewareContext::new("demo");
+        chain
+            .decode(&mut capsule, &mut ctx, |_, _| unreachable!())
+            .unwrap();
+        assert!(ctx.is_halted());
+    }
+}
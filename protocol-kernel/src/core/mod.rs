@@ -1,21 +1,90 @@
-use protocol_base::{ProtocolResult, ProtocolError};
+use alloc::string::{String, ToString};
+use protocol_base::{ProtocolError, ProtocolResult};
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "std")]
+pub mod aggregate;
+#[cfg(feature = "std")]
+pub mod backend;
+#[cfg(feature = "std")]
 pub mod cache;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod context;
+#[cfg(feature = "std")]
+pub mod dedup;
+#[cfg(feature = "std")]
+pub mod definition_store;
+#[cfg(feature = "std")]
+pub mod device_lock;
+#[cfg(feature = "std")]
+pub mod dispatch;
+#[cfg(feature = "std")]
+pub mod downlink;
+#[cfg(feature = "std")]
+pub mod dsl;
+#[cfg(feature = "std")]
+pub mod envelope;
+#[cfg(feature = "std")]
+pub mod event;
+#[cfg(feature = "std")]
+pub mod idempotency;
+#[cfg(feature = "std")]
+pub mod inspector;
+#[cfg(feature = "std")]
+pub mod json_mapper;
 mod macro_plugin;
+#[cfg(feature = "std")]
+pub mod middleware;
+#[cfg(feature = "std")]
+pub mod ota;
+#[cfg(feature = "std")]
+pub mod parallel;
 pub mod parts;
+#[cfg(feature = "std")]
+pub mod pool;
+#[cfg(feature = "std")]
+pub mod quarantine;
 pub mod reader;
+#[cfg(feature = "std")]
+pub mod reassembly;
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod sniffer;
+#[cfg(feature = "std")]
+pub mod stateful;
+#[cfg(feature = "std")]
+pub mod streaming;
 pub mod type_converter;
+#[cfg(feature = "std")]
+pub mod validation;
 pub mod writer;
 
-#[derive(Debug, Clone)]
+/// 解码模式。Strict: 任一字段失败即中止；Lenient: 失败字段记为告警字段 (值为错误信息) 后继续解码
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DecodeMode {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+impl DecodeMode {
+    pub fn is_lenient(&self) -> bool {
+        matches!(self, DecodeMode::Lenient)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RW {
     Read,
     Write,
     WriteThenRead,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 /// 方向
 pub enum DirectionEnum {
     Upstream,   // 上行
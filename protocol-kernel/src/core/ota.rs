@@ -0,0 +1,193 @@
+use protocol_base::definitions::defi::CrcType;
+use serde::{Deserialize, Serialize};
+
+use crate::{utils::crc_util, ProtocolError, ProtocolResult};
+
+/// 固件中的一个分段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtaSegment<'a> {
+    // 从 0 开始
+    pub index: u16,
+    pub total: u16,
+    pub offset: usize,
+    pub data: &'a [u8],
+    pub crc: u16,
+}
+
+/// 升级进度，可以存入 TransportCarrier::set_ext，网关重启或表端掉线后据此续传
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OtaProgress {
+    pub image_len: usize,
+    pub image_crc: u16,
+    pub segment_size: usize,
+    // 已确认分段的位图，低位在前
+    pub acked: Vec<u8>,
+}
+
+/// 固件升级会话: 把固件按协议的分段大小切开，生成下行帧，并根据上行确认跟踪进度
+#[derive(Debug, Clone)]
+pub struct OtaSession {
+    image: Vec<u8>,
+    segment_size: usize,
+    crc_type: CrcType,
+    image_crc: u16,
+    acked: Vec<bool>,
+}
+
+impl OtaSession {
+    pub fn new(image: &[u8], segment_size: usize, crc_type: CrcType) -> ProtocolResult<Self> {
+        if image.is_empty() || segment_size == 0 {
+            return Err(ProtocolError::ValidationFailed(
+                "ota image and segment size must not be empty".into(),
+            ));
+        }
+        let total = image.len().div_ceil(segment_size);
+        if total > u16::MAX as usize {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "ota image of {} bytes needs {} segments, more than {}",
+                image.len(),
+                total,
+                u16::MAX
+            )));
+        }
+        Ok(Self {
+            image: image.to_vec(),
+            segment_size,
+            crc_type,
+            image_crc: crc_util::calculate_from_bytes(crc_type, image)?,
+            acked: vec![false; total],
+        })
+    }
+
+    /// 按保存的进度续传。固件或分段大小变了时返回错误，需要重新开始
+    pub fn resume(image: &[u8], crc_type: CrcType, progress: &OtaProgress) -> ProtocolResult<Self> {
+        let mut session = Self::new(image, progress.segment_size, crc_type)?;
+        if session.image.len() != progress.image_len || session.image_crc != progress.image_crc {
+            return Err(ProtocolError::ValidationFailed(
+                "ota progress does not belong to this image".into(),
+            ));
+        }
+        session.ack_bitmap(&progress.acked);
+        Ok(session)
+    }
+
+    pub fn progress(&self) -> OtaProgress {
+        let mut acked = vec![0u8; self.acked.len().div_ceil(8)];
+        for (i, _) in self.acked.iter().enumerate().filter(|(_, a)| **a) {
+            acked[i / 8] |= 1 << (i % 8);
+        }
+        OtaProgress {
+            image_len: self.image.len(),
+            image_crc: self.image_crc,
+            segment_size: self.segment_size,
+            acked,
+        }
+    }
+
+    pub fn total_segments(&self) -> u16 {
+        self.acked.len() as u16
+    }
+
+    /// 整个固件的 CRC，通常在升级开始帧中下发
+    pub fn image_crc(&self) -> u16 {
+        self.image_crc
+    }
+
+    pub fn segment(&self, index: u16) -> ProtocolResult<OtaSegment<'_>> {
+        let total = self.total_segments();
+        if index >= total {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "ota segment {} out of range (total {})",
+                index, total
+            )));
+        }
+        let offset = index as usize * self.segment_size;
+        let end = (offset + self.segment_size).min(self.image.len());
+        let data = &self.image[offset..end];
+        Ok(OtaSegment {
+            index,
+            total,
+            offset,
+            data,
+            crc: crc_util::calculate_from_bytes(self.crc_type, data)?,
+        })
+    }
+
+    /// 尚未确认的分段序号
+    pub fn pending(&self) -> Vec<u16> {
+        (0..self.total_segments())
+            .filter(|i| !self.acked[*i as usize])
+            .collect()
+    }
+
+    /// 为未确认的分段生成下行帧，encode 负责按协议组帧
+    pub fn frames<F>(&self, mut encode: F) -> ProtocolResult<Vec<Vec<u8>>>
+    where
+        F: FnMut(&OtaSegment) -> ProtocolResult<Vec<u8>>,
+    {
+        self.pending()
+            .into_iter()
+            .map(|i| encode(&self.segment(i)?))
+            .collect()
+    }
+
+    pub fn ack(&mut self, index: u16) -> ProtocolResult<()> {
+        let slot = self.acked.get_mut(index as usize).ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!("ota ack for unknown segment {}", index))
+        })?;
+        *slot = true;
+        Ok(())
+    }
+
+    /// 表端上报的接收位图 (低位在前)，置位的分段视为已确认
+    pub fn ack_bitmap(&mut self, bitmap: &[u8]) {
+        for (i, acked) in self.acked.iter_mut().enumerate() {
+            if bitmap.get(i / 8).is_some_and(|b| b & (1 << (i % 8)) != 0) {
+                *acked = true;
+            }
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.acked.iter().all(|a| *a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_and_resume() {
+        let image: Vec<u8> = (0..=20).collect();
+        let mut session = OtaSession::new(&image, 8, CrcType::Crc16Modbus).unwrap();
+        assert_eq!(session.total_segments(), 3);
+        let last = session.segment(2).unwrap();
+        assert_eq!((last.offset, last.data.len()), (16, 5));
+
+        // 帧: 序号 + 总数 + 数据 + CRC
+        let frames = session
+            .frames(|s| {
+                let mut frame = vec![s.index as u8, s.total as u8];
+                frame.extend_from_slice(s.data);
+                frame.extend_from_slice(&s.crc.to_be_bytes());
+                Ok(frame)
+            })
+            .unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(&frames[1][..3], &[1, 3, 8]);
+
+        session.ack(0).unwrap();
+        session.ack_bitmap(&[0b100]);
+        assert_eq!(session.pending(), vec![1]);
+        assert!(session.ack(3).is_err());
+
+        let progress = session.progress();
+        let resumed = OtaSession::resume(&image, CrcType::Crc16Modbus, &progress).unwrap();
+        assert_eq!(resumed.pending(), vec![1]);
+        let mut other = image.clone();
+        other[0] = 0xFF;
+        assert!(OtaSession::resume(&other, CrcType::Crc16Modbus, &progress).is_err());
+    }
+}
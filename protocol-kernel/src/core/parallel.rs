@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use protocol_base::{ProtocolError, ProtocolResult};
+use rayon::prelude::*;
+
+use crate::{bridge::guard::catch_panic, core::config::ProtocolConfig};
+
+/// 按协议配置的地址域区分设备，并行解码一批存量报文 (如协议修正后重新解析历史数据)。
+/// 同一设备的帧在同一任务内按原顺序处理，结果与 frames 一一对应。
+/// 在 rayon 全局线程池上执行
+pub fn decode_batch<T, F>(
+    config: &ProtocolConfig,
+    frames: Vec<Vec<u8>>,
+    handler: F,
+) -> Vec<ProtocolResult<T>>
+where
+    T: Send,
+    F: Fn(&[u8]) -> ProtocolResult<T> + Sync,
+{
+    let total = frames.len();
+    let groups = group_by_device(frames, |frame| config.address_of(frame).ok().flatten());
+    decode_groups(groups, total, &handler)
+}
+
+/// 同 decode_batch，自定义线程数与设备 key。
+/// key 返回 None 的帧没有顺序要求，各自单独调度
+pub fn decode_batch_by<T, K, F>(
+    frames: Vec<Vec<u8>>,
+    workers: usize,
+    key: K,
+    handler: F,
+) -> Vec<ProtocolResult<T>>
+where
+    T: Send,
+    K: Fn(&[u8]) -> Option<String>,
+    F: Fn(&[u8]) -> ProtocolResult<T> + Sync,
+{
+    let total = frames.len();
+    let groups = group_by_device(frames, key);
+    match rayon::ThreadPoolBuilder::new()
+        .num_threads(workers.clamp(1, total.max(1)))
+        .build()
+    {
+        Ok(pool) => pool.install(|| decode_groups(groups, total, &handler)),
+        // 无法创建线程池时退回全局线程池
+        Err(_) => decode_groups(groups, total, &handler),
+    }
+}
+
+// 按设备 key 分组，组内保持 frames 中的原顺序；key 为 None 的帧各成一组
+fn group_by_device<K>(frames: Vec<Vec<u8>>, key: K) -> Vec<Vec<(usize, Vec<u8>)>>
+where
+    K: Fn(&[u8]) -> Option<String>,
+{
+    let mut groups: Vec<Vec<(usize, Vec<u8>)>> = Vec::new();
+    let mut devices: HashMap<String, usize> = HashMap::new();
+    for (index, frame) in frames.into_iter().enumerate() {
+        let group = match key(&frame) {
+            Some(device) => *devices.entry(device).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            }),
+            None => {
+                groups.push(Vec::new());
+                groups.len() - 1
+            }
+        };
+        groups[group].push((index, frame));
+    }
+    groups
+}
+
+fn decode_groups<T, F>(
+    groups: Vec<Vec<(usize, Vec<u8>)>>,
+    total: usize,
+    handler: &F,
+) -> Vec<ProtocolResult<T>>
+where
+    T: Send,
+    F: Fn(&[u8]) -> ProtocolResult<T> + Sync,
+{
+    let decoded: Vec<Vec<(usize, ProtocolResult<T>)>> = groups
+        .into_par_iter()
+        .map(|group| {
+            // 组内顺序执行，同一设备的帧不会并发
+            group
+                .into_iter()
+                .map(|(index, frame)| (index, catch_panic(|| handler(&frame))))
+                .collect()
+        })
+        .collect();
+
+    let mut results: Vec<Option<ProtocolResult<T>>> = (0..total).map(|_| None).collect();
+    for (index, result) in decoded.into_iter().flatten() {
+        results[index] = Some(result);
+    }
+    results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| {
+                Err(ProtocolError::CommonError(
+                    "decode worker exited unexpectedly".into(),
+                ))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_decode_batch() {
+        let config = ProtocolConfig::new("demo", 2, 1)
+            .with_head(&[0x68])
+            .with_address(1, 1)
+            .with_tail(&[0x16]);
+        // 帧: 68 地址 序号 16
+        let frames: Vec<Vec<u8>> = (0..40u8).map(|i| vec![0x68, i % 4, i, 0x16]).collect();
+        let seen = Mutex::new(Vec::new());
+        let results = decode_batch(&config, frames, |frame| {
+            if frame[2] == 7 {
+                panic!("bad frame");
+            }
+            seen.lock().unwrap().push((frame[1], frame[2]));
+            Ok(frame[2])
+        });
+
+        assert_eq!(results.len(), 40);
+        for (i, result) in results.iter().enumerate() {
+            match result {
+                Ok(seq) => assert_eq!(*seq as usize, i),
+                Err(e) => assert!(i == 7 && e.to_string().contains("bad frame")),
+            }
+        }
+        // 同一地址的帧按原顺序处理
+        let seen = seen.into_inner().unwrap();
+        for address in 0..4 {
+            let order: Vec<u8> = seen
+                .iter()
+                .filter(|(a, _)| *a == address)
+                .map(|(_, seq)| *seq)
+                .collect();
+            assert!(order.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+
+    #[test]
+    fn test_decode_batch_by_workers() {
+        let frames: Vec<Vec<u8>> = (0..20u8).map(|i| vec![i % 3, i]).collect();
+        let results = decode_batch_by(
+            frames,
+            2,
+            |frame| (frame[0] != 0).then(|| frame[0].to_string()),
+            |frame| {
+                assert!(rayon::current_num_threads() <= 2);
+                Ok(frame[1])
+            },
+        );
+        let seqs: Vec<u8> = results.into_iter().map(Result::unwrap).collect();
+        assert_eq!(seqs, (0..20u8).collect::<Vec<_>>());
+        assert!(decode_batch_by(Vec::new(), 4, |_| None, |f| Ok(f.len())).is_empty());
+    }
+}
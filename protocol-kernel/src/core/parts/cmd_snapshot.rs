@@ -0,0 +1,194 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+
+use crate::{
+    bridge::as_micros,
+    core::parts::{
+        diagnostic::Diagnostic,
+        raw_capsule::{self, RawCapsule},
+        traits::Cmd,
+    },
+    DirectionEnum, MsgTypeEnum, ReportField, RW,
+};
+
+/// Cmd 的可序列化快照。归档的 RawCapsule 中保存的是它，
+/// 离线工具没有协议的命令枚举时可以直接用 RawCapsule<CmdSnapshot> 读取
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CmdSnapshot {
+    pub code: String,
+    pub title: String,
+    pub direction: DirectionEnum,
+    #[serde(default)]
+    pub rw: Option<RW>,
+    #[serde(default)]
+    pub msg_type: Option<MsgTypeEnum>,
+    #[serde(default = "default_success")]
+    pub success: bool,
+}
+
+fn default_success() -> bool {
+    true
+}
+
+impl CmdSnapshot {
+    pub fn of<T: Cmd + ?Sized>(cmd: &T) -> Self {
+        Self {
+            code: cmd.code(),
+            title: cmd.title(),
+            direction: cmd.direction(),
+            rw: cmd.rw(),
+            msg_type: cmd.msg_type(),
+            success: cmd.is_success(),
+        }
+    }
+}
+
+impl Cmd for CmdSnapshot {
+    fn code(&self) -> String {
+        self.code.clone()
+    }
+
+    fn title(&self) -> String {
+        self.title.clone()
+    }
+
+    fn direction(&self) -> DirectionEnum {
+        self.direction.clone()
+    }
+
+    fn rw(&self) -> Option<RW> {
+        self.rw.clone()
+    }
+
+    fn msg_type(&self) -> Option<MsgTypeEnum> {
+        self.msg_type.clone()
+    }
+
+    fn is_success(&self) -> bool {
+        self.success
+    }
+}
+
+// RawCapsule 的序列化形式。时间点 (Instant) 无法跨进程保存，只保留耗时；payload 不保存
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CapsuleRecord {
+    hex: String,
+    #[serde(default)]
+    field_details: Vec<ReportField>,
+    #[serde(default)]
+    cmd: Option<CmdSnapshot>,
+    #[serde(default)]
+    device_no: Option<String>,
+    #[serde(default)]
+    device_id: Option<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    temp_hex: String,
+    direction: DirectionEnum,
+    success: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<Diagnostic>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    decode_micros: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    encode_micros: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    idempotency_token: Option<String>,
+}
+
+impl<T: Cmd + 'static> Serialize for RawCapsule<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        CapsuleRecord {
+            hex: self.hex.clone(),
+            field_details: self.field_details.clone(),
+            cmd: self.cmd.as_ref().map(CmdSnapshot::of),
+            device_no: self.device_no.clone(),
+            device_id: self.device_id.clone(),
+            temp_hex: hex::encode_upper(&self.temp_bytes),
+            direction: self.direction.clone(),
+            success: self.success,
+            warnings: self.warnings.clone(),
+            decode_micros: self.decode_duration().map(as_micros),
+            encode_micros: self.encode_duration().map(as_micros),
+            idempotency_token: self.idempotency_token.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// 反序列化时命令通过 TryFrom<CmdSnapshot> 还原: RawCapsule<CmdSnapshot> 直接可用，
+/// cmd_table! 生成的命令表按 code 反查
+impl<'de, T> Deserialize<'de> for RawCapsule<T>
+where
+    T: Cmd + TryFrom<CmdSnapshot>,
+    T::Error: Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let record = CapsuleRecord::deserialize(deserializer)?;
+        let bytes = hex::decode(&record.hex).map_err(D::Error::custom)?;
+        let temp_bytes = hex::decode(&record.temp_hex).map_err(D::Error::custom)?;
+        let cmd = record
+            .cmd
+            .map(T::try_from)
+            .transpose()
+            .map_err(D::Error::custom)?;
+        Ok(Self {
+            bytes: bytes.into(),
+            hex: record.hex,
+            field_details: record.field_details,
+            cmd,
+            device_no: record.device_no,
+            device_id: record.device_id,
+            temp_bytes: temp_bytes.into(),
+            direction: record.direction,
+            success: record.success,
+            warnings: record.warnings,
+            received_at: raw_capsule::now(),
+            decoded_at: None,
+            encoded_at: None,
+            payload: None,
+            idempotency_token: record.idempotency_token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cmd_table, RawChamber};
+
+    cmd_table! {
+        enum ReplayCmd {
+            Report => ("02", "数据上报", Upstream, None, DataReport),
+            Ack => ("82", "上报应答", Downstream, Write, None),
+        }
+    }
+
+    #[test]
+    fn test_chamber_round_trip() {
+        let mut up = RawCapsule::new_upstream(&[0x68, 0x02, 0x16]);
+        up.set_cmd(ReplayCmd::Report);
+        up.set_device_no("0001");
+        up.set_fields(vec![ReportField::new("t", "温度", "21.5".into())]);
+        let mut down = RawCapsule::new_downstream_from_upstream(&up);
+        down.set_cmd(ReplayCmd::Ack);
+        down.set_bytes_and_generate_hex(&[0x68, 0x82, 0x16]).unwrap();
+        let chamber = RawChamber::new(&up, &down);
+
+        let json = serde_json::to_string(&chamber).unwrap();
+        let typed: RawChamber<ReplayCmd> = serde_json::from_str(&json).unwrap();
+        assert_eq!(typed.downstream().unwrap().cmd(), Some(&ReplayCmd::Ack));
+        assert_eq!(typed.upstream().unwrap().bytes(), &[0x68, 0x02, 0x16]);
+        assert_eq!(typed.cmd_code(), "82");
+
+        // 没有命令枚举时按快照读取
+        let offline: RawChamber<CmdSnapshot> = serde_json::from_str(&json).unwrap();
+        let up = offline.upstream().unwrap();
+        assert_eq!(up.cmd().unwrap().title, "数据上报");
+        assert_eq!(up.field_details()[0].value, "21.5");
+        assert_eq!(up.device_no(), Some("0001"));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::hex_util;
+use crate::{hex_util, ProtocolResult};
 
 // 拦截器。如果bytes跟输入值匹配上了，就返回value_if_matches
 pub struct DecodingFilter {
@@ -7,12 +7,17 @@ pub struct DecodingFilter {
 }
 
 impl DecodingFilter {
+    /// hex 非法时会 panic，JNI/FFI 调用链上请使用 `try_new_from_hex`
     pub fn new_from_hex(hex: &str, matched_title: String) -> Self {
-        let bytes = hex_util::hex_to_bytes(hex).unwrap();
-        DecodingFilter {
+        Self::try_new_from_hex(hex, matched_title).unwrap()
+    }
+
+    pub fn try_new_from_hex(hex: &str, matched_title: String) -> ProtocolResult<Self> {
+        let bytes = hex_util::hex_to_bytes(hex)?;
+        Ok(DecodingFilter {
             bytes,
             value_if_matches: matched_title,
-        }
+        })
     }
 
     pub fn new(bytes: Vec<u8>, matched_title: String) -> Self {
@@ -26,12 +31,30 @@ impl DecodingFilter {
         self.bytes == input_bytes
     }
 
+    // 非法的 hex 视为不匹配
     pub fn matches_hex(&self, input_hex: &str) -> bool {
-        let bytes = hex_util::hex_to_bytes(input_hex).unwrap();
-        self.matches(&bytes)
+        hex_util::hex_to_bytes(input_hex)
+            .map(|bytes| self.matches(&bytes))
+            .unwrap_or(false)
     }
 
     pub fn title(&self) -> String {
         self.value_if_matches.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_new_from_hex() {
+        let filter = DecodingFilter::try_new_from_hex("FFFF", "无数据".into()).unwrap();
+        assert!(filter.matches(&[0xFF, 0xFF]));
+        assert!(!filter.matches(&[0xFF]));
+        assert!(filter.matches_hex("ffff"));
+        assert!(!filter.matches_hex("FFFG"));
+        assert_eq!(filter.title(), "无数据");
+        assert!(DecodingFilter::try_new_from_hex("XYZ", "无数据".into()).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+use alloc::string::String;
+use serde::{Deserialize, Serialize};
+
+// 非致命的解码诊断信息。例如 crc 正确但保留字节不为 0、时间戳超出范围等，
+// 不影响 success，但需要随结果一起上报
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub code: String,
+    pub message: String,
+    #[serde(default)]
+    pub field: Option<String>,
+    #[serde(default)]
+    pub offset: Option<usize>,
+}
+
+impl Diagnostic {
+    pub fn new(code: &str, message: &str) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+            field: None,
+            offset: None,
+        }
+    }
+
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+}
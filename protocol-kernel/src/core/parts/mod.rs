@@ -1,8 +1,23 @@
+#[cfg(feature = "std")]
+pub mod cmd_snapshot;
+#[cfg(feature = "std")]
 pub mod decoding_filter;
+pub mod diagnostic;
 pub mod placeholder;
+#[cfg(feature = "std")]
 pub mod raw_capsule;
+#[cfg(feature = "std")]
+pub mod raw_capsule_builder;
+#[cfg(feature = "std")]
 pub mod raw_chamber;
 pub mod rawfield;
+pub mod report_field;
+#[cfg(feature = "std")]
+pub mod schema;
+pub mod title;
+#[cfg(feature = "std")]
 pub mod traits;
+#[cfg(feature = "std")]
 pub mod transport_carrier;
+#[cfg(feature = "std")]
 pub mod transport_pair;
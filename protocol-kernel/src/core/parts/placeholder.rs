@@ -1,3 +1,5 @@
+use alloc::string::String;
+
 // 占位符
 #[derive(Debug, Clone, Default)]
 pub struct PlaceHolder {
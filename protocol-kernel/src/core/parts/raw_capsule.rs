@@ -1,40 +1,89 @@
-use crate::{core::parts::traits::Cmd, DirectionEnum, ProtocolError, ReportField};
+use crate::{
+    core::{
+        idempotency::idempotency_token,
+        parts::{diagnostic::Diagnostic, traits::Cmd},
+    },
+    DirectionEnum, ProtocolError, ReportField,
+};
+use bytes::Bytes;
 use dyn_clone::DynClone;
+use std::{
+    any::Any,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+// wasm32-unknown-unknown 上 Instant::now() 会 panic，那里不记录时间点
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) fn now() -> Option<Instant> {
+    Some(Instant::now())
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) fn now() -> Option<Instant> {
+    None
+}
 
 // 报文上/下行解析 处理之后的结果 第二小解析单位，比RawField大
 #[derive(Debug, Clone)]
 pub struct RawCapsule<T: Cmd> {
-    pub(crate) bytes: Vec<u8>,
+    // 原始报文，clone 与 Reader::from_shared 解析都只增加引用计数
+    pub(crate) bytes: Bytes,
     pub(crate) hex: String,
     pub(crate) field_details: Vec<ReportField>,
     pub(crate) cmd: Option<T>,
     pub(crate) device_no: Option<String>,
     pub(crate) device_id: Option<String>,
     // 临时二进制存放处
-    pub(crate) temp_bytes: Vec<u8>,
+    pub(crate) temp_bytes: Bytes,
     pub(crate) direction: DirectionEnum,
     pub(crate) success: bool,
+    // 非致命的诊断信息，success 为 true 时也可能存在
+    pub(crate) warnings: Vec<Diagnostic>,
+    // 生命周期时间点: 收到 (创建) / 解析完成 / 编码完成，没有单调时钟的平台上均为 None
+    pub(crate) received_at: Option<Instant>,
+    pub(crate) decoded_at: Option<Instant>,
+    pub(crate) encoded_at: Option<Instant>,
+    // 解析出的强类型结构，业务层可直接使用而不必再解析 field_details。不参与序列化
+    pub(crate) payload: Option<Arc<dyn Any + Send + Sync>>,
+    // 下行命令的幂等令牌，见 attach_idempotency_token
+    pub(crate) idempotency_token: Option<String>,
 }
 
 impl<T: Cmd + 'static> RawCapsule<T> {
     pub fn new_upstream(bytes: &[u8]) -> Self {
-        let hex = hex::encode_upper(bytes);
+        Self::new_upstream_shared(Bytes::copy_from_slice(bytes))
+    }
+
+    /// 直接持有收到的报文 (如网络层的 `Bytes`)，不复制
+    pub fn new_upstream_shared(bytes: Bytes) -> Self {
+        let hex = hex::encode_upper(&bytes);
         Self {
-            bytes: bytes.to_vec(),
+            bytes,
             hex,
             field_details: Vec::new(),
             cmd: None,
             device_no: None,
             device_id: None,
-            temp_bytes: Vec::new(),
+            temp_bytes: Bytes::new(),
             direction: DirectionEnum::Upstream,
             success: true,
+            warnings: Vec::new(),
+            received_at: now(),
+            decoded_at: None,
+            encoded_at: None,
+            payload: None,
+            idempotency_token: None,
         }
     }
 
+    pub fn builder<'a>() -> crate::core::parts::raw_capsule_builder::RawCapsuleBuilder<'a, T> {
+        crate::core::parts::raw_capsule_builder::RawCapsuleBuilder::new()
+    }
+
     pub fn new_downstream(cmd: T, device_no: &str, device_id: &str) -> Self {
         Self {
-            bytes: Vec::new(),
+            bytes: Bytes::new(),
             hex: String::new(),
             field_details: Vec::new(),
             cmd: Some(cmd),
@@ -44,9 +93,15 @@ impl<T: Cmd + 'static> RawCapsule<T> {
             } else {
                 Some(device_id.into())
             },
-            temp_bytes: Vec::new(),
+            temp_bytes: Bytes::new(),
             direction: DirectionEnum::Downstream,
             success: true,
+            warnings: Vec::new(),
+            received_at: now(),
+            decoded_at: None,
+            encoded_at: None,
+            payload: None,
+            idempotency_token: None,
         }
     }
 
@@ -86,15 +141,21 @@ impl<T: Cmd + 'static> RawCapsule<T> {
             None
         };
         Self {
-            bytes: Vec::new(),
+            bytes: Bytes::new(),
             hex: String::new(),
             field_details: Vec::new(),
             cmd: up_stream_capsule.cmd_clone(),
             device_no,
             device_id,
-            temp_bytes: Vec::new(),
+            temp_bytes: Bytes::new(),
             direction: DirectionEnum::Downstream,
             success: true,
+            warnings: Vec::new(),
+            received_at: now(),
+            decoded_at: None,
+            encoded_at: None,
+            payload: None,
+            idempotency_token: None,
         }
     }
 
@@ -110,11 +171,36 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         self.success
     }
 
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    pub fn warnings_clone(&self) -> Vec<Diagnostic> {
+        self.warnings.clone()
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+
+    pub fn add_warning(&mut self, diagnostic: Diagnostic) {
+        self.warnings.push(diagnostic);
+    }
+
+    pub fn append_warnings(&mut self, warnings: Vec<Diagnostic>) {
+        self.warnings.extend(warnings);
+    }
+
     pub fn bytes(&self) -> &[u8] {
         &self.bytes
     }
 
     pub fn bytes_clone(&self) -> Vec<u8> {
+        self.bytes.to_vec()
+    }
+
+    /// 共享的报文引用，可交给 `Reader::from_shared` 做零拷贝解析
+    pub fn shared_bytes(&self) -> Bytes {
         self.bytes.clone()
     }
 
@@ -166,7 +252,7 @@ impl<T: Cmd + 'static> RawCapsule<T> {
     }
 
     pub fn temp_bytes_clone(&self) -> Vec<u8> {
-        self.temp_bytes.clone()
+        self.temp_bytes.to_vec()
     }
 
     pub fn direction(&self) -> &DirectionEnum {
@@ -186,11 +272,100 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         &mut self,
         bytes: &[u8],
     ) -> protocol_base::ProtocolResult<()> {
-        self.bytes = bytes.to_vec();
-        self.hex = crate::utils::hex_util::bytes_to_hex(bytes)?;
+        self.set_shared_bytes_and_generate_hex(Bytes::copy_from_slice(bytes))
+    }
+
+    /// 同 set_bytes_and_generate_hex，直接持有 Writer::freeze 的结果
+    pub fn set_shared_bytes_and_generate_hex(
+        &mut self,
+        bytes: Bytes,
+    ) -> protocol_base::ProtocolResult<()> {
+        self.hex = crate::utils::hex_util::bytes_to_hex(&bytes)?;
+        self.bytes = bytes;
+        self.mark_encoded();
         Ok(())
     }
 
+    /// 附加强类型的解析结果，会替换已有的 payload
+    pub fn set_payload<P: Any + Send + Sync>(&mut self, payload: P) {
+        self.payload = Some(Arc::new(payload));
+    }
+
+    /// 取出 payload。没有或类型不符时返回 None
+    pub fn payload<P: Any>(&self) -> Option<&P> {
+        self.payload.as_deref()?.downcast_ref::<P>()
+    }
+
+    pub fn has_payload(&self) -> bool {
+        self.payload.is_some()
+    }
+
+    pub fn clear_payload(&mut self) {
+        self.payload = None;
+    }
+
+    pub fn idempotency_token(&self) -> Option<&str> {
+        self.idempotency_token.as_deref()
+    }
+
+    pub fn idempotency_token_clone(&self) -> Option<String> {
+        self.idempotency_token.clone()
+    }
+
+    pub fn set_idempotency_token(&mut self, token: &str) {
+        self.idempotency_token = Some(token.into());
+    }
+
+    /// 由 device_no、本次下行序号、cmd_code 与命令创建时间 (unix 毫秒) 生成幂等令牌并附加到 capsule。
+    /// 重发同一命令时应沿用原序号与创建时间，令牌才能保持一致
+    pub fn attach_idempotency_token(
+        &mut self,
+        downstream_count_hex: &str,
+        issued_at: u64,
+    ) -> protocol_base::ProtocolResult<String> {
+        let device_no = self.device_no.as_deref().ok_or_else(|| {
+            ProtocolError::ValidationFailed("idempotency token requires a device_no".into())
+        })?;
+        let cmd_code = self.cmd.as_ref().map(|c| c.code()).ok_or_else(|| {
+            ProtocolError::ValidationFailed("idempotency token requires a cmd".into())
+        })?;
+        let token = idempotency_token(device_no, downstream_count_hex, &cmd_code, issued_at);
+        self.idempotency_token = Some(token.clone());
+        Ok(token)
+    }
+
+    pub fn received_at(&self) -> Option<Instant> {
+        self.received_at
+    }
+
+    pub fn decoded_at(&self) -> Option<Instant> {
+        self.decoded_at
+    }
+
+    pub fn encoded_at(&self) -> Option<Instant> {
+        self.encoded_at
+    }
+
+    // 上行解析完成时调用
+    pub fn mark_decoded(&mut self) {
+        self.decoded_at = now();
+    }
+
+    // 编码完成时调用，set_bytes_and_generate_hex 会自动调用
+    pub fn mark_encoded(&mut self) {
+        self.encoded_at = now();
+    }
+
+    /// 收到到解析完成的耗时，未调用 mark_decoded 时为 None
+    pub fn decode_duration(&self) -> Option<Duration> {
+        Some(self.decoded_at?.duration_since(self.received_at?))
+    }
+
+    /// 创建到编码完成的耗时
+    pub fn encode_duration(&self) -> Option<Duration> {
+        Some(self.encoded_at?.duration_since(self.received_at?))
+    }
+
     pub fn is_upstream(&self) -> bool {
         self.direction.is_upstream()
     }
@@ -212,7 +387,7 @@ impl<T: Cmd + 'static> RawCapsule<T> {
     }
 
     pub fn set_temp_bytes(&mut self, bytes: &[u8]) {
-        self.temp_bytes = bytes.to_vec();
+        self.temp_bytes = Bytes::copy_from_slice(bytes);
     }
 
     pub fn set_fields(&mut self, fields: Vec<ReportField>) {
@@ -229,3 +404,75 @@ impl<T: Cmd + 'static> RawCapsule<T> {
         self.field_details = new_fields;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Report;
+
+    impl Cmd for Report {
+        fn code(&self) -> String {
+            "02".into()
+        }
+        fn title(&self) -> String {
+            "数据上报".into()
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Reading {
+        volume: f64,
+        valve_open: bool,
+    }
+
+    #[test]
+    fn test_payload() {
+        let mut capsule: RawCapsule<Report> = RawCapsule::new_upstream(&[0x68, 0x16]);
+        assert!(!capsule.has_payload());
+        capsule.set_payload(Reading {
+            volume: 12.5,
+            valve_open: true,
+        });
+        let cloned = capsule.clone();
+        assert_eq!(
+            cloned.payload::<Reading>(),
+            Some(&Reading {
+                volume: 12.5,
+                valve_open: true
+            })
+        );
+        assert!(cloned.payload::<String>().is_none());
+        capsule.clear_payload();
+        assert!(capsule.payload::<Reading>().is_none());
+    }
+
+    #[test]
+    fn test_idempotency_token() {
+        let mut capsule = RawCapsule::new_downstream(Report, "20250917", "");
+        let token = capsule
+            .attach_idempotency_token("0A", 1_758_096_000_000)
+            .unwrap();
+        assert_eq!(
+            token,
+            idempotency_token("20250917", "0A", "02", 1_758_096_000_000)
+        );
+        let rsp = crate::JniResponse::downstream_response(&capsule).unwrap();
+        assert_eq!(rsp.idempotency_token(), Some(token.as_str()));
+
+        let mut upstream: RawCapsule<Report> = RawCapsule::new_upstream(&[0x68]);
+        assert!(upstream.attach_idempotency_token("0A", 0).is_err());
+    }
+
+    #[test]
+    fn test_warnings_keep_success() {
+        let mut capsule: RawCapsule<Report> = RawCapsule::new_upstream(&[0x68, 0x16]);
+        assert!(!capsule.has_warnings());
+        capsule.add_warning(Diagnostic::new("reserved_nonzero", "保留字节不为 0").with_offset(1));
+        capsule.append_warnings(vec![Diagnostic::new("timestamp_range", "时间超出范围")]);
+        assert!(capsule.is_success());
+        assert_eq!(capsule.warnings().len(), 2);
+        assert_eq!(capsule.clone().warnings_clone(), capsule.warnings());
+    }
+}
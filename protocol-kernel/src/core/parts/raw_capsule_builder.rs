@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use protocol_base::{ProtocolError, ProtocolResult};
+
+use crate::core::{
+    parts::{raw_capsule::RawCapsule, traits::Cmd},
+    writer::Writer,
+};
+
+type WriteFn<'a> =
+    Box<dyn FnOnce(&mut Writer, &HashMap<String, String>) -> ProtocolResult<()> + 'a>;
+
+/// 下行 RawCapsule 的链式构造器。build 时校验命令、设备标识和编码闭包，
+/// 并执行编码、回填 bytes/hex 和字段明细
+pub struct RawCapsuleBuilder<'a, T: Cmd> {
+    cmd: Option<T>,
+    device_no: Option<String>,
+    device_id: Option<String>,
+    params: HashMap<String, String>,
+    writer: Option<WriteFn<'a>>,
+}
+
+impl<T: Cmd> Default for RawCapsuleBuilder<'_, T> {
+    fn default() -> Self {
+        Self {
+            cmd: None,
+            device_no: None,
+            device_id: None,
+            params: HashMap::new(),
+            writer: None,
+        }
+    }
+}
+
+impl<'a, T: Cmd + 'static> RawCapsuleBuilder<'a, T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 回复上行时使用: 沿用上行的命令和设备标识
+    pub fn reply_to(upstream: &RawCapsule<T>) -> Self {
+        Self {
+            cmd: upstream.cmd_clone(),
+            device_no: upstream.device_no_clone(),
+            device_id: upstream.device_id_clone(),
+            ..Self::default()
+        }
+    }
+
+    pub fn cmd(mut self, cmd: T) -> Self {
+        self.cmd = Some(cmd);
+        self
+    }
+
+    pub fn device_no(mut self, device_no: &str) -> Self {
+        self.device_no = Some(device_no.into());
+        self
+    }
+
+    /// 空字符串视为没有 device_id，与 new_downstream 一致
+    pub fn device_id(mut self, device_id: &str) -> Self {
+        self.device_id = (!device_id.is_empty()).then(|| device_id.into());
+        self
+    }
+
+    pub fn param(mut self, key: &str, value: &str) -> Self {
+        self.params.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn params(mut self, params: HashMap<String, String>) -> Self {
+        self.params.extend(params);
+        self
+    }
+
+    /// 编码闭包，参数为空的 Writer 和下行参数
+    pub fn write<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut Writer, &HashMap<String, String>) -> ProtocolResult<()> + 'a,
+    {
+        self.writer = Some(Box::new(f));
+        self
+    }
+
+    pub fn build(self) -> ProtocolResult<RawCapsule<T>> {
+        let cmd = self.cmd.ok_or_else(|| {
+            ProtocolError::ValidationFailed("RawCapsuleBuilder requires a cmd".into())
+        })?;
+        if self.device_no.is_none() && self.device_id.is_none() {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "RawCapsuleBuilder for cmd {} requires device_no or device_id",
+                cmd.code()
+            )));
+        }
+        let write = self.writer.ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!(
+                "RawCapsuleBuilder for cmd {} requires a writer",
+                cmd.code()
+            ))
+        })?;
+
+        let mut writer = Writer::new();
+        write(&mut writer, &self.params)?;
+
+        let mut capsule = RawCapsule::new_downstream(cmd, "", "");
+        capsule.device_no = self.device_no;
+        capsule.device_id = self.device_id;
+        capsule.set_fields(writer.to_report_fields()?);
+        capsule.set_shared_bytes_and_generate_hex(writer.freeze())?;
+        Ok(capsule)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct SetPrice;
+
+    impl Cmd for SetPrice {
+        fn code(&self) -> String {
+            "25".into()
+        }
+        fn title(&self) -> String {
+            "调价".into()
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let capsule = RawCapsule::builder()
+            .cmd(SetPrice)
+            .device_no("0001")
+            .param("price", "0325")
+            .write(|w, params| {
+                w.write_bytes("起始符", &[0x68], "68")?;
+                let price = hex::decode(&params["price"]).unwrap();
+                w.write_bytes("价格", &price, "3.25")?;
+                Ok(())
+            })
+            .build()
+            .unwrap();
+        assert_eq!(capsule.hex(), "680325");
+        assert_eq!(capsule.field_details().len(), 2);
+        assert_eq!(capsule.device_no(), Some("0001"));
+        assert!(capsule.device_id().is_none());
+        assert!(capsule.is_downstream());
+    }
+
+    #[test]
+    fn test_build_validates() {
+        let missing_device = RawCapsule::builder()
+            .cmd(SetPrice)
+            .write(|_, _| Ok(()))
+            .build();
+        assert!(matches!(
+            missing_device,
+            Err(ProtocolError::ValidationFailed(_))
+        ));
+        let missing_writer = RawCapsuleBuilder::new()
+            .cmd(SetPrice)
+            .device_id("A1")
+            .build();
+        assert!(missing_writer.is_err());
+    }
+}
@@ -1,10 +1,19 @@
+use crate::core::parts::cmd_snapshot::CmdSnapshot;
 use crate::core::parts::raw_capsule::RawCapsule;
 use crate::core::parts::traits::Cmd;
+use serde::{Deserialize, Serialize};
 
 /// 对上行而言，它通常需要回复。因此上行需要2个raw-capsule，一上一下. RawChamber用来组合2个raw-capsule
 /// 对下行而言，它只需要一个下行的raw-capsule. 此时不需要RawChamber
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(
+    rename_all = "camelCase",
+    bound(
+        serialize = "T: 'static",
+        deserialize = "T: TryFrom<CmdSnapshot>, <T as TryFrom<CmdSnapshot>>::Error: std::fmt::Display"
+    )
+)]
 pub struct RawChamber<T: Cmd + Clone> {
     pub(crate) upstream: Option<RawCapsule<T>>,
     pub(crate) downstream: Option<RawCapsule<T>>,
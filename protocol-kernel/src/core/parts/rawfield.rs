@@ -1,35 +1,68 @@
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use bytes::Bytes;
+use protocol_base::ProtocolResult;
+
+use crate::core::parts::{report_field::ValueType, title::Title};
+
 // 报文帧字段 最小解析单位
 #[derive(Debug, Clone, Default)]
 pub struct Rawfield {
-    pub(crate) bytes: Vec<u8>,
+    // 通过 Reader::from_shared 解析时与原始报文共享同一块内存
+    pub(crate) bytes: Bytes,
     // 帧字段名称
-    pub(crate) title: String,
-    // hex值
+    pub(crate) title: Title,
+    // hex值，紧凑模式下为空，由 bytes 按需生成
     pub(crate) hex: String,
+    // 紧凑模式下记录字段在报文中的 (起始偏移, 长度)
+    pub(crate) span: Option<(usize, usize)>,
     // 真值
     pub(crate) value: String,
+    // 可选的类型信息，随 ReportField 一起上报
+    pub(crate) value_type: Option<ValueType>,
+    pub(crate) unit: Option<String>,
+    pub(crate) scale: Option<f64>,
+    // 宽松模式下解码失败的字段，value 为错误信息
+    pub(crate) alert: bool,
 }
 
 impl Rawfield {
     /// 一个构造函数，用于根据原始字节和翻译结果来创建Rawfield
-    pub fn new(raw_bytes: &[u8], title: String, value: String) -> Self {
+    pub fn new(raw_bytes: &[u8], title: impl Into<Title>, value: String) -> Self {
         Self {
-            bytes: raw_bytes.to_vec(),
-            title,
+            bytes: Bytes::copy_from_slice(raw_bytes),
+            title: title.into(),
             hex: hex::encode_upper(raw_bytes), // 编码为Hex字符串
             value,
+            ..Default::default()
         }
     }
 
-    pub fn new_with_hex(hex: &str, title: &str, value: String) -> Self {
+    /// 直接持有 `Bytes` (通常是原始报文的切片)，不复制字节
+    pub fn from_shared(bytes: Bytes, title: impl Into<Title>, value: String) -> Self {
         Self {
-            bytes: crate::utils::hex_util::hex_to_bytes(hex).unwrap(),
+            hex: hex::encode_upper(&bytes),
+            bytes,
             title: title.into(),
-            hex: hex.into(),
             value,
+            ..Default::default()
         }
     }
 
+    /// hex 非法时会 panic，JNI/FFI 调用链上请使用 `try_new_with_hex`
+    pub fn new_with_hex(hex: &str, title: &str, value: String) -> Self {
+        Self::try_new_with_hex(hex, title, value).unwrap()
+    }
+
+    pub fn try_new_with_hex(hex: &str, title: &str, value: String) -> ProtocolResult<Self> {
+        Ok(Self {
+            bytes: crate::utils::hex_util::hex_to_bytes(hex)?.into(),
+            title: title.into(),
+            hex: hex.into(),
+            value,
+            ..Default::default()
+        })
+    }
+
     // pub fn hex_to_bytes(&self) -> crate::defi::ProtocolResult<Vec<u8>> {
     //     crate::utils::hex_util::hex_to_bytes(&self.hex)
     // }
@@ -40,6 +73,11 @@ impl Rawfield {
     }
 
     pub fn bytes_clone(&self) -> Vec<u8> {
+        self.bytes.to_vec()
+    }
+
+    /// 共享的字节引用，clone 只增加引用计数
+    pub fn shared_bytes(&self) -> Bytes {
         self.bytes.clone()
     }
 
@@ -48,15 +86,34 @@ impl Rawfield {
     }
 
     pub fn title_clone(&self) -> String {
+        self.title.as_str().into()
+    }
+
+    /// 共享的标题，clone 只增加引用计数
+    pub fn title_shared(&self) -> Title {
         self.title.clone()
     }
 
-    pub fn hex(&self) -> &str {
-        &self.hex
+    /// 紧凑模式下 hex 没有保存，每次调用都从 bytes 生成 (大写)
+    pub fn hex(&self) -> Cow<'_, str> {
+        if self.hex.is_empty() && !self.bytes.is_empty() {
+            Cow::Owned(hex::encode_upper(&self.bytes))
+        } else {
+            Cow::Borrowed(&self.hex)
+        }
     }
 
     pub fn hex_clone(&self) -> String {
-        self.hex.clone()
+        self.hex().into_owned()
+    }
+
+    /// 字段在报文中的 (起始偏移, 长度)，仅 Reader 紧凑模式下解析的字段有
+    pub fn span(&self) -> Option<(usize, usize)> {
+        self.span
+    }
+
+    pub fn is_compact(&self) -> bool {
+        self.span.is_some()
     }
 
     pub fn value(&self) -> &str {
@@ -66,4 +123,55 @@ impl Rawfield {
     pub fn value_clone(&self) -> String {
         self.value.clone()
     }
+
+    pub fn value_type(&self) -> Option<ValueType> {
+        self.value_type
+    }
+
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    pub fn scale(&self) -> Option<f64> {
+        self.scale
+    }
+
+    pub fn with_value_type(mut self, value_type: ValueType) -> Self {
+        self.value_type = Some(value_type);
+        self
+    }
+
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    pub fn is_alert(&self) -> bool {
+        self.alert
+    }
+
+    pub fn with_alert(mut self) -> Self {
+        self.alert = true;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_new_with_hex() {
+        let rf = Rawfield::try_new_with_hex("0A1B", "阀门状态", "开".into()).unwrap();
+        assert_eq!(rf.bytes(), &[0x0A, 0x1B]);
+        assert_eq!(rf.hex(), "0A1B");
+        // 非法 hex 返回错误而不是 panic
+        assert!(Rawfield::try_new_with_hex("0G", "阀门状态", String::new()).is_err());
+        assert!(Rawfield::try_new_with_hex("1Z", "阀门状态", String::new()).is_err());
+    }
 }
@@ -0,0 +1,200 @@
+use alloc::string::{String, ToString};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::parts::title::Title;
+
+#[cfg(feature = "std")]
+use crate::{
+    core::parts::rawfield::Rawfield,
+    utils::{self, code_registry::CodeRegistry},
+    ProtocolResult,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportField {
+    // 驻留的标题，同名字段共享存储
+    pub name: Title,
+    pub code: String,
+    pub value: String,
+    pub alert: bool,
+    // 以下为可选的类型信息，平台可据此直接存储数值而无需解析展示字符串
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<ValueType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_hex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+}
+
+/// ReportField.value 的实际类型
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    Int,
+    Float,
+    String,
+    Bool,
+    Enum,
+}
+
+impl ValueType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValueType::Int => "int",
+            ValueType::Float => "float",
+            ValueType::String => "string",
+            ValueType::Bool => "bool",
+            ValueType::Enum => "enum",
+        }
+    }
+
+    pub fn code_of(code: &str) -> Option<Self> {
+        match code {
+            "int" => Some(ValueType::Int),
+            "float" => Some(ValueType::Float),
+            "string" => Some(ValueType::String),
+            "bool" => Some(ValueType::Bool),
+            "enum" => Some(ValueType::Enum),
+            _ => None,
+        }
+    }
+}
+
+// 实现一个便捷的构造函数
+impl ReportField {
+    pub fn new(name: &str, code: &str, value: String) -> Self {
+        Self {
+            name: name.into(),
+            code: code.to_string(),
+            value,
+            alert: false, // 默认为false
+            value_type: None,
+            raw_hex: None,
+            unit: None,
+            scale: None,
+        }
+    }
+
+    pub fn with_value_type(mut self, value_type: ValueType) -> Self {
+        self.value_type = Some(value_type);
+        self
+    }
+
+    pub fn with_raw_hex(mut self, raw_hex: &str) -> Self {
+        self.raw_hex = Some(raw_hex.into());
+        self
+    }
+
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+}
+
+#[cfg(feature = "std")]
+impl Rawfield {
+    pub fn to_report_field(self) -> ReportField {
+        let code = utils::to_pinyin_cached(&self.title);
+        self.into_report_field(code, false)
+    }
+
+    /// 通过协议的 code 注册表生成 code，保证同一协议内 code 唯一且稳定。code 冲突时返回错误
+    pub fn to_report_field_with(self, registry: &mut CodeRegistry) -> ProtocolResult<ReportField> {
+        let code = registry.register(&self.title)?;
+        Ok(self.into_report_field(code, false))
+    }
+
+    // raw_hex 只在调用方要求时填充 (Reader::with_raw_hex)
+    pub(crate) fn into_report_field(self, code: String, keep_raw_hex: bool) -> ReportField {
+        let raw_hex = match keep_raw_hex {
+            false => String::new(),
+            true if self.hex.is_empty() => hex::encode_upper(&self.bytes),
+            true => self.hex,
+        };
+        ReportField {
+            name: self.title,
+            code,
+            value: self.value,
+            alert: self.alert,
+            value_type: self.value_type,
+            raw_hex: if raw_hex.is_empty() {
+                None
+            } else {
+                Some(raw_hex)
+            },
+            unit: self.unit,
+            scale: self.scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{
+            type_converter::{FieldConvertDecoder, FieldTranslator},
+            Symbol,
+        },
+        FieldType,
+    };
+
+    #[test]
+    fn test_optional_fields_serialization() {
+        // 未设置时不出现在 JSON 中，旧平台看到的格式不变
+        let plain = ReportField::new("读数", "du_shu", "1.5".into());
+        assert_eq!(
+            serde_json::to_string(&plain).unwrap(),
+            r#"{"name":"读数","code":"du_shu","value":"1.5","alert":false}"#
+        );
+
+        let typed = plain
+            .with_value_type(ValueType::Float)
+            .with_raw_hex("0096")
+            .with_unit("m³")
+            .with_scale(0.01);
+        let json = serde_json::to_string(&typed).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"读数","code":"du_shu","value":"1.5","alert":false,"valueType":"float","rawHex":"0096","unit":"m³","scale":0.01}"#
+        );
+        assert_eq!(serde_json::from_str::<ReportField>(&json).unwrap(), typed);
+        // 缺省字段可以反序列化
+        let old: ReportField =
+            serde_json::from_str(r#"{"name":"读数","code":"du_shu","value":"1.5","alert":false}"#)
+                .unwrap();
+        assert_eq!(old.value_type, None);
+        assert_eq!(old.scale, None);
+    }
+
+    #[test]
+    fn test_unit_kept_in_value() {
+        let decoder = FieldConvertDecoder::new(
+            "累计流量",
+            FieldType::UnsignedU32(0.01),
+            Some(Symbol::CubicMeter),
+            false,
+        );
+        let field = decoder.translate(&[0x00, 0x00, 0x04, 0xD2]).unwrap();
+        assert_eq!(field.value, "12.34 m³");
+        assert_eq!(field.unit.as_deref(), Some("m³"));
+        assert_eq!(field.value_type, Some(ValueType::Float));
+        assert_eq!(field.scale, Some(0.01));
+        #[cfg(feature = "std")]
+        {
+            let field = field.to_report_field();
+            assert_eq!(field.value, "12.34 m³");
+            assert_eq!(field.unit.as_deref(), Some("m³"));
+            assert_eq!(field.raw_hex, None);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use protocol_base::{ProtocolError, ProtocolResult};
+use serde::{Deserialize, Serialize};
+
+use crate::core::parts::traits::AutoEncodingParam;
+
+/// 下行参数的描述信息，前端据此自动渲染参数表单
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParamSchema {
+    pub code: String,
+    pub title: String,
+    pub cmd_code: String,
+    pub input_field_type: String,
+    pub byte_length: usize,
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub default_value: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub default_hex: String,
+}
+
+impl ParamSchema {
+    pub fn of<T: AutoEncodingParam>(param: &T) -> Self {
+        Self {
+            code: param.code(),
+            title: param.title(),
+            cmd_code: param.cmd_code(),
+            input_field_type: param.input_field_type(),
+            byte_length: param.byte_length(),
+            required: param.required(),
+            default_value: param.default_value(),
+            default_hex: param.default_hex(),
+        }
+    }
+}
+
+/// 取出属于 cmd_code 的参数定义 (hex 不区分大小写)。未声明 cmd_code 的参数视为通用参数，总是包含在内
+pub fn param_schema_for<T: AutoEncodingParam>(cmd_code: &str, params: &[T]) -> Vec<ParamSchema> {
+    params
+        .iter()
+        .filter(|p| {
+            let code = p.cmd_code();
+            code.is_empty() || code.eq_ignore_ascii_case(cmd_code)
+        })
+        .map(ParamSchema::of)
+        .collect()
+}
+
+pub fn param_schema_json<T: AutoEncodingParam>(
+    cmd_code: &str,
+    params: &[T],
+) -> ProtocolResult<String> {
+    serde_json::to_string(&param_schema_for(cmd_code, params))
+        .map_err(|e| ProtocolError::CommonError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FieldType;
+
+    enum Price {
+        Gas,
+        Water,
+    }
+
+    impl AutoEncodingParam for Price {
+        fn code(&self) -> String {
+            match self {
+                Price::Gas => "gas_price".into(),
+                Price::Water => "water_price".into(),
+            }
+        }
+        fn title(&self) -> String {
+            "单价".into()
+        }
+        fn byte_length(&self) -> usize {
+            4
+        }
+        fn cmd_code(&self) -> String {
+            match self {
+                Price::Gas => "31".into(),
+                Price::Water => "3A".into(),
+            }
+        }
+        fn field_type(&self) -> FieldType {
+            FieldType::UnsignedU32(0.01)
+        }
+    }
+
+    #[test]
+    fn test_schema_filter_and_json() {
+        let schema = param_schema_for("31", &[Price::Gas, Price::Water]);
+        assert_eq!(schema.len(), 1);
+        assert_eq!(schema[0].input_field_type, "int");
+        let json = param_schema_json("3a", &[Price::Gas, Price::Water]).unwrap();
+        assert!(json.contains("\"code\":\"water_price\""));
+        assert!(json.contains("\"byteLength\":4"));
+    }
+}
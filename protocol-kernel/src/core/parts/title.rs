@@ -0,0 +1,172 @@
+use alloc::{string::String, sync::Arc};
+use core::{borrow::Borrow, fmt, ops::Deref};
+
+#[cfg(feature = "std")]
+use std::{collections::HashSet, sync::RwLock};
+
+#[cfg(feature = "std")]
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// 驻留池最多保存的标题数，超出后新标题不再驻留 (防止动态拼接的标题无限增长)
+#[cfg(feature = "std")]
+pub const MAX_INTERNED: usize = 4096;
+
+#[cfg(feature = "std")]
+static INTERNED: Lazy<RwLock<HashSet<Arc<str>>>> = Lazy::new(|| RwLock::new(HashSet::new()));
+
+/// 字段标题。同一标题 (如 "设备编号") 在所有帧、所有字段间共享一份存储，
+/// clone 只增加引用计数。序列化为普通字符串
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Title(Arc<str>);
+
+impl Title {
+    /// 取驻留的标题，没有时加入驻留池。no_std 下没有全局池，每次新建
+    pub fn intern(title: &str) -> Self {
+        #[cfg(feature = "std")]
+        {
+            if let Some(found) = INTERNED
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(title)
+            {
+                return Title(found.clone());
+            }
+            let mut interned = INTERNED.write().unwrap_or_else(|e| e.into_inner());
+            if let Some(found) = interned.get(title) {
+                return Title(found.clone());
+            }
+            let title: Arc<str> = Arc::from(title);
+            if interned.len() < MAX_INTERNED {
+                interned.insert(title.clone());
+            }
+            Title(title)
+        }
+        #[cfg(not(feature = "std"))]
+        Title(Arc::from(title))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// 驻留池中的标题数
+    #[cfg(feature = "std")]
+    pub fn interned_count() -> usize {
+        INTERNED.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+impl Default for Title {
+    fn default() -> Self {
+        Title::intern("")
+    }
+}
+
+impl Deref for Title {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Title {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Title {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Title {
+    fn from(title: &str) -> Self {
+        Title::intern(title)
+    }
+}
+
+impl From<&String> for Title {
+    fn from(title: &String) -> Self {
+        Title::intern(title)
+    }
+}
+
+impl From<String> for Title {
+    fn from(title: String) -> Self {
+        Title::intern(&title)
+    }
+}
+
+impl From<Title> for String {
+    fn from(title: Title) -> Self {
+        title.0.as_ref().into()
+    }
+}
+
+impl PartialEq<str> for Title {
+    fn eq(&self, other: &str) -> bool {
+        &*self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Title {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.0 == *other
+    }
+}
+
+impl PartialEq<String> for Title {
+    fn eq(&self, other: &String) -> bool {
+        &*self.0 == other.as_str()
+    }
+}
+
+impl fmt::Debug for Title {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Title {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for Title {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Title {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let title = String::deserialize(deserializer)?;
+        Ok(Title::intern(&title))
+    }
+}
+
+// 驻留池只在 std 下提供
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern() {
+        let a = Title::from("设备编号");
+        let b = Title::from(String::from("设备编号"));
+        assert!(Arc::ptr_eq(&a.0, &b.0));
+        assert_eq!(a, "设备编号");
+        assert_eq!(a.len(), "设备编号".len());
+        assert_eq!(format!("{}|{:?}", a, a), "设备编号|\"设备编号\"");
+
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, "\"设备编号\"");
+        let back: Title = serde_json::from_str(&json).unwrap();
+        assert!(Arc::ptr_eq(&a.0, &back.0));
+    }
+}
@@ -2,12 +2,14 @@ use std::collections::HashMap;
 
 use crate::{
     core::{
-        parts::{decoding_filter::DecodingFilter, transport_pair::TransportPair},
+        context::DecodeContext,
+        parts::{decoding_filter::DecodingFilter, schema, transport_pair::TransportPair},
         type_converter::FieldTranslator,
         RW,
     },
     hex_util, DirectionEnum, FieldCompareDecoder, FieldConvertDecoder, FieldEnumDecoder, FieldType,
-    MsgTypeEnum, ProtocolError, ProtocolResult, Rawfield, Reader, Symbol, TryFromBytes, Writer,
+    MsgTypeEnum, ProtocolError, ProtocolResult, Rawfield, Reader, ResultExt, Symbol, TryFromBytes,
+    Writer,
 };
 use dyn_clone::DynClone;
 
@@ -79,6 +81,17 @@ pub trait Cmd: DynClone {
     }
 }
 
+/// 命令表: 由枚举实现，可按 code 反查命令。一般由 `cmd_table!` 生成
+pub trait CmdTable: Cmd + Sized {
+    fn variants() -> Vec<Self>;
+
+    fn from_code(code: &str) -> Option<Self> {
+        Self::variants()
+            .into_iter()
+            .find(|c| c.code().eq_ignore_ascii_case(code))
+    }
+}
+
 // 下行参数设置，针对单个帧字段
 pub trait AutoEncodingParam {
     fn code(&self) -> String; // 唯一标识符
@@ -129,7 +142,7 @@ pub trait AutoEncodingParam {
                 bytes = hex_util::hex_to_bytes(&default_hex)?;
             } else if !default_value.is_empty() {
                 // 1-1: 使用 default_value 并根据 FieldType 编码
-                bytes = ft.encode(&default_value)?;
+                bytes = ft.encode_with_title(&default_value, &self.title())?;
             } else {
                 // 1-2: 两者都为空且该值是必须的，抛错
                 if self.required() {
@@ -142,7 +155,7 @@ pub trait AutoEncodingParam {
             }
         } else {
             // 情况2: 输入有值
-            bytes = ft.encode(input)?;
+            bytes = ft.encode_with_title(input, &self.title())?;
         }
 
         // 步骤2: 调整字节长度
@@ -183,6 +196,11 @@ pub trait AutoEncoding<T: AutoEncodingParam>: Sized {
         HashMap::new()
     }
 
+    /// 导出 cmd_code 对应的参数描述 (JSON 数组)，供前端渲染参数表单
+    fn param_schema_json(&self, cmd_code: &str) -> ProtocolResult<String> {
+        schema::param_schema_json(cmd_code, &self.variants())
+    }
+
     // 只要定义好了trait:AutoEncodingParams，它就会自动实现它的to_bytes方法。
     // 这里只需要挨个调用AutoEncodingParams.to_bytes方法就好了
     // 返回的是整个处理的总长度
@@ -271,30 +289,68 @@ where
     // 只要按照规则定义了以上的内容，这个方法就会自动解码。
     // 如果你懒得看以上定义，那就重写这个方法
     fn translate(&self, bytes: &[u8]) -> ProtocolResult<Rawfield> {
-        if let Some(filter) = self.filter() {
-            // 如果拦截器拦截到了，终止之后的解析
-            if filter.matches(bytes) {
-                let value = filter.title();
-                return Ok(Rawfield::new(bytes, self.title(), value));
-            }
-        }
-        // 优先级从上到下分别是:
-        if self.is_compare_mode() {
-            // 1.比较模式(这种模式如果匹配不上会抛错,比如crc的比较就可以用这个)
-            FieldCompareDecoder::new(&self.title(), self.compare_target(), self.swap())
-                .translate(bytes)
-        } else if self.is_translate_mode() {
-            // 2.翻译模式(按照定义的FieldType进行翻译,包含所有16进制支持的类型)
-            FieldConvertDecoder::new(&self.title(), self.field_type(), self.symbol(), self.swap())
-                .translate(bytes)
-        } else if self.is_enum_mode() {
-            // 3.枚举模式(指定几个枚举值)
-            FieldEnumDecoder::new(&self.title(), self.enum_values(), self.swap()).translate(bytes)
-        } else {
-            // 一个解析器都找不到，那就抛错。
-            Err(ProtocolError::CommonError("auto-decoding-params requires at least one of the following: enum, translate, compare".into()))
+        auto_translate(self, bytes, None)
+    }
+
+    // 带上下文的解码，默认流程同 translate，由各翻译器的 translate_with 拿到上下文。
+    // 需要设备状态 (密钥槽、上次读数) 的字段覆盖此方法；重写了 translate 的字段也要一并覆盖，
+    // 否则 auto_process_with 走的仍是默认流程
+    fn translate_with(&self, bytes: &[u8], ctx: &mut DecodeContext) -> ProtocolResult<Rawfield> {
+        auto_translate(self, bytes, Some(ctx))
+    }
+}
+
+// translate 与 translate_with 的默认实现，ctx 为 None 时不带上下文
+fn auto_translate<P, U>(
+    param: &P,
+    bytes: &[u8],
+    ctx: Option<&mut DecodeContext>,
+) -> ProtocolResult<Rawfield>
+where
+    P: AutoDecodingParam<U> + ?Sized,
+    U: TryFromBytes,
+{
+    let run = |translator: &dyn FieldTranslator| match ctx {
+        Some(ctx) => translator.translate_with(bytes, ctx),
+        None => translator.translate(bytes),
+    };
+    if let Some(filter) = param.filter() {
+        // 如果拦截器拦截到了，终止之后的解析
+        if filter.matches(bytes) {
+            let value = filter.title();
+            return Ok(Rawfield::new(bytes, param.title(), value));
         }
     }
+    // 优先级从上到下分别是:
+    if param.is_compare_mode() {
+        // 1.比较模式(这种模式如果匹配不上会抛错,比如crc的比较就可以用这个)
+        run(&FieldCompareDecoder::new(
+            &param.title(),
+            param.compare_target(),
+            param.swap(),
+        ))
+    } else if param.is_translate_mode() {
+        // 2.翻译模式(按照定义的FieldType进行翻译,包含所有16进制支持的类型)
+        run(&FieldConvertDecoder::new(
+            &param.title(),
+            param.field_type(),
+            param.symbol(),
+            param.swap(),
+        ))
+    } else if param.is_enum_mode() {
+        // 3.枚举模式(指定几个枚举值)
+        run(&FieldEnumDecoder::new(
+            &param.title(),
+            param.enum_values(),
+            param.swap(),
+        ))
+    } else {
+        // 一个解析器都找不到，那就抛错。
+        Err(ProtocolError::CommonError(
+            "auto-decoding-params requires at least one of the following: enum, translate, compare"
+                .into(),
+        ))
+    }
 }
 
 /// 自动解码处理trait
@@ -319,8 +375,113 @@ where
         let definitions = self.variants();
         for definition in definitions {
             let byte_length = definition.byte_length();
-            reader.read_and_translate_head(byte_length, |h| definition.translate(h))?;
+            let offset = reader.position();
+            let title = definition.title();
+            reader
+                .read_and_translate_field(&title, byte_length, |h| definition.translate(h))
+                .with_context(&title, offset)?;
+        }
+        Ok(())
+    }
+
+    // 同 auto_process，各字段通过 translate_with 拿到上下文。全部成功后把字段改动的状态写回缓存
+    fn auto_process_with(
+        &self,
+        reader: &mut Reader,
+        ctx: &mut DecodeContext,
+    ) -> ProtocolResult<()> {
+        for definition in self.variants() {
+            let offset = reader.position();
+            let title = definition.title();
+            reader
+                .read_and_translate_field(&title, definition.byte_length(), |h| {
+                    definition.translate_with(h, ctx)
+                })
+                .with_context(&title, offset)?;
         }
+        ctx.commit()?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    enum Field {
+        Head,
+        Total,
+        Tail,
+    }
+
+    impl AutoDecodingParam for Field {
+        fn byte_length(&self) -> usize {
+            match self {
+                Field::Total => 4,
+                _ => 1,
+            }
+        }
+
+        fn title(&self) -> String {
+            match self {
+                Field::Head => "起始符",
+                Field::Total => "累计流量",
+                Field::Tail => "结束符",
+            }
+            .into()
+        }
+
+        fn field_type(&self) -> FieldType {
+            match self {
+                Field::Total => FieldType::UnsignedU32(0.01),
+                _ => FieldType::Empty,
+            }
+        }
+
+        fn compare_target(&self) -> Vec<u8> {
+            match self {
+                Field::Head => vec![0x68],
+                Field::Total => vec![],
+                Field::Tail => vec![0x16],
+            }
+        }
+    }
+
+    struct Report;
+
+    impl AutoDecoding<Field> for Report {
+        fn variants(&self) -> Vec<Field> {
+            vec![Field::Head, Field::Total, Field::Tail]
+        }
+    }
+
+    #[test]
+    fn test_auto_process_error_context() {
+        let frame = [0x68, 0x01, 0x02, 0x03, 0x04, 0x16];
+        let mut reader = Reader::new(&frame);
+        Report.auto_process(&mut reader).unwrap();
+        assert_eq!(reader.position(), frame.len());
+
+        // 结束符不匹配，错误带上字段名和它在报文中的偏移
+        let frame = [0x68, 0x01, 0x02, 0x03, 0x04, 0x17];
+        let mut reader = Reader::new(&frame);
+        let err = Report.auto_process(&mut reader).unwrap_err();
+        match &err {
+            ProtocolError::DecodeError { field, offset, .. } => {
+                assert_eq!((field.as_str(), *offset), ("结束符", 5));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(err.to_string().starts_with("field 结束符 at offset 5: "));
+        assert!(!matches!(err.root(), ProtocolError::DecodeError { .. }));
+        assert_eq!(err.code(), err.root().code());
+
+        // 报文长度不足
+        let frame = [0x68, 0x01, 0x02];
+        let err = Report.auto_process(&mut Reader::new(&frame)).unwrap_err();
+        assert!(matches!(
+            err,
+            ProtocolError::DecodeError { ref field, offset: 1, .. } if field == "累计流量"
+        ));
+    }
+}
@@ -1,9 +1,20 @@
 use crate::core::parts::traits::Transport;
 use crate::core::parts::transport_pair::TransportPair;
-use crate::hex_util;
+use crate::{bcd_util, hex_util, ProtocolError, ProtocolResult};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 上下行序号的编码方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CountEncoding {
+    #[default]
+    Binary, // 00..FF 回绕
+    Bcd, // 00..99 回绕
+}
 
 // informations with hex + bytes
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TransportCarrier {
     pub(crate) device_no: Option<TransportPair>,
     pub(crate) device_no_padding: Option<TransportPair>,
@@ -16,16 +27,29 @@ pub struct TransportCarrier {
     pub(crate) upstream_count: Option<TransportPair>,
     pub(crate) downstream_count: Option<TransportPair>,
     pub(crate) cipher_slot: i8,
+    pub(crate) count_encoding: CountEncoding,
+    // 序号字节数，0 表示沿用当前序号的长度 (没有时为 1)
+    pub(crate) count_width: usize,
+    // 协议自定义状态 (会话密钥、上次的价格、协商出的版本等)，以 JSON 保存以便随缓存/快照序列化
+    pub(crate) ext: BTreeMap<String, serde_json::Value>,
 }
 
 impl TransportCarrier {
+    /// hex 非法时会 panic，JNI/FFI 调用链上请使用 try_ 版本
     pub fn new_with_device_no_and_upstream_count_hex(
         device_no: &str,
         upstream_count: &str,
     ) -> Self {
-        let device_no_bytes = hex_util::hex_to_bytes(device_no).unwrap();
-        let upstream_count_bytes = hex_util::hex_to_bytes(upstream_count).unwrap();
-        Self {
+        Self::try_new_with_device_no_and_upstream_count_hex(device_no, upstream_count).unwrap()
+    }
+
+    pub fn try_new_with_device_no_and_upstream_count_hex(
+        device_no: &str,
+        upstream_count: &str,
+    ) -> ProtocolResult<Self> {
+        let device_no_bytes = hex_util::hex_to_bytes(device_no)?;
+        let upstream_count_bytes = hex_util::hex_to_bytes(upstream_count)?;
+        Ok(Self {
             device_no: Some(TransportPair::new(device_no.into(), device_no_bytes)),
             device_no_padding: None,
             device_no_length: None,
@@ -40,7 +64,10 @@ impl TransportCarrier {
             )),
             downstream_count: None,
             cipher_slot: -1,
-        }
+            count_encoding: CountEncoding::Binary,
+            count_width: 0,
+            ext: BTreeMap::new(),
+        })
     }
 
     pub fn new_with_device_no(
@@ -64,6 +91,9 @@ impl TransportCarrier {
             upstream_count: None,
             downstream_count: None,
             cipher_slot: -1,
+            count_encoding: CountEncoding::Binary,
+            count_width: 0,
+            ext: BTreeMap::new(),
         }
     }
 
@@ -160,6 +190,84 @@ impl TransportCarrier {
     fn _set_downstream_count(&mut self, count: Option<TransportPair>) {
         self.downstream_count = count;
     }
+
+    /// 设置序号的编码方式与字节数 (width 为 0 表示沿用当前序号的长度)
+    pub fn set_count_format(&mut self, encoding: CountEncoding, width: usize) {
+        self.count_encoding = encoding;
+        self.count_width = width;
+    }
+
+    /// 上行序号 +1 (到最大值后回绕到 0)，返回新的序号。没有序号时从 1 开始
+    pub fn next_upstream_count(&mut self) -> ProtocolResult<TransportPair> {
+        let next = self.next_count(self.upstream_count.as_ref())?;
+        self.upstream_count = Some(next.clone());
+        Ok(next)
+    }
+
+    /// 下行序号 +1，规则同 next_upstream_count
+    pub fn next_downstream_count(&mut self) -> ProtocolResult<TransportPair> {
+        let next = self.next_count(self.downstream_count.as_ref())?;
+        self.downstream_count = Some(next.clone());
+        Ok(next)
+    }
+
+    /// 保存协议自定义状态，同名 key 会被覆盖
+    pub fn set_ext<T: Serialize>(&mut self, key: &str, value: T) -> ProtocolResult<()> {
+        let value = serde_json::to_value(value).map_err(|e| {
+            ProtocolError::CommonError(format!("failed to store extension {}: {}", key, e))
+        })?;
+        self.ext.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    /// 读取协议自定义状态。key 不存在或类型不符时返回 None
+    pub fn get_ext<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.ext.get(key)?;
+        T::deserialize(value).ok()
+    }
+
+    pub fn has_ext(&self, key: &str) -> bool {
+        self.ext.contains_key(key)
+    }
+
+    pub fn remove_ext(&mut self, key: &str) {
+        self.ext.remove(key);
+    }
+
+    fn next_count(&self, current: Option<&TransportPair>) -> ProtocolResult<TransportPair> {
+        let width = match self.count_width {
+            0 => current
+                .map(|c| c.bytes.len())
+                .filter(|l| *l > 0)
+                .unwrap_or(1),
+            w => w,
+        };
+        if width > 8 {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "count width {} exceeds 8 bytes",
+                width
+            )));
+        }
+        let value = match current {
+            None => 0,
+            Some(c) if c.bytes.is_empty() => 0,
+            Some(c) => match self.count_encoding {
+                CountEncoding::Binary => c.bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64),
+                CountEncoding::Bcd => bcd_util::bcd_to_u64(&c.bytes)?,
+            },
+        };
+        let bytes = match self.count_encoding {
+            CountEncoding::Binary => {
+                let next = value.wrapping_add(1);
+                next.to_be_bytes()[8 - width..].to_vec()
+            }
+            CountEncoding::Bcd => {
+                let next = (value + 1) % 10u64.pow(2 * width as u32);
+                bcd_util::u64_to_bcd(next, width)?
+            }
+        };
+        Ok(TransportPair::new(hex_util::bytes_to_hex(&bytes)?, bytes))
+    }
 }
 
 impl Transport for TransportCarrier {
@@ -293,4 +401,67 @@ impl TransportCarrier {
     pub fn cipher_slot(&self) -> i8 {
         self.cipher_slot
     }
+
+    pub fn count_encoding(&self) -> CountEncoding {
+        self.count_encoding
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProtocolCache;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_next_count_wraps() {
+        let mut carrier =
+            TransportCarrier::try_new_with_device_no_and_upstream_count_hex("0102", "FE").unwrap();
+        assert_eq!(carrier.next_upstream_count().unwrap().hex(), "FF");
+        assert_eq!(carrier.next_upstream_count().unwrap().hex(), "00");
+        assert_eq!(carrier.next_downstream_count().unwrap().hex(), "01");
+
+        carrier.set_count_format(CountEncoding::Bcd, 2);
+        carrier.set_downstream_count("9999".into(), vec![0x99, 0x99]);
+        assert_eq!(carrier.next_downstream_count().unwrap().hex(), "0000");
+        assert_eq!(carrier.next_downstream_count().unwrap().hex(), "0001");
+    }
+
+    #[test]
+    fn test_ext() {
+        let mut carrier =
+            TransportCarrier::try_new_with_device_no_and_upstream_count_hex("0104", "01").unwrap();
+        carrier.set_ext("session_key", "A1B2C3").unwrap();
+        carrier.set_ext("last_price", 3.25f64).unwrap();
+        assert_eq!(
+            carrier.get_ext::<String>("session_key").as_deref(),
+            Some("A1B2C3")
+        );
+        assert_eq!(carrier.get_ext::<f64>("last_price"), Some(3.25));
+        assert_eq!(carrier.get_ext::<u8>("session_key"), None);
+
+        // 随序列化一起保存
+        let json = serde_json::to_string(&carrier).unwrap();
+        let mut restored: TransportCarrier = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_ext::<f64>("last_price"), Some(3.25));
+        restored.remove_ext("last_price");
+        assert!(!restored.has_ext("last_price"));
+    }
+
+    #[test]
+    fn test_cache_write_back() {
+        let unique = "carrier-count-test";
+        assert!(ProtocolCache::next_upstream_count(unique).is_err());
+        let carrier =
+            TransportCarrier::try_new_with_device_no_and_upstream_count_hex("0103", "0009")
+                .unwrap();
+        ProtocolCache::store(unique, Arc::new(carrier));
+        assert_eq!(
+            ProtocolCache::next_upstream_count(unique).unwrap().hex(),
+            "000A"
+        );
+        let cached = ProtocolCache::read(unique).unwrap();
+        assert_eq!(cached.upstream_count().unwrap().hex(), "000A");
+        ProtocolCache::remove(unique);
+    }
 }
@@ -1,5 +1,7 @@
+use serde::{Deserialize, Serialize};
+
 // hex + bytes
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TransportPair {
     pub(crate) hex: String,
     pub(crate) bytes: Vec<u8>,
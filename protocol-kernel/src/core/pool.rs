@@ -0,0 +1,138 @@
+use std::sync::Mutex;
+
+use crate::core::{
+    parts::{diagnostic::Diagnostic, rawfield::Rawfield},
+    reader::Reader,
+    writer::Writer,
+};
+
+/// 默认最多缓存的空闲对象数
+pub const DEFAULT_MAX_IDLE: usize = 64;
+
+type ReaderStorage = (Vec<Rawfield>, Vec<Diagnostic>);
+
+/// Reader 复用池。Reader 借用报文无法整体缓存，池里缓存的是字段与警告列表，
+/// 高频解码时不必为每一帧重新分配
+#[derive(Debug)]
+pub struct ReaderPool {
+    idle: Mutex<Vec<ReaderStorage>>,
+    max_idle: usize,
+}
+
+impl Default for ReaderPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IDLE)
+    }
+}
+
+impl ReaderPool {
+    pub fn new(max_idle: usize) -> Self {
+        Self {
+            idle: Mutex::new(Vec::new()),
+            max_idle,
+        }
+    }
+
+    /// 取一个 Reader，有空闲存储时复用
+    pub fn get<'a>(&self, buffer: &'a [u8]) -> Reader<'a> {
+        let storage = self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        match storage {
+            Some((fields, warnings)) => Reader::with_storage(buffer, fields, warnings),
+            None => Reader::new(buffer),
+        }
+    }
+
+    /// 用完后放回，字段被清空但保留容量。池满时直接丢弃
+    pub fn put(&self, reader: Reader<'_>) {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if idle.len() < self.max_idle {
+            idle.push(reader.into_storage());
+        }
+    }
+
+    pub fn idle(&self) -> usize {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+/// Writer 复用池，放回时 reset，缓冲区与字段列表保留容量
+#[derive(Debug)]
+pub struct WriterPool {
+    idle: Mutex<Vec<Writer>>,
+    max_idle: usize,
+}
+
+impl Default for WriterPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IDLE)
+    }
+}
+
+impl WriterPool {
+    pub fn new(max_idle: usize) -> Self {
+        Self {
+            idle: Mutex::new(Vec::new()),
+            max_idle,
+        }
+    }
+
+    pub fn get(&self) -> Writer {
+        self.idle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop()
+            .unwrap_or_default()
+    }
+
+    /// 用完后放回。Writer::freeze/full_hex 会消耗 Writer，这种情况无需放回
+    pub fn put(&self, mut writer: Writer) {
+        writer.reset();
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if idle.len() < self.max_idle {
+            idle.push(writer);
+        }
+    }
+
+    pub fn idle(&self) -> usize {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_pool() {
+        let pool = ReaderPool::new(1);
+        let frame = [0x68, 0x02, 0x16];
+        let mut reader = pool.get(&frame);
+        reader
+            .read_and_translate_head(1, |b| Ok(Rawfield::new(b, "起始符", "68".into())))
+            .unwrap();
+        pool.put(reader);
+        pool.put(Reader::new(&frame));
+        assert_eq!(pool.idle(), 1);
+
+        let reader = pool.get(&frame[1..]);
+        assert!(reader.fields().unwrap().is_empty());
+        assert_eq!(reader.remaining_len(), 2);
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn test_writer_pool() {
+        let pool = WriterPool::default();
+        let mut writer = pool.get();
+        writer.write_bytes("起始符", &[0x68; 32], "68").unwrap();
+        writer.write_placeholder("crc", 2).unwrap();
+        let capacity = writer.capacity().unwrap();
+        pool.put(writer);
+
+        let writer = pool.get();
+        assert!(writer.buffer().unwrap().is_empty());
+        assert!(writer.fields().unwrap().is_empty());
+        assert!(writer.placeholders_tags().unwrap().is_empty());
+        assert_eq!(writer.capacity().unwrap(), capacity);
+    }
+}
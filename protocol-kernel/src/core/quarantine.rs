@@ -0,0 +1,204 @@
+//! 无法识别的上行帧 (没有匹配的协议或命令码) 的隔离与原始数据采集。
+//! 帧连同分类线索 (帧头、长度、按各协议配置位置取出的候选命令码/控制码) 交给可替换的 sink，
+//! 便于收集样本后补充协议实现
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::{bridge::trace, core::config::ProtocolConfig};
+
+/// 按某个协议配置的字段位置取出的候选值。帧未通过该协议的校验，只作为线索
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateCode {
+    pub protocol: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cmd_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub control_code: Option<String>,
+}
+
+impl CandidateCode {
+    /// 不做帧校验，按位置直接读取，帧长不够时对应项为 None，两项都没有时返回 None
+    pub fn probe(config: &ProtocolConfig, frame: &[u8]) -> Option<Self> {
+        let cmd_code = frame
+            .get(config.cmd_index..config.cmd_index + config.cmd_len)
+            .filter(|code| !code.is_empty())
+            .map(hex::encode_upper);
+        let control_code = config
+            .control_index
+            .and_then(|index| frame.get(index))
+            .map(|b| format!("{:02X}", b));
+        if cmd_code.is_none() && control_code.is_none() {
+            return None;
+        }
+        Some(Self {
+            protocol: config.name.clone(),
+            cmd_code,
+            control_code,
+        })
+    }
+}
+
+/// 一帧被隔离的上行数据
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnknownFrame {
+    // 已确定协议、只是命令码未知时为协议名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
+    pub hex: String,
+    pub len: usize,
+    // 首字节 hex
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head_tag: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub candidates: Vec<CandidateCode>,
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    // 采集时间，unix 毫秒
+    pub captured_at: u64,
+}
+
+impl UnknownFrame {
+    pub fn new(frame: &[u8], reason: &str) -> Self {
+        let captured_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        Self {
+            protocol: None,
+            hex: hex::encode_upper(frame),
+            len: frame.len(),
+            head_tag: frame.first().map(|b| format!("{:02X}", b)),
+            candidates: Vec::new(),
+            reason: reason.into(),
+            trace_id: trace::current_trace_id(),
+            captured_at,
+        }
+    }
+
+    pub fn with_protocol(mut self, protocol: &str) -> Self {
+        self.protocol = Some(protocol.into());
+        self
+    }
+
+    /// 按 config 的字段位置补充候选码
+    pub fn probe(mut self, config: &ProtocolConfig, frame: &[u8]) -> Self {
+        if let Some(candidate) = CandidateCode::probe(config, frame) {
+            self.candidates.push(candidate);
+        }
+        self
+    }
+}
+
+/// 隔离帧的去向，如写文件、发 kafka 或上报到平台
+pub trait QuarantineSink: Send + Sync {
+    fn capture(&self, frame: &UnknownFrame);
+}
+
+static SINK: Lazy<RwLock<Option<Arc<dyn QuarantineSink>>>> = Lazy::new(|| RwLock::new(None));
+
+/// 设置 (或替换) 全局 sink，未设置时隔离帧只打印日志
+pub fn set_quarantine_sink<S: QuarantineSink + 'static>(sink: S) {
+    let mut guard = SINK.write().unwrap_or_else(|e| e.into_inner());
+    *guard = Some(Arc::new(sink));
+}
+
+pub fn clear_quarantine_sink() {
+    let mut guard = SINK.write().unwrap_or_else(|e| e.into_inner());
+    *guard = None;
+}
+
+/// 隔离一帧。CmdDispatcher 与 ProtocolSniffer::sniff_or_quarantine 识别失败时自动调用，
+/// 手写命令码 match 的协议可在兜底分支中调用
+pub fn quarantine(frame: UnknownFrame) {
+    let sink = SINK.read().unwrap_or_else(|e| e.into_inner()).clone();
+    match sink {
+        Some(sink) => sink.capture(&frame),
+        None => eprintln!(
+            "[WARN] {}Unknown frame quarantined ({}): {}",
+            trace::log_prefix(),
+            frame.reason,
+            frame.hex
+        ),
+    }
+}
+
+/// 内存中保留最近 capacity 帧的 sink，用于调试与测试
+#[derive(Clone)]
+pub struct MemoryQuarantine {
+    capacity: usize,
+    frames: Arc<Mutex<VecDeque<UnknownFrame>>>,
+}
+
+impl MemoryQuarantine {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            frames: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub fn frames(&self) -> Vec<UnknownFrame> {
+        let frames = self.frames.lock().unwrap_or_else(|e| e.into_inner());
+        frames.iter().cloned().collect()
+    }
+
+    /// 取出并清空
+    pub fn drain(&self) -> Vec<UnknownFrame> {
+        let mut frames = self.frames.lock().unwrap_or_else(|e| e.into_inner());
+        frames.drain(..).collect()
+    }
+}
+
+impl QuarantineSink for MemoryQuarantine {
+    fn capture(&self, frame: &UnknownFrame) {
+        let mut frames = self.frames.lock().unwrap_or_else(|e| e.into_inner());
+        if frames.len() == self.capacity {
+            frames.pop_front();
+        }
+        frames.push_back(frame.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_and_ring() {
+        let config = ProtocolConfig::new("demo", 1, 1)
+            .with_head(&[0x68])
+            .with_control(2);
+        let frame = UnknownFrame::new(&[0x68, 0x7f, 0x81, 0x16], "test")
+            .with_protocol("demo")
+            .probe(&config, &[0x68, 0x7f, 0x81, 0x16]);
+        assert_eq!(frame.head_tag.as_deref(), Some("68"));
+        assert_eq!(frame.len, 4);
+        assert_eq!(
+            frame.candidates,
+            [CandidateCode {
+                protocol: "demo".into(),
+                cmd_code: Some("7F".into()),
+                control_code: Some("81".into()),
+            }]
+        );
+        assert!(CandidateCode::probe(&config, &[0x68]).is_none());
+
+        let sink = MemoryQuarantine::new(2);
+        for i in 0..3u8 {
+            sink.capture(&UnknownFrame::new(&[i], "test"));
+        }
+        let frames = sink.drain();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].hex, "01");
+        assert!(sink.frames().is_empty());
+    }
+}
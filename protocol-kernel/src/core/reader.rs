@@ -1,20 +1,38 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use bytes::Bytes;
 use protocol_base::{ProtocolError, ProtocolResult};
 
+#[cfg(feature = "std")]
+use crate::{bridge::trace, utils::code_registry::CodeRegistry, ReportField};
 use crate::{
-    core::parts::rawfield::Rawfield,
+    core::{
+        parts::{diagnostic::Diagnostic, rawfield::Rawfield},
+        DecodeMode,
+    },
     utils::{crc_util, hex_util},
-    ReportField,
 };
 
+// 宽松模式下未指定字段名时，失败字段的名称
+const LENIENT_TITLE: &str = "解析失败";
+
 /// 状态化的字节读取器，用于解析并收集 `Rawfield`。
 #[derive(Debug, Clone)]
 pub struct Reader<'a> {
-    buffer: &'a [u8], // 借用原始报文，零拷贝读取
-    pos: usize,       // 头部游标 (从0开始, 向前推进)
-    sop: usize,       // 尾部游标 (排他性, 从len()开始, 向后推进)
+    buffer: &'a [u8],      // 借用原始报文，零拷贝读取
+    shared: Option<Bytes>, // from_shared 时持有原始报文，字段字节直接切片共享
+    compact: bool,         // 紧凑模式: 字段只记录偏移，不保存 hex
+    raw_hex: bool,         // 生成 ReportField 时是否带上字段的原始 hex
+    pos: usize,            // 头部游标 (从0开始, 向前推进)
+    sop: usize,            // 尾部游标 (排他性, 从len()开始, 向后推进)
     total: usize,
     fields: Vec<Rawfield>,           // 收集所有解析出的字段
     current_field: Option<Rawfield>, // 当前正在解析的字段
+    warnings: Vec<Diagnostic>,       // 非致命的诊断信息
+    mode: DecodeMode,                // 字段翻译失败时中止还是记为告警字段
 }
 
 impl<'a> Reader<'a> {
@@ -22,13 +40,114 @@ impl<'a> Reader<'a> {
     pub fn new(buffer: &'a [u8]) -> Self {
         Self {
             buffer,
+            shared: None,
+            compact: false,
+            raw_hex: false,
             pos: 0,
             sop: buffer.len(), // 初始sop指向缓冲区的末尾 (排他性)
             total: buffer.len(),
             fields: Vec::new(),
             current_field: None,
+            warnings: Vec::new(),
+            mode: DecodeMode::Strict,
+        }
+    }
+
+    /// 换一帧报文重新开始解析，清空字段和警告但保留它们的容量
+    pub fn reset(&mut self, buffer: &'a [u8]) {
+        self.buffer = buffer;
+        self.shared = None;
+        self.pos = 0;
+        self.sop = buffer.len();
+        self.total = buffer.len();
+        self.fields.clear();
+        self.current_field = None;
+        self.warnings.clear();
+    }
+
+    // 取出字段/警告的存储，供 ReaderPool 复用
+    #[cfg(feature = "std")]
+    pub(crate) fn into_storage(mut self) -> (Vec<Rawfield>, Vec<Diagnostic>) {
+        self.fields.clear();
+        self.warnings.clear();
+        (self.fields, self.warnings)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn with_storage(
+        buffer: &'a [u8],
+        fields: Vec<Rawfield>,
+        warnings: Vec<Diagnostic>,
+    ) -> Self {
+        Self {
+            fields,
+            warnings,
+            ..Self::new(buffer)
+        }
+    }
+
+    /// 从共享的报文创建 Reader，解析出的 Rawfield 字节是 `buffer` 的切片而不是副本，
+    /// 大报文逐字段 to_vec 的分配由此省去
+    pub fn from_shared(buffer: &'a Bytes) -> Self {
+        Self {
+            shared: Some(buffer.clone()),
+            ..Self::new(buffer)
+        }
+    }
+
+    /// 宽松模式下字段翻译失败不中止，失败的字段以 alert 字段 (值为错误信息) 记录并跳过。
+    /// 长度不足等游标错误两种模式下都会中止
+    pub fn with_decode_mode(mut self, mode: DecodeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn decode_mode(&self) -> DecodeMode {
+        self.mode
+    }
+
+    /// to_report_fields 生成的 ReportField 带上字段的原始 hex (raw_hex)，默认不带
+    pub fn with_raw_hex(mut self) -> Self {
+        self.raw_hex = true;
+        self
+    }
+
+    /// 紧凑模式: 字段不再保存 hex 字符串，只记录 (起始偏移, 长度)，hex 在读取时生成。
+    /// 与 from_shared 一起使用时字段字节也不复制，适合几百个字段的大报文
+    pub fn compact(mut self) -> Self {
+        self.compact = true;
+        self
+    }
+
+    // [start..end] 的字节，共享模式下只增加引用计数
+    fn slice(&self, start: usize, end: usize) -> Bytes {
+        match self.shared.as_ref() {
+            Some(shared) => shared.slice(start..end),
+            None => Bytes::copy_from_slice(&self.buffer[start..end]),
+        }
+    }
+
+    // translator 原样返回报文字节时: 共享模式下换成报文切片 (它复制出来的那份随即释放)，
+    // 紧凑模式下丢掉 hex 只记录偏移
+    fn share(&self, mut field: Rawfield, start: usize, end: usize) -> Rawfield {
+        if field.bytes[..] != self.buffer[start..end] {
+            return field;
+        }
+        if let Some(shared) = self.shared.as_ref() {
+            field.bytes = shared.slice(start..end);
         }
+        if self.compact {
+            field.hex = String::new();
+            field.span = Some((start, end - start));
+        }
+        field
     }
+
+    /// 返回头部游标的位置，即下一个待读字节在报文中的偏移
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
     /// 返回总字节数
     pub fn total_len(&self) -> usize {
         self.buffer.len()
@@ -73,17 +192,110 @@ impl<'a> Reader<'a> {
         Ok(())
     }
 
+    /// 记录一条非致命警告，自动带上当前字段名和头部游标位置
+    pub fn warn(&mut self, code: &str, message: &str) {
+        let mut diagnostic = Diagnostic::new(code, message).with_offset(self.pos);
+        if let Some(field) = self.current_field.as_ref() {
+            diagnostic = diagnostic.with_field(field.title());
+        }
+        self.warnings.push(diagnostic);
+    }
+
+    pub fn push_warning(&mut self, diagnostic: Diagnostic) {
+        self.warnings.push(diagnostic);
+    }
+
+    pub fn warnings(&self) -> &[Diagnostic] {
+        &self.warnings
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+
+    /// 取出所有警告 (通常在解码结束后转移到 RawCapsule)
+    pub fn take_warnings(&mut self) -> Vec<Diagnostic> {
+        core::mem::take(&mut self.warnings)
+    }
+
+    // 字段解码失败时发出 trace 事件，结果原样返回
+    fn traced<T>(
+        &self,
+        field: Option<&str>,
+        offset: usize,
+        result: ProtocolResult<T>,
+    ) -> ProtocolResult<T> {
+        #[cfg(feature = "std")]
+        if let Err(e) = &result {
+            trace::field_failed(field, offset, e);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = (field, offset);
+        result
+    }
+
+    // 宽松模式下把翻译失败换成 alert 字段，并记一条警告
+    fn recover(
+        &mut self,
+        title: Option<&str>,
+        start: usize,
+        end: usize,
+        result: ProtocolResult<Rawfield>,
+    ) -> ProtocolResult<Rawfield> {
+        match result {
+            Err(e) if self.mode.is_lenient() => {
+                let title = title.unwrap_or(LENIENT_TITLE);
+                let message = e.to_string();
+                self.warnings.push(
+                    Diagnostic::new("field_failed", &message)
+                        .with_field(title)
+                        .with_offset(start),
+                );
+                Ok(Rawfield::from_shared(self.slice(start, end), title, message).with_alert())
+            }
+            result => result,
+        }
+    }
+
     /// 返回剩余未读字节的数量 (pos 和 sop 之间的距离)
     pub fn remaining_len(&self) -> usize {
         self.sop.saturating_sub(self.pos)
     }
 
+    /// 已解析的字段 (no_std 下没有拼音 code，直接使用 Rawfield)
+    pub fn fields(&self) -> ProtocolResult<&Vec<Rawfield>> {
+        Ok(&self.fields)
+    }
+
+    #[cfg(feature = "std")]
     pub fn to_report_fields(&self) -> ProtocolResult<Vec<ReportField>> {
-        let fields = self.fields.clone();
-        let r: Vec<ReportField> = fields.into_iter().map(|f| f.to_report_field()).collect();
+        let r: Vec<ReportField> = self
+            .fields
+            .iter()
+            .cloned()
+            .map(|f| {
+                let code = crate::utils::to_pinyin_cached(&f.title);
+                f.into_report_field(code, self.raw_hex)
+            })
+            .collect();
         Ok(r)
     }
 
+    #[cfg(feature = "std")]
+    pub fn to_report_fields_with(
+        &self,
+        registry: &mut CodeRegistry,
+    ) -> ProtocolResult<Vec<ReportField>> {
+        self.fields
+            .iter()
+            .cloned()
+            .map(|f| {
+                let code = registry.register(&f.title)?;
+                Ok(f.into_report_field(code, self.raw_hex))
+            })
+            .collect()
+    }
+
     /// 核心功能5: (CRC专用) 获取当前游标之间的所有数据
     /// (这个方法*不*移动游标，仅用于CRC计算)
     pub fn read_between_pos_to_sop_not_move(&self) -> ProtocolResult<&[u8]> {
@@ -99,6 +311,31 @@ impl<'a> Reader<'a> {
         Ok(slice.to_vec()) // to_vec() 创建一个副本
     }
 
+    /// 读取n个字节(大端)，共享模式下不复制 (并使游标前进 n)
+    pub fn read_bytes_shared(&mut self, len: usize) -> ProtocolResult<Bytes> {
+        self.check_remaining(len)?;
+        let bytes = self.slice(self.pos, self.pos + len);
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// 读取 N 个字节到定长数组，不分配堆内存 (并使游标前进 N)。
+    /// 常见的 1~8 字节字段用它配合 `u32::from_be_bytes` 等，长度在编译期确定
+    pub fn read_array<const N: usize>(&mut self) -> ProtocolResult<[u8; N]> {
+        self.check_remaining(N)?;
+        let mut array = [0u8; N];
+        array.copy_from_slice(&self.buffer[self.pos..self.pos + N]);
+        self.pos += N;
+        Ok(array)
+    }
+
+    /// 同 read_array，按小端读取 (字节顺序反转)
+    pub fn read_array_le<const N: usize>(&mut self) -> ProtocolResult<[u8; N]> {
+        let mut array = self.read_array::<N>()?;
+        array.reverse();
+        Ok(array)
+    }
+
     /// 2. 读取n个字节并且按照小端格式 -> 返回这n个字节按照小端排列之后的数组 (副本) (并使游标前进 n)
     pub fn read_bytes_le(&mut self, len: usize) -> ProtocolResult<Vec<u8>> {
         self.check_remaining(len)?;
@@ -121,8 +358,12 @@ impl<'a> Reader<'a> {
     where
         F: FnOnce(&[u8]) -> ProtocolResult<Rawfield>,
     {
-        let remaining_bytes = self.read_remaining()?;
-        let raw_field = translator(&remaining_bytes)?;
+        let (offset, end) = (self.pos, self.sop);
+        let buffer = self.buffer;
+        self.pos = self.sop;
+        let raw_field = self.traced(None, offset, translator(&buffer[offset..end]));
+        let raw_field = self.recover(None, offset, end, raw_field)?;
+        let raw_field = self.share(raw_field, offset, end);
         self.current_field = Some(raw_field.clone());
         // 3. 创建并存储 Rawfield
         self.fields.push(raw_field);
@@ -138,13 +379,40 @@ impl<'a> Reader<'a> {
     where
         // 翻译函数接收原始字节切片，返回一个翻译结果
         F: FnOnce(&[u8]) -> ProtocolResult<Rawfield>,
+    {
+        self.translate_head(None, len, translator)
+    }
+
+    /// 同 read_and_translate_head，宽松模式下失败的字段以 title 命名
+    pub fn read_and_translate_field<F>(
+        &mut self,
+        title: &str,
+        len: usize,
+        translator: F,
+    ) -> ProtocolResult<&mut Self>
+    where
+        F: FnOnce(&[u8]) -> ProtocolResult<Rawfield>,
+    {
+        self.translate_head(Some(title), len, translator)
+    }
+
+    fn translate_head<F>(
+        &mut self,
+        title: Option<&str>,
+        len: usize,
+        translator: F,
+    ) -> ProtocolResult<&mut Self>
+    where
+        F: FnOnce(&[u8]) -> ProtocolResult<Rawfield>,
     {
         // 1. 检查并获取原始字节切片 (零拷贝)
         self.check_remaining(len)?;
         let raw_bytes = &self.buffer[self.pos..self.pos + len];
 
         // 2. 调用翻译闭包
-        let raw_field = translator(raw_bytes)?;
+        let raw_field = self.traced(title, self.pos, translator(raw_bytes));
+        let raw_field = self.recover(title, self.pos, self.pos + len, raw_field)?;
+        let raw_field = self.share(raw_field, self.pos, self.pos + len);
         self.current_field = Some(raw_field.clone());
         // 3. 创建并存储 Rawfield
         self.fields.push(raw_field);
@@ -176,7 +444,9 @@ impl<'a> Reader<'a> {
         let raw_bytes = &self.buffer[new_sop..self.sop];
 
         // 4. 调用翻译
-        let raw_field = translator(raw_bytes)?;
+        let raw_field = self.traced(None, new_sop, translator(raw_bytes));
+        let raw_field = self.recover(None, new_sop, self.sop, raw_field)?;
+        let raw_field = self.share(raw_field, new_sop, self.sop);
         self.current_field = Some(raw_field.clone());
         self.fields.push(raw_field);
 
@@ -206,10 +476,15 @@ impl<'a> Reader<'a> {
         // 4. 计算crc并且进行比较
         let expected_crc_bytes = self.read_by_index_not_move(crc_start_pos, crc_end_pos)?;
         let calculated_crc_bytes = crc_util::calculate_from_bytes(crc_mode, expected_crc_bytes)?;
-        crc_util::compare_crc(&crc_hex, calculated_crc_bytes)?;
+        self.traced(
+            Some("crc"),
+            new_sop,
+            crc_util::compare_crc(&crc_hex, calculated_crc_bytes),
+        )?;
 
         // 4. 创建 Rawfield (注意：是 *原始* 字节 `raw_bytes`)
-        let raw_field = Rawfield::new(crc_bytes, "crc".into(), crc_hex);
+        let crc_field = Rawfield::from_shared(self.slice(new_sop, self.sop), "crc", crc_hex);
+        let raw_field = self.share(crc_field, new_sop, self.sop);
         self.current_field = Some(raw_field.clone());
         self.fields.push(raw_field);
 
@@ -288,3 +563,143 @@ impl<'a> Reader<'a> {
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_base::definitions::defi::CrcType;
+
+    fn translate(title: &str) -> impl FnOnce(&[u8]) -> ProtocolResult<Rawfield> + '_ {
+        move |b| Ok(Rawfield::new(b, title, String::new()))
+    }
+
+    #[test]
+    fn test_from_shared() {
+        let mut frame = alloc::vec![0x68, 0x02, 0xAA, 0xBB];
+        let crc = crc_util::calculate_from_bytes(CrcType::Crc16Modbus, &frame).unwrap();
+        frame.extend_from_slice(&crc.to_be_bytes());
+        let frame = Bytes::from(frame);
+
+        let mut reader = Reader::from_shared(&frame);
+        reader
+            .read_and_translate_crc(2, CrcType::Crc16Modbus, 0, -2)
+            .unwrap()
+            .read_and_translate_head(1, translate("起始符"))
+            .unwrap()
+            .read_and_translate_remaining(translate("数据"))
+            .unwrap();
+        let fields = reader.fields().unwrap();
+        assert_eq!(fields[2].bytes(), &[0x02, 0xAA, 0xBB]);
+        // 字段字节指向原始报文，而不是 translator 复制出来的副本
+        for (field, offset) in fields.iter().zip([4, 0, 1]) {
+            assert_eq!(field.bytes().as_ptr(), frame[offset..].as_ptr());
+        }
+
+        let mut reader = Reader::new(&frame);
+        reader
+            .read_and_translate_head(1, translate("起始符"))
+            .unwrap();
+        assert_ne!(reader.fields().unwrap()[0].bytes().as_ptr(), frame.as_ptr());
+    }
+
+    #[test]
+    fn test_compact() {
+        let frame = Bytes::from_static(&[0x68, 0x02, 0xAA, 0xBB, 0x16]);
+        let mut reader = Reader::from_shared(&frame).compact();
+        reader
+            .read_and_translate_head(2, translate("头"))
+            .unwrap()
+            .read_and_translate_tail(1, translate("结束符"))
+            .unwrap()
+            .read_and_translate_remaining(|b| {
+                Ok(Rawfield::new(&[b[0] ^ 0xFF, b[1]], "解密", String::new()))
+            })
+            .unwrap();
+        let fields = reader.fields().unwrap();
+        assert_eq!(fields[0].span(), Some((0, 2)));
+        assert_eq!(fields[0].hex(), "6802");
+        assert_eq!(fields[1].span(), Some((4, 1)));
+        assert_eq!(fields[1].hex_clone(), "16");
+        // translator 改写过的字节与报文不一致，照常保存
+        assert!(!fields[2].is_compact());
+        assert_eq!(fields[2].hex(), "55BB");
+    }
+
+    #[test]
+    fn test_lenient_mode() {
+        let frame = [0x68, 0x01, 0x02, 0x16];
+        let bad = |_: &[u8]| -> ProtocolResult<Rawfield> {
+            Err(ProtocolError::ValidationFailed("bad bcd".into()))
+        };
+        assert!(Reader::new(&frame)
+            .read_and_translate_head(1, translate("起始符"))
+            .unwrap()
+            .read_and_translate_field("读数", 2, bad)
+            .is_err());
+
+        let mut reader = Reader::new(&frame).with_decode_mode(DecodeMode::Lenient);
+        reader
+            .read_and_translate_field("读数", 2, bad)
+            .unwrap()
+            .read_and_translate_tail(1, bad)
+            .unwrap()
+            .read_and_translate_head(1, translate("结束"))
+            .unwrap();
+        let fields = reader.fields().unwrap();
+        assert_eq!(fields[0].title(), "读数");
+        assert_eq!(fields[0].hex(), "6801");
+        assert!(fields[0].is_alert());
+        assert!(fields[0].value().contains("bad bcd"));
+        assert_eq!(fields[1].title(), LENIENT_TITLE);
+        assert!(!fields[2].is_alert());
+        assert_eq!(reader.warnings().len(), 2);
+        // 长度不足仍然中止
+        assert!(reader.read_and_translate_head(1, bad).is_err());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let frame = [0x68, 0x00, 0x01, 0x16];
+        let mut reader = Reader::new(&frame);
+        reader.warn("frame_start", "before any field");
+        reader
+            .read_and_translate_head(1, translate("起始符"))
+            .unwrap()
+            .read_and_translate_field("保留字节", 2, translate("保留字节"))
+            .unwrap();
+        reader.warn("reserved_nonzero", "保留字节不为 0");
+        // 警告不影响后续解析
+        reader
+            .read_and_translate_tail(1, translate("结束符"))
+            .unwrap();
+        assert!(reader.has_warnings());
+        let warnings = reader.warnings();
+        assert_eq!(
+            warnings[0],
+            Diagnostic::new("frame_start", "before any field").with_offset(0)
+        );
+        assert_eq!(warnings[1].field.as_deref(), Some("保留字节"));
+        assert_eq!(warnings[1].offset, Some(3));
+        assert_eq!(reader.fields().unwrap().len(), 3);
+
+        assert_eq!(reader.take_warnings().len(), 2);
+        assert!(!reader.has_warnings());
+    }
+
+    #[test]
+    fn test_read_array() {
+        let frame = [0x68, 0x00, 0x00, 0x01, 0x02, 0x34, 0x12];
+        let mut reader = Reader::new(&frame);
+        let [head] = reader.read_array::<1>().unwrap();
+        assert_eq!(head, 0x68);
+        assert_eq!(u32::from_be_bytes(reader.read_array().unwrap()), 0x0102);
+        assert_eq!(reader.read_array_le::<2>().unwrap(), [0x12, 0x34]);
+        assert!(matches!(
+            reader.read_array::<1>(),
+            Err(ProtocolError::InputTooShort {
+                needed: 1,
+                available: 0
+            })
+        ));
+    }
+}
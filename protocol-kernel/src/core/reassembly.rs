@@ -0,0 +1,242 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    core::parts::{raw_capsule::RawCapsule, traits::Cmd},
+    ProtocolError, ProtocolResult,
+};
+
+/// 分段上传中的一帧携带的分段信息，由协议从帧中解析
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UplinkSegment {
+    // 从 0 开始
+    pub index: u16,
+    pub total: u16,
+    // 表端声明的总字节数，没有时不校验
+    pub total_len: Option<usize>,
+    // 本段的数据部分 (不含帧头、CRC 等)
+    pub data: Vec<u8>,
+}
+
+struct Assembly<T: Cmd> {
+    total: u16,
+    total_len: Option<usize>,
+    started: Instant,
+    // 序号 -> (数据, 该帧的 capsule)
+    parts: BTreeMap<u16, (Vec<u8>, RawCapsule<T>)>,
+}
+
+/// 多帧上传 (冻结数据、事件记录等) 的重组，按 设备 + 会话 区分。
+/// 收齐全部分段后生成一个 RawCapsule: bytes 为各段数据按序拼接，字段与告警依次合并
+pub struct Reassembler<T: Cmd> {
+    timeout: Duration,
+    pending: HashMap<(String, String), Assembly<T>>,
+}
+
+impl<T: Cmd + 'static> Reassembler<T> {
+    /// timeout: 从第一段开始多久内没有收齐就丢弃
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// 加入一段。收齐时返回合并后的 capsule，否则返回 None。
+    /// 分段信息不一致时丢弃该会话已收到的数据并返回错误
+    pub fn push(
+        &mut self,
+        unique: &str,
+        session: &str,
+        segment: UplinkSegment,
+        capsule: RawCapsule<T>,
+    ) -> ProtocolResult<Option<RawCapsule<T>>> {
+        let key = (unique.to_string(), session.to_string());
+        let result = self.insert(&key, segment, capsule);
+        match result {
+            Ok(true) => {
+                let assembly = self.pending.remove(&key).expect("complete assembly");
+                Self::combine(unique, session, assembly).map(Some)
+            }
+            Ok(false) => Ok(None),
+            Err(e) => {
+                self.pending.remove(&key);
+                Err(e)
+            }
+        }
+    }
+
+    // 返回是否已收齐
+    fn insert(
+        &mut self,
+        key: &(String, String),
+        segment: UplinkSegment,
+        capsule: RawCapsule<T>,
+    ) -> ProtocolResult<bool> {
+        if segment.total == 0 || segment.index >= segment.total {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "segment {} of {} is out of range",
+                segment.index, segment.total
+            )));
+        }
+        if self
+            .pending
+            .get(key)
+            .is_some_and(|a| a.started.elapsed() > self.timeout)
+        {
+            self.pending.remove(key);
+        }
+        let assembly = self.pending.entry(key.clone()).or_insert_with(|| Assembly {
+            total: segment.total,
+            total_len: segment.total_len,
+            started: Instant::now(),
+            parts: BTreeMap::new(),
+        });
+        if assembly.total != segment.total {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "segment total changed from {} to {}",
+                assembly.total, segment.total
+            )));
+        }
+        if let Some((data, _)) = assembly.parts.get(&segment.index) {
+            // 重发的同一段忽略，内容不同说明会话已乱
+            if *data != segment.data {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "segment {} received twice with different data",
+                    segment.index
+                )));
+            }
+            return Ok(false);
+        }
+        assembly.total_len = assembly.total_len.or(segment.total_len);
+        assembly
+            .parts
+            .insert(segment.index, (segment.data, capsule));
+        Ok(assembly.parts.len() == assembly.total as usize)
+    }
+
+    fn combine(
+        unique: &str,
+        session: &str,
+        assembly: Assembly<T>,
+    ) -> ProtocolResult<RawCapsule<T>> {
+        let len: usize = assembly.parts.values().map(|(d, _)| d.len()).sum();
+        if let Some(expected) = assembly.total_len {
+            if expected != len {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "reassembled {} bytes for {}/{}, expected {}",
+                    len, unique, session, expected
+                )));
+            }
+        }
+        let mut bytes = Vec::with_capacity(len);
+        let mut parts = assembly.parts.into_values();
+        let (data, first) = parts.next().expect("at least 1 segment");
+        bytes.extend_from_slice(&data);
+        let mut combined = first;
+        for (data, capsule) in parts {
+            bytes.extend_from_slice(&data);
+            combined.append_fields(capsule.field_details);
+            combined.append_warnings(capsule.warnings);
+            combined.success &= capsule.success;
+        }
+        combined.hex = crate::utils::hex_util::bytes_to_hex(&bytes)?;
+        combined.bytes = bytes.into();
+        combined.mark_decoded();
+        Ok(combined)
+    }
+
+    /// 丢弃超时未收齐的会话，返回丢弃的数量
+    pub fn purge_expired(&mut self) -> usize {
+        let before = self.pending.len();
+        let timeout = self.timeout;
+        self.pending.retain(|_, a| a.started.elapsed() <= timeout);
+        before - self.pending.len()
+    }
+
+    /// 正在重组的会话数
+    pub fn in_progress(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ReportField;
+
+    #[derive(Clone)]
+    struct FrozenData;
+
+    impl Cmd for FrozenData {
+        fn code(&self) -> String {
+            "40".into()
+        }
+        fn title(&self) -> String {
+            "冻结数据".into()
+        }
+    }
+
+    fn part(index: u16, data: &[u8]) -> (UplinkSegment, RawCapsule<FrozenData>) {
+        let mut capsule = RawCapsule::new_upstream(&[0x68, index as u8, 0x16]);
+        capsule.set_cmd(FrozenData);
+        capsule.set_device_no("0001");
+        capsule.set_fields(vec![ReportField::new("seg", "分段", index.to_string())]);
+        let segment = UplinkSegment {
+            index,
+            total: 3,
+            total_len: Some(5),
+            data: data.to_vec(),
+        };
+        (segment, capsule)
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let mut reassembler = Reassembler::new(Duration::from_secs(60));
+        for (index, data) in [(2u16, &[0x05][..]), (0, &[0x01, 0x02]), (0, &[0x01, 0x02])] {
+            let (segment, capsule) = part(index, data);
+            assert!(reassembler
+                .push("0001", "s1", segment, capsule)
+                .unwrap()
+                .is_none());
+        }
+        let (segment, capsule) = part(1, &[0x03, 0x04]);
+        let combined = reassembler
+            .push("0001", "s1", segment, capsule)
+            .unwrap()
+            .unwrap();
+        assert_eq!(combined.hex(), "0102030405");
+        let values: Vec<&str> = combined
+            .field_details()
+            .iter()
+            .map(|f| f.value.as_str())
+            .collect();
+        assert_eq!(values, ["0", "1", "2"]);
+        assert_eq!(reassembler.in_progress(), 0);
+    }
+
+    #[test]
+    fn test_invalid_segments() {
+        let mut reassembler = Reassembler::new(Duration::from_secs(60));
+        let (mut segment, capsule) = part(0, &[0x01]);
+        segment.index = 3;
+        assert!(reassembler.push("0001", "s2", segment, capsule).is_err());
+
+        let (segment, capsule) = part(0, &[0x01]);
+        reassembler.push("0001", "s2", segment, capsule).unwrap();
+        let (segment, capsule) = part(0, &[0x09]);
+        assert!(reassembler.push("0001", "s2", segment, capsule).is_err());
+        assert_eq!(reassembler.in_progress(), 0);
+
+        // 总长度不符
+        for (index, data) in [(0u16, &[0x01][..]), (1, &[0x02])] {
+            let (segment, capsule) = part(index, data);
+            reassembler.push("0001", "s3", segment, capsule).unwrap();
+        }
+        let (segment, capsule) = part(2, &[0x03]);
+        assert!(reassembler.push("0001", "s3", segment, capsule).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{core::parts::transport_carrier::TransportCarrier, ProtocolError, ProtocolResult};
+
+// 快照格式版本，格式不兼容地变化时递增
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// 缓存快照。网关重启前导出、启动时导入，避免上下行序号和会话密钥丢失导致设备批量重新注册
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheSnapshot {
+    pub version: u32,
+    // 导出时间 (unix 毫秒)
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub entries: Vec<SnapshotEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotEntry {
+    pub unique: String,
+    pub carrier: TransportCarrier,
+}
+
+impl CacheSnapshot {
+    pub fn new(entries: Vec<SnapshotEntry>) -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            created_at: chrono::Utc::now().timestamp_millis(),
+            entries,
+        }
+    }
+
+    pub fn to_bytes(&self) -> ProtocolResult<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| ProtocolError::CommonError(e.to_string()))
+    }
+
+    pub fn from_bytes(data: &[u8]) -> ProtocolResult<Self> {
+        let snapshot: Self =
+            serde_json::from_slice(data).map_err(|e| ProtocolError::CommonError(e.to_string()))?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(ProtocolError::UnsupportedMode(format!(
+                "cache snapshot version {} (supported up to {})",
+                snapshot.version, SNAPSHOT_VERSION
+            )));
+        }
+        Ok(snapshot)
+    }
+}
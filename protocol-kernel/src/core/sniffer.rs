@@ -0,0 +1,162 @@
+use protocol_base::definitions::defi::CrcType;
+
+use crate::core::{
+    config::ProtocolConfig,
+    quarantine::{quarantine, UnknownFrame},
+    validation::check_crc,
+};
+
+// 各项检查的权重，全部通过时置信度为 1.0
+const HEAD_WEIGHT: f32 = 0.3;
+const TAIL_WEIGHT: f32 = 0.2;
+const LENGTH_WEIGHT: f32 = 0.25;
+const CRC_WEIGHT: f32 = 0.25;
+
+struct Candidate {
+    config: ProtocolConfig,
+    // CRC 算法及参与计算的起始位置，范围到 CRC 之前为止
+    crc: Option<(CrcType, usize)>,
+}
+
+/// 识别结果。confidence 取值 0~1，只累计实际做过的检查 (帧头、帧尾、长度、CRC)，
+/// 所以配置越完整的协议匹配时置信度越高
+#[derive(Debug, Clone, PartialEq)]
+pub struct SniffMatch<'a> {
+    pub config: &'a ProtocolConfig,
+    pub confidence: f32,
+}
+
+/// 多个品牌的表共用一个通道时，按已登记的 ProtocolConfig 识别帧属于哪个协议
+#[derive(Default)]
+pub struct ProtocolSniffer {
+    candidates: Vec<Candidate>,
+}
+
+impl ProtocolSniffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(mut self, config: ProtocolConfig) -> Self {
+        self.candidates.push(Candidate { config, crc: None });
+        self
+    }
+
+    /// 登记协议并校验 CRC (crc_len 需为 2)
+    pub fn register_with_crc(
+        mut self,
+        config: ProtocolConfig,
+        crc_type: CrcType,
+        crc_start: usize,
+    ) -> Self {
+        self.candidates.push(Candidate {
+            config,
+            crc: Some((crc_type, crc_start)),
+        });
+        self
+    }
+
+    /// 所有可能的协议，按置信度从高到低排列。任意一项检查不通过的协议会被排除
+    pub fn rank(&self, frame: &[u8]) -> Vec<SniffMatch<'_>> {
+        let mut matches: Vec<SniffMatch> = self
+            .candidates
+            .iter()
+            .filter_map(|c| {
+                score(c, frame).map(|confidence| SniffMatch {
+                    config: &c.config,
+                    confidence,
+                })
+            })
+            .filter(|m| m.confidence > 0.0)
+            .collect();
+        // 同分时保持登记顺序
+        matches.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        matches
+    }
+
+    /// 置信度最高的协议
+    pub fn sniff(&self, frame: &[u8]) -> Option<SniffMatch<'_>> {
+        self.rank(frame).into_iter().next()
+    }
+
+    /// 同 sniff，没有协议匹配时隔离该帧，并按每个已登记协议的位置附上候选命令码/控制码
+    pub fn sniff_or_quarantine(&self, frame: &[u8]) -> Option<SniffMatch<'_>> {
+        let found = self.sniff(frame);
+        if found.is_none() {
+            let unknown = self
+                .candidates
+                .iter()
+                .fold(UnknownFrame::new(frame, "no protocol matched"), |f, c| {
+                    f.probe(&c.config, frame)
+                });
+            quarantine(unknown);
+        }
+        found
+    }
+}
+
+fn score(candidate: &Candidate, frame: &[u8]) -> Option<f32> {
+    let config = &candidate.config;
+    // 帧头/帧尾、长度域、定长以及字段位置
+    config.check_frame(frame).ok()?;
+    let mut confidence = 0.0;
+    if !config.head.is_empty() {
+        confidence += HEAD_WEIGHT;
+    }
+    if !config.tail.is_empty() {
+        confidence += TAIL_WEIGHT;
+    }
+    if config.fixed_len.is_some() || config.length_index.is_some() {
+        confidence += LENGTH_WEIGHT;
+    }
+    if let Some((crc_type, crc_start)) = candidate.crc {
+        check_crc(config, frame, crc_type, crc_start).ok()?;
+        confidence += CRC_WEIGHT;
+    }
+    Some(confidence)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::crc_util;
+
+    fn with_crc(mut body: Vec<u8>, tail: u8) -> Vec<u8> {
+        let crc = crc_util::calculate_from_bytes(CrcType::Crc16Modbus, &body[1..]).unwrap();
+        body.extend_from_slice(&crc.to_be_bytes());
+        body.push(tail);
+        body
+    }
+
+    #[test]
+    fn test_sniff() {
+        let brand_a = ProtocolConfig::new("brand-a", 1, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16])
+            .with_length_field(2, 1, false)
+            .with_data_offset(3)
+            .with_crc_len(2);
+        let brand_b = ProtocolConfig::new("brand-b", 1, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16]);
+        let brand_c = ProtocolConfig::new("brand-c", 1, 1).with_head(&[0xAA]);
+        let sniffer = ProtocolSniffer::new()
+            .register(brand_b)
+            .register_with_crc(brand_a, CrcType::Crc16Modbus, 1)
+            .register(brand_c);
+
+        let frame = with_crc(vec![0x68, 0x01, 0x02, 0x11, 0x22], 0x16);
+        let ranked = sniffer.rank(&frame);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].config.name, "brand-a");
+        assert!((ranked[0].confidence - 1.0).abs() < f32::EPSILON);
+        assert_eq!(ranked[1].config.name, "brand-b");
+
+        // CRC 错误时只剩下不校验 CRC 的协议
+        let mut corrupted = frame.clone();
+        corrupted[3] ^= 0xFF;
+        assert_eq!(sniffer.sniff(&corrupted).unwrap().config.name, "brand-b");
+
+        assert!(sniffer.sniff(&[0x55, 0x01, 0x16]).is_none());
+    }
+}
@@ -0,0 +1,301 @@
+//! 依赖上一帧的字段: 增量编码的累计量、每帧翻转的应答位。
+//! 上一帧的值通过 DecodeContext 的 state 读写，解码成功后随 commit 写回缓存
+use protocol_base::{ProtocolError, ProtocolResult};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{context::DecodeContext, parts::report_field::ValueType},
+    FieldTranslator, Rawfield,
+};
+
+/// 帧中为本次增量，输出累计值 = 上次累计值 + 增量。
+/// 没有上次的值 (新设备或缓存过期) 时以 base 为起点。
+/// 增量与上一帧相同视为重发，不再累加，直接输出上次的累计值
+#[derive(Debug, Clone)]
+pub struct DeltaCounterDecoder {
+    title: String,
+    // 状态 key，同一协议内的多个累计量需不同
+    key: String,
+    // 小数位数，累计值按整数保存，输出时换算
+    scale: u32,
+    swap: bool,
+    base: u64,
+}
+
+// 累计值与上一帧的增量一起保存，用于识别重发
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+struct DeltaState {
+    total: u64,
+    upstream: u64,
+}
+
+impl DeltaCounterDecoder {
+    pub fn new(title: &str, key: &str) -> Self {
+        Self {
+            title: title.into(),
+            key: key.into(),
+            scale: 0,
+            swap: false,
+            base: 0,
+        }
+    }
+
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// 小端
+    pub fn swapped(mut self) -> Self {
+        self.swap = true;
+        self
+    }
+
+    pub fn with_base(mut self, base: u64) -> Self {
+        self.base = base;
+        self
+    }
+
+    fn delta(&self, bytes: &[u8]) -> ProtocolResult<u64> {
+        if bytes.is_empty() || bytes.len() > 8 {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "{} delta must be 1..=8 bytes, got {}",
+                self.title,
+                bytes.len()
+            )));
+        }
+        let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+        Ok(if self.swap {
+            bytes.iter().rev().fold(0, fold)
+        } else {
+            bytes.iter().fold(0, fold)
+        })
+    }
+
+    fn field(&self, bytes: &[u8], units: u64) -> ProtocolResult<Rawfield> {
+        let units = i64::try_from(units).map_err(|_| {
+            ProtocolError::ValidationFailed(format!("{} overflows: {}", self.title, units))
+        })?;
+        // scale 超过 28 位时 Decimal 无法表示
+        let value = Decimal::try_new(units, self.scale)
+            .map_err(|e| ProtocolError::ValidationFailed(format!("{}: {}", self.title, e)))?
+            .to_string();
+        let value_type = if self.scale == 0 {
+            ValueType::Int
+        } else {
+            ValueType::Float
+        };
+        Ok(Rawfield::new(bytes, self.title.as_str(), value).with_value_type(value_type))
+    }
+}
+
+impl FieldTranslator for DeltaCounterDecoder {
+    /// 没有上下文时只能输出增量本身
+    fn translate(&self, bytes: &[u8]) -> ProtocolResult<Rawfield> {
+        self.field(bytes, self.delta(bytes)?)
+    }
+
+    fn translate_with(&self, bytes: &[u8], ctx: &mut DecodeContext) -> ProtocolResult<Rawfield> {
+        let delta = self.delta(bytes)?;
+        let previous = ctx.state::<DeltaState>(&self.key);
+        if let Some(state) = previous.filter(|state| state.upstream == delta) {
+            return self.field(bytes, state.total);
+        }
+        let total = previous
+            .map_or(self.base, |state| state.total)
+            .checked_add(delta)
+            .ok_or_else(|| {
+                ProtocolError::ValidationFailed(format!("{} accumulation overflows", self.title))
+            })?;
+        let field = self.field(bytes, total)?;
+        ctx.set_state(
+            &self.key,
+            DeltaState {
+                total,
+                upstream: delta,
+            },
+        )?;
+        Ok(field)
+    }
+}
+
+/// 每发一帧新数据就翻转一次的标志位 (常见于应答/序列位)。
+/// 与上一帧相同说明是重发，输出 "重发"，否则输出 "新帧"
+#[derive(Debug, Clone)]
+pub struct ToggleBitDecoder {
+    title: String,
+    key: String,
+    mask: u8,
+}
+
+pub const TOGGLE_NEW: &str = "新帧";
+pub const TOGGLE_REPEATED: &str = "重发";
+
+impl ToggleBitDecoder {
+    pub fn new(title: &str, key: &str, mask: u8) -> Self {
+        Self {
+            title: title.into(),
+            key: key.into(),
+            mask,
+        }
+    }
+
+    fn bit(&self, bytes: &[u8]) -> ProtocolResult<bool> {
+        bytes
+            .first()
+            .map(|b| b & self.mask != 0)
+            .ok_or(ProtocolError::InputTooShort {
+                needed: 1,
+                available: 0,
+            })
+    }
+}
+
+impl FieldTranslator for ToggleBitDecoder {
+    /// 没有上下文时输出位的取值
+    fn translate(&self, bytes: &[u8]) -> ProtocolResult<Rawfield> {
+        let bit = self.bit(bytes)?;
+        Ok(
+            Rawfield::new(bytes, self.title.as_str(), (bit as u8).to_string())
+                .with_value_type(ValueType::Bool),
+        )
+    }
+
+    fn translate_with(&self, bytes: &[u8], ctx: &mut DecodeContext) -> ProtocolResult<Rawfield> {
+        let bit = self.bit(bytes)?;
+        let value = if ctx.state::<bool>(&self.key) == Some(bit) {
+            TOGGLE_REPEATED
+        } else {
+            ctx.set_state(&self.key, bit)?;
+            TOGGLE_NEW
+        };
+        Ok(
+            Rawfield::new(bytes, self.title.as_str(), value.into())
+                .with_value_type(ValueType::Enum),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{core::cache::ProtocolCache, AutoDecoding, AutoDecodingParam, Reader};
+
+    enum Field {
+        Flow,
+        Ack,
+    }
+
+    impl AutoDecodingParam for Field {
+        fn byte_length(&self) -> usize {
+            match self {
+                Field::Flow => 2,
+                Field::Ack => 1,
+            }
+        }
+
+        fn title(&self) -> String {
+            match self {
+                Field::Flow => "累计流量".into(),
+                Field::Ack => "应答位".into(),
+            }
+        }
+
+        fn translate_with(
+            &self,
+            bytes: &[u8],
+            ctx: &mut DecodeContext,
+        ) -> ProtocolResult<Rawfield> {
+            match self {
+                Field::Flow => DeltaCounterDecoder::new("累计流量", "flow")
+                    .with_scale(2)
+                    .translate_with(bytes, ctx),
+                Field::Ack => {
+                    ToggleBitDecoder::new("应答位", "ack", 0x80).translate_with(bytes, ctx)
+                }
+            }
+        }
+    }
+
+    struct Report;
+
+    impl AutoDecoding<Field> for Report {
+        fn variants(&self) -> Vec<Field> {
+            vec![Field::Flow, Field::Ack]
+        }
+    }
+
+    fn decode(unique: &str, frame: &[u8]) -> Vec<String> {
+        let mut ctx = DecodeContext::for_device("demo", unique);
+        let mut reader = Reader::new(frame);
+        Report.auto_process_with(&mut reader, &mut ctx).unwrap();
+        assert!(!ctx.is_dirty());
+        reader
+            .fields()
+            .unwrap()
+            .iter()
+            .map(|f| f.value_clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_state_persists_across_frames() {
+        ProtocolCache::remove("stateful-delta-test");
+        assert_eq!(
+            decode("stateful-delta-test", &[0x00, 0x64, 0x80]),
+            ["1.00", TOGGLE_NEW]
+        );
+        assert_eq!(
+            decode("stateful-delta-test", &[0x00, 0x32, 0x00]),
+            ["1.50", TOGGLE_NEW]
+        );
+        assert_eq!(
+            decode("stateful-delta-test", &[0x00, 0x00, 0x00]),
+            ["1.50", TOGGLE_REPEATED]
+        );
+        let carrier = ProtocolCache::read("stateful-delta-test").unwrap();
+        assert_eq!(
+            carrier.get_ext::<DeltaState>("flow"),
+            Some(DeltaState {
+                total: 150,
+                upstream: 0
+            })
+        );
+        ProtocolCache::remove("stateful-delta-test");
+
+        assert_eq!(
+            DeltaCounterDecoder::new("t", "k")
+                .swapped()
+                .translate(&[0x01, 0x02])
+                .unwrap()
+                .value(),
+            "513"
+        );
+        // 超出 Decimal 的小数位数上限时报错而不是 panic
+        assert!(matches!(
+            DeltaCounterDecoder::new("t", "k")
+                .with_scale(29)
+                .translate(&[0x01]),
+            Err(ProtocolError::ValidationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_retransmit_not_accumulated() {
+        ProtocolCache::remove("stateful-retransmit-test");
+        assert_eq!(
+            decode("stateful-retransmit-test", &[0x00, 0x64, 0x80]),
+            ["1.00", TOGGLE_NEW]
+        );
+        assert_eq!(
+            decode("stateful-retransmit-test", &[0x00, 0x64, 0x80]),
+            ["1.00", TOGGLE_REPEATED]
+        );
+        assert_eq!(
+            decode("stateful-retransmit-test", &[0x00, 0x32, 0x00]),
+            ["1.50", TOGGLE_NEW]
+        );
+        ProtocolCache::remove("stateful-retransmit-test");
+    }
+}
@@ -0,0 +1,186 @@
+use crate::{core::config::ProtocolConfig, ProtocolError};
+
+// 缓冲区上限，超过后丢弃旧数据重新同步
+const DEFAULT_MAX_BUFFER: usize = 4096;
+
+/// 从字节流 (TCP、串口) 中按 ProtocolConfig 切出完整的帧。
+/// 有定长或长度域时按长度切分，否则按帧尾切分；不合法的数据会被逐字节丢弃直到重新对齐帧头
+#[derive(Debug, Clone)]
+pub struct StreamingReader {
+    config: ProtocolConfig,
+    buffer: Vec<u8>,
+    max_buffer: usize,
+}
+
+impl StreamingReader {
+    pub fn new(config: ProtocolConfig) -> Self {
+        Self {
+            config,
+            buffer: Vec::new(),
+            max_buffer: DEFAULT_MAX_BUFFER,
+        }
+    }
+
+    pub fn with_max_buffer(mut self, max_buffer: usize) -> Self {
+        self.max_buffer = max_buffer;
+        self
+    }
+
+    pub fn config(&self) -> &ProtocolConfig {
+        &self.config
+    }
+
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// 尚未切出的字节数
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// 取出下一个完整的帧，数据还不够时返回 None
+    pub fn next_frame(&mut self) -> Option<Vec<u8>> {
+        loop {
+            if !self.align_head() {
+                return None;
+            }
+            match self.frame_len() {
+                Ok(Some(len)) => return Some(self.buffer.drain(..len).collect()),
+                Ok(None) => return None,
+                // 当前位置不是合法的帧，丢弃 1 字节重新对齐
+                Err(_) => {
+                    self.buffer.drain(..1);
+                }
+            }
+        }
+    }
+
+    /// 所有已缓冲的完整帧
+    pub fn drain_frames(&mut self) -> Vec<Vec<u8>> {
+        std::iter::from_fn(|| self.next_frame()).collect()
+    }
+
+    // 丢弃帧头之前的数据，没有找到帧头时返回 false (保留可能是半个帧头的尾部字节)
+    fn align_head(&mut self) -> bool {
+        let head = &self.config.head;
+        if head.is_empty() {
+            return !self.buffer.is_empty();
+        }
+        match self.buffer.windows(head.len()).position(|w| w == head) {
+            Some(pos) => {
+                self.buffer.drain(..pos);
+                true
+            }
+            None => {
+                let keep = (head.len() - 1).min(self.buffer.len());
+                self.buffer.drain(..self.buffer.len() - keep);
+                false
+            }
+        }
+    }
+
+    // 缓冲区开头完整帧的长度。Ok(None) 表示需要更多数据，Err 表示开头不是合法的帧
+    fn frame_len(&self) -> Result<Option<usize>, ProtocolError> {
+        let config = &self.config;
+        match config.expected_len(&self.buffer) {
+            Ok(Some(len)) => {
+                if len < config.min_len() || len > self.max_buffer {
+                    return Err(ProtocolError::ValidationFailed(format!(
+                        "{} frame length {} out of range",
+                        config.name, len
+                    )));
+                }
+                if self.buffer.len() < len {
+                    return Ok(None);
+                }
+                config.check_frame(&self.buffer[..len]).map(|_| Some(len))
+            }
+            Ok(None) => self.tail_delimited_len(),
+            Err(ProtocolError::InputTooShort { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn tail_delimited_len(&self) -> Result<Option<usize>, ProtocolError> {
+        let config = &self.config;
+        if config.tail.is_empty() {
+            // 既没有长度信息也没有帧尾 (如 UDP)，缓冲区整体视为一帧
+            return match config.check_frame(&self.buffer) {
+                Ok(()) => Ok(Some(self.buffer.len())),
+                Err(ProtocolError::InputTooShort { .. }) => Ok(None),
+                Err(e) => Err(e),
+            };
+        }
+        let tail = &config.tail;
+        let start = config.min_len().max(tail.len()) - tail.len();
+        let found = (start..=self.buffer.len().saturating_sub(tail.len()))
+            .filter(|i| self.buffer[*i..].starts_with(tail))
+            .map(|i| i + tail.len())
+            .find(|end| config.check_frame(&self.buffer[..*end]).is_ok());
+        match found {
+            Some(end) => Ok(Some(end)),
+            None if self.buffer.len() > self.max_buffer => {
+                Err(ProtocolError::ValidationFailed(format!(
+                    "no {} frame tail within {} bytes",
+                    config.name, self.max_buffer
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_delimited() {
+        // 68 | cmd | len | data | 16
+        let config = ProtocolConfig::new("len", 1, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16])
+            .with_length_field(2, 1, false)
+            .with_data_offset(3);
+        let mut reader = StreamingReader::new(config);
+        // 垃圾数据 + 一帧半
+        reader.push(&[0x00, 0xFF, 0x68, 0x01, 0x02, 0xAA]);
+        assert!(reader.next_frame().is_none());
+        reader.push(&[0xBB, 0x16, 0x68, 0x02, 0x00]);
+        assert_eq!(
+            reader.next_frame().unwrap(),
+            vec![0x68, 0x01, 0x02, 0xAA, 0xBB, 0x16]
+        );
+        reader.push(&[0x16]);
+        assert_eq!(reader.drain_frames(), vec![vec![0x68, 0x02, 0x00, 0x16]]);
+        assert_eq!(reader.buffered(), 0);
+
+        // 帧尾不符的帧被跳过
+        reader.push(&[0x68, 0x01, 0x01, 0xAA, 0x17, 0x68, 0x03, 0x00, 0x16]);
+        assert_eq!(reader.next_frame().unwrap(), vec![0x68, 0x03, 0x00, 0x16]);
+    }
+
+    #[test]
+    fn test_tail_delimited() {
+        let config = ProtocolConfig::new("tail", 1, 1)
+            .with_head(&[0x7E, 0x7E])
+            .with_tail(&[0x0D, 0x0A]);
+        let mut reader = StreamingReader::new(config);
+        reader.push(&[0x01, 0x7E]);
+        assert!(reader.next_frame().is_none());
+        reader.push(&[0x7E, 0x10, 0x20, 0x0D]);
+        assert!(reader.next_frame().is_none());
+        reader.push(&[0x0A, 0x7E, 0x7E]);
+        assert_eq!(
+            reader.next_frame().unwrap(),
+            vec![0x7E, 0x7E, 0x10, 0x20, 0x0D, 0x0A]
+        );
+        assert!(reader.next_frame().is_none());
+        assert_eq!(reader.buffered(), 2);
+    }
+}
@@ -1,6 +1,13 @@
-use std::fmt::Display;
-use std::marker::PhantomData;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Display;
+use core::marker::PhantomData;
 
+use crate::core::parts::report_field::ValueType;
 use crate::math_util::{self, DecimalRoundingMode};
 use crate::{
     handle_int, handle_int_encode, hex_util, ProtocolError, ProtocolResult, Rawfield, Symbol,
@@ -26,7 +33,7 @@ pub enum FieldType {
 
 impl PartialEq for FieldType {
     fn eq(&self, other: &Self) -> bool {
-        std::mem::discriminant(self) == std::mem::discriminant(other)
+        core::mem::discriminant(self) == core::mem::discriminant(other)
     }
 }
 
@@ -77,39 +84,62 @@ impl FieldType {
         }
     }
 
+    /// 解码结果的值类型。整数带缩放 (scale != 1) 时结果是小数
+    pub fn value_type(&self) -> Option<ValueType> {
+        match self {
+            FieldType::Empty => None,
+            FieldType::StringOrBCD | FieldType::Ascii => Some(ValueType::String),
+            FieldType::Float | FieldType::Double => Some(ValueType::Float),
+            _ => match self.scale() {
+                Some(scale) if scale != 1.0 => Some(ValueType::Float),
+                _ => Some(ValueType::Int),
+            },
+        }
+    }
+
+    /// 整数类型的缩放倍数
+    pub fn scale(&self) -> Option<f64> {
+        match self {
+            FieldType::UnsignedU8(scale)
+            | FieldType::UnsignedU16(scale)
+            | FieldType::UnsignedU32(scale)
+            | FieldType::UnsignedU64(scale)
+            | FieldType::SignedI8(scale)
+            | FieldType::SignedI16(scale)
+            | FieldType::SignedI32(scale)
+            | FieldType::SignedI64(scale) => Some(*scale),
+            _ => None,
+        }
+    }
+
     // 下行编码
     pub fn encode(&self, input: &str) -> ProtocolResult<Vec<u8>> {
+        self.encode_with_title(input, "")
+    }
+
+    // 下行编码，出错时在错误信息里带上字段名称
+    pub fn encode_with_title(&self, input: &str, title: &str) -> ProtocolResult<Vec<u8>> {
         match self {
             FieldType::Empty => Ok(vec![]),
             FieldType::StringOrBCD => {
                 let bytes = hex_util::hex_to_bytes(input)?;
                 Ok(bytes)
             }
-            FieldType::UnsignedU8(scale) => handle_int_encode!(u8, 1, input, *scale),
-            FieldType::UnsignedU16(scale) => handle_int_encode!(u16, 2, input, *scale),
-            FieldType::UnsignedU32(scale) => handle_int_encode!(u32, 4, input, *scale),
-            FieldType::UnsignedU64(scale) => handle_int_encode!(u64, 8, input, *scale),
-            FieldType::SignedI8(scale) => handle_int_encode!(i8, 1, input, *scale),
-            FieldType::SignedI16(scale) => handle_int_encode!(i16, 2, input, *scale),
-            FieldType::SignedI32(scale) => handle_int_encode!(i32, 4, input, *scale),
-            FieldType::SignedI64(scale) => handle_int_encode!(i64, 8, input, *scale),
+            FieldType::UnsignedU8(scale) => handle_int_encode!(u8, 1, input, *scale, title),
+            FieldType::UnsignedU16(scale) => handle_int_encode!(u16, 2, input, *scale, title),
+            FieldType::UnsignedU32(scale) => handle_int_encode!(u32, 4, input, *scale, title),
+            FieldType::UnsignedU64(scale) => handle_int_encode!(u64, 8, input, *scale, title),
+            FieldType::SignedI8(scale) => handle_int_encode!(i8, 1, input, *scale, title),
+            FieldType::SignedI16(scale) => handle_int_encode!(i16, 2, input, *scale, title),
+            FieldType::SignedI32(scale) => handle_int_encode!(i32, 4, input, *scale, title),
+            FieldType::SignedI64(scale) => handle_int_encode!(i64, 8, input, *scale, title),
             FieldType::Float => {
-                let value: f32 = input.parse().map_err(|_| {
-                    ProtocolError::ValidationFailed(format!(
-                        "Failed to parse input '{}' as f32",
-                        input
-                    ))
-                })?;
+                let value = math_util::parse_numeric(input)? as f32;
                 let bytes = value.to_be_bytes();
                 Ok(bytes.to_vec())
             }
             FieldType::Double => {
-                let value: f64 = input.parse().map_err(|_| {
-                    ProtocolError::ValidationFailed(format!(
-                        "Failed to parse input '{}' as f64",
-                        input
-                    ))
-                })?;
+                let value = math_util::parse_numeric(input)?;
                 let bytes = value.to_be_bytes();
                 Ok(bytes.to_vec())
             }
@@ -223,27 +253,36 @@ impl<T: TryFromBytes> SingleFieldDecode for FieldEnumDecoder<T> {
 
 pub trait FieldTranslator {
     fn translate(&self, bytes: &[u8]) -> ProtocolResult<Rawfield>;
+
+    /// 需要设备状态或请求参数的翻译器覆盖此方法，默认忽略上下文
+    #[cfg(feature = "std")]
+    fn translate_with(
+        &self,
+        bytes: &[u8],
+        _ctx: &mut crate::core::context::DecodeContext,
+    ) -> ProtocolResult<Rawfield> {
+        self.translate(bytes)
+    }
 }
 
 impl FieldTranslator for FieldConvertDecoder {
     fn translate(&self, bytes: &[u8]) -> ProtocolResult<Rawfield> {
-        let mut copied_bytes = bytes.to_vec(); // 替代 clone_from_slice，更简单
-        let input_bytes = if self.swap && bytes.len() > 1 {
-            copied_bytes.reverse();
-            copied_bytes
+        let ft = &self.filed_type;
+        let value = if self.swap && bytes.len() > 1 {
+            hex_util::with_swapped(bytes, |swapped| ft.decode(swapped))?
         } else {
-            copied_bytes
+            ft.decode(bytes)?
         };
-        let ft = &self.filed_type;
-        let mut value = ft.decode(&input_bytes)?;
-        // 如果有符号，拼接上去
-        if self.symbol.is_some() {
-            let symbol_some_clone = self.symbol.clone();
-            let symbol = symbol_some_clone.unwrap();
-            value += " ";
-            value += symbol.tag().as_str();
+        let mut rf = Rawfield::new(bytes, self.title.as_str(), value);
+        // value 仍拼接单位 (兼容已有平台)，同时单独记录在 unit 中
+        if let Some(symbol) = &self.symbol {
+            let tag = symbol.tag();
+            rf.value = format!("{} {}", rf.value, tag);
+            rf.unit = Some(tag);
         }
-        Ok(Rawfield::new(bytes, self.title.clone(), value))
+        rf.value_type = ft.value_type();
+        rf.scale = ft.scale();
+        Ok(rf)
     }
 }
 
@@ -265,7 +304,7 @@ impl FieldTranslator for FieldCompareDecoder {
         }
         let hex = hex_util::bytes_to_hex(&input_bytes)?;
 
-        let rf = Rawfield::new(bytes, self.title.clone(), hex);
+        let rf = Rawfield::new(bytes, self.title.as_str(), hex);
 
         Ok(rf)
     }
@@ -288,7 +327,8 @@ impl<T: TryFromBytes> FieldTranslator for FieldEnumDecoder<T> {
             .unwrap_or_else(|| key_value.to_string());
 
         // 3. 构建 Rawfield
-        let rf = Rawfield::new(bytes, self.title.clone(), value_str);
+        let rf = Rawfield::new(bytes, self.title.as_str(), value_str)
+            .with_value_type(ValueType::Enum);
         Ok(rf)
     }
 }
@@ -455,3 +495,45 @@ impl TryFromBytes for String {
         }
     }
 }
+
+// 不依赖 std，CI 中同时以 --no-default-features 运行
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_type_round_trip() {
+        let cases: [(FieldType, &[u8], &str); 8] = [
+            (FieldType::StringOrBCD, &[0x20, 0x23, 0x05], "202305"),
+            (FieldType::UnsignedU8(1.0), &[0xFF], "255"),
+            (FieldType::UnsignedU16(0.1), &[0x04, 0xD2], "123.4"),
+            (
+                FieldType::UnsignedU32(0.01),
+                &[0x00, 0x00, 0x04, 0xD2],
+                "12.34",
+            ),
+            (FieldType::SignedI16(1.0), &[0xFF, 0x85], "-123"),
+            (
+                FieldType::SignedI32(0.001),
+                &[0xFF, 0xFF, 0xFF, 0x85],
+                "-0.123",
+            ),
+            (FieldType::Float, &[0x40, 0x48, 0x00, 0x00], "3.125"),
+            (FieldType::Ascii, b"GAS01", "GAS01"),
+        ];
+        for (field_type, bytes, text) in cases {
+            assert_eq!(field_type.decode(bytes).unwrap(), text, "{:?}", field_type);
+            assert_eq!(field_type.encode(text).unwrap(), bytes, "{:?}", field_type);
+        }
+    }
+
+    #[test]
+    fn test_field_type_invalid_input() {
+        assert!(FieldType::UnsignedU16(1.0).decode(&[0x01]).is_err());
+        assert!(FieldType::Float.decode(&[0x00; 8]).is_err());
+        assert!(FieldType::Ascii.decode(&[0xC3, 0xA9]).is_err());
+        assert!(FieldType::SignedI8(1.0).encode("128").is_err());
+        assert!(FieldType::UnsignedU8(1.0).encode("abc").is_err());
+        assert!(FieldType::StringOrBCD.encode("ZZ").is_err());
+    }
+}
@@ -0,0 +1,263 @@
+//! 只做帧级校验 (帧头、帧尾、帧长、CRC)，不解码字段。
+//! 网络层收到数据后先用它丢弃明显的垃圾帧，健康检查也用它统计坏帧原因
+use protocol_base::{definitions::defi::CrcType, ProtocolError, ProtocolResult};
+use serde::Serialize;
+
+use crate::{core::config::ProtocolConfig, utils::crc_util};
+
+/// 校验项，按执行顺序排列
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FrameCheck {
+    // 帧长足以包含配置中的各个固定位置字段
+    MinLength,
+    Head,
+    Tail,
+    // 定长或长度域
+    Length,
+    Crc,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    // 配置中没有该项 (如无帧尾)，或前面的检查失败导致无法检查
+    Skipped,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckOutcome {
+    pub check: FrameCheck,
+    pub status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// 一帧的校验报告。与 check_frame 不同，不在第一个错误处返回，尽量给出每一项的结果
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameReport {
+    pub protocol: String,
+    pub len: usize,
+    pub valid: bool,
+    pub checks: Vec<CheckOutcome>,
+}
+
+impl FrameReport {
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    pub fn status(&self, check: FrameCheck) -> Option<CheckStatus> {
+        self.checks
+            .iter()
+            .find(|c| c.check == check)
+            .map(|c| c.status)
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &CheckOutcome> {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Failed)
+    }
+
+    /// 转为 ProtocolResult，失败时错误信息为第一个失败项
+    pub fn into_result(self) -> ProtocolResult<()> {
+        match self.failed().next() {
+            None => Ok(()),
+            Some(c) => Err(ProtocolError::ValidationFailed(format!(
+                "{} frame check {:?} failed: {}",
+                self.protocol,
+                c.check,
+                c.detail.as_deref().unwrap_or_default()
+            ))),
+        }
+    }
+
+    fn push(&mut self, check: FrameCheck, result: Option<ProtocolResult<()>>) {
+        let (status, detail) = match result {
+            None => (CheckStatus::Skipped, None),
+            Some(Ok(())) => (CheckStatus::Passed, None),
+            Some(Err(e)) => (CheckStatus::Failed, Some(e.to_string())),
+        };
+        if status == CheckStatus::Failed {
+            self.valid = false;
+        }
+        self.checks.push(CheckOutcome {
+            check,
+            status,
+            detail,
+        });
+    }
+}
+
+/// 校验帧头/帧尾/帧长，config 中没有配置的项记为 Skipped，CRC 始终为 Skipped
+pub fn validate_frame(bytes: &[u8], config: &ProtocolConfig) -> FrameReport {
+    validate(bytes, config, None)
+}
+
+/// 同 validate_frame，并按 crc_type 从 crc_start 起计算 CRC (crc_len 需为 2)
+pub fn validate_frame_with_crc(
+    bytes: &[u8],
+    config: &ProtocolConfig,
+    crc_type: CrcType,
+    crc_start: usize,
+) -> FrameReport {
+    validate(bytes, config, Some((crc_type, crc_start)))
+}
+
+fn validate(bytes: &[u8], config: &ProtocolConfig, crc: Option<(CrcType, usize)>) -> FrameReport {
+    let mut report = FrameReport {
+        protocol: config.name.clone(),
+        len: bytes.len(),
+        valid: true,
+        checks: Vec::with_capacity(5),
+    };
+    let needed = config.min_len();
+    let long_enough = bytes.len() >= needed;
+    report.push(
+        FrameCheck::MinLength,
+        Some(if long_enough {
+            Ok(())
+        } else {
+            Err(ProtocolError::InputTooShort {
+                needed,
+                available: bytes.len(),
+            })
+        }),
+    );
+    report.push(
+        FrameCheck::Head,
+        (!config.head.is_empty()).then(|| {
+            bytes
+                .starts_with(&config.head)
+                .then_some(())
+                .ok_or_else(|| mismatch("head", &config.head, bytes.get(..config.head.len())))
+        }),
+    );
+    report.push(
+        FrameCheck::Tail,
+        (!config.tail.is_empty()).then(|| {
+            let tail = bytes
+                .len()
+                .checked_sub(config.tail.len())
+                .map(|start| &bytes[start..]);
+            bytes
+                .ends_with(&config.tail)
+                .then_some(())
+                .ok_or_else(|| mismatch("tail", &config.tail, tail))
+        }),
+    );
+    let has_length = config.fixed_len.is_some() || config.length_index.is_some();
+    report.push(
+        FrameCheck::Length,
+        has_length.then(|| match config.expected_len(bytes)? {
+            Some(expected) if expected != bytes.len() => {
+                Err(ProtocolError::ValidationFailed(format!(
+                    "length {} does not match expected {}",
+                    bytes.len(),
+                    expected
+                )))
+            }
+            _ => Ok(()),
+        }),
+    );
+    // 帧长不够时 CRC 的位置无从确定
+    report.push(
+        FrameCheck::Crc,
+        crc.filter(|_| long_enough)
+            .map(|(crc_type, crc_start)| check_crc(config, bytes, crc_type, crc_start)),
+    );
+    report
+}
+
+fn mismatch(what: &str, expected: &[u8], actual: Option<&[u8]>) -> ProtocolError {
+    ProtocolError::ValidationFailed(format!(
+        "{} expected {} got {}",
+        what,
+        hex::encode_upper(expected),
+        actual.map(hex::encode_upper).unwrap_or_default()
+    ))
+}
+
+/// CRC 位于帧尾之前，范围 [crc_start, CRC 起始位置)
+pub(crate) fn check_crc(
+    config: &ProtocolConfig,
+    frame: &[u8],
+    crc_type: CrcType,
+    crc_start: usize,
+) -> ProtocolResult<()> {
+    let crc_pos = frame
+        .len()
+        .checked_sub(config.tail.len() + config.crc_len)
+        .filter(|pos| config.crc_len == 2 && crc_start <= *pos)
+        .ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!(
+                "{} crc range invalid: crc_len {}, start {}, frame len {}",
+                config.name,
+                config.crc_len,
+                crc_start,
+                frame.len()
+            ))
+        })?;
+    let calculated = crc_util::calculate_from_bytes(crc_type, &frame[crc_start..crc_pos])?;
+    crc_util::compare_crc(
+        &hex::encode_upper(&frame[crc_pos..crc_pos + config.crc_len]),
+        calculated,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ProtocolConfig {
+        ProtocolConfig::new("demo", 1, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16])
+            .with_length_field(2, 1, false)
+            .with_data_offset(3)
+            .with_crc_len(2)
+    }
+
+    fn frame() -> Vec<u8> {
+        let mut frame = vec![0x68, 0x01, 0x02, 0x11, 0x22];
+        let crc = crc_util::calculate_from_bytes(CrcType::Crc16Modbus, &frame[1..]).unwrap();
+        frame.extend_from_slice(&crc.to_be_bytes());
+        frame.push(0x16);
+        frame
+    }
+
+    #[test]
+    fn test_validate_frame() {
+        let config = config();
+        let report = validate_frame_with_crc(&frame(), &config, CrcType::Crc16Modbus, 1);
+        assert!(report.is_valid());
+        assert!(report
+            .checks
+            .iter()
+            .all(|c| c.status == CheckStatus::Passed));
+        let report = validate_frame(&frame(), &config);
+        assert!(report.is_valid());
+        assert_eq!(report.status(FrameCheck::Crc), Some(CheckStatus::Skipped));
+
+        // 帧尾和 CRC 同时出错时两项都报告
+        let mut bad = frame();
+        bad[3] ^= 0xFF;
+        *bad.last_mut().unwrap() = 0x17;
+        let report = validate_frame_with_crc(&bad, &config, CrcType::Crc16Modbus, 1);
+        let failed: Vec<FrameCheck> = report.failed().map(|c| c.check).collect();
+        assert_eq!(failed, [FrameCheck::Tail, FrameCheck::Crc]);
+        assert!(report.into_result().is_err());
+
+        let report = validate_frame_with_crc(&[0x68, 0x01], &config, CrcType::Crc16Modbus, 1);
+        assert_eq!(
+            report.status(FrameCheck::MinLength),
+            Some(CheckStatus::Failed)
+        );
+        assert_eq!(report.status(FrameCheck::Crc), Some(CheckStatus::Skipped));
+    }
+}
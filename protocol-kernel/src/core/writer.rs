@@ -1,45 +1,120 @@
-use std::collections::HashMap;
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
+use bytes::{Bytes, BytesMut};
 use protocol_base::{ProtocolError, ProtocolResult};
+use serde::Serialize;
 
 use crate::{
     core::parts::{placeholder::PlaceHolder, rawfield::Rawfield},
     utils::{crc_util, hex_util},
-    ReportField,
 };
+#[cfg(feature = "std")]
+use crate::{utils::code_registry::CodeRegistry, ReportField};
+
+/// 编码预览中的一个字段
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewField {
+    pub offset: usize,
+    pub len: usize,
+    pub title: String,
+    pub value: String,
+    // 尚未回填的占位符为每字节 "XX"
+    pub hex: String,
+    // 尚未回填的占位符的 tag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+}
+
+/// 编码预览 (dry-run)，下发前给操作员确认将要发送的帧
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodePreview {
+    // 整帧 hex，未回填的占位符 (如 CRC) 显示为 XX
+    pub hex: String,
+    pub len: usize,
+    pub fields: Vec<PreviewField>,
+    // 尚未回填的占位符 tag，按在帧中的位置排列
+    pub unresolved: Vec<String>,
+}
+
+impl EncodePreview {
+    pub fn is_complete(&self) -> bool {
+        self.unresolved.is_empty()
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct Writer {
-    buffer: Vec<u8>,
+    buffer: BytesMut,
     fields: Vec<Rawfield>,
-    placeholders: HashMap<String, PlaceHolder>, // 占位符(标记名称，起始位置，终止位置)
+    placeholders: BTreeMap<String, PlaceHolder>, // 占位符(标记名称，起始位置，终止位置)
 }
 
 impl Writer {
     pub fn new() -> Self {
         Self {
-            buffer: Vec::new(),
+            buffer: BytesMut::new(),
             fields: Vec::new(),
-            placeholders: HashMap::new(),
+            placeholders: BTreeMap::new(),
+        }
+    }
+
+    /// 预分配缓冲区与字段容量
+    pub fn with_capacity(bytes: usize, fields: usize) -> Self {
+        Self {
+            buffer: BytesMut::with_capacity(bytes),
+            fields: Vec::with_capacity(fields),
+            placeholders: BTreeMap::new(),
         }
     }
 
+    /// 清空已写入的内容以便复用，缓冲区与字段列表保留容量
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.fields.clear();
+        self.placeholders.clear();
+    }
+
     /// (非消耗) 获取对当前 buffer 的引用
     pub fn buffer(&self) -> ProtocolResult<&[u8]> {
         Ok(&self.buffer)
     }
 
+    /// (消耗) 取出编码结果，不复制缓冲区
+    pub fn freeze(self) -> Bytes {
+        self.buffer.freeze()
+    }
+
     /// (非消耗) 获取对当前 fields 的引用
     pub fn fields(&self) -> ProtocolResult<&Vec<Rawfield>> {
         Ok(&self.fields)
     }
 
+    #[cfg(feature = "std")]
     pub fn to_report_fields(&self) -> ProtocolResult<Vec<ReportField>> {
         let fields = self.fields.clone();
         let r: Vec<ReportField> = fields.into_iter().map(|f| f.to_report_field()).collect();
         Ok(r)
     }
 
+    #[cfg(feature = "std")]
+    pub fn to_report_fields_with(
+        &self,
+        registry: &mut CodeRegistry,
+    ) -> ProtocolResult<Vec<ReportField>> {
+        self.fields
+            .iter()
+            .cloned()
+            .map(|f| f.to_report_field_with(registry))
+            .collect()
+    }
+
     pub fn full_hex(self) -> ProtocolResult<String> {
         let bytes = self.buffer()?;
         hex_util::bytes_to_hex(bytes)
@@ -59,6 +134,57 @@ impl Writer {
             .ok_or_else(|| ProtocolError::CommonError("未找到标签为 '{tag}' 的占位符".into()))
     }
 
+    /// (非消耗) 当前已写入内容的预览: 字段及其偏移，未回填的占位符 (CRC、长度等) 以 XX 显示。
+    /// 不要求占位符全部可回填，可在 write_crc 之前调用
+    pub fn preview(&self) -> EncodePreview {
+        let mut pending: Vec<&PlaceHolder> = self.placeholders.values().collect();
+        pending.sort_by_key(|p| p.start_index);
+        let mut pending = pending.into_iter().peekable();
+        let mut fields = Vec::with_capacity(self.fields.len() + self.placeholders.len());
+        let mut offset = 0;
+        let mut emit_pending = |offset: &mut usize, fields: &mut Vec<PreviewField>| {
+            while let Some(p) = pending.next_if(|p| p.start_index <= *offset) {
+                fields.push(PreviewField {
+                    offset: p.start_index,
+                    len: p.capacity(),
+                    title: p.tag.clone(),
+                    value: String::new(),
+                    hex: "XX".repeat(p.capacity()),
+                    placeholder: Some(p.tag.clone()),
+                });
+                *offset = p.end_index;
+            }
+        };
+        for field in &self.fields {
+            emit_pending(&mut offset, &mut fields);
+            fields.push(PreviewField {
+                offset,
+                len: field.bytes.len(),
+                title: field.title.to_string(),
+                value: field.value.clone(),
+                hex: hex::encode_upper(&field.bytes),
+                placeholder: None,
+            });
+            offset += field.bytes.len();
+        }
+        emit_pending(&mut offset, &mut fields);
+
+        let mut hex = hex::encode_upper(&self.buffer);
+        let mut unresolved = Vec::new();
+        for field in &fields {
+            if let Some(tag) = &field.placeholder {
+                hex.replace_range(field.offset * 2..(field.offset + field.len) * 2, &field.hex);
+                unresolved.push(tag.clone());
+            }
+        }
+        EncodePreview {
+            hex,
+            len: self.buffer.len(),
+            fields,
+            unresolved,
+        }
+    }
+
     /// 核心写入方法：调用一个闭包来生成 Rawfield，然后写入其字节
     ///
     /// 闭包 `translator` 负责“创造”一个 Rawfield。
@@ -70,13 +196,10 @@ impl Writer {
         // 1. 调用闭包，获取“翻译”结果
         let field = translator()?;
 
-        // 2. 从 Rawfield 中提取字节
-        let bytes_to_write = field.bytes.clone();
+        // 2. 追加 Rawfield 的字节到缓冲区
+        self.buffer.extend_from_slice(&field.bytes);
 
-        // 3. 追加字节到缓冲区
-        self.buffer.extend_from_slice(&bytes_to_write);
-
-        // 4. 存储翻译记录
+        // 3. 存储翻译记录
         self.fields.push(field);
 
         Ok(self)
@@ -89,12 +212,33 @@ impl Writer {
         data: &[u8],
         value: &str,
     ) -> ProtocolResult<&mut Self> {
-        let field = Rawfield::new(data, title.into(), value.into()); //
+        let field = Rawfield::new(data, title, value.into()); //
         self.buffer.extend_from_slice(data);
         self.fields.push(field);
         Ok(self)
     }
 
+    /// 写入定长数组，配合 `u16::to_be_bytes` 等使用，长度在编译期确定
+    pub fn write_array<const N: usize>(
+        &mut self,
+        title: &str,
+        data: [u8; N],
+        value: &str,
+    ) -> ProtocolResult<&mut Self> {
+        self.write_bytes(title, &data, value)
+    }
+
+    /// 同 write_array，按小端写入 (字节顺序反转)
+    pub fn write_array_le<const N: usize>(
+        &mut self,
+        title: &str,
+        mut data: [u8; N],
+        value: &str,
+    ) -> ProtocolResult<&mut Self> {
+        data.reverse();
+        self.write_bytes(title, &data, value)
+    }
+
     /// 写入 N 字节的占位符 (默认为 0x00)，并返回其在缓冲区中的起始位置。
     ///
     /// 这用于稍后 "回填" 动态数据 (如总长度或 CRC)。
@@ -114,18 +258,15 @@ impl Writer {
             ));
         }
 
-        // 2. 创建占位符字节
-        let placeholder_bytes = vec![0u8; byte_len];
-
         let end_pos = start_pos + byte_len;
         let fields_pos = self.fields.len();
         let placeholder = PlaceHolder::new(tag, fields_pos, start_pos, end_pos);
 
-        // 3. 写入占位符 (使用已有的 write_bytes 逻辑)
-        self.buffer.extend_from_slice(&placeholder_bytes);
+        // 2. 写入占位符 (补 0x00)
+        self.buffer.resize(start_pos + byte_len, 0);
         self.placeholders.insert(tag.into(), placeholder);
 
-        // 4. 返回写入的起始位置
+        // 3. 返回写入的起始位置
         Ok(self)
     }
 
@@ -167,7 +308,7 @@ impl Writer {
         dest_slice.copy_from_slice(bytes);
 
         // 5. 创建 Rawfield
-        let field = Rawfield::new(bytes, title.into(), hex.into());
+        let field = Rawfield::new(bytes, title, hex.into());
 
         // 6. 将 Rawfield 插入到 fields 列表的正确位置
         self.fields.insert(placeholder.pos, field);
@@ -252,3 +393,69 @@ impl Writer {
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_base::definitions::defi::CrcType;
+
+    #[test]
+    fn test_write_array() {
+        let mut writer = Writer::new();
+        writer
+            .write_array("起始符", [0x68], "68")
+            .unwrap()
+            .write_array("读数", 0x0102u16.to_be_bytes(), "258")
+            .unwrap()
+            .write_array_le("单价", 0x1234u16.to_be_bytes(), "4660")
+            .unwrap();
+        assert_eq!(writer.buffer().unwrap(), &[0x68, 0x01, 0x02, 0x34, 0x12]);
+        assert_eq!(writer.fields().unwrap()[2].hex(), "3412");
+    }
+
+    #[test]
+    fn test_preview() {
+        let mut writer = Writer::new();
+        writer
+            .write_array("起始符", [0x68], "68")
+            .unwrap()
+            .write_placeholder("len", 1)
+            .unwrap()
+            .write_array("读数", 0x0102u16.to_be_bytes(), "258")
+            .unwrap()
+            .write_placeholder("crc", 2)
+            .unwrap()
+            .write_array("结束符", [0x16], "16")
+            .unwrap();
+        writer
+            .rewrite_placeholder("len", "长度", &[0x02], "2")
+            .unwrap();
+
+        let preview = writer.preview();
+        assert_eq!(preview.hex, "68020102XXXX16");
+        assert_eq!(preview.len, 7);
+        assert_eq!(preview.unresolved, ["crc"]);
+        let layout: Vec<(usize, &str)> = preview
+            .fields
+            .iter()
+            .map(|f| (f.offset, f.title.as_str()))
+            .collect();
+        assert_eq!(
+            layout,
+            [
+                (0, "起始符"),
+                (1, "长度"),
+                (2, "读数"),
+                (4, "crc"),
+                (6, "结束符")
+            ]
+        );
+
+        writer
+            .write_crc::<()>(CrcType::Crc16Modbus, 1, -3, "crc", false)
+            .unwrap();
+        let preview = writer.preview();
+        assert!(preview.is_complete());
+        assert!(!preview.hex.contains('X'));
+    }
+}
@@ -1,30 +1,90 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod bridge;
 pub mod core;
 pub mod utils;
 
 // Re-export protocol-base types
-pub use protocol_base::{ProtocolError, ProtocolResult};
+pub use protocol_base::{ErrorEnvelope, ProtocolError, ProtocolResult, ResultExt};
+// 帧与字段字节使用的共享缓冲区
+pub use bytes::{Bytes, BytesMut};
 
-pub use crate::bridge::{JniRequest, JniResponse, ReportField};
+#[cfg(feature = "std")]
+pub use crate::bridge::{
+    BridgeFormat, BusinessCommand, CommandDescription, CommandDoc, CommandMapping, FieldDoc,
+    FieldSchema, FrameDoc, JniBatchRequest, JniBatchResponse, JniRequest, JniRequestBuilder,
+    JniResponse, PageInfo, ProtocolDescription, ProtocolHandler, ResponseSegment,
+};
+#[cfg(feature = "std")]
 pub use crate::core::{
-    cache::ProtocolCache,
+    aggregate::{AggregateMode, AggregateWindow, Aggregator, SeriesPoint},
+    backend::{CacheBackend, CacheConfig, EvictionCause, MemoryBackend},
+    cache::{CacheNamespace, ProtocolCache},
+    config::ProtocolConfig,
+    context::{CipherRegistry, DecodeContext, FrameCipher},
+    dedup::FrameDedup,
+    definition_store::{DefinitionPoller, DefinitionStore, LoadedDefinition, ReloadEvent},
+    device_lock::{DeviceLockFuture, DeviceLockGuard},
+    dispatch::CmdDispatcher,
+    downlink::{DownlinkCommand, DownlinkQueue, DropReason},
+    dsl::ProtocolDefinition,
+    envelope::{Envelope, EnvelopeDecoder, Payload},
+    event::{DeviceEvent, EventDecoder, EventDefinition},
+    idempotency::{idempotency_token, IdempotencyGuard},
+    inspector::{FieldSpan, FrameInspector, InspectorKey},
+    json_mapper::{AlertRule, JsonField, JsonFieldMapper},
+    middleware::{Middleware, MiddlewareChain, MiddlewareContext, MiddlewareStage},
+    ota::{OtaProgress, OtaSegment, OtaSession},
+    parallel::{decode_batch, decode_batch_by},
+    quarantine::{
+        clear_quarantine_sink, quarantine, set_quarantine_sink, CandidateCode, MemoryQuarantine,
+        QuarantineSink, UnknownFrame,
+    },
     parts::{
-        placeholder::PlaceHolder,
+        cmd_snapshot::CmdSnapshot,
         raw_capsule::RawCapsule,
+        raw_capsule_builder::RawCapsuleBuilder,
         raw_chamber::RawChamber,
-        rawfield::Rawfield,
+        schema::{param_schema_for, param_schema_json, ParamSchema},
         traits::{
-            AutoDecoding, AutoDecodingParam, AutoEncoding, AutoEncodingParam, Cmd, Transport,
+            AutoDecoding, AutoDecodingParam, AutoEncoding, AutoEncodingParam, Cmd, CmdTable,
+            Transport,
         },
-        transport_carrier::TransportCarrier,
+        transport_carrier::{CountEncoding, TransportCarrier},
         transport_pair::TransportPair,
     },
+    pool::{ReaderPool, WriterPool},
+    reassembly::{Reassembler, UplinkSegment},
+    snapshot::CacheSnapshot,
+    sniffer::{ProtocolSniffer, SniffMatch},
+    stateful::{DeltaCounterDecoder, ToggleBitDecoder},
+    streaming::StreamingReader,
+    validation::{
+        validate_frame, validate_frame_with_crc, CheckOutcome, CheckStatus, FrameCheck, FrameReport,
+    },
+};
+pub use crate::core::{
+    parts::{
+        diagnostic::Diagnostic,
+        placeholder::PlaceHolder,
+        rawfield::Rawfield,
+        report_field::{ReportField, ValueType},
+        title::Title,
+    },
     reader::Reader,
     type_converter::{
         FieldCompareDecoder, FieldConvertDecoder, FieldEnumDecoder, FieldTranslator, FieldType,
         TryFromBytes,
     },
-    writer::Writer,
-    DirectionEnum, MsgTypeEnum, Symbol, RW,
+    writer::{EncodePreview, PreviewField, Writer},
+    DecodeMode, DirectionEnum, MsgTypeEnum, Symbol, RW,
+};
+pub use crate::utils::{bcd_util, hex_util, math_util, tariff, timestamp_util};
+#[cfg(feature = "std")]
+pub use crate::utils::{
+    code_registry, generate_rand, generate_rand_bcd, generate_rand_hex, generate_rand_range,
+    precompute_pinyin, to_pinyin, to_pinyin_cached,
 };
-pub use crate::utils::{generate_rand, hex_util, math_util, timestamp_util, to_pinyin};
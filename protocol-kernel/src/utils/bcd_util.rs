@@ -0,0 +1,346 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use protocol_base::{
+    error::{hex_error::HexError, ProtocolError},
+    ProtocolResult,
+};
+
+use crate::utils::hex_util;
+
+/// 内部辅助函数：拆出一个 BCD 字节的高低两位，非法半字节返回 None
+fn _split_bcd_byte(byte: u8) -> Option<(u8, u8)> {
+    let high = byte >> 4;
+    let low = byte & 0x0F;
+    if high > 9 || low > 9 {
+        None
+    } else {
+        Some((high, low))
+    }
+}
+
+/// 内部辅助函数：生成 NotBcd 错误
+fn _not_bcd(bytes: &[u8]) -> ProtocolError {
+    ProtocolError::HexError(HexError::NotBcd(hex::encode_upper(bytes)))
+}
+
+/// 检查字节是否全部为合法 BCD (每个半字节 0-9)
+pub fn is_bcd_bytes(bytes: &[u8]) -> bool {
+    bytes.iter().all(|&b| _split_bcd_byte(b).is_some())
+}
+
+/// BCD 字节 (大端) -> u64。例如 [0x12, 0x34] -> 1234
+pub fn bcd_to_u64(bytes: &[u8]) -> ProtocolResult<u64> {
+    let mut result: u64 = 0;
+    for &byte in bytes {
+        let (high, low) = _split_bcd_byte(byte).ok_or_else(|| _not_bcd(bytes))?;
+        for digit in [high, low] {
+            result = result
+                .checked_mul(10)
+                .and_then(|r| r.checked_add(digit as u64))
+                .ok_or_else(|| {
+                    ProtocolError::ValidationFailed(format!(
+                        "BCD value {} overflows u64",
+                        hex::encode_upper(bytes)
+                    ))
+                })?;
+        }
+    }
+    Ok(result)
+}
+
+/// BCD 字节 (小端) -> u64。例如 [0x34, 0x12] -> 1234
+pub fn bcd_to_u64_swap(bytes: &[u8]) -> ProtocolResult<u64> {
+    let swapped = hex_util::swap_bytes(bytes)?;
+    bcd_to_u64(&swapped)
+}
+
+/// u64 -> 指定字节长度的 BCD (大端, 高位补0)。放不下时返回错误，不会截断
+pub fn u64_to_bcd(value: u64, byte_length: usize) -> ProtocolResult<Vec<u8>> {
+    let digits = value.to_string();
+    if digits.len() > byte_length * 2 {
+        return Err(ProtocolError::HexError(HexError::HexLengthError {
+            context: "bcd",
+            max_chars: byte_length * 2,
+            actual_chars: digits.len(),
+        }));
+    }
+    let padded = format!("{:0>width$}", digits, width = byte_length * 2);
+    hex_util::hex_to_bytes(&padded)
+}
+
+/// u64 -> 指定字节长度的 BCD (小端)
+pub fn u64_to_bcd_swap(value: u64, byte_length: usize) -> ProtocolResult<Vec<u8>> {
+    let bytes = u64_to_bcd(value, byte_length)?;
+    hex_util::swap_bytes(&bytes)
+}
+
+/// BCD hex-string -> u64。例如 "001234" -> 1234
+pub fn bcd_hex_to_u64(bcd_hex: &str) -> ProtocolResult<u64> {
+    let bytes = hex_util::hex_to_bytes(bcd_hex)?;
+    bcd_to_u64(&bytes)
+}
+
+/// u64 -> 指定字节长度的 BCD hex-string
+pub fn u64_to_bcd_hex(value: u64, byte_length: usize) -> ProtocolResult<String> {
+    let bytes = u64_to_bcd(value, byte_length)?;
+    hex_util::bytes_to_hex(&bytes)
+}
+
+/// 带符号 BCD 的符号位约定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BcdSignMode {
+    /// 最高字节的最高位 (0x80) 为符号位，1 表示负数。例如 [0x80, 0x12] -> -12
+    HighBit,
+    /// 最高半字节整体作为符号位：0 表示正数，等于给定值(常见 0xF / 0x8 / 0x1)表示负数。
+    /// 例如 HighNibble(0xF): [0xF0, 0x12] -> -12
+    HighNibble(u8),
+}
+
+/// 带符号 BCD (大端) -> i64
+pub fn signed_bcd_to_i64(bytes: &[u8], mode: BcdSignMode) -> ProtocolResult<i64> {
+    if bytes.is_empty() {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(
+            "signed BCD requires at least 1 byte".into(),
+        )));
+    }
+    let mut digits = bytes.to_vec();
+    let negative = match mode {
+        BcdSignMode::HighBit => {
+            let negative = digits[0] & 0x80 != 0;
+            digits[0] &= 0x7F;
+            negative
+        }
+        BcdSignMode::HighNibble(sign_nibble) => {
+            let nibble = digits[0] >> 4;
+            digits[0] &= 0x0F;
+            if nibble == sign_nibble & 0x0F && nibble != 0 {
+                true
+            } else if nibble == 0 {
+                false
+            } else {
+                return Err(_not_bcd(bytes));
+            }
+        }
+    };
+    let magnitude = bcd_to_u64(&digits).map_err(|_| _not_bcd(bytes))?;
+    let magnitude = i64::try_from(magnitude).map_err(|_| {
+        ProtocolError::ValidationFailed(format!(
+            "signed BCD value {} overflows i64",
+            hex::encode_upper(bytes)
+        ))
+    })?;
+    Ok(if negative { -magnitude } else { magnitude })
+}
+
+/// 带符号 BCD (小端) -> i64
+pub fn signed_bcd_to_i64_swap(bytes: &[u8], mode: BcdSignMode) -> ProtocolResult<i64> {
+    let swapped = hex_util::swap_bytes(bytes)?;
+    signed_bcd_to_i64(&swapped, mode)
+}
+
+/// i64 -> 指定字节长度的带符号 BCD (大端)。
+/// 符号位占用的空间不能再存放数字，放不下时返回错误。
+pub fn i64_to_signed_bcd(
+    value: i64,
+    byte_length: usize,
+    mode: BcdSignMode,
+) -> ProtocolResult<Vec<u8>> {
+    let mut bytes = u64_to_bcd(value.unsigned_abs(), byte_length)?;
+    if bytes.is_empty() {
+        return Ok(bytes);
+    }
+    match mode {
+        BcdSignMode::HighBit => {
+            // 最高位数字不能超过 7，否则会与符号位冲突
+            if bytes[0] & 0x80 != 0 {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "value {} does not fit in {} bytes of signed BCD (high bit is the sign)",
+                    value, byte_length
+                )));
+            }
+            if value < 0 {
+                bytes[0] |= 0x80;
+            }
+        }
+        BcdSignMode::HighNibble(sign_nibble) => {
+            if bytes[0] & 0xF0 != 0 {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "value {} does not fit in {} bytes of signed BCD (high nibble is the sign)",
+                    value, byte_length
+                )));
+            }
+            if value < 0 {
+                bytes[0] |= (sign_nibble & 0x0F) << 4;
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+/// i64 -> 指定字节长度的带符号 BCD (小端)
+pub fn i64_to_signed_bcd_swap(
+    value: i64,
+    byte_length: usize,
+    mode: BcdSignMode,
+) -> ProtocolResult<Vec<u8>> {
+    let bytes = i64_to_signed_bcd(value, byte_length, mode)?;
+    hex_util::swap_bytes(&bytes)
+}
+
+/// 内部辅助函数：逐位十进制相加。返回结果(长度为两者较长者)和最高位进位
+fn _bcd_add_internal(a: &[u8], b: &[u8]) -> ProtocolResult<(Vec<u8>, bool)> {
+    if !is_bcd_bytes(a) {
+        return Err(_not_bcd(a));
+    }
+    if !is_bcd_bytes(b) {
+        return Err(_not_bcd(b));
+    }
+    let len = a.len().max(b.len());
+    let mut result = vec![0u8; len];
+    let mut carry = 0u8;
+    // 从最低位字节(尾部)开始相加
+    for i in 0..len {
+        let x = if i < a.len() { a[a.len() - 1 - i] } else { 0 };
+        let y = if i < b.len() { b[b.len() - 1 - i] } else { 0 };
+
+        let mut low = (x & 0x0F) + (y & 0x0F) + carry;
+        carry = if low > 9 {
+            low -= 10;
+            1
+        } else {
+            0
+        };
+        let mut high = (x >> 4) + (y >> 4) + carry;
+        carry = if high > 9 {
+            high -= 10;
+            1
+        } else {
+            0
+        };
+        result[len - 1 - i] = (high << 4) | low;
+    }
+    Ok((result, carry == 1))
+}
+
+/// BCD 加法 (大端)。结果长度为两者中较长者，溢出时返回错误
+pub fn bcd_add(a: &[u8], b: &[u8]) -> ProtocolResult<Vec<u8>> {
+    let (result, overflow) = _bcd_add_internal(a, b)?;
+    if overflow {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "BCD addition overflow: {} + {} exceeds {} bytes",
+            hex::encode_upper(a),
+            hex::encode_upper(b),
+            result.len()
+        )));
+    }
+    Ok(result)
+}
+
+/// BCD 加法 (大端)，溢出时回绕。返回 (结果, 是否发生溢出)
+pub fn bcd_add_wrapping(a: &[u8], b: &[u8]) -> ProtocolResult<(Vec<u8>, bool)> {
+    _bcd_add_internal(a, b)
+}
+
+/// BCD 自增 1 (大端)。例如序列号 [0x00, 0x99] -> [0x01, 0x00]
+///
+/// # Arguments
+/// * `wrap` - 溢出时是否回绕到全0 (例如 [0x99] -> [0x00])，false 时溢出返回错误
+pub fn bcd_increment(bytes: &[u8], wrap: bool) -> ProtocolResult<Vec<u8>> {
+    if bytes.is_empty() {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(
+            "BCD increment requires at least 1 byte".into(),
+        )));
+    }
+    let (result, overflow) = _bcd_add_internal(bytes, &[0x01])?;
+    if overflow && !wrap {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "BCD increment overflow: {} is already the max value of {} bytes",
+            hex::encode_upper(bytes),
+            bytes.len()
+        )));
+    }
+    Ok(result)
+}
+
+/// BCD hex-string 自增 1，返回同样长度的 hex-string
+pub fn bcd_hex_increment(bcd_hex: &str, wrap: bool) -> ProtocolResult<String> {
+    let bytes = hex_util::hex_to_bytes(bcd_hex)?;
+    let result = bcd_increment(&bytes, wrap)?;
+    hex_util::bytes_to_hex(&result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bcd_to_u64() {
+        assert_eq!(bcd_to_u64(&[0x12, 0x34]).unwrap(), 1234);
+        assert_eq!(bcd_to_u64_swap(&[0x34, 0x12]).unwrap(), 1234);
+        assert!(bcd_to_u64(&[0x1A]).is_err());
+        assert!(bcd_to_u64(&[0x99; 11]).is_err());
+    }
+
+    #[test]
+    fn test_u64_to_bcd() {
+        assert_eq!(u64_to_bcd(1234, 3).unwrap(), vec![0x00, 0x12, 0x34]);
+        assert_eq!(u64_to_bcd_swap(1234, 2).unwrap(), vec![0x34, 0x12]);
+        assert_eq!(u64_to_bcd_hex(7, 2).unwrap(), "0007");
+        assert!(u64_to_bcd(12345, 2).is_err());
+    }
+
+    #[test]
+    fn test_signed_bcd() {
+        assert_eq!(
+            signed_bcd_to_i64(&[0x80, 0x12], BcdSignMode::HighBit).unwrap(),
+            -12
+        );
+        assert_eq!(
+            signed_bcd_to_i64(&[0x00, 0x12], BcdSignMode::HighBit).unwrap(),
+            12
+        );
+        assert_eq!(
+            signed_bcd_to_i64_swap(&[0x34, 0xF1], BcdSignMode::HighNibble(0xF)).unwrap(),
+            -134
+        );
+        assert!(signed_bcd_to_i64(&[0x81, 0x12], BcdSignMode::HighNibble(0xF)).is_err());
+        assert_eq!(
+            i64_to_signed_bcd(-12, 2, BcdSignMode::HighBit).unwrap(),
+            vec![0x80, 0x12]
+        );
+        assert_eq!(
+            i64_to_signed_bcd(-134, 2, BcdSignMode::HighNibble(0xF)).unwrap(),
+            vec![0xF1, 0x34]
+        );
+        assert!(i64_to_signed_bcd(8000, 2, BcdSignMode::HighBit).is_err());
+        assert!(i64_to_signed_bcd(1000, 2, BcdSignMode::HighNibble(0xF)).is_err());
+    }
+
+    #[test]
+    fn test_bcd_add() {
+        assert_eq!(bcd_add(&[0x00, 0x59], &[0x41]).unwrap(), vec![0x01, 0x00]);
+        assert!(bcd_add(&[0x99], &[0x01]).is_err());
+        assert_eq!(
+            bcd_add_wrapping(&[0x99], &[0x02]).unwrap(),
+            (vec![0x01], true)
+        );
+    }
+
+    #[test]
+    fn test_bcd_increment() {
+        assert_eq!(
+            bcd_increment(&[0x00, 0x99], false).unwrap(),
+            vec![0x01, 0x00]
+        );
+        assert_eq!(
+            bcd_increment(&[0x99, 0x99], true).unwrap(),
+            vec![0x00, 0x00]
+        );
+        assert!(bcd_increment(&[0x99], false).is_err());
+        assert_eq!(bcd_hex_increment("0009", false).unwrap(), "0010");
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, HashMap};
+
+use protocol_base::{ProtocolError, ProtocolResult};
+
+use crate::utils::to_pinyin_cached;
+
+/// 字段 code 注册表。
+///
+/// `to_pinyin` 生成的 code 可能冲突 (例如 "状态" 与 "装态" 都是 zhuang_tai)，
+/// 而 Java 端是按 code 持久化数据的。注册表为每个协议保证:
+/// * 唯一: 同一个 code 只对应一个 title，冲突且没有 override 时注册失败，
+///   不会自动追加后缀 (否则谁拿到原始 code 取决于注册顺序)；
+/// * 稳定: code 只由 title 与 override 决定，与注册顺序无关；
+/// * 可控: 可以为个别字段显式指定 code (override)，优先级最高。
+#[derive(Debug, Clone, Default)]
+pub struct CodeRegistry {
+    protocol: String,
+    title_to_code: HashMap<String, String>,
+    code_to_title: HashMap<String, String>,
+    // 发生过冲突的 pinyin code -> 涉及的 title
+    collisions: BTreeMap<String, Vec<String>>,
+}
+
+impl CodeRegistry {
+    pub fn new(protocol: &str) -> Self {
+        Self {
+            protocol: protocol.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn protocol(&self) -> &str {
+        &self.protocol
+    }
+
+    /// 为某个 title 显式指定 code。code 已被其他 title 占用时返回错误
+    pub fn set_override(&mut self, title: &str, code: &str) -> ProtocolResult<&mut Self> {
+        if let Some(owner) = self.code_to_title.get(code) {
+            if owner != title {
+                return Err(ProtocolError::ValidationFailed(format!(
+                    "[{}] code '{}' is already used by title '{}'",
+                    self.protocol, code, owner
+                )));
+            }
+        }
+        if let Some(old) = self.title_to_code.remove(title) {
+            self.code_to_title.remove(&old);
+        }
+        self.title_to_code.insert(title.into(), code.into());
+        self.code_to_title.insert(code.into(), title.into());
+        Ok(self)
+    }
+
+    /// 注册一个 title 并返回它的 code (已注册过或有 override 则直接返回)。
+    /// pinyin code 已被其他 title 占用时返回错误，需为其中一个 title 设置 override
+    pub fn register(&mut self, title: &str) -> ProtocolResult<String> {
+        if let Some(code) = self.title_to_code.get(title) {
+            return Ok(code.clone());
+        }
+        let code = to_pinyin_cached(title);
+        if let Some(owner) = self.code_to_title.get(&code) {
+            let entry = self.collisions.entry(code.clone()).or_default();
+            for t in [owner.as_str(), title] {
+                if !entry.iter().any(|e| e == t) {
+                    entry.push(t.into());
+                }
+            }
+            return Err(ProtocolError::ValidationFailed(format!(
+                "[{}] title '{}' collides with '{}' on code '{}', set an override for one of them",
+                self.protocol, title, owner, code
+            )));
+        }
+        self.title_to_code.insert(title.into(), code.clone());
+        self.code_to_title.insert(code.clone(), title.into());
+        Ok(code)
+    }
+
+    /// 批量注册，通常在协议初始化时调用。任一 title 冲突时返回错误
+    pub fn register_all(&mut self, titles: &[&str]) -> ProtocolResult<Vec<String>> {
+        titles.iter().map(|t| self.register(t)).collect()
+    }
+
+    /// title -> code (未注册返回 None)
+    pub fn code_of(&self, title: &str) -> Option<&str> {
+        self.title_to_code.get(title).map(|s| s.as_str())
+    }
+
+    /// 反向映射 code -> title
+    pub fn title_of(&self, code: &str) -> Option<&str> {
+        self.code_to_title.get(code).map(|s| s.as_str())
+    }
+
+    /// 注册过程中发现的冲突 (pinyin code, 涉及的 titles)，用于提示补充 override
+    pub fn collisions(&self) -> Vec<(String, Vec<String>)> {
+        self.collisions
+            .iter()
+            .map(|(code, titles)| (code.clone(), titles.clone()))
+            .collect()
+    }
+
+    pub fn has_collisions(&self) -> bool {
+        !self.collisions.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.title_to_code.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.title_to_code.is_empty()
+    }
+}
+
+/// 找出一组 title 中 pinyin code 相同的 (code, titles)，不修改任何状态
+pub fn detect_collisions(titles: &[&str]) -> Vec<(String, Vec<String>)> {
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for title in titles {
+        let list = groups.entry(to_pinyin_cached(title)).or_default();
+        if !list.iter().any(|t| t == title) {
+            list.push(title.to_string());
+        }
+    }
+    groups.into_iter().filter(|(_, t)| t.len() > 1).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collision_requires_override() {
+        let mut reg = CodeRegistry::new("demo");
+        assert_eq!(reg.register("状态").unwrap(), "zhuang_tai");
+        assert!(reg.register("装态").is_err());
+        assert!(reg.has_collisions());
+        assert_eq!(reg.register("状态").unwrap(), "zhuang_tai");
+        assert_eq!(reg.title_of("zhuang_tai"), Some("状态"));
+        assert_eq!(detect_collisions(&["状态", "装态", "电压"]).len(), 1);
+    }
+
+    #[test]
+    fn test_override_independent_of_order() {
+        let codes = |titles: &[&str]| {
+            let mut reg = CodeRegistry::new("demo");
+            reg.set_override("装态", "device_state").unwrap();
+            let mut codes: Vec<(String, String)> = titles
+                .iter()
+                .map(|t| (t.to_string(), reg.register(t).unwrap()))
+                .collect();
+            codes.sort();
+            codes
+        };
+        let forward = codes(&["状态", "装态", "电压"]);
+        let reversed = codes(&["电压", "装态", "状态"]);
+        assert_eq!(forward, reversed);
+        assert!(forward.contains(&("装态".into(), "device_state".into())));
+        assert!(forward.contains(&("状态".into(), "zhuang_tai".into())));
+
+        let mut reg = CodeRegistry::new("demo");
+        reg.set_override("装态", "device_state").unwrap();
+        assert!(reg.set_override("状态", "device_state").is_err());
+    }
+
+    #[test]
+    fn test_pinyin_cache() {
+        crate::utils::precompute_pinyin(["累计流量", "阀门状态"]);
+        assert!(crate::utils::pinyin_cache_len() >= 2);
+        assert_eq!(to_pinyin_cached("累计流量"), "lei_ji_liu_liang");
+        assert_eq!(
+            to_pinyin_cached("阀门状态"),
+            crate::utils::to_pinyin("阀门状态")
+        );
+    }
+}
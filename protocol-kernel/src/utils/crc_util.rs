@@ -1,3 +1,4 @@
+use alloc::{format, string::String, vec::Vec};
 use protocol_base::{ProtocolError, ProtocolResult};
 use rust_decimal::prelude::ToPrimitive;
 
@@ -1,8 +1,16 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt::LowerHex, mem::size_of};
 use protocol_base::{
+    error::{hex_error::HexError, ProtocolError},
     ProtocolResult,
-    error::{ProtocolError, hex_error::HexError},
-};
-use std::{fmt::LowerHex, mem::size_of}; // 引入 size_of
+}; // 引入 size_of
+
+use crate::core::parts::rawfield::Rawfield;
 
 // --- 核心转换 ---
 
@@ -32,9 +40,7 @@ pub fn hex_to_bytes_swap(s: &str) -> ProtocolResult<Vec<u8>> {
 
 /// 将字节切片反转顺序，然后编码为大写 Hex 字符串。
 pub fn bytes_to_hex_swap(bytes: &[u8]) -> ProtocolResult<String> {
-    let mut swapped_bytes = bytes.to_vec();
-    swapped_bytes.reverse();
-    bytes_to_hex(&swapped_bytes)
+    with_swapped(bytes, bytes_to_hex)
 }
 
 // --- 字节到数字转换 (大端序) ---
@@ -203,13 +209,13 @@ where
     let native_hex = format!("{:0width$x}", number, width = native_char_length).to_uppercase();
 
     match expected_char_length.cmp(&native_char_length) {
-        std::cmp::Ordering::Less => {
+        core::cmp::Ordering::Less => {
             // 截断
             let start_index = native_char_length - expected_char_length;
             Ok(native_hex[start_index..].to_string())
         }
-        std::cmp::Ordering::Equal => Ok(native_hex), // 长度相等
-        std::cmp::Ordering::Greater => {
+        core::cmp::Ordering::Equal => Ok(native_hex), // 长度相等
+        core::cmp::Ordering::Greater => {
             // 补位
             let padding_len = expected_char_length - native_char_length;
             // 使用 PartialOrd 和 Default 判断符号
@@ -346,13 +352,13 @@ fn _number_to_bits_internal(
     let native_len = native_width as usize;
 
     match expected_bit_length.cmp(&native_len) {
-        std::cmp::Ordering::Less => {
+        core::cmp::Ordering::Less => {
             // 截断
             let start_index = native_len - expected_bit_length;
             Ok(native_binary[start_index..].to_string())
         }
-        std::cmp::Ordering::Equal => Ok(native_binary), // 长度相等
-        std::cmp::Ordering::Greater => {
+        core::cmp::Ordering::Equal => Ok(native_binary), // 长度相等
+        core::cmp::Ordering::Greater => {
             // 补位 (零扩展)
             let padding_len = expected_bit_length - native_len;
             let mut padded_binary = String::with_capacity(expected_bit_length);
@@ -483,6 +489,126 @@ pub fn binary_str_to_bits(binary_str: &str) -> ProtocolResult<Vec<bool>> {
         .collect() // 收集 Result<bool, ProtocolError> 到 Result<Vec<bool>, ProtocolError>
 }
 
+// --- 位操作 ---
+
+/// 内部辅助函数：校验位区间 [bit_offset, bit_offset + bit_len) 是否落在 bytes 内
+fn _check_bit_range(total_bytes: usize, bit_offset: usize, bit_len: usize) -> ProtocolResult<()> {
+    if bit_len == 0 || bit_len > 64 {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(format!(
+            "bit_len must be in 1..=64, but got {}",
+            bit_len
+        ))));
+    }
+    // bit_offset 来自帧内容或配置，相加可能溢出
+    let total_bits = total_bytes.saturating_mul(8);
+    match bit_offset.checked_add(bit_len) {
+        Some(end) if end <= total_bits => Ok(()),
+        end => Err(ProtocolError::HexError(HexError::InvalidRange {
+            start: i64::try_from(bit_offset).unwrap_or(i64::MAX),
+            end: end.and_then(|e| i64::try_from(e).ok()).unwrap_or(i64::MAX),
+            reason: format!("bit range exceeds {} bits of input", total_bits),
+        })),
+    }
+}
+
+/// 从字节切片中提取任意位段，返回无符号整数。
+///
+/// 位序为大端 (MSB first)：bit_offset=0 表示第一个字节的最高位。
+/// 例如 `extract_bits(&[0b1010_1100, 0b1100_0000], 4, 6)` -> 0b110011
+pub fn extract_bits(bytes: &[u8], bit_offset: usize, bit_len: usize) -> ProtocolResult<u64> {
+    _check_bit_range(bytes.len(), bit_offset, bit_len)?;
+    let mut result: u64 = 0;
+    for i in bit_offset..bit_offset + bit_len {
+        let bit = (bytes[i / 8] >> (7 - i % 8)) & 1;
+        result = (result << 1) | bit as u64;
+    }
+    Ok(result)
+}
+
+/// 把 value 的低 bit_len 位写入字节切片的指定位段 (位序同 `extract_bits`)。
+/// value 超出 bit_len 能表示的范围时返回错误，而不是静默截断。
+pub fn set_bits(
+    bytes: &mut [u8],
+    bit_offset: usize,
+    bit_len: usize,
+    value: u64,
+) -> ProtocolResult<()> {
+    _check_bit_range(bytes.len(), bit_offset, bit_len)?;
+    if bit_len < 64 && value >> bit_len != 0 {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(format!(
+            "value {} does not fit in {} bits",
+            value, bit_len
+        ))));
+    }
+    for (n, i) in (bit_offset..bit_offset + bit_len).enumerate() {
+        let bit = ((value >> (bit_len - 1 - n)) & 1) as u8;
+        let mask = 1u8 << (7 - i % 8);
+        if bit == 1 {
+            bytes[i / 8] |= mask;
+        } else {
+            bytes[i / 8] &= !mask;
+        }
+    }
+    Ok(())
+}
+
+/// 读取单个位 (位序同 `extract_bits`)
+pub fn get_bit(bytes: &[u8], bit_offset: usize) -> ProtocolResult<bool> {
+    Ok(extract_bits(bytes, bit_offset, 1)? == 1)
+}
+
+/// 设置单个位 (位序同 `extract_bits`)
+pub fn set_bit(bytes: &mut [u8], bit_offset: usize, on: bool) -> ProtocolResult<()> {
+    set_bits(bytes, bit_offset, 1, on as u64)
+}
+
+// --- 字节变换 (XOR / 循环移位) ---
+
+/// 两个等长字节切片逐字节异或
+pub fn xor_bytes(a: &[u8], b: &[u8]) -> ProtocolResult<Vec<u8>> {
+    if a.len() != b.len() {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(format!(
+            "xor_bytes requires equal lengths, but got {} and {}",
+            a.len(),
+            b.len()
+        ))));
+    }
+    Ok(a.iter().zip(b).map(|(x, y)| x ^ y).collect())
+}
+
+/// 用循环密钥对数据逐字节异或 (密钥不足时从头重复)。再次调用即可还原
+pub fn xor_with_key(data: &[u8], key: &[u8]) -> ProtocolResult<Vec<u8>> {
+    if key.is_empty() {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(
+            "xor_with_key requires a non-empty key".into(),
+        )));
+    }
+    Ok(data
+        .iter()
+        .zip(key.iter().cycle())
+        .map(|(x, k)| x ^ k)
+        .collect())
+}
+
+/// hex-string 版本的 xor_with_key
+pub fn xor_hex_with_key(hex: &str, key_hex: &str) -> ProtocolResult<String> {
+    let data = hex_to_bytes(hex)?;
+    let key = hex_to_bytes(key_hex)?;
+    bytes_to_hex(&xor_with_key(&data, &key)?)
+}
+
+/// 按字节循环移位。n > 0 向左 (例如 [1,2,3], 1 -> [2,3,1])，n < 0 向右
+pub fn rotate_bytes(data: &[u8], n: i64) -> ProtocolResult<Vec<u8>> {
+    let mut result = data.to_vec();
+    if result.is_empty() {
+        return Ok(result);
+    }
+    let len = result.len() as i64;
+    let shift = n.rem_euclid(len) as usize;
+    result.rotate_left(shift);
+    Ok(result)
+}
+
 // --- 辅助函数 ---
 
 /// 反转 Hex 字符串的字节序 (e.g., "123456" -> "563412")
@@ -494,9 +620,70 @@ pub fn swap(hex: &str) -> ProtocolResult<String> {
 
 /// 反转字节切片的副本
 pub fn swap_bytes(bytes: &[u8]) -> ProtocolResult<Vec<u8>> {
-    let mut new_bytes = bytes.to_vec();
-    new_bytes.reverse();
-    Ok(new_bytes)
+    Ok(bytes.iter().rev().copied().collect())
+}
+
+// 小端字段通常不超过 16 字节，在栈上反转即可
+const SWAP_STACK_LEN: usize = 16;
+
+/// 以反转后的字节调用 f。不超过 16 字节时在栈上完成，不分配堆内存
+pub fn with_swapped<T>(bytes: &[u8], f: impl FnOnce(&[u8]) -> T) -> T {
+    if bytes.len() <= SWAP_STACK_LEN {
+        let mut buf = [0u8; SWAP_STACK_LEN];
+        let swapped = &mut buf[..bytes.len()];
+        swapped.copy_from_slice(bytes);
+        swapped.reverse();
+        f(swapped)
+    } else {
+        f(&swap_bytes(bytes).unwrap_or_default())
+    }
+}
+
+/// 反转定长数组 (2/4/8 字节字段常用)
+pub fn swap_array<const N: usize>(mut bytes: [u8; N]) -> [u8; N] {
+    bytes.reverse();
+    bytes
+}
+
+fn _check_word_len(bytes: &[u8], width: usize) -> ProtocolResult<()> {
+    if !bytes.len().is_multiple_of(width) {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(format!(
+            "buffer length {} is not a multiple of {}",
+            bytes.len(),
+            width
+        ))));
+    }
+    Ok(())
+}
+
+/// 原地把每 2 字节一组的大小端互换 (如 u16 数组)，长度须为 2 的倍数
+pub fn swap_u16_slice(bytes: &mut [u8]) -> ProtocolResult<()> {
+    _check_word_len(bytes, 2)?;
+    for word in bytes.chunks_exact_mut(2) {
+        word.swap(0, 1);
+    }
+    Ok(())
+}
+
+/// 原地把每 4 字节一组的大小端互换，长度须为 4 的倍数
+pub fn swap_u32_slice(bytes: &mut [u8]) -> ProtocolResult<()> {
+    _check_word_len(bytes, 4)?;
+    for word in bytes.chunks_exact_mut(4) {
+        let value = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+        word.copy_from_slice(&value.to_be_bytes());
+    }
+    Ok(())
+}
+
+/// 原地把每 8 字节一组的大小端互换，长度须为 8 的倍数
+pub fn swap_u64_slice(bytes: &mut [u8]) -> ProtocolResult<()> {
+    _check_word_len(bytes, 8)?;
+    for word in bytes.chunks_exact_mut(8) {
+        let mut array = [0u8; 8];
+        array.copy_from_slice(word);
+        word.copy_from_slice(&u64::from_le_bytes(array).to_be_bytes());
+    }
+    Ok(())
 }
 
 /// 截取字节数组的指定部分 (panic-safe)
@@ -760,10 +947,13 @@ pub fn ascii_to_string(ascii_hex_str: &str) -> ProtocolResult<String> {
     if v.is_empty() {
         return Ok(String::new());
     }
-    ensure_is_ascii_hex(&v)?;
-    let bytes = hex::decode(&v).unwrap(); // 安全，已检查
-    // from_utf8 在这里也是安全的，因为我们保证了是ASCII
-    Ok(String::from_utf8(bytes).unwrap())
+    // 不能先 ensure_is_ascii_hex 再 decode: 它会再清理一次前缀，"0x0x41" 能通过校验但 decode 失败
+    let bytes = hex::decode(&v)
+        .ok()
+        .filter(|b| b.is_ascii())
+        .ok_or_else(|| ProtocolError::HexError(HexError::NotAscii(v.clone())))?;
+    // ASCII 一定是合法的 UTF-8
+    Ok(bytes.into_iter().map(char::from).collect())
 }
 
 /// String -> ASCII Hex
@@ -779,6 +969,201 @@ pub fn string_to_ascii(plain_str: &str) -> ProtocolResult<String> {
     bytes_to_hex(plain_str.as_bytes())
 }
 
+// --- Base64 / Base58 转换 ---
+
+/// 内部辅助函数：统一 base64 解码的错误
+fn _decode_base64<E: base64::Engine>(
+    engine: &E,
+    s: &str,
+    context: &'static str,
+) -> ProtocolResult<Vec<u8>> {
+    engine.decode(s.trim()).map_err(|e| {
+        ProtocolError::HexError(HexError::HexParseError {
+            context,
+            reason: e.to_string(),
+        })
+    })
+}
+
+/// Base64 (标准字母表，带补位) -> 字节
+pub fn base64_to_bytes(s: &str) -> ProtocolResult<Vec<u8>> {
+    _decode_base64(&base64::engine::general_purpose::STANDARD, s, "base64")
+}
+
+/// 字节 -> Base64 (标准字母表，带补位)
+pub fn bytes_to_base64(bytes: &[u8]) -> ProtocolResult<String> {
+    Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        bytes,
+    ))
+}
+
+/// Base64 (URL 安全字母表，不带补位) -> 字节
+pub fn base64_url_to_bytes(s: &str) -> ProtocolResult<Vec<u8>> {
+    _decode_base64(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        s.trim_end_matches('='),
+        "base64-url",
+    )
+}
+
+/// 字节 -> Base64 (URL 安全字母表，不带补位)
+pub fn bytes_to_base64_url(bytes: &[u8]) -> ProtocolResult<String> {
+    Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        bytes,
+    ))
+}
+
+/// Base64 -> hex-string (大写)。MQTT 平台下发的 payload 常见此格式
+pub fn base64_to_hex(s: &str) -> ProtocolResult<String> {
+    let bytes = base64_to_bytes(s)?;
+    bytes_to_hex(&bytes)
+}
+
+/// hex-string -> Base64
+pub fn hex_to_base64(hex: &str) -> ProtocolResult<String> {
+    let bytes = hex_to_bytes(hex)?;
+    bytes_to_base64(&bytes)
+}
+
+// Base58 字母表 (比特币风格，去掉了 0 O I l)
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// 字节 -> Base58
+pub fn bytes_to_base58(bytes: &[u8]) -> ProtocolResult<String> {
+    // 前导 0x00 原样映射为 '1'
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+    // 以 58 进制存放的大数 (小端)
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &byte in &bytes[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let mut result = String::with_capacity(zeros + digits.len());
+    result.extend(core::iter::repeat_n('1', zeros));
+    result.extend(
+        digits
+            .iter()
+            .rev()
+            .map(|&d| BASE58_ALPHABET[d as usize] as char),
+    );
+    Ok(result)
+}
+
+/// Base58 -> 字节
+pub fn base58_to_bytes(s: &str) -> ProtocolResult<Vec<u8>> {
+    let s = s.trim();
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+    // 以 256 进制存放的大数 (小端)
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len() * 733 / 1000 + 1);
+    for c in s.bytes().skip(zeros) {
+        let value = BASE58_ALPHABET
+            .iter()
+            .position(|&a| a == c)
+            .ok_or_else(|| {
+                ProtocolError::HexError(HexError::HexParseError {
+                    context: "base58",
+                    reason: format!("Invalid character '{}' found in base58 string", c as char),
+                })
+            })?;
+        let mut carry = value as u32;
+        for byte in bytes.iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xFF) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push((carry & 0xFF) as u8);
+            carry >>= 8;
+        }
+    }
+    let mut result = vec![0u8; zeros];
+    result.extend(bytes.iter().rev());
+    Ok(result)
+}
+
+// --- 格式化输出 ---
+
+/// 按组输出带空格的 hex-string。例如 group_size=2: "6810 0001 16"
+pub fn format_hex_spaced(bytes: &[u8], group_size: usize) -> ProtocolResult<String> {
+    if group_size == 0 {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(
+            "group_size must be greater than 0".into(),
+        )));
+    }
+    let groups: Vec<String> = bytes.chunks(group_size).map(hex::encode_upper).collect();
+    Ok(groups.join(" "))
+}
+
+/// 经典的 hex dump: 每行 "偏移量: hex | ascii"
+pub fn hex_dump(bytes: &[u8], bytes_per_line: usize) -> ProtocolResult<String> {
+    if bytes_per_line == 0 {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(
+            "bytes_per_line must be greater than 0".into(),
+        )));
+    }
+    let hex_width = bytes_per_line * 3 - 1;
+    let lines: Vec<String> = bytes
+        .chunks(bytes_per_line)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex = format_hex_spaced(chunk, 1).unwrap_or_default();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!(
+                "{:04X}: {:<width$} | {}",
+                i * bytes_per_line,
+                hex,
+                ascii,
+                width = hex_width
+            )
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+/// 带注释的 dump: 按 Rawfield 的顺序输出 偏移量、hex 和 标题/真值，便于排查 rsp_hex。
+/// 偏移量按字段字节长度依次累加得到，因此要求 fields 与报文字节顺序一致。
+pub fn annotated_dump(fields: &[Rawfield]) -> ProtocolResult<String> {
+    let hex_column: Vec<String> = fields
+        .iter()
+        .map(|f| format_hex_spaced(f.bytes(), 1))
+        .collect::<ProtocolResult<_>>()?;
+    let hex_width = hex_column.iter().map(|h| h.len()).max().unwrap_or(0);
+
+    let mut offset = 0usize;
+    let mut lines = Vec::with_capacity(fields.len());
+    for (field, hex) in fields.iter().zip(hex_column) {
+        lines.push(format!(
+            "{:04X}  {:<width$}  {}: {}",
+            offset,
+            hex,
+            field.title(),
+            field.value(),
+            width = hex_width
+        ));
+        offset += field.bytes().len();
+    }
+    Ok(lines.join("\n"))
+}
+
 // --- 内部辅助函数 ---
 
 /// 辅助函数：清理 hex 字符串 (trim, strip "0x")
@@ -798,3 +1183,218 @@ fn _clean_and_pad_hex_str(hex: &str) -> String {
         format!("0{}", cleaned)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_slices() {
+        let mut words = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        swap_u16_slice(&mut words).unwrap();
+        assert_eq!(words, [0x02, 0x01, 0x04, 0x03, 0x06, 0x05, 0x08, 0x07]);
+        swap_u16_slice(&mut words).unwrap();
+        swap_u32_slice(&mut words).unwrap();
+        assert_eq!(words, [0x04, 0x03, 0x02, 0x01, 0x08, 0x07, 0x06, 0x05]);
+        swap_u32_slice(&mut words).unwrap();
+        swap_u64_slice(&mut words).unwrap();
+        assert_eq!(words, [0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]);
+        assert!(matches!(
+            swap_u32_slice(&mut words[..6]),
+            Err(ProtocolError::HexError(HexError::InvalidInput(_)))
+        ));
+
+        assert_eq!(swap_array([0x12, 0x34]), [0x34, 0x12]);
+        let long: Vec<u8> = (0..20).collect();
+        assert_eq!(with_swapped(&long, |b| b[0]), 19);
+        assert_eq!(bytes_to_hex_swap(&[0x12, 0x34, 0x56]).unwrap(), "563412");
+    }
+
+    #[test]
+    fn test_xor_bytes() {
+        assert_eq!(
+            xor_bytes(&[0xFF, 0x0F], &[0x0F, 0x0F]).unwrap(),
+            vec![0xF0, 0x00]
+        );
+        assert!(xor_bytes(&[0x01], &[0x01, 0x02]).is_err());
+    }
+
+    #[test]
+    fn test_xor_with_key() {
+        let data = [0x11, 0x22, 0x33, 0x44, 0x55];
+        let masked = xor_with_key(&data, &[0xA5, 0x5A]).unwrap();
+        assert_eq!(masked, vec![0xB4, 0x78, 0x96, 0x1E, 0xF0]);
+        assert_eq!(xor_with_key(&masked, &[0xA5, 0x5A]).unwrap(), data);
+        assert_eq!(xor_hex_with_key("1122", "FF").unwrap(), "EEDD");
+        assert!(xor_with_key(&data, &[]).is_err());
+    }
+
+    #[test]
+    fn test_rotate_bytes() {
+        assert_eq!(rotate_bytes(&[1, 2, 3], 1).unwrap(), vec![2, 3, 1]);
+        assert_eq!(rotate_bytes(&[1, 2, 3], -1).unwrap(), vec![3, 1, 2]);
+        assert_eq!(rotate_bytes(&[1, 2, 3], 4).unwrap(), vec![2, 3, 1]);
+        assert!(rotate_bytes(&[], 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_extract_bits() {
+        assert_eq!(
+            extract_bits(&[0b1010_1100, 0b1100_0000], 4, 6).unwrap(),
+            0b110011
+        );
+        // 10 位字段跨两个字节: 第一个字节低 5 位 + 第二个字节高 5 位
+        assert_eq!(
+            extract_bits(&[0b0001_0110, 0b1011_1000], 3, 10).unwrap(),
+            0b10110_10111
+        );
+        assert_eq!(extract_bits(&[0xFF; 8], 0, 64).unwrap(), u64::MAX);
+        assert!(extract_bits(&[0x00, 0x00], 7, 10).is_err());
+        assert!(extract_bits(&[0x00], 0, 0).is_err());
+        assert!(extract_bits(&[0x00; 9], 0, 65).is_err());
+        assert!(matches!(
+            extract_bits(&[0x00], usize::MAX, 2),
+            Err(ProtocolError::HexError(HexError::InvalidRange { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_set_bits() {
+        let mut bytes = [0xFF, 0xFF];
+        set_bits(&mut bytes, 3, 10, 0).unwrap();
+        assert_eq!(bytes, [0b1110_0000, 0b0000_0111]);
+        set_bits(&mut bytes, 3, 10, 0b10110_10111).unwrap();
+        assert_eq!(bytes, [0b1111_0110, 0b1011_1111]);
+        assert_eq!(extract_bits(&bytes, 3, 10).unwrap(), 0b10110_10111);
+        // 超出位宽报错，不截断
+        assert!(set_bits(&mut bytes, 3, 10, 1 << 10).is_err());
+        assert!(set_bits(&mut bytes, 10, 10, 0).is_err());
+        assert!(set_bits(&mut bytes, usize::MAX, 1, 0).is_err());
+        assert_eq!(bytes, [0b1111_0110, 0b1011_1111]);
+    }
+
+    #[test]
+    fn test_get_set_bit() {
+        let mut bytes = [0x00, 0x00];
+        set_bit(&mut bytes, 0, true).unwrap();
+        set_bit(&mut bytes, 15, true).unwrap();
+        assert_eq!(bytes, [0x80, 0x01]);
+        assert!(get_bit(&bytes, 0).unwrap());
+        assert!(!get_bit(&bytes, 1).unwrap());
+        assert!(get_bit(&bytes, 15).unwrap());
+        set_bit(&mut bytes, 0, false).unwrap();
+        assert_eq!(bytes, [0x00, 0x01]);
+        assert!(get_bit(&bytes, 16).is_err());
+        assert!(set_bit(&mut bytes, 16, true).is_err());
+    }
+
+    #[test]
+    fn test_base64_round_trip() {
+        assert_eq!(bytes_to_base64(b"hello").unwrap(), "aGVsbG8=");
+        assert_eq!(base64_to_bytes(" aGVsbG8= ").unwrap(), b"hello");
+        // 标准与 URL 安全字母表的区别在 62/63 两个字符和补位
+        assert_eq!(bytes_to_base64(&[0xFB, 0xFF]).unwrap(), "+/8=");
+        assert_eq!(bytes_to_base64_url(&[0xFB, 0xFF]).unwrap(), "-_8");
+        assert_eq!(base64_url_to_bytes("-_8=").unwrap(), [0xFB, 0xFF]);
+        assert_eq!(hex_to_base64("6810FF16").unwrap(), "aBD/Fg==");
+        assert_eq!(base64_to_hex("aBD/Fg==").unwrap(), "6810FF16");
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(
+            base64_to_bytes(&bytes_to_base64(&all).unwrap()).unwrap(),
+            all
+        );
+        assert_eq!(
+            base64_url_to_bytes(&bytes_to_base64_url(&all).unwrap()).unwrap(),
+            all
+        );
+        assert!(bytes_to_base64(&[]).unwrap().is_empty());
+
+        assert!(base64_to_bytes("aGVs!G8=").is_err());
+        assert!(base64_to_bytes("-_8=").is_err());
+        assert!(base64_url_to_bytes("+/8").is_err());
+    }
+
+    #[test]
+    fn test_base58_round_trip() {
+        assert_eq!(
+            bytes_to_base58(b"Hello World!").unwrap(),
+            "2NEpo7TZRRrLZSi2U"
+        );
+        assert_eq!(
+            base58_to_bytes("2NEpo7TZRRrLZSi2U").unwrap(),
+            b"Hello World!"
+        );
+        // 前导 0x00 一一对应前导 '1'
+        assert_eq!(
+            bytes_to_base58(&[0x00, 0x00, 0x28, 0x7F, 0xB4, 0xCD]).unwrap(),
+            "11233QC4"
+        );
+        assert_eq!(
+            base58_to_bytes("11233QC4").unwrap(),
+            [0x00, 0x00, 0x28, 0x7F, 0xB4, 0xCD]
+        );
+        assert_eq!(bytes_to_base58(&[0x00, 0x00]).unwrap(), "11");
+        assert_eq!(base58_to_bytes("11").unwrap(), [0x00, 0x00]);
+        assert!(bytes_to_base58(&[]).unwrap().is_empty());
+        assert!(base58_to_bytes("").unwrap().is_empty());
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(
+            base58_to_bytes(&bytes_to_base58(&all).unwrap()).unwrap(),
+            all
+        );
+
+        // 0 O I l 不在字母表中
+        for invalid in ["0", "2NEpO7", "I1", "1l", "2NE+"] {
+            assert!(base58_to_bytes(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_format_hex_spaced() {
+        let bytes = [0x68, 0x10, 0x00, 0x01, 0x16];
+        assert_eq!(format_hex_spaced(&bytes, 1).unwrap(), "68 10 00 01 16");
+        assert_eq!(format_hex_spaced(&bytes, 2).unwrap(), "6810 0001 16");
+        assert_eq!(format_hex_spaced(&bytes, 8).unwrap(), "6810000116");
+        assert_eq!(format_hex_spaced(&[], 2).unwrap(), "");
+        assert!(format_hex_spaced(&bytes, 0).is_err());
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let bytes = b"AB\x01\x7Fhi j";
+        let expected = "\
+0000: 41 42 01 7F | AB..
+0004: 68 69 20 6A | hi j";
+        assert_eq!(hex_dump(bytes, 4).unwrap(), expected);
+        // 最后一行不足时 hex 列补齐，ascii 列对齐
+        let expected = "\
+0000: 41 42 01 | AB.
+0003: 7F 68 69 | .hi
+0006: 20 6A    |  j";
+        assert_eq!(hex_dump(bytes, 3).unwrap(), expected);
+        assert_eq!(hex_dump(&[], 16).unwrap(), "");
+        assert!(hex_dump(bytes, 0).is_err());
+    }
+
+    #[test]
+    fn test_annotated_dump() {
+        let fields = [
+            Rawfield::new(&[0x68], "起始符", "68".into()),
+            Rawfield::new(&[0x12, 0x34, 0x56], "地址", "563412".into()),
+            Rawfield::new(&[0x16], "结束符", "16".into()),
+        ];
+        let expected = "\
+0000  68        起始符: 68
+0001  12 34 56  地址: 563412
+0004  16        结束符: 16";
+        assert_eq!(annotated_dump(&fields).unwrap(), expected);
+        assert_eq!(annotated_dump(&[]).unwrap(), "");
+    }
+
+    #[test]
+    fn test_ascii_to_string_double_prefix() {
+        assert_eq!(ascii_to_string("0x4142").unwrap(), "AB");
+        assert!(ascii_to_string("0x0x41").is_err());
+        assert!(ascii_to_string("C1").is_err());
+    }
+}
@@ -1,3 +1,8 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 use protocol_base::{ProtocolResult, error::ProtocolError};
 use rust_decimal::RoundingStrategy;
 use rust_decimal::prelude::*;
@@ -19,7 +24,7 @@ pub enum DecimalRoundingMode {
 
 impl DecimalRoundingMode {
     /// 转换为 rust_decimal 库的内部策略
-    fn to_strategy(self) -> RoundingStrategy {
+    pub(crate) fn to_strategy(self) -> RoundingStrategy {
         match self {
             DecimalRoundingMode::HalfUp => RoundingStrategy::MidpointAwayFromZero,
             DecimalRoundingMode::Down => RoundingStrategy::ToZero,
@@ -124,3 +129,315 @@ pub fn divide(
     let final_result = result.round_dp_with_strategy(scale, rounding_mode.to_strategy());
     Ok(decimal_to_f64(final_result))
 }
+
+/// 高精度取余 (对应 Java remainder)，结果符号与被除数相同
+pub fn remainder(dividend: f64, divisor: f64) -> ProtocolResult<f64> {
+    let d_dividend = f64_to_decimal(dividend)?;
+    let d_divisor = f64_to_decimal(divisor)?;
+
+    if d_divisor.is_zero() {
+        return Err(ProtocolError::CommonError("Division by zero".into()));
+    }
+
+    let result = d_dividend
+        .checked_rem(d_divisor)
+        .ok_or_else(|| ProtocolError::CommonError("Decimal remainder overflow".into()))?;
+    Ok(decimal_to_f64(result))
+}
+
+/// 高精度整数次幂 (对应 Java pow)，exponent 可以为负数
+///
+/// # Arguments
+/// * `scale` - 小数位数
+/// * `rounding_mode` - 舍入模式
+pub fn power(
+    base: f64,
+    exponent: i32,
+    scale: u32,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<f64> {
+    let overflow = || ProtocolError::CommonError("Decimal power overflow".into());
+    let d_base = f64_to_decimal(base)?;
+    if exponent < 0 && d_base.is_zero() {
+        return Err(ProtocolError::CommonError("Division by zero".into()));
+    }
+
+    // 快速幂
+    let mut result = Decimal::ONE;
+    let mut factor = d_base;
+    let mut n = exponent.unsigned_abs();
+    while n > 0 {
+        if n & 1 == 1 {
+            result = result.checked_mul(factor).ok_or_else(overflow)?;
+        }
+        n >>= 1;
+        if n > 0 {
+            factor = factor.checked_mul(factor).ok_or_else(overflow)?;
+        }
+    }
+    if exponent < 0 {
+        result = Decimal::ONE.checked_div(result).ok_or_else(overflow)?;
+    }
+    let final_result = result.round_dp_with_strategy(scale, rounding_mode.to_strategy());
+    Ok(decimal_to_f64(final_result))
+}
+
+/// 高精度差的绝对值 |a - b|
+pub fn abs_diff(a: f64, b: f64) -> ProtocolResult<f64> {
+    let result = f64_to_decimal(a)?
+        .checked_sub(f64_to_decimal(b)?)
+        .ok_or_else(|| ProtocolError::CommonError("Decimal subtraction overflow".into()))?;
+    Ok(decimal_to_f64(result.abs()))
+}
+
+/// 把 value 限制在 [min, max] 之间
+pub fn clamp(value: f64, min: f64, max: f64) -> ProtocolResult<f64> {
+    let d_min = f64_to_decimal(min)?;
+    let d_max = f64_to_decimal(max)?;
+    if d_min > d_max {
+        return Err(ProtocolError::CommonError(format!(
+            "Invalid clamp range: min {} is greater than max {}",
+            min, max
+        )));
+    }
+    let result = f64_to_decimal(value)?.clamp(d_min, d_max);
+    Ok(decimal_to_f64(result))
+}
+
+/// 按小数位数舍入 (对应 Java setScale)
+pub fn round(value: f64, scale: u32, rounding_mode: DecimalRoundingMode) -> ProtocolResult<f64> {
+    let result = f64_to_decimal(value)?.round_dp_with_strategy(scale, rounding_mode.to_strategy());
+    Ok(decimal_to_f64(result))
+}
+
+/// 按步长舍入。例如价格按 0.01 取整: round_to_step(3.14159, 0.01, HalfUp) -> 3.14
+/// 或按 0.5 取整: round_to_step(2.7, 0.5, Floor) -> 2.5
+pub fn round_to_step(
+    value: f64,
+    step: f64,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<f64> {
+    let d_step = f64_to_decimal(step)?;
+    if d_step <= Decimal::ZERO {
+        return Err(ProtocolError::CommonError(format!(
+            "Rounding step must be positive, but got {}",
+            step
+        )));
+    }
+    let steps = f64_to_decimal(value)?
+        .checked_div(d_step)
+        .ok_or_else(|| ProtocolError::CommonError("Decimal division overflow".into()))?
+        .round_dp_with_strategy(0, rounding_mode.to_strategy());
+    let result = steps
+        .checked_mul(d_step)
+        .ok_or_else(|| ProtocolError::CommonError("Decimal multiplication overflow".into()))?;
+    Ok(decimal_to_f64(result.normalize()))
+}
+
+// --- 批量版本 ---
+
+/// 批量按小数位数舍入
+pub fn round_batch(
+    values: &[f64],
+    scale: u32,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<Vec<f64>> {
+    values
+        .iter()
+        .map(|&v| round(v, scale, rounding_mode))
+        .collect()
+}
+
+/// 批量按步长舍入
+pub fn round_to_step_batch(
+    values: &[f64],
+    step: f64,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<Vec<f64>> {
+    values
+        .iter()
+        .map(|&v| round_to_step(v, step, rounding_mode))
+        .collect()
+}
+
+/// 批量限制范围
+pub fn clamp_batch(values: &[f64], min: f64, max: f64) -> ProtocolResult<Vec<f64>> {
+    values.iter().map(|&v| clamp(v, min, max)).collect()
+}
+
+/// 批量取余
+pub fn remainder_batch(values: &[f64], divisor: f64) -> ProtocolResult<Vec<f64>> {
+    values.iter().map(|&v| remainder(v, divisor)).collect()
+}
+
+/// 批量乘以同一个系数 (常用于按缩放倍数换算一组读数)
+pub fn multiply_batch(
+    values: &[f64],
+    factor: f64,
+    scale: u32,
+    rounding_mode: DecimalRoundingMode,
+) -> ProtocolResult<Vec<f64>> {
+    values
+        .iter()
+        .map(|&v| multiply(scale, rounding_mode, &[v, factor]))
+        .collect()
+}
+
+/// 解析来自操作台/前端的数字输入，统一格式后再转换为 f64
+///
+/// 支持:
+/// * 千分位逗号: "1,234.5" -> 1234.5
+/// * 科学计数法: "1.5e3" -> 1500
+/// * 前导 "+" 和首尾空白
+///
+/// 拒绝有歧义的格式 (例如欧洲写法 "1.234,5"、分组不是3位的 "1,23")，以及 NaN / inf。
+pub fn parse_numeric(input: &str) -> ProtocolResult<f64> {
+    let normalized = normalize_numeric(input)?;
+    let value: f64 = normalized.parse().map_err(|_| {
+        ProtocolError::ValidationFailed(format!("Failed to parse input '{}' as number", input))
+    })?;
+    if !value.is_finite() {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "Input '{}' is not a finite number",
+            input
+        )));
+    }
+    Ok(value)
+}
+
+/// (内部) 去掉空白、前导 "+" 和千分位逗号，并校验分组是否合法
+fn normalize_numeric(input: &str) -> ProtocolResult<String> {
+    let ambiguous = || {
+        ProtocolError::ValidationFailed(format!(
+            "Ambiguous numeric input '{}'. Use '.' as the decimal separator and ',' only as a thousand separator",
+            input
+        ))
+    };
+    let trimmed = input.trim();
+    let unsigned = trimmed.strip_prefix('+').unwrap_or(trimmed);
+    if !unsigned.contains(',') {
+        return Ok(unsigned.to_string());
+    }
+
+    let (sign, body) = match unsigned.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", unsigned),
+    };
+    // 千分位只能出现在整数部分
+    let int_end = body.find(['.', 'e', 'E']).unwrap_or(body.len());
+    let (int_part, rest) = body.split_at(int_end);
+    if rest.contains(',') {
+        return Err(ambiguous());
+    }
+    let groups: Vec<&str> = int_part.split(',').collect();
+    let first_ok = !groups[0].is_empty()
+        && groups[0].len() <= 3
+        && groups[0].chars().all(|c| c.is_ascii_digit());
+    let rest_ok = groups[1..]
+        .iter()
+        .all(|g| g.len() == 3 && g.chars().all(|c| c.is_ascii_digit()));
+    if !first_ok || !rest_ok {
+        return Err(ambiguous());
+    }
+    Ok(format!("{}{}{}", sign, groups.concat(), rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_numeric() {
+        assert_eq!(parse_numeric("1,234.5").unwrap(), 1234.5);
+        assert_eq!(parse_numeric(" +1,234,567 ").unwrap(), 1234567.0);
+        assert_eq!(parse_numeric("-1,234.5").unwrap(), -1234.5);
+        assert_eq!(parse_numeric("1.5e3").unwrap(), 1500.0);
+        assert_eq!(parse_numeric("0.01").unwrap(), 0.01);
+        assert_eq!(normalize_numeric("1,234.5").unwrap(), "1234.5");
+        assert_eq!(normalize_numeric("+12").unwrap(), "12");
+
+        // 欧式写法 (逗号作小数点) 与分组不规范的输入一律拒绝，不做猜测
+        for ambiguous in [
+            "1.234,5", "1,23", "12,34.5", ",123", "1,2345", "1,234,", "1.5,0",
+        ] {
+            let err = normalize_numeric(ambiguous).unwrap_err().to_string();
+            assert!(err.contains("Ambiguous"), "{}: {}", ambiguous, err);
+        }
+        assert!(parse_numeric("abc").is_err());
+        assert!(parse_numeric("").is_err());
+        assert!(parse_numeric("inf").is_err());
+        assert!(parse_numeric("NaN").is_err());
+    }
+
+    #[test]
+    fn test_remainder() {
+        assert_eq!(remainder(10.5, 3.0).unwrap(), 1.5);
+        assert_eq!(remainder(0.3, 0.1).unwrap(), 0.0);
+        // 结果符号与被除数相同
+        assert_eq!(remainder(-7.0, 3.0).unwrap(), -1.0);
+        assert_eq!(remainder(7.0, -3.0).unwrap(), 1.0);
+        assert!(remainder(1.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_power() {
+        use DecimalRoundingMode::*;
+        assert_eq!(power(2.0, 10, 0, HalfUp).unwrap(), 1024.0);
+        assert_eq!(power(1.1, 2, 2, HalfUp).unwrap(), 1.21);
+        assert_eq!(power(-2.0, 3, 0, HalfUp).unwrap(), -8.0);
+        assert_eq!(power(5.0, 0, 0, HalfUp).unwrap(), 1.0);
+        assert_eq!(power(2.0, -2, 4, HalfUp).unwrap(), 0.25);
+        assert_eq!(power(-0.5, -1, 2, HalfUp).unwrap(), -2.0);
+        assert_eq!(power(3.0, -1, 4, HalfUp).unwrap(), 0.3333);
+        assert_eq!(power(3.0, -1, 4, Up).unwrap(), 0.3334);
+        assert!(power(0.0, -1, 2, HalfUp).is_err());
+        assert!(power(1e10, 10, 0, HalfUp).is_err());
+    }
+
+    #[test]
+    fn test_round_to_step() {
+        use DecimalRoundingMode::*;
+        assert_eq!(round_to_step(3.456, 0.01, HalfUp).unwrap(), 3.46);
+        assert_eq!(round_to_step(2.7, 0.5, Floor).unwrap(), 2.5);
+        assert_eq!(round_to_step(2.75, 0.5, HalfUp).unwrap(), 3.0);
+        // 负数: Floor 趋向负无穷，Down 趋向零，HalfUp 远离零
+        assert_eq!(round_to_step(-2.7, 0.5, Floor).unwrap(), -3.0);
+        assert_eq!(round_to_step(-2.7, 0.5, Down).unwrap(), -2.5);
+        assert_eq!(round_to_step(-2.75, 0.5, HalfUp).unwrap(), -3.0);
+        assert_eq!(round_to_step(-2.7, 0.5, Ceiling).unwrap(), -2.5);
+        assert_eq!(round_to_step(17.0, 5.0, Up).unwrap(), 20.0);
+        // 步长必须为正
+        assert!(round_to_step(1.0, 0.0, HalfUp).is_err());
+        assert!(round_to_step(1.0, -0.5, HalfUp).is_err());
+    }
+
+    #[test]
+    fn test_batch() {
+        use DecimalRoundingMode::*;
+        assert_eq!(
+            round_batch(&[1.005, -1.005], 2, HalfUp).unwrap(),
+            [1.01, -1.01]
+        );
+        assert_eq!(round_batch(&[1.005, -1.005], 2, Down).unwrap(), [1.0, -1.0]);
+        assert_eq!(
+            round_to_step_batch(&[3.2, -2.7], 0.5, Floor).unwrap(),
+            [3.0, -3.0]
+        );
+        assert!(round_to_step_batch(&[], 0.5, HalfUp).unwrap().is_empty());
+        assert!(round_to_step_batch(&[1.0, 2.0], 0.0, HalfUp).is_err());
+        assert_eq!(
+            remainder_batch(&[5.0, -5.0, 6.0], 3.0).unwrap(),
+            [2.0, -2.0, 0.0]
+        );
+        assert!(remainder_batch(&[1.0], 0.0).is_err());
+        assert_eq!(
+            clamp_batch(&[-5.0, 5.0, 15.0], 0.0, 10.0).unwrap(),
+            [0.0, 5.0, 10.0]
+        );
+        assert!(clamp_batch(&[1.0], 10.0, 0.0).is_err());
+        assert_eq!(
+            multiply_batch(&[1234.0, -56.0], 0.01, 2, HalfUp).unwrap(),
+            [12.34, -0.56]
+        );
+    }
+}
@@ -1,14 +1,34 @@
+#[cfg(feature = "std")]
+use std::{collections::HashMap, sync::RwLock};
+
+#[cfg(feature = "std")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "std")]
 use pinyin::ToPinyin;
+#[cfg(feature = "std")]
+use protocol_base::{ProtocolError, ProtocolResult};
+#[cfg(feature = "std")]
 use rand::Rng;
 
+pub mod bcd_util;
+#[cfg(feature = "std")]
+pub mod code_registry;
 pub mod crc_util;
 pub mod hex_util;
 pub mod math_util;
+pub mod tariff;
 pub mod timestamp_util;
 
+#[cfg(feature = "std")]
+use crate::core::parts::title::Title;
+
+// 以下随机数与拼音工具依赖 std (rand/pinyin)，no_std 构建中不提供
+
 // 定义字符集：大写字母(A-Z) + 小写字母(a-z) + 数字(0-9)
+#[cfg(feature = "std")]
 const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
 
+#[cfg(feature = "std")]
 pub fn generate_rand(len: usize) -> String {
     let mut rng = rand::rng();
     std::iter::repeat_with(|| {
@@ -19,6 +39,41 @@ pub fn generate_rand(len: usize) -> String {
     .collect()
 }
 
+/// 生成 byte_len 个随机字节，返回大写 hex 字符串 (长度 byte_len * 2)
+#[cfg(feature = "std")]
+pub fn generate_rand_hex(byte_len: usize) -> String {
+    let mut rng = rand::rng();
+    (0..byte_len)
+        .map(|_| format!("{:02X}", rng.random::<u8>()))
+        .collect()
+}
+
+/// 生成 digits 位随机十进制数的 BCD hex 字符串，奇数位时高位补 0 凑满整字节
+#[cfg(feature = "std")]
+pub fn generate_rand_bcd(digits: usize) -> String {
+    let mut rng = rand::rng();
+    let mut s: String = (0..digits)
+        .map(|_| char::from(b'0' + rng.random_range(0..10u8)))
+        .collect();
+    if digits % 2 == 1 {
+        s.insert(0, '0');
+    }
+    s
+}
+
+/// 生成 [min, max] 闭区间内的随机数，常用于报文序号
+#[cfg(feature = "std")]
+pub fn generate_rand_range(min: u64, max: u64) -> ProtocolResult<u64> {
+    if min > max {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "invalid random range: min {} > max {}",
+            min, max
+        )));
+    }
+    Ok(rand::rng().random_range(min..=max))
+}
+
+#[cfg(feature = "std")]
 pub fn to_pinyin(s: &str) -> String {
     let mut result: Vec<String> = Vec::new();
     let mut non_chinese_buffer = String::new();
@@ -60,3 +115,89 @@ pub fn to_pinyin(s: &str) -> String {
 
     result.join("_").trim().to_string()
 }
+
+/// 拼音缓存最多保存的标题数，超出后不再缓存 (仍然正常计算)
+#[cfg(feature = "std")]
+pub const PINYIN_CACHE_CAPACITY: usize = 4096;
+
+#[cfg(feature = "std")]
+static PINYIN_CACHE: Lazy<RwLock<HashMap<Title, String>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 带全局缓存的 to_pinyin，同一个标题只计算一次。字段 code 都经过这里
+#[cfg(feature = "std")]
+pub fn to_pinyin_cached(s: &str) -> String {
+    if let Some(code) = PINYIN_CACHE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(s)
+    {
+        return code.clone();
+    }
+    let code = to_pinyin(s);
+    let mut cache = PINYIN_CACHE.write().unwrap_or_else(|e| e.into_inner());
+    if cache.len() < PINYIN_CACHE_CAPACITY {
+        cache.insert(Title::intern(s), code.clone());
+    }
+    code
+}
+
+/// 预先计算一批标题的拼音 (通常在协议注册时)，避免第一帧解码时集中计算
+#[cfg(feature = "std")]
+pub fn precompute_pinyin<I, S>(titles: I)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    for title in titles {
+        to_pinyin_cached(title.as_ref());
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn pinyin_cache_len() -> usize {
+    PINYIN_CACHE.read().unwrap_or_else(|e| e.into_inner()).len()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_rand_hex() {
+        assert_eq!(generate_rand_hex(0), "");
+        for _ in 0..32 {
+            let s = generate_rand_hex(8);
+            assert_eq!(s.len(), 16);
+            assert!(s.chars().all(|c| matches!(c, '0'..='9' | 'A'..='F')));
+            assert_eq!(hex_util::hex_to_bytes(&s).unwrap().len(), 8);
+        }
+    }
+
+    #[test]
+    fn test_generate_rand_bcd() {
+        for digits in [1, 2, 5, 12] {
+            let s = generate_rand_bcd(digits);
+            // 奇数位补一个前导 0
+            assert_eq!(s.len(), digits + digits % 2);
+            assert!(hex_util::is_bcd(&s));
+            if digits % 2 == 1 {
+                assert!(s.starts_with('0'));
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_rand_range() {
+        for _ in 0..64 {
+            let n = generate_rand_range(10, 12).unwrap();
+            assert!((10..=12).contains(&n));
+        }
+        assert_eq!(generate_rand_range(7, 7).unwrap(), 7);
+        assert!(generate_rand_range(0, u64::MAX).is_ok());
+        assert!(matches!(
+            generate_rand_range(3, 2),
+            Err(ProtocolError::ValidationFailed(_))
+        ));
+    }
+}
@@ -0,0 +1,320 @@
+//! 阶梯计价与余额扣减。金额全程使用 Decimal，只在写入下行帧时转换为整数的最小单位 (分)
+use alloc::{format, vec::Vec};
+use protocol_base::{error::ProtocolError, ProtocolResult};
+use rust_decimal::prelude::*;
+
+use crate::utils::math_util::DecimalRoundingMode;
+
+/// 一档价格。upper 为本档的累计用量上限 (不含)，None 表示不封顶，只能出现在最后一档
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TariffTier {
+    pub upper: Option<Decimal>,
+    pub price: Decimal,
+}
+
+/// 舍入发生的位置。有的水司按每档分别舍入后相加，有的只对总额舍入
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingPoint {
+    PerTier,
+    #[default]
+    Total,
+}
+
+/// 计费周期 (通常为自然年) 内按累计用量分档的价格表
+#[derive(Debug, Clone)]
+pub struct TariffTable {
+    tiers: Vec<TariffTier>,
+    // 金额保留的小数位数
+    scale: u32,
+    rounding: DecimalRoundingMode,
+    rounding_point: RoundingPoint,
+}
+
+/// 一次扣费的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deduction {
+    pub cost: Decimal,
+    // 扣费后的余额，可能为负 (透支)
+    pub balance: Decimal,
+    // 扣费后的周期累计用量
+    pub used: Decimal,
+}
+
+impl Deduction {
+    pub fn is_overdrawn(&self) -> bool {
+        self.balance.is_sign_negative() && !self.balance.is_zero()
+    }
+}
+
+impl TariffTable {
+    /// 单一价格，price 必须大于 0
+    pub fn flat(price: Decimal) -> ProtocolResult<Self> {
+        Self::tiered(alloc::vec![TariffTier { upper: None, price }])
+    }
+
+    /// 阶梯价格，tiers 按上限升序，最后一档不封顶；每档价格必须大于 0
+    pub fn tiered(tiers: Vec<TariffTier>) -> ProtocolResult<Self> {
+        let invalid = |msg: &str| Err(ProtocolError::ValidationFailed(format!("tariff {}", msg)));
+        let Some(last) = tiers.last() else {
+            return invalid("requires at least 1 tier");
+        };
+        if last.upper.is_some() {
+            return invalid("last tier must be unlimited");
+        }
+        let mut lower = Decimal::ZERO;
+        for tier in &tiers[..tiers.len() - 1] {
+            match tier.upper {
+                Some(upper) if upper > lower => lower = upper,
+                Some(_) => return invalid("tier uppers must be ascending and positive"),
+                None => return invalid("only the last tier may be unlimited"),
+            }
+        }
+        // 价格为 0 时 volume_for 无法换算用量
+        if tiers.iter().any(|t| t.price <= Decimal::ZERO) {
+            return invalid("price must be positive");
+        }
+        Ok(Self {
+            tiers,
+            scale: 2,
+            rounding: DecimalRoundingMode::HalfUp,
+            rounding_point: RoundingPoint::Total,
+        })
+    }
+
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_rounding(mut self, rounding: DecimalRoundingMode, point: RoundingPoint) -> Self {
+        self.rounding = rounding;
+        self.rounding_point = point;
+        self
+    }
+
+    pub fn tiers(&self) -> &[TariffTier] {
+        &self.tiers
+    }
+
+    /// 累计用量为 used 时下一单位用量的价格，即需要下发给表端的当前单价
+    pub fn price_at(&self, used: Decimal) -> Decimal {
+        self.tiers
+            .iter()
+            .find(|t| t.upper.is_none_or(|upper| used < upper))
+            .map(|t| t.price)
+            .unwrap_or_default()
+    }
+
+    /// 周期内已用 used 时，再用 volume 的费用
+    pub fn cost(&self, used: Decimal, volume: Decimal) -> ProtocolResult<Decimal> {
+        if volume.is_sign_negative() || used.is_sign_negative() {
+            return Err(ProtocolError::ValidationFailed(format!(
+                "tariff usage must not be negative: used {}, volume {}",
+                used, volume
+            )));
+        }
+        let end = used.checked_add(volume).ok_or_else(overflow)?;
+        let mut lower = Decimal::ZERO;
+        let mut total = Decimal::ZERO;
+        for tier in &self.tiers {
+            let upper = tier.upper.unwrap_or(end.max(lower));
+            let start = used.max(lower);
+            let stop = end.min(upper);
+            if stop > start {
+                let part = (stop - start)
+                    .checked_mul(tier.price)
+                    .ok_or_else(overflow)?;
+                let part = match self.rounding_point {
+                    RoundingPoint::PerTier => self.round(part),
+                    RoundingPoint::Total => part,
+                };
+                total = total.checked_add(part).ok_or_else(overflow)?;
+            }
+            lower = upper;
+        }
+        Ok(self.round(total))
+    }
+
+    /// 周期内已用 used 时，amount 可购买的用量 (预付费表按量充值)。用量向下舍入，不多给
+    pub fn volume_for(
+        &self,
+        used: Decimal,
+        amount: Decimal,
+        volume_scale: u32,
+    ) -> ProtocolResult<Decimal> {
+        let mut remaining = amount;
+        let mut lower = Decimal::ZERO;
+        let mut volume = Decimal::ZERO;
+        for tier in &self.tiers {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+            let start = used.max(lower);
+            let capacity = tier.upper.map(|upper| (upper - start).max(Decimal::ZERO));
+            let affordable = remaining.checked_div(tier.price).ok_or_else(|| {
+                ProtocolError::ValidationFailed(format!(
+                    "tariff price {} cannot convert amount to volume",
+                    tier.price
+                ))
+            })?;
+            let take = capacity.map_or(affordable, |c| affordable.min(c));
+            volume = volume.checked_add(take).ok_or_else(overflow)?;
+            let spent = take.checked_mul(tier.price).ok_or_else(overflow)?;
+            remaining = remaining.checked_sub(spent).ok_or_else(overflow)?;
+            if let Some(upper) = tier.upper {
+                lower = upper;
+            }
+        }
+        Ok(volume.round_dp_with_strategy(volume_scale, RoundingStrategy::ToZero))
+    }
+
+    /// 按用量从余额中扣费
+    pub fn deduct(
+        &self,
+        balance: Decimal,
+        used: Decimal,
+        volume: Decimal,
+    ) -> ProtocolResult<Deduction> {
+        let cost = self.cost(used, volume)?;
+        Ok(Deduction {
+            cost,
+            balance: balance.checked_sub(cost).ok_or_else(overflow)?,
+            used: used.checked_add(volume).ok_or_else(overflow)?,
+        })
+    }
+
+    fn round(&self, value: Decimal) -> Decimal {
+        value.round_dp_with_strategy(self.scale, self.rounding.to_strategy())
+    }
+}
+
+fn overflow() -> ProtocolError {
+    ProtocolError::CommonError("tariff amount overflow".into())
+}
+
+/// 金额转换为最小单位的整数 (scale 为 2 时即为分)，用于写入下行帧
+pub fn to_minor_units(amount: Decimal, scale: u32) -> ProtocolResult<i64> {
+    10i64
+        .checked_pow(scale)
+        .and_then(|factor| amount.checked_mul(Decimal::from(factor)))
+        .map(|v| v.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero))
+        .and_then(|v| v.to_i64())
+        .ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!(
+                "amount {} does not fit in minor units (scale {})",
+                amount, scale
+            ))
+        })
+}
+
+/// to_minor_units 的逆运算，用于解析上行帧中的余额。scale 超过 28 时报错
+pub fn from_minor_units(units: i64, scale: u32) -> ProtocolResult<Decimal> {
+    Decimal::try_new(units, scale).map_err(|e| {
+        ProtocolError::ValidationFailed(format!(
+            "minor units {} with scale {}: {}",
+            units, scale, e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    // 居民阶梯气价: 0-300 方 2.5 元，300-600 方 3.0 元，600 方以上 3.75 元
+    fn gas() -> TariffTable {
+        TariffTable::tiered(alloc::vec![
+            TariffTier {
+                upper: Some(dec!(300)),
+                price: dec!(2.5),
+            },
+            TariffTier {
+                upper: Some(dec!(600)),
+                price: dec!(3.0),
+            },
+            TariffTier {
+                upper: None,
+                price: dec!(3.75),
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tiered_cost() {
+        let tariff = gas();
+        assert_eq!(tariff.cost(dec!(0), dec!(100)).unwrap(), dec!(250));
+        // 跨两档: 20 * 2.5 + 30 * 3.0
+        assert_eq!(tariff.cost(dec!(280), dec!(50)).unwrap(), dec!(140));
+        // 跨三档
+        assert_eq!(tariff.cost(dec!(250), dec!(400)).unwrap(), dec!(1212.5));
+        assert_eq!(tariff.price_at(dec!(299.9)), dec!(2.5));
+        assert_eq!(tariff.price_at(dec!(600)), dec!(3.75));
+
+        let deduction = tariff.deduct(dec!(100), dec!(280), dec!(50)).unwrap();
+        assert_eq!(deduction.balance, dec!(-40));
+        assert_eq!(deduction.used, dec!(330));
+        assert!(deduction.is_overdrawn());
+
+        assert_eq!(
+            tariff.volume_for(dec!(280), dec!(140), 2).unwrap(),
+            dec!(50)
+        );
+        assert!(TariffTable::tiered(alloc::vec![]).is_err());
+        assert!(tariff.cost(dec!(0), dec!(-1)).is_err());
+    }
+
+    #[test]
+    fn test_rounding() {
+        let tariff = TariffTable::tiered(alloc::vec![
+            TariffTier {
+                upper: Some(dec!(1)),
+                price: dec!(1.005),
+            },
+            TariffTier {
+                upper: None,
+                price: dec!(1.005),
+            },
+        ])
+        .unwrap();
+        assert_eq!(tariff.cost(dec!(0), dec!(2)).unwrap(), dec!(2.01));
+        let per_tier = tariff.with_rounding(DecimalRoundingMode::HalfUp, RoundingPoint::PerTier);
+        assert_eq!(per_tier.cost(dec!(0), dec!(2)).unwrap(), dec!(2.02));
+        let down = per_tier.with_rounding(DecimalRoundingMode::Down, RoundingPoint::Total);
+        assert_eq!(down.cost(dec!(0), dec!(2)).unwrap(), dec!(2.01));
+
+        assert_eq!(to_minor_units(dec!(12.345), 2).unwrap(), 1235);
+        assert_eq!(from_minor_units(1235, 2).unwrap(), dec!(12.35));
+        assert!(from_minor_units(1235, 29).is_err());
+        assert!(to_minor_units(Decimal::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn test_invalid_price_and_overflow() {
+        let tier = |price| TariffTier { upper: None, price };
+        for price in [dec!(0), dec!(-1)] {
+            assert!(matches!(
+                TariffTable::flat(price),
+                Err(ProtocolError::ValidationFailed(_))
+            ));
+            assert!(matches!(
+                TariffTable::tiered(alloc::vec![tier(price)]),
+                Err(ProtocolError::ValidationFailed(_))
+            ));
+        }
+
+        let flat = TariffTable::flat(dec!(2.5)).unwrap();
+        assert_eq!(flat.volume_for(dec!(0), dec!(10), 2).unwrap(), dec!(4));
+        assert_eq!(flat.volume_for(dec!(0), dec!(0), 2).unwrap(), dec!(0));
+        // 用量 * 价格溢出
+        assert!(flat.cost(dec!(0), Decimal::MAX).is_err());
+        assert!(flat.cost(Decimal::MAX, dec!(1)).is_err());
+        // 余额扣减溢出
+        assert!(flat.deduct(Decimal::MIN, dec!(0), dec!(1)).is_err());
+        assert!(flat.deduct(dec!(0), Decimal::MAX, dec!(1)).is_err());
+        // 极小价格换算用量溢出
+        let cheap = TariffTable::flat(dec!(0.0000000000000000000000000001)).unwrap();
+        assert!(cheap.volume_for(dec!(0), Decimal::MAX, 2).is_err());
+    }
+}
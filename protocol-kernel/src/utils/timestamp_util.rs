@@ -1,4 +1,11 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
 use chrono::Local;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
 
 use crate::utils::hex_util;
 use protocol_base::{
@@ -7,6 +14,7 @@ use protocol_base::{
 };
 
 /// 定义了 BCD 时间戳的格式化类型
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TimestampType {
     Year,                   //yyyy
     YearMonth,              //yyyy-MM
@@ -24,6 +32,78 @@ pub enum TimestampType {
 
 const YEAR_PREFIX: &str = "20";
 
+/// 2 位年份 (yy) 的世纪处理方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Century {
+    /// 固定世纪。Fixed(20) -> 20yy (默认)，Fixed(19) -> 19yy，Fixed(21) -> 21yy
+    Fixed(u32),
+    /// 滑动窗口：yy >= pivot 时为 19yy，否则为 20yy。例如 Pivot(70): 69 -> 2069, 70 -> 1970
+    Pivot(u8),
+}
+
+impl Default for Century {
+    fn default() -> Self {
+        Century::Fixed(20)
+    }
+}
+
+impl Century {
+    /// 2 位年份 -> 4 位年份
+    pub fn expand(&self, yy: u32) -> u32 {
+        match self {
+            Century::Fixed(century) => century * 100 + yy,
+            Century::Pivot(pivot) => {
+                if yy >= *pivot as u32 {
+                    1900 + yy
+                } else {
+                    2000 + yy
+                }
+            }
+        }
+    }
+}
+
+/// BCD 时间所在的时区
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceTimeZone {
+    /// 服务器本地时区 (需要 std)
+    #[cfg(feature = "std")]
+    Local,
+    /// UTC
+    Utc,
+    /// 固定偏移 (东区为正，单位: 秒)。例如东八区 = 8 * 3600
+    Offset(i32),
+}
+
+impl DeviceTimeZone {
+    /// 东八区 (北京时间)，表端最常见的时区
+    pub fn beijing() -> Self {
+        DeviceTimeZone::Offset(8 * 3600)
+    }
+
+    // naive 只在 Local 时区下用到
+    #[cfg_attr(not(feature = "std"), allow(unused_variables))]
+    fn fixed_offset(&self, naive: &NaiveDateTime) -> ProtocolResult<FixedOffset> {
+        match self {
+            DeviceTimeZone::Utc => Ok(FixedOffset::east_opt(0).unwrap()),
+            DeviceTimeZone::Offset(secs) => FixedOffset::east_opt(*secs).ok_or_else(|| {
+                ProtocolError::ValidationFailed(format!("Invalid UTC offset: {} seconds", secs))
+            }),
+            #[cfg(feature = "std")]
+            DeviceTimeZone::Local => Local
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|dt| *dt.offset())
+                .ok_or_else(|| {
+                    ProtocolError::ValidationFailed(format!(
+                        "Local time {} does not exist in the server time zone",
+                        naive
+                    ))
+                }),
+        }
+    }
+}
+
 /// 核心转换函数：将 BCD 字节切片按指定格式转换为日期字符串
 ///
 /// # Arguments
@@ -42,12 +122,15 @@ pub fn convert(bcd_bytes: &[u8], timestamp_type: TimestampType) -> ProtocolResul
         return Err(ProtocolError::HexError(HexError::NotBcd(bcd_str)));
     }
 
-    // 3. 规范化：如果 BCD 字符串以 "20" 开头 (例如 "20230515")，
+    // 3. 规范化：如果 BCD 字符串带 4 位年份 (例如 "20230515")，
     //    则将其剥离为 "230515"，以便后续函数统一处理 "yy" 格式。
-    //
-    let ts = match bcd_str.starts_with(YEAR_PREFIX) {
-        true => &bcd_str[YEAR_PREFIX.len()..],
-        false => &bcd_str,
+    //    只看开头是否为 "20" 会误伤 2 位年份的 2020 年 ("200515") 和 20 点 ("203015")
+    let ts = if bcd_str.len() >= _short_year_digit_count(timestamp_type) + YEAR_PREFIX.len()
+        && bcd_str.starts_with(YEAR_PREFIX)
+    {
+        &bcd_str[YEAR_PREFIX.len()..]
+    } else {
+        &bcd_str
     };
 
     // 4. 根据类型分派给辅助函数
@@ -72,6 +155,7 @@ pub fn convert(bcd_bytes: &[u8], timestamp_type: TimestampType) -> ProtocolResul
 
 // --- 公共 API 别名 ---
 
+#[cfg(feature = "std")]
 pub fn now_to_timestamp(timestamp_type: TimestampType) -> ProtocolResult<String> {
     // 2. 获取当前本地时间
     let now = Local::now();
@@ -97,6 +181,414 @@ pub fn now_to_timestamp(timestamp_type: TimestampType) -> ProtocolResult<String>
     Ok(now.format(format_string).to_string())
 }
 
+/// 反向编码：把日期时间按指定格式编码为 BCD 字节，用于下行帧写时间字段
+///
+/// 报文里的格式与 `convert` 的输入一致：除 `YyyyMmDd*` 外年份都只占 2 位 (yy)。
+/// 例如 `YearMonthDayHourMinSec` + 2023-05-15 10:20:30 -> [0x23, 0x05, 0x15, 0x10, 0x20, 0x30]
+///
+/// # Arguments
+/// * `datetime` - 任意实现了 Datelike + Timelike 的时间 (NaiveDateTime, DateTime<Local> 等)
+/// * `timestamp_type` - 期望的时间戳格式
+/// * `swap` - 是否翻转字节 (小端)
+pub fn encode<T: Datelike + Timelike>(
+    datetime: &T,
+    timestamp_type: TimestampType,
+    swap: bool,
+) -> ProtocolResult<Vec<u8>> {
+    let yyyy = format!("{:04}", datetime.year());
+    let yy = &yyyy[yyyy.len() - 2..];
+    let mm = format!("{:02}", datetime.month());
+    let dd = format!("{:02}", datetime.day());
+    let hh = format!("{:02}", datetime.hour());
+    let mi = format!("{:02}", datetime.minute());
+    let ss = format!("{:02}", datetime.second());
+
+    let digits = match timestamp_type {
+        TimestampType::Year => yy.to_string(),
+        TimestampType::YearMonth => format!("{}{}", yy, mm),
+        TimestampType::YearMonthDay | TimestampType::YyMmDd => format!("{}{}{}", yy, mm, dd),
+        TimestampType::YearMonthDayHour => format!("{}{}{}{}", yy, mm, dd, hh),
+        TimestampType::YearMonthDayHourMin => format!("{}{}{}{}{}", yy, mm, dd, hh, mi),
+        TimestampType::YearMonthDayHourMinSec | TimestampType::YyMmDdHHmmss => {
+            format!("{}{}{}{}{}{}", yy, mm, dd, hh, mi, ss)
+        }
+        TimestampType::HourMinSec | TimestampType::HHmmss => format!("{}{}{}", hh, mi, ss),
+        TimestampType::YyyyMmDdHHmmss => format!("{}{}{}{}{}{}", yyyy, mm, dd, hh, mi, ss),
+        TimestampType::YyyyMmDd => format!("{}{}{}", yyyy, mm, dd),
+    };
+    _digits_to_bcd(&digits, swap)
+}
+
+/// 反向编码：把时间字符串编码为 BCD 字节
+///
+/// 字符串中的分隔符 (`-`, `:`, 空格, `/`, `T` 等) 会被忽略，只保留数字，
+/// 因此 "2023-05-15 10:20:30"、"20230515102030"、"230515102030" 都可以作为
+/// `YearMonthDayHourMinSec` 的输入。需要 2 位年份的格式传入 4 位年份时会自动去掉世纪。
+pub fn encode_str(
+    timestamp: &str,
+    timestamp_type: TimestampType,
+    swap: bool,
+) -> ProtocolResult<Vec<u8>> {
+    let digits: String = timestamp
+        .chars()
+        .filter(|c| !matches!(c, '-' | ':' | ' ' | '/' | 'T' | '.'))
+        .collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(ProtocolError::HexError(HexError::NotBcd(timestamp.into())));
+    }
+
+    let expected = _bcd_digit_count(timestamp_type);
+    let has_short_year = !matches!(
+        timestamp_type,
+        TimestampType::YyyyMmDdHHmmss
+            | TimestampType::YyyyMmDd
+            | TimestampType::HourMinSec
+            | TimestampType::HHmmss
+    );
+    let digits = if digits.len() == expected {
+        digits.as_str()
+    } else if has_short_year && digits.len() == expected + 2 {
+        // 4位年份 -> 2位年份
+        &digits[2..]
+    } else {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(format!(
+            "timestamp '{}' does not match {:?}: expected {} digits, got {}",
+            timestamp,
+            timestamp_type,
+            expected,
+            digits.len()
+        ))));
+    };
+    _digits_to_bcd(digits, swap)
+}
+
+/// 当前本地时间编码为 BCD 字节
+#[cfg(feature = "std")]
+pub fn now_to_bcd_bytes(timestamp_type: TimestampType) -> ProtocolResult<Vec<u8>> {
+    encode(&Local::now(), timestamp_type, false)
+}
+
+// 各格式在报文中的 BCD 数字位数
+fn _bcd_digit_count(timestamp_type: TimestampType) -> usize {
+    match timestamp_type {
+        TimestampType::Year => 2,
+        TimestampType::YearMonth => 4,
+        TimestampType::YearMonthDay | TimestampType::YyMmDd => 6,
+        TimestampType::YearMonthDayHour | TimestampType::YyyyMmDd => 8,
+        TimestampType::YearMonthDayHourMin => 10,
+        TimestampType::YearMonthDayHourMinSec | TimestampType::YyMmDdHHmmss => 12,
+        TimestampType::HourMinSec | TimestampType::HHmmss => 6,
+        TimestampType::YyyyMmDdHHmmss => 14,
+    }
+}
+
+// 各格式按 2 位年份计的 BCD 数字位数，不含年份的格式不会带世纪前缀
+fn _short_year_digit_count(timestamp_type: TimestampType) -> usize {
+    match timestamp_type {
+        TimestampType::YyyyMmDd => 6,
+        TimestampType::YyyyMmDdHHmmss => 12,
+        TimestampType::HourMinSec | TimestampType::HHmmss => usize::MAX - YEAR_PREFIX.len(),
+        other => _bcd_digit_count(other),
+    }
+}
+
+fn _digits_to_bcd(digits: &str, swap: bool) -> ProtocolResult<Vec<u8>> {
+    if swap {
+        hex_util::hex_to_bytes_swap(digits)
+    } else {
+        hex_util::hex_to_bytes(digits)
+    }
+}
+
+/// 严格模式的 `convert`：渲染之后再校验年月日时分秒是否在合法范围内
+/// (例如拒绝 "2099-13-45 27:61:61")，长度不足时也会返回错误而不是原样返回。
+///
+/// # Errors
+/// * `HexError::InvalidTimestamp` - 时间字段超出范围或长度不足
+pub fn convert_strict(bcd_bytes: &[u8], timestamp_type: TimestampType) -> ProtocolResult<String> {
+    let rendered = convert(bcd_bytes, timestamp_type)?;
+    validate(&rendered, timestamp_type)?;
+    Ok(rendered)
+}
+
+// 时间字段
+#[derive(Clone, Copy)]
+enum TimePart {
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+/// 校验一个按 TimestampType 渲染出来的时间字符串 (`convert` 的输出)。
+pub fn validate(rendered: &str, timestamp_type: TimestampType) -> ProtocolResult<()> {
+    use TimePart::*;
+    let invalid = |reason: String| {
+        ProtocolError::HexError(HexError::InvalidTimestamp {
+            value: rendered.to_string(),
+            reason,
+        })
+    };
+    // (年份位数, 之后依次出现的2位字段)
+    let (year_len, parts): (usize, &[TimePart]) = match timestamp_type {
+        TimestampType::Year => (4, &[]),
+        TimestampType::YearMonth => (4, &[Month]),
+        TimestampType::YearMonthDay | TimestampType::YyyyMmDd => (4, &[Month, Day]),
+        TimestampType::YearMonthDayHour => (4, &[Month, Day, Hour]),
+        TimestampType::YearMonthDayHourMin => (4, &[Month, Day, Hour, Minute]),
+        TimestampType::YearMonthDayHourMinSec | TimestampType::YyyyMmDdHHmmss => {
+            (4, &[Month, Day, Hour, Minute, Second])
+        }
+        TimestampType::HourMinSec | TimestampType::HHmmss => (0, &[Hour, Minute, Second]),
+        TimestampType::YyMmDdHHmmss => (2, &[Month, Day, Hour, Minute, Second]),
+        TimestampType::YyMmDd => (2, &[Month, Day]),
+    };
+
+    let digits: String = rendered
+        .chars()
+        .filter(|c| !matches!(c, '-' | ':' | ' '))
+        .collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid("contains non-digit characters".into()));
+    }
+    let expected = year_len + parts.len() * 2;
+    if digits.len() != expected {
+        return Err(invalid(format!(
+            "expected {} digits for {:?}, got {}",
+            expected,
+            timestamp_type,
+            digits.len()
+        )));
+    }
+
+    let year: i32 = match year_len {
+        0 => 2000,
+        2 => 2000 + digits[0..2].parse::<i32>().unwrap_or_default(),
+        _ => digits[0..4].parse().unwrap_or_default(),
+    };
+    let (mut month, mut day) = (1u32, 1u32);
+    for (i, part) in parts.iter().enumerate() {
+        let start = year_len + i * 2;
+        let value: u32 = digits[start..start + 2].parse().unwrap_or_default();
+        let (name, ok) = match part {
+            Month => {
+                month = value;
+                ("month", (1..=12).contains(&value))
+            }
+            Day => {
+                day = value;
+                ("day", (1..=31).contains(&value))
+            }
+            Hour => ("hour", value < 24),
+            Minute => ("minute", value < 60),
+            Second => ("second", value < 60),
+        };
+        if !ok {
+            return Err(invalid(format!("{} {} is out of range", name, value)));
+        }
+    }
+    if NaiveDate::from_ymd_opt(year, month, day).is_none() {
+        return Err(invalid(format!(
+            "day {} does not exist in {:04}-{:02}",
+            day, year, month
+        )));
+    }
+    Ok(())
+}
+
+/// 带世纪处理的 `convert`。2 位年份按 `century` 展开，报文里本身带 4 位年份时原样使用，
+/// 不会像 `convert` 那样把 "20" 开头误认为世纪前缀。
+pub fn convert_with_century(
+    bcd_bytes: &[u8],
+    timestamp_type: TimestampType,
+    century: Century,
+) -> ProtocolResult<String> {
+    let digits = _expand_year(bcd_bytes, timestamp_type, century)?;
+    Ok(_render(&digits, timestamp_type))
+}
+
+/// BCD 时间 -> NaiveDateTime (不含时区)。格式本身不含的月/日补 1，时/分/秒补 0；
+/// 字节数与格式不符时返回 `HexError::InvalidInput`
+pub fn to_naive_datetime(
+    bcd_bytes: &[u8],
+    timestamp_type: TimestampType,
+    century: Century,
+) -> ProtocolResult<NaiveDateTime> {
+    if matches!(
+        timestamp_type,
+        TimestampType::HourMinSec | TimestampType::HHmmss
+    ) {
+        return Err(ProtocolError::ValidationFailed(format!(
+            "{:?} has no date part and cannot be converted to a date time",
+            timestamp_type
+        )));
+    }
+    let digits = _expand_year(bcd_bytes, timestamp_type, century)?;
+    // digits 形如 yyyy[MM[dd[HH[mm[ss]]]]]
+    let part = |start: usize, default: u32| -> u32 {
+        digits
+            .get(start..start + 2)
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(default)
+    };
+    let year: i32 = digits[0..4].parse().unwrap_or_default();
+    let invalid = || {
+        ProtocolError::ValidationFailed(format!(
+            "BCD {} is not a valid {:?} timestamp",
+            hex::encode_upper(bcd_bytes),
+            timestamp_type
+        ))
+    };
+    NaiveDate::from_ymd_opt(year, part(4, 1), part(6, 1))
+        .and_then(|d| d.and_hms_opt(part(8, 0), part(10, 0), part(12, 0)))
+        .ok_or_else(invalid)
+}
+
+/// BCD 时间 -> 带时区的 DateTime
+pub fn to_datetime(
+    bcd_bytes: &[u8],
+    timestamp_type: TimestampType,
+    century: Century,
+    tz: DeviceTimeZone,
+) -> ProtocolResult<DateTime<FixedOffset>> {
+    let naive = to_naive_datetime(bcd_bytes, timestamp_type, century)?;
+    let offset = tz.fixed_offset(&naive)?;
+    offset
+        .from_local_datetime(&naive)
+        .single()
+        .ok_or_else(|| ProtocolError::ValidationFailed(format!("Ambiguous time {}", naive)))
+}
+
+/// BCD 时间 -> Unix 时间戳 (秒)
+pub fn to_epoch_seconds(
+    bcd_bytes: &[u8],
+    timestamp_type: TimestampType,
+    century: Century,
+    tz: DeviceTimeZone,
+) -> ProtocolResult<i64> {
+    Ok(to_datetime(bcd_bytes, timestamp_type, century, tz)?.timestamp())
+}
+
+/// BCD 时间 -> Unix 时间戳 (毫秒)
+pub fn to_epoch_millis(
+    bcd_bytes: &[u8],
+    timestamp_type: TimestampType,
+    century: Century,
+    tz: DeviceTimeZone,
+) -> ProtocolResult<i64> {
+    Ok(to_datetime(bcd_bytes, timestamp_type, century, tz)?.timestamp_millis())
+}
+
+/// Unix 时间戳 (秒) -> 指定时区下的 BCD 时间
+pub fn epoch_seconds_to_bcd(
+    epoch_seconds: i64,
+    timestamp_type: TimestampType,
+    tz: DeviceTimeZone,
+    swap: bool,
+) -> ProtocolResult<Vec<u8>> {
+    epoch_millis_to_bcd(epoch_seconds.saturating_mul(1000), timestamp_type, tz, swap)
+}
+
+/// Unix 时间戳 (毫秒) -> 指定时区下的 BCD 时间
+pub fn epoch_millis_to_bcd(
+    epoch_millis: i64,
+    timestamp_type: TimestampType,
+    tz: DeviceTimeZone,
+    swap: bool,
+) -> ProtocolResult<Vec<u8>> {
+    let utc = Utc
+        .timestamp_millis_opt(epoch_millis)
+        .single()
+        .ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!("Invalid epoch millis: {}", epoch_millis))
+        })?;
+    match tz {
+        #[cfg(feature = "std")]
+        DeviceTimeZone::Local => encode(&utc.with_timezone(&Local), timestamp_type, swap),
+        DeviceTimeZone::Utc => encode(&utc, timestamp_type, swap),
+        DeviceTimeZone::Offset(_) => {
+            let offset = tz.fixed_offset(&utc.naive_utc())?;
+            encode(&utc.with_timezone(&offset), timestamp_type, swap)
+        }
+    }
+}
+
+/// (内部) 校验 BCD，并把 2 位年份展开为 4 位。返回的数字串对日期类型总是以 yyyy 开头
+fn _expand_year(
+    bcd_bytes: &[u8],
+    timestamp_type: TimestampType,
+    century: Century,
+) -> ProtocolResult<String> {
+    let bcd_str = hex_util::bytes_to_hex(bcd_bytes)?;
+    if !hex_util::is_bcd(&bcd_str) {
+        return Err(ProtocolError::HexError(HexError::NotBcd(bcd_str)));
+    }
+    let expected = _bcd_digit_count(timestamp_type);
+    // 不含年份或本身就是 4 位年份的格式只接受精确位数；其余格式接受 2 位年份或自带 4 位年份两种长度
+    let exact_only = matches!(
+        timestamp_type,
+        TimestampType::HourMinSec
+            | TimestampType::HHmmss
+            | TimestampType::YyyyMmDd
+            | TimestampType::YyyyMmDdHHmmss
+    );
+    if bcd_str.len() == expected + 2 && !exact_only {
+        // 报文本身带了 4 位年份
+        return Ok(bcd_str);
+    }
+    if bcd_str.len() != expected {
+        return Err(ProtocolError::HexError(HexError::InvalidInput(format!(
+            "expected {} BCD digits for {:?}, got {} ({})",
+            expected,
+            timestamp_type,
+            bcd_str.len(),
+            bcd_str
+        ))));
+    }
+    if exact_only {
+        return Ok(bcd_str);
+    }
+    let yy: u32 = bcd_str[0..2].parse().unwrap_or_default();
+    Ok(format!("{:04}{}", century.expand(yy), &bcd_str[2..]))
+}
+
+/// (内部) 把 4 位年份的数字串渲染成 TimestampType 对应的显示格式
+fn _render(digits: &str, timestamp_type: TimestampType) -> String {
+    let p = |start: usize, end: usize| digits.get(start..end).unwrap_or("");
+    match timestamp_type {
+        TimestampType::Year => p(0, 4).to_string(),
+        TimestampType::YearMonth => format!("{}-{}", p(0, 4), p(4, 6)),
+        TimestampType::YearMonthDay => format!("{}-{}-{}", p(0, 4), p(4, 6), p(6, 8)),
+        TimestampType::YearMonthDayHour => {
+            format!("{}-{}-{} {}", p(0, 4), p(4, 6), p(6, 8), p(8, 10))
+        }
+        TimestampType::YearMonthDayHourMin => format!(
+            "{}-{}-{} {}:{}",
+            p(0, 4),
+            p(4, 6),
+            p(6, 8),
+            p(8, 10),
+            p(10, 12)
+        ),
+        TimestampType::YearMonthDayHourMinSec => format!(
+            "{}-{}-{} {}:{}:{}",
+            p(0, 4),
+            p(4, 6),
+            p(6, 8),
+            p(8, 10),
+            p(10, 12),
+            p(12, 14)
+        ),
+        TimestampType::HourMinSec => format!("{}:{}:{}", p(0, 2), p(2, 4), p(4, 6)),
+        TimestampType::YyyyMmDdHHmmss => p(0, 14).to_string(),
+        TimestampType::YyyyMmDd => p(0, 8).to_string(),
+        TimestampType::HHmmss => p(0, 6).to_string(),
+        TimestampType::YyMmDdHHmmss => p(2, 14).to_string(),
+        TimestampType::YyMmDd => p(2, 8).to_string(),
+    }
+}
+
 pub fn to_year(bcd_bytes: &[u8]) -> ProtocolResult<String> {
     convert(bcd_bytes, TimestampType::Year)
 }
@@ -271,3 +763,376 @@ fn convert_to_hour_min_sec(timestamp: &str) -> String {
         timestamp.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_year_2020_and_hour_20() {
+        let ymd = convert(&[0x20, 0x05, 0x15], TimestampType::YearMonthDay).unwrap();
+        assert_eq!(ymd, "2020-05-15");
+        let ymd = convert(&[0x20, 0x23, 0x05, 0x15], TimestampType::YearMonthDay).unwrap();
+        assert_eq!(ymd, "2023-05-15");
+        let hms = convert(&[0x20, 0x30, 0x15], TimestampType::HourMinSec).unwrap();
+        assert_eq!(hms, "20:30:15");
+    }
+
+    // 2023-05-15 10:20:30 在各格式下的报文字节与 convert 的输出
+    fn samples() -> Vec<(TimestampType, Vec<u8>, &'static str)> {
+        use TimestampType::*;
+        alloc::vec![
+            (Year, alloc::vec![0x23], "2023"),
+            (YearMonth, alloc::vec![0x23, 0x05], "2023-05"),
+            (YearMonthDay, alloc::vec![0x23, 0x05, 0x15], "2023-05-15"),
+            (
+                YearMonthDayHour,
+                alloc::vec![0x23, 0x05, 0x15, 0x10],
+                "2023-05-15 10"
+            ),
+            (
+                YearMonthDayHourMin,
+                alloc::vec![0x23, 0x05, 0x15, 0x10, 0x20],
+                "2023-05-15 10:20"
+            ),
+            (
+                YearMonthDayHourMinSec,
+                alloc::vec![0x23, 0x05, 0x15, 0x10, 0x20, 0x30],
+                "2023-05-15 10:20:30"
+            ),
+            (HourMinSec, alloc::vec![0x10, 0x20, 0x30], "10:20:30"),
+            (
+                YyyyMmDdHHmmss,
+                alloc::vec![0x20, 0x23, 0x05, 0x15, 0x10, 0x20, 0x30],
+                "20230515102030"
+            ),
+            (YyyyMmDd, alloc::vec![0x20, 0x23, 0x05, 0x15], "20230515"),
+            (HHmmss, alloc::vec![0x10, 0x20, 0x30], "102030"),
+            (
+                YyMmDdHHmmss,
+                alloc::vec![0x23, 0x05, 0x15, 0x10, 0x20, 0x30],
+                "230515102030"
+            ),
+            (YyMmDd, alloc::vec![0x23, 0x05, 0x15], "230515"),
+        ]
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        let datetime = NaiveDate::from_ymd_opt(2023, 5, 15)
+            .unwrap()
+            .and_hms_opt(10, 20, 30)
+            .unwrap();
+        for (timestamp_type, bytes, rendered) in samples() {
+            assert_eq!(convert(&bytes, timestamp_type).unwrap(), rendered);
+            assert_eq!(
+                encode_str(rendered, timestamp_type, false).unwrap(),
+                bytes,
+                "{:?}",
+                timestamp_type
+            );
+            assert_eq!(
+                encode(&datetime, timestamp_type, false).unwrap(),
+                bytes,
+                "{:?}",
+                timestamp_type
+            );
+            let mut swapped = bytes.clone();
+            swapped.reverse();
+            assert_eq!(encode(&datetime, timestamp_type, true).unwrap(), swapped);
+        }
+        // 分隔符与 4 位年份都可以省略
+        for input in ["2023-05-15 10:20:30", "2023/05/15T10:20:30", "230515102030"] {
+            assert_eq!(
+                encode_str(input, TimestampType::YearMonthDayHourMinSec, false).unwrap(),
+                [0x23, 0x05, 0x15, 0x10, 0x20, 0x30]
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_malformed() {
+        let not_bcd = encode_str("2023-05-1x", TimestampType::YearMonthDay, false);
+        assert!(matches!(
+            not_bcd,
+            Err(ProtocolError::HexError(HexError::NotBcd(_)))
+        ));
+        // 位数不符
+        for (input, timestamp_type) in [
+            ("2023-5-15", TimestampType::YearMonthDay),
+            ("", TimestampType::HourMinSec),
+            ("10:20:30:40", TimestampType::HourMinSec),
+            // 4 位年份的格式不接受 2 位年份
+            ("230515", TimestampType::YyyyMmDd),
+        ] {
+            assert!(
+                matches!(
+                    encode_str(input, timestamp_type, false),
+                    Err(ProtocolError::HexError(HexError::InvalidInput(_)))
+                ),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_now_to_bcd_bytes() {
+        let bytes = now_to_bcd_bytes(TimestampType::YyyyMmDdHHmmss).unwrap();
+        assert_eq!(bytes.len(), 7);
+        assert!(convert_strict(&bytes, TimestampType::YyyyMmDdHHmmss).is_ok());
+        assert_eq!(now_to_bcd_bytes(TimestampType::HHmmss).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_convert_keeps_20_prefix_behaviour() {
+        // 已有调用方: 2 位年份固定补 "20"，报文自带 4 位 20xx 年份时去掉重复的世纪
+        let cases: [(&[u8], TimestampType, &str); 6] = [
+            (
+                &[0x99, 0x12, 0x31],
+                TimestampType::YearMonthDay,
+                "2099-12-31",
+            ),
+            (
+                &[0x20, 0x23, 0x05, 0x15],
+                TimestampType::YearMonthDay,
+                "2023-05-15",
+            ),
+            (&[0x20, 0x23], TimestampType::Year, "2023"),
+            (
+                &[0x20, 0x23, 0x05, 0x15, 0x10, 0x20, 0x30],
+                TimestampType::YearMonthDayHourMinSec,
+                "2023-05-15 10:20:30",
+            ),
+            (
+                &[0x20, 0x23, 0x05, 0x15, 0x10, 0x20, 0x30],
+                TimestampType::YyyyMmDdHHmmss,
+                "20230515102030",
+            ),
+            (&[0x20, 0x59, 0x59], TimestampType::HHmmss, "205959"),
+        ];
+        for (bytes, timestamp_type, expected) in cases {
+            assert_eq!(convert(bytes, timestamp_type).unwrap(), expected);
+        }
+        // 默认世纪与 convert 一致
+        for (timestamp_type, bytes, rendered) in samples() {
+            assert_eq!(
+                convert_with_century(&bytes, timestamp_type, Century::default()).unwrap(),
+                rendered
+            );
+        }
+    }
+
+    #[test]
+    fn test_century() {
+        assert_eq!(Century::Fixed(19).expand(99), 1999);
+        assert_eq!(Century::Fixed(21).expand(1), 2101);
+        assert_eq!(Century::Pivot(70).expand(69), 2069);
+        assert_eq!(Century::Pivot(70).expand(70), 1970);
+
+        let ymd = TimestampType::YearMonthDay;
+        let with = |bytes: &[u8], century| convert_with_century(bytes, ymd, century).unwrap();
+        assert_eq!(with(&[0x99, 0x12, 0x31], Century::Fixed(19)), "1999-12-31");
+        assert_eq!(with(&[0x99, 0x12, 0x31], Century::Fixed(20)), "2099-12-31");
+        assert_eq!(with(&[0x01, 0x01, 0x01], Century::Fixed(21)), "2101-01-01");
+        assert_eq!(with(&[0x85, 0x06, 0x01], Century::Pivot(70)), "1985-06-01");
+        assert_eq!(with(&[0x05, 0x06, 0x01], Century::Pivot(70)), "2005-06-01");
+        // 报文自带 4 位年份时忽略 century
+        assert_eq!(
+            with(&[0x19, 0x99, 0x12, 0x31], Century::Fixed(20)),
+            "1999-12-31"
+        );
+        assert_eq!(
+            with(&[0x21, 0x01, 0x01, 0x01], Century::Fixed(20)),
+            "2101-01-01"
+        );
+
+        let naive = to_naive_datetime(
+            &[0x99, 0x12, 0x31, 0x23, 0x59, 0x58],
+            TimestampType::YearMonthDayHourMinSec,
+            Century::Fixed(19),
+        )
+        .unwrap();
+        assert_eq!(
+            naive,
+            NaiveDate::from_ymd_opt(1999, 12, 31)
+                .unwrap()
+                .and_hms_opt(23, 59, 58)
+                .unwrap()
+        );
+        // 只有年月时日补 1
+        let naive =
+            to_naive_datetime(&[0x01, 0x02], TimestampType::YearMonth, Century::Fixed(21)).unwrap();
+        assert_eq!((naive.year(), naive.month(), naive.day()), (2101, 2, 1));
+        assert!(to_naive_datetime(
+            &[0x10, 0x20, 0x30],
+            TimestampType::HourMinSec,
+            Century::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_truncated_input_rejected() {
+        let is_invalid_input = |r: ProtocolResult<NaiveDateTime>| {
+            matches!(r, Err(ProtocolError::HexError(HexError::InvalidInput(_))))
+        };
+        let century = Century::default();
+        for timestamp_type in [
+            TimestampType::YyyyMmDd,
+            TimestampType::YyyyMmDdHHmmss,
+            TimestampType::YyMmDd,
+            TimestampType::YearMonthDayHourMinSec,
+        ] {
+            for bytes in [&[][..], &[0x20], &[0x25]] {
+                assert!(is_invalid_input(to_naive_datetime(
+                    bytes,
+                    timestamp_type,
+                    century
+                )));
+                assert!(
+                    to_epoch_seconds(bytes, timestamp_type, century, DeviceTimeZone::Utc).is_err()
+                );
+                assert!(
+                    to_epoch_millis(bytes, timestamp_type, century, DeviceTimeZone::Utc).is_err()
+                );
+                assert!(convert_with_century(bytes, timestamp_type, century).is_err());
+            }
+        }
+        // 缺了日的 YyMmDd 不再补默认值
+        assert!(is_invalid_input(to_naive_datetime(
+            &[0x25, 0x01],
+            TimestampType::YyMmDd,
+            century
+        )));
+        // 4 位年份格式多出的字节同样拒绝
+        assert!(is_invalid_input(to_naive_datetime(
+            &[0x20, 0x23, 0x05, 0x15, 0x01],
+            TimestampType::YyyyMmDd,
+            century
+        )));
+    }
+
+    #[test]
+    fn test_device_time_zone() {
+        // 2023-05-15 10:20:30 UTC
+        const UTC_EPOCH: i64 = 1_684_146_030;
+        let bcd = [0x23, 0x05, 0x15, 0x10, 0x20, 0x30];
+        let ts = TimestampType::YearMonthDayHourMinSec;
+        let epoch = |tz| to_epoch_seconds(&bcd, ts, Century::default(), tz).unwrap();
+        assert_eq!(epoch(DeviceTimeZone::Utc), UTC_EPOCH);
+        assert_eq!(epoch(DeviceTimeZone::beijing()), UTC_EPOCH - 8 * 3600);
+        assert_eq!(
+            epoch(DeviceTimeZone::Offset(-5 * 3600)),
+            UTC_EPOCH + 5 * 3600
+        );
+        assert_eq!(
+            epoch(DeviceTimeZone::Offset(5 * 3600 + 1800)),
+            UTC_EPOCH - 5 * 3600 - 1800
+        );
+        assert_eq!(
+            to_epoch_millis(&bcd, ts, Century::default(), DeviceTimeZone::Utc).unwrap(),
+            UTC_EPOCH * 1000
+        );
+
+        let dt = to_datetime(
+            &bcd,
+            ts,
+            Century::default(),
+            DeviceTimeZone::Offset(-5 * 3600),
+        )
+        .unwrap();
+        assert_eq!(dt.offset().local_minus_utc(), -5 * 3600);
+        assert_eq!((dt.hour(), dt.minute(), dt.second()), (10, 20, 30));
+
+        // 超过 ±24h 的偏移
+        assert!(to_datetime(&bcd, ts, Century::default(), DeviceTimeZone::Offset(86_400)).is_err());
+        assert!(
+            epoch_seconds_to_bcd(UTC_EPOCH, ts, DeviceTimeZone::Offset(-86_400), false).is_err()
+        );
+    }
+
+    #[test]
+    fn test_epoch_round_trip() {
+        const UTC_EPOCH: i64 = 1_684_146_030;
+        let ts = TimestampType::YearMonthDayHourMinSec;
+        let zones = [
+            DeviceTimeZone::Utc,
+            DeviceTimeZone::beijing(),
+            DeviceTimeZone::Offset(-5 * 3600),
+            DeviceTimeZone::Offset(-(9 * 3600 + 1800)),
+        ];
+        for tz in zones {
+            let bcd = epoch_seconds_to_bcd(UTC_EPOCH, ts, tz, false).unwrap();
+            assert_eq!(
+                to_epoch_seconds(&bcd, ts, Century::default(), tz).unwrap(),
+                UTC_EPOCH
+            );
+            // 毫秒部分在 BCD 中丢失
+            let bcd = epoch_millis_to_bcd(UTC_EPOCH * 1000 + 999, ts, tz, false).unwrap();
+            assert_eq!(
+                to_epoch_millis(&bcd, ts, Century::default(), tz).unwrap(),
+                UTC_EPOCH * 1000
+            );
+        }
+        assert_eq!(
+            epoch_seconds_to_bcd(UTC_EPOCH, ts, DeviceTimeZone::Offset(-5 * 3600), false).unwrap(),
+            [0x23, 0x05, 0x15, 0x05, 0x20, 0x30]
+        );
+        // 负偏移跨到前一天
+        assert_eq!(
+            epoch_seconds_to_bcd(
+                1_684_108_800,
+                TimestampType::YyyyMmDd,
+                DeviceTimeZone::Offset(-3600),
+                true
+            )
+            .unwrap(),
+            [0x14, 0x05, 0x23, 0x20]
+        );
+    }
+
+    #[test]
+    fn test_strict_range_checks() {
+        let ymdhms = TimestampType::YearMonthDayHourMinSec;
+        assert_eq!(
+            convert_strict(&[0x23, 0x05, 0x15, 0x10, 0x20, 0x30], ymdhms).unwrap(),
+            "2023-05-15 10:20:30"
+        );
+        let rejected: [(&[u8], TimestampType); 8] = [
+            (&[0x23, 0x13, 0x01], TimestampType::YearMonthDay),
+            (&[0x23, 0x00, 0x01], TimestampType::YearMonthDay),
+            (&[0x23, 0x02, 0x30], TimestampType::YearMonthDay),
+            (&[0x23, 0x02, 0x29], TimestampType::YearMonthDay),
+            (&[0x23, 0x05, 0x15, 0x24, 0x00, 0x00], ymdhms),
+            (&[0x23, 0x05, 0x15, 0x10, 0x60, 0x00], ymdhms),
+            (&[0x10, 0x20, 0x61], TimestampType::HHmmss),
+            // 长度不足
+            (&[0x23, 0x05], TimestampType::YearMonthDay),
+        ];
+        for (bytes, timestamp_type) in rejected {
+            assert!(
+                matches!(
+                    convert_strict(bytes, timestamp_type),
+                    Err(ProtocolError::HexError(HexError::InvalidTimestamp { .. }))
+                ),
+                "{:02X?} as {:?}",
+                bytes,
+                timestamp_type
+            );
+        }
+        // 闰年 2 月 29 日合法
+        assert!(convert_strict(&[0x24, 0x02, 0x29], TimestampType::YearMonthDay).is_ok());
+
+        assert!(validate("2023-05-15 23:59:59", ymdhms).is_ok());
+        assert!(validate("2023-05-15 23:59", ymdhms).is_err());
+        assert!(validate("2023-05-1a", TimestampType::YearMonthDay).is_err());
+        match validate("2023-04-31", TimestampType::YearMonthDay) {
+            Err(ProtocolError::HexError(HexError::InvalidTimestamp { value, .. })) => {
+                assert_eq!(value, "2023-04-31")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
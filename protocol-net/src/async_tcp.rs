@@ -0,0 +1,220 @@
+use std::{io, net::SocketAddr};
+
+use protocol_kernel::{bridge::trace, ProtocolConfig, StreamingReader};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, UnboundedSender},
+};
+
+use crate::{handle_frame, on_listen_error, reply_bytes, session::DeviceSessions};
+
+const READ_BUFFER: usize = 1024;
+
+/// 基于 tokio 的 TCP 接入，行为与 `TcpServer` 一致，每个连接一个任务。
+/// 解析在连接任务中同步执行 (单帧解析是微秒级的纯计算)
+pub struct AsyncTcpServer {
+    listener: TcpListener,
+    protocol_id: String,
+    config: ProtocolConfig,
+    sessions: DeviceSessions,
+}
+
+impl AsyncTcpServer {
+    pub async fn bind<A: ToSocketAddrs>(
+        addr: A,
+        protocol_id: &str,
+        config: ProtocolConfig,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            protocol_id: protocol_id.into(),
+            config,
+            sessions: DeviceSessions::new(),
+        })
+    }
+
+    /// 与其他服务端共用同一张设备绑定表
+    pub fn with_sessions(mut self, sessions: DeviceSessions) -> Self {
+        self.sessions = sessions;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn sessions(&self) -> DeviceSessions {
+        self.sessions.clone()
+    }
+
+    /// 接受连接，每个连接 spawn 一个任务；需要在 tokio 运行时中调用。
+    /// accept 失败 (对端提前断开、文件描述符耗尽等) 时记录后继续，不影响其他设备
+    pub async fn serve(self) -> io::Result<()> {
+        loop {
+            let (stream, peer) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    if let Some(backoff) = on_listen_error("accept connection", &e) {
+                        tokio::time::sleep(backoff).await;
+                    }
+                    continue;
+                }
+            };
+            let connection = Connection {
+                protocol_id: self.protocol_id.clone(),
+                reader: StreamingReader::new(self.config.clone()),
+                sessions: self.sessions.clone(),
+                peer,
+            };
+            tokio::spawn(connection.run(stream));
+        }
+    }
+}
+
+struct Connection {
+    protocol_id: String,
+    reader: StreamingReader,
+    sessions: DeviceSessions,
+    peer: SocketAddr,
+}
+
+impl Connection {
+    async fn run(mut self, stream: TcpStream) {
+        let (mut read, mut write) = stream.into_split();
+        // 回复与 DeviceSessions::send 的主动下发都经由写任务，保证帧不会交错
+        let (writer, mut outgoing) = mpsc::unbounded_channel::<Vec<u8>>();
+        tokio::spawn(async move {
+            while let Some(frame) = outgoing.recv().await {
+                if write.write_all(&frame).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let mut buf = [0u8; READ_BUFFER];
+        loop {
+            let n = match read.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            self.reader.push(&buf[..n]);
+            for frame in self.reader.drain_frames() {
+                if let Err(e) = self.on_frame(&writer, &frame) {
+                    eprintln!(
+                        "[WARN] {}Failed to handle frame from {}: {}",
+                        trace::log_prefix(),
+                        self.peer,
+                        e
+                    );
+                }
+            }
+        }
+        // 绑定表中的 sender 随之释放，写任务在队列写完后退出
+        self.sessions.unbind_peer(self.peer);
+    }
+
+    fn on_frame(&self, writer: &UnboundedSender<Vec<u8>>, frame: &[u8]) -> io::Result<()> {
+        let response = handle_frame(&self.protocol_id, frame).map_err(io::Error::other)?;
+        // 设备重连时旧的半开连接可能还没断开，以最新收到帧的连接为准
+        if let Some(device_no) = response.device_no() {
+            if self.sessions.bound_peer(device_no) != Some(self.peer) {
+                self.sessions.bind_channel(device_no, writer, self.peer);
+            }
+        }
+        let reply = reply_bytes(&response).map_err(io::Error::other)?;
+        if !reply.is_empty() {
+            writer
+                .send(reply)
+                .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_kernel::{
+        bridge::registry::{register_protocol, ProtocolHandler},
+        JniRequest, JniResponse, ProtocolResult,
+    };
+
+    // 回复 68 <命令码|0x80> 16，设备号取帧中第 3 字节
+    struct Ack;
+
+    impl ProtocolHandler for Ack {
+        fn decode_upstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+            let frame = protocol_kernel::hex_util::hex_to_bytes(request.hex())?;
+            let mut rsp = JniResponse::from(
+                format!(r#"{{"success":true,"deviceNo":"{:02X}"}}"#, frame[2]).as_bytes(),
+            )?;
+            rsp.set_rsp_hex(&format!("68{:02X}16", frame[1] | 0x80));
+            Ok(rsp)
+        }
+
+        fn encode_downstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn test_async_tcp_round_trip() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            register_protocol("net-test/async-tcp", Ack);
+            let config = ProtocolConfig::new("net-test", 1, 1)
+                .with_head(&[0x68])
+                .with_tail(&[0x16]);
+            let server = AsyncTcpServer::bind("127.0.0.1:0", "net-test/async-tcp", config)
+                .await
+                .unwrap();
+            let addr = server.local_addr().unwrap();
+            let sessions = server.sessions();
+            tokio::spawn(server.serve());
+
+            let mut client = TcpStream::connect(addr).await.unwrap();
+            // 一帧分两次发送
+            client.write_all(&[0x68, 0x01]).await.unwrap();
+            client.write_all(&[0x42, 0x16]).await.unwrap();
+            let mut reply = [0u8; 3];
+            client.read_exact(&mut reply).await.unwrap();
+            assert_eq!(reply, [0x68, 0x81, 0x16]);
+
+            assert_eq!(sessions.devices(), vec!["42".to_string()]);
+            sessions.send("42", &[0x68, 0x20, 0x16]).unwrap();
+            client.read_exact(&mut reply).await.unwrap();
+            assert_eq!(reply, [0x68, 0x20, 0x16]);
+            assert!(sessions.send("43", &[0x68]).is_err());
+
+            // 设备在旧连接断开前重连，下发走新连接；旧连接关闭后绑定仍然保留
+            let mut reconnected = TcpStream::connect(addr).await.unwrap();
+            reconnected
+                .write_all(&[0x68, 0x01, 0x42, 0x16])
+                .await
+                .unwrap();
+            reconnected.read_exact(&mut reply).await.unwrap();
+            assert_eq!(
+                sessions.bound_peer("42"),
+                Some(reconnected.local_addr().unwrap())
+            );
+            sessions.send("42", &[0x68, 0x21, 0x16]).unwrap();
+            reconnected.read_exact(&mut reply).await.unwrap();
+            assert_eq!(reply, [0x68, 0x21, 0x16]);
+
+            // 再走一轮上行，让服务端有机会处理旧连接的 EOF
+            drop(client);
+            reconnected
+                .write_all(&[0x68, 0x01, 0x42, 0x16])
+                .await
+                .unwrap();
+            reconnected.read_exact(&mut reply).await.unwrap();
+            assert_eq!(sessions.devices(), vec!["42".to_string()]);
+            sessions.send("42", &[0x68, 0x22, 0x16]).unwrap();
+            reconnected.read_exact(&mut reply).await.unwrap();
+            assert_eq!(reply, [0x68, 0x22, 0x16]);
+        });
+    }
+}
@@ -0,0 +1,102 @@
+//! 网络接入层: 监听 TCP/UDP 端口，按 ProtocolConfig 分帧 (StreamingReader)，
+//! 交给 registry 中注册的 ProtocolHandler 解析，并把回复帧写回表端。
+//!
+//! - `TcpServer` / `UdpServer` (feature `blocking`，默认开启): 每个 TCP 连接一个线程，不依赖异步运行时
+//! - `AsyncTcpServer` (feature `tokio`): 在 tokio 运行时上每个连接一个任务
+//!
+//! 响应中带有 device_no 时 (通常是注册帧) 连接会与该设备绑定，之后平台可以通过
+//! `DeviceSessions::send` 主动下发。
+//! 维护工具直连表计时，可以用 `SerialTransport` 走 RS-485 半双工一问一答
+//! (feature `serial` 提供基于 serialport 的真实串口)。
+#[cfg(feature = "tokio")]
+mod async_tcp;
+mod serial;
+#[cfg(any(feature = "blocking", feature = "tokio"))]
+mod session;
+#[cfg(feature = "blocking")]
+mod tcp;
+#[cfg(feature = "blocking")]
+mod udp;
+
+#[cfg(any(feature = "blocking", feature = "tokio"))]
+use std::{io, time::Duration};
+
+#[cfg(any(feature = "blocking", feature = "tokio"))]
+use protocol_kernel::bridge::trace;
+use protocol_kernel::{
+    bridge::registry::protocol_handler, hex_util, JniRequest, JniResponse, ProtocolResult,
+};
+
+#[cfg(feature = "tokio")]
+pub use async_tcp::AsyncTcpServer;
+pub use serial::{HalfDuplexPort, SerialSettings, SerialTransport};
+#[cfg(feature = "serial")]
+pub use serialport;
+#[cfg(any(feature = "blocking", feature = "tokio"))]
+pub use session::DeviceSessions;
+#[cfg(feature = "blocking")]
+pub use tcp::TcpServer;
+#[cfg(feature = "blocking")]
+pub use udp::UdpServer;
+
+/// 用 protocol_id 对应的 ProtocolHandler 解析一帧上行
+pub fn handle_frame(protocol_id: &str, frame: &[u8]) -> ProtocolResult<JniResponse> {
+    let request = JniRequest::builder()
+        .uri(protocol_id)
+        .hex(&hex_util::bytes_to_hex(frame)?)
+        .build()?;
+    protocol_handler(protocol_id)?.decode_upstream(&request)
+}
+
+// 需要写回表端的字节，没有回复帧时为空
+#[cfg(any(feature = "blocking", feature = "tokio"))]
+fn reply_bytes(response: &JniResponse) -> ProtocolResult<Vec<u8>> {
+    match response.rsp_hex() {
+        "" => Ok(Vec::new()),
+        hex => hex_util::hex_to_bytes(hex),
+    }
+}
+
+// 文件描述符耗尽时暂停 accept 的时长，等待已有连接释放
+#[cfg(any(feature = "blocking", feature = "tokio"))]
+const EXHAUSTED_BACKOFF: Duration = Duration::from_millis(100);
+
+// accept/recv_from 的错误大多是暂时的 (对端在 accept 前断开、文件描述符耗尽、
+// Windows 上 UDP 收到 ICMP 端口不可达)，记录后继续服务其他设备。
+// 返回继续之前需要等待的时长
+#[cfg(any(feature = "blocking", feature = "tokio"))]
+fn on_listen_error(action: &str, e: &io::Error) -> Option<Duration> {
+    eprintln!("[WARN] {}Failed to {}: {}", trace::log_prefix(), action, e);
+    resource_exhausted(e).then_some(EXHAUSTED_BACKOFF)
+}
+
+#[cfg(any(feature = "blocking", feature = "tokio"))]
+fn resource_exhausted(e: &io::Error) -> bool {
+    // ENFILE/EMFILE，Linux 与 macOS 取值相同
+    #[cfg(unix)]
+    const CODES: &[i32] = &[23, 24];
+    // WSAEMFILE
+    #[cfg(windows)]
+    const CODES: &[i32] = &[10024];
+    #[cfg(not(any(unix, windows)))]
+    const CODES: &[i32] = &[];
+    e.kind() == io::ErrorKind::OutOfMemory || e.raw_os_error().is_some_and(|c| CODES.contains(&c))
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "tokio")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listen_error_backoff() {
+        let aborted = io::Error::from(io::ErrorKind::ConnectionAborted);
+        assert_eq!(on_listen_error("accept", &aborted), None);
+        let reset = io::Error::from(io::ErrorKind::ConnectionReset);
+        assert_eq!(on_listen_error("receive", &reset), None);
+        #[cfg(unix)]
+        assert_eq!(
+            on_listen_error("accept", &io::Error::from_raw_os_error(24)),
+            Some(EXHAUSTED_BACKOFF)
+        );
+    }
+}
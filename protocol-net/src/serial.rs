@@ -0,0 +1,218 @@
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+    thread,
+    time::{Duration, Instant},
+};
+
+use protocol_kernel::{bridge::trace, ProtocolConfig, StreamingReader};
+
+/// 半双工端口 (RS-485 串口、串口服务器的 TCP 透传等)。
+/// 开启 feature `serial` 后 serialport 打开的串口可以直接使用
+pub trait HalfDuplexPort: Read + Write {
+    /// 单次 read 的最长等待时间，超时应返回 TimedOut/WouldBlock
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()>;
+
+    /// 丢弃输入缓冲区中的残留数据
+    fn clear_input(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl HalfDuplexPort for TcpStream {
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, Some(timeout))
+    }
+}
+
+#[cfg(feature = "serial")]
+impl HalfDuplexPort for Box<dyn serialport::SerialPort> {
+    fn set_read_timeout(&mut self, timeout: Duration) -> io::Result<()> {
+        self.set_timeout(timeout).map_err(io::Error::from)
+    }
+
+    fn clear_input(&mut self) -> io::Result<()> {
+        self.clear(serialport::ClearBuffer::Input)
+            .map_err(io::Error::from)
+    }
+}
+
+/// 串口收发时序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialSettings {
+    // 两帧之间总线至少静默的时间，发送前等待
+    pub inter_frame_gap: Duration,
+    // 发出请求后等待表端回复的最长时间
+    pub turnaround_timeout: Duration,
+    // 超时后的重发次数
+    pub retries: u32,
+}
+
+impl Default for SerialSettings {
+    fn default() -> Self {
+        Self {
+            inter_frame_gap: Duration::from_millis(50),
+            turnaround_timeout: Duration::from_secs(2),
+            retries: 2,
+        }
+    }
+}
+
+/// 主站模式的半双工收发: 一问一答，超时重发
+pub struct SerialTransport<P: HalfDuplexPort> {
+    port: P,
+    reader: StreamingReader,
+    settings: SerialSettings,
+    last_activity: Option<Instant>,
+}
+
+#[cfg(feature = "serial")]
+impl SerialTransport<Box<dyn serialport::SerialPort>> {
+    /// 打开串口。波特率、校验位等由 builder 指定，
+    /// 如 DL/T 645 常用 `serialport::new("/dev/ttyUSB0", 2400).parity(serialport::Parity::Even)`
+    pub fn open(
+        builder: serialport::SerialPortBuilder,
+        config: ProtocolConfig,
+        settings: SerialSettings,
+    ) -> io::Result<Self> {
+        let port = builder
+            .timeout(settings.turnaround_timeout)
+            .open()
+            .map_err(io::Error::from)?;
+        Ok(Self::new(port, config, settings))
+    }
+}
+
+impl<P: HalfDuplexPort> SerialTransport<P> {
+    pub fn new(port: P, config: ProtocolConfig, settings: SerialSettings) -> Self {
+        Self {
+            port,
+            reader: StreamingReader::new(config),
+            settings,
+            last_activity: None,
+        }
+    }
+
+    pub fn settings(&self) -> &SerialSettings {
+        &self.settings
+    }
+
+    pub fn into_inner(self) -> P {
+        self.port
+    }
+
+    /// 等总线静默 inter_frame_gap 后发送一帧，并丢弃之前残留的输入
+    pub fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        if let Some(last) = self.last_activity {
+            let quiet = last.elapsed();
+            if quiet < self.settings.inter_frame_gap {
+                thread::sleep(self.settings.inter_frame_gap - quiet);
+            }
+        }
+        self.port.clear_input()?;
+        self.reader.clear();
+        self.port.write_all(frame)?;
+        self.port.flush()?;
+        self.last_activity = Some(Instant::now());
+        Ok(())
+    }
+
+    /// 在 turnaround_timeout 内收一帧完整的回复，超时返回 TimedOut
+    pub fn receive(&mut self) -> io::Result<Vec<u8>> {
+        let deadline = Instant::now() + self.settings.turnaround_timeout;
+        let mut buf = [0u8; 256];
+        loop {
+            if let Some(frame) = self.reader.next_frame() {
+                return Ok(frame);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "no reply within turnaround timeout",
+                ));
+            }
+            self.port.set_read_timeout(remaining)?;
+            match self.port.read(&mut buf) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => {
+                    self.reader.push(&buf[..n]);
+                    self.last_activity = Some(Instant::now());
+                }
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::TimedOut
+                            | io::ErrorKind::WouldBlock
+                            | io::ErrorKind::Interrupted
+                    ) => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 发送请求并等待回复，超时后按 retries 重发
+    pub fn request(&mut self, frame: &[u8]) -> io::Result<Vec<u8>> {
+        let mut attempt = 0;
+        loop {
+            self.send(frame)?;
+            match self.receive() {
+                Err(e)
+                    if e.kind() == io::ErrorKind::TimedOut && attempt < self.settings.retries =>
+                {
+                    attempt += 1;
+                    eprintln!(
+                        "[WARN] {}No reply on serial link, retry {}/{}",
+                        trace::log_prefix(),
+                        attempt,
+                        self.settings.retries
+                    );
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_request_retries() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        // 模拟表端: 第一次请求不回复，第二次分两段回复
+        let meter = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 3];
+            stream.read_exact(&mut buf).unwrap();
+            stream.read_exact(&mut buf).unwrap();
+            stream.write_all(&[0x00, 0x68, 0x81]).unwrap();
+            thread::sleep(Duration::from_millis(20));
+            stream.write_all(&[0x16]).unwrap();
+            buf
+        });
+
+        let config = ProtocolConfig::new("serial-test", 1, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16]);
+        let settings = SerialSettings {
+            inter_frame_gap: Duration::from_millis(5),
+            turnaround_timeout: Duration::from_millis(200),
+            retries: 1,
+        };
+        let port = TcpStream::connect(addr).unwrap();
+        let mut link = SerialTransport::new(port, config, settings);
+        let reply = link.request(&[0x68, 0x01, 0x16]).unwrap();
+        assert_eq!(reply, vec![0x68, 0x81, 0x16]);
+        assert_eq!(meter.join().unwrap(), [0x68, 0x01, 0x16]);
+
+        let err = link.request(&[0x68, 0x02, 0x16]).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            io::ErrorKind::TimedOut | io::ErrorKind::UnexpectedEof
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+#[cfg(feature = "blocking")]
+use std::{net::UdpSocket, sync::mpsc::Sender};
+
+// 设备所在的连接
+#[derive(Clone)]
+enum Session {
+    // 阻塞连接的写线程，按顺序写出回复与主动下发的帧
+    #[cfg(feature = "blocking")]
+    Tcp(Sender<Vec<u8>>, SocketAddr),
+    #[cfg(feature = "blocking")]
+    Udp(Arc<UdpSocket>, SocketAddr),
+    // 异步连接的写任务，按顺序写出回复与主动下发的帧
+    #[cfg(feature = "tokio")]
+    Channel(tokio::sync::mpsc::UnboundedSender<Vec<u8>>, SocketAddr),
+}
+
+impl Session {
+    fn peer(&self) -> SocketAddr {
+        match self {
+            #[cfg(feature = "blocking")]
+            Session::Tcp(_, peer) => *peer,
+            #[cfg(feature = "blocking")]
+            Session::Udp(_, peer) => *peer,
+            #[cfg(feature = "tokio")]
+            Session::Channel(_, peer) => *peer,
+        }
+    }
+}
+
+/// 设备 -> 连接的绑定表，由服务端在收到带 device_no 的响应后维护
+#[derive(Clone, Default)]
+pub struct DeviceSessions {
+    inner: Arc<Mutex<HashMap<String, Session>>>,
+}
+
+impl DeviceSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn bind_tcp(&self, device_no: &str, writer: &Sender<Vec<u8>>, peer: SocketAddr) {
+        let session = Session::Tcp(writer.clone(), peer);
+        self.lock().insert(device_no.into(), session);
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn bind_udp(&self, device_no: &str, socket: &Arc<UdpSocket>, peer: SocketAddr) {
+        let session = Session::Udp(socket.clone(), peer);
+        self.lock().insert(device_no.into(), session);
+    }
+
+    #[cfg(feature = "tokio")]
+    pub(crate) fn bind_channel(
+        &self,
+        device_no: &str,
+        writer: &tokio::sync::mpsc::UnboundedSender<Vec<u8>>,
+        peer: SocketAddr,
+    ) {
+        let session = Session::Channel(writer.clone(), peer);
+        self.lock().insert(device_no.into(), session);
+    }
+
+    // 连接断开时解除该连接上的所有设备。设备已经在新连接上重新绑定时不受影响
+    pub(crate) fn unbind_peer(&self, peer: SocketAddr) {
+        self.lock().retain(|_, s| s.peer() != peer);
+    }
+
+    /// 设备当前绑定的对端地址
+    pub fn bound_peer(&self, device_no: &str) -> Option<SocketAddr> {
+        self.lock().get(device_no).map(Session::peer)
+    }
+
+    pub fn is_bound(&self, device_no: &str) -> bool {
+        self.lock().contains_key(device_no)
+    }
+
+    pub fn devices(&self) -> Vec<String> {
+        let mut devices: Vec<String> = self.lock().keys().cloned().collect();
+        devices.sort();
+        devices
+    }
+
+    /// 向已绑定的设备主动下发一帧。TCP 连接只是把帧交给该连接的写线程/任务，不会阻塞调用方
+    pub fn send(&self, device_no: &str, frame: &[u8]) -> io::Result<()> {
+        // 先取出会话再发送，不在持有全局锁时做 I/O
+        let session = self.lock().get(device_no).cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotConnected,
+                format!("device {} is not connected", device_no),
+            )
+        })?;
+        match session {
+            #[cfg(feature = "blocking")]
+            Session::Tcp(writer, _) => writer
+                .send(frame.to_vec())
+                .map_err(|_| io::ErrorKind::BrokenPipe.into()),
+            #[cfg(feature = "blocking")]
+            Session::Udp(socket, peer) => socket.send_to(frame, peer).map(|_| ()),
+            #[cfg(feature = "tokio")]
+            Session::Channel(writer, _) => writer
+                .send(frame.to_vec())
+                .map_err(|_| io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Session>> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Sender},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use protocol_kernel::{bridge::trace, ProtocolConfig, StreamingReader};
+
+use crate::{handle_frame, on_listen_error, reply_bytes, session::DeviceSessions};
+
+const READ_BUFFER: usize = 1024;
+// 对端不再收数据 (半开连接) 时写线程最多阻塞这么久，超时后关闭连接
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// TCP 接入。一个端口对应一个协议 (protocol_id 为 register_protocol 时的 id)
+pub struct TcpServer {
+    listener: TcpListener,
+    protocol_id: String,
+    config: ProtocolConfig,
+    sessions: DeviceSessions,
+}
+
+impl TcpServer {
+    pub fn bind<A: ToSocketAddrs>(
+        addr: A,
+        protocol_id: &str,
+        config: ProtocolConfig,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            protocol_id: protocol_id.into(),
+            config,
+            sessions: DeviceSessions::new(),
+        })
+    }
+
+    /// 与其他服务端共用同一张设备绑定表
+    pub fn with_sessions(mut self, sessions: DeviceSessions) -> Self {
+        self.sessions = sessions;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn sessions(&self) -> DeviceSessions {
+        self.sessions.clone()
+    }
+
+    /// 在当前线程上接受连接，每个连接一个线程。
+    /// accept 失败 (对端提前断开、文件描述符耗尽等) 时记录后继续，不影响其他设备
+    pub fn serve(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    if let Some(backoff) = on_listen_error("accept connection", &e) {
+                        thread::sleep(backoff);
+                    }
+                    continue;
+                }
+            };
+            let connection = Connection {
+                protocol_id: self.protocol_id.clone(),
+                reader: StreamingReader::new(self.config.clone()),
+                sessions: self.sessions.clone(),
+            };
+            thread::spawn(move || connection.run(stream));
+        }
+        Ok(())
+    }
+
+    /// 在后台线程上运行 serve
+    pub fn spawn(self) -> JoinHandle<io::Result<()>> {
+        thread::spawn(move || self.serve())
+    }
+}
+
+struct Connection {
+    protocol_id: String,
+    reader: StreamingReader,
+    sessions: DeviceSessions,
+}
+
+impl Connection {
+    fn run(mut self, mut stream: TcpStream) {
+        let Ok(peer) = stream.peer_addr() else {
+            return;
+        };
+        let Ok(mut write) = stream.try_clone() else {
+            return;
+        };
+        // 回复与 DeviceSessions::send 的主动下发都经由写线程，保证帧不会交错，
+        // 也不会在持有绑定表的锁时阻塞在慢连接上
+        let (writer, outgoing) = mpsc::channel::<Vec<u8>>();
+        thread::spawn(move || {
+            if write.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+                return;
+            }
+            for frame in outgoing {
+                if write.write_all(&frame).is_err() {
+                    // 关闭连接让读循环退出并解除绑定
+                    let _ = write.shutdown(Shutdown::Both);
+                    break;
+                }
+            }
+        });
+        let mut buf = [0u8; READ_BUFFER];
+        loop {
+            let n = match stream.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            self.reader.push(&buf[..n]);
+            for frame in self.reader.drain_frames() {
+                if let Err(e) = self.on_frame(&writer, peer, &frame) {
+                    eprintln!(
+                        "[WARN] {}Failed to handle frame from {}: {}",
+                        trace::log_prefix(),
+                        peer,
+                        e
+                    );
+                }
+            }
+        }
+        // 绑定表中的 sender 随之释放，写线程在队列写完后退出
+        self.sessions.unbind_peer(peer);
+    }
+
+    fn on_frame(&self, writer: &Sender<Vec<u8>>, peer: SocketAddr, frame: &[u8]) -> io::Result<()> {
+        let response = handle_frame(&self.protocol_id, frame).map_err(io::Error::other)?;
+        // 设备重连时旧的半开连接可能还没断开，以最新收到帧的连接为准
+        if let Some(device_no) = response.device_no() {
+            if self.sessions.bound_peer(device_no) != Some(peer) {
+                self.sessions.bind_tcp(device_no, writer, peer);
+            }
+        }
+        let reply = reply_bytes(&response).map_err(io::Error::other)?;
+        if !reply.is_empty() {
+            writer
+                .send(reply)
+                .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_kernel::{
+        bridge::registry::{register_protocol, ProtocolHandler},
+        JniRequest, JniResponse, ProtocolResult,
+    };
+    use std::time::Duration;
+
+    // 回复 68 <命令码|0x80> 16，设备号取帧中第 3 字节
+    struct Ack;
+
+    impl ProtocolHandler for Ack {
+        fn decode_upstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+            let frame = protocol_kernel::hex_util::hex_to_bytes(request.hex())?;
+            let mut rsp = JniResponse::from(
+                format!(r#"{{"success":true,"deviceNo":"{:02X}"}}"#, frame[2]).as_bytes(),
+            )?;
+            rsp.set_rsp_hex(&format!("68{:02X}16", frame[1] | 0x80));
+            Ok(rsp)
+        }
+
+        fn encode_downstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn test_tcp_round_trip() {
+        register_protocol("net-test/tcp", Ack);
+        let config = ProtocolConfig::new("net-test", 1, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16]);
+        let server = TcpServer::bind("127.0.0.1:0", "net-test/tcp", config).unwrap();
+        let addr = server.local_addr().unwrap();
+        let sessions = server.sessions();
+        server.spawn();
+
+        let mut client = TcpStream::connect(addr).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        // 一帧分两次发送
+        client.write_all(&[0x68, 0x01]).unwrap();
+        client.write_all(&[0x42, 0x16]).unwrap();
+        let mut reply = [0u8; 3];
+        client.read_exact(&mut reply).unwrap();
+        assert_eq!(reply, [0x68, 0x81, 0x16]);
+
+        assert_eq!(sessions.devices(), vec!["42".to_string()]);
+        sessions.send("42", &[0x68, 0x20, 0x16]).unwrap();
+        client.read_exact(&mut reply).unwrap();
+        assert_eq!(reply, [0x68, 0x20, 0x16]);
+        assert!(sessions.send("43", &[0x68]).is_err());
+
+        // 设备在旧连接断开前重连，下发走新连接；旧连接关闭后绑定仍然保留
+        let mut reconnected = TcpStream::connect(addr).unwrap();
+        reconnected
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        reconnected.write_all(&[0x68, 0x01, 0x42, 0x16]).unwrap();
+        reconnected.read_exact(&mut reply).unwrap();
+        assert_eq!(
+            sessions.bound_peer("42"),
+            Some(reconnected.local_addr().unwrap())
+        );
+        sessions.send("42", &[0x68, 0x21, 0x16]).unwrap();
+        reconnected.read_exact(&mut reply).unwrap();
+        assert_eq!(reply, [0x68, 0x21, 0x16]);
+
+        drop(client);
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(sessions.devices(), vec!["42".to_string()]);
+        sessions.send("42", &[0x68, 0x22, 0x16]).unwrap();
+        reconnected.read_exact(&mut reply).unwrap();
+        assert_eq!(reply, [0x68, 0x22, 0x16]);
+    }
+}
@@ -0,0 +1,94 @@
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
+
+use protocol_kernel::{bridge::trace, ProtocolConfig, StreamingReader};
+
+use crate::{handle_frame, on_listen_error, reply_bytes, session::DeviceSessions};
+
+// UDP 报文最大长度
+const DATAGRAM_BUFFER: usize = 65_536;
+
+/// UDP 接入。每个报文独立分帧 (一个报文可以包含多帧)，回复发往报文的来源地址
+pub struct UdpServer {
+    socket: Arc<UdpSocket>,
+    protocol_id: String,
+    config: ProtocolConfig,
+    sessions: DeviceSessions,
+}
+
+impl UdpServer {
+    pub fn bind<A: ToSocketAddrs>(
+        addr: A,
+        protocol_id: &str,
+        config: ProtocolConfig,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            socket: Arc::new(UdpSocket::bind(addr)?),
+            protocol_id: protocol_id.into(),
+            config,
+            sessions: DeviceSessions::new(),
+        })
+    }
+
+    pub fn with_sessions(mut self, sessions: DeviceSessions) -> Self {
+        self.sessions = sessions;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn sessions(&self) -> DeviceSessions {
+        self.sessions.clone()
+    }
+
+    /// 在当前线程上接收报文。recv_from 失败 (如 Windows 上的 ICMP 端口不可达) 时记录后继续
+    pub fn serve(self) -> io::Result<()> {
+        let mut buf = vec![0u8; DATAGRAM_BUFFER];
+        loop {
+            let (n, peer) = match self.socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) => {
+                    if let Some(backoff) = on_listen_error("receive datagram", &e) {
+                        thread::sleep(backoff);
+                    }
+                    continue;
+                }
+            };
+            let mut reader = StreamingReader::new(self.config.clone());
+            reader.push(&buf[..n]);
+            for frame in reader.drain_frames() {
+                if let Err(e) = self.on_frame(peer, &frame) {
+                    eprintln!(
+                        "[WARN] {}Failed to handle datagram from {}: {}",
+                        trace::log_prefix(),
+                        peer,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<io::Result<()>> {
+        thread::spawn(move || self.serve())
+    }
+
+    fn on_frame(&self, peer: SocketAddr, frame: &[u8]) -> io::Result<()> {
+        let response = handle_frame(&self.protocol_id, frame).map_err(io::Error::other)?;
+        // UDP 没有连接，每次都以最新的来源地址为准
+        if let Some(device_no) = response.device_no() {
+            self.sessions.bind_udp(device_no, &self.socket, peer);
+        }
+        let reply = reply_bytes(&response).map_err(io::Error::other)?;
+        if !reply.is_empty() {
+            self.socket.send_to(&reply, peer)?;
+        }
+        Ok(())
+    }
+}
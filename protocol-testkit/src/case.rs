@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use protocol_kernel::{ProtocolError, ProtocolResult};
+
+/// 一条黄金帧用例: 上行帧 -> 期望字段，或下行参数 -> 期望帧
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum GoldenCase {
+    Decode(DecodeCase),
+    Encode(EncodeCase),
+}
+
+impl GoldenCase {
+    pub fn decode(name: &str, hex: &str) -> DecodeCase {
+        DecodeCase {
+            name: name.into(),
+            hex: hex.into(),
+            cmd_code: None,
+            fields: BTreeMap::new(),
+            reply_hex: None,
+        }
+    }
+
+    pub fn encode(name: &str, cmd_code: &str) -> EncodeCase {
+        EncodeCase {
+            name: name.into(),
+            cmd_code: cmd_code.into(),
+            device_no: None,
+            params: BTreeMap::new(),
+            hex: String::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            GoldenCase::Decode(case) => &case.name,
+            GoldenCase::Encode(case) => &case.name,
+        }
+    }
+
+    /// 从 JSON 数组加载用例，便于把表端抓包整理成文件后 include_str!
+    pub fn load_json(json: &str) -> ProtocolResult<Vec<GoldenCase>> {
+        serde_json::from_str(json)
+            .map_err(|e| ProtocolError::CommonError(format!("invalid golden cases: {}", e)))
+    }
+}
+
+/// 上行解析用例。fields 只校验列出的字段 (按 code 匹配)，其余字段不管
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodeCase {
+    pub name: String,
+    pub hex: String,
+    #[serde(default)]
+    pub cmd_code: Option<String>,
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+    // 期望的回复帧，为 None 时不校验
+    #[serde(default)]
+    pub reply_hex: Option<String>,
+}
+
+impl DecodeCase {
+    pub fn cmd(mut self, cmd_code: &str) -> Self {
+        self.cmd_code = Some(cmd_code.into());
+        self
+    }
+
+    pub fn field(mut self, code: &str, value: &str) -> Self {
+        self.fields.insert(code.into(), value.into());
+        self
+    }
+
+    pub fn reply(mut self, hex: &str) -> Self {
+        self.reply_hex = Some(hex.into());
+        self
+    }
+}
+
+/// 下行编码用例
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodeCase {
+    pub name: String,
+    pub cmd_code: String,
+    #[serde(default)]
+    pub device_no: Option<String>,
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+    pub hex: String,
+}
+
+impl EncodeCase {
+    pub fn device_no(mut self, device_no: &str) -> Self {
+        self.device_no = Some(device_no.into());
+        self
+    }
+
+    pub fn param(mut self, key: &str, value: &str) -> Self {
+        self.params.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn hex(mut self, hex: &str) -> Self {
+        self.hex = hex.into();
+        self
+    }
+}
+
+impl From<DecodeCase> for GoldenCase {
+    fn from(case: DecodeCase) -> Self {
+        GoldenCase::Decode(case)
+    }
+}
+
+impl From<EncodeCase> for GoldenCase {
+    fn from(case: EncodeCase) -> Self {
+        GoldenCase::Encode(case)
+    }
+}
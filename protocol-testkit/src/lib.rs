@@ -0,0 +1,119 @@
+//! 协议一致性测试: 每个协议实现附带一张黄金帧表 (上行帧 -> 期望字段，下行参数 -> 期望帧)，
+//! 用 `golden_frames!` 生成 #[test]，在 cargo test 中逐条校验。
+//!
+//! ```ignore
+//! golden_frames!(water_meter_golden, WaterMeterHandler::new(), [
+//!     GoldenCase::decode("定时上报", "68 10 01 ... 16").cmd("01").field("lei_ji_liu_liang", "12.34"),
+//!     GoldenCase::encode("开阀", "25").param("valve", "1").hex("68 25 01 01 16"),
+//! ]);
+//!
+//! golden_frames!(water_meter_captured, WaterMeterHandler::new(), json = include_str!("golden.json"));
+//! ```
+//!
+//! `DeviceSimulator` 按帧模板模拟表端上报与应答，用于没有硬件时的压测。
+//! `fuzz` 模块提供 cargo-fuzz 的入口函数，以及从黄金帧生成初始语料。
+//! `prop` 模块提供字段值/时间/BCD 的 proptest Strategy，校验各 FieldType 编码与解码的对称性。
+//! `Replayer` 用当前实现重放生产日志中的上行帧，按字段报告与记录的差异。
+mod case;
+pub mod fuzz;
+pub mod prop;
+mod replay;
+mod runner;
+mod simulator;
+
+pub use case::{DecodeCase, EncodeCase, GoldenCase};
+pub use replay::{load_jsonl, FieldDiff, ReplayDiff, ReplayReport, Replayer};
+pub use runner::{assert_golden, run_golden, GoldenFailure};
+pub use simulator::{DeviceSimulator, FramePart, FrameTemplate};
+
+/// 生成一个执行黄金帧表的测试函数。handler 为实现了 ProtocolHandler 的表达式
+#[macro_export]
+macro_rules! golden_frames {
+    ($name:ident, $handler:expr, json = $json:expr $(,)?) => {
+        #[test]
+        fn $name() {
+            let cases = $crate::GoldenCase::load_json($json).expect("golden cases");
+            $crate::assert_golden(&$handler, &cases);
+        }
+    };
+    ($name:ident, $handler:expr, [$($case:expr),* $(,)?] $(,)?) => {
+        #[test]
+        fn $name() {
+            let cases: Vec<$crate::GoldenCase> = vec![$($crate::GoldenCase::from($case)),*];
+            $crate::assert_golden(&$handler, &cases);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_kernel::{
+        hex_util, JniRequest, JniResponse, ProtocolHandler, ProtocolResult, ReportField,
+    };
+
+    // 上行: 68 + 命令 + 读数 + 16，回复 68 + (命令|80) + 16；下行: 68 + 命令 + valve + 16
+    struct Demo;
+
+    impl ProtocolHandler for Demo {
+        fn decode_upstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+            let bytes = hex_util::hex_to_bytes(request.hex())?;
+            let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+            rsp.set_cmd_code(&format!("{:02X}", bytes[1]));
+            rsp.set_req_jsons(vec![ReportField::new(
+                "读数",
+                "reading",
+                bytes[2].to_string(),
+            )]);
+            rsp.set_rsp_hex(&format!("68{:02X}16", bytes[1] | 0x80));
+            Ok(rsp)
+        }
+
+        fn encode_downstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+            let valve = request.params_clone().remove("valve").unwrap_or_default();
+            let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+            rsp.set_rsp_hex(&format!("68{}0{}16", request.cmd_code_clone(), valve));
+            Ok(rsp)
+        }
+    }
+
+    golden_frames!(
+        demo_golden,
+        Demo,
+        [
+            GoldenCase::decode("上报", "68 01 0C 16")
+                .cmd("01")
+                .field("reading", "12")
+                .reply("688116"),
+            GoldenCase::encode("开阀", "25")
+                .param("valve", "1")
+                .hex("68 25 01 16"),
+        ]
+    );
+
+    golden_frames!(
+        demo_golden_json,
+        Demo,
+        json = r#"[
+            {"kind": "decode", "name": "上报", "hex": "68010516", "fields": {"reading": "5"}},
+            {"kind": "encode", "name": "关阀", "cmdCode": "25", "params": {"valve": "0"}, "hex": "68250016"}
+        ]"#
+    );
+
+    #[test]
+    fn test_failures_reported() {
+        let cases = vec![
+            GoldenCase::from(GoldenCase::decode("错误读数", "68010C16").field("reading", "13")),
+            GoldenCase::from(GoldenCase::decode("缺少字段", "68010C16").field("flow", "1")),
+            GoldenCase::from(
+                GoldenCase::encode("开阀", "25")
+                    .param("valve", "1")
+                    .hex("68250116"),
+            ),
+        ];
+        let failures = run_golden(&Demo, &cases);
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].reasons, ["field reading: expected 13, got 12"]);
+        assert_eq!(failures[1].reasons, ["field flow missing"]);
+    }
+}
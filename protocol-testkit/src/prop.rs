@@ -0,0 +1,248 @@
+//! 基于 proptest 的往返测试: 生成随机但合法的字段值，校验 encode -> decode 的对称性。
+//! 各生成函数返回 Strategy，失败时 proptest 会收缩到最小的反例，并记录在 proptest-regressions 下
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn roundtrip((field_type, value, swap) in prop::field_case()) {
+//!         prop::assert_roundtrip(field_type, &value, swap)?;
+//!     }
+//! }
+//! ```
+use chrono::{NaiveDate, NaiveDateTime};
+use proptest::{
+    prelude::*,
+    sample::select,
+    test_runner::{TestCaseError, TestCaseResult},
+};
+
+use protocol_kernel::{
+    core::type_converter::{FieldConvertDecoder, FieldTranslator, FieldType},
+    hex_util, timestamp_util,
+    timestamp_util::TimestampType,
+    ProtocolError,
+};
+
+// 缩放倍数只取 10 的整数次幂，生成的值才能被精确表示
+const SCALES: [f64; 5] = [1.0, 0.1, 0.01, 0.001, 10.0];
+
+// 整数编码要经过 f64，64 位整数只生成这个范围内的值
+const MAX_EXACT: i128 = 1_000_000_000_000;
+
+/// 任意一种 FieldType，整数类型带随机缩放
+pub fn field_type() -> impl Strategy<Value = FieldType> {
+    let scale = || select(SCALES.to_vec());
+    prop_oneof![
+        Just(FieldType::Empty),
+        Just(FieldType::StringOrBCD),
+        scale().prop_map(FieldType::UnsignedU8),
+        scale().prop_map(FieldType::UnsignedU16),
+        scale().prop_map(FieldType::UnsignedU32),
+        scale().prop_map(FieldType::UnsignedU64),
+        scale().prop_map(FieldType::SignedI8),
+        scale().prop_map(FieldType::SignedI16),
+        scale().prop_map(FieldType::SignedI32),
+        scale().prop_map(FieldType::SignedI64),
+        Just(FieldType::Float),
+        Just(FieldType::Double),
+        Just(FieldType::Ascii),
+    ]
+}
+
+/// field_type 能精确编码的值 (encode 的输入)
+pub fn field_value(field_type: &FieldType) -> BoxedStrategy<String> {
+    let int = |min: i128, max: i128, scale: f64| {
+        (min.max(-MAX_EXACT)..=max.min(MAX_EXACT))
+            .prop_map(move |raw| scaled(raw, scale))
+            .boxed()
+    };
+    match *field_type {
+        FieldType::Empty => Just(String::new()).boxed(),
+        FieldType::StringOrBCD => (1..=8usize)
+            .prop_flat_map(|len| bcd_string(len * 2))
+            .boxed(),
+        FieldType::UnsignedU8(s) => int(0, u8::MAX as i128, s),
+        FieldType::UnsignedU16(s) => int(0, u16::MAX as i128, s),
+        FieldType::UnsignedU32(s) => int(0, u32::MAX as i128, s),
+        FieldType::UnsignedU64(s) => int(0, u64::MAX as i128, s),
+        FieldType::SignedI8(s) => int(i8::MIN as i128, i8::MAX as i128, s),
+        FieldType::SignedI16(s) => int(i16::MIN as i128, i16::MAX as i128, s),
+        FieldType::SignedI32(s) => int(i32::MIN as i128, i32::MAX as i128, s),
+        FieldType::SignedI64(s) => int(i64::MIN as i128, i64::MAX as i128, s),
+        FieldType::Float => finite_f32().prop_map(|v| v.to_string()).boxed(),
+        FieldType::Double => finite_f64().prop_map(|v| v.to_string()).boxed(),
+        FieldType::Ascii => proptest::collection::vec(0x20u8..=0x7E, 0..=16)
+            .prop_map(|bytes| bytes.into_iter().map(char::from).collect())
+            .boxed(),
+    }
+}
+
+/// (字段类型, 该类型的值, 是否 swap)，供 assert_roundtrip 使用
+pub fn field_case() -> impl Strategy<Value = (FieldType, String, bool)> {
+    field_type().prop_flat_map(|ft| {
+        let value = field_value(&ft);
+        (Just(ft), value, any::<bool>())
+    })
+}
+
+fn finite_f32() -> impl Strategy<Value = f32> {
+    use proptest::num::f32;
+    f32::POSITIVE | f32::NEGATIVE | f32::NORMAL | f32::SUBNORMAL | f32::ZERO
+}
+
+fn finite_f64() -> impl Strategy<Value = f64> {
+    use proptest::num::f64;
+    f64::POSITIVE | f64::NEGATIVE | f64::NORMAL | f64::SUBNORMAL | f64::ZERO
+}
+
+// raw * scale 的十进制字符串，scale 为 10 的整数次幂
+fn scaled(raw: i128, scale: f64) -> String {
+    if scale >= 1.0 {
+        return (raw * scale as i128).to_string();
+    }
+    let decimals = (-scale.log10()).round() as usize;
+    let digits = format!("{:0>width$}", raw.unsigned_abs(), width = decimals + 1);
+    let (int, frac) = digits.split_at(digits.len() - decimals);
+    let sign = if raw < 0 { "-" } else { "" };
+    format!("{}{}.{}", sign, int, frac)
+}
+
+/// digits 位的 BCD 字符串 (全数字)
+pub fn bcd_string(digits: usize) -> impl Strategy<Value = String> {
+    proptest::collection::vec(0u8..=9, digits)
+        .prop_map(|ds| ds.into_iter().map(|d| char::from(b'0' + d)).collect())
+}
+
+/// 2000-01-01 ~ 2099-12-28 之间的时间，覆盖 2 位年份的全部取值
+pub fn timestamp() -> impl Strategy<Value = NaiveDateTime> {
+    (
+        2000..=2099i32,
+        1..=12u32,
+        1..=28u32,
+        0..=23u32,
+        0..=59u32,
+        0..=59u32,
+    )
+        .prop_map(|(y, mo, d, h, mi, s)| {
+            NaiveDate::from_ymd_opt(y, mo, d)
+                .and_then(|date| date.and_hms_opt(h, mi, s))
+                .expect("valid datetime")
+        })
+}
+
+pub fn timestamp_type() -> impl Strategy<Value = TimestampType> {
+    select(vec![
+        TimestampType::Year,
+        TimestampType::YearMonth,
+        TimestampType::YearMonthDay,
+        TimestampType::YearMonthDayHour,
+        TimestampType::YearMonthDayHourMin,
+        TimestampType::YearMonthDayHourMinSec,
+        TimestampType::HourMinSec,
+        TimestampType::YyyyMmDdHHmmss,
+        TimestampType::YyyyMmDd,
+        TimestampType::HHmmss,
+        TimestampType::YyMmDdHHmmss,
+        TimestampType::YyMmDd,
+    ])
+}
+
+fn fail(e: ProtocolError) -> TestCaseError {
+    TestCaseError::fail(e.to_string())
+}
+
+/// 按 field_type 编码 value (swap 时与 Writer 一样翻转字节)，再用 FieldConvertDecoder 解码，
+/// 校验结果与 value 一致。数值按数值比较，字符串按原样比较 (BCD 不区分大小写)。
+/// 在 proptest! 中用 `?` 调用，失败时参与收缩
+pub fn assert_roundtrip(field_type: FieldType, value: &str, swap: bool) -> TestCaseResult {
+    let mut bytes = field_type.encode(value).map_err(fail)?;
+    if swap {
+        bytes.reverse();
+    }
+    let decoded = FieldConvertDecoder::new("prop", field_type.clone(), None, swap)
+        .translate(&bytes)
+        .map_err(fail)?
+        .value_clone();
+    let equal = match field_type {
+        FieldType::Empty => decoded.is_empty(),
+        FieldType::StringOrBCD => decoded.eq_ignore_ascii_case(value),
+        FieldType::Ascii => decoded == value,
+        FieldType::Float => decoded.parse::<f32>().ok() == value.parse::<f32>().ok(),
+        _ => decoded.parse::<f64>().ok() == value.parse::<f64>().ok(),
+    };
+    prop_assert!(
+        equal,
+        "{:?} (swap {}) roundtrip: {} -> {} -> {}",
+        field_type,
+        swap,
+        value,
+        hex_util::bytes_to_hex(&bytes).map_err(fail)?,
+        decoded
+    );
+    Ok(())
+}
+
+/// 时间按 timestamp_type 编码为 BCD 再解析，校验与直接格式化的结果一致
+pub fn assert_timestamp_roundtrip(
+    datetime: &NaiveDateTime,
+    timestamp_type: TimestampType,
+) -> TestCaseResult {
+    let bytes = timestamp_util::encode(datetime, timestamp_type, false).map_err(fail)?;
+    let decoded = timestamp_util::convert_strict(&bytes, timestamp_type).map_err(fail)?;
+    let expected = datetime.format(match timestamp_type {
+        TimestampType::Year => "%Y",
+        TimestampType::YearMonth => "%Y-%m",
+        TimestampType::YearMonthDay => "%Y-%m-%d",
+        TimestampType::YearMonthDayHour => "%Y-%m-%d %H",
+        TimestampType::YearMonthDayHourMin => "%Y-%m-%d %H:%M",
+        TimestampType::YearMonthDayHourMinSec => "%Y-%m-%d %H:%M:%S",
+        TimestampType::HourMinSec => "%H:%M:%S",
+        TimestampType::YyyyMmDdHHmmss => "%Y%m%d%H%M%S",
+        TimestampType::YyyyMmDd => "%Y%m%d",
+        TimestampType::HHmmss => "%H%M%S",
+        TimestampType::YyMmDdHHmmss => "%y%m%d%H%M%S",
+        TimestampType::YyMmDd => "%y%m%d",
+    });
+    prop_assert_eq!(
+        &decoded,
+        &expected.to_string(),
+        "{:?} roundtrip: {} -> {}",
+        timestamp_type,
+        datetime,
+        hex_util::bytes_to_hex(&bytes).map_err(fail)?
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+
+        #[test]
+        fn test_field_type_roundtrip((ft, value, swap) in field_case()) {
+            assert_roundtrip(ft, &value, swap)?;
+        }
+
+        #[test]
+        fn test_timestamp_roundtrip(datetime in timestamp(), ts_type in timestamp_type()) {
+            assert_timestamp_roundtrip(&datetime, ts_type)?;
+        }
+
+        #[test]
+        fn test_bcd_string(s in bcd_string(6)) {
+            prop_assert_eq!(s.len(), 6);
+            prop_assert!(s.bytes().all(|b| b.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn test_scaled() {
+        assert_eq!(scaled(-5, 0.01), "-0.05");
+        assert_eq!(scaled(12345, 0.001), "12.345");
+        assert_eq!(scaled(7, 10.0), "70");
+        assert!(assert_roundtrip(FieldType::UnsignedU8(1.0), "256", false).is_err());
+    }
+}
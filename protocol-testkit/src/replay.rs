@@ -0,0 +1,341 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+};
+
+use protocol_kernel::{
+    JniRequest, JniResponse, ProtocolError, ProtocolHandler, ProtocolResult, ReportField,
+};
+
+/// 一个字段的差异，code 相同的字段按出现顺序配对
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldDiff {
+    Changed {
+        code: String,
+        recorded: String,
+        current: String,
+    },
+    // 记录中有、当前实现没有解析出来
+    Missing {
+        code: String,
+        recorded: String,
+    },
+    // 当前实现多解析出来的字段
+    Added {
+        code: String,
+        current: String,
+    },
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldDiff::Changed {
+                code,
+                recorded,
+                current,
+            } => write!(f, "{}: {} -> {}", code, recorded, current),
+            FieldDiff::Missing { code, recorded } => {
+                write!(f, "{}: {} -> (missing)", code, recorded)
+            }
+            FieldDiff::Added { code, current } => write!(f, "{}: (added) {}", code, current),
+        }
+    }
+}
+
+/// 一条记录重放后的差异
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayDiff {
+    // 在日志中的序号
+    pub index: usize,
+    pub req_hex: String,
+    // (记录值, 当前值)
+    pub cmd_code: Option<(String, String)>,
+    pub rsp_hex: Option<(String, String)>,
+    pub req_fields: Vec<FieldDiff>,
+    pub rsp_fields: Vec<FieldDiff>,
+    // 当前实现解析失败
+    pub error: Option<String>,
+}
+
+impl ReplayDiff {
+    fn is_empty(&self) -> bool {
+        self.cmd_code.is_none()
+            && self.rsp_hex.is_none()
+            && self.req_fields.is_empty()
+            && self.rsp_fields.is_empty()
+            && self.error.is_none()
+    }
+}
+
+impl fmt::Display for ReplayDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "#{} {}", self.index, self.req_hex)?;
+        if let Some(err) = &self.error {
+            writeln!(f, "  error: {}", err)?;
+        }
+        if let Some((recorded, current)) = &self.cmd_code {
+            writeln!(f, "  cmd_code: {} -> {}", recorded, current)?;
+        }
+        if let Some((recorded, current)) = &self.rsp_hex {
+            writeln!(f, "  rsp_hex: {} -> {}", recorded, current)?;
+        }
+        for diff in &self.req_fields {
+            writeln!(f, "  req {}", diff)?;
+        }
+        for diff in &self.rsp_fields {
+            writeln!(f, "  rsp {}", diff)?;
+        }
+        Ok(())
+    }
+}
+
+/// 重放结果，diffs 只包含有差异的记录
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    pub total: usize,
+    pub diffs: Vec<ReplayDiff>,
+}
+
+impl ReplayReport {
+    pub fn is_clean(&self) -> bool {
+        self.diffs.is_empty()
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "replayed {} frames, {} changed",
+            self.total,
+            self.diffs.len()
+        )?;
+        for diff in &self.diffs {
+            write!(f, "{}", diff)?;
+        }
+        Ok(())
+    }
+}
+
+/// 把日志中记录的上行帧 (JniResponse 的 req_hex) 用当前的协议实现重新解析，
+/// 与记录的响应逐字段比较。协议定义修改后用生产日志回归
+pub struct Replayer<'a> {
+    handler: &'a dyn ProtocolHandler,
+    uri: Option<String>,
+    ignored_fields: HashSet<String>,
+    ignore_reply: bool,
+}
+
+impl<'a> Replayer<'a> {
+    pub fn new(handler: &'a dyn ProtocolHandler) -> Self {
+        Self {
+            handler,
+            uri: None,
+            ignored_fields: HashSet::new(),
+            ignore_reply: false,
+        }
+    }
+
+    /// 请求中带上的 uri (protocol_id)
+    pub fn uri(mut self, uri: &str) -> Self {
+        self.uri = Some(uri.into());
+        self
+    }
+
+    /// 不比较的字段，例如解析时刻、随机数
+    pub fn ignore_field(mut self, code: &str) -> Self {
+        self.ignored_fields.insert(code.into());
+        self
+    }
+
+    /// 不比较回复帧 (回复中带当前时间等每次都不同的内容时)
+    pub fn ignore_reply(mut self) -> Self {
+        self.ignore_reply = true;
+        self
+    }
+
+    pub fn replay(&self, records: &[JniResponse]) -> ReplayReport {
+        let diffs = records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| !record.req_hex().is_empty())
+            .map(|(index, record)| self.replay_one(index, record))
+            .filter(|diff| !diff.is_empty())
+            .collect();
+        ReplayReport {
+            total: records.len(),
+            diffs,
+        }
+    }
+
+    fn replay_one(&self, index: usize, record: &JniResponse) -> ReplayDiff {
+        let mut diff = ReplayDiff {
+            index,
+            req_hex: record.req_hex_clone(),
+            ..ReplayDiff::default()
+        };
+        let current = match self.decode(record) {
+            Ok(rsp) => rsp,
+            Err(e) => {
+                diff.error = Some(e.to_string());
+                return diff;
+            }
+        };
+        if let Some(err) = current.err_msg().filter(|_| !current.success()) {
+            diff.error = Some(err.into());
+        }
+        if record.cmd_code() != current.cmd_code() {
+            diff.cmd_code = Some((record.cmd_code_clone(), current.cmd_code_clone()));
+        }
+        if !self.ignore_reply && !record.rsp_hex().eq_ignore_ascii_case(current.rsp_hex()) {
+            diff.rsp_hex = Some((record.rsp_hex_clone(), current.rsp_hex_clone()));
+        }
+        diff.req_fields = self.diff_fields(record.req_jsons(), current.req_jsons());
+        diff.rsp_fields = self.diff_fields(record.rsp_jsons(), current.rsp_jsons());
+        diff
+    }
+
+    fn decode(&self, record: &JniResponse) -> ProtocolResult<JniResponse> {
+        let mut builder = JniRequest::builder().hex(record.req_hex());
+        if let Some(uri) = &self.uri {
+            builder = builder.uri(uri);
+        }
+        if let Some(device_no) = record.device_no() {
+            builder = builder.device_no(device_no);
+        }
+        if let Some(device_id) = record.device_id() {
+            builder = builder.device_id(device_id);
+        }
+        self.handler.decode_upstream(&builder.build()?)
+    }
+
+    fn diff_fields(&self, recorded: &[ReportField], current: &[ReportField]) -> Vec<FieldDiff> {
+        let group = |fields: &[ReportField]| {
+            let mut map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for f in fields
+                .iter()
+                .filter(|f| !self.ignored_fields.contains(&f.code))
+            {
+                map.entry(f.code.clone()).or_default().push(f.value.clone());
+            }
+            map
+        };
+        let (recorded, mut current) = (group(recorded), group(current));
+        let mut diffs = Vec::new();
+        for (code, values) in recorded {
+            let mut now = current.remove(&code).unwrap_or_default().into_iter();
+            for value in values {
+                match now.next() {
+                    Some(v) if v == value => {}
+                    Some(v) => diffs.push(FieldDiff::Changed {
+                        code: code.clone(),
+                        recorded: value,
+                        current: v,
+                    }),
+                    None => diffs.push(FieldDiff::Missing {
+                        code: code.clone(),
+                        recorded: value,
+                    }),
+                }
+            }
+            diffs.extend(now.map(|v| FieldDiff::Added {
+                code: code.clone(),
+                current: v,
+            }));
+        }
+        for (code, values) in current {
+            diffs.extend(values.into_iter().map(|v| FieldDiff::Added {
+                code: code.clone(),
+                current: v,
+            }));
+        }
+        diffs
+    }
+}
+
+/// 读取按行存放的 JniResponse (JSON Lines)，空行忽略
+pub fn load_jsonl(text: &str) -> ProtocolResult<Vec<JniResponse>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            JniResponse::from(line.as_bytes()).map_err(|e| {
+                ProtocolError::CommonError(format!("replay log line {}: {}", i + 1, e))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protocol_kernel::hex_util;
+
+    // 68 + 命令 + 读数 + 16；v2 把读数缩小了 10 倍并新增了 "version" 字段
+    struct Meter {
+        v2: bool,
+    }
+
+    impl ProtocolHandler for Meter {
+        fn decode_upstream(&self, request: &JniRequest) -> ProtocolResult<JniResponse> {
+            let bytes = hex_util::hex_to_bytes(request.hex())?;
+            let reading = bytes.get(2).copied().ok_or(ProtocolError::InputTooShort {
+                needed: 3,
+                available: bytes.len(),
+            })?;
+            let mut fields = vec![ReportField::new("读数", "reading", reading.to_string())];
+            if self.v2 {
+                fields[0].value = (reading as f64 / 10.0).to_string();
+                fields.push(ReportField::new("版本", "version", "2".into()));
+            }
+            let mut rsp = JniResponse::from(br#"{"success":true}"#)?;
+            rsp.set_cmd_code(&format!("{:02X}", bytes[1]));
+            rsp.set_req_hex(request.hex());
+            rsp.set_req_jsons(fields);
+            rsp.set_rsp_hex("6881");
+            Ok(rsp)
+        }
+
+        fn encode_downstream(&self, _request: &JniRequest) -> ProtocolResult<JniResponse> {
+            JniResponse::from(br#"{"success":true}"#)
+        }
+    }
+
+    fn record_log() -> Vec<JniResponse> {
+        let v1 = Meter { v2: false };
+        ["68011416", "68010016"]
+            .iter()
+            .map(|hex| {
+                let request = JniRequest::builder().hex(hex).build().unwrap();
+                v1.decode_upstream(&request).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_replay_reports_field_diffs() {
+        let log = record_log();
+        assert!(Replayer::new(&Meter { v2: false }).replay(&log).is_clean());
+
+        let lines: Vec<String> = log
+            .iter()
+            .map(|r| String::from_utf8(r.to_bytes().unwrap()).unwrap())
+            .collect();
+        let log = load_jsonl(&lines.join("\n\n")).unwrap();
+        let report = Replayer::new(&Meter { v2: true })
+            .ignore_field("version")
+            .replay(&log);
+        assert_eq!(report.total, 2);
+        assert_eq!(report.diffs.len(), 1);
+        assert_eq!(
+            report.diffs[0].req_fields,
+            vec![FieldDiff::Changed {
+                code: "reading".into(),
+                recorded: "20".into(),
+                current: "2".into(),
+            }]
+        );
+        assert!(report.to_string().contains("req reading: 20 -> 2"));
+    }
+}
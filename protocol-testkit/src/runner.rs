@@ -0,0 +1,123 @@
+use std::fmt;
+
+use protocol_kernel::{JniRequest, JniResponse, ProtocolHandler, ProtocolResult};
+
+use crate::case::{DecodeCase, EncodeCase, GoldenCase};
+
+/// 未通过的用例
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenFailure {
+    pub name: String,
+    pub reasons: Vec<String>,
+}
+
+impl fmt::Display for GoldenFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.name, self.reasons.join("; "))
+    }
+}
+
+/// 执行全部用例，返回未通过的用例 (全部通过时为空)
+pub fn run_golden(handler: &dyn ProtocolHandler, cases: &[GoldenCase]) -> Vec<GoldenFailure> {
+    cases
+        .iter()
+        .filter_map(|case| {
+            let reasons = match case {
+                GoldenCase::Decode(case) => check_decode(handler, case),
+                GoldenCase::Encode(case) => check_encode(handler, case),
+            }
+            .unwrap_or_else(|e| vec![format!("handler error: {}", e)]);
+            (!reasons.is_empty()).then(|| GoldenFailure {
+                name: case.name().into(),
+                reasons,
+            })
+        })
+        .collect()
+}
+
+/// 执行全部用例，有未通过的用例时 panic 并列出所有失败原因
+pub fn assert_golden(handler: &dyn ProtocolHandler, cases: &[GoldenCase]) {
+    let failures = run_golden(handler, cases);
+    if !failures.is_empty() {
+        let report: Vec<String> = failures.iter().map(|f| f.to_string()).collect();
+        panic!(
+            "{} of {} golden cases failed:\n{}",
+            failures.len(),
+            cases.len(),
+            report.join("\n")
+        );
+    }
+}
+
+// 去掉空格并转大写，用例中的 hex 可以按字节分组书写
+fn normalize_hex(hex: &str) -> String {
+    hex.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+fn check_response(rsp: &JniResponse, reasons: &mut Vec<String>) {
+    if !rsp.success() {
+        reasons.push(format!(
+            "response not successful: {}",
+            rsp.err_msg().unwrap_or_default()
+        ));
+    }
+}
+
+fn check_decode(handler: &dyn ProtocolHandler, case: &DecodeCase) -> ProtocolResult<Vec<String>> {
+    let request = JniRequest::builder()
+        .hex(&normalize_hex(&case.hex))
+        .build()?;
+    let rsp = handler.decode_upstream(&request)?;
+    let mut reasons = Vec::new();
+    check_response(&rsp, &mut reasons);
+    if let Some(cmd_code) = &case.cmd_code {
+        if rsp.cmd_code() != Some(cmd_code.as_str()) {
+            reasons.push(format!(
+                "cmd_code: expected {}, got {}",
+                cmd_code,
+                rsp.cmd_code().unwrap_or_default()
+            ));
+        }
+    }
+    for (code, expected) in &case.fields {
+        match rsp.req_jsons().iter().find(|f| &f.code == code) {
+            Some(field) if &field.value == expected => {}
+            Some(field) => reasons.push(format!(
+                "field {}: expected {}, got {}",
+                code, expected, field.value
+            )),
+            None => reasons.push(format!("field {} missing", code)),
+        }
+    }
+    if let Some(reply) = &case.reply_hex {
+        let expected = normalize_hex(reply);
+        if rsp.rsp_hex().to_ascii_uppercase() != expected {
+            reasons.push(format!(
+                "reply: expected {}, got {}",
+                expected,
+                rsp.rsp_hex()
+            ));
+        }
+    }
+    Ok(reasons)
+}
+
+fn check_encode(handler: &dyn ProtocolHandler, case: &EncodeCase) -> ProtocolResult<Vec<String>> {
+    let mut builder = JniRequest::builder()
+        .cmd_code(&case.cmd_code)
+        .params(case.params.clone().into_iter().collect());
+    if let Some(device_no) = &case.device_no {
+        builder = builder.device_no(device_no);
+    }
+    let rsp = handler.encode_downstream(&builder.build()?)?;
+    let mut reasons = Vec::new();
+    check_response(&rsp, &mut reasons);
+    let expected = normalize_hex(&case.hex);
+    if rsp.rsp_hex().to_ascii_uppercase() != expected {
+        reasons.push(format!("hex: expected {}, got {}", expected, rsp.rsp_hex()));
+    }
+    Ok(reasons)
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use protocol_base::definitions::defi::CrcType;
+use protocol_kernel::{
+    bcd_util, bridge::registry::protocol_handler, generate_rand_bcd, generate_rand_range, hex_util,
+    utils::crc_util, JniRequest, JniResponse, ProtocolConfig, ProtocolError, ProtocolResult,
+};
+
+/// 帧模板中的一段
+#[derive(Debug, Clone, PartialEq)]
+pub enum FramePart {
+    // 固定字节 (帧头、命令码、帧尾等)
+    Bytes(Vec<u8>),
+    // 表号，按 BCD 编码，不足 len 字节时高位补 0
+    DeviceNo {
+        len: usize,
+        swap: bool,
+    },
+    // 随机但合法的 BCD 值
+    RandomBcd {
+        len: usize,
+    },
+    // 累计量 (表码)，每帧增加 0..=max_step，超出 len 字节能表示的范围时翻转
+    Counter {
+        name: String,
+        len: usize,
+        max_step: u64,
+        swap: bool,
+    },
+    // 帧序号 (二进制，大端)，每帧加 1
+    Sequence {
+        len: usize,
+    },
+    // 长度域: 从 from 到 CRC 之前 (没有 CRC 时到帧尾) 的字节数
+    Length {
+        len: usize,
+        from: usize,
+        le: bool,
+    },
+    // 对 from 到本段之前的字节计算的 CRC (2 字节)
+    Crc {
+        crc_type: CrcType,
+        from: usize,
+        swap: bool,
+    },
+}
+
+/// 按顺序拼接的帧模板
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameTemplate {
+    parts: Vec<FramePart>,
+}
+
+impl FrameTemplate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn part(mut self, part: FramePart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    pub fn bytes(self, bytes: &[u8]) -> Self {
+        self.part(FramePart::Bytes(bytes.to_vec()))
+    }
+
+    pub fn device_no(self, len: usize) -> Self {
+        self.part(FramePart::DeviceNo { len, swap: false })
+    }
+
+    pub fn random_bcd(self, len: usize) -> Self {
+        self.part(FramePart::RandomBcd { len })
+    }
+
+    pub fn counter(self, name: &str, len: usize, max_step: u64) -> Self {
+        self.part(FramePart::Counter {
+            name: name.into(),
+            len,
+            max_step,
+            swap: false,
+        })
+    }
+
+    pub fn sequence(self, len: usize) -> Self {
+        self.part(FramePart::Sequence { len })
+    }
+
+    pub fn length(self, len: usize, from: usize) -> Self {
+        self.part(FramePart::Length {
+            len,
+            from,
+            le: false,
+        })
+    }
+
+    pub fn crc(self, crc_type: CrcType, from: usize) -> Self {
+        self.part(FramePart::Crc {
+            crc_type,
+            from,
+            swap: false,
+        })
+    }
+}
+
+/// 模拟一台表: 按模板生成上行帧 (随机 BCD、递增的表码与序号、正确的长度和 CRC)，
+/// 并按命令码回复下行帧。用于在没有硬件时对平台做压测
+#[derive(Debug, Clone)]
+pub struct DeviceSimulator {
+    protocol_id: String,
+    config: ProtocolConfig,
+    device_no: String,
+    upstream: FrameTemplate,
+    replies: HashMap<String, FrameTemplate>,
+    counters: HashMap<String, u64>,
+    counter_starts: HashMap<String, u64>,
+    sequence: u64,
+}
+
+impl DeviceSimulator {
+    /// protocol_id 为 registry 中注册的协议，config 用于识别下行帧的命令码
+    pub fn new(
+        protocol_id: &str,
+        config: ProtocolConfig,
+        device_no: &str,
+        upstream: FrameTemplate,
+    ) -> Self {
+        Self {
+            protocol_id: protocol_id.into(),
+            config,
+            device_no: device_no.into(),
+            upstream,
+            replies: HashMap::new(),
+            counters: HashMap::new(),
+            counter_starts: HashMap::new(),
+            sequence: 0,
+        }
+    }
+
+    /// 同一模板的另一台表，计数器与序号从头开始
+    pub fn with_device_no(&self, device_no: &str) -> Self {
+        let mut sim = self.clone();
+        sim.device_no = device_no.into();
+        sim.counters = sim.counter_starts.clone();
+        sim.sequence = 0;
+        sim
+    }
+
+    /// 计数器初值
+    pub fn with_counter(mut self, name: &str, start: u64) -> Self {
+        self.counters.insert(name.into(), start);
+        self.counter_starts.insert(name.into(), start);
+        self
+    }
+
+    /// 收到 cmd_code 的下行帧时按 reply 模板回复
+    pub fn on_downstream(mut self, cmd_code: &str, reply: FrameTemplate) -> Self {
+        self.replies.insert(cmd_code.to_ascii_uppercase(), reply);
+        self
+    }
+
+    pub fn device_no(&self) -> &str {
+        &self.device_no
+    }
+
+    pub fn counter(&self, name: &str) -> Option<u64> {
+        self.counters.get(name).copied()
+    }
+
+    /// 生成下一帧上行
+    pub fn next_upstream(&mut self) -> ProtocolResult<Vec<u8>> {
+        let template = self.upstream.clone();
+        self.render(&template)
+    }
+
+    /// 生成下一帧上行并交给注册的协议解析，相当于表端上报一次
+    pub fn report(&mut self) -> ProtocolResult<JniResponse> {
+        let frame = self.next_upstream()?;
+        let request = JniRequest::builder()
+            .uri(&self.protocol_id)
+            .hex(&hex_util::bytes_to_hex(&frame)?)
+            .build()?;
+        protocol_handler(&self.protocol_id)?.decode_upstream(&request)
+    }
+
+    /// 处理平台下发的帧，没有对应的回复模板时返回 None
+    pub fn respond(&mut self, frame: &[u8]) -> ProtocolResult<Option<Vec<u8>>> {
+        let cmd_code = self.config.cmd_code_of(frame)?;
+        match self.replies.get(&cmd_code).cloned() {
+            Some(template) => self.render(&template).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn render(&mut self, template: &FrameTemplate) -> ProtocolResult<Vec<u8>> {
+        self.sequence += 1;
+        let mut buf = Vec::new();
+        // 长度与 CRC 依赖整帧，先占位
+        let mut deferred = Vec::new();
+        for part in &template.parts {
+            match part {
+                FramePart::Bytes(bytes) => buf.extend_from_slice(bytes),
+                FramePart::DeviceNo { len, swap } => {
+                    let digits = format!("{:0>width$}", self.device_no, width = len * 2);
+                    let mut bytes = hex_util::hex_to_bytes(&digits)?;
+                    if bytes.len() != *len || !bcd_util::is_bcd_bytes(&bytes) {
+                        return Err(ProtocolError::ValidationFailed(format!(
+                            "device_no {} does not fit {} BCD bytes",
+                            self.device_no, len
+                        )));
+                    }
+                    if *swap {
+                        bytes.reverse();
+                    }
+                    buf.extend(bytes);
+                }
+                FramePart::RandomBcd { len } => {
+                    buf.extend(hex_util::hex_to_bytes(&generate_rand_bcd(len * 2))?)
+                }
+                FramePart::Counter {
+                    name,
+                    len,
+                    max_step,
+                    swap,
+                } => {
+                    let modulus = 10u64.checked_pow(*len as u32 * 2).unwrap_or(u64::MAX);
+                    let value = self.counters.entry(name.clone()).or_insert(0);
+                    *value = (*value + generate_rand_range(0, *max_step)?) % modulus;
+                    buf.extend(if *swap {
+                        bcd_util::u64_to_bcd_swap(*value, *len)?
+                    } else {
+                        bcd_util::u64_to_bcd(*value, *len)?
+                    });
+                }
+                FramePart::Sequence { len } => {
+                    let bytes = self.sequence.to_be_bytes();
+                    buf.extend_from_slice(&bytes[bytes.len().saturating_sub(*len)..]);
+                }
+                FramePart::Length { len, .. } => {
+                    deferred.push((buf.len(), part));
+                    buf.resize(buf.len() + len, 0);
+                }
+                FramePart::Crc { .. } => {
+                    deferred.push((buf.len(), part));
+                    buf.resize(buf.len() + 2, 0);
+                }
+            }
+        }
+        let crc_pos = deferred
+            .iter()
+            .find(|(_, p)| matches!(p, FramePart::Crc { .. }))
+            .map_or(buf.len(), |(pos, _)| *pos);
+        for (pos, part) in &deferred {
+            if let FramePart::Length { len, from, le } = part {
+                let value = crc_pos.saturating_sub(*from) as u64;
+                let bytes = value.to_be_bytes();
+                let mut field = bytes[bytes.len() - len..].to_vec();
+                if *le {
+                    field.reverse();
+                }
+                buf[*pos..*pos + len].copy_from_slice(&field);
+            }
+        }
+        for (pos, part) in &deferred {
+            if let FramePart::Crc {
+                crc_type,
+                from,
+                swap,
+            } = part
+            {
+                let crc = crc_util::calculate_from_bytes(*crc_type, &buf[*from..*pos])?;
+                let bytes = if *swap {
+                    crc.to_le_bytes()
+                } else {
+                    crc.to_be_bytes()
+                };
+                buf[*pos..*pos + 2].copy_from_slice(&bytes);
+            }
+        }
+        Ok(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 68 + 表号(4) + 命令 + 长度 + 表码(4) + 电压(2) + 序号 + CRC + 16
+    fn water_meter() -> DeviceSimulator {
+        let config = ProtocolConfig::new("sim-water", 5, 1)
+            .with_head(&[0x68])
+            .with_tail(&[0x16])
+            .with_address(1, 4);
+        let upstream = FrameTemplate::new()
+            .bytes(&[0x68])
+            .device_no(4)
+            .bytes(&[0x01])
+            .length(1, 7)
+            .counter("total", 4, 50)
+            .random_bcd(2)
+            .sequence(1)
+            .crc(CrcType::Crc16Modbus, 0)
+            .bytes(&[0x16]);
+        let ack = FrameTemplate::new()
+            .bytes(&[0x68])
+            .device_no(4)
+            .bytes(&[0xA5])
+            .crc(CrcType::Crc16Modbus, 0)
+            .bytes(&[0x16]);
+        DeviceSimulator::new("sim-water", config, "12345678", upstream)
+            .with_counter("total", 99_999_990)
+            .on_downstream("25", ack)
+    }
+
+    #[test]
+    fn test_upstream_frames() {
+        let mut sim = water_meter();
+        let first = sim.next_upstream().unwrap();
+        assert_eq!(first.len(), 17);
+        assert_eq!(&first[..7], &[0x68, 0x12, 0x34, 0x56, 0x78, 0x01, 0x07]);
+        assert!(bcd_util::is_bcd_bytes(&first[7..13]));
+        let crc = crc_util::calculate_from_bytes(CrcType::Crc16Modbus, &first[..14]).unwrap();
+        assert_eq!(&first[14..16], &crc.to_be_bytes());
+
+        let second = sim.next_upstream().unwrap();
+        assert_eq!(second[13], 2);
+        // 表码递增，超出 8 位后翻转
+        let total = bcd_util::bcd_to_u64(&second[7..11]).unwrap();
+        assert_eq!(sim.counter("total"), Some(total));
+        let other = sim.with_device_no("1");
+        assert_eq!(other.counter("total"), Some(99_999_990));
+    }
+
+    #[test]
+    fn test_respond() {
+        let mut sim = water_meter();
+        let reply = sim
+            .respond(&[0x68, 0x12, 0x34, 0x56, 0x78, 0x25, 0x16])
+            .unwrap()
+            .unwrap();
+        assert_eq!(&reply[..6], &[0x68, 0x12, 0x34, 0x56, 0x78, 0xA5]);
+        assert!(sim
+            .respond(&[0x68, 0x12, 0x34, 0x56, 0x78, 0x26, 0x16])
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,160 @@
+//! 浏览器端排障用的 wasm 绑定: hex 进，ReportField 出。
+//!
+//! 构建: `wasm-pack build --target web`，测试: `wasm-pack test --node`。
+//! JS 侧直接调用 wasm-bindgen 生成的 `decode`，成功返回字段数组，失败抛出 Error:
+//!
+//! ```text
+//! import init, { decode } from "./pkg/protocol_wasm.js";
+//! await init();
+//! try {
+//!     const fields = decode("68...16", "water_meter"); // [{name, title, value, ...}]
+//! } catch (e) {
+//!     console.error(e.message); // "[E_CORE_003] ..."
+//! }
+//! ```
+//!
+//! 具体协议通过 `register_decoder` 注册，protocol_id 与 Java 端使用的协议标识一致。
+#[cfg(not(target_arch = "wasm32"))]
+use std::panic::{self, AssertUnwindSafe};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use once_cell::sync::Lazy;
+use protocol_base::{ProtocolError, ProtocolResult};
+use protocol_kernel::{hex_util, ReportField};
+use wasm_bindgen::prelude::*;
+
+/// 单帧解码器
+pub trait FrameDecoder: Send + Sync {
+    fn decode(&self, bytes: &[u8]) -> ProtocolResult<Vec<ReportField>>;
+}
+
+impl<F> FrameDecoder for F
+where
+    F: Fn(&[u8]) -> ProtocolResult<Vec<ReportField>> + Send + Sync,
+{
+    fn decode(&self, bytes: &[u8]) -> ProtocolResult<Vec<ReportField>> {
+        self(bytes)
+    }
+}
+
+static DECODERS: Lazy<RwLock<HashMap<String, Arc<dyn FrameDecoder>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+pub fn register_decoder<D: FrameDecoder + 'static>(protocol_id: &str, decoder: D) {
+    let mut guard = DECODERS.write().unwrap_or_else(|e| e.into_inner());
+    guard.insert(protocol_id.into(), Arc::new(decoder));
+}
+
+pub fn protocol_ids() -> Vec<String> {
+    let guard = DECODERS.read().unwrap_or_else(|e| e.into_inner());
+    let mut ids: Vec<String> = guard.keys().cloned().collect();
+    ids.sort();
+    ids
+}
+
+/// 按 protocol_id 解码一帧 hex
+pub fn decode_frame(hex: &str, protocol_id: &str) -> ProtocolResult<Vec<ReportField>> {
+    let decoder = {
+        let guard = DECODERS.read().unwrap_or_else(|e| e.into_inner());
+        guard.get(protocol_id).cloned().ok_or_else(|| {
+            ProtocolError::ValidationFailed(format!("unknown protocol id '{}'", protocol_id))
+        })?
+    };
+    let bytes = hex_util::hex_to_bytes(hex.trim())?;
+    catch_panic(|| decoder.decode(&bytes))
+}
+
+// 作为 rlib 在本机运行时把解码器中的 panic 转成错误 (同 bridge::guard::catch_panic，
+// 不依赖 kernel 的 std 特性)；wasm32 默认 panic=abort，那里的 panic 无法捕获
+#[cfg(not(target_arch = "wasm32"))]
+fn catch_panic<T>(f: impl FnOnce() -> ProtocolResult<T>) -> ProtocolResult<T> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".into());
+        Err(ProtocolError::CommonError(format!(
+            "panic in protocol handler: {}",
+            message
+        )))
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn catch_panic<T>(f: impl FnOnce() -> ProtocolResult<T>) -> ProtocolResult<T> {
+    f()
+}
+
+/// 导出给 JS 的解码入口: 成功返回 ReportField 数组，失败抛出 Error，
+/// message 为 `[错误码] 错误信息`，错误码与 Java 端 ErrorEnvelope 的 code 一致
+#[wasm_bindgen]
+pub fn decode(hex: &str, protocol_id: &str) -> Result<JsValue, JsError> {
+    match decode_frame(hex, protocol_id) {
+        Ok(fields) => Ok(serde_wasm_bindgen::to_value(&fields)?),
+        Err(e) => {
+            let envelope = e.to_envelope();
+            Err(JsError::new(&format!(
+                "[{}] {}",
+                envelope.code, envelope.message
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn register_demo() {
+        register_decoder("demo", |bytes: &[u8]| {
+            Ok(vec![ReportField::new(
+                "长度",
+                "chang_du",
+                bytes.len().to_string(),
+            )])
+        });
+    }
+
+    #[test]
+    fn test_decode_frame() {
+        register_demo();
+        let fields = decode_frame("68AA16", "demo").unwrap();
+        assert_eq!(fields[0].value, "3");
+
+        let err = decode_frame("68", "none").unwrap_err();
+        assert_eq!(err.to_envelope().code, "E_CORE_003");
+
+        register_decoder("panics", |bytes: &[u8]| {
+            Ok(vec![ReportField::new(
+                "越界",
+                "yue_jie",
+                bytes[bytes.len()].to_string(),
+            )])
+        });
+        let err = decode_frame("68", "panics").unwrap_err();
+        assert_eq!(err.to_envelope().code, "E_CORE_001");
+    }
+
+    // JsValue 只能在 wasm32 上构造，这部分用 `wasm-pack test --node` 执行
+    #[cfg(target_arch = "wasm32")]
+    mod wasm {
+        use super::*;
+        use wasm_bindgen_test::wasm_bindgen_test;
+
+        #[wasm_bindgen_test]
+        fn test_decode() {
+            register_demo();
+            let value = decode("68AA16", "demo").unwrap_or_else(|_| panic!("decode failed"));
+            let fields: serde_json::Value = serde_wasm_bindgen::from_value(value).unwrap();
+            assert_eq!(fields[0]["code"], "chang_du");
+            assert_eq!(fields[0]["value"], "3");
+
+            assert!(decode("68", "none").is_err());
+            assert!(decode("ZZ", "demo").is_err());
+        }
+    }
+}